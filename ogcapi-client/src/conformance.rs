@@ -0,0 +1,362 @@
+//! Conformance tests
+//!
+//! Re-implementations of key abstract tests of the `OGC API - Features`
+//! conformance classes, run against a deployed service. They complement,
+//! but don't replace, the executable test suites of the OGC TEAM Engine.
+//! Only classes declared by the service and covered by tests are reported.
+//!
+//! Assertions are grouped by the abstract tests of the standards they
+//! implement, identified as in the abstract test suites, e.g.
+//! `/ats/core/fc-bbox-response`, failures are prefixed with the test.
+
+use chrono::Utc;
+use reqwest::{
+    header::{HeaderMap, CONTENT_TYPE},
+    StatusCode,
+};
+use serde_json::Value;
+
+use ogcapi_types::common::{
+    link_rel::{CONFORMANCE, DATA, SELF},
+    media_type::GEO_JSON,
+    AbstractTest, ConformanceReport, ConformanceResult,
+};
+
+use crate::{Error, FeaturesClient};
+
+pub const CORE: &str = "http://www.opengis.net/spec/ogcapi-features-1/1.0/conf/core";
+pub const GEOJSON: &str = "http://www.opengis.net/spec/ogcapi-features-1/1.0/conf/geojson";
+pub const CRS: &str = "http://www.opengis.net/spec/ogcapi-features-2/1.0/conf/crs";
+pub const QUERYABLES: &str = "http://www.opengis.net/spec/ogcapi-features-3/1.0/conf/queryables";
+
+/// Conformance classes with embedded tests
+pub const TESTED_CLASSES: [&str; 4] = [CORE, GEOJSON, CRS, QUERYABLES];
+
+impl FeaturesClient {
+    /// Runs the tests of the conformance classes declared by the endpoint
+    ///
+    /// Tests on single features use the first feature of the first
+    /// collection that has any, they are skipped for services without data.
+    pub async fn test_conformance(&self) -> Result<ConformanceReport, Error> {
+        let conformance = self.conformance().await?;
+
+        let mut results = Vec::new();
+        for class in TESTED_CLASSES {
+            if !conformance.conforms_to.iter().any(|c| c == class) {
+                continue;
+            }
+
+            let mut tester = Tester::new(self);
+            match class {
+                CORE => tester.core().await,
+                GEOJSON => tester.geojson().await,
+                CRS => tester.crs().await,
+                _ => tester.queryables().await,
+            }
+
+            results.push(ConformanceResult {
+                class: class.to_string(),
+                assertions: tester.assertions,
+                failures: tester.failures,
+                tests: tester.tests,
+            });
+        }
+
+        Ok(ConformanceReport {
+            endpoint: self.endpoint.to_string(),
+            created: Some(Utc::now()),
+            results,
+        })
+    }
+}
+
+/// Collection and feature the tests are run on
+struct Sample {
+    collection: String,
+    feature: Option<String>,
+}
+
+/// Records the assertions of the tests of a class
+struct Tester<'a> {
+    client: &'a FeaturesClient,
+    assertions: usize,
+    failures: Vec<String>,
+    tests: Vec<AbstractTest>,
+}
+
+impl<'a> Tester<'a> {
+    fn new(client: &'a FeaturesClient) -> Self {
+        Tester {
+            client,
+            assertions: 0,
+            failures: Vec::new(),
+            tests: Vec::new(),
+        }
+    }
+
+    /// Start an abstract test, the following assertions belong to it
+    fn test(&mut self, id: &str) {
+        self.tests.push(AbstractTest {
+            id: id.to_string(),
+            passed: true,
+        });
+    }
+
+    fn check(&mut self, passed: bool, failure: impl FnOnce() -> String) {
+        self.assertions += 1;
+        if !passed {
+            match self.tests.last_mut() {
+                Some(test) => {
+                    test.passed = false;
+                    self.failures.push(format!("{}: {}", test.id, failure()));
+                }
+                None => self.failures.push(failure()),
+            }
+        }
+    }
+
+    /// Request a path relative to the endpoint, returning the status, the
+    /// headers and the body if it is JSON
+    async fn get(&mut self, path: &str) -> Option<(StatusCode, HeaderMap, Option<Value>)> {
+        let response = match self.client.endpoint.join(path) {
+            Ok(url) => self.client.client.get(url).send().await,
+            Err(e) => {
+                self.check(false, || format!("Invalid path `{path}`: {e}"));
+                return None;
+            }
+        };
+
+        match response {
+            Ok(response) => {
+                let (status, headers) = (response.status(), response.headers().to_owned());
+                let body = response.json::<Value>().await.ok();
+                Some((status, headers, body))
+            }
+            Err(e) => {
+                self.check(false, || format!("Request to `{path}` failed: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Request a JSON document, checking that it is served with `200`
+    async fn document(&mut self, path: &str) -> Option<(HeaderMap, Value)> {
+        let (status, headers, body) = self.get(path).await?;
+
+        self.check(status == StatusCode::OK, || {
+            format!("`{path}` responded with `{status}` instead of `200`")
+        });
+        self.check(body.is_some(), || format!("`{path}` is not valid JSON"));
+
+        body.filter(|_| status == StatusCode::OK)
+            .map(|body| (headers, body))
+    }
+
+    async fn sample(&mut self) -> Option<Sample> {
+        let (_, collections) = self.document("collections").await?;
+
+        let mut sample = None;
+        for id in collections["collections"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|c| c["id"].as_str())
+        {
+            let feature = match self.get(&format!("collections/{id}/items?limit=1")).await {
+                Some((_, _, Some(items))) => items["features"][0]["id"]
+                    .as_str()
+                    .map(ToOwned::to_owned)
+                    .or_else(|| items["features"][0]["id"].as_i64().map(|i| i.to_string())),
+                _ => None,
+            };
+
+            let found = feature.is_some();
+            if sample.is_none() || found {
+                sample = Some(Sample {
+                    collection: id.to_owned(),
+                    feature,
+                });
+            }
+            if found {
+                break;
+            }
+        }
+
+        sample
+    }
+
+    /// Abstract tests of `/conf/core`
+    async fn core(&mut self) {
+        self.test("/ats/core/root-success");
+        if let Some((_, root)) = self.document("").await {
+            for rel in [CONFORMANCE, DATA] {
+                self.check(has_link(&root, rel), || {
+                    format!("Landing page has no link with relation `{rel}`")
+                });
+            }
+        }
+
+        self.test("/ats/core/fc-md-items");
+        if let Some((_, collections)) = self.document("collections").await {
+            match collections["collections"].as_array() {
+                Some(collections) => {
+                    for collection in collections {
+                        let id = collection["id"].as_str().unwrap_or_default();
+                        self.check(!id.is_empty(), || "Collection without `id`".to_string());
+                        self.check(collection["links"].is_array(), || {
+                            format!("Collection `{id}` has no `links`")
+                        });
+                    }
+                }
+                None => self.check(false, || "`collections` is not a list".to_string()),
+            }
+        }
+
+        let Some(sample) = self.sample().await else {
+            return;
+        };
+        let collection = sample.collection;
+
+        self.test("/ats/core/fc-response");
+        let path = format!("collections/{collection}/items?limit=1");
+        if let Some((_, items)) = self.document(&path).await {
+            self.check(items["type"] == "FeatureCollection", || {
+                format!("`{path}` is not a `FeatureCollection`")
+            });
+            let features = items["features"].as_array().map(Vec::len);
+            self.check(features.is_some_and(|n| n <= 1), || {
+                format!("`{path}` returned more features than the limit")
+            });
+            if let Some(returned) = items["numberReturned"].as_u64() {
+                self.check(Some(returned as usize) == features, || {
+                    format!("`numberReturned` of `{path}` differs from the features")
+                });
+            }
+            self.check(has_link(&items, SELF), || {
+                format!("`{path}` has no link with relation `self`")
+            });
+        }
+
+        self.test("/ats/core/fc-bbox-response");
+        let path = format!("collections/{collection}/items?bbox=-180,-90,180,90");
+        self.document(&path).await;
+
+        self.test("/ats/core/fc-bbox-definition");
+        let path = format!("collections/{collection}/items?bbox=1,2,3");
+        if let Some((status, _, _)) = self.get(&path).await {
+            self.check(status == StatusCode::BAD_REQUEST, || {
+                format!("Invalid bbox responded with `{status}` instead of `400`")
+            });
+        }
+
+        if let Some(id) = sample.feature {
+            self.test("/ats/core/f-success");
+            let path = format!("collections/{collection}/items/{id}");
+            if let Some((_, feature)) = self.document(&path).await {
+                self.check(feature["type"] == "Feature", || {
+                    format!("`{path}` is not a `Feature`")
+                });
+            }
+        }
+
+        self.test("/ats/core/f-op");
+        let path = format!("collections/{collection}/items/conformance-test-missing-feature");
+        if let Some((status, _, _)) = self.get(&path).await {
+            self.check(status == StatusCode::NOT_FOUND, || {
+                format!("Unknown feature responded with `{status}` instead of `404`")
+            });
+        }
+    }
+
+    /// Abstract tests of `/conf/geojson`
+    async fn geojson(&mut self) {
+        self.test("/ats/geojson/content");
+        let Some(sample) = self.sample().await else {
+            return;
+        };
+
+        let mut paths = vec![format!("collections/{}/items", sample.collection)];
+        if let Some(id) = &sample.feature {
+            paths.push(format!("collections/{}/items/{id}", sample.collection));
+        }
+
+        for path in paths {
+            if let Some((headers, _)) = self.document(&path).await {
+                self.check(has_media_type(&headers, GEO_JSON), || {
+                    format!("`{path}` is not served as `{GEO_JSON}`")
+                });
+            }
+        }
+    }
+
+    /// Abstract tests of `/conf/crs`
+    async fn crs(&mut self) {
+        self.test("/conf/crs/fc-md-crs-list");
+        let Some(sample) = self.sample().await else {
+            return;
+        };
+        let collection = sample.collection;
+
+        let path = format!("collections/{collection}");
+        if let Some((_, metadata)) = self.document(&path).await {
+            let crs = metadata["crs"].as_array().cloned().unwrap_or_default();
+            self.check(!crs.is_empty(), || {
+                format!("Collection `{collection}` lists no `crs`")
+            });
+            if let Some(storage_crs) = metadata.get("storageCrs") {
+                self.test("/conf/crs/fc-md-storageCrs");
+                self.check(crs.contains(storage_crs), || {
+                    format!("Storage crs of `{collection}` is not listed in `crs`")
+                });
+            }
+        }
+
+        self.test("/conf/crs/ogc-crs-header");
+        let path = format!("collections/{collection}/items");
+        if let Some((headers, _)) = self.document(&path).await {
+            self.check(headers.contains_key("content-crs"), || {
+                format!("`{path}` has no `Content-Crs` header")
+            });
+        }
+
+        self.test("/conf/crs/fc-crs-invalid");
+        let path =
+            format!("collections/{collection}/items?crs=http://www.opengis.net/def/crs/EPSG/0/0");
+        if let Some((status, _, _)) = self.get(&path).await {
+            self.check(status == StatusCode::BAD_REQUEST, || {
+                format!("Unsupported crs responded with `{status}` instead of `400`")
+            });
+        }
+    }
+
+    /// Abstract tests of `/conf/queryables`
+    async fn queryables(&mut self) {
+        self.test("/conf/queryables/get-queryables-response");
+        let Some(sample) = self.sample().await else {
+            return;
+        };
+
+        let path = format!("collections/{}/queryables", sample.collection);
+        if let Some((_, queryables)) = self.document(&path).await {
+            self.check(queryables["type"] == "object", || {
+                format!("`{path}` is not a schema of type `object`")
+            });
+            self.check(queryables["properties"].is_object(), || {
+                format!("`{path}` has no `properties`")
+            });
+        }
+    }
+}
+
+fn has_link(document: &Value, rel: &str) -> bool {
+    document["links"]
+        .as_array()
+        .is_some_and(|links| links.iter().any(|link| link["rel"] == rel))
+}
+
+fn has_media_type(headers: &HeaderMap, mime: &str) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with(mime))
+}
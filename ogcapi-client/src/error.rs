@@ -18,6 +18,9 @@ pub enum Error {
     #[error("Encountered a serialization error: {0}")]
     DeserializationError(serde_json::Error),
 
+    #[error("Encountered an io error: {0}")]
+    IoError(#[from] std::io::Error),
+
     #[error("Encountered a client error: {0}")]
     ClientError(String),
 }
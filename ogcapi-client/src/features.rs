@@ -0,0 +1,337 @@
+use std::sync::{Arc, OnceLock};
+
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
+use reqwest::{
+    header::{HeaderMap, HeaderValue, CONTENT_TYPE, LOCATION, USER_AGENT},
+    Client as ReqwestClient, Response, StatusCode, Url,
+};
+
+use ogcapi_types::{
+    common::{
+        link_rel::{CONFORMANCE, NEXT},
+        media_type::GEO_JSON,
+        Collection, Collections, Conformance, LandingPage, Links,
+    },
+    features::{Changeset, Feature, FeatureCollection, Query},
+};
+
+use crate::Error;
+
+static UA_STRING: &str = "OGCAPI-CLIENT";
+
+/// Conformance class of transactions
+///
+/// Writes are attempted whether or not a server declares it, servers without
+/// transactions answer with `405` or `501`.
+pub const CREATE_REPLACE_DELETE: &str =
+    "http://www.opengis.net/spec/ogcapi-features-4/1.0/conf/create-replace-delete";
+
+/// Async client to access `OGC API - Features` endpoints.
+///
+/// # Example:
+///
+/// ```rust, no_run
+/// use futures::TryStreamExt;
+/// use ogcapi_client::FeaturesClient;
+/// use ogcapi_types::features::Query;
+///
+/// # async fn run() -> Result<(), ogcapi_client::Error> {
+/// let client = FeaturesClient::new("http://localhost:8484/")?;
+///
+/// let query = Query {
+///     limit: Some(100),
+///     ..Default::default()
+/// };
+/// let features: Vec<_> = client.items("countries", &query)?.try_collect().await?;
+/// println!("Fetched {} features", features.len());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FeaturesClient {
+    pub(crate) client: ReqwestClient,
+    pub(crate) endpoint: Url,
+    conformance: Arc<OnceLock<Conformance>>,
+}
+
+impl FeaturesClient {
+    /// Creates a client for a given `OGC API - Features` endpoint.
+    pub fn new(endpoint: &str) -> Result<Self, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static(UA_STRING));
+
+        let client = ReqwestClient::builder().default_headers(headers).build()?;
+
+        FeaturesClient::with_client(client, endpoint)
+    }
+
+    /// Creates a client reusing a preconfigured `reqwest` client, e.g. with
+    /// authentication headers or timeouts.
+    pub fn with_client(client: ReqwestClient, endpoint: &str) -> Result<Self, Error> {
+        let endpoint = if endpoint.ends_with('/') {
+            endpoint.parse::<Url>()?
+        } else {
+            format!("{}/", endpoint).parse::<Url>()?
+        };
+
+        Ok(Self {
+            client,
+            endpoint,
+            conformance: Default::default(),
+        })
+    }
+
+    /// Creates a client sending an api key in the `X-API-Key` header, e.g. to
+    /// read collections restricted to some users.
+    pub fn with_api_key(endpoint: &str, api_key: &str) -> Result<Self, Error> {
+        let mut key = HeaderValue::from_str(api_key)
+            .map_err(|_| Error::ClientError("Invalid api key".to_string()))?;
+        key.set_sensitive(true);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static(UA_STRING));
+        headers.insert("X-API-Key", key);
+
+        let client = ReqwestClient::builder().default_headers(headers).build()?;
+
+        FeaturesClient::with_client(client, endpoint)
+    }
+
+    /// Returns the landing page.
+    pub async fn root(&self) -> Result<LandingPage, Error> {
+        self.fetch(self.endpoint.to_owned()).await
+    }
+
+    /// Returns the conformance declaration, discovered through the landing
+    /// page on first use.
+    pub async fn conformance(&self) -> Result<Conformance, Error> {
+        if let Some(conformance) = self.conformance.get() {
+            return Ok(conformance.to_owned());
+        }
+
+        let url = match self
+            .root()
+            .await?
+            .links
+            .iter()
+            .find(|l| l.rel == CONFORMANCE)
+        {
+            Some(link) => self.endpoint.join(&link.href)?,
+            None => self.endpoint.join("conformance")?,
+        };
+
+        let conformance: Conformance = self.fetch(url).await?;
+
+        Ok(self.conformance.get_or_init(|| conformance).to_owned())
+    }
+
+    /// Checks whether the server declares conformance to a given class.
+    pub async fn conforms_to(&self, class: &str) -> Result<bool, Error> {
+        Ok(self
+            .conformance()
+            .await?
+            .conforms_to
+            .iter()
+            .any(|c| c == class))
+    }
+
+    /// Returns a stream over all collections, following `next` links.
+    pub fn collections(&self) -> Result<BoxStream<'static, Result<Collection, Error>>, Error> {
+        let url = self.endpoint.join("collections")?;
+        let client = self.to_owned();
+
+        Ok(paginate(url, move |url| {
+            let client = client.to_owned();
+            async move {
+                let collections: Collections = client.fetch(url).await?;
+                Ok((collections.collections, collections.links))
+            }
+        }))
+    }
+
+    /// Returns the collection with the given id.
+    pub async fn collection(&self, id: &str) -> Result<Collection, Error> {
+        let url = self.endpoint.join(&format!("collections/{id}"))?;
+        self.fetch(url).await
+    }
+
+    /// Returns a stream over the items of a collection matching the query,
+    /// following `next` links.
+    pub fn items(
+        &self,
+        collection: &str,
+        query: &Query,
+    ) -> Result<BoxStream<'static, Result<Feature, Error>>, Error> {
+        let mut url = self.items_url(collection)?;
+        let query = serde_qs::to_string(query)?;
+        url.set_query(Some(query.as_str()).filter(|q| !q.is_empty()));
+
+        let client = self.to_owned();
+
+        Ok(paginate(url, move |url| {
+            let client = client.to_owned();
+            async move {
+                let items: FeatureCollection = client.fetch(url).await?;
+                Ok((items.features, items.links))
+            }
+        }))
+    }
+
+    /// Returns a single item of a collection.
+    pub async fn item(&self, collection: &str, id: &str) -> Result<Feature, Error> {
+        let url = self.item_url(collection, id)?;
+        self.fetch(url).await
+    }
+
+    /// Creates an item and returns its id.
+    pub async fn create_item(&self, collection: &str, feature: &Feature) -> Result<String, Error> {
+        let url = self.items_url(collection)?;
+        let body = serde_json::to_vec(feature).map_err(Error::DeserializationError)?;
+
+        let response = self
+            .client
+            .post(url)
+            .header(CONTENT_TYPE, GEO_JSON)
+            .body(body)
+            .send()
+            .await?;
+        let response = transaction(response)?;
+
+        response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| location.trim_end_matches('/').rsplit('/').next())
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| Error::ClientError("Missing `Location` header".to_string()))
+    }
+
+    /// Replaces an existing item.
+    pub async fn replace_item(
+        &self,
+        collection: &str,
+        id: &str,
+        feature: &Feature,
+    ) -> Result<(), Error> {
+        let url = self.item_url(collection, id)?;
+        let body = serde_json::to_vec(feature).map_err(Error::DeserializationError)?;
+
+        let response = self
+            .client
+            .put(url)
+            .header(CONTENT_TYPE, GEO_JSON)
+            .body(body)
+            .send()
+            .await?;
+        transaction(response)?;
+
+        Ok(())
+    }
+
+    /// Deletes an item.
+    pub async fn delete_item(&self, collection: &str, id: &str) -> Result<(), Error> {
+        let url = self.item_url(collection, id)?;
+
+        transaction(self.client.delete(url).send().await?)?;
+
+        Ok(())
+    }
+
+    /// Returns the changes of the items of a collection after a token, in
+    /// order, or without token the token marking the end of the change log.
+    pub async fn changes(
+        &self,
+        collection: &str,
+        since: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Changeset, Error> {
+        let mut url = self
+            .endpoint
+            .join(&format!("collections/{collection}/changes"))?;
+
+        let mut pairs = Vec::new();
+        if let Some(since) = since {
+            pairs.push(("since", since.to_owned()));
+        }
+        if let Some(limit) = limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+        if !pairs.is_empty() {
+            url.query_pairs_mut().extend_pairs(pairs);
+        }
+
+        self.fetch(url).await
+    }
+
+    fn items_url(&self, collection: &str) -> Result<Url, Error> {
+        Ok(self
+            .endpoint
+            .join(&format!("collections/{collection}/items"))?)
+    }
+
+    fn item_url(&self, collection: &str, id: &str) -> Result<Url, Error> {
+        Ok(self
+            .endpoint
+            .join(&format!("collections/{collection}/items/{id}"))?)
+    }
+
+    pub(crate) async fn fetch<T>(&self, url: Url) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        log::debug!("Fetching {}", url);
+
+        Ok(self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<T>()
+            .await?)
+    }
+}
+
+/// Stream the entries of consecutive pages, starting at `url`
+pub(crate) fn paginate<T, F, Fut>(url: Url, fetch: F) -> BoxStream<'static, Result<T, Error>>
+where
+    T: Send + 'static,
+    F: Fn(Url) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Result<(Vec<T>, Links), Error>> + Send + 'static,
+{
+    stream::try_unfold((Some(url), fetch), |(url, fetch)| async move {
+        let Some(url) = url else {
+            return Ok(None);
+        };
+
+        let (entries, links) = fetch(url.to_owned()).await?;
+
+        let next = links
+            .iter()
+            .find(|l| l.rel == NEXT)
+            .map(|l| url.join(&l.href))
+            .transpose()?
+            // guard against servers linking to the same page
+            .filter(|next| *next != url && !entries.is_empty());
+
+        let entries = stream::iter(entries.into_iter().map(Ok::<T, Error>));
+
+        Ok::<_, Error>(Some((entries, (next, fetch))))
+    })
+    .try_flatten()
+    .boxed()
+}
+
+/// Response of a transaction, servers without transactions are a conformance
+/// error rather than a request error
+fn transaction(response: Response) -> Result<Response, Error> {
+    match response.status() {
+        StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED => Err(
+            Error::UnknownConformance(format!("Server does not support `{CREATE_REPLACE_DELETE}`")),
+        ),
+        _ => Ok(response.error_for_status()?),
+    }
+}
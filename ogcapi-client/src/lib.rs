@@ -30,7 +30,14 @@
 //! # }
 
 mod client;
+pub mod conformance;
 mod error;
+mod features;
+#[cfg(feature = "stac")]
+mod stac;
 
 pub use client::Client;
 pub use error::Error;
+pub use features::{FeaturesClient, CREATE_REPLACE_DELETE};
+#[cfg(feature = "stac")]
+pub use stac::DownloadOptions;
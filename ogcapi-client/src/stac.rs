@@ -0,0 +1,348 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
+use reqwest::{
+    header::{ACCEPT_RANGES, CONTENT_LENGTH, RANGE},
+    StatusCode, Url,
+};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use ogcapi_types::{
+    common::link_rel::SELF,
+    features::FeatureCollection,
+    stac::{Item, SearchParams},
+};
+
+use crate::{features::paginate, Error, FeaturesClient};
+
+/// Options for asset downloads
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// Maximal number of concurrent range requests per asset
+    pub concurrency: usize,
+    /// Size in bytes of the ranges requested at once
+    pub chunk_size: u64,
+    /// Number of retries of failed requests, with exponential backoff
+    pub retries: u32,
+    /// Overwrite existing files instead of skipping them
+    pub overwrite: bool,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            chunk_size: 8 * 1024 * 1024,
+            retries: 3,
+            overwrite: false,
+        }
+    }
+}
+
+impl FeaturesClient {
+    /// Returns a stream over the items matching the STAC search parameters,
+    /// fetching further pages lazily.
+    pub fn search(
+        &self,
+        params: &SearchParams,
+    ) -> Result<BoxStream<'static, Result<Item, Error>>, Error> {
+        let mut url = self.endpoint.join("search")?;
+        let query = serde_qs::to_string(params)?;
+        url.set_query(Some(query.as_str()).filter(|q| !q.is_empty()));
+
+        let client = self.to_owned();
+
+        Ok(paginate(url, move |url| {
+            let client = client.to_owned();
+            async move {
+                let items: FeatureCollection = client.fetch(url).await?;
+                Ok((items.features, items.links))
+            }
+        }))
+    }
+
+    /// Downloads all assets of an item into a directory, returning the paths
+    /// by asset key.
+    ///
+    /// Relative asset hrefs are resolved against the `self` link of the item.
+    /// Files are named after the asset key, with the extension of the href,
+    /// and are always written into `dir`.
+    pub async fn download_assets(
+        &self,
+        item: &Item,
+        dir: &Path,
+        options: &DownloadOptions,
+    ) -> Result<HashMap<String, PathBuf>, Error> {
+        let base = match item.links.iter().find(|l| l.rel == SELF) {
+            Some(link) => self.endpoint.join(&link.href)?,
+            None => self.endpoint.to_owned(),
+        };
+
+        tokio::fs::create_dir_all(dir).await?;
+
+        let mut paths: HashMap<String, PathBuf> = HashMap::new();
+        for (key, asset) in item.assets.iter() {
+            let url = base.join(&asset.href)?;
+
+            // keys sanitized alike get a counter appended
+            let name = file_name(key, &url);
+            let mut path = dir.join(&name);
+            let mut n = 1;
+            while paths.values().any(|p| *p == path) {
+                n += 1;
+                path = dir.join(match name.split_once('.') {
+                    Some((stem, extension)) => format!("{stem}_{n}.{extension}"),
+                    None => format!("{name}_{n}"),
+                });
+            }
+
+            self.download_asset(&url, &path, options).await?;
+
+            paths.insert(key.to_owned(), path);
+        }
+
+        Ok(paths)
+    }
+
+    /// Downloads a single asset to a file and returns the number of bytes written.
+    ///
+    /// Large assets are fetched with concurrent range requests if the server
+    /// supports them. The file is written as `{name}.part` and renamed once
+    /// complete, so failed downloads are not mistaken for existing files.
+    pub async fn download_asset(
+        &self,
+        url: &Url,
+        path: &Path,
+        options: &DownloadOptions,
+    ) -> Result<u64, Error> {
+        if !options.overwrite && tokio::fs::try_exists(path).await? {
+            log::debug!("Skipping existing {}", path.display());
+            return Ok(0);
+        }
+
+        log::debug!("Downloading {} to {}", url, path.display());
+
+        // some servers (e.g. presigned urls) reject `HEAD` requests
+        let head = retry(options.retries, || async {
+            Ok(self
+                .client
+                .head(url.to_owned())
+                .send()
+                .await?
+                .error_for_status()?)
+        })
+        .await
+        .ok();
+
+        let length = head.as_ref().and_then(|head| {
+            head.headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+        });
+        let ranges = head.as_ref().is_some_and(|head| {
+            head.headers()
+                .get(ACCEPT_RANGES)
+                .is_some_and(|v| v.as_bytes() == b"bytes")
+        });
+
+        let part = part_path(path);
+        let written = match length {
+            Some(length) if ranges && length > options.chunk_size => {
+                self.download_ranges(url, &part, length, options).await
+            }
+            _ => self.download_whole(url, &part, options).await,
+        };
+
+        match written {
+            Ok(written) => {
+                tokio::fs::rename(&part, path).await?;
+                Ok(written)
+            }
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&part).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn download_whole(
+        &self,
+        url: &Url,
+        path: &Path,
+        options: &DownloadOptions,
+    ) -> Result<u64, Error> {
+        retry(options.retries, || async {
+            let mut response = self
+                .client
+                .get(url.to_owned())
+                .send()
+                .await?
+                .error_for_status()?;
+
+            let mut file = tokio::fs::File::create(path).await?;
+            let mut written = 0;
+            while let Some(chunk) = response.chunk().await? {
+                file.write_all(&chunk).await?;
+                written += chunk.len() as u64;
+            }
+            file.flush().await?;
+
+            Ok(written)
+        })
+        .await
+    }
+
+    async fn download_ranges(
+        &self,
+        url: &Url,
+        path: &Path,
+        length: u64,
+        options: &DownloadOptions,
+    ) -> Result<u64, Error> {
+        let file = tokio::fs::File::create(path).await?;
+        file.set_len(length).await?;
+
+        let ranges = (0..length)
+            .step_by(options.chunk_size as usize)
+            .map(|start| (start, (start + options.chunk_size).min(length) - 1));
+
+        stream::iter(ranges)
+            .map(|(start, end)| async move {
+                let bytes = retry(options.retries, || async {
+                    let response = self
+                        .client
+                        .get(url.to_owned())
+                        .header(RANGE, format!("bytes={start}-{end}"))
+                        .send()
+                        .await?
+                        .error_for_status()?;
+
+                    if response.status() != StatusCode::PARTIAL_CONTENT {
+                        return Err(Error::ClientError(format!(
+                            "Range request to `{url}` not honored"
+                        )));
+                    }
+
+                    Ok(response.bytes().await?)
+                })
+                .await?;
+
+                let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+                file.seek(SeekFrom::Start(start)).await?;
+                file.write_all(&bytes).await?;
+                file.flush().await?;
+
+                Ok::<_, Error>(bytes.len() as u64)
+            })
+            .buffer_unordered(options.concurrency.max(1))
+            .try_fold(0, |total, written| async move { Ok(total + written) })
+            .await
+    }
+}
+
+/// File name of an asset, the key reduced to a single path component, with
+/// the extension of the last segment of the url
+fn file_name(key: &str, url: &Url) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect();
+    if name.trim_matches('_').is_empty() {
+        name = "asset".to_string();
+    }
+
+    let extension = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|segment| segment.rsplit_once('.'))
+        .map(|(_, extension)| extension)
+        .filter(|extension| {
+            !extension.is_empty() && extension.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+    if let Some(extension) = extension {
+        name.push('.');
+        name.push_str(extension);
+    }
+
+    name
+}
+
+/// Path a download is written to before it is complete, next to the file
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+/// Retry transient failures with exponential backoff
+async fn retry<T, F, Fut>(retries: u32, f: F) -> Result<T, Error>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < retries && is_transient(&err) => {
+                attempt += 1;
+                log::warn!("Retrying ({attempt}/{retries}) after error: {err}");
+                tokio::time::sleep(Duration::from_millis(250 * 2u64.pow(attempt))).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::RequestError(e) => match e.status() {
+            Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+            None => true,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use reqwest::Url;
+
+    use super::{file_name, part_path};
+
+    #[test]
+    fn file_names() {
+        let url = Url::parse("https://example.com/b/scene/B04.tif?sig=1").unwrap();
+        assert_eq!(file_name("B04", &url), "B04.tif");
+        assert_eq!(file_name("../../etc/passwd", &url), "______etc_passwd.tif");
+        assert_eq!(file_name("..", &url), "asset.tif");
+
+        let url = Url::parse("https://example.com/data/").unwrap();
+        assert_eq!(file_name("metadata", &url), "metadata");
+        let url = Url::parse("https://example.com/a.%2F..").unwrap();
+        assert_eq!(file_name("x", &url), "x");
+    }
+
+    #[test]
+    fn part_paths() {
+        assert_eq!(
+            part_path(Path::new("assets/B04.tif")),
+            PathBuf::from("assets/B04.tif.part")
+        );
+    }
+}
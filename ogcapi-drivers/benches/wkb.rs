@@ -0,0 +1,49 @@
+//! Decoding geometries as read from the database, `GeoJSON` text as returned
+//! by `ST_AsGeoJSON` versus `WKB` as returned by `ST_AsEWKB`
+//!
+//! Run with `cargo bench -p ogcapi-drivers --features postgres`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use geojson::{Geometry, Value};
+
+use ogcapi_drivers::wkb;
+
+/// Polygon with a shell of `n` vertices and a hole
+fn polygon(n: usize) -> Value {
+    let ring = |radius: f64| {
+        let mut ring: Vec<Vec<f64>> = (0..n)
+            .map(|i| {
+                let a = i as f64 / n as f64 * std::f64::consts::TAU;
+                vec![7.4474 + radius * a.cos(), 46.948 + radius * a.sin()]
+            })
+            .collect();
+        ring.push(ring[0].to_owned());
+        ring
+    };
+
+    Value::Polygon(vec![ring(0.1), ring(0.05)])
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+
+    for n in [8, 128, 2048] {
+        let value = polygon(n);
+
+        let json = serde_json::to_string(&Geometry::new(value.to_owned())).unwrap();
+        // GeoPackage blobs without envelope are a header of 8 bytes and WKB
+        let wkb = wkb::to_gpkg(&value, 4326)[8..].to_vec();
+
+        group.bench_with_input(BenchmarkId::new("geojson", n), &json, |b, json| {
+            b.iter(|| serde_json::from_str::<Geometry>(black_box(json)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("wkb", n), &wkb, |b, wkb| {
+            b.iter(|| wkb::from_ewkb(black_box(wkb)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+-- Joins
+CREATE TABLE meta.files (
+    id text PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    name text,
+    fields jsonb NOT NULL DEFAULT '[]'::jsonb,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE meta.file_rows (
+    file_id text NOT NULL REFERENCES meta.files(id) ON DELETE CASCADE,
+    row jsonb NOT NULL
+);
+
+CREATE INDEX ON meta.file_rows USING btree (file_id);
+
+CREATE TABLE meta.joins (
+    id text PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    definition jsonb NOT NULL
+);
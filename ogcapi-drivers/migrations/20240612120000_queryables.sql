@@ -0,0 +1,2 @@
+-- Queryables (JSON Schema) of collections
+ALTER TABLE meta.collections ADD COLUMN queryables jsonb;
@@ -0,0 +1,14 @@
+-- Users and api keys
+CREATE TABLE meta.users (
+    id text PRIMARY KEY,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE meta.api_keys (
+    id text PRIMARY KEY,
+    user_id text NOT NULL REFERENCES meta.users(id) ON DELETE CASCADE,
+    hash text NOT NULL,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ON meta.api_keys USING btree (user_id);
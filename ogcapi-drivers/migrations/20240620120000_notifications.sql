@@ -0,0 +1,36 @@
+-- Notify about feature changes on the `item_changes` channel
+CREATE FUNCTION meta.notify_item_change() RETURNS trigger AS $$
+DECLARE
+    item record;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        item := OLD;
+    ELSE
+        item := NEW;
+    END IF;
+
+    PERFORM pg_notify('item_changes', json_build_object(
+        'kind', CASE TG_OP WHEN 'INSERT' THEN 'create' WHEN 'UPDATE' THEN 'update' ELSE 'delete' END,
+        'collection', TG_TABLE_NAME,
+        'id', item.id
+    )::text);
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Attach to the tables of existing collections
+DO $$
+DECLARE
+    t record;
+BEGIN
+    FOR t IN
+        SELECT table_name FROM information_schema.tables
+        WHERE table_schema = 'items' AND table_type = 'BASE TABLE'
+    LOOP
+        EXECUTE format(
+            'CREATE TRIGGER notify_item_change AFTER INSERT OR UPDATE OR DELETE ON items.%I FOR EACH ROW EXECUTE FUNCTION meta.notify_item_change()',
+            t.table_name
+        );
+    END LOOP;
+END $$;
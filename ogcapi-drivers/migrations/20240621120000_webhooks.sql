@@ -0,0 +1,15 @@
+-- Webhooks and their delivery log
+CREATE TABLE meta.webhooks (
+    id text PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    definition jsonb NOT NULL,
+    secret text,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE meta.webhook_deliveries (
+    webhook_id text NOT NULL REFERENCES meta.webhooks(id) ON DELETE CASCADE,
+    delivery jsonb NOT NULL,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ON meta.webhook_deliveries USING btree (webhook_id, created);
@@ -0,0 +1,23 @@
+-- Vertical extent of a geometry, `NULL` for geometries without height
+CREATE FUNCTION meta.zrange(geom geometry) RETURNS numrange AS $$
+    SELECT CASE
+        WHEN ST_HasZ(geom) THEN numrange(ST_ZMin(geom)::numeric, ST_ZMax(geom)::numeric, '[]')
+    END
+$$ LANGUAGE sql IMMUTABLE PARALLEL SAFE;
+
+-- Add to the tables of existing collections
+DO $$
+DECLARE
+    t record;
+BEGIN
+    FOR t IN
+        SELECT table_name FROM information_schema.tables
+        WHERE table_schema = 'items' AND table_type = 'BASE TABLE'
+    LOOP
+        EXECUTE format(
+            'ALTER TABLE items.%I ADD COLUMN zrange numrange GENERATED ALWAYS AS (meta.zrange(geom)) STORED',
+            t.table_name
+        );
+        EXECUTE format('CREATE INDEX ON items.%I USING gist (zrange)', t.table_name);
+    END LOOP;
+END $$;
@@ -0,0 +1,11 @@
+-- Idempotency keys of write requests and their responses
+CREATE TABLE meta.idempotency_keys (
+    key text PRIMARY KEY,
+    request text NOT NULL,
+    status smallint,
+    headers jsonb,
+    body bytea,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ON meta.idempotency_keys USING btree (created);
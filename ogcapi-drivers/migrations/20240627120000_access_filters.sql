@@ -0,0 +1,10 @@
+-- Feature level access filters of collections per user, or for requests
+-- without api key if the user is null
+CREATE TABLE meta.access_filters (
+    collection_id text NOT NULL REFERENCES meta.collections(id) ON DELETE CASCADE,
+    user_id text REFERENCES meta.users(id) ON DELETE CASCADE,
+    filter jsonb NOT NULL,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX ON meta.access_filters (collection_id, COALESCE(user_id, ''));
@@ -0,0 +1,5 @@
+-- Catalogs grouping collections, nested by the `parent` of the catalog
+CREATE TABLE meta.catalogs (
+    id text PRIMARY KEY,
+    catalog jsonb NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Reset the generalized geometries of changed features, to be computed again
+-- by the next generalization run
+CREATE FUNCTION meta.reset_generalized() RETURNS trigger AS $$
+BEGIN
+    NEW.geom_z4 := NULL;
+    NEW.geom_z8 := NULL;
+    NEW.geom_z12 := NULL;
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,9 @@
+-- Tile requests per collection and tile matrix, accumulated by the services
+CREATE TABLE meta.tile_usage (
+    collection_id text NOT NULL REFERENCES meta.collections(id) ON DELETE CASCADE,
+    tile_matrix text NOT NULL,
+    requests bigint NOT NULL DEFAULT 0,
+    hits bigint NOT NULL DEFAULT 0,
+    updated timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (collection_id, tile_matrix)
+);
@@ -0,0 +1,54 @@
+-- Ordered log of the feature changes, read by instances replicating collections
+CREATE TABLE meta.item_changes (
+    seq bigserial PRIMARY KEY,
+    -- transaction of the change, changes are listed once it finished
+    tx xid8 NOT NULL DEFAULT pg_current_xact_id(),
+    collection_id text NOT NULL,
+    id text NOT NULL,
+    kind text NOT NULL,
+    changed timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ON meta.item_changes (collection_id, tx, seq);
+
+-- Positions up to which collections were replicated from other instances
+CREATE TABLE meta.sync_tokens (
+    source text NOT NULL,
+    collection_id text NOT NULL,
+    token text NOT NULL,
+    updated timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source, collection_id)
+);
+
+-- Log feature changes next to the notification, skipping updates leaving the
+-- feature as is, e.g. of the generalized geometries
+CREATE OR REPLACE FUNCTION meta.notify_item_change() RETURNS trigger AS $$
+DECLARE
+    item record;
+    kind text;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        item := OLD;
+    ELSE
+        item := NEW;
+    END IF;
+
+    IF TG_OP = 'UPDATE' AND (NEW.id, NEW.properties, NEW.geom, NEW.links, NEW.assets)
+        IS NOT DISTINCT FROM (OLD.id, OLD.properties, OLD.geom, OLD.links, OLD.assets) THEN
+        RETURN NULL;
+    END IF;
+
+    kind := CASE TG_OP WHEN 'INSERT' THEN 'create' WHEN 'UPDATE' THEN 'update' ELSE 'delete' END;
+
+    INSERT INTO meta.item_changes (collection_id, id, kind)
+    VALUES (TG_TABLE_NAME, item.id, kind);
+
+    PERFORM pg_notify('item_changes', json_build_object(
+        'kind', kind,
+        'collection', TG_TABLE_NAME,
+        'id', item.id
+    )::text);
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,12 @@
+-- Files attached to features, their content is kept in object storage
+CREATE TABLE meta.attachments (
+    id text PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    collection_id text NOT NULL REFERENCES meta.collections(id) ON DELETE CASCADE,
+    feature_id text NOT NULL,
+    title text,
+    type text NOT NULL,
+    length bigint NOT NULL,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ON meta.attachments USING btree (collection_id, feature_id, created);
@@ -0,0 +1,8 @@
+-- Scheduled harvests of remote sources into collections
+CREATE TABLE meta.harvests (
+    id text PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    collection_id text NOT NULL REFERENCES meta.collections(id) ON DELETE CASCADE,
+    definition jsonb NOT NULL,
+    last_run timestamptz,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,8 @@
+-- Checksums of the content of harvested and imported features, to write only
+-- the features changed in the source
+CREATE TABLE meta.feature_checksums (
+    collection_id text NOT NULL REFERENCES meta.collections(id) ON DELETE CASCADE,
+    feature_id text NOT NULL,
+    checksum text NOT NULL,
+    PRIMARY KEY (collection_id, feature_id)
+);
@@ -0,0 +1,12 @@
+-- Queue of jobs shared by the replicas of the service, claimed with leases
+CREATE TABLE meta.job_queue (
+    job_id text PRIMARY KEY REFERENCES meta.jobs(job_id) ON DELETE CASCADE,
+    process_id text NOT NULL,
+    inputs jsonb NOT NULL,
+    worker text,
+    lease_until timestamptz,
+    attempts integer NOT NULL DEFAULT 0,
+    queued timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ON meta.job_queue (queued);
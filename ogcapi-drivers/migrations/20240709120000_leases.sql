@@ -0,0 +1,7 @@
+-- Roles held by a single replica of the service at a time, e.g. dispatching
+-- the feature change feed
+CREATE TABLE meta.leases (
+    role text PRIMARY KEY,
+    holder text NOT NULL,
+    lease_until timestamptz NOT NULL
+);
@@ -0,0 +1,22 @@
+-- Metered usage of the api keys per month, kept after keys are revoked for
+-- billing
+CREATE TABLE meta.key_usage (
+    key_id text NOT NULL,
+    month text NOT NULL,
+    requests bigint NOT NULL DEFAULT 0,
+    features bigint NOT NULL DEFAULT 0,
+    tiles bigint NOT NULL DEFAULT 0,
+    job_seconds double precision NOT NULL DEFAULT 0,
+    updated timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (key_id, month)
+);
+
+-- Monthly limits of the usage of api keys, unlimited where NULL
+CREATE TABLE meta.quotas (
+    key_id text PRIMARY KEY REFERENCES meta.api_keys(id) ON DELETE CASCADE,
+    requests bigint,
+    features bigint,
+    tiles bigint,
+    job_seconds double precision,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,10 @@
+-- Geometries changed while a collection is reprojected next to the stored
+-- ones are reprojected again when the reprojection finishes
+CREATE FUNCTION meta.reset_reprojected_geom() RETURNS trigger AS $$
+BEGIN
+    IF NEW.geom IS DISTINCT FROM OLD.geom THEN
+        NEW.geom_reprojected := NULL;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
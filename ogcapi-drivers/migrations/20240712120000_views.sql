@@ -0,0 +1,9 @@
+-- Saved views of the features of collections
+CREATE TABLE meta.views (
+    id text PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    owner text,
+    definition jsonb NOT NULL,
+    created timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ON meta.views USING btree (owner, created);
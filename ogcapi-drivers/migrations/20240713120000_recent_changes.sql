@@ -0,0 +1,2 @@
+-- Feeds list the latest changes of a collection by time
+CREATE INDEX ON meta.item_changes (collection_id, changed);
@@ -0,0 +1,8 @@
+-- Idempotency keys are scoped to the user of the api key and bound to the
+-- body of the request they were first used for
+ALTER TABLE meta.idempotency_keys
+    ADD COLUMN scope text NOT NULL DEFAULT '',
+    ADD COLUMN fingerprint text NOT NULL DEFAULT '';
+
+ALTER TABLE meta.idempotency_keys DROP CONSTRAINT idempotency_keys_pkey;
+ALTER TABLE meta.idempotency_keys ADD PRIMARY KEY (scope, key);
@@ -0,0 +1,6 @@
+-- Settings of the service shared by its replicas, e.g. the mode
+CREATE TABLE meta.settings (
+    name text PRIMARY KEY,
+    value text NOT NULL,
+    updated timestamptz NOT NULL DEFAULT NOW()
+);
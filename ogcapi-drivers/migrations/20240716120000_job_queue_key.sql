@@ -0,0 +1,2 @@
+-- Metered api key of the request a queued job was submitted with
+ALTER TABLE meta.job_queue ADD COLUMN key text;
@@ -0,0 +1,38 @@
+use anyhow::bail;
+
+use ogcapi_types::{auth::AccessFilter, cql2::Expr};
+
+use crate::AccessFilterTransactions;
+
+use super::Files;
+
+/// Static datasets are public, no features are hidden
+#[async_trait::async_trait]
+impl AccessFilterTransactions for Files {
+    async fn set_access_filter(&self, _filter: &AccessFilter) -> anyhow::Result<()> {
+        bail!("Access filters are not supported on static datasets")
+    }
+
+    async fn delete_access_filter(
+        &self,
+        _collection: &str,
+        _user: Option<&str>,
+    ) -> anyhow::Result<()> {
+        bail!("Access filters are not supported on static datasets")
+    }
+
+    async fn list_access_filters(
+        &self,
+        _collection: Option<&str>,
+    ) -> anyhow::Result<Vec<AccessFilter>> {
+        Ok(Vec::new())
+    }
+
+    async fn access_filter(
+        &self,
+        _collection: &str,
+        _user: Option<&str>,
+    ) -> anyhow::Result<Option<Expr>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::bail;
+
+use ogcapi_types::features::Attachment;
+
+use crate::AttachmentTransactions;
+
+use super::Files;
+
+/// Features of static datasets have no attachments
+#[async_trait::async_trait]
+impl AttachmentTransactions for Files {
+    async fn create_attachment(
+        &self,
+        _collection: &str,
+        _feature: &str,
+        _attachment: &Attachment,
+    ) -> anyhow::Result<String> {
+        bail!("Attachments are not supported on static datasets")
+    }
+
+    async fn read_attachment(
+        &self,
+        _collection: &str,
+        _feature: &str,
+        _id: &str,
+    ) -> anyhow::Result<Option<Attachment>> {
+        Ok(None)
+    }
+
+    async fn update_attachment(
+        &self,
+        _collection: &str,
+        _feature: &str,
+        _attachment: &Attachment,
+    ) -> anyhow::Result<()> {
+        bail!("Attachments are not supported on static datasets")
+    }
+
+    async fn delete_attachment(
+        &self,
+        _collection: &str,
+        _feature: &str,
+        _id: &str,
+    ) -> anyhow::Result<()> {
+        bail!("Attachments are not supported on static datasets")
+    }
+
+    async fn list_attachments(
+        &self,
+        _collection: &str,
+        _feature: &str,
+    ) -> anyhow::Result<Vec<Attachment>> {
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,63 @@
+use anyhow::bail;
+
+use ogcapi_types::{
+    common::{Bbox, Collection, Collections, Query},
+    features::Queryables,
+};
+
+use crate::CollectionTransactions;
+
+use super::Files;
+
+#[async_trait::async_trait]
+impl CollectionTransactions for Files {
+    async fn create_collection(&self, _collection: &Collection) -> anyhow::Result<String> {
+        bail!("Collections of static datasets are read-only")
+    }
+
+    async fn read_collection(&self, id: &str) -> anyhow::Result<Option<Collection>> {
+        let datasets = self.datasets.read().unwrap();
+        Ok(datasets.get(id).map(|d| d.collection.to_owned()))
+    }
+
+    async fn update_collection(&self, _collection: &Collection) -> anyhow::Result<()> {
+        bail!("Collections of static datasets are read-only")
+    }
+
+    async fn delete_collection(&self, _id: &str) -> anyhow::Result<()> {
+        bail!("Collections of static datasets are read-only")
+    }
+
+    async fn list_collections(&self, query: &Query) -> anyhow::Result<Collections> {
+        let datasets = self.datasets.read().unwrap();
+
+        let matching = datasets
+            .values()
+            .filter(|d| query.matches(&d.collection))
+            .map(|d| d.collection.to_owned())
+            .collect();
+
+        Ok(Collections::paginated(matching, query))
+    }
+
+    async fn read_queryables(&self, _id: &str) -> anyhow::Result<Option<Queryables>> {
+        Ok(None)
+    }
+
+    async fn update_queryables(&self, _id: &str, _queryables: &Queryables) -> anyhow::Result<()> {
+        bail!("Collections of static datasets are read-only")
+    }
+
+    async fn extent(&self, id: &str) -> anyhow::Result<Option<Bbox>> {
+        let datasets = self.datasets.read().unwrap();
+
+        Ok(datasets.get(id).and_then(|d| {
+            d.envelopes
+                .iter()
+                .flatten()
+                .fold(None, |extent: Option<Bbox>, envelope| {
+                    Some(extent.map_or_else(|| envelope.to_owned(), |e| e.union(envelope)))
+                })
+        }))
+    }
+}
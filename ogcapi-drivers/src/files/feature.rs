@@ -0,0 +1,229 @@
+use anyhow::bail;
+use futures::{stream::BoxStream, StreamExt};
+use serde_json::Value;
+use tokio::sync::broadcast::error::RecvError;
+
+use ogcapi_types::{
+    common::{Bbox, Crs, Datetime, TemporalInterval},
+    features::{Feature, FeatureChange, FeatureCollection, Query},
+};
+
+use crate::{transform::transformer, FeatureChanges, FeatureTransactions};
+
+use super::{Dataset, Files};
+
+#[async_trait::async_trait]
+impl FeatureTransactions for Files {
+    async fn create_feature(&self, _feature: &Feature) -> anyhow::Result<String> {
+        bail!("Features of static datasets are read-only")
+    }
+
+    async fn create_features(
+        &self,
+        _collection: &str,
+        _features: &[Feature],
+        _crs: &Crs,
+    ) -> anyhow::Result<Vec<String>> {
+        bail!("Features of static datasets are read-only")
+    }
+
+    async fn read_feature(
+        &self,
+        collection: &str,
+        id: &str,
+        crs: &Crs,
+    ) -> anyhow::Result<Option<Feature>> {
+        let (mut feature, storage_crs) = {
+            let datasets = self.datasets.read().unwrap();
+            let Some(dataset) = datasets.get(collection) else {
+                return Ok(None);
+            };
+            let Some(i) = dataset.ids.get(id) else {
+                return Ok(None);
+            };
+            (dataset.features[*i].to_owned(), dataset.storage_crs())
+        };
+
+        transformer().transform_feature(&storage_crs, crs, &mut feature)?;
+
+        Ok(Some(feature))
+    }
+
+    async fn update_feature(&self, _feature: &Feature) -> anyhow::Result<()> {
+        bail!("Features of static datasets are read-only")
+    }
+
+    async fn delete_feature(&self, _collection: &str, _id: &str) -> anyhow::Result<()> {
+        bail!("Features of static datasets are read-only")
+    }
+
+    async fn list_items(
+        &self,
+        collection: &str,
+        query: &Query,
+    ) -> anyhow::Result<FeatureCollection> {
+        let (features, number_matched) = self.select(collection, query)?;
+
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = Some(number_matched as u64);
+
+        Ok(fc)
+    }
+
+    fn stream_items(
+        &self,
+        collection: &str,
+        query: &Query,
+    ) -> BoxStream<'static, anyhow::Result<Feature>> {
+        match self.select(collection, query) {
+            Ok((features, _)) => futures::stream::iter(features.into_iter().map(Ok)).boxed(),
+            Err(e) => futures::stream::once(async { Err(e) }).boxed(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl FeatureChanges for Files {
+    async fn subscribe_all(
+        &self,
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<FeatureChange>>> {
+        let receiver = self.changes.subscribe();
+
+        let changes = futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(change) => return Some((Ok(change), receiver)),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Ok(changes.boxed())
+    }
+}
+
+impl Files {
+    /// Features of a collection matching the query, in the requested crs,
+    /// along with the number of matches before paging
+    fn select(&self, collection: &str, query: &Query) -> anyhow::Result<(Vec<Feature>, usize)> {
+        if query.filter.is_some() || query.access_filter.is_some() {
+            bail!("Filters are not supported on static datasets");
+        }
+
+        let datasets = self.datasets.read().unwrap();
+        let Some(dataset) = datasets.get(collection) else {
+            return Ok((Vec::new(), 0));
+        };
+
+        let storage_crs = dataset.storage_crs();
+        let transform = transformer();
+
+        let bbox = query
+            .bbox
+            .as_ref()
+            .map(|bbox| transform.transform_bbox(&query.bbox_crs, &storage_crs, bbox))
+            .transpose()?;
+        let interval = query.datetime.as_ref().map(|d| d.interval());
+
+        let mut matches: Vec<(&Feature, &Option<Bbox>)> = dataset
+            .features
+            .iter()
+            .zip(&dataset.envelopes)
+            .filter(|(_, envelope)| match (&bbox, envelope) {
+                (Some(bbox), Some(envelope)) => bbox.intersects(envelope),
+                (Some(_), None) => false,
+                (None, _) => true,
+            })
+            .filter(|(feature, _)| match &interval {
+                Some(interval) => temporal_interval(feature).is_none_or(|i| i.intersects(interval)),
+                None => true,
+            })
+            .filter(|(feature, _)| {
+                query
+                    .additional_parameters
+                    .iter()
+                    .all(|(key, value)| matches_property(feature, key, value))
+            })
+            .collect();
+
+        // nearest features first, by the distance to their envelope
+        if let Some(nearest) = query.nearest {
+            let [x, y, ..] = transform
+                .transform_bbox(
+                    &Crs::default(),
+                    &storage_crs,
+                    &Bbox::Bbox2D([nearest.lon, nearest.lat, nearest.lon, nearest.lat]),
+                )?
+                .to_2d();
+            let distance = |envelope: &Option<Bbox>| match envelope {
+                Some(envelope) => {
+                    let [minx, miny, maxx, maxy] = envelope.to_2d();
+                    let dx = (minx - x).max(x - maxx).max(0.0);
+                    let dy = (miny - y).max(y - maxy).max(0.0);
+                    dx.hypot(dy)
+                }
+                None => f64::INFINITY,
+            };
+            matches.sort_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)));
+        }
+
+        // features requested by id, in the order of the ids
+        if let Some(ids) = query.ids.as_ref() {
+            matches.retain(|(feature, _)| feature.id.as_ref().is_some_and(|id| ids.contains(id)));
+            matches.sort_by_key(|(feature, _)| {
+                ids.iter().position(|id| feature.id.as_ref() == Some(id))
+            });
+        }
+
+        let number_matched = matches.len();
+
+        let mut features: Vec<Feature> = matches
+            .into_iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .map(|(feature, _)| feature.to_owned())
+            .collect();
+
+        for feature in features.iter_mut() {
+            transform.transform_feature(&storage_crs, &query.crs, feature)?;
+        }
+
+        Ok((features, number_matched))
+    }
+}
+
+impl Dataset {
+    fn storage_crs(&self) -> Crs {
+        self.collection.storage_crs.to_owned().unwrap_or_default()
+    }
+}
+
+/// Temporal extent of a feature from its `datetime` or `start_datetime` and
+/// `end_datetime` properties
+fn temporal_interval(feature: &Feature) -> Option<TemporalInterval> {
+    let properties = feature.properties.as_ref()?;
+
+    let datetime = match properties.get("datetime").and_then(Value::as_str) {
+        Some(datetime) => datetime.to_owned(),
+        None => format!(
+            "{}/{}",
+            properties.get("start_datetime")?.as_str()?,
+            properties.get("end_datetime")?.as_str()?
+        ),
+    };
+
+    datetime.parse::<Datetime>().ok().map(|d| d.interval())
+}
+
+/// Compare a property with a query parameter, features without the property
+/// are not excluded
+fn matches_property(feature: &Feature, key: &str, value: &str) -> bool {
+    match feature.properties.as_ref().and_then(|p| p.get(key)) {
+        Some(Value::String(s)) => s == value,
+        Some(Value::Number(n)) => n.as_f64() == value.parse::<f64>().ok(),
+        Some(Value::Bool(b)) => value.parse::<bool>().ok() == Some(*b),
+        Some(_) => false,
+        None => true,
+    }
+}
@@ -0,0 +1,378 @@
+//! Minimal reader of `FlatGeobuf` files, see <https://flatgeobuf.org>
+//!
+//! The spatial index is skipped, features are read sequentially.
+
+use anyhow::{bail, Context};
+use geojson::{Geometry, Value};
+use serde_json::Map;
+
+use ogcapi_types::{common::Crs, features::Feature};
+
+const UNKNOWN: u8 = 0;
+const POINT: u8 = 1;
+const LINE_STRING: u8 = 2;
+const POLYGON: u8 = 3;
+const MULTI_POINT: u8 = 4;
+const MULTI_LINE_STRING: u8 = 5;
+const MULTI_POLYGON: u8 = 6;
+const GEOMETRY_COLLECTION: u8 = 7;
+
+/// Size of a node of the packed R-tree
+const NODE_ITEM_SIZE: u64 = 40;
+
+/// Read the crs and features of a `FlatGeobuf` file
+pub(super) fn read(buf: &[u8]) -> anyhow::Result<(Crs, Vec<Feature>)> {
+    if buf.len() < 12 || &buf[..3] != b"fgb" || &buf[4..7] != b"fgb" {
+        bail!("Invalid FlatGeobuf magic bytes");
+    }
+
+    let header_size = u32::from_le_bytes(take(buf, 8)?) as usize;
+    let header = Table::root(buf.get(12..12 + header_size).context("Truncated header")?)?;
+
+    let geometry_type = header.u8(2)?;
+    let columns = header
+        .tables(7)?
+        .iter()
+        .map(Column::new)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let features_count = header.u64(8)?;
+    let index_node_size = header.u16(9)?.unwrap_or(16);
+
+    let crs = match header.table(10)? {
+        Some(crs) => {
+            // organization defaults to `EPSG`, geometries are in `x`/`y` order
+            let epsg = crs
+                .string(0)?
+                .is_none_or(|org| org.eq_ignore_ascii_case("EPSG"));
+            match crs.i32(1)? {
+                Some(code) if epsg && code != 0 && code != 4326 => Crs::from_epsg(code),
+                _ => Crs::default(),
+            }
+        }
+        None => Crs::default(),
+    };
+
+    let mut pos = 12 + header_size;
+    if index_node_size > 0 && features_count > 0 {
+        pos += index_size(features_count, index_node_size) as usize;
+    }
+
+    let mut features = Vec::with_capacity(features_count as usize);
+    while pos < buf.len() {
+        let size = u32::from_le_bytes(take(buf, pos)?) as usize;
+        let feature = Table::root(
+            buf.get(pos + 4..pos + 4 + size)
+                .context("Truncated feature")?,
+        )?;
+        pos += 4 + size;
+
+        let Some(geometry) = feature.table(0)? else {
+            continue;
+        };
+
+        let own_columns = feature
+            .tables(2)?
+            .iter()
+            .map(Column::new)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let columns = if own_columns.is_empty() {
+            &columns
+        } else {
+            &own_columns
+        };
+
+        let properties = properties(feature.bytes(1)?, columns)?;
+
+        features.push(serde_json::from_value(serde_json::json!({
+            "type": "Feature",
+            "properties": properties,
+            "geometry": Geometry::new(read_geometry(&geometry, geometry_type)?),
+        }))?);
+    }
+
+    Ok((crs, features))
+}
+
+/// Size of the packed R-tree in bytes
+fn index_size(count: u64, node_size: u16) -> u64 {
+    let node_size = u64::from(node_size.max(2));
+
+    let mut n = count;
+    let mut nodes = n;
+    while n != 1 {
+        n = n.div_ceil(node_size);
+        nodes += n;
+    }
+
+    nodes * NODE_ITEM_SIZE
+}
+
+fn read_geometry(geometry: &Table, geometry_type: u8) -> anyhow::Result<Value> {
+    let geometry_type = match geometry_type {
+        UNKNOWN => geometry.u8(6)?,
+        t => t,
+    };
+
+    let value = match geometry_type {
+        MULTI_POLYGON => Value::MultiPolygon(
+            geometry
+                .tables(7)?
+                .iter()
+                .map(|part| match read_geometry(part, POLYGON)? {
+                    Value::Polygon(p) => Ok(p),
+                    _ => bail!("Expected polygon in multi polygon"),
+                })
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        GEOMETRY_COLLECTION => Value::GeometryCollection(
+            geometry
+                .tables(7)?
+                .iter()
+                .map(|part| read_geometry(part, UNKNOWN).map(Geometry::new))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        t => {
+            let xy = geometry.f64s(1)?;
+            let z = geometry.f64s(2)?;
+
+            let positions: Vec<Vec<f64>> = xy
+                .chunks_exact(2)
+                .enumerate()
+                .map(|(i, xy)| match z.get(i) {
+                    Some(z) => vec![xy[0], xy[1], *z],
+                    None => vec![xy[0], xy[1]],
+                })
+                .collect();
+
+            match t {
+                POINT => Value::Point(positions.into_iter().next().context("Empty point")?),
+                LINE_STRING => Value::LineString(positions),
+                MULTI_POINT => Value::MultiPoint(positions),
+                POLYGON => Value::Polygon(split(positions, &geometry.u32s(0)?)),
+                MULTI_LINE_STRING => Value::MultiLineString(split(positions, &geometry.u32s(0)?)),
+                t => bail!("Unsupported FlatGeobuf geometry type `{t}`"),
+            }
+        }
+    };
+
+    Ok(value)
+}
+
+/// Split positions into parts at the given end indices
+fn split(positions: Vec<Vec<f64>>, ends: &[u32]) -> Vec<Vec<Vec<f64>>> {
+    if ends.is_empty() {
+        return vec![positions];
+    }
+
+    let mut start = 0;
+    ends.iter()
+        .map(|end| {
+            let end = (*end as usize).clamp(start, positions.len());
+            let part = positions[start..end].to_vec();
+            start = end;
+            part
+        })
+        .collect()
+}
+
+/// Attribute column
+struct Column {
+    name: String,
+    r#type: u8,
+}
+
+impl Column {
+    fn new(table: &Table) -> anyhow::Result<Self> {
+        Ok(Column {
+            name: table.string(0)?.context("Column without name")?.to_owned(),
+            r#type: table.u8(1)?,
+        })
+    }
+}
+
+/// Decode the properties buffer of a feature
+fn properties(buf: &[u8], columns: &[Column]) -> anyhow::Result<Map<String, serde_json::Value>> {
+    let mut properties = Map::new();
+
+    let mut pos = 0;
+    while pos < buf.len() {
+        let index = u16::from_le_bytes(take(buf, pos)?) as usize;
+        pos += 2;
+
+        let column = columns.get(index).context("Invalid column index")?;
+
+        let value = match column.r#type {
+            0 => serde_json::Value::from(i8::from_le_bytes(take(buf, pos)?)),
+            1 => serde_json::Value::from(u8::from_le_bytes(take(buf, pos)?)),
+            2 => serde_json::Value::from(take::<1>(buf, pos)?[0] != 0),
+            3 => serde_json::Value::from(i16::from_le_bytes(take(buf, pos)?)),
+            4 => serde_json::Value::from(u16::from_le_bytes(take(buf, pos)?)),
+            5 => serde_json::Value::from(i32::from_le_bytes(take(buf, pos)?)),
+            6 => serde_json::Value::from(u32::from_le_bytes(take(buf, pos)?)),
+            7 => serde_json::Value::from(i64::from_le_bytes(take(buf, pos)?)),
+            8 => serde_json::Value::from(u64::from_le_bytes(take(buf, pos)?)),
+            9 => serde_json::Value::from(f32::from_le_bytes(take(buf, pos)?)),
+            10 => serde_json::Value::from(f64::from_le_bytes(take(buf, pos)?)),
+            // string, json, datetime and binary values are length prefixed
+            t @ 11..=14 => {
+                let len = u32::from_le_bytes(take(buf, pos)?) as usize;
+                pos += 4;
+                let bytes = buf.get(pos..pos + len).context("Truncated property")?;
+                pos += len;
+                match t {
+                    12 => serde_json::from_slice(bytes)?,
+                    // binaries are not representable in properties
+                    14 => continue,
+                    _ => serde_json::Value::from(String::from_utf8_lossy(bytes)),
+                }
+            }
+            t => bail!("Unsupported FlatGeobuf column type `{t}`"),
+        };
+
+        pos += match column.r#type {
+            0..=2 => 1,
+            3 | 4 => 2,
+            5 | 6 | 9 => 4,
+            7 | 8 | 10 => 8,
+            _ => 0,
+        };
+
+        properties.insert(column.name.to_owned(), value);
+    }
+
+    Ok(properties)
+}
+
+fn take<const N: usize>(buf: &[u8], pos: usize) -> anyhow::Result<[u8; N]> {
+    Ok(buf
+        .get(pos..pos + N)
+        .context("Unexpected end of FlatGeobuf")?
+        .try_into()?)
+}
+
+/// Table of a flatbuffer
+struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    vtable: usize,
+}
+
+impl<'a> Table<'a> {
+    /// Root table of a flatbuffer
+    fn root(buf: &'a [u8]) -> anyhow::Result<Self> {
+        Table::at(buf, u32::from_le_bytes(take(buf, 0)?) as usize)
+    }
+
+    fn at(buf: &'a [u8], pos: usize) -> anyhow::Result<Self> {
+        let offset = i32::from_le_bytes(take(buf, pos)?) as isize;
+        let vtable = pos
+            .checked_add_signed(-offset)
+            .context("Invalid vtable offset")?;
+
+        Ok(Table { buf, pos, vtable })
+    }
+
+    /// Position of a field, `None` if not present
+    fn field(&self, index: usize) -> anyhow::Result<Option<usize>> {
+        let vtable_size = u16::from_le_bytes(take(self.buf, self.vtable)?) as usize;
+
+        let entry = 4 + 2 * index;
+        if entry + 2 > vtable_size {
+            return Ok(None);
+        }
+
+        let offset = u16::from_le_bytes(take(self.buf, self.vtable + entry)?) as usize;
+        Ok((offset != 0).then_some(self.pos + offset))
+    }
+
+    /// Position of the target of an offset field
+    fn target(&self, index: usize) -> anyhow::Result<Option<usize>> {
+        match self.field(index)? {
+            Some(pos) => Ok(Some(
+                pos + u32::from_le_bytes(take(self.buf, pos)?) as usize,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn u8(&self, index: usize) -> anyhow::Result<u8> {
+        match self.field(index)? {
+            Some(pos) => Ok(take::<1>(self.buf, pos)?[0]),
+            None => Ok(0),
+        }
+    }
+
+    fn u16(&self, index: usize) -> anyhow::Result<Option<u16>> {
+        self.field(index)?
+            .map(|pos| Ok(u16::from_le_bytes(take(self.buf, pos)?)))
+            .transpose()
+    }
+
+    fn i32(&self, index: usize) -> anyhow::Result<Option<i32>> {
+        self.field(index)?
+            .map(|pos| Ok(i32::from_le_bytes(take(self.buf, pos)?)))
+            .transpose()
+    }
+
+    fn u64(&self, index: usize) -> anyhow::Result<u64> {
+        match self.field(index)? {
+            Some(pos) => Ok(u64::from_le_bytes(take(self.buf, pos)?)),
+            None => Ok(0),
+        }
+    }
+
+    fn table(&self, index: usize) -> anyhow::Result<Option<Table<'a>>> {
+        self.target(index)?
+            .map(|pos| Table::at(self.buf, pos))
+            .transpose()
+    }
+
+    /// Start and length of a vector field
+    fn vector(&self, index: usize) -> anyhow::Result<(usize, usize)> {
+        match self.target(index)? {
+            Some(pos) => Ok((pos + 4, u32::from_le_bytes(take(self.buf, pos)?) as usize)),
+            None => Ok((0, 0)),
+        }
+    }
+
+    fn bytes(&self, index: usize) -> anyhow::Result<&'a [u8]> {
+        let (start, len) = self.vector(index)?;
+        self.buf
+            .get(start..start + len)
+            .context("Unexpected end of FlatGeobuf")
+    }
+
+    fn string(&self, index: usize) -> anyhow::Result<Option<&'a str>> {
+        if self.field(index)?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(std::str::from_utf8(self.bytes(index)?)?))
+    }
+
+    fn u32s(&self, index: usize) -> anyhow::Result<Vec<u32>> {
+        let (start, len) = self.vector(index)?;
+        (0..len)
+            .map(|i| Ok(u32::from_le_bytes(take(self.buf, start + 4 * i)?)))
+            .collect()
+    }
+
+    fn f64s(&self, index: usize) -> anyhow::Result<Vec<f64>> {
+        let (start, len) = self.vector(index)?;
+        (0..len)
+            .map(|i| Ok(f64::from_le_bytes(take(self.buf, start + 8 * i)?)))
+            .collect()
+    }
+
+    fn tables(&self, index: usize) -> anyhow::Result<Vec<Table<'a>>> {
+        let (start, len) = self.vector(index)?;
+        (0..len)
+            .map(|i| {
+                let pos = start + 4 * i;
+                Table::at(
+                    self.buf,
+                    pos + u32::from_le_bytes(take(self.buf, pos)?) as usize,
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,391 @@
+mod access;
+mod attachment;
+mod collection;
+mod feature;
+mod fgb;
+mod mode;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use anyhow::Context;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use sqlx::{sqlite::SqliteConnectOptions, Connection, SqliteConnection};
+use tokio::sync::{broadcast, mpsc};
+
+use ogcapi_types::{
+    common::{Bbox, Collection, Crs, Extent, SpatialExtent},
+    features::{ChangeKind, Feature, FeatureChange},
+};
+
+use crate::{geopackage, transform::transformer};
+
+/// File extensions of the supported formats
+const EXTENSIONS: [&str; 4] = ["geojson", "json", "fgb", "gpkg"];
+
+/// Time to wait for further file events before reloading
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Capacity of the change notification channel
+const CAPACITY: usize = 1024;
+
+/// Read-only driver serving the `GeoJSON`, `FlatGeobuf` and `GeoPackage`
+/// files of a directory without database
+///
+/// Every file becomes a collection named after the file stem, every feature
+/// table of a `GeoPackage` one named after the table. The features are held
+/// in memory and reloaded when the files change.
+#[derive(Clone)]
+pub struct Files {
+    dir: PathBuf,
+    datasets: Arc<RwLock<BTreeMap<String, Dataset>>>,
+    changes: broadcast::Sender<FeatureChange>,
+    watcher: Arc<Mutex<Option<RecommendedWatcher>>>,
+}
+
+/// Collection read from a file
+struct Dataset {
+    /// File the collection was read from
+    path: PathBuf,
+    collection: Collection,
+    features: Vec<Feature>,
+    /// Envelopes of the feature geometries in storage crs
+    envelopes: Vec<Option<Bbox>>,
+    /// Position of the features by id
+    ids: HashMap<String, usize>,
+}
+
+impl Files {
+    /// Load the files of a directory
+    pub async fn open(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let dir = dir.as_ref().canonicalize()?;
+
+        let files = Files {
+            dir: dir.clone(),
+            datasets: Default::default(),
+            changes: broadcast::channel(CAPACITY).0,
+            watcher: Default::default(),
+        };
+
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if is_supported(&path) {
+                // a broken file should not keep the others from being served
+                if let Err(e) = files.reload(&path).await {
+                    tracing::warn!("Failed to load `{}`: {e:#}", path.display());
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Watch the directory and reload changed files, announcing the changed
+    /// features to subscribers
+    pub fn watch(self) -> anyhow::Result<Self> {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    for path in event.paths {
+                        let _ = sender.send(path);
+                    }
+                }
+            })?;
+        watcher.watch(&self.dir, RecursiveMode::NonRecursive)?;
+
+        *self.watcher.lock().unwrap() = Some(watcher);
+
+        let files = self.clone();
+        tokio::spawn(async move {
+            while let Some(path) = receiver.recv().await {
+                // wait for writes to settle
+                let mut paths = vec![path];
+                while let Ok(Some(path)) = tokio::time::timeout(DEBOUNCE, receiver.recv()).await {
+                    paths.push(path);
+                }
+                paths.sort();
+                paths.dedup();
+
+                for path in paths.into_iter().filter(|p| is_supported(p)) {
+                    if let Err(e) = files.reload(&path).await {
+                        tracing::warn!("Failed to reload `{}`: {e:#}", path.display());
+                    }
+                }
+            }
+        });
+
+        Ok(self)
+    }
+
+    /// Replace the collections of a file with its current content
+    async fn reload(&self, path: &Path) -> anyhow::Result<()> {
+        let datasets = if path.exists() {
+            read(path)
+                .await
+                .with_context(|| format!("Read `{}`", path.display()))?
+        } else {
+            Vec::new()
+        };
+
+        let mut changes = Vec::new();
+        {
+            let mut current = self.datasets.write().unwrap();
+
+            let previous: Vec<String> = current
+                .iter()
+                .filter(|(_, d)| d.path == path)
+                .map(|(id, _)| id.to_owned())
+                .collect();
+
+            let mut previous: HashMap<String, Dataset> = previous
+                .into_iter()
+                .filter_map(|id| current.remove_entry(&id))
+                .collect();
+
+            for dataset in datasets {
+                let id = dataset.collection.id.to_owned();
+                match previous.remove(&id) {
+                    Some(old) => changes.extend(diff(&id, &old, &dataset)),
+                    None => changes.extend(
+                        dataset
+                            .features
+                            .iter()
+                            .map(|f| change(&id, f, ChangeKind::Create)),
+                    ),
+                }
+                current.insert(id, dataset);
+            }
+
+            for (id, old) in previous {
+                changes.extend(
+                    old.features
+                        .iter()
+                        .map(|f| change(&id, f, ChangeKind::Delete)),
+                );
+            }
+        }
+
+        for change in changes {
+            // no subscribers is not an error
+            let _ = self.changes.send(change);
+        }
+
+        Ok(())
+    }
+}
+
+fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Read the collections of a file
+async fn read(path: &Path) -> anyhow::Result<Vec<Dataset>> {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .context("Invalid file name")?;
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    let datasets = match extension.as_deref() {
+        Some("geojson" | "json") => {
+            let mut value: serde_json::Value =
+                serde_json::from_slice(&tokio::fs::read(path).await?)?;
+            let features = match value.get("type").and_then(|t| t.as_str()) {
+                Some("FeatureCollection") => serde_json::from_value(value["features"].take())?,
+                Some("Feature") => vec![serde_json::from_value(value)?],
+                _ => anyhow::bail!("Expected a GeoJSON feature collection"),
+            };
+            // GeoJSON is always in CRS84, see RFC 7946
+            vec![Dataset::new(path, stem, Crs::default(), features)]
+        }
+        Some("fgb") => {
+            let (crs, features) = fgb::read(&tokio::fs::read(path).await?)?;
+            vec![Dataset::new(path, stem, crs, features)]
+        }
+        Some("gpkg") => {
+            let mut conn = SqliteConnection::connect_with(
+                &SqliteConnectOptions::new().filename(path).read_only(true),
+            )
+            .await?;
+
+            let mut datasets = Vec::new();
+            for table in geopackage::feature_tables(&mut conn).await? {
+                let pk = table.primary_key(&mut conn).await?;
+
+                let rows = sqlx::query(&format!(r#"SELECT * FROM "{}""#, table.table_name))
+                    .fetch_all(&mut conn)
+                    .await?;
+
+                let mut features = Vec::with_capacity(rows.len());
+                for row in rows {
+                    if let Some(feature) = geopackage::read_feature(
+                        &row,
+                        &table.table_name,
+                        &table.column_name,
+                        pk.as_deref(),
+                    )? {
+                        features.push(feature);
+                    }
+                }
+
+                datasets.push(Dataset::new(path, &table.table_name, table.crs(), features));
+            }
+
+            conn.close().await?;
+
+            datasets
+        }
+        _ => Vec::new(),
+    };
+
+    Ok(datasets)
+}
+
+impl Dataset {
+    fn new(path: &Path, id: &str, crs: Crs, mut features: Vec<Feature>) -> Self {
+        let mut ids = HashMap::with_capacity(features.len());
+        let mut envelopes = Vec::with_capacity(features.len());
+        let mut extent: Option<Bbox> = None;
+
+        for (i, feature) in features.iter_mut().enumerate() {
+            // features without id are identified by their position
+            let feature_id = feature.id.get_or_insert_with(|| (i + 1).to_string());
+            ids.insert(feature_id.to_owned(), i);
+            feature.collection = Some(id.to_owned());
+
+            let envelope = envelope(&feature.geometry.value);
+            if let Some(envelope) = &envelope {
+                extent = Some(match extent {
+                    Some(extent) => extent.union(envelope),
+                    None => envelope.to_owned(),
+                });
+            }
+            envelopes.push(envelope);
+        }
+
+        let mut collection = Collection {
+            id: id.to_owned(),
+            title: Some(id.to_owned()),
+            item_type: Some("feature".to_owned()),
+            storage_crs: Some(crs.to_owned()),
+            ..Default::default()
+        };
+
+        if let Some(bbox) = extent {
+            if let Ok(bbox) = transformer().transform_bbox(&crs, &Crs::default(), &bbox) {
+                collection.extent = Some(Extent {
+                    spatial: Some(SpatialExtent {
+                        bbox: vec![bbox],
+                        crs: Crs::default(),
+                    }),
+                    ..Default::default()
+                });
+            }
+        }
+
+        collection.normalize_crs();
+
+        Dataset {
+            path: path.to_owned(),
+            collection,
+            features,
+            envelopes,
+            ids,
+        }
+    }
+}
+
+/// Bounding box of a geometry, three dimensional if it has heights
+fn envelope(value: &geojson::Value) -> Option<Bbox> {
+    fn extend(bbox: &mut Option<[f64; 6]>, position: &[f64]) {
+        let z = position.get(2).copied();
+        let (x, y) = (position[0], position[1]);
+        match bbox {
+            Some(b) => {
+                b[0] = b[0].min(x);
+                b[1] = b[1].min(y);
+                b[3] = b[3].max(x);
+                b[4] = b[4].max(y);
+                if let Some(z) = z {
+                    b[2] = b[2].min(z);
+                    b[5] = b[5].max(z);
+                }
+            }
+            None => {
+                let z = z.unwrap_or(f64::NAN);
+                *bbox = Some([x, y, z, x, y, z]);
+            }
+        }
+    }
+
+    fn visit(value: &geojson::Value, bbox: &mut Option<[f64; 6]>) {
+        use geojson::Value;
+
+        match value {
+            Value::Point(p) => extend(bbox, p),
+            Value::MultiPoint(ps) | Value::LineString(ps) => {
+                ps.iter().for_each(|p| extend(bbox, p))
+            }
+            Value::MultiLineString(ls) | Value::Polygon(ls) => {
+                ls.iter().flatten().for_each(|p| extend(bbox, p))
+            }
+            Value::MultiPolygon(ps) => ps.iter().flatten().flatten().for_each(|p| extend(bbox, p)),
+            Value::GeometryCollection(gs) => gs.iter().for_each(|g| visit(&g.value, bbox)),
+        }
+    }
+
+    let mut bbox = None;
+    visit(value, &mut bbox);
+
+    bbox.map(|b| {
+        if b[2].is_nan() {
+            Bbox::Bbox2D([b[0], b[1], b[3], b[4]])
+        } else {
+            Bbox::Bbox3D(b)
+        }
+    })
+}
+
+fn change(collection: &str, feature: &Feature, kind: ChangeKind) -> FeatureChange {
+    FeatureChange {
+        kind,
+        collection: collection.to_owned(),
+        id: feature.id.to_owned().unwrap_or_default(),
+    }
+}
+
+/// Changes between two versions of a collection
+fn diff(collection: &str, old: &Dataset, new: &Dataset) -> Vec<FeatureChange> {
+    let mut changes = Vec::new();
+
+    for feature in &new.features {
+        let id = feature.id.as_deref().unwrap_or_default();
+        match old.ids.get(id).map(|i| &old.features[*i]) {
+            Some(previous) if previous == feature => {}
+            Some(_) => changes.push(change(collection, feature, ChangeKind::Update)),
+            None => changes.push(change(collection, feature, ChangeKind::Create)),
+        }
+    }
+
+    for feature in &old.features {
+        let id = feature.id.as_deref().unwrap_or_default();
+        if !new.ids.contains_key(id) {
+            changes.push(change(collection, feature, ChangeKind::Delete));
+        }
+    }
+
+    changes
+}
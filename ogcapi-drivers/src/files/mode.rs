@@ -0,0 +1,18 @@
+use anyhow::bail;
+
+use crate::ModeTransactions;
+
+use super::Files;
+
+/// Services of static datasets are not switched by replicas, the mode is kept
+/// by the process
+#[async_trait::async_trait]
+impl ModeTransactions for Files {
+    async fn read_mode(&self) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    async fn write_mode(&self, _mode: &str) -> anyhow::Result<()> {
+        bail!("Switching the mode is not supported on static datasets")
+    }
+}
@@ -0,0 +1,113 @@
+//! Reading the feature tables of `GeoPackage` files
+
+use serde_json::{Map, Value};
+use sqlx::{sqlite::SqliteRow, Column, Row, SqliteConnection, TypeInfo, ValueRef};
+
+use ogcapi_types::{common::Crs, features::Feature};
+
+use crate::wkb;
+
+/// Feature table as registered in `gpkg_contents`
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct FeatureTable {
+    pub table_name: String,
+    pub column_name: String,
+    organization: Option<String>,
+    organization_coordsys_id: Option<i32>,
+}
+
+impl FeatureTable {
+    /// Crs of the geometry column, `CRS84` for undefined or `EPSG:4326`
+    /// reference systems as `GeoPackage` geometries are in `x`/`y` order
+    pub fn crs(&self) -> Crs {
+        match (self.organization.as_deref(), self.organization_coordsys_id) {
+            (Some(org), Some(code)) if org.eq_ignore_ascii_case("EPSG") && code != 4326 => {
+                Crs::from_epsg(code)
+            }
+            _ => Crs::default(),
+        }
+    }
+
+    /// Name of the integer primary key column, if any
+    pub async fn primary_key(&self, conn: &mut SqliteConnection) -> anyhow::Result<Option<String>> {
+        Ok(sqlx::query_scalar(&format!(
+            r#"SELECT name FROM pragma_table_info('{}') WHERE pk = 1"#,
+            self.table_name.replace('\'', "''")
+        ))
+        .fetch_optional(conn)
+        .await?)
+    }
+}
+
+/// List the feature tables of a `GeoPackage`
+pub async fn feature_tables(conn: &mut SqliteConnection) -> anyhow::Result<Vec<FeatureTable>> {
+    Ok(sqlx::query_as(
+        r#"
+        SELECT c.table_name, g.column_name, s.organization, s.organization_coordsys_id
+        FROM gpkg_contents c
+        JOIN gpkg_geometry_columns g ON c.table_name = g.table_name
+        LEFT JOIN gpkg_spatial_ref_sys s ON g.srs_id = s.srs_id
+        WHERE c.data_type = 'features'
+        "#,
+    )
+    .fetch_all(conn)
+    .await?)
+}
+
+/// Convert a row of a feature table, rows without geometry are skipped
+pub fn read_feature(
+    row: &SqliteRow,
+    collection_id: &str,
+    geometry_column: &str,
+    pk: Option<&str>,
+) -> anyhow::Result<Option<Feature>> {
+    let mut id = None;
+    let mut geometry = None;
+    let mut properties = Map::new();
+
+    for column in row.columns() {
+        let name = column.name();
+        let raw = row.try_get_raw(column.ordinal())?;
+
+        if name == geometry_column {
+            if !raw.is_null() {
+                geometry = wkb::from_gpkg(&row.try_get::<Vec<u8>, _>(column.ordinal())?)?;
+            }
+            continue;
+        }
+
+        let value = if raw.is_null() {
+            Value::Null
+        } else {
+            match raw.type_info().name() {
+                "INTEGER" | "BOOLEAN" => Value::from(row.try_get::<i64, _>(column.ordinal())?),
+                "REAL" => Value::from(row.try_get::<f64, _>(column.ordinal())?),
+                "TEXT" | "DATE" | "DATETIME" => {
+                    Value::from(row.try_get::<String, _>(column.ordinal())?)
+                }
+                // blobs are not representable in properties
+                _ => continue,
+            }
+        };
+
+        if Some(name) == pk {
+            id = Some(value.to_string().trim_matches('"').to_owned());
+        } else {
+            properties.insert(name.to_owned(), value);
+        }
+    }
+
+    let Some(geometry) = geometry else {
+        return Ok(None);
+    };
+
+    let feature = serde_json::from_value(serde_json::json!({
+        "id": id,
+        "type": "Feature",
+        "collection": collection_id,
+        "properties": properties,
+        "geometry": geojson::Geometry::new(geometry),
+    }))?;
+
+    Ok(Some(feature))
+}
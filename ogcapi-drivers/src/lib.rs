@@ -1,17 +1,46 @@
+#[cfg(feature = "files")]
+pub mod files;
+#[cfg(feature = "geopackage")]
+pub mod geopackage;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "pmtiles")]
+pub mod pmtiles;
 #[cfg(feature = "postgres")]
 pub mod postgres;
+pub mod raster;
 #[cfg(feature = "s3")]
 pub mod s3;
+pub mod transform;
+#[cfg(any(feature = "geopackage", feature = "postgres"))]
+pub mod wkb;
+
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+use futures::{stream::BoxStream, StreamExt};
+
+use raster::{Grid, Raster, RasterRequest};
 
 #[cfg(feature = "stac")]
 use ogcapi_types::stac::SearchParams;
 use ogcapi_types::{
-    common::{Collection, Collections, Crs, Query as CollectionQuery},
+    auth::{AccessFilter, ApiKey, KeyUsage, Quota, User},
+    common::{Bbox, Catalog, Collection, Collections, Crs, Query as CollectionQuery},
+    cql2::Expr,
     edr::{Query as EdrQuery, QueryType},
-    features::{Feature, FeatureCollection, Query as FeatureQuery},
-    processes::{Results, StatusInfo},
+    features::{
+        AggregateQuery, Attachment, CollectionStats, Feature, FeatureChange, FeatureCollection,
+        PropertyValues, Query as FeatureQuery, Queryables, RecentChange, StatsQuery,
+        ValidationRule, ValuesQuery, Violation,
+    },
+    harvest::Harvest,
+    joins::{DataFile, Join},
+    processes::{JobQuery, Results, StatusInfo},
     styles::Styles,
-    tiles::TileMatrixSet,
+    tiles::{Aggregation, TileMatrixSet, TileUsage},
+    views::View,
+    webhooks::{Delivery, Webhook},
 };
 
 /// Trait for `Collection` transactions
@@ -26,6 +55,39 @@ pub trait CollectionTransactions: Send + Sync {
     async fn delete_collection(&self, id: &str) -> anyhow::Result<()>;
 
     async fn list_collections(&self, query: &CollectionQuery) -> anyhow::Result<Collections>;
+
+    async fn read_queryables(&self, id: &str) -> anyhow::Result<Option<Queryables>>;
+
+    async fn update_queryables(&self, id: &str, queryables: &Queryables) -> anyhow::Result<()>;
+
+    /// Bounding box of the features of a collection in its storage crs,
+    /// `None` if unknown or the collection is empty
+    async fn extent(&self, id: &str) -> anyhow::Result<Option<Bbox>> {
+        let _ = id;
+        Ok(None)
+    }
+
+    /// Store a catalog grouping collections, replacing an existing one with
+    /// the same id
+    async fn upsert_catalog(&self, catalog: &Catalog) -> anyhow::Result<()> {
+        let _ = catalog;
+        anyhow::bail!("Catalogs are not supported")
+    }
+
+    async fn read_catalog(&self, id: &str) -> anyhow::Result<Option<Catalog>> {
+        let _ = id;
+        Ok(None)
+    }
+
+    async fn delete_catalog(&self, id: &str) -> anyhow::Result<()> {
+        let _ = id;
+        anyhow::bail!("Catalogs are not supported")
+    }
+
+    /// All catalogs, nested ones included
+    async fn list_catalogs(&self) -> anyhow::Result<Vec<Catalog>> {
+        Ok(Vec::new())
+    }
 }
 
 /// Trait for `Feature` transactions
@@ -33,6 +95,33 @@ pub trait CollectionTransactions: Send + Sync {
 pub trait FeatureTransactions: Send + Sync {
     async fn create_feature(&self, feature: &Feature) -> anyhow::Result<String>;
 
+    /// Create multiple features of a collection at once, reprojecting the
+    /// geometries from `crs` to the storage crs of the collection
+    async fn create_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+    ) -> anyhow::Result<Vec<String>>;
+
+    /// Create the features of a stream of batches, e.g. of a bulk ingest,
+    /// within one transaction which is rolled back if the stream yields an
+    /// error, returns the number of created features
+    ///
+    /// Drivers without transactions create the batches one by one.
+    async fn create_feature_batches(
+        &self,
+        collection: &str,
+        mut batches: BoxStream<'_, anyhow::Result<Vec<Feature>>>,
+        crs: &Crs,
+    ) -> anyhow::Result<usize> {
+        let mut count = 0;
+        while let Some(batch) = batches.next().await {
+            count += self.create_features(collection, &batch?, crs).await?.len();
+        }
+        Ok(count)
+    }
+
     async fn read_feature(
         &self,
         collection: &str,
@@ -41,13 +130,269 @@ pub trait FeatureTransactions: Send + Sync {
     ) -> anyhow::Result<Option<Feature>>;
     async fn update_feature(&self, feature: &Feature) -> anyhow::Result<()>;
 
+    /// Replace a feature only if the stored one has the `version`, features
+    /// without version property count as version `0`, returns whether the
+    /// feature was replaced
+    async fn update_feature_if_version(
+        &self,
+        feature: &Feature,
+        version: u64,
+    ) -> anyhow::Result<bool> {
+        let (Some(collection), Some(id)) = (&feature.collection, &feature.id) else {
+            return Ok(false);
+        };
+        let stored = self.read_feature(collection, id, &Crs::default()).await?;
+        if stored.is_some_and(|stored| stored.version().unwrap_or(0) == version) {
+            self.update_feature(feature).await?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Create features of a collection, replacing the stored features with the
+    /// same ids, geometries are in `crs`
+    async fn upsert_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+    ) -> anyhow::Result<Vec<String>> {
+        let mut ids = Vec::with_capacity(features.len());
+        let mut new = Vec::new();
+        for feature in features {
+            let stored = match &feature.id {
+                Some(id) => self.read_feature(collection, id, crs).await?.is_some(),
+                None => false,
+            };
+            if stored {
+                let mut feature = feature.to_owned();
+                feature.collection = Some(collection.to_owned());
+                self.update_feature(&feature).await?;
+                ids.extend(feature.id);
+            } else {
+                new.push(feature.to_owned());
+            }
+        }
+        if !new.is_empty() {
+            ids.extend(self.create_features(collection, &new, crs).await?);
+        }
+        Ok(ids)
+    }
+
     async fn delete_feature(&self, collection: &str, id: &str) -> anyhow::Result<()>;
 
+    /// Checksums of the stored features recorded by earlier deltas, by id
+    async fn checksums(&self, collection: &str) -> anyhow::Result<HashMap<String, String>> {
+        let _ = collection;
+        anyhow::bail!("Deltas are not supported")
+    }
+
+    /// Apply a delta to a collection at once, recording the checksums of the
+    /// upserted features
+    async fn apply_delta(&self, collection: &str, delta: &Delta, crs: &Crs) -> anyhow::Result<()> {
+        let _ = (collection, delta, crs);
+        anyhow::bail!("Deltas are not supported")
+    }
+
     async fn list_items(
         &self,
         collection: &str,
         query: &FeatureQuery,
     ) -> anyhow::Result<FeatureCollection>;
+
+    /// Stream the items of a collection one by one, e.g. for `GeoJSON` text sequences
+    fn stream_items(
+        &self,
+        collection: &str,
+        query: &FeatureQuery,
+    ) -> BoxStream<'static, anyhow::Result<Feature>>;
+
+    /// Estimate the cost of listing items without running the query, `None`
+    /// if not supported
+    async fn plan_items(
+        &self,
+        _collection: &str,
+        _query: &FeatureQuery,
+    ) -> anyhow::Result<Option<QueryPlan>> {
+        Ok(None)
+    }
+
+    /// Checks which features match a filter, e.g. an access filter, without
+    /// storing them
+    async fn match_filter(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+        filter: &Expr,
+    ) -> anyhow::Result<Vec<bool>> {
+        let _ = (collection, features, crs, filter);
+        anyhow::bail!("Filters are not supported")
+    }
+
+    /// Statistics of the features of a collection, `None` if not supported
+    async fn stats(
+        &self,
+        _collection: &str,
+        _query: &StatsQuery,
+    ) -> anyhow::Result<Option<CollectionStats>> {
+        Ok(None)
+    }
+
+    /// Distinct values of a property with the number of features having
+    /// them, `None` if not supported
+    async fn property_values(
+        &self,
+        _collection: &str,
+        _property: &str,
+        _query: &ValuesQuery,
+    ) -> anyhow::Result<Option<PropertyValues>> {
+        Ok(None)
+    }
+
+    /// Aggregates of the features of a collection per spatial group, as
+    /// features with the geometry of their group in `CRS84`, `None` if not
+    /// supported
+    async fn aggregate(
+        &self,
+        _collection: &str,
+        _query: &AggregateQuery,
+    ) -> anyhow::Result<Option<FeatureCollection>> {
+        Ok(None)
+    }
+
+    /// Reproject the geometries of up to `limit` more features of a
+    /// collection to another storage crs, next to the stored ones, returns
+    /// the number of reprojected features and of all features
+    async fn reproject_features(
+        &self,
+        collection: &str,
+        crs: &Crs,
+        limit: usize,
+    ) -> anyhow::Result<(u64, u64)> {
+        let _ = (collection, crs, limit);
+        anyhow::bail!("Reprojection is not supported")
+    }
+
+    /// Replace the stored geometries of a collection by the reprojected ones,
+    /// including those of features written meanwhile, and declare the storage
+    /// crs at once
+    async fn finish_reprojection(&self, collection: &str, crs: &Crs) -> anyhow::Result<()> {
+        let _ = (collection, crs);
+        anyhow::bail!("Reprojection is not supported")
+    }
+
+    /// Violations of the rules by features to be written, checked against
+    /// each other and the stored features of the collection
+    async fn validate_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+        rules: &[ValidationRule],
+    ) -> anyhow::Result<Vec<Violation>> {
+        let _ = (collection, features, crs, rules);
+        anyhow::bail!("Validation rules are not supported")
+    }
+
+    /// Violations of the rules by the stored features of a collection
+    async fn validate_collection(
+        &self,
+        collection: &str,
+        rules: &[ValidationRule],
+    ) -> anyhow::Result<Vec<Violation>> {
+        let _ = (collection, rules);
+        anyhow::bail!("Validation rules are not supported")
+    }
+}
+
+/// Changes of a collection from a complete source, e.g. a harvest
+#[derive(Debug, Clone, Default)]
+pub struct Delta {
+    /// New and changed features with the checksums of their content
+    pub upserts: Vec<(Feature, String)>,
+    /// Ids of the features missing from the source
+    pub deletes: Vec<String>,
+}
+
+/// Planner estimate of a feature query
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QueryPlan {
+    /// Estimated total cost, in units of the query planner
+    pub cost: f64,
+    /// Whether a table is scanned sequentially, i.e. without index
+    pub full_scan: bool,
+}
+
+/// Trait for subscribing to `Feature` changes
+#[async_trait::async_trait]
+pub trait FeatureChanges: Send + Sync {
+    /// Stream of changes to the features of all collections, starting now
+    async fn subscribe_all(
+        &self,
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<FeatureChange>>>;
+
+    /// Stream of changes to the features of a collection, starting now
+    async fn subscribe(
+        &self,
+        collection: &str,
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<FeatureChange>>> {
+        let collection = collection.to_owned();
+
+        let changes = self.subscribe_all().await?.filter(move |change| {
+            let other = matches!(change, Ok(change) if change.collection != collection);
+            futures::future::ready(!other)
+        });
+
+        Ok(changes.boxed())
+    }
+
+    /// Logged changes to the features of a collection after a token, in
+    /// order, with the token following them, `None` if changes are not logged
+    ///
+    /// Without token no changes are listed, the token marks the current end of
+    /// the log to replicate the changes after a full copy from. Tokens are
+    /// positions `{transaction}-{sequence}`.
+    async fn changes_since(
+        &self,
+        _collection: &str,
+        _since: Option<&str>,
+        _limit: usize,
+    ) -> anyhow::Result<Option<(Vec<FeatureChange>, String)>> {
+        Ok(None)
+    }
+
+    /// Logged changes to the features of a collection before a time, newest
+    /// first, `None` if changes are not logged
+    ///
+    /// With the `position` of a change made at `before`, the changes made at
+    /// the same time after it in this order are listed as well, to page
+    /// through changes sharing their time.
+    async fn recent_changes(
+        &self,
+        _collection: &str,
+        _before: Option<DateTime<Utc>>,
+        _position: Option<&str>,
+        _limit: usize,
+    ) -> anyhow::Result<Option<Vec<RecentChange>>> {
+        Ok(None)
+    }
+
+    /// Token up to which a collection was replicated from a source
+    async fn sync_token(&self, _source: &str, _collection: &str) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Store the token up to which a collection was replicated from a source
+    async fn set_sync_token(
+        &self,
+        _source: &str,
+        _collection: &str,
+        _token: &str,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("Replication is not supported by the driver")
+    }
 }
 
 /// Trait for `STAC` search
@@ -75,11 +420,103 @@ pub trait JobHandler: Send + Sync {
 
     async fn status(&self, id: &str) -> anyhow::Result<Option<StatusInfo>>;
 
+    async fn update(&self, job: &StatusInfo) -> anyhow::Result<()>;
+
+    /// Store the results of a finished job
+    async fn set_results(&self, id: &str, results: &Results) -> anyhow::Result<()>;
+
     async fn dismiss(&self, id: &str) -> anyhow::Result<Option<StatusInfo>>;
 
+    /// Jobs matching the query, latest first
+    async fn list_jobs(&self, query: &JobQuery) -> anyhow::Result<Vec<StatusInfo>>;
+
     async fn results(&self, id: &str) -> anyhow::Result<Option<Results>>;
 }
 
+/// Trait for a queue of jobs shared by the replicas of a service
+///
+/// A claimed job is leased to its worker, which extends the lease while it
+/// runs the job. Jobs whose lease expired, e.g. as their worker died, are
+/// claimed again.
+#[async_trait::async_trait]
+pub trait JobQueue: Send + Sync {
+    /// Queue a registered job with the inputs of its execution and the
+    /// metered api key it was submitted with, if any
+    async fn enqueue(
+        &self,
+        job_id: &str,
+        process_id: &str,
+        inputs: &serde_json::Value,
+        key: Option<&str>,
+    ) -> anyhow::Result<()>;
+
+    /// Claim the next job that is accepted or orphaned, leased to `worker`
+    /// for the duration of `lease`
+    async fn claim_job(&self, worker: &str, lease: Duration) -> anyhow::Result<Option<QueuedJob>>;
+
+    /// Extend the lease of a claimed job, `false` if the worker lost it
+    async fn heartbeat(&self, job_id: &str, worker: &str, lease: Duration) -> anyhow::Result<bool>;
+
+    /// Remove a finished job from the queue
+    async fn complete_job(&self, job_id: &str) -> anyhow::Result<()>;
+}
+
+/// Trait for roles held by a single replica of a service at a time
+#[async_trait::async_trait]
+pub trait Leases: Send + Sync {
+    /// Claim or renew a role for `holder` for the duration of `lease`, which
+    /// fails while another holder has an unexpired lease
+    async fn lead(&self, role: &str, holder: &str, lease: Duration) -> anyhow::Result<bool>;
+}
+
+/// Trait for the availability mode of a service, shared by its replicas
+#[async_trait::async_trait]
+pub trait ModeTransactions: Send + Sync {
+    /// Mode the service was last switched to, `None` if it never was
+    async fn read_mode(&self) -> anyhow::Result<Option<String>>;
+
+    async fn write_mode(&self, mode: &str) -> anyhow::Result<()>;
+}
+
+/// Job claimed from a [`JobQueue`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedJob {
+    pub job_id: String,
+    pub process_id: String,
+    pub inputs: serde_json::Value,
+    /// Number of claims of the job, including this one
+    pub attempts: u32,
+    /// Metered api key the job was submitted with
+    pub key: Option<String>,
+}
+
+/// Trait for `Joins` files and transactions
+#[async_trait::async_trait]
+pub trait JoinTransactions: Send + Sync {
+    async fn create_file(
+        &self,
+        file: &DataFile,
+        rows: &[serde_json::Map<String, serde_json::Value>],
+    ) -> anyhow::Result<String>;
+
+    async fn read_file(&self, id: &str) -> anyhow::Result<Option<DataFile>>;
+
+    async fn delete_file(&self, id: &str) -> anyhow::Result<()>;
+
+    async fn list_files(&self) -> anyhow::Result<Vec<DataFile>>;
+
+    async fn create_join(&self, join: &Join) -> anyhow::Result<String>;
+
+    async fn read_join(&self, id: &str) -> anyhow::Result<Option<Join>>;
+
+    async fn delete_join(&self, id: &str) -> anyhow::Result<()>;
+
+    async fn list_joins(&self) -> anyhow::Result<Vec<Join>>;
+
+    /// Executes the join, populating the target collection
+    async fn execute_join(&self, join: &Join) -> anyhow::Result<()>;
+}
+
 /// Trait for `Style` transactions
 #[async_trait::async_trait]
 pub trait StyleTransactions: Send + Sync {
@@ -88,9 +525,29 @@ pub trait StyleTransactions: Send + Sync {
     async fn read_style(&self, id: &str) -> anyhow::Result<Option<serde_json::Value>>;
 }
 
+/// Trait for reading rasters, e.g. for coverages, maps and raster tiles
+#[async_trait::async_trait]
+pub trait RasterSource: Send + Sync {
+    /// Grid of the raster of a collection, `None` if it has none
+    async fn grid(&self, collection: &str) -> anyhow::Result<Option<Grid>>;
+
+    /// Read a window of the raster of a collection, resampled to the
+    /// requested size from the closest overview
+    async fn read(&self, collection: &str, request: &RasterRequest) -> anyhow::Result<Raster>;
+
+    /// Encode a raster, e.g. as Cloud Optimized GeoTIFF
+    /// (`image/tiff; application=geotiff; profile=cloud-optimized`) or
+    /// `image/png`
+    async fn encode(&self, raster: &Raster, media_type: &str) -> anyhow::Result<Vec<u8>>;
+}
+
 /// Trait for `Tile` transacions
 #[async_trait::async_trait]
 pub trait TileTransactions: Send + Sync {
+    /// Renders a vector tile of the collections, leaving out the properties
+    /// redacted for the requesting `user`, with a layer of label points per
+    /// collection if `labels` is set
+    #[allow(clippy::too_many_arguments)]
     async fn tile(
         &self,
         collections: &str,
@@ -98,5 +555,316 @@ pub trait TileTransactions: Send + Sync {
         matrix: &str,
         row: u32,
         col: u32,
+        user: Option<&str>,
+        labels: bool,
     ) -> anyhow::Result<Vec<u8>>;
+
+    /// Renders a vector tile of the point features of the collections
+    /// aggregated into cells, with the `count` and the `avg` of the parameter
+    /// per cell, `None` if not supported
+    async fn aggregated_tile(
+        &self,
+        _collections: &str,
+        _tms: &TileMatrixSet,
+        _matrix: &str,
+        _row: u32,
+        _col: u32,
+        _aggregation: &Aggregation,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Refresh the simplified geometries tiles of low zoom levels are
+    /// rendered from, a no-op for drivers rendering the full geometries
+    async fn generalize(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Add counts of tile requests to the recorded usage, a no-op for
+    /// drivers not keeping it
+    async fn record_usage(&self, _usage: &[TileUsage]) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Recorded usage of the tiles, of a single collection if given
+    async fn tile_usage(&self, _collection: Option<&str>) -> anyhow::Result<Vec<TileUsage>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Trait for `User` and `ApiKey` management
+#[async_trait::async_trait]
+pub trait UserTransactions: Send + Sync {
+    async fn create_user(&self, id: &str) -> anyhow::Result<String>;
+
+    async fn delete_user(&self, id: &str) -> anyhow::Result<()>;
+
+    async fn list_users(&self) -> anyhow::Result<Vec<User>>;
+
+    /// Create an api key for a user, the plain key is only returned here
+    async fn create_key(&self, user: &str) -> anyhow::Result<ApiKey>;
+
+    async fn delete_key(&self, id: &str) -> anyhow::Result<()>;
+
+    async fn list_keys(&self, user: Option<&str>) -> anyhow::Result<Vec<ApiKey>>;
+
+    /// Look up the user an api key belongs to
+    async fn verify_key(&self, key: &str) -> anyhow::Result<Option<String>>;
+}
+
+/// Trait for the metered usage and the quotas of api keys
+#[async_trait::async_trait]
+pub trait QuotaTransactions: Send + Sync {
+    /// Add usage to the recorded usage of the keys in its month
+    async fn record_key_usage(&self, usage: &[KeyUsage]) -> anyhow::Result<()>;
+
+    /// Recorded usage of the keys, of a user or month if given, latest month
+    /// first
+    async fn key_usage(
+        &self,
+        user: Option<&str>,
+        month: Option<&str>,
+    ) -> anyhow::Result<Vec<KeyUsage>>;
+
+    /// Set the quota of a key, replacing an existing one
+    async fn set_quota(&self, quota: &Quota) -> anyhow::Result<()>;
+
+    async fn delete_quota(&self, key: &str) -> anyhow::Result<()>;
+
+    /// Quotas of the keys, of a user if given
+    async fn list_quotas(&self, user: Option<&str>) -> anyhow::Result<Vec<Quota>>;
+}
+
+/// Trait for feature level access filters of collections
+#[async_trait::async_trait]
+pub trait AccessFilterTransactions: Send + Sync {
+    /// Set the filter of a user, or of requests without api key, replacing
+    /// an existing one
+    async fn set_access_filter(&self, filter: &AccessFilter) -> anyhow::Result<()>;
+
+    async fn delete_access_filter(
+        &self,
+        collection: &str,
+        user: Option<&str>,
+    ) -> anyhow::Result<()>;
+
+    async fn list_access_filters(
+        &self,
+        collection: Option<&str>,
+    ) -> anyhow::Result<Vec<AccessFilter>>;
+
+    /// Filter restricting the features of a collection for a user, or for
+    /// requests without api key
+    async fn access_filter(
+        &self,
+        collection: &str,
+        user: Option<&str>,
+    ) -> anyhow::Result<Option<Expr>>;
+}
+
+/// Trait for `Idempotency-Key` bookkeeping of write requests
+///
+/// A key is claimed before a request is processed and completed with its
+/// response, which is replayed to retries with the same key. Keys of failed
+/// requests are released, so the request may be retried. Keys are scoped,
+/// e.g. to the user of an api key, the same key of different scopes is
+/// unrelated.
+#[async_trait::async_trait]
+pub trait IdempotencyKeys: Send + Sync {
+    /// Claim a key for a request, or look up its earlier use
+    ///
+    /// The request is identified by method and target and its body by a
+    /// fingerprint, a key used for another request is reported with the
+    /// request and fingerprint it was used for. Claims of requests that
+    /// don't complete expire after a short lease.
+    async fn claim_key(
+        &self,
+        scope: &str,
+        key: &str,
+        request: &str,
+        fingerprint: &str,
+    ) -> anyhow::Result<Idempotency>;
+
+    /// Store the response of the request that claimed a key
+    async fn complete_key(
+        &self,
+        scope: &str,
+        key: &str,
+        response: &StoredResponse,
+    ) -> anyhow::Result<()>;
+
+    /// Release a key after the request failed
+    async fn release_key(&self, scope: &str, key: &str) -> anyhow::Result<()>;
+}
+
+/// State of an idempotency key
+#[derive(Debug, Clone, PartialEq)]
+pub enum Idempotency {
+    /// First use of the key, the request is to be processed
+    Claimed,
+    /// The key is in use by a request still being processed
+    InProgress {
+        request: String,
+        fingerprint: String,
+    },
+    /// The key was used by a completed request
+    Completed {
+        request: String,
+        fingerprint: String,
+        response: StoredResponse,
+    },
+}
+
+/// Response of a completed request, as replayed for retries
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Trait for `Webhook` registrations and their delivery log
+#[async_trait::async_trait]
+pub trait WebhookTransactions: Send + Sync {
+    async fn create_webhook(&self, webhook: &Webhook) -> anyhow::Result<String>;
+
+    /// Read a webhook, including its secret
+    async fn read_webhook(&self, id: &str) -> anyhow::Result<Option<Webhook>>;
+
+    async fn delete_webhook(&self, id: &str) -> anyhow::Result<()>;
+
+    async fn list_webhooks(&self) -> anyhow::Result<Vec<Webhook>>;
+
+    /// Log a delivery attempt, older entries may be discarded
+    async fn log_delivery(&self, delivery: &Delivery) -> anyhow::Result<()>;
+
+    /// Logged deliveries of a webhook, latest first
+    async fn list_deliveries(&self, webhook: &str) -> anyhow::Result<Vec<Delivery>>;
+}
+
+/// Trait for saved `View`s of the features of collections
+#[async_trait::async_trait]
+pub trait ViewTransactions: Send + Sync {
+    async fn create_view(&self, view: &View) -> anyhow::Result<String>;
+
+    async fn read_view(&self, id: &str) -> anyhow::Result<Option<View>>;
+
+    /// Replace the definition of a view, keeping its owner
+    async fn update_view(&self, view: &View) -> anyhow::Result<()>;
+
+    async fn delete_view(&self, id: &str) -> anyhow::Result<()>;
+
+    /// Views saved by a user, or without api key for `None`
+    async fn list_views(&self, owner: Option<&str>) -> anyhow::Result<Vec<View>>;
+}
+
+/// Trait for scheduled `Harvest` configurations
+#[async_trait::async_trait]
+pub trait HarvestTransactions: Send + Sync {
+    async fn create_harvest(&self, harvest: &Harvest) -> anyhow::Result<String>;
+
+    async fn read_harvest(&self, id: &str) -> anyhow::Result<Option<Harvest>>;
+
+    /// Replace the configuration of a harvest, keeping the time of its latest
+    /// run
+    async fn update_harvest(&self, harvest: &Harvest) -> anyhow::Result<()>;
+
+    async fn delete_harvest(&self, id: &str) -> anyhow::Result<()>;
+
+    async fn list_harvests(&self) -> anyhow::Result<Vec<Harvest>>;
+
+    /// Claim the run of a harvest scheduled at `time`, which fails if the run
+    /// or a later one was claimed already, e.g. by another instance
+    async fn claim_run(&self, id: &str, time: DateTime<Utc>) -> anyhow::Result<bool>;
+}
+
+/// Trait for the files attached to features, the content of an attachment is
+/// kept in object storage
+#[async_trait::async_trait]
+pub trait AttachmentTransactions: Send + Sync {
+    async fn create_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        attachment: &Attachment,
+    ) -> anyhow::Result<String>;
+
+    async fn read_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        id: &str,
+    ) -> anyhow::Result<Option<Attachment>>;
+
+    async fn update_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        attachment: &Attachment,
+    ) -> anyhow::Result<()>;
+
+    async fn delete_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        id: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Attachments of a feature, oldest first
+    async fn list_attachments(
+        &self,
+        collection: &str,
+        feature: &str,
+    ) -> anyhow::Result<Vec<Attachment>>;
+}
+
+/// Outcome of a check of the environment of the service, e.g. of the database
+/// schema or the object store credentials
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// What was checked, e.g. `postgis`
+    pub check: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// How to fix a problem
+    pub hint: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Ok,
+    /// The service runs, with limitations or degraded performance
+    Warning,
+    /// The service or some of its modules fail
+    Error,
+}
+
+impl Diagnostic {
+    pub fn ok(check: &'static str, message: impl ToString) -> Self {
+        Diagnostic {
+            check,
+            severity: Severity::Ok,
+            message: message.to_string(),
+            hint: None,
+        }
+    }
+
+    pub fn warning(check: &'static str, message: impl ToString) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::ok(check, message)
+        }
+    }
+
+    pub fn error(check: &'static str, message: impl ToString) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            ..Diagnostic::ok(check, message)
+        }
+    }
+
+    pub fn hint(mut self, hint: impl ToString) -> Self {
+        self.hint = Some(hint.to_string());
+        self
+    }
 }
@@ -0,0 +1,108 @@
+use chrono::Utc;
+
+use ogcapi_types::features::Attachment;
+
+use crate::AttachmentTransactions;
+
+use super::Mock;
+
+#[async_trait::async_trait]
+impl AttachmentTransactions for Mock {
+    async fn create_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        attachment: &Attachment,
+    ) -> anyhow::Result<String> {
+        self.fault("create_attachment").await?;
+
+        let mut data = self.data.write().unwrap();
+        data.sequence += 1;
+        let id = data.sequence.to_string();
+
+        let attachment = Attachment {
+            id: id.to_owned(),
+            created: Some(Utc::now()),
+            links: Default::default(),
+            ..attachment.to_owned()
+        };
+        data.attachments
+            .entry((collection.to_owned(), feature.to_owned()))
+            .or_default()
+            .push(attachment);
+
+        Ok(id)
+    }
+
+    async fn read_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        id: &str,
+    ) -> anyhow::Result<Option<Attachment>> {
+        self.fault("read_attachment").await?;
+
+        let data = self.data.read().unwrap();
+        Ok(data
+            .attachments
+            .get(&(collection.to_owned(), feature.to_owned()))
+            .and_then(|attachments| attachments.iter().find(|a| a.id == id))
+            .cloned())
+    }
+
+    async fn update_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        attachment: &Attachment,
+    ) -> anyhow::Result<()> {
+        self.fault("update_attachment").await?;
+
+        let mut data = self.data.write().unwrap();
+        if let Some(existing) = data
+            .attachments
+            .get_mut(&(collection.to_owned(), feature.to_owned()))
+            .and_then(|attachments| attachments.iter_mut().find(|a| a.id == attachment.id))
+        {
+            existing.title = attachment.title.to_owned();
+            existing.r#type = attachment.r#type.to_owned();
+            existing.length = attachment.length;
+        }
+
+        Ok(())
+    }
+
+    async fn delete_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        id: &str,
+    ) -> anyhow::Result<()> {
+        self.fault("delete_attachment").await?;
+
+        let mut data = self.data.write().unwrap();
+        if let Some(attachments) = data
+            .attachments
+            .get_mut(&(collection.to_owned(), feature.to_owned()))
+        {
+            attachments.retain(|a| a.id != id);
+        }
+
+        Ok(())
+    }
+
+    async fn list_attachments(
+        &self,
+        collection: &str,
+        feature: &str,
+    ) -> anyhow::Result<Vec<Attachment>> {
+        self.fault("list_attachments").await?;
+
+        let data = self.data.read().unwrap();
+        Ok(data
+            .attachments
+            .get(&(collection.to_owned(), feature.to_owned()))
+            .cloned()
+            .unwrap_or_default())
+    }
+}
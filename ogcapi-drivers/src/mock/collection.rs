@@ -0,0 +1,121 @@
+use anyhow::bail;
+
+use ogcapi_types::{
+    common::{Catalog, Collection, Collections, Query},
+    features::Queryables,
+};
+
+use crate::CollectionTransactions;
+
+use super::Mock;
+
+#[async_trait::async_trait]
+impl CollectionTransactions for Mock {
+    async fn create_collection(&self, collection: &Collection) -> anyhow::Result<String> {
+        self.fault("create_collection").await?;
+
+        let mut data = self.data.write().unwrap();
+        if data.collections.contains_key(&collection.id) {
+            bail!("Collection `{}` already exists", collection.id);
+        }
+        data.features.entry(collection.id.to_owned()).or_default();
+        data.collections
+            .insert(collection.id.to_owned(), collection.to_owned());
+
+        Ok(collection.id.to_owned())
+    }
+
+    async fn read_collection(&self, id: &str) -> anyhow::Result<Option<Collection>> {
+        self.fault("read_collection").await?;
+
+        let data = self.data.read().unwrap();
+        Ok(data.collections.get(id).cloned())
+    }
+
+    async fn update_collection(&self, collection: &Collection) -> anyhow::Result<()> {
+        self.fault("update_collection").await?;
+
+        let mut data = self.data.write().unwrap();
+        match data.collections.get_mut(&collection.id) {
+            Some(existing) => *existing = collection.to_owned(),
+            None => bail!("Unknown collection `{}`", collection.id),
+        }
+
+        Ok(())
+    }
+
+    async fn delete_collection(&self, id: &str) -> anyhow::Result<()> {
+        self.fault("delete_collection").await?;
+
+        let mut data = self.data.write().unwrap();
+        data.collections.remove(id);
+        data.features.remove(id);
+        data.queryables.remove(id);
+
+        Ok(())
+    }
+
+    async fn list_collections(&self, query: &Query) -> anyhow::Result<Collections> {
+        self.fault("list_collections").await?;
+
+        let data = self.data.read().unwrap();
+
+        let matching = data
+            .collections
+            .values()
+            .filter(|collection| query.matches(collection))
+            .cloned()
+            .collect();
+
+        Ok(Collections::paginated(matching, query))
+    }
+
+    async fn read_queryables(&self, id: &str) -> anyhow::Result<Option<Queryables>> {
+        self.fault("read_queryables").await?;
+
+        let data = self.data.read().unwrap();
+        Ok(data.queryables.get(id).cloned())
+    }
+
+    async fn update_queryables(&self, id: &str, queryables: &Queryables) -> anyhow::Result<()> {
+        self.fault("update_queryables").await?;
+
+        let mut data = self.data.write().unwrap();
+        data.queryables.insert(id.to_owned(), queryables.to_owned());
+
+        Ok(())
+    }
+
+    async fn upsert_catalog(&self, catalog: &Catalog) -> anyhow::Result<()> {
+        self.fault("upsert_catalog").await?;
+
+        let mut data = self.data.write().unwrap();
+        data.catalogs
+            .insert(catalog.id.to_owned(), catalog.to_owned());
+
+        Ok(())
+    }
+
+    async fn read_catalog(&self, id: &str) -> anyhow::Result<Option<Catalog>> {
+        self.fault("read_catalog").await?;
+
+        let data = self.data.read().unwrap();
+        Ok(data.catalogs.get(id).cloned())
+    }
+
+    async fn delete_catalog(&self, id: &str) -> anyhow::Result<()> {
+        self.fault("delete_catalog").await?;
+
+        let mut data = self.data.write().unwrap();
+        data.catalogs.remove(id);
+
+        Ok(())
+    }
+
+    async fn list_catalogs(&self) -> anyhow::Result<Vec<Catalog>> {
+        self.fault("list_catalogs").await?;
+
+        let data = self.data.read().unwrap();
+        Ok(data.catalogs.values().cloned().collect())
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::{bail, Context};
+use futures::{stream::BoxStream, StreamExt};
+use serde_json::Value;
+
+use ogcapi_types::{
+    common::Crs,
+    features::{Feature, FeatureChange, FeatureCollection, Query},
+};
+
+use crate::{FeatureChanges, FeatureTransactions};
+
+use super::Mock;
+
+#[async_trait::async_trait]
+impl FeatureTransactions for Mock {
+    async fn create_feature(&self, feature: &Feature) -> anyhow::Result<String> {
+        self.fault("create_feature").await?;
+
+        let collection = feature
+            .collection
+            .to_owned()
+            .context("Feature without collection")?;
+
+        let mut data = self.data.write().unwrap();
+        if !data.collections.contains_key(&collection) {
+            bail!("Unknown collection `{collection}`");
+        }
+
+        Ok(data.insert_feature(&collection, feature.to_owned()))
+    }
+
+    async fn create_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        _crs: &Crs,
+    ) -> anyhow::Result<Vec<String>> {
+        self.fault("create_features").await?;
+
+        let mut data = self.data.write().unwrap();
+        if !data.collections.contains_key(collection) {
+            bail!("Unknown collection `{collection}`");
+        }
+
+        Ok(features
+            .iter()
+            .map(|feature| data.insert_feature(collection, feature.to_owned()))
+            .collect())
+    }
+
+    async fn read_feature(
+        &self,
+        collection: &str,
+        id: &str,
+        _crs: &Crs,
+    ) -> anyhow::Result<Option<Feature>> {
+        self.fault("read_feature").await?;
+
+        let data = self.data.read().unwrap();
+        Ok(data.features.get(collection).and_then(|features| {
+            features
+                .iter()
+                .find(|f| f.id.as_deref() == Some(id))
+                .cloned()
+        }))
+    }
+
+    async fn update_feature(&self, feature: &Feature) -> anyhow::Result<()> {
+        self.fault("update_feature").await?;
+
+        let collection = feature
+            .collection
+            .to_owned()
+            .context("Feature without collection")?;
+
+        let mut data = self.data.write().unwrap();
+        data.insert_feature(&collection, feature.to_owned());
+
+        Ok(())
+    }
+
+    async fn delete_feature(&self, collection: &str, id: &str) -> anyhow::Result<()> {
+        self.fault("delete_feature").await?;
+
+        let mut data = self.data.write().unwrap();
+        if let Some(features) = data.features.get_mut(collection) {
+            features.retain(|f| f.id.as_deref() != Some(id));
+        }
+
+        Ok(())
+    }
+
+    async fn list_items(
+        &self,
+        collection: &str,
+        query: &Query,
+    ) -> anyhow::Result<FeatureCollection> {
+        self.fault("list_items").await?;
+
+        let (features, number_matched) = self.select(collection, query);
+
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = Some(number_matched as u64);
+
+        Ok(fc)
+    }
+
+    fn stream_items(
+        &self,
+        collection: &str,
+        query: &Query,
+    ) -> BoxStream<'static, anyhow::Result<Feature>> {
+        let (mock, collection, query) = (self.clone(), collection.to_owned(), query.to_owned());
+
+        futures::stream::once(async move {
+            mock.fault("stream_items").await?;
+            Ok(mock.select(&collection, &query).0)
+        })
+        .flat_map(|features: anyhow::Result<Vec<Feature>>| match features {
+            Ok(features) => futures::stream::iter(features.into_iter().map(Ok)).boxed(),
+            Err(e) => futures::stream::once(async { Err(e) }).boxed(),
+        })
+        .boxed()
+    }
+}
+
+/// Changes are not notified, canned data is changed by tests directly
+#[async_trait::async_trait]
+impl FeatureChanges for Mock {
+    async fn subscribe_all(
+        &self,
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<FeatureChange>>> {
+        self.fault("subscribe_all").await?;
+
+        Ok(futures::stream::pending().boxed())
+    }
+}
+
+impl Mock {
+    /// Page of the features matching the property filters of a query, and
+    /// the number of matching features
+    fn select(&self, collection: &str, query: &Query) -> (Vec<Feature>, usize) {
+        let data = self.data.read().unwrap();
+
+        let mut matching: Vec<&Feature> = data
+            .features
+            .get(collection)
+            .into_iter()
+            .flatten()
+            .filter(|feature| {
+                query.additional_parameters.iter().all(|(key, value)| {
+                    match feature.properties.as_ref().and_then(|p| p.get(key)) {
+                        Some(Value::String(s)) => s == value,
+                        Some(other) => {
+                            serde_json::from_str::<Value>(value).is_ok_and(|v| v == *other)
+                        }
+                        None => false,
+                    }
+                })
+            })
+            .collect();
+
+        if let Some(ids) = query.ids.as_ref() {
+            matching.retain(|feature| feature.id.as_ref().is_some_and(|id| ids.contains(id)));
+            matching
+                .sort_by_key(|feature| ids.iter().position(|id| feature.id.as_ref() == Some(id)));
+        }
+
+        let features = matching
+            .iter()
+            .skip(query.offset.unwrap_or_default())
+            .take(query.limit.unwrap_or(usize::MAX))
+            .map(|feature| (*feature).to_owned())
+            .collect();
+
+        (features, matching.len())
+    }
+}
@@ -0,0 +1,84 @@
+use ogcapi_types::processes::{JobQuery, Results, StatusCode, StatusInfo};
+
+use crate::JobHandler;
+
+use super::Mock;
+
+#[async_trait::async_trait]
+impl JobHandler for Mock {
+    async fn register(&self, job: &StatusInfo) -> anyhow::Result<String> {
+        self.fault("register").await?;
+
+        let mut data = self.data.write().unwrap();
+        data.jobs.insert(job.job_id.to_owned(), job.to_owned());
+
+        Ok(job.job_id.to_owned())
+    }
+
+    async fn status(&self, id: &str) -> anyhow::Result<Option<StatusInfo>> {
+        self.fault("status").await?;
+
+        let data = self.data.read().unwrap();
+        Ok(data.jobs.get(id).cloned())
+    }
+
+    async fn update(&self, job: &StatusInfo) -> anyhow::Result<()> {
+        self.fault("update").await?;
+
+        let mut data = self.data.write().unwrap();
+        data.jobs.insert(job.job_id.to_owned(), job.to_owned());
+
+        Ok(())
+    }
+
+    async fn set_results(&self, id: &str, results: &Results) -> anyhow::Result<()> {
+        self.fault("set_results").await?;
+
+        let results = serde_json::to_value(results)?;
+
+        let mut data = self.data.write().unwrap();
+        data.results.insert(id.to_owned(), results);
+
+        Ok(())
+    }
+
+    async fn dismiss(&self, id: &str) -> anyhow::Result<Option<StatusInfo>> {
+        self.fault("dismiss").await?;
+
+        let mut data = self.data.write().unwrap();
+        Ok(data.jobs.get_mut(id).and_then(|job| {
+            matches!(job.status, StatusCode::Accepted | StatusCode::Running).then(|| {
+                job.status = StatusCode::Dismissed;
+                job.message = Some("Job dismissed".to_string());
+                job.to_owned()
+            })
+        }))
+    }
+
+    async fn list_jobs(&self, query: &JobQuery) -> anyhow::Result<Vec<StatusInfo>> {
+        self.fault("list_jobs").await?;
+
+        let data = self.data.read().unwrap();
+        let mut jobs: Vec<StatusInfo> = data
+            .jobs
+            .values()
+            .filter(|job| query.process_id.is_none() || job.process_id == query.process_id)
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.created));
+
+        Ok(jobs
+            .into_iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    async fn results(&self, id: &str) -> anyhow::Result<Option<Results>> {
+        self.fault("results").await?;
+
+        let results = self.data.read().unwrap().results.get(id).cloned();
+
+        Ok(results.map(serde_json::from_value).transpose()?)
+    }
+}
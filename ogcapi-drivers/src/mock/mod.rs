@@ -0,0 +1,240 @@
+//! Scriptable in-memory driver for tests
+//!
+//! Serves canned collections, features and jobs from memory, and injects
+//! scripted faults into its operations: delays, lost connections, data that
+//! fails to deserialize or arbitrary errors. Clients and custom processors
+//! can thereby be tested against the failure modes of real backends.
+//!
+//! Queries are only evaluated partially, `limit`, `offset` and property
+//! filters (additional parameters) are applied, geometries are returned as
+//! stored regardless of the requested crs.
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use ogcapi_drivers::mock::{Fault, Mock};
+//!
+//! let mock = Mock::new();
+//!
+//! // listing items is slow, and fails once
+//! mock.inject(Fault::delay(Duration::from_millis(500)).on("list_items"));
+//! mock.inject(Fault::connection_lost().on("list_items").times(1));
+//! ```
+
+mod access;
+mod attachment;
+mod collection;
+mod feature;
+mod job;
+mod mode;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use ogcapi_types::{
+    common::{Catalog, Collection},
+    features::{Attachment, Feature, Queryables},
+    processes::StatusInfo,
+};
+
+/// In-memory driver with fault injection, clones share their data and faults
+#[derive(Clone, Default)]
+pub struct Mock {
+    pub(crate) data: Arc<RwLock<Data>>,
+    faults: Arc<Mutex<Vec<Fault>>>,
+}
+
+#[derive(Default)]
+pub(crate) struct Data {
+    pub(crate) collections: BTreeMap<String, Collection>,
+    pub(crate) catalogs: BTreeMap<String, Catalog>,
+    pub(crate) queryables: HashMap<String, Queryables>,
+    /// Features by collection, in insertion order
+    pub(crate) features: HashMap<String, Vec<Feature>>,
+    /// Attachments by collection and feature, in insertion order
+    pub(crate) attachments: HashMap<(String, String), Vec<Attachment>>,
+    pub(crate) jobs: BTreeMap<String, StatusInfo>,
+    /// Results of finished jobs, as JSON
+    pub(crate) results: HashMap<String, serde_json::Value>,
+    /// Counter of generated ids
+    pub(crate) sequence: u64,
+    /// Mode of the service
+    pub(crate) mode: Option<String>,
+}
+
+/// Failure injected into the operations of the mock driver
+#[derive(Debug, Clone)]
+pub enum FaultKind {
+    /// Respond after a delay, the operation itself succeeds
+    Delay(Duration),
+    /// Fail as if the connection to the backend was reset
+    ConnectionLost,
+    /// Fail as if stored data could not be deserialized
+    Serialization,
+    /// Fail with a message
+    Error(String),
+}
+
+/// Fault of an operation, e.g. `list_items`, or of all operations
+#[derive(Debug, Clone)]
+pub struct Fault {
+    kind: FaultKind,
+    operation: Option<String>,
+    /// Number of remaining injections, unlimited if `None`
+    remaining: Option<usize>,
+}
+
+impl Fault {
+    pub fn new(kind: FaultKind) -> Self {
+        Fault {
+            kind,
+            operation: None,
+            remaining: None,
+        }
+    }
+
+    pub fn delay(delay: Duration) -> Self {
+        Fault::new(FaultKind::Delay(delay))
+    }
+
+    pub fn connection_lost() -> Self {
+        Fault::new(FaultKind::ConnectionLost)
+    }
+
+    pub fn serialization() -> Self {
+        Fault::new(FaultKind::Serialization)
+    }
+
+    pub fn error(message: impl ToString) -> Self {
+        Fault::new(FaultKind::Error(message.to_string()))
+    }
+
+    /// Only inject into an operation, named after the trait method
+    pub fn on(mut self, operation: impl ToString) -> Self {
+        self.operation = Some(operation.to_string());
+        self
+    }
+
+    /// Only inject the next `n` times
+    pub fn times(mut self, n: usize) -> Self {
+        self.remaining = Some(n);
+        self
+    }
+}
+
+impl Mock {
+    pub fn new() -> Self {
+        Mock::default()
+    }
+
+    /// Add a canned collection
+    pub fn with_collection(self, collection: Collection) -> Self {
+        {
+            let mut data = self.data.write().unwrap();
+            data.features.entry(collection.id.to_owned()).or_default();
+            data.collections
+                .insert(collection.id.to_owned(), collection);
+        }
+        self
+    }
+
+    /// Add canned features to a collection, ids are generated for features
+    /// without
+    pub fn with_features(
+        self,
+        collection: &str,
+        features: impl IntoIterator<Item = Feature>,
+    ) -> Self {
+        {
+            let mut data = self.data.write().unwrap();
+            for feature in features {
+                data.insert_feature(collection, feature);
+            }
+        }
+        self
+    }
+
+    /// Script a fault, faults are applied in the order they were injected
+    pub fn inject(&self, fault: Fault) {
+        self.faults.lock().unwrap().push(fault);
+    }
+
+    /// Remove all scripted faults
+    pub fn clear_faults(&self) {
+        self.faults.lock().unwrap().clear();
+    }
+
+    /// Apply the faults scripted for an operation, delaying it or failing
+    pub(crate) async fn fault(&self, operation: &str) -> anyhow::Result<()> {
+        let kinds: Vec<FaultKind> = {
+            let mut faults = self.faults.lock().unwrap();
+
+            let mut kinds = Vec::new();
+            for fault in faults.iter_mut().filter(|fault| {
+                fault.operation.as_deref().is_none_or(|o| o == operation)
+                    && fault.remaining != Some(0)
+            }) {
+                if let Some(remaining) = fault.remaining.as_mut() {
+                    *remaining -= 1;
+                }
+                kinds.push(fault.kind.to_owned());
+
+                // later faults are not reached once the operation failed
+                if !matches!(fault.kind, FaultKind::Delay(_)) {
+                    break;
+                }
+            }
+            faults.retain(|fault| fault.remaining != Some(0));
+
+            kinds
+        };
+
+        for kind in kinds {
+            match kind {
+                FaultKind::Delay(delay) => tokio::time::sleep(delay).await,
+                FaultKind::ConnectionLost => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionReset,
+                        format!("Connection lost during `{operation}`"),
+                    )
+                    .into())
+                }
+                FaultKind::Serialization => {
+                    let e = serde_json::from_str::<serde_json::Value>("{\"truncated\":")
+                        .expect_err("invalid JSON");
+                    return Err(anyhow::Error::new(e)
+                        .context(format!("Failed to deserialize data of `{operation}`")));
+                }
+                FaultKind::Error(message) => anyhow::bail!(message),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Data {
+    /// Insert or replace a feature, returning its id
+    pub(crate) fn insert_feature(&mut self, collection: &str, mut feature: Feature) -> String {
+        let id = match feature.id.to_owned() {
+            Some(id) => id,
+            None => {
+                self.sequence += 1;
+                self.sequence.to_string()
+            }
+        };
+        feature.id = Some(id.to_owned());
+        feature.collection = Some(collection.to_owned());
+
+        let features = self.features.entry(collection.to_owned()).or_default();
+        match features.iter_mut().find(|f| f.id.as_ref() == Some(&id)) {
+            Some(existing) => *existing = feature,
+            None => features.push(feature),
+        }
+
+        id
+    }
+}
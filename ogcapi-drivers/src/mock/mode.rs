@@ -0,0 +1,19 @@
+use crate::ModeTransactions;
+
+use super::Mock;
+
+#[async_trait::async_trait]
+impl ModeTransactions for Mock {
+    async fn read_mode(&self) -> anyhow::Result<Option<String>> {
+        self.fault("read_mode").await?;
+
+        Ok(self.data.read().unwrap().mode.to_owned())
+    }
+
+    async fn write_mode(&self, mode: &str) -> anyhow::Result<()> {
+        self.fault("write_mode").await?;
+
+        self.data.write().unwrap().mode = Some(mode.to_owned());
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+//! Writer of `PMTiles` (version 3) archives of vector tiles
+//!
+//! Tiles are appended to the tile data in order of their tile id, the
+//! [`Archive`] keeps their entries to write the header, directories and
+//! metadata preceding the tile data. Tiles and directories are stored
+//! uncompressed.
+
+use serde_json::Value;
+
+/// Length of the header
+const HEADER_LENGTH: usize = 127;
+
+/// Maximum length of header and root directory, read at once by clients
+const ROOT_LENGTH: usize = 16_384;
+
+/// Compression `none`
+const NO_COMPRESSION: u8 = 1;
+
+/// Tile type `mvt`
+const MVT: u8 = 1;
+
+/// Tile id of a tile, numbered along a Hilbert curve per zoom level
+pub fn tile_id(z: u8, x: u32, y: u32) -> u64 {
+    // tiles of the lower zoom levels
+    let mut id = ((1u64 << (2 * u32::from(z))) - 1) / 3;
+
+    let (mut x, mut y) = (u64::from(x), u64::from(y));
+    let mut s = 1u64 << z >> 1;
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        id += s * s * ((3 * rx) ^ ry);
+
+        // rotate the quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - (x & (s - 1));
+                y = s - 1 - (y & (s - 1));
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s >>= 1;
+    }
+
+    id
+}
+
+/// Entry of a directory, of a tile or a leaf directory (`run_length` 0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub tile_id: u64,
+    pub offset: u64,
+    pub length: u32,
+    pub run_length: u32,
+}
+
+/// Archive of vector tiles, bounded by `bounds` in `CRS84`
+#[derive(Debug)]
+pub struct Archive {
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    pub bounds: [f64; 4],
+    /// Metadata, e.g. with the `vector_layers` of the tiles
+    pub metadata: Value,
+    entries: Vec<Entry>,
+    data_length: u64,
+}
+
+impl Archive {
+    pub fn new(min_zoom: u8, max_zoom: u8, bounds: [f64; 4], metadata: Value) -> Self {
+        Archive {
+            min_zoom,
+            max_zoom,
+            bounds,
+            metadata,
+            entries: Vec::new(),
+            data_length: 0,
+        }
+    }
+
+    /// Add a tile of a length appended to the tile data, in order of the ids
+    pub fn add_tile(&mut self, tile_id: u64, length: u32) -> anyhow::Result<()> {
+        if self.entries.last().is_some_and(|e| e.tile_id >= tile_id) {
+            anyhow::bail!("Tile {tile_id} is not added in order");
+        }
+
+        self.entries.push(Entry {
+            tile_id,
+            offset: self.data_length,
+            length,
+            run_length: 1,
+        });
+        self.data_length += u64::from(length);
+
+        Ok(())
+    }
+
+    /// Number of added tiles
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Header, root directory, metadata and leaf directories, to be followed
+    /// by the tile data
+    pub fn head(&self) -> anyhow::Result<Vec<u8>> {
+        let (root, leaves) = self.directories();
+        let metadata = serde_json::to_vec(&self.metadata)?;
+
+        let root_offset = HEADER_LENGTH as u64;
+        let metadata_offset = root_offset + root.len() as u64;
+        let leaves_offset = metadata_offset + metadata.len() as u64;
+        let data_offset = leaves_offset + leaves.len() as u64;
+
+        let e7 = |v: f64| ((v * 1e7).round() as i32).to_le_bytes();
+        let center = [
+            (self.bounds[0] + self.bounds[2]) / 2.0,
+            (self.bounds[1] + self.bounds[3]) / 2.0,
+        ];
+        let tiles = self.entries.len() as u64;
+
+        let mut head = Vec::with_capacity(data_offset as usize);
+        head.extend_from_slice(b"PMTiles");
+        head.push(3);
+        for value in [
+            root_offset,
+            root.len() as u64,
+            metadata_offset,
+            metadata.len() as u64,
+            leaves_offset,
+            leaves.len() as u64,
+            data_offset,
+            self.data_length,
+            // addressed tiles, tile entries and tile contents
+            tiles,
+            tiles,
+            tiles,
+        ] {
+            head.extend_from_slice(&value.to_le_bytes());
+        }
+        // clustered, internal and tile compression, tile type
+        head.extend_from_slice(&[1, NO_COMPRESSION, NO_COMPRESSION, MVT]);
+        head.extend_from_slice(&[self.min_zoom, self.max_zoom]);
+        for v in self.bounds {
+            head.extend_from_slice(&e7(v));
+        }
+        head.push(self.min_zoom);
+        head.extend_from_slice(&e7(center[0]));
+        head.extend_from_slice(&e7(center[1]));
+        debug_assert_eq!(head.len(), HEADER_LENGTH);
+
+        head.extend_from_slice(&root);
+        head.extend_from_slice(&metadata);
+        head.extend_from_slice(&leaves);
+
+        Ok(head)
+    }
+
+    /// Root directory and leaf directories, split into leaves of growing
+    /// size until the root directory fits the first request of a client
+    fn directories(&self) -> (Vec<u8>, Vec<u8>) {
+        let root = serialize(&self.entries);
+        if root.len() <= ROOT_LENGTH - HEADER_LENGTH {
+            return (root, Vec::new());
+        }
+
+        let mut leaf_size = 4096;
+        loop {
+            let mut entries = Vec::new();
+            let mut leaves = Vec::new();
+            for chunk in self.entries.chunks(leaf_size) {
+                let leaf = serialize(chunk);
+                entries.push(Entry {
+                    tile_id: chunk[0].tile_id,
+                    offset: leaves.len() as u64,
+                    length: leaf.len() as u32,
+                    run_length: 0,
+                });
+                leaves.extend(leaf);
+            }
+
+            let root = serialize(&entries);
+            if root.len() <= ROOT_LENGTH - HEADER_LENGTH {
+                return (root, leaves);
+            }
+            leaf_size *= 2;
+        }
+    }
+}
+
+/// Serialize the entries of a directory as columns of varints
+pub fn serialize(entries: &[Entry]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_varint(&mut buf, entries.len() as u64);
+
+    let mut last_id = 0;
+    for entry in entries {
+        write_varint(&mut buf, entry.tile_id - last_id);
+        last_id = entry.tile_id;
+    }
+    for entry in entries {
+        write_varint(&mut buf, u64::from(entry.run_length));
+    }
+    for entry in entries {
+        write_varint(&mut buf, u64::from(entry.length));
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        // 0 for an entry following the previous one
+        let contiguous = i > 0 && {
+            let previous = entries[i - 1];
+            entry.offset == previous.offset + u64::from(previous.length)
+        };
+        write_varint(&mut buf, if contiguous { 0 } else { entry.offset + 1 });
+    }
+
+    buf
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
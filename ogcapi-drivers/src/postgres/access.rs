@@ -0,0 +1,91 @@
+use ogcapi_types::{auth::AccessFilter, cql2::Expr};
+
+use crate::AccessFilterTransactions;
+
+use super::{cql2, Db};
+
+#[async_trait::async_trait]
+impl AccessFilterTransactions for Db {
+    async fn set_access_filter(&self, filter: &AccessFilter) -> anyhow::Result<()> {
+        // reject filters that can't be applied to queries
+        cql2::to_sql(&filter.filter, 4326)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO meta.access_filters (collection_id, user_id, filter)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (collection_id, COALESCE(user_id, ''))
+            DO UPDATE SET filter = EXCLUDED.filter, created = NOW()
+            "#,
+        )
+        .bind(&filter.collection)
+        .bind(&filter.user)
+        .bind(sqlx::types::Json(&filter.filter))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_access_filter(
+        &self,
+        collection: &str,
+        user: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM meta.access_filters
+            WHERE collection_id = $1 AND user_id IS NOT DISTINCT FROM $2
+            "#,
+        )
+        .bind(collection)
+        .bind(user)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_access_filters(
+        &self,
+        collection: Option<&str>,
+    ) -> anyhow::Result<Vec<AccessFilter>> {
+        let filters: Vec<sqlx::types::Json<AccessFilter>> = sqlx::query_scalar(
+            r#"
+            SELECT json_build_object(
+                'collection', collection_id,
+                'user', user_id,
+                'filter', filter,
+                'created', created
+            ) as "filter!"
+            FROM meta.access_filters
+            WHERE $1::text IS NULL OR collection_id = $1
+            ORDER BY collection_id, user_id NULLS FIRST
+            "#,
+        )
+        .bind(collection)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(filters.into_iter().map(|f| f.0).collect())
+    }
+
+    async fn access_filter(
+        &self,
+        collection: &str,
+        user: Option<&str>,
+    ) -> anyhow::Result<Option<Expr>> {
+        let filter: Option<sqlx::types::Json<Expr>> = sqlx::query_scalar(
+            r#"
+            SELECT filter FROM meta.access_filters
+            WHERE collection_id = $1 AND user_id IS NOT DISTINCT FROM $2
+            "#,
+        )
+        .bind(collection)
+        .bind(user)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(filter.map(|f| f.0))
+    }
+}
@@ -0,0 +1,170 @@
+use serde_json::{json, Value};
+use sqlx::types::Json;
+
+use ogcapi_types::features::{AggregateQuery, Feature, FeatureCollection, Grouping};
+
+use crate::CollectionTransactions;
+
+use super::{cql2::quote, Db};
+
+/// Maximum number of cells of the grid covering the features
+const MAX_CELLS: f64 = 1_000_000.0;
+
+impl Db {
+    /// Aggregates of the features per group, with the geometries of the
+    /// groups in `CRS84`, `None` for H3 cells without the `h3_postgis`
+    /// extension
+    pub(crate) async fn aggregate_features(
+        &self,
+        collection: &str,
+        query: &AggregateQuery,
+    ) -> anyhow::Result<Option<FeatureCollection>> {
+        let storage_srid = self.storage_srid(collection).await?;
+
+        let condition = match query.bbox.as_ref() {
+            Some(bbox) => {
+                let [minx, miny, maxx, maxy] = bbox.to_2d();
+                format!(
+                    "geom && ST_Transform(ST_MakeEnvelope({minx}, {miny}, {maxx}, {maxy}, 4326), {storage_srid})"
+                )
+            }
+            None => "TRUE".to_owned(),
+        };
+        let source = format!(
+            r#"
+            SELECT ST_PointOnSurface(geom) AS point, properties
+            FROM items."{collection}"
+            WHERE geom IS NOT NULL AND {condition}
+            "#
+        );
+
+        let aggregates = query
+            .aggregates()
+            .iter()
+            .map(|aggregate| {
+                let expr = match aggregate.property() {
+                    None => "count(*)".to_owned(),
+                    Some(property) => format!(
+                        "{function}((p.properties ->> {property})::float8) FILTER (WHERE jsonb_typeof(p.properties -> {property}) = 'number')",
+                        function = aggregate.function(),
+                        property = quote(property),
+                    ),
+                };
+                format!("{}, {expr}", quote(&aggregate.to_string()))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let sql = match &query.by {
+            Grouping::H3(resolution) => {
+                let installed: bool = sqlx::query_scalar(
+                    "SELECT EXISTS (SELECT FROM pg_extension WHERE extname = 'h3_postgis')",
+                )
+                .fetch_one(&self.pool)
+                .await?;
+                if !installed {
+                    return Ok(None);
+                }
+
+                format!(
+                    r#"
+                    SELECT
+                        cell::text AS id,
+                        ST_AsGeoJSON(h3_cell_to_boundary_geometry(cell))::jsonb AS geometry,
+                        jsonb_build_object({aggregates}) AS properties
+                    FROM (
+                        SELECT h3_lat_lng_to_cell(ST_Transform(point, 4326)::point, {resolution}) AS cell, properties
+                        FROM ({source}) s
+                    ) p
+                    GROUP BY cell
+                    ORDER BY cell
+                    "#
+                )
+            }
+            Grouping::Hexagon(size) | Grouping::Square(size) => {
+                let grid = match query.by {
+                    Grouping::Hexagon(_) => "ST_HexagonGrid",
+                    _ => "ST_SquareGrid",
+                };
+
+                // the grid covers the extent of the features
+                let extent: Option<(f64, f64)> = sqlx::query_as(&format!(
+                    r#"
+                    SELECT ST_XMax(e) - ST_XMin(e), ST_YMax(e) - ST_YMin(e)
+                    FROM (
+                        SELECT ST_Extent(ST_Transform(point, 3857)) AS e FROM ({source}) s
+                    ) t
+                    WHERE e IS NOT NULL
+                    "#
+                ))
+                .fetch_optional(&self.pool)
+                .await?;
+                let Some((width, height)) = extent else {
+                    let mut fc = FeatureCollection::new(Vec::new());
+                    fc.number_matched = Some(0);
+                    return Ok(Some(fc));
+                };
+                if (width / size + 1.0) * (height / size + 1.0) > MAX_CELLS {
+                    anyhow::bail!(
+                        "The grid of cells of size {size} exceeds {MAX_CELLS} cells, choose a larger size or a smaller bbox"
+                    );
+                }
+
+                format!(
+                    r#"
+                    WITH p AS (
+                        SELECT ST_Transform(point, 3857) AS point, properties FROM ({source}) s
+                    )
+                    SELECT
+                        cell.i || '_' || cell.j AS id,
+                        ST_AsGeoJSON(ST_Transform(cell.geom, 4326))::jsonb AS geometry,
+                        jsonb_build_object({aggregates}) AS properties
+                    FROM {grid}({size}, (SELECT ST_SetSRID(ST_Extent(point), 3857) FROM p)) AS cell
+                    JOIN p ON ST_Intersects(cell.geom, p.point)
+                    GROUP BY cell.i, cell.j, cell.geom
+                    ORDER BY cell.i, cell.j
+                    "#
+                )
+            }
+            Grouping::Collection(groups) => {
+                let Some(other) = self.read_collection(groups).await? else {
+                    anyhow::bail!("Unknown collection `{groups}`");
+                };
+                let other_srid = other.storage_crs.unwrap_or_default().as_srid();
+
+                format!(
+                    r#"
+                    SELECT
+                        g.id,
+                        ST_AsGeoJSON(ST_Transform(g.geom, 4326))::jsonb AS geometry,
+                        jsonb_build_object({aggregates}) AS properties
+                    FROM items."{groups}" g
+                    JOIN ({source}) p ON ST_Intersects(g.geom, ST_Transform(p.point, {other_srid}))
+                    GROUP BY g.id, g.geom
+                    ORDER BY g.id
+                    "#
+                )
+            }
+        };
+
+        let rows: Vec<(String, Json<Value>, Json<Value>)> =
+            sqlx::query_as(&sql).fetch_all(&self.pool).await?;
+
+        let features = rows
+            .into_iter()
+            .map(|(id, geometry, properties)| {
+                serde_json::from_value::<Feature>(json!({
+                    "type": "Feature",
+                    "id": id,
+                    "geometry": geometry.0,
+                    "properties": properties.0,
+                }))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = fc.number_returned;
+
+        Ok(Some(fc))
+    }
+}
@@ -0,0 +1,130 @@
+use ogcapi_types::features::Attachment;
+
+use crate::AttachmentTransactions;
+
+use super::Db;
+
+#[async_trait::async_trait]
+impl AttachmentTransactions for Db {
+    async fn create_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        attachment: &Attachment,
+    ) -> anyhow::Result<String> {
+        let id: String = sqlx::query_scalar(
+            r#"
+            INSERT INTO meta.attachments (collection_id, feature_id, title, type, length)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+        )
+        .bind(collection)
+        .bind(feature)
+        .bind(&attachment.title)
+        .bind(&attachment.r#type)
+        .bind(attachment.length)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn read_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        id: &str,
+    ) -> anyhow::Result<Option<Attachment>> {
+        let attachment: Option<sqlx::types::Json<Attachment>> = sqlx::query_scalar(
+            r#"
+            SELECT json_build_object(
+                'id', id,
+                'title', title,
+                'type', type,
+                'length', length,
+                'created', created
+            ) as "attachment!"
+            FROM meta.attachments
+            WHERE collection_id = $1 AND feature_id = $2 AND id = $3
+            "#,
+        )
+        .bind(collection)
+        .bind(feature)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(attachment.map(|a| a.0))
+    }
+
+    async fn update_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        attachment: &Attachment,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE meta.attachments
+            SET title = $4, type = $5, length = $6
+            WHERE collection_id = $1 AND feature_id = $2 AND id = $3
+            "#,
+        )
+        .bind(collection)
+        .bind(feature)
+        .bind(&attachment.id)
+        .bind(&attachment.title)
+        .bind(&attachment.r#type)
+        .bind(attachment.length)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_attachment(
+        &self,
+        collection: &str,
+        feature: &str,
+        id: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "DELETE FROM meta.attachments WHERE collection_id = $1 AND feature_id = $2 AND id = $3",
+        )
+        .bind(collection)
+        .bind(feature)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_attachments(
+        &self,
+        collection: &str,
+        feature: &str,
+    ) -> anyhow::Result<Vec<Attachment>> {
+        let attachments: Vec<sqlx::types::Json<Attachment>> = sqlx::query_scalar(
+            r#"
+            SELECT json_build_object(
+                'id', id,
+                'title', title,
+                'type', type,
+                'length', length,
+                'created', created
+            ) as "attachment!"
+            FROM meta.attachments
+            WHERE collection_id = $1 AND feature_id = $2
+            ORDER BY created, id
+            "#,
+        )
+        .bind(collection)
+        .bind(feature)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(attachments.into_iter().map(|a| a.0).collect())
+    }
+}
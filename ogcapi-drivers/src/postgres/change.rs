@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use futures::{stream::BoxStream, StreamExt};
+use sqlx::{postgres::PgListener, types::Json};
+
+use ogcapi_types::features::{FeatureChange, RecentChange};
+
+use crate::FeatureChanges;
+
+use super::Db;
+
+/// Channel the feature tables notify on, see `meta.notify_item_change()`
+const CHANNEL: &str = "item_changes";
+
+#[async_trait::async_trait]
+impl FeatureChanges for Db {
+    async fn subscribe_all(
+        &self,
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<FeatureChange>>> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(CHANNEL).await?;
+
+        let changes = listener.into_stream().map(|notification| {
+            Ok(serde_json::from_str::<FeatureChange>(
+                notification?.payload(),
+            )?)
+        });
+
+        Ok(changes.boxed())
+    }
+
+    async fn changes_since(
+        &self,
+        collection: &str,
+        since: Option<&str>,
+        limit: usize,
+    ) -> anyhow::Result<Option<(Vec<FeatureChange>, String)>> {
+        // Changes are listed by transaction once it finished, as running ones
+        // may still log changes ordered before the listed ones. Tokens are the
+        // transaction and sequence number of the last listed change.
+        let Some(since) = since else {
+            let horizon: String =
+                sqlx::query_scalar("SELECT pg_snapshot_xmin(pg_current_snapshot())::text")
+                    .fetch_one(&self.pool)
+                    .await?;
+            return Ok(Some((Vec::new(), format!("{horizon}-0"))));
+        };
+
+        let (tx, seq) = since
+            .split_once('-')
+            .and_then(|(tx, seq)| Some((tx.parse::<u64>().ok()?, seq.parse::<i64>().ok()?)))
+            .ok_or_else(|| anyhow::anyhow!("Invalid token `{since}`"))?;
+
+        let rows: Vec<(String, i64, Json<FeatureChange>)> = sqlx::query_as(
+            r#"
+            SELECT tx::text, seq, json_build_object('kind', kind, 'collection', collection_id, 'id', id)
+            FROM meta.item_changes
+            WHERE collection_id = $1
+                AND (tx, seq) > ($2::text::xid8, $3)
+                AND tx < pg_snapshot_xmin(pg_current_snapshot())
+            ORDER BY tx, seq
+            LIMIT $4
+            "#,
+        )
+        .bind(collection)
+        .bind(tx.to_string())
+        .bind(seq)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let token = match rows.last() {
+            Some((tx, seq, _)) => format!("{tx}-{seq}"),
+            None => since.to_owned(),
+        };
+
+        Ok(Some((
+            rows.into_iter().map(|(_, _, change)| change.0).collect(),
+            token,
+        )))
+    }
+
+    async fn recent_changes(
+        &self,
+        collection: &str,
+        before: Option<DateTime<Utc>>,
+        position: Option<&str>,
+        limit: usize,
+    ) -> anyhow::Result<Option<Vec<RecentChange>>> {
+        let position = position
+            .map(|position| {
+                position
+                    .split_once('-')
+                    .and_then(|(tx, seq)| {
+                        Some((
+                            tx.parse::<u64>().ok()?.to_string(),
+                            seq.parse::<i64>().ok()?,
+                        ))
+                    })
+                    .ok_or_else(|| anyhow::anyhow!("Invalid position `{position}`"))
+            })
+            .transpose()?;
+        let (tx, seq) = position.unzip();
+
+        // changes of a transaction share their time, they are told apart by
+        // their position
+        let changes: Vec<Json<RecentChange>> = sqlx::query_scalar(
+            r#"
+            SELECT json_build_object(
+                'kind', kind,
+                'collection', collection_id,
+                'id', id,
+                'changed', changed,
+                'position', tx::text || '-' || seq
+            ) as "change!"
+            FROM meta.item_changes
+            WHERE collection_id = $1
+                AND (
+                    $2::timestamptz IS NULL
+                    OR changed < $2
+                    OR (changed = $2 AND (tx, seq) < ($3::text::xid8, $4::int8))
+                )
+                AND tx < pg_snapshot_xmin(pg_current_snapshot())
+            ORDER BY changed DESC, tx DESC, seq DESC
+            LIMIT $5
+            "#,
+        )
+        .bind(collection)
+        .bind(before)
+        .bind(tx)
+        .bind(seq)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Some(changes.into_iter().map(|c| c.0).collect()))
+    }
+
+    async fn sync_token(&self, source: &str, collection: &str) -> anyhow::Result<Option<String>> {
+        Ok(sqlx::query_scalar(
+            "SELECT token FROM meta.sync_tokens WHERE source = $1 AND collection_id = $2",
+        )
+        .bind(source)
+        .bind(collection)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn set_sync_token(
+        &self,
+        source: &str,
+        collection: &str,
+        token: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO meta.sync_tokens (source, collection_id, token)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (source, collection_id)
+            DO UPDATE SET token = EXCLUDED.token, updated = NOW()
+            "#,
+        )
+        .bind(source)
+        .bind(collection)
+        .bind(token)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use ogcapi_types::common::{Collection, Collections, Query};
+use ogcapi_types::{
+    common::{Bbox, Catalog, Collection, Collections, Query},
+    features::Queryables,
+};
 
 use crate::CollectionTransactions;
 
@@ -18,7 +21,8 @@ impl CollectionTransactions for Db {
                 geom geometry NOT NULL,
                 links jsonb NOT NULL DEFAULT '[]'::jsonb,
                 assets jsonb NOT NULL DEFAULT '{{}}'::jsonb,
-                bbox jsonb
+                bbox jsonb,
+                zrange numrange GENERATED ALWAYS AS (meta.zrange(geom)) STORED
             )
             "#,
             collection.id
@@ -47,6 +51,24 @@ impl CollectionTransactions for Db {
         .execute(&mut *tx)
         .await?;
 
+        sqlx::query(&format!(
+            r#"CREATE INDEX ON items."{}" USING gist (zrange)"#,
+            collection.id
+        ))
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(&format!(
+            r#"
+            CREATE TRIGGER notify_item_change
+            AFTER INSERT OR UPDATE OR DELETE ON items."{}"
+            FOR EACH ROW EXECUTE FUNCTION meta.notify_item_change()
+            "#,
+            collection.id
+        ))
+        .execute(&mut *tx)
+        .await?;
+
         sqlx::query("SELECT UpdateGeometrySRID('items', $1, 'geom', $2)")
             .bind(&collection.id)
             .bind(collection.storage_crs.clone().unwrap_or_default().as_srid())
@@ -92,9 +114,26 @@ impl CollectionTransactions for Db {
     async fn delete_collection(&self, id: &str) -> anyhow::Result<()> {
         let mut tx = self.pool.begin().await?;
 
-        sqlx::query(&format!(r#"DROP TABLE IF EXISTS items."{}""#, id))
-            .execute(&mut *tx)
-            .await?;
+        // joined collections may be backed by a view
+        let table_type: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT table_type FROM information_schema.tables
+            WHERE table_schema = 'items' AND table_name = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if table_type.as_deref() == Some("VIEW") {
+            sqlx::query(&format!(r#"DROP VIEW IF EXISTS items."{}""#, id))
+                .execute(&mut *tx)
+                .await?;
+        } else {
+            sqlx::query(&format!(r#"DROP TABLE IF EXISTS items."{}""#, id))
+                .execute(&mut *tx)
+                .await?;
+        }
 
         sqlx::query("DELETE FROM meta.collections WHERE id = $1")
             .bind(id)
@@ -106,21 +145,125 @@ impl CollectionTransactions for Db {
         Ok(())
     }
 
-    async fn list_collections(&self, _query: &Query) -> anyhow::Result<Collections> {
+    async fn list_collections(&self, query: &Query) -> anyhow::Result<Collections> {
         let collections: Option<sqlx::types::Json<Vec<Collection>>> = sqlx::query_scalar(
             r#"
-            SELECT array_to_json(array_agg(collection))
+            SELECT array_to_json(array_agg(collection ORDER BY id))
             FROM meta.collections
             WHERE collection ->> 'type' = 'Collection'
+            AND (cardinality($1::text[]) = 0 OR EXISTS (
+                SELECT 1 FROM unnest($1::text[]) term
+                WHERE strpos(lower(concat_ws(' ',
+                    collection ->> 'title',
+                    collection ->> 'description',
+                    collection ->> 'keywords'
+                )), term) > 0
+            ))
+            AND (cardinality($2::text[]) = 0 OR EXISTS (
+                SELECT 1
+                FROM jsonb_array_elements_text(COALESCE(collection -> 'keywords', '[]')) keyword
+                WHERE lower(keyword) = ANY($2::text[])
+            ))
+            AND ($3::text IS NULL OR EXISTS (
+                SELECT 1
+                FROM jsonb_array_elements(COALESCE(collection -> 'providers', '[]')) provider
+                WHERE strpos(lower(provider ->> 'name'), lower($3)) > 0
+            ))
+            AND ($4::text IS NULL OR lower(collection ->> 'itemType') = lower($4))
             "#,
         )
+        .bind(query.terms())
+        .bind(query.keywords())
+        .bind(query.provider.as_deref())
+        .bind(query.item_type.as_deref())
         .fetch_one(&self.pool)
         .await?;
 
-        let collections = collections.map(|c| c.0).unwrap_or_default();
-        let mut collections = Collections::new(collections);
-        collections.number_matched = collections.number_returned;
+        // extents are matched after the metadata, antimeridian aware
+        let matching = collections
+            .map(|c| c.0)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|collection| query.matches_extent(collection))
+            .collect();
+
+        Ok(Collections::paginated(matching, query))
+    }
+
+    async fn read_queryables(&self, id: &str) -> anyhow::Result<Option<Queryables>> {
+        let queryables: Option<Option<sqlx::types::Json<Queryables>>> =
+            sqlx::query_scalar("SELECT queryables FROM meta.collections WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(queryables.flatten().map(|q| q.0))
+    }
+
+    async fn update_queryables(&self, id: &str, queryables: &Queryables) -> anyhow::Result<()> {
+        sqlx::query("UPDATE meta.collections SET queryables = $2 WHERE id = $1")
+            .bind(id)
+            .bind(sqlx::types::Json(queryables))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn extent(&self, id: &str) -> anyhow::Result<Option<Bbox>> {
+        let extent: Option<(f64, f64, f64, f64)> = sqlx::query_as(&format!(
+            r#"
+            SELECT ST_XMin(e), ST_YMin(e), ST_XMax(e), ST_YMax(e)
+            FROM (SELECT ST_Extent(geom) AS e FROM items."{id}") t
+            WHERE e IS NOT NULL
+            "#
+        ))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(extent.map(|(minx, miny, maxx, maxy)| Bbox::Bbox2D([minx, miny, maxx, maxy])))
+    }
+
+    async fn upsert_catalog(&self, catalog: &Catalog) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO meta.catalogs (id, catalog) VALUES ($1, $2)
+            ON CONFLICT (id) DO UPDATE SET catalog = EXCLUDED.catalog
+            "#,
+        )
+        .bind(&catalog.id)
+        .bind(sqlx::types::Json(catalog))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn read_catalog(&self, id: &str) -> anyhow::Result<Option<Catalog>> {
+        let catalog: Option<sqlx::types::Json<Catalog>> =
+            sqlx::query_scalar("SELECT catalog FROM meta.catalogs WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(catalog.map(|c| c.0))
+    }
+
+    async fn delete_catalog(&self, id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM meta.catalogs WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_catalogs(&self) -> anyhow::Result<Vec<Catalog>> {
+        let catalogs: Vec<sqlx::types::Json<Catalog>> =
+            sqlx::query_scalar("SELECT catalog FROM meta.catalogs ORDER BY id")
+                .fetch_all(&self.pool)
+                .await?;
 
-        Ok(collections)
+        Ok(catalogs.into_iter().map(|c| c.0).collect())
     }
 }
@@ -0,0 +1,128 @@
+//! Translation of computed properties to expressions of the select list
+//!
+//! Properties which aren't of the expected type evaluate to `NULL`, as do
+//! divisions by zero, so that a single feature doesn't fail a whole page.
+
+use anyhow::{bail, Result};
+
+use ogcapi_types::features::{ComputedProperty, Expression, Function, Kind};
+
+use crate::CollectionTransactions;
+
+use super::{cql2::quote, Db};
+
+impl Db {
+    /// Object of the computed properties of a collection, `NULL` if there
+    /// are none
+    pub(super) async fn computed_properties(&self, collection: &str) -> Result<String> {
+        let properties = self
+            .read_collection(collection)
+            .await?
+            .map(|c| c.computed_properties)
+            .unwrap_or_default();
+
+        to_sql(&properties)
+    }
+}
+
+pub(super) fn to_sql(properties: &[ComputedProperty]) -> Result<String> {
+    if properties.is_empty() {
+        return Ok("NULL::jsonb".to_string());
+    }
+
+    let mut pairs = Vec::new();
+    for property in properties {
+        let expression = property.parse().map_err(anyhow::Error::msg)?;
+        pairs.push(format!("{}, {}", quote(&property.name), json(&expression)?));
+    }
+
+    Ok(format!("jsonb_build_object({})", pairs.join(", ")))
+}
+
+fn json(expression: &Expression) -> Result<String> {
+    match (expression.kind(), expression) {
+        (Some(Kind::Number), _) => Ok(format!("to_jsonb({})", number(expression)?)),
+        (Some(Kind::Text), _) => Ok(format!("to_jsonb({})", text(expression)?)),
+        (_, Expression::Property(name)) => Ok(property(name)),
+        (
+            _,
+            Expression::Function {
+                function: Function::Coalesce,
+                args,
+            },
+        ) => Ok(format!("COALESCE({})", list(args, json)?)),
+        _ => bail!("Unsupported computed expression `{expression:?}`"),
+    }
+}
+
+fn number(expression: &Expression) -> Result<String> {
+    let sql = match expression {
+        Expression::Number(n) => format!("{n}::float8"),
+        Expression::Property(name) => format!(
+            "(CASE WHEN jsonb_typeof(properties -> {0}) = 'number' \
+            THEN (properties ->> {0})::float8 END)",
+            quote(name)
+        ),
+        Expression::Negate(operand) => format!("(-{})", number(operand)?),
+        Expression::Binary { op: '/', lhs, rhs } => {
+            format!("({} / NULLIF({}, 0))", number(lhs)?, number(rhs)?)
+        }
+        Expression::Binary { op, lhs, rhs } => {
+            format!("({} {op} {})", number(lhs)?, number(rhs)?)
+        }
+        Expression::Function { function, args } => match (function, args.as_slice()) {
+            (Function::Area, _) => "ST_Area(ST_Transform(geom, 4326)::geography)".to_string(),
+            (Function::Length, _) => "ST_Length(ST_Transform(geom, 4326)::geography)".to_string(),
+            (Function::Perimeter, _) => {
+                "ST_Perimeter(ST_Transform(geom, 4326)::geography)".to_string()
+            }
+            (Function::NumPoints, _) => "ST_NPoints(geom)::float8".to_string(),
+            (Function::Round, [value]) => format!("round({}::numeric)::float8", number(value)?),
+            (Function::Round, [value, Expression::Number(places)]) => format!(
+                "round({}::numeric, {})::float8",
+                number(value)?,
+                *places as i32
+            ),
+            (Function::Abs | Function::Floor | Function::Ceil, [value]) => {
+                format!("{function}({})", number(value)?)
+            }
+            (Function::Sqrt, [value]) => {
+                let value = number(value)?;
+                format!("(CASE WHEN {value} >= 0 THEN sqrt({value}) END)")
+            }
+            (Function::Coalesce, args) => format!("COALESCE({})", list(args, number)?),
+            _ => bail!("Function `{function}` doesn't compute a number"),
+        },
+        Expression::Text(_) | Expression::Geometry => {
+            bail!("Expected a number, found `{expression:?}`")
+        }
+    };
+
+    Ok(sql)
+}
+
+fn text(expression: &Expression) -> Result<String> {
+    let sql = match expression {
+        Expression::Text(t) => quote(t),
+        Expression::Property(name) => format!("(properties ->> {})", quote(name)),
+        Expression::Function { function, args } => match (function, args.as_slice()) {
+            (Function::Lower | Function::Upper, [value]) => {
+                format!("{function}({})", text(value)?)
+            }
+            (Function::Coalesce, args) => format!("COALESCE({})", list(args, text)?),
+            _ => bail!("Function `{function}` doesn't compute a text"),
+        },
+        _ => bail!("Expected a text, found `{expression:?}`"),
+    };
+
+    Ok(sql)
+}
+
+/// Property as `jsonb`, with JSON `null` as SQL `NULL`
+fn property(name: &str) -> String {
+    format!("NULLIF(properties -> {}, 'null'::jsonb)", quote(name))
+}
+
+fn list(args: &[Expression], f: fn(&Expression) -> Result<String>) -> Result<String> {
+    Ok(args.iter().map(f).collect::<Result<Vec<_>>>()?.join(", "))
+}
@@ -0,0 +1,212 @@
+//! Translation of `CQL2` expressions to conditions on item tables
+//!
+//! Properties are read from the `properties` column, except `id` and the
+//! geometry. Literals are inlined as escaped SQL literals, spatial literals
+//! are in `CRS84` and transformed to the storage crs of the collection.
+
+use anyhow::{bail, Context, Result};
+
+use ogcapi_types::cql2::Expr;
+
+/// Property names referring to the geometry
+const GEOMETRY: [&str; 2] = ["geometry", "geom"];
+
+/// Translate an expression to a SQL condition, with `srid` being the srid of
+/// the geometry column
+pub(crate) fn to_sql(expr: &Expr, srid: i32) -> Result<String> {
+    Sql { srid }.condition(expr)
+}
+
+struct Sql {
+    srid: i32,
+}
+
+/// Type of the values compared, properties are cast accordingly
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Text,
+    Number,
+    Boolean,
+    Timestamp,
+}
+
+impl Sql {
+    fn condition(&self, expr: &Expr) -> Result<String> {
+        match expr {
+            Expr::Bool(b) => Ok(b.to_string().to_uppercase()),
+            Expr::Operation { op, args } => self.operation(op, args),
+            expr => bail!("Expected a condition, found `{expr}`"),
+        }
+    }
+
+    fn operation(&self, op: &str, args: &[Expr]) -> Result<String> {
+        match op {
+            "and" | "or" => {
+                let conditions = args
+                    .iter()
+                    .map(|arg| self.condition(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                let separator = if op == "and" { " AND " } else { " OR " };
+                Ok(format!("({})", conditions.join(separator)))
+            }
+            "not" => Ok(format!("NOT ({})", self.condition(arg(args, 0)?)?)),
+            "=" | "<>" | "<" | "<=" | ">" | ">=" => {
+                let kind = kind_of(args);
+                Ok(format!(
+                    "{} {op} {}",
+                    self.value(arg(args, 0)?, kind)?,
+                    self.value(arg(args, 1)?, kind)?
+                ))
+            }
+            "like" => Ok(format!(
+                "{} LIKE {}",
+                self.value(arg(args, 0)?, Kind::Text)?,
+                self.value(arg(args, 1)?, Kind::Text)?
+            )),
+            "between" => {
+                let kind = kind_of(args);
+                Ok(format!(
+                    "{} BETWEEN {} AND {}",
+                    self.value(arg(args, 0)?, kind)?,
+                    self.value(arg(args, 1)?, kind)?,
+                    self.value(arg(args, 2)?, kind)?
+                ))
+            }
+            "in" => {
+                let Expr::Array(items) = arg(args, 1)? else {
+                    bail!("Operation `in` expects a list");
+                };
+                let kind = kind_of(items);
+                let items = items
+                    .iter()
+                    .map(|item| self.value(item, kind))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(format!(
+                    "{} IN ({})",
+                    self.value(arg(args, 0)?, kind)?,
+                    items.join(", ")
+                ))
+            }
+            "isNull" => match arg(args, 0)? {
+                Expr::Property { property } if GEOMETRY.contains(&property.as_str()) => {
+                    Ok("geom IS NULL".to_string())
+                }
+                Expr::Property { property } if property == "id" => Ok("id IS NULL".to_string()),
+                Expr::Property { property } => {
+                    let key = quote(property);
+                    Ok(format!(
+                        "(properties -> {key} IS NULL OR properties -> {key} = 'null'::jsonb)"
+                    ))
+                }
+                expr => Ok(format!("{} IS NULL", self.value(expr, Kind::Text)?)),
+            },
+            "s_intersects" | "s_disjoint" | "s_equals" | "s_touches" | "s_within"
+            | "s_overlaps" | "s_crosses" | "s_contains" => {
+                let function = match op {
+                    "s_intersects" => "ST_Intersects",
+                    "s_disjoint" => "ST_Disjoint",
+                    "s_equals" => "ST_Equals",
+                    "s_touches" => "ST_Touches",
+                    "s_within" => "ST_Within",
+                    "s_overlaps" => "ST_Overlaps",
+                    "s_crosses" => "ST_Crosses",
+                    _ => "ST_Contains",
+                };
+                Ok(format!(
+                    "{function}({}, {})",
+                    self.geometry(arg(args, 0)?)?,
+                    self.geometry(arg(args, 1)?)?
+                ))
+            }
+            op => bail!("Operation `{op}` is not supported in filters"),
+        }
+    }
+
+    fn value(&self, expr: &Expr, kind: Kind) -> Result<String> {
+        match expr {
+            Expr::Property { property } => Ok(property_sql(property, kind)),
+            Expr::String(s) if kind == Kind::Timestamp => {
+                Ok(format!("CAST({} AS timestamptz)", quote(s)))
+            }
+            Expr::String(s) => Ok(quote(s)),
+            Expr::Number(n) => Ok(n.to_string()),
+            Expr::Bool(b) => Ok(b.to_string().to_uppercase()),
+            Expr::Timestamp { timestamp: t } | Expr::Date { date: t } => {
+                Ok(format!("CAST({} AS timestamptz)", quote(t)))
+            }
+            Expr::Null => Ok("NULL".to_string()),
+            Expr::Operation { op, args } if op == "casei" => {
+                Ok(format!("lower({})", self.value(arg(args, 0)?, Kind::Text)?))
+            }
+            expr => bail!("Unsupported value `{expr}` in filter"),
+        }
+    }
+
+    fn geometry(&self, expr: &Expr) -> Result<String> {
+        match expr {
+            Expr::Property { property } if GEOMETRY.contains(&property.as_str()) => {
+                Ok("geom".to_string())
+            }
+            Expr::Geometry(geometry) => Ok(format!(
+                "ST_Transform(ST_SetSRID(ST_GeomFromGeoJSON({}), 4326), {})",
+                quote(&serde_json::to_string(geometry)?),
+                self.srid
+            )),
+            Expr::BBox { bbox } => {
+                let [minx, miny, maxx, maxy] = match bbox[..] {
+                    [minx, miny, maxx, maxy] | [minx, miny, _, maxx, maxy, _] => {
+                        [minx, miny, maxx, maxy]
+                    }
+                    _ => bail!("Invalid bbox with {} values", bbox.len()),
+                };
+                Ok(format!(
+                    "ST_Transform(ST_MakeEnvelope({minx}, {miny}, {maxx}, {maxy}, 4326), {})",
+                    self.srid
+                ))
+            }
+            expr => bail!("Expected a geometry, found `{expr}`"),
+        }
+    }
+}
+
+fn arg(args: &[Expr], index: usize) -> Result<&Expr> {
+    args.get(index)
+        .with_context(|| format!("Missing argument {}", index + 1))
+}
+
+/// Type of the literals of an operation, text if there are none
+fn kind_of(args: &[Expr]) -> Kind {
+    args.iter()
+        .find_map(|arg| match arg {
+            Expr::Number(_) => Some(Kind::Number),
+            Expr::Bool(_) => Some(Kind::Boolean),
+            Expr::Timestamp { .. } | Expr::Date { .. } => Some(Kind::Timestamp),
+            Expr::String(_) => Some(Kind::Text),
+            _ => None,
+        })
+        .unwrap_or(Kind::Text)
+}
+
+/// Property as value of a type, values of other types are `NULL`
+fn property_sql(name: &str, kind: Kind) -> String {
+    if name == "id" {
+        return "id".to_string();
+    }
+
+    let key = quote(name);
+    match kind {
+        Kind::Text => format!("(properties ->> {key})"),
+        Kind::Number => format!(
+            "(CASE WHEN jsonb_typeof(properties -> {key}) = 'number' THEN (properties ->> {key})::numeric END)"
+        ),
+        Kind::Boolean => format!(
+            "(CASE WHEN jsonb_typeof(properties -> {key}) = 'boolean' THEN (properties ->> {key})::boolean END)"
+        ),
+        Kind::Timestamp => format!("CAST(properties ->> {key} AS timestamptz)"),
+    }
+}
+
+/// Quote a string literal
+pub(super) fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
@@ -0,0 +1,255 @@
+use sqlx::{migrate::MigrateDatabase, Postgres};
+use url::Url;
+
+use crate::Diagnostic;
+
+use super::Db;
+
+/// Oldest PostGIS providing the functions used, e.g. `ST_SquareGrid`
+const POSTGIS_MIN: (u32, u32) = (3, 1);
+
+impl Db {
+    /// Connect without creating the database or running migrations, e.g. to
+    /// diagnose it
+    pub async fn connect(url: &Url) -> Result<Self, sqlx::Error> {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(1)
+            .connect(url.as_str())
+            .await?;
+
+        Ok(Db { pool })
+    }
+
+    /// Check the connection, schema version, PostGIS and the spatial indexes
+    /// of the collections, without changing the database
+    pub async fn diagnose(url: &Url) -> Vec<Diagnostic> {
+        match Postgres::database_exists(url.as_str()).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return vec![Diagnostic::warning("database", "Database does not exist")
+                    .hint("Run `ogcapi migrate` or start the service, which creates it")]
+            }
+            Err(e) => {
+                return vec![
+                    Diagnostic::error("database", format!("Failed to connect: {e}")).hint(
+                        "Check the host, port and credentials of `DATABASE_URL` and that the \
+                         server accepts connections",
+                    ),
+                ]
+            }
+        }
+
+        let db = match Db::connect(url).await {
+            Ok(db) => db,
+            Err(e) => {
+                return vec![Diagnostic::error(
+                    "database",
+                    format!("Failed to connect: {e}"),
+                )]
+            }
+        };
+
+        let mut diagnostics = vec![Diagnostic::ok("database", "Connected")];
+        diagnostics.extend(db.inspect().await);
+        diagnostics
+    }
+
+    /// Check the schema version, PostGIS and the spatial indexes of the
+    /// collections of a connected database
+    pub async fn inspect(&self) -> Vec<Diagnostic> {
+        [
+            self.schema().await,
+            self.postgis().await,
+            self.indexes().await,
+        ]
+        .into_iter()
+        .map(|diagnostic| {
+            diagnostic.unwrap_or_else(|e| {
+                Diagnostic::error("database", format!("Failed to inspect the database: {e}"))
+            })
+        })
+        .collect()
+    }
+
+    /// Compare the applied migrations to the ones of this build
+    async fn schema(&self) -> Result<Diagnostic, sqlx::Error> {
+        let migrator = sqlx::migrate!();
+        let known: Vec<_> = migrator
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .collect();
+        let latest = known.last().map(|m| m.version).unwrap_or_default();
+
+        let applied: Vec<(i64, bool, Vec<u8>)> = match sqlx::query_as(
+            "SELECT version, success, checksum FROM _sqlx_migrations ORDER BY version",
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(applied) => applied,
+            // no migrations table yet
+            Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("42P01") => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        if let Some((version, ..)) = applied.iter().find(|(_, success, _)| !success) {
+            return Ok(
+                Diagnostic::error("schema", format!("Migration {version} failed partway")).hint(
+                    format!(
+                        "Repair the database and delete the row of version {version} from \
+                 `_sqlx_migrations`, then run `ogcapi migrate`"
+                    ),
+                ),
+            );
+        }
+
+        let unknown: Vec<_> = applied
+            .iter()
+            .filter(|(version, ..)| !known.iter().any(|m| m.version == *version))
+            .map(|(version, ..)| version.to_string())
+            .collect();
+        if !unknown.is_empty() {
+            return Ok(Diagnostic::error(
+                "schema",
+                format!(
+                    "Database has migrations unknown to this build ({}), its schema is newer \
+                     than {latest}",
+                    unknown.join(", ")
+                ),
+            )
+            .hint("Upgrade `ogcapi` to the version which migrated the database"));
+        }
+
+        let modified: Vec<_> = applied
+            .iter()
+            .filter(|(version, _, checksum)| {
+                known
+                    .iter()
+                    .any(|m| m.version == *version && *m.checksum != checksum[..])
+            })
+            .map(|(version, ..)| version.to_string())
+            .collect();
+        if !modified.is_empty() {
+            return Ok(Diagnostic::error(
+                "schema",
+                format!(
+                    "Migrations changed after they were applied: {}",
+                    modified.join(", ")
+                ),
+            )
+            .hint("Restore the migrations as applied, migrations must not be edited"));
+        }
+
+        let pending = known.len() - applied.len();
+        if pending > 0 {
+            return Ok(Diagnostic::warning(
+                "schema",
+                format!("{pending} pending migrations, up to version {latest}"),
+            )
+            .hint("Run `ogcapi migrate` or start the service, which applies them"));
+        }
+
+        Ok(Diagnostic::ok("schema", format!("Schema version {latest}")))
+    }
+
+    /// Check that PostGIS is available in a supported version
+    async fn postgis(&self) -> Result<Diagnostic, sqlx::Error> {
+        let versions: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT default_version, installed_version FROM pg_available_extensions WHERE name = 'postgis'",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some((available, installed)) = versions else {
+            return Ok(
+                Diagnostic::error("postgis", "PostGIS is not available on the server").hint(
+                    format!(
+                        "Install PostGIS {}.{} or later on the database server",
+                        POSTGIS_MIN.0, POSTGIS_MIN.1
+                    ),
+                ),
+            );
+        };
+
+        let (version, diagnostic) = match installed {
+            Some(version) => {
+                let diagnostic = Diagnostic::ok("postgis", format!("PostGIS {version}"));
+                (version, diagnostic)
+            }
+            None => {
+                let version = available.unwrap_or_default();
+                let diagnostic = Diagnostic::warning(
+                    "postgis",
+                    format!("PostGIS {version} is available but not installed"),
+                )
+                .hint("Run `ogcapi migrate` or start the service, which installs it");
+                (version, diagnostic)
+            }
+        };
+
+        if !supported(&version) {
+            return Ok(Diagnostic::error(
+                "postgis",
+                format!(
+                    "PostGIS {version} is older than {}.{}",
+                    POSTGIS_MIN.0, POSTGIS_MIN.1
+                ),
+            )
+            .hint("Upgrade PostGIS and run `ALTER EXTENSION postgis UPDATE`"));
+        }
+
+        Ok(diagnostic)
+    }
+
+    /// Find collections without spatial index, whose queries scan the table
+    async fn indexes(&self) -> Result<Diagnostic, sqlx::Error> {
+        let missing: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT c.relname
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE n.nspname = 'items'
+            AND c.relkind IN ('r', 'p')
+            AND NOT EXISTS (
+                SELECT 1
+                FROM pg_index i
+                JOIN pg_class ic ON ic.oid = i.indexrelid
+                JOIN pg_am am ON am.oid = ic.relam
+                JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum = ANY(i.indkey)
+                WHERE i.indrelid = c.oid AND am.amname = 'gist' AND a.attname = 'geom'
+            )
+            ORDER BY c.relname
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        if missing.is_empty() {
+            return Ok(Diagnostic::ok(
+                "indexes",
+                "All collections have a spatial index",
+            ));
+        }
+
+        let hint = missing
+            .iter()
+            .map(|table| format!(r#"CREATE INDEX ON items."{table}" USING gist (geom);"#))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(Diagnostic::warning(
+            "indexes",
+            format!("Collections without spatial index: {}", missing.join(", ")),
+        )
+        .hint(hint))
+    }
+}
+
+/// Whether a PostGIS version like `3.4.2` is supported
+fn supported(version: &str) -> bool {
+    let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+    match (parts.next().flatten(), parts.next().flatten()) {
+        (Some(major), Some(minor)) => (major, minor) >= POSTGIS_MIN,
+        _ => false,
+    }
+}
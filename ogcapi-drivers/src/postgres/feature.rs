@@ -1,19 +1,31 @@
+use std::collections::HashMap;
+
+use futures::{stream::BoxStream, TryStreamExt};
+use serde_json::{Map, Value};
+use sqlx::types::Json;
+
+#[cfg(feature = "stac")]
+use ogcapi_types::{common::Bbox, stac::Asset};
 use ogcapi_types::{
-    common::{Bbox, Crs, Datetime, IntervalDatetime},
-    features::{Feature, FeatureCollection, Query},
+    common::{Crs, Links},
+    cql2::Expr,
+    features::{
+        AggregateQuery, CollectionStats, Feature, FeatureCollection, PropertyValues, Query,
+        StatsQuery, ValidationRule, ValuesQuery, Violation,
+    },
 };
 
-use crate::{CollectionTransactions, FeatureTransactions};
+use crate::{wkb, CollectionTransactions, Delta, FeatureTransactions, QueryPlan};
 
-use super::Db;
+use super::{cql2, Db};
 
 #[cfg(not(feature = "stac"))]
 static ROWS: &str = "
 items.id,
 items.collection,
 properties,
-ST_AsGeoJSON(ST_Transform(geom, $1))::jsonb AS geometry,
-links,
+ST_AsEWKB(ST_Transform(geom, $1)) AS geometry,
+links
 ";
 
 #[cfg(feature = "stac")]
@@ -21,7 +33,7 @@ static ROWS: &str = "
 items.id,
 items.collection,
 properties,
-ST_AsGeoJSON(ST_Transform(geom, $1))::jsonb AS geometry,
+ST_AsEWKB(ST_Transform(geom, $1)) AS geometry,
 links,
 meta.collection ->> 'stac_version' AS stac_version,
 COALESCE(
@@ -33,15 +45,68 @@ COALESCE(
     bbox,
     array_to_json(
         ARRAY[
-            st_xmin(st_transform(geom, 4326)::box2d),
-            st_ymin(st_transform(geom, 4326)::box2d),
-            st_xmax(st_transform(geom, 4326)::box2d),
-            st_ymax(st_transform(geom, 4326)::box2d)
+            st_xmin(st_transform(geom, 4326)::box3d),
+            st_ymin(st_transform(geom, 4326)::box3d)
         ]
+        || CASE WHEN ST_HasZ(geom) THEN ARRAY[st_zmin(geom::box3d)] ELSE '{}' END
+        || ARRAY[
+            st_xmax(st_transform(geom, 4326)::box3d),
+            st_ymax(st_transform(geom, 4326)::box3d)
+        ]
+        || CASE WHEN ST_HasZ(geom) THEN ARRAY[st_zmax(geom::box3d)] ELSE '{}' END
     )::jsonb
 ) as bbox
 ";
 
+/// Row of a feature, the geometry is read as `EWKB` and converted in place
+/// instead of being formatted and parsed as `GeoJSON`
+#[derive(sqlx::FromRow)]
+struct FeatureRow {
+    id: Option<String>,
+    collection: Option<String>,
+    properties: Option<Json<Map<String, Value>>>,
+    geometry: Vec<u8>,
+    links: Json<Links>,
+    /// Computed properties, overriding stored ones of the same name
+    computed: Option<Json<Map<String, Value>>>,
+    #[cfg(feature = "stac")]
+    stac_version: String,
+    #[cfg(feature = "stac")]
+    stac_extensions: Json<Vec<String>>,
+    #[cfg(feature = "stac")]
+    assets: Json<HashMap<String, Asset>>,
+    #[cfg(feature = "stac")]
+    bbox: Option<Json<Bbox>>,
+}
+
+impl TryFrom<FeatureRow> for Feature {
+    type Error = anyhow::Error;
+
+    fn try_from(row: FeatureRow) -> Result<Self, Self::Error> {
+        let mut properties = row.properties.map(|p| p.0);
+        if let Some(computed) = row.computed {
+            properties.get_or_insert_with(Map::new).extend(computed.0);
+        }
+
+        Ok(Feature {
+            id: row.id,
+            collection: row.collection,
+            r#type: Default::default(),
+            properties,
+            geometry: wkb::from_ewkb(&row.geometry)?,
+            links: row.links.0,
+            #[cfg(feature = "stac")]
+            stac_version: row.stac_version,
+            #[cfg(feature = "stac")]
+            stac_extensions: row.stac_extensions.0,
+            #[cfg(feature = "stac")]
+            assets: row.assets.0,
+            #[cfg(feature = "stac")]
+            bbox: row.bbox.map(|b| b.0),
+        })
+    }
+}
+
 #[async_trait::async_trait]
 impl FeatureTransactions for Db {
     async fn create_feature(&self, feature: &Feature) -> anyhow::Result<String> {
@@ -59,7 +124,7 @@ impl FeatureTransactions for Db {
             ) VALUES (
                 COALESCE($1 ->> 'id', gen_random_uuid()::text),
                 $1 -> 'properties',
-                ST_GeomFromGeoJSON($1 -> 'geometry'),
+                ST_Transform(ST_GeomFromGeoJSON($1 -> 'geometry'), Find_SRID('items', '{0}', 'geom')),
                 $1 -> 'links',
                 COALESCE($1 -> 'assets', '{{}}'::jsonb),
                 $1 -> 'bbox'
@@ -75,21 +140,98 @@ impl FeatureTransactions for Db {
         Ok(id.0)
     }
 
+    async fn create_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+    ) -> anyhow::Result<Vec<String>> {
+        let storage_srid = self.storage_srid(collection).await?;
+
+        let features = features
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let ids: Vec<String> = sqlx::query_scalar(&insert_query(collection))
+            .bind(features)
+            .bind(crs.as_srid())
+            .bind(storage_srid)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(ids)
+    }
+
+    async fn create_feature_batches(
+        &self,
+        collection: &str,
+        mut batches: BoxStream<'_, anyhow::Result<Vec<Feature>>>,
+        crs: &Crs,
+    ) -> anyhow::Result<usize> {
+        let storage_srid = self.storage_srid(collection).await?;
+        let query = insert_query(collection);
+
+        let mut tx = self.pool.begin().await?;
+
+        let mut count = 0;
+        while let Some(batch) = batches.try_next().await? {
+            let features = batch
+                .iter()
+                .map(serde_json::to_value)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let result = sqlx::query(&query)
+                .bind(features)
+                .bind(crs.as_srid())
+                .bind(storage_srid)
+                .execute(&mut *tx)
+                .await?;
+            count += result.rows_affected() as usize;
+        }
+
+        tx.commit().await?;
+
+        Ok(count)
+    }
+
+    async fn upsert_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+    ) -> anyhow::Result<Vec<String>> {
+        let storage_srid = self.storage_srid(collection).await?;
+
+        let features = features
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let ids: Vec<String> = sqlx::query_scalar(&upsert_query(collection))
+            .bind(features)
+            .bind(crs.as_srid())
+            .bind(storage_srid)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(ids)
+    }
+
     async fn read_feature(
         &self,
         collection: &str,
         id: &str,
         crs: &Crs,
     ) -> anyhow::Result<Option<Feature>> {
-        let feature: Option<sqlx::types::Json<Feature>> = sqlx::query_scalar(&format!(
+        let computed = self.computed_properties(collection).await?;
+
+        let row: Option<FeatureRow> = sqlx::query_as(&format!(
             r#"
-            SELECT row_to_json(t)
-            FROM (
-                SELECT {ROWS}
-                FROM items."{collection}" items JOIN meta.collections meta
-                    ON items.collection = meta.id
-                WHERE items.id = $2
-            ) t
+            SELECT {ROWS}, {computed} AS computed
+            FROM items."{collection}" items JOIN meta.collections meta
+                ON items.collection = meta.id
+            WHERE items.id = $2
             "#
         ))
         .bind(crs.as_srid())
@@ -97,7 +239,7 @@ impl FeatureTransactions for Db {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(feature.map(|f| f.0))
+        row.map(Feature::try_from).transpose()
     }
 
     async fn update_feature(&self, feature: &Feature) -> anyhow::Result<()> {
@@ -106,7 +248,7 @@ impl FeatureTransactions for Db {
             UPDATE items."{0}"
             SET
                 properties = $1 -> 'properties',
-                geom = ST_GeomFromGeoJSON($1 -> 'geometry'),
+                geom = ST_Transform(ST_GeomFromGeoJSON($1 -> 'geometry'), Find_SRID('items', '{0}', 'geom')),
                 links = $1 -> 'links',
                 assets = COALESCE($1 -> 'assets', '{{}}'::jsonb)
             WHERE id = $1 ->> 'id'
@@ -120,6 +262,33 @@ impl FeatureTransactions for Db {
         Ok(())
     }
 
+    async fn update_feature_if_version(
+        &self,
+        feature: &Feature,
+        version: u64,
+    ) -> anyhow::Result<bool> {
+        // checked and replaced in one statement, concurrent writers can't
+        // both succeed
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE items."{0}"
+            SET
+                properties = $1 -> 'properties',
+                geom = ST_Transform(ST_GeomFromGeoJSON($1 -> 'geometry'), Find_SRID('items', '{0}', 'geom')),
+                links = $1 -> 'links',
+                assets = COALESCE($1 -> 'assets', '{{}}'::jsonb)
+            WHERE id = $1 ->> 'id' AND COALESCE(properties -> 'version', '0') = to_jsonb($2::bigint)
+            "#,
+            &feature.collection.as_ref().unwrap()
+        ))
+        .bind(serde_json::to_value(feature)?)
+        .bind(version as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
     async fn delete_feature(&self, collection: &str, id: &str) -> anyhow::Result<()> {
         sqlx::query(&format!(
             r#"DELETE FROM items."{}" WHERE id = $1"#,
@@ -132,63 +301,354 @@ impl FeatureTransactions for Db {
         Ok(())
     }
 
+    async fn checksums(&self, collection: &str) -> anyhow::Result<HashMap<String, String>> {
+        // features deleted since are inserted again
+        let checksums: Vec<(String, String)> = sqlx::query_as(&format!(
+            r#"
+            SELECT c.feature_id, c.checksum
+            FROM meta.feature_checksums c JOIN items."{collection}" items
+                ON items.id = c.feature_id
+            WHERE c.collection_id = $1
+            "#
+        ))
+        .bind(collection)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(checksums.into_iter().collect())
+    }
+
+    async fn apply_delta(&self, collection: &str, delta: &Delta, crs: &Crs) -> anyhow::Result<()> {
+        let storage_srid = self.storage_srid(collection).await?;
+
+        let features = delta
+            .upserts
+            .iter()
+            .map(|(feature, _)| serde_json::to_value(feature))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (ids, checksums): (Vec<String>, Vec<String>) = delta
+            .upserts
+            .iter()
+            .map(|(feature, checksum)| {
+                (
+                    feature.id.to_owned().unwrap_or_default(),
+                    checksum.to_owned(),
+                )
+            })
+            .unzip();
+
+        let mut tx = self.pool.begin().await?;
+
+        if !features.is_empty() {
+            sqlx::query(&upsert_query(collection))
+                .bind(features)
+                .bind(crs.as_srid())
+                .bind(storage_srid)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO meta.feature_checksums (collection_id, feature_id, checksum)
+                SELECT $1, UNNEST($2::text[]), UNNEST($3::text[])
+                ON CONFLICT (collection_id, feature_id)
+                DO UPDATE SET checksum = EXCLUDED.checksum
+                "#,
+            )
+            .bind(collection)
+            .bind(ids)
+            .bind(checksums)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if !delta.deletes.is_empty() {
+            sqlx::query(&format!(
+                r#"DELETE FROM items."{collection}" WHERE id = ANY($1)"#
+            ))
+            .bind(&delta.deletes)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "DELETE FROM meta.feature_checksums WHERE collection_id = $1 AND feature_id = ANY($2)",
+            )
+            .bind(collection)
+            .bind(&delta.deletes)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
     async fn list_items(
         &self,
         collection: &str,
         query: &Query,
     ) -> anyhow::Result<FeatureCollection> {
+        let conditions = self.conditions(collection, query).await?;
+
+        // count
+        let number_matched: (i64,) = sqlx::query_as(&format!(
+            r#"
+            SELECT count(*) FROM items."{collection}" items
+            WHERE {conditions}
+            "#,
+        ))
+        .fetch_one(&self.pool)
+        .await?;
+
+        // only the number of matches is requested
+        if query.limit == Some(0) {
+            let mut fc = FeatureCollection::new(Vec::new());
+            fc.number_matched = Some(number_matched.0 as u64);
+            return Ok(fc);
+        }
+
+        // fetch
+        let order = self.order(collection, query).await?;
+        let computed = self.computed_properties(collection).await?;
+        let rows: Vec<FeatureRow> = sqlx::query_as(&format!(
+            r#"
+            SELECT {ROWS}, {computed} AS computed
+            FROM items."{collection}" items JOIN meta.collections meta
+                ON items.collection = meta.id
+            WHERE {conditions}
+            {order}
+            LIMIT {}
+            OFFSET {}
+            "#,
+            query
+                .limit
+                .map_or_else(|| String::from("NULL"), |l| l.to_string()),
+            query.offset.unwrap_or(0)
+        ))
+        .bind(query.crs.as_srid())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let features = rows
+            .into_iter()
+            .map(Feature::try_from)
+            .collect::<anyhow::Result<_>>()?;
+        let mut fc = FeatureCollection::new(features);
+        fc.number_matched = Some(number_matched.0 as u64);
+
+        Ok(fc)
+    }
+
+    fn stream_items(
+        &self,
+        collection: &str,
+        query: &Query,
+    ) -> BoxStream<'static, anyhow::Result<Feature>> {
+        let db = self.clone();
+        let collection = collection.to_owned();
+        let query = query.clone();
+
+        Box::pin(async_stream::try_stream! {
+            let conditions = db.conditions(&collection, &query).await?;
+            let order = db.order(&collection, &query).await?;
+            let computed = db.computed_properties(&collection).await?;
+
+            let sql = format!(
+                r#"
+                SELECT {ROWS}, {computed} AS computed
+                FROM items."{collection}" items JOIN meta.collections meta
+                    ON items.collection = meta.id
+                WHERE {conditions}
+                {order}
+                LIMIT {}
+                OFFSET {}
+                "#,
+                query
+                    .limit
+                    .map_or_else(|| String::from("NULL"), |l| l.to_string()),
+                query.offset.unwrap_or(0)
+            );
+
+            let mut rows = sqlx::query_as::<_, FeatureRow>(&sql)
+                .bind(query.crs.as_srid())
+                .fetch(&db.pool);
+
+            while let Some(row) = rows.try_next().await? {
+                yield Feature::try_from(row)?;
+            }
+        })
+    }
+
+    async fn plan_items(
+        &self,
+        collection: &str,
+        query: &Query,
+    ) -> anyhow::Result<Option<QueryPlan>> {
+        let conditions = self.conditions(collection, query).await?;
+
+        // counting the matches is the expensive part of listing items
+        let explain: Json<Value> = sqlx::query_scalar(&format!(
+            r#"
+            EXPLAIN (FORMAT JSON)
+            SELECT count(*) FROM items."{collection}" items
+            WHERE {conditions}
+            "#,
+        ))
+        .fetch_one(&self.pool)
+        .await?;
+
+        let plan = &explain.0[0]["Plan"];
+
+        Ok(Some(QueryPlan {
+            cost: plan["Total Cost"].as_f64().unwrap_or_default(),
+            full_scan: has_seq_scan(plan),
+        }))
+    }
+
+    async fn stats(
+        &self,
+        collection: &str,
+        query: &StatsQuery,
+    ) -> anyhow::Result<Option<CollectionStats>> {
+        self.collection_stats(collection, query).await.map(Some)
+    }
+
+    async fn property_values(
+        &self,
+        collection: &str,
+        property: &str,
+        query: &ValuesQuery,
+    ) -> anyhow::Result<Option<PropertyValues>> {
+        self.distinct_values(collection, property, query)
+            .await
+            .map(Some)
+    }
+
+    async fn aggregate(
+        &self,
+        collection: &str,
+        query: &AggregateQuery,
+    ) -> anyhow::Result<Option<FeatureCollection>> {
+        self.aggregate_features(collection, query).await
+    }
+
+    async fn reproject_features(
+        &self,
+        collection: &str,
+        crs: &Crs,
+        limit: usize,
+    ) -> anyhow::Result<(u64, u64)> {
+        self.reproject_batch(collection, crs, limit).await
+    }
+
+    async fn finish_reprojection(&self, collection: &str, crs: &Crs) -> anyhow::Result<()> {
+        self.swap_reprojected(collection, crs).await
+    }
+
+    async fn validate_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+        rules: &[ValidationRule],
+    ) -> anyhow::Result<Vec<Violation>> {
+        self.feature_violations(collection, features, crs, rules)
+            .await
+    }
+
+    async fn validate_collection(
+        &self,
+        collection: &str,
+        rules: &[ValidationRule],
+    ) -> anyhow::Result<Vec<Violation>> {
+        self.collection_violations(collection, rules).await
+    }
+
+    async fn match_filter(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+        filter: &Expr,
+    ) -> anyhow::Result<Vec<bool>> {
+        let storage_srid = self.storage_srid(collection).await?;
+        let condition = cql2::to_sql(filter, storage_srid)?;
+
+        let features = features
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // evaluated on rows shaped like the item table
+        let matches: Vec<bool> = sqlx::query_scalar(&format!(
+            r#"
+            SELECT COALESCE({condition}, FALSE)
+            FROM (
+                SELECT
+                    f ->> 'id' AS id,
+                    f -> 'properties' AS properties,
+                    ST_Transform(ST_SetSRID(ST_GeomFromGeoJSON(f -> 'geometry'), $2), $3) AS geom,
+                    n
+                FROM UNNEST($1::jsonb[]) WITH ORDINALITY AS t(f, n)
+            ) items
+            ORDER BY n
+            "#
+        ))
+        .bind(features)
+        .bind(crs.as_srid())
+        .bind(storage_srid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(matches)
+    }
+}
+
+/// Checks whether a node of a query plan scans a table sequentially
+fn has_seq_scan(plan: &Value) -> bool {
+    plan["Node Type"] == "Seq Scan"
+        || plan["Plans"]
+            .as_array()
+            .is_some_and(|plans| plans.iter().any(has_seq_scan))
+}
+
+impl Db {
+    /// Build the `WHERE` clause for a feature query
+    async fn conditions(&self, collection: &str, query: &Query) -> anyhow::Result<String> {
         let mut where_conditions = vec!["TRUE".to_owned()];
 
         // bbox
         if let Some(bbox) = query.bbox.as_ref() {
             // TODO: Properly handle crs and bbox transformation
             let bbox_srid: i32 = query.bbox_crs.as_srid();
+            let storage_srid = self.storage_srid(collection).await?;
 
-            let c = self.read_collection(collection).await?;
-            let storage_srid = c
-                .expect("collection exists")
-                .storage_crs
-                .unwrap_or_default()
-                .as_srid();
-
-            let envelope = match bbox {
-                Bbox::Bbox2D(bbox) => format!(
-                    "ST_MakeEnvelope({}, {}, {}, {}, {})",
-                    bbox[0], bbox[1], bbox[2], bbox[3], bbox_srid
-                ),
-                Bbox::Bbox3D(bbox) => format!(
-                    "ST_MakeEnvelope({}, {}, {}, {}, {})",
-                    bbox[0], bbox[1], bbox[3], bbox[4], bbox_srid
-                ),
-            };
+            let [minx, miny, maxx, maxy] = bbox.to_2d();
+            let envelope = format!("ST_MakeEnvelope({minx}, {miny}, {maxx}, {maxy}, {bbox_srid})");
             where_conditions.push(format!(
                 "geom && ST_Transform({}, {})",
                 envelope, storage_srid
             ));
+
+            // vertical extent, geometries without height are not excluded
+            if let Some((minz, maxz)) = bbox.z_range() {
+                where_conditions.push(format!(
+                    "(zrange IS NULL OR zrange && numrange({minz}, {maxz}, '[]'))"
+                ));
+            }
         }
 
         // datetime
         if let Some(datetime) = query.datetime.as_ref() {
-            let (from, to) = match datetime {
-                Datetime::Datetime(_) => (
-                    format!("CAST('{datetime}' AS timestamptz)"),
-                    format!("CAST('{datetime}' AS timestamptz)"),
-                ),
-                Datetime::Interval { from, to } => {
-                    let from = match from {
-                        IntervalDatetime::Datetime(_) => {
-                            format!("CAST('{from}' AS timestamptz)")
-                        }
-                        IntervalDatetime::Open => "to_timestamp('-infinity')".to_owned(),
-                    };
-                    let to = match to {
-                        IntervalDatetime::Datetime(_) => {
-                            format!("CAST('{to}' AS timestamptz)")
-                        }
-                        IntervalDatetime::Open => "NOW()".to_owned(),
-                    };
-                    (from, to)
-                }
+            let interval = datetime.interval();
+            let from = match interval.start {
+                Some(start) => format!("CAST('{}' AS timestamptz)", start.to_rfc3339()),
+                None => "to_timestamp('-infinity')".to_owned(),
+            };
+            let to = match interval.end {
+                Some(end) => format!("CAST('{}' AS timestamptz)", end.to_rfc3339()),
+                None => "NOW()".to_owned(),
             };
 
             where_conditions.push(format!(
@@ -216,6 +676,11 @@ impl FeatureTransactions for Db {
             ));
         }
 
+        // ids
+        if let Some(ids) = query.ids.as_ref() {
+            where_conditions.push(format!("items.id = ANY({})", id_array(ids)));
+        }
+
         // kv
         for (k, v) in query.additional_parameters.iter() {
             where_conditions.push(format!(
@@ -234,44 +699,108 @@ impl FeatureTransactions for Db {
             ));
         }
 
-        let conditions = where_conditions.join(" AND ");
+        // access filter of the requesting user
+        if let Some(filter) = query.access_filter.as_ref() {
+            let storage_srid = self.storage_srid(collection).await?;
+            where_conditions.push(cql2::to_sql(filter, storage_srid)?);
+        }
 
-        // count
-        let number_matched: (i64,) = sqlx::query_as(&format!(
-            r#"
-            SELECT count(*) FROM items."{collection}"
-            WHERE {conditions}
-            "#,
-        ))
-        .fetch_one(&self.pool)
-        .await?;
+        Ok(where_conditions.join(" AND "))
+    }
 
-        // fetch
-        let features: Option<sqlx::types::Json<Vec<Feature>>> = sqlx::query_scalar(&format!(
-            r#"
-            SELECT array_to_json(array_agg(row_to_json(t)))
-            FROM (
-                SELECT {ROWS}
-                FROM items."{collection}" items JOIN meta.collections meta
-                    ON items.collection = meta.id
-                WHERE {conditions}
-                LIMIT {}
-                OFFSET {}
-            ) t
-            "#,
-            query
-                .limit
-                .map_or_else(|| String::from("NULL"), |l| l.to_string()),
-            query.offset.unwrap_or(0)
-        ))
-        .bind(query.crs.as_srid())
-        .fetch_one(&self.pool)
-        .await?;
+    /// Order of the features, as listed by the requested ids or the ones
+    /// nearest to a position first
+    async fn order(&self, collection: &str, query: &Query) -> anyhow::Result<String> {
+        if let Some(ids) = query.ids.as_ref() {
+            return Ok(format!(
+                "ORDER BY array_position({}, items.id)",
+                id_array(ids)
+            ));
+        }
 
-        let features = features.map(|f| f.0).unwrap_or_default();
-        let mut fc = FeatureCollection::new(features);
-        fc.number_matched = Some(number_matched.0 as u64);
+        // nearest neighbours with the spatial index
+        if let Some(nearest) = query.nearest {
+            let storage_srid = self.storage_srid(collection).await?;
+            return Ok(format!(
+                "ORDER BY items.geom <-> ST_Transform(ST_SetSRID(ST_MakePoint({}, {}), 4326), {storage_srid})",
+                nearest.lon, nearest.lat
+            ));
+        }
 
-        Ok(fc)
+        Ok(String::new())
+    }
+
+    /// Srid of the geometries of a collection
+    pub(super) async fn storage_srid(&self, collection: &str) -> anyhow::Result<i32> {
+        let srid = self
+            .read_collection(collection)
+            .await?
+            .and_then(|c| c.storage_crs)
+            .unwrap_or_default()
+            .as_srid();
+
+        Ok(srid)
     }
 }
+
+fn id_array(ids: &[String]) -> String {
+    let ids: Vec<String> = ids.iter().map(|id| cql2::quote(id)).collect();
+    format!("ARRAY[{}]::text[]", ids.join(", "))
+}
+
+/// Insert of the features of a `jsonb[]` with the geometries in the srid `$2`
+fn insert_query(collection: &str) -> String {
+    format!(
+        r#"
+        INSERT INTO items."{collection}" (
+            id,
+            properties,
+            geom,
+            links,
+            assets,
+            bbox
+        )
+        SELECT
+            COALESCE(f ->> 'id', gen_random_uuid()::text),
+            f -> 'properties',
+            ST_Transform(ST_SetSRID(ST_GeomFromGeoJSON(f -> 'geometry'), $2), $3),
+            COALESCE(f -> 'links', '[]'::jsonb),
+            COALESCE(f -> 'assets', '{{}}'::jsonb),
+            f -> 'bbox'
+        FROM UNNEST($1::jsonb[]) f
+        RETURNING id
+        "#
+    )
+}
+
+/// Insert of the features of a `jsonb[]` with the geometries in the srid `$2`,
+/// replacing the stored features with the same ids
+fn upsert_query(collection: &str) -> String {
+    format!(
+        r#"
+        INSERT INTO items."{collection}" (
+            id,
+            properties,
+            geom,
+            links,
+            assets,
+            bbox
+        )
+        SELECT
+            COALESCE(f ->> 'id', gen_random_uuid()::text),
+            f -> 'properties',
+            ST_Transform(ST_SetSRID(ST_GeomFromGeoJSON(f -> 'geometry'), $2), $3),
+            COALESCE(f -> 'links', '[]'::jsonb),
+            COALESCE(f -> 'assets', '{{}}'::jsonb),
+            f -> 'bbox'
+        FROM UNNEST($1::jsonb[]) f
+        ON CONFLICT (id) DO UPDATE SET
+            properties = EXCLUDED.properties,
+            geom = EXCLUDED.geom,
+            links = EXCLUDED.links,
+            assets = EXCLUDED.assets,
+            bbox = EXCLUDED.bbox
+        RETURNING id
+        "#
+    )
+}
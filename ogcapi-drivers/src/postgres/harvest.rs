@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+
+use ogcapi_types::harvest::Harvest;
+
+use crate::HarvestTransactions;
+
+use super::Db;
+
+#[async_trait::async_trait]
+impl HarvestTransactions for Db {
+    async fn create_harvest(&self, harvest: &Harvest) -> anyhow::Result<String> {
+        let id: String = sqlx::query_scalar(
+            r#"
+            INSERT INTO meta.harvests (collection_id, definition)
+            VALUES ($1, $2::jsonb - ARRAY['id', 'collection', 'created', 'lastRun', 'links'])
+            RETURNING id
+            "#,
+        )
+        .bind(&harvest.collection)
+        .bind(sqlx::types::Json(harvest))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn read_harvest(&self, id: &str) -> anyhow::Result<Option<Harvest>> {
+        let harvest: Option<sqlx::types::Json<Harvest>> = sqlx::query_scalar(
+            r#"
+            SELECT definition || jsonb_strip_nulls(jsonb_build_object(
+                'id', id, 'collection', collection_id, 'created', created, 'lastRun', last_run
+            )) as "harvest!"
+            FROM meta.harvests WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(harvest.map(|h| h.0))
+    }
+
+    async fn update_harvest(&self, harvest: &Harvest) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE meta.harvests
+            SET collection_id = $2,
+                definition = $3::jsonb - ARRAY['id', 'collection', 'created', 'lastRun', 'links']
+            WHERE id = $1
+            "#,
+        )
+        .bind(&harvest.id)
+        .bind(&harvest.collection)
+        .bind(sqlx::types::Json(harvest))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_harvest(&self, id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM meta.harvests WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_harvests(&self) -> anyhow::Result<Vec<Harvest>> {
+        let harvests: Vec<sqlx::types::Json<Harvest>> = sqlx::query_scalar(
+            r#"
+            SELECT definition || jsonb_strip_nulls(jsonb_build_object(
+                'id', id, 'collection', collection_id, 'created', created, 'lastRun', last_run
+            )) as "harvest!"
+            FROM meta.harvests ORDER BY created
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(harvests.into_iter().map(|h| h.0).collect())
+    }
+
+    async fn claim_run(&self, id: &str, time: DateTime<Utc>) -> anyhow::Result<bool> {
+        let claimed = sqlx::query(
+            r#"
+            UPDATE meta.harvests
+            SET last_run = $2
+            WHERE id = $1 AND (last_run IS NULL OR last_run < $2)
+            "#,
+        )
+        .bind(id)
+        .bind(time)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(claimed == 1)
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{Idempotency, IdempotencyKeys, StoredResponse};
+
+use super::Db;
+
+/// Time keys are kept, after which they may be reused
+const RETENTION: &str = "24 hours";
+
+/// Time keys of requests that never completed are held, e.g. after a crash
+const LEASE: &str = "5 minutes";
+
+#[async_trait::async_trait]
+impl IdempotencyKeys for Db {
+    async fn claim_key(
+        &self,
+        scope: &str,
+        key: &str,
+        request: &str,
+        fingerprint: &str,
+    ) -> anyhow::Result<Idempotency> {
+        // expired keys and lapsed claims
+        sqlx::query(
+            r#"
+            DELETE FROM meta.idempotency_keys
+            WHERE created < NOW() - $1::interval
+                OR (status IS NULL AND created < NOW() - $2::interval)
+            "#,
+        )
+        .bind(RETENTION)
+        .bind(LEASE)
+        .execute(&self.pool)
+        .await?;
+
+        let claimed: Option<String> = sqlx::query_scalar(
+            r#"
+            INSERT INTO meta.idempotency_keys (scope, key, request, fingerprint)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT DO NOTHING
+            RETURNING key
+            "#,
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(request)
+        .bind(fingerprint)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if claimed.is_some() {
+            return Ok(Idempotency::Claimed);
+        }
+
+        let row: Option<(
+            String,
+            String,
+            Option<i16>,
+            Option<sqlx::types::Json<Vec<(String, String)>>>,
+            Option<Vec<u8>>,
+        )> = sqlx::query_as(
+            r#"
+            SELECT request, fingerprint, status, headers, body
+            FROM meta.idempotency_keys
+            WHERE scope = $1 AND key = $2
+            "#,
+        )
+        .bind(scope)
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        // released in the meantime, the claiming request failed
+        let Some((request, fingerprint, status, headers, body)) = row else {
+            return Ok(Idempotency::InProgress {
+                request: request.to_owned(),
+                fingerprint: fingerprint.to_owned(),
+            });
+        };
+
+        Ok(match status {
+            Some(status) => Idempotency::Completed {
+                request,
+                fingerprint,
+                response: StoredResponse {
+                    status: status as u16,
+                    headers: headers.map(|h| h.0).unwrap_or_default(),
+                    body: body.unwrap_or_default(),
+                },
+            },
+            None => Idempotency::InProgress {
+                request,
+                fingerprint,
+            },
+        })
+    }
+
+    async fn complete_key(
+        &self,
+        scope: &str,
+        key: &str,
+        response: &StoredResponse,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE meta.idempotency_keys
+            SET status = $3, headers = $4, body = $5
+            WHERE scope = $1 AND key = $2
+            "#,
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(response.status as i16)
+        .bind(sqlx::types::Json(&response.headers))
+        .bind(&response.body)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn release_key(&self, scope: &str, key: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            "DELETE FROM meta.idempotency_keys WHERE scope = $1 AND key = $2 AND status IS NULL",
+        )
+        .bind(scope)
+        .bind(key)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
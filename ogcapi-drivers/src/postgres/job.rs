@@ -1,4 +1,4 @@
-use ogcapi_types::processes::{Results, StatusCode, StatusInfo};
+use ogcapi_types::processes::{JobQuery, Results, StatusCode, StatusInfo};
 
 use crate::JobHandler;
 
@@ -38,6 +38,35 @@ impl JobHandler for Db {
         Ok(status.map(|s| s.0))
     }
 
+    async fn update(&self, job: &StatusInfo) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE meta.jobs
+            SET status = $1 -> 'status',
+                message = $1 ->> 'message',
+                finished = CAST($1 ->> 'finished' AS timestamptz),
+                updated = NOW(),
+                progress = CAST($1 ->> 'progress' AS smallint)
+            WHERE job_id = $1 ->> 'jobID'
+            "#,
+        )
+        .bind(sqlx::types::Json(job))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_results(&self, id: &str, results: &Results) -> anyhow::Result<()> {
+        sqlx::query("UPDATE meta.jobs SET results = $2 WHERE job_id = $1")
+            .bind(id)
+            .bind(sqlx::types::Json(results))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     async fn dismiss(&self, id: &str) -> anyhow::Result<Option<StatusInfo>> {
         let status: Option<sqlx::types::Json<StatusInfo>> = sqlx::query_scalar(
             r#"
@@ -56,6 +85,25 @@ impl JobHandler for Db {
         Ok(status.map(|s| s.0))
     }
 
+    async fn list_jobs(&self, query: &JobQuery) -> anyhow::Result<Vec<StatusInfo>> {
+        let jobs: Vec<sqlx::types::Json<StatusInfo>> = sqlx::query_scalar(
+            r#"
+            SELECT row_to_json(jobs) as "status_info!"
+            FROM meta.jobs
+            WHERE $1::text IS NULL OR process_id = $1
+            ORDER BY created DESC NULLS LAST, job_id
+            OFFSET $2 LIMIT $3
+            "#,
+        )
+        .bind(&query.process_id)
+        .bind(query.offset.unwrap_or(0) as i64)
+        .bind(query.limit.map(|l| l as i64))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(jobs.into_iter().map(|j| j.0).collect())
+    }
+
     async fn results(&self, id: &str) -> anyhow::Result<Option<Results>> {
         let results: Option<sqlx::types::Json<Results>> = sqlx::query_scalar(
             r#"
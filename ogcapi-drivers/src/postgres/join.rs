@@ -0,0 +1,255 @@
+use serde_json::{Map, Value};
+
+use ogcapi_types::{
+    common::Collection,
+    joins::{DataFile, Join},
+};
+
+use crate::{CollectionTransactions, JoinTransactions};
+
+use super::Db;
+
+#[async_trait::async_trait]
+impl JoinTransactions for Db {
+    async fn create_file(
+        &self,
+        file: &DataFile,
+        rows: &[Map<String, Value>],
+    ) -> anyhow::Result<String> {
+        let mut tx = self.pool.begin().await?;
+
+        let (id,): (String,) = sqlx::query_as(
+            r#"
+            INSERT INTO meta.files (id, name, fields)
+            VALUES (COALESCE(NULLIF($1, ''), gen_random_uuid()::text), $2, $3)
+            RETURNING id
+            "#,
+        )
+        .bind(&file.id)
+        .bind(&file.name)
+        .bind(sqlx::types::Json(&file.fields))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for batch in rows.chunks(10000) {
+            let batch: Vec<sqlx::types::Json<&Map<String, Value>>> =
+                batch.iter().map(sqlx::types::Json).collect();
+
+            sqlx::query(
+                r#"
+                INSERT INTO meta.file_rows (file_id, row)
+                SELECT $1, * FROM UNNEST($2::jsonb[])
+                "#,
+            )
+            .bind(&id)
+            .bind(batch)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(id)
+    }
+
+    async fn read_file(&self, id: &str) -> anyhow::Result<Option<DataFile>> {
+        let file: Option<sqlx::types::Json<DataFile>> = sqlx::query_scalar(
+            r#"
+            SELECT json_build_object(
+                'id', id,
+                'name', name,
+                'fields', fields,
+                'numberOfRows', (SELECT count(*) FROM meta.file_rows WHERE file_id = $1)
+            ) as "file!"
+            FROM meta.files WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(file.map(|f| f.0))
+    }
+
+    async fn delete_file(&self, id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM meta.files WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_files(&self) -> anyhow::Result<Vec<DataFile>> {
+        let files: Option<sqlx::types::Json<Vec<DataFile>>> = sqlx::query_scalar(
+            r#"
+            SELECT array_to_json(array_agg(json_build_object(
+                'id', id,
+                'name', name,
+                'fields', fields
+            )))
+            FROM meta.files
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(files.map(|f| f.0).unwrap_or_default())
+    }
+
+    async fn create_join(&self, join: &Join) -> anyhow::Result<String> {
+        let (id,): (String,) = sqlx::query_as(
+            r#"
+            INSERT INTO meta.joins (id, definition)
+            VALUES (COALESCE(NULLIF($1 ->> 'id', ''), gen_random_uuid()::text), $1)
+            RETURNING id
+            "#,
+        )
+        .bind(sqlx::types::Json(join))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn read_join(&self, id: &str) -> anyhow::Result<Option<Join>> {
+        let join: Option<sqlx::types::Json<Join>> = sqlx::query_scalar(
+            r#"
+            SELECT definition || jsonb_build_object('id', id) as "join!"
+            FROM meta.joins WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(join.map(|j| j.0))
+    }
+
+    async fn delete_join(&self, id: &str) -> anyhow::Result<()> {
+        if let Some(join) = self.read_join(id).await? {
+            self.delete_collection(join.target()).await?;
+        }
+
+        sqlx::query("DELETE FROM meta.joins WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_joins(&self) -> anyhow::Result<Vec<Join>> {
+        let joins: Option<sqlx::types::Json<Vec<Join>>> = sqlx::query_scalar(
+            r#"
+            SELECT array_to_json(array_agg(definition || jsonb_build_object('id', id)))
+            FROM meta.joins
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(joins.map(|j| j.0).unwrap_or_default())
+    }
+
+    async fn execute_join(&self, join: &Join) -> anyhow::Result<()> {
+        let source = self
+            .read_collection(&join.collection_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Unknown collection `{}`", join.collection_id))?;
+
+        let target = join.target();
+
+        let collection = Collection {
+            id: target.to_owned(),
+            title: Some(format!(
+                "{} joined with file `{}`",
+                source.title.as_deref().unwrap_or(&source.id),
+                join.file_id
+            )),
+            links: Vec::new(),
+            ..source
+        };
+
+        if join.materialize {
+            self.create_collection(&collection).await?;
+
+            sqlx::query(&format!(
+                r#"
+                INSERT INTO items.{} (id, collection, properties, geom, links, assets, bbox)
+                {}
+                "#,
+                quote_ident(target),
+                select(&join.collection_id, ["$1", "$2", "$3", "$4"])
+            ))
+            .bind(&join.file_id)
+            .bind(&join.file_key)
+            .bind(&join.collection_key)
+            .bind(target)
+            .execute(&self.pool)
+            .await?;
+        } else {
+            let mut tx = self.pool.begin().await?;
+
+            // views do not support bind parameters
+            let select = select(
+                &join.collection_id,
+                [
+                    &quote_literal(&join.file_id),
+                    &quote_literal(&join.file_key),
+                    &quote_literal(&join.collection_key),
+                    &quote_literal(target),
+                ],
+            );
+
+            sqlx::query(&format!(
+                "CREATE VIEW items.{} AS {select}",
+                quote_ident(target)
+            ))
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query("INSERT INTO meta.collections ( id, collection ) VALUES ( $1, $2 )")
+                .bind(&collection.id)
+                .bind(sqlx::types::Json(&collection))
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Features of the source collection with the joined properties, which take
+/// precedence over the feature properties
+///
+/// The values are the file id, file key, collection key and target collection
+/// id, either as placeholders or quoted literals.
+fn select(source: &str, [file_id, file_key, collection_key, target]: [&str; 4]) -> String {
+    format!(
+        r#"
+        SELECT
+            items.id,
+            {target}::text AS collection,
+            COALESCE(items.properties, '{{}}'::jsonb) || (rows.row - {file_key}::text) AS properties,
+            items.geom,
+            items.links,
+            items.assets,
+            items.bbox
+        FROM items.{source} items
+        JOIN meta.file_rows rows
+            ON rows.file_id = {file_id} AND rows.row ->> {file_key}::text = items.properties ->> {collection_key}::text
+        "#,
+        source = quote_ident(source)
+    )
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn quote_ident(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use crate::Leases;
+
+use super::Db;
+
+#[async_trait::async_trait]
+impl Leases for Db {
+    async fn lead(&self, role: &str, holder: &str, lease: Duration) -> anyhow::Result<bool> {
+        let leading: Option<String> = sqlx::query_scalar(
+            r#"
+            INSERT INTO meta.leases (role, holder, lease_until)
+            VALUES ($1, $2, NOW() + make_interval(secs => $3))
+            ON CONFLICT (role) DO UPDATE
+            SET holder = EXCLUDED.holder, lease_until = EXCLUDED.lease_until
+            WHERE leases.holder = EXCLUDED.holder OR leases.lease_until < NOW()
+            RETURNING holder
+            "#,
+        )
+        .bind(role)
+        .bind(holder)
+        .bind(lease.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(leading.is_some())
+    }
+}
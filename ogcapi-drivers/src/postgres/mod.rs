@@ -1,19 +1,44 @@
+mod access;
+mod aggregate;
+mod attachment;
+mod change;
 mod collection;
+mod computed;
+mod cql2;
+mod doctor;
 mod edr;
 mod feature;
+mod harvest;
+mod idempotency;
 mod job;
+mod join;
+mod lease;
+mod mode;
+mod queue;
+mod quota;
+mod reproject;
 #[cfg(feature = "stac")]
 mod stac;
+mod stats;
 mod style;
 mod tile;
+mod user;
+mod validation;
+mod view;
+mod webhook;
+
+use std::{str::FromStr, time::Duration};
 
 use sqlx::{
     migrate::MigrateDatabase,
-    postgres::{PgConnectOptions, PgPool, PgPoolOptions},
-    Postgres,
+    postgres::{PgConnectOptions, PgConnection, PgPool, PgPoolOptions},
+    Connection, Postgres,
 };
 use url::Url;
 
+/// Database transaction, rolled back when dropped without commit
+pub type Transaction = sqlx::Transaction<'static, Postgres>;
+
 #[derive(Debug, Clone)]
 pub struct Db {
     pub pool: PgPool,
@@ -37,22 +62,48 @@ impl Db {
         Ok(Db { pool })
     }
 
+    /// Create driver without connecting, the pool connects on first use
+    /// with `PGUSER` and friends
+    pub fn lazy() -> Self {
+        let pool = PgPoolOptions::new()
+            .max_connections(8)
+            .connect_lazy_with(PgConnectOptions::new());
+
+        Db { pool }
+    }
+
     /// Setup database driver from url
     pub async fn setup(url: &Url) -> Result<Self, sqlx::Error> {
+        Db::setup_with(url, None).await
+    }
+
+    /// Setup database driver from url, aborting statements running longer
+    /// than the timeout
+    pub async fn setup_with(
+        url: &Url,
+        statement_timeout: Option<Duration>,
+    ) -> Result<Self, sqlx::Error> {
         // Create database if not exists
         if !Postgres::database_exists(url.as_str()).await? {
             Postgres::create_database(url.as_str()).await?
         }
 
+        // Run embedded migrations, not subject to the timeout
+        let mut conn = PgConnection::connect(url.as_str()).await?;
+        sqlx::migrate!().run(&mut conn).await?;
+        conn.close().await?;
+
         // Create pool
+        let mut options = PgConnectOptions::from_str(url.as_str())?;
+        if let Some(timeout) = statement_timeout {
+            options = options.options([("statement_timeout", timeout.as_millis().to_string())]);
+        }
+
         let pool = PgPoolOptions::new()
             .max_connections(8)
-            .connect(url.as_str())
+            .connect_with(options)
             .await?;
 
-        // Run embedded migrations
-        sqlx::migrate!().run(&pool).await?;
-
         Ok(Db { pool })
     }
 }
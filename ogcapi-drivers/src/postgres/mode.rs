@@ -0,0 +1,29 @@
+use crate::ModeTransactions;
+
+use super::Db;
+
+#[async_trait::async_trait]
+impl ModeTransactions for Db {
+    async fn read_mode(&self) -> anyhow::Result<Option<String>> {
+        let mode = sqlx::query_scalar("SELECT value FROM meta.settings WHERE name = 'mode'")
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(mode)
+    }
+
+    async fn write_mode(&self, mode: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO meta.settings (name, value) VALUES ('mode', $1)
+            ON CONFLICT (name) DO UPDATE
+            SET value = EXCLUDED.value, updated = NOW()
+            "#,
+        )
+        .bind(mode)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::{JobQueue, QueuedJob};
+
+use super::Db;
+
+#[async_trait::async_trait]
+impl JobQueue for Db {
+    async fn enqueue(
+        &self,
+        job_id: &str,
+        process_id: &str,
+        inputs: &Value,
+        key: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO meta.job_queue (job_id, process_id, inputs, key) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(job_id)
+        .bind(process_id)
+        .bind(sqlx::types::Json(inputs))
+        .bind(key)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn claim_job(&self, worker: &str, lease: Duration) -> anyhow::Result<Option<QueuedJob>> {
+        // unclaimed jobs which were dismissed or finished meanwhile
+        sqlx::query(
+            r#"
+            DELETE FROM meta.job_queue q
+            USING meta.jobs j
+            WHERE q.job_id = j.job_id
+                AND NOT j.status <@ '["accepted", "running"]'::jsonb
+                AND (q.lease_until IS NULL OR q.lease_until < NOW())
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let job: Option<(
+            String,
+            String,
+            sqlx::types::Json<Value>,
+            i32,
+            Option<String>,
+        )> = sqlx::query_as(
+            r#"
+            UPDATE meta.job_queue
+            SET worker = $1,
+                lease_until = NOW() + make_interval(secs => $2),
+                attempts = attempts + 1
+            WHERE job_id = (
+                SELECT job_id FROM meta.job_queue
+                WHERE lease_until IS NULL OR lease_until < NOW()
+                ORDER BY queued
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING job_id, process_id, inputs, attempts, key
+            "#,
+        )
+        .bind(worker)
+        .bind(lease.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(
+            job.map(|(job_id, process_id, inputs, attempts, key)| QueuedJob {
+                job_id,
+                process_id,
+                inputs: inputs.0,
+                attempts: attempts as u32,
+                key,
+            }),
+        )
+    }
+
+    async fn heartbeat(&self, job_id: &str, worker: &str, lease: Duration) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE meta.job_queue
+            SET lease_until = NOW() + make_interval(secs => $3)
+            WHERE job_id = $1 AND worker = $2
+            "#,
+        )
+        .bind(job_id)
+        .bind(worker)
+        .bind(lease.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn complete_job(&self, job_id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM meta.job_queue WHERE job_id = $1")
+            .bind(job_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
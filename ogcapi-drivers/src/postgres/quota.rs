@@ -0,0 +1,127 @@
+use ogcapi_types::auth::{KeyUsage, Quota};
+
+use crate::QuotaTransactions;
+
+use super::Db;
+
+#[async_trait::async_trait]
+impl QuotaTransactions for Db {
+    async fn record_key_usage(&self, usage: &[KeyUsage]) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO meta.key_usage (key_id, month, requests, features, tiles, job_seconds)
+            SELECT * FROM UNNEST($1::text[], $2::text[], $3::int8[], $4::int8[], $5::int8[], $6::float8[])
+            ON CONFLICT (key_id, month) DO UPDATE
+            SET requests = key_usage.requests + EXCLUDED.requests,
+                features = key_usage.features + EXCLUDED.features,
+                tiles = key_usage.tiles + EXCLUDED.tiles,
+                job_seconds = key_usage.job_seconds + EXCLUDED.job_seconds,
+                updated = NOW()
+            "#,
+        )
+        .bind(usage.iter().map(|u| u.key.to_owned()).collect::<Vec<_>>())
+        .bind(usage.iter().map(|u| u.month.to_owned()).collect::<Vec<_>>())
+        .bind(usage.iter().map(|u| u.requests as i64).collect::<Vec<_>>())
+        .bind(usage.iter().map(|u| u.features as i64).collect::<Vec<_>>())
+        .bind(usage.iter().map(|u| u.tiles as i64).collect::<Vec<_>>())
+        .bind(usage.iter().map(|u| u.job_seconds).collect::<Vec<_>>())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn key_usage(
+        &self,
+        user: Option<&str>,
+        month: Option<&str>,
+    ) -> anyhow::Result<Vec<KeyUsage>> {
+        let rows: Vec<(String, String, i64, i64, i64, f64)> = sqlx::query_as(
+            r#"
+            SELECT u.key_id, u.month, u.requests, u.features, u.tiles, u.job_seconds
+            FROM meta.key_usage u
+            WHERE ($1::text IS NULL OR u.key_id IN (
+                    SELECT id FROM meta.api_keys WHERE user_id = $1
+                ))
+                AND ($2::text IS NULL OR u.month = $2)
+            ORDER BY u.month DESC, u.key_id
+            "#,
+        )
+        .bind(user)
+        .bind(month)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(key, month, requests, features, tiles, job_seconds)| KeyUsage {
+                    key,
+                    month,
+                    requests: requests as u64,
+                    features: features as u64,
+                    tiles: tiles as u64,
+                    job_seconds,
+                },
+            )
+            .collect())
+    }
+
+    async fn set_quota(&self, quota: &Quota) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO meta.quotas (key_id, requests, features, tiles, job_seconds)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (key_id) DO UPDATE
+            SET requests = EXCLUDED.requests,
+                features = EXCLUDED.features,
+                tiles = EXCLUDED.tiles,
+                job_seconds = EXCLUDED.job_seconds
+            "#,
+        )
+        .bind(&quota.key)
+        .bind(quota.requests.map(|n| n as i64))
+        .bind(quota.features.map(|n| n as i64))
+        .bind(quota.tiles.map(|n| n as i64))
+        .bind(quota.job_seconds)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_quota(&self, key: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM meta.quotas WHERE key_id = $1")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_quotas(&self, user: Option<&str>) -> anyhow::Result<Vec<Quota>> {
+        let rows: Vec<(String, Option<i64>, Option<i64>, Option<i64>, Option<f64>)> =
+            sqlx::query_as(
+                r#"
+                SELECT q.key_id, q.requests, q.features, q.tiles, q.job_seconds
+                FROM meta.quotas q JOIN meta.api_keys k ON k.id = q.key_id
+                WHERE $1::text IS NULL OR k.user_id = $1
+                ORDER BY q.key_id
+                "#,
+            )
+            .bind(user)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(key, requests, features, tiles, job_seconds)| Quota {
+                key,
+                requests: requests.map(|n| n as u64),
+                features: features.map(|n| n as u64),
+                tiles: tiles.map(|n| n as u64),
+                job_seconds,
+            })
+            .collect())
+    }
+}
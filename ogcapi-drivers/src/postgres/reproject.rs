@@ -0,0 +1,171 @@
+use ogcapi_types::common::Crs;
+
+use crate::CollectionTransactions;
+
+use super::{tile::GENERALIZED_ZOOMS, Db};
+
+impl Db {
+    /// Reproject the geometries of up to `limit` more features into the
+    /// column `geom_reprojected`, which is added on the first batch
+    pub(crate) async fn reproject_batch(
+        &self,
+        collection: &str,
+        crs: &Crs,
+        limit: usize,
+    ) -> anyhow::Result<(u64, u64)> {
+        let srid = crs.as_srid();
+
+        let started: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT FROM information_schema.columns
+                WHERE table_schema = 'items' AND table_name = $1 AND column_name = 'geom_reprojected'
+            )
+            "#,
+        )
+        .bind(collection)
+        .fetch_one(&self.pool)
+        .await?;
+
+        // a reprojection to another crs is started over
+        if started {
+            let previous: Option<i32> = sqlx::query_scalar(&format!(
+                r#"SELECT ST_SRID(geom_reprojected) FROM items."{collection}" WHERE geom_reprojected IS NOT NULL LIMIT 1"#
+            ))
+            .fetch_optional(&self.pool)
+            .await?;
+            if previous.is_some_and(|previous| previous != srid) {
+                sqlx::query(&format!(
+                    r#"UPDATE items."{collection}" SET geom_reprojected = NULL"#
+                ))
+                .execute(&self.pool)
+                .await?;
+            }
+        } else {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query(&format!(
+                r#"ALTER TABLE items."{collection}" ADD COLUMN geom_reprojected geometry"#
+            ))
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(&format!(
+                r#"
+                CREATE TRIGGER reset_reprojected_geom
+                BEFORE UPDATE OF geom ON items."{collection}"
+                FOR EACH ROW EXECUTE FUNCTION meta.reset_reprojected_geom()
+                "#
+            ))
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+        }
+
+        sqlx::query(&format!(
+            r#"
+            UPDATE items."{collection}" SET geom_reprojected = ST_Transform(geom, {srid})
+            WHERE id IN (
+                SELECT id FROM items."{collection}" WHERE geom_reprojected IS NULL
+                LIMIT {limit} FOR UPDATE SKIP LOCKED
+            )
+            "#
+        ))
+        .execute(&self.pool)
+        .await?;
+
+        let (done, total): (i64, i64) = sqlx::query_as(&format!(
+            r#"
+            SELECT count(*) FILTER (WHERE geom_reprojected IS NOT NULL), count(*)
+            FROM items."{collection}"
+            "#
+        ))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((done as u64, total as u64))
+    }
+
+    /// Swap the reprojected geometries in and declare the storage crs at once
+    pub(crate) async fn swap_reprojected(&self, collection: &str, crs: &Crs) -> anyhow::Result<()> {
+        let Some(mut document) = self.read_collection(collection).await? else {
+            anyhow::bail!("Unknown collection `{collection}`");
+        };
+        let srid = crs.as_srid();
+        let generalized = self.generalized_zoom(collection, 0).await?.is_some();
+
+        let mut tx = self.pool.begin().await?;
+
+        // writes wait for the swap, reads go on
+        sqlx::query(&format!(
+            r#"LOCK TABLE items."{collection}" IN SHARE ROW EXCLUSIVE MODE"#
+        ))
+        .execute(&mut *tx)
+        .await?;
+
+        // features written since their batch
+        sqlx::query(&format!(
+            r#"
+            UPDATE items."{collection}" SET geom_reprojected = ST_Transform(geom, {srid})
+            WHERE geom_reprojected IS NULL
+            "#
+        ))
+        .execute(&mut *tx)
+        .await?;
+
+        for statement in [
+            format!(r#"DROP TRIGGER reset_reprojected_geom ON items."{collection}""#),
+            format!(r#"DROP TRIGGER IF EXISTS reset_generalized ON items."{collection}""#),
+            format!(r#"ALTER TABLE items."{collection}" DROP COLUMN zrange, DROP COLUMN geom"#),
+            format!(r#"ALTER TABLE items."{collection}" RENAME COLUMN geom_reprojected TO geom"#),
+            format!(
+                r#"
+                ALTER TABLE items."{collection}"
+                    ALTER COLUMN geom SET NOT NULL,
+                    ADD COLUMN zrange numrange GENERATED ALWAYS AS (meta.zrange(geom)) STORED
+                "#
+            ),
+            format!(r#"CREATE INDEX ON items."{collection}" USING gist (geom)"#),
+            format!(r#"CREATE INDEX ON items."{collection}" USING gist (zrange)"#),
+        ] {
+            sqlx::query(&statement).execute(&mut *tx).await?;
+        }
+
+        // generalized geometries are computed again by the next generalization
+        if generalized {
+            let columns: Vec<String> = GENERALIZED_ZOOMS
+                .iter()
+                .map(|z| format!("geom_z{z} = NULL"))
+                .collect();
+            for statement in [
+                format!(r#"UPDATE items."{collection}" SET {}"#, columns.join(", ")),
+                format!(
+                    r#"
+                    CREATE TRIGGER reset_generalized BEFORE UPDATE OF geom ON items."{collection}"
+                    FOR EACH ROW EXECUTE FUNCTION meta.reset_generalized()
+                    "#
+                ),
+            ] {
+                sqlx::query(&statement).execute(&mut *tx).await?;
+            }
+        }
+
+        sqlx::query("SELECT UpdateGeometrySRID('items', $1, 'geom', $2)")
+            .bind(collection)
+            .bind(srid)
+            .execute(&mut *tx)
+            .await?;
+
+        if !document.crs.contains(crs) {
+            document.crs.push(crs.to_owned());
+        }
+        document.storage_crs = Some(crs.to_owned());
+        sqlx::query("UPDATE meta.collections SET collection = $2 WHERE id = $1")
+            .bind(collection)
+            .bind(sqlx::types::Json(&document))
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
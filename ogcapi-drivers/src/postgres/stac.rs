@@ -1,5 +1,4 @@
 use ogcapi_types::{
-    common::{Bbox, Datetime, IntervalDatetime},
     features::{Feature, FeatureCollection},
     stac::SearchParams,
 };
@@ -31,6 +30,14 @@ impl StacSeach for Db {
                 .collect();
         }
 
+        if collection_ids.is_empty() {
+            tx.commit().await?;
+
+            let mut fc = FeatureCollection::new(Vec::new());
+            fc.number_matched = Some(0);
+            return Ok(fc);
+        }
+
         let union_all_items = collection_ids
             .iter()
             .map(|collection_id| {
@@ -48,41 +55,27 @@ impl StacSeach for Db {
 
         // bbox
         if let Some(bbox) = query.bbox.as_ref() {
-            let envelope = match bbox {
-                Bbox::Bbox2D(bbox) => format!(
-                    "ST_MakeEnvelope({}, {}, {}, {}, 4326)",
-                    bbox[0], bbox[1], bbox[2], bbox[3]
-                ),
-                Bbox::Bbox3D(bbox) => format!(
-                    "ST_MakeEnvelope({}, {}, {}, {}, 4326)",
-                    bbox[0], bbox[1], bbox[3], bbox[4]
-                ),
-            };
+            let [minx, miny, maxx, maxy] = bbox.to_2d();
+            let envelope = format!("ST_MakeEnvelope({minx}, {miny}, {maxx}, {maxy}, 4326)");
             where_conditions.push(format!("geom && {}", envelope));
+
+            if let Some((minz, maxz)) = bbox.z_range() {
+                where_conditions.push(format!(
+                    "(zrange IS NULL OR zrange && numrange({minz}, {maxz}, '[]'))"
+                ));
+            }
         }
 
         // datetime
         if let Some(datetime) = query.datetime.as_ref() {
-            let (from, to) = match datetime {
-                Datetime::Datetime(_) => (
-                    format!("CAST('{datetime}' AS timestamptz)"),
-                    format!("CAST('{datetime}' AS timestamptz)"),
-                ),
-                Datetime::Interval { from, to } => {
-                    let from = match from {
-                        IntervalDatetime::Datetime(_) => {
-                            format!("CAST('{from}' AS timestamptz)")
-                        }
-                        IntervalDatetime::Open => "to_timestamp('-infinity')".to_owned(),
-                    };
-                    let to = match to {
-                        IntervalDatetime::Datetime(_) => {
-                            format!("CAST('{to}' AS timestamptz)")
-                        }
-                        IntervalDatetime::Open => "NOW()".to_owned(),
-                    };
-                    (from, to)
-                }
+            let interval = datetime.interval();
+            let from = match interval.start {
+                Some(start) => format!("CAST('{}' AS timestamptz)", start.to_rfc3339()),
+                None => "to_timestamp('-infinity')".to_owned(),
+            };
+            let to = match interval.end {
+                Some(end) => format!("CAST('{}' AS timestamptz)", end.to_rfc3339()),
+                None => "NOW()".to_owned(),
             };
 
             where_conditions.push(format!(
@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use sqlx::types::Json;
+
+use ogcapi_types::{
+    common::Bbox,
+    features::{
+        Bin, CollectionStats, Histogram, PropertyStats, PropertyValues, StatsQuery, ValueCount,
+        ValuesQuery,
+    },
+};
+
+use crate::CollectionTransactions;
+
+use super::Db;
+
+/// Collections with more features are described by a sample of about this size
+const SAMPLE_SIZE: f64 = 100_000.0;
+
+/// Maximum number of properties described if none are requested
+const MAX_PROPERTIES: i64 = 100;
+
+/// Maximum number of distinct values listed at once
+const MAX_VALUES: usize = 1000;
+
+impl Db {
+    pub(crate) async fn collection_stats(
+        &self,
+        collection: &str,
+        query: &StatsQuery,
+    ) -> anyhow::Result<CollectionStats> {
+        let table = format!(r#"items."{collection}""#);
+
+        // planner estimate, negative for tables which were never analyzed
+        let estimate: f32 =
+            sqlx::query_scalar("SELECT reltuples FROM pg_class WHERE oid = $1::regclass")
+                .bind(&table)
+                .fetch_one(&self.pool)
+                .await?;
+        let estimate = estimate as f64;
+
+        // a repeatable sample, so that all statistics describe the same features
+        let (source, exact) = if estimate > SAMPLE_SIZE {
+            let percent = SAMPLE_SIZE / estimate * 100.0;
+            (
+                format!("{table} TABLESAMPLE SYSTEM ({percent}) REPEATABLE (0)"),
+                false,
+            )
+        } else {
+            (table.to_owned(), true)
+        };
+
+        let sample_size: i64 = sqlx::query_scalar(&format!("SELECT count(*) FROM {source}"))
+            .fetch_one(&self.pool)
+            .await?;
+
+        let geometry_types: Vec<(String, i64)> = sqlx::query_as(&format!(
+            r#"
+            SELECT replace(ST_GeometryType(geom), 'ST_', ''), count(*)
+            FROM {source} GROUP BY 1
+            "#
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let extent = if exact {
+            self.extent(collection).await?
+        } else {
+            // requires planner statistics, which tables this large have
+            sqlx::query_as::<_, (f64, f64, f64, f64)>(
+                r#"
+                SELECT ST_XMin(e), ST_YMin(e), ST_XMax(e), ST_YMax(e)
+                FROM (SELECT ST_EstimatedExtent('items', $1, 'geom') AS e) t
+                WHERE e IS NOT NULL
+                "#,
+            )
+            .bind(collection)
+            .fetch_optional(&self.pool)
+            .await
+            .ok()
+            .flatten()
+            .map(|(minx, miny, maxx, maxy)| Bbox::Bbox2D([minx, miny, maxx, maxy]))
+        };
+
+        let names = match query.properties() {
+            Some(names) => names,
+            None => {
+                sqlx::query_scalar(&format!(
+                    r#"
+                    SELECT DISTINCT jsonb_object_keys(properties) AS key
+                    FROM {source} WHERE jsonb_typeof(properties) = 'object'
+                    ORDER BY key LIMIT $1
+                    "#
+                ))
+                .bind(MAX_PROPERTIES)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        let mut properties = BTreeMap::new();
+        for name in names {
+            let stats = self.property_stats(&source, &name, query).await?;
+            properties.insert(name, stats);
+        }
+
+        Ok(CollectionStats {
+            number_matched: if exact {
+                sample_size as u64
+            } else {
+                estimate as u64
+            },
+            exact,
+            sample_size: sample_size as u64,
+            extent,
+            geometry_types: geometry_types
+                .into_iter()
+                .map(|(kind, count)| (kind, count as u64))
+                .collect(),
+            properties,
+        })
+    }
+
+    async fn property_stats(
+        &self,
+        source: &str,
+        name: &str,
+        query: &StatsQuery,
+    ) -> anyhow::Result<PropertyStats> {
+        let types: Vec<(String, i64)> = sqlx::query_as(&format!(
+            r#"
+            SELECT jsonb_typeof(properties -> $1), count(*)
+            FROM {source}
+            WHERE jsonb_typeof(properties -> $1) <> 'null'
+            GROUP BY 1 ORDER BY 2 DESC
+            "#
+        ))
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let count = types.iter().map(|(_, count)| *count as u64).sum();
+
+        let (distinct, histogram) = match types.first().map(|(t, _)| t.as_str()) {
+            Some("number") => self.numeric_histogram(source, name, query).await?,
+            Some("string" | "boolean") => self.categorical_histogram(source, name, query).await?,
+            _ => (None, None),
+        };
+
+        Ok(PropertyStats {
+            count,
+            distinct,
+            histogram,
+        })
+    }
+
+    async fn numeric_histogram(
+        &self,
+        source: &str,
+        name: &str,
+        query: &StatsQuery,
+    ) -> anyhow::Result<(Option<u64>, Option<Histogram>)> {
+        let values = format!(
+            r#"
+            SELECT (properties ->> $1)::float8 AS v FROM {source}
+            WHERE jsonb_typeof(properties -> $1) = 'number'
+            "#
+        );
+
+        let (min, max, distinct): (Option<f64>, Option<f64>, i64) = sqlx::query_as(&format!(
+            "SELECT min(v), max(v), count(DISTINCT v) FROM ({values}) t"
+        ))
+        .bind(name)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let (Some(min), Some(max)) = (min, max) else {
+            return Ok((Some(distinct as u64), None));
+        };
+
+        let bins = query.bins.clamp(1, 100) as i32;
+
+        let counts = if min == max {
+            let total: i64 = sqlx::query_scalar(&format!("SELECT count(*) FROM ({values}) t"))
+                .bind(name)
+                .fetch_one(&self.pool)
+                .await?;
+            vec![total as u64]
+        } else {
+            // the maximum falls into the bucket after the last one
+            let buckets: Vec<(i32, i64)> = sqlx::query_as(&format!(
+                r#"
+                SELECT least(width_bucket(v, $2, $3, $4), $4), count(*)
+                FROM ({values}) t GROUP BY 1
+                "#
+            ))
+            .bind(name)
+            .bind(min)
+            .bind(max)
+            .bind(bins)
+            .fetch_all(&self.pool)
+            .await?;
+
+            let mut counts = vec![0; bins as usize];
+            for (bucket, count) in buckets {
+                if let Some(c) = counts.get_mut((bucket - 1) as usize) {
+                    *c = count as u64;
+                }
+            }
+            counts
+        };
+
+        Ok((
+            Some(distinct as u64),
+            Some(Histogram::Numeric {
+                min,
+                max,
+                bins: Bin::equal_width(min, max, &counts),
+            }),
+        ))
+    }
+
+    async fn categorical_histogram(
+        &self,
+        source: &str,
+        name: &str,
+        query: &StatsQuery,
+    ) -> anyhow::Result<(Option<u64>, Option<Histogram>)> {
+        let condition = "jsonb_typeof(properties -> $1) IN ('string', 'boolean')";
+
+        let distinct: i64 = sqlx::query_scalar(&format!(
+            "SELECT count(DISTINCT properties -> $1) FROM {source} WHERE {condition}"
+        ))
+        .bind(name)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let values: Vec<(Json<Value>, i64)> = sqlx::query_as(&format!(
+            r#"
+            SELECT properties -> $1, count(*) FROM {source}
+            WHERE {condition}
+            GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT $2
+            "#
+        ))
+        .bind(name)
+        .bind(query.values.clamp(1, 100) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((
+            Some(distinct as u64),
+            Some(Histogram::Categorical {
+                values: values
+                    .into_iter()
+                    .map(|(value, count)| ValueCount {
+                        value: value.0,
+                        count: count as u64,
+                    })
+                    .collect(),
+            }),
+        ))
+    }
+
+    /// Distinct scalar values of a property over all features, most
+    /// frequent first
+    pub(crate) async fn distinct_values(
+        &self,
+        collection: &str,
+        property: &str,
+        query: &ValuesQuery,
+    ) -> anyhow::Result<PropertyValues> {
+        let condition = r#"
+            jsonb_typeof(properties -> $1) IN ('string', 'number', 'boolean')
+            AND ($2::text IS NULL OR starts_with(lower(properties ->> $1), lower($2)))
+        "#;
+
+        let distinct: i64 = sqlx::query_scalar(&format!(
+            r#"
+            SELECT count(DISTINCT properties -> $1) FROM items."{collection}"
+            WHERE {condition}
+            "#
+        ))
+        .bind(property)
+        .bind(query.prefix.as_deref())
+        .fetch_one(&self.pool)
+        .await?;
+
+        let values: Vec<(Json<Value>, i64)> = sqlx::query_as(&format!(
+            r#"
+            SELECT properties -> $1, count(*) FROM items."{collection}"
+            WHERE {condition}
+            GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT $3
+            "#
+        ))
+        .bind(property)
+        .bind(query.prefix.as_deref())
+        .bind(query.limit.clamp(1, MAX_VALUES) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(PropertyValues {
+            property: property.to_owned(),
+            distinct: distinct as u64,
+            values: values
+                .into_iter()
+                .map(|(value, count)| ValueCount {
+                    value: value.0,
+                    count: count as u64,
+                })
+                .collect(),
+        })
+    }
+}
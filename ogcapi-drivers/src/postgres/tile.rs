@@ -1,9 +1,25 @@
-use ogcapi_types::tiles::TileMatrixSet;
+use serde_json::Value;
+
+use ogcapi_types::{
+    common::Collection,
+    tiles::{Aggregation, CellGrid, TileMatrixSet, TileUsage},
+};
 
 use crate::{CollectionTransactions, TileTransactions};
 
 use super::Db;
 
+/// Width of the web mercator world in meters
+const WORLD_SIZE: f64 = 2.0 * 20037508.342789244;
+
+/// Zoom levels of the generalized geometries, tiles are rendered from the
+/// first at or above their zoom level, tiles of higher levels from the full
+/// geometries
+pub(super) const GENERALIZED_ZOOMS: [i32; 3] = [4, 8, 12];
+
+/// Number of features generalized per statement
+const GENERALIZE_BATCH: i64 = 1000;
+
 #[async_trait::async_trait]
 impl TileTransactions for Db {
     async fn tile(
@@ -13,37 +29,378 @@ impl TileTransactions for Db {
         matrix: &str,
         row: u32,
         col: u32,
+        user: Option<&str>,
+        labels: bool,
     ) -> anyhow::Result<Vec<u8>> {
+        let zoom = matrix.parse::<i32>()?;
+
         let mut sql: Vec<String> = Vec::new();
 
         for collection in collections.split(',') {
             if let Some(c) = self.read_collection(collection).await? {
-                let storage_srid = c.storage_crs.unwrap_or_default().as_srid();
+                let storage_srid = c.storage_crs.to_owned().unwrap_or_default().as_srid();
 
+                // geometries not generalized yet are simplified by ST_AsMVTGeom
+                let geom = match self.generalized_zoom(collection, zoom).await? {
+                    Some(z) => format!("COALESCE(geom_z{z}, ST_Transform(ST_Force2D(geom), 3857))"),
+                    None => "ST_Transform(ST_Force2D(geom), 3857)".to_string(),
+                };
+
+                let hidden = c.hidden_properties(user);
+                let layers = layers(&c, &hidden);
+                let hidden: Vec<String> = hidden.iter().map(|p| literal(p)).collect();
+
+                for (layer, filter) in layers {
+                    sql.push(format!(
+                        r#"
+                        SELECT ST_AsMVT(mvtgeom, {4}, 4096, 'geom')
+                        FROM (
+                            SELECT
+                                ST_AsMVTGeom({3}, ST_TileEnvelope($1, $3, $2), 4096, 64, TRUE) AS geom,
+                                '{0}' as collection,
+                                properties - ARRAY[{2}]::text[] AS properties
+                            FROM items.{0}
+                            WHERE geom && ST_Transform(ST_TileEnvelope($1, $3, $2, margin => (64.0 / 4096)), {1})
+                                AND {5}
+                        ) AS mvtgeom
+                        "#,
+                        collection,
+                        storage_srid,
+                        hidden.join(", "),
+                        geom,
+                        literal(&layer),
+                        filter
+                    ));
+                }
+
+                // label points are only rendered in the tile containing them,
+                // so that labels are not repeated across tiles
+                if labels {
+                    sql.push(format!(
+                        r#"
+                        SELECT ST_AsMVT(mvtgeom, '{0}_labels', 4096, 'geom')
+                        FROM (
+                            SELECT
+                                ST_AsMVTGeom(label, ST_TileEnvelope($1, $3, $2), 4096, 0, TRUE) AS geom,
+                                '{0}' as collection,
+                                properties
+                            FROM (
+                                SELECT
+                                    CASE ST_Dimension(geom)
+                                        WHEN 2 THEN ST_PointOnSurface(geom)
+                                        ELSE ST_LineInterpolatePoint(ST_GeometryN(ST_LineMerge(geom), 1), 0.5)
+                                    END AS label,
+                                    properties - ARRAY[{2}]::text[] AS properties
+                                FROM (
+                                    SELECT ST_Transform(ST_Force2D(geom), 3857) AS geom, properties
+                                    FROM items.{0}
+                                    WHERE geom && ST_Transform(ST_TileEnvelope($1, $3, $2), {1})
+                                        AND ST_Dimension(geom) > 0
+                                ) AS g
+                            ) AS l
+                            WHERE ST_Intersects(label, ST_TileEnvelope($1, $3, $2))
+                        ) AS mvtgeom
+                        "#,
+                        collection,
+                        storage_srid,
+                        hidden.join(", ")
+                    ));
+                }
+            };
+        }
+
+        let tiles: Vec<Vec<u8>> = sqlx::query_scalar(&sql.join(" UNION ALL "))
+            .bind(zoom)
+            .bind(row as i32)
+            .bind(col as i32)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(tiles.concat())
+    }
+
+    async fn aggregated_tile(
+        &self,
+        collections: &str,
+        _tms: &TileMatrixSet,
+        matrix: &str,
+        row: u32,
+        col: u32,
+        aggregation: &Aggregation,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        let zoom = matrix.parse::<i32>()?;
+
+        // cells keep their size on screen, the grid is aligned across tiles
+        let cell_size = WORLD_SIZE / 2f64.powi(zoom) * aggregation.cell_size as f64 / 256.0;
+        let grid = match aggregation.grid {
+            CellGrid::Hexagon => "ST_HexagonGrid",
+            CellGrid::Square => "ST_SquareGrid",
+        };
+
+        let mut sql: Vec<String> = Vec::new();
+
+        for collection in collections.split(',') {
+            if let Some(c) = self.read_collection(collection).await? {
+                let storage_srid = c.storage_crs.to_owned().unwrap_or_default().as_srid();
+
+                // points of cells reaching into neighbouring tiles are
+                // counted as well
                 sql.push(format!(
                     r#"
                     SELECT ST_AsMVT(mvtgeom, '{0}', 4096, 'geom')
                     FROM (
                         SELECT
-                            ST_AsMVTGeom(ST_Transform(ST_Force2D(geom), 3857), ST_TileEnvelope($1, $3, $2), 4096, 64, TRUE) AS geom,
-                            '{0}' as collection,
-                            properties
-                        FROM items.{0}
-                        WHERE geom && ST_Transform(ST_TileEnvelope($1, $3, $2, margin => (64.0 / 4096)), {1})
+                            ST_AsMVTGeom(cell.geom, ST_TileEnvelope($1, $3, $2), 4096, 64, TRUE) AS geom,
+                            count(*) AS count,
+                            avg((p.properties ->> $4)::float8)
+                                FILTER (WHERE jsonb_typeof(p.properties -> $4) = 'number') AS avg
+                        FROM {2}($5, ST_TileEnvelope($1, $3, $2)) AS cell
+                        JOIN (
+                            SELECT ST_Transform(ST_Force2D(geom), 3857) AS geom, properties
+                            FROM items.{0}
+                            WHERE geom && ST_Transform(ST_Expand(ST_TileEnvelope($1, $3, $2), $5), {1})
+                        ) AS p ON ST_Intersects(cell.geom, p.geom)
+                        GROUP BY cell.geom
                     ) AS mvtgeom
                     "#,
-                    collection,storage_srid
+                    collection, storage_srid, grid
                 ));
             };
         }
 
+        if sql.is_empty() {
+            return Ok(Some(Vec::new()));
+        }
+
         let tiles: Vec<Vec<u8>> = sqlx::query_scalar(&sql.join(" UNION ALL "))
-            .bind(matrix.parse::<i32>().unwrap())
+            .bind(zoom)
             .bind(row as i32)
             .bind(col as i32)
+            .bind(&aggregation.parameter)
+            .bind(cell_size)
             .fetch_all(&self.pool)
             .await?;
 
-        Ok(tiles.concat())
+        Ok(Some(tiles.concat()))
+    }
+
+    async fn generalize(&self) -> anyhow::Result<()> {
+        let collections: Vec<String> = sqlx::query_scalar("SELECT id FROM meta.collections")
+            .fetch_all(&self.pool)
+            .await?;
+
+        for collection in collections {
+            self.generalize_collection(&collection).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn record_usage(&self, usage: &[TileUsage]) -> anyhow::Result<()> {
+        // usage of unknown or deleted collections is dropped
+        sqlx::query(
+            r#"
+            INSERT INTO meta.tile_usage (collection_id, tile_matrix, requests, hits)
+            SELECT u.collection_id, u.tile_matrix, u.requests, u.hits
+            FROM UNNEST($1::text[], $2::text[], $3::int8[], $4::int8[])
+                AS u(collection_id, tile_matrix, requests, hits)
+            JOIN meta.collections c ON c.id = u.collection_id
+            ON CONFLICT (collection_id, tile_matrix) DO UPDATE
+            SET requests = tile_usage.requests + EXCLUDED.requests,
+                hits = tile_usage.hits + EXCLUDED.hits,
+                updated = NOW()
+            "#,
+        )
+        .bind(
+            usage
+                .iter()
+                .map(|u| u.collection.to_owned())
+                .collect::<Vec<_>>(),
+        )
+        .bind(
+            usage
+                .iter()
+                .map(|u| u.tile_matrix.to_owned())
+                .collect::<Vec<_>>(),
+        )
+        .bind(usage.iter().map(|u| u.requests as i64).collect::<Vec<_>>())
+        .bind(usage.iter().map(|u| u.hits as i64).collect::<Vec<_>>())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn tile_usage(&self, collection: Option<&str>) -> anyhow::Result<Vec<TileUsage>> {
+        let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT collection_id, tile_matrix, requests, hits
+            FROM meta.tile_usage
+            WHERE $1::text IS NULL OR collection_id = $1
+            ORDER BY collection_id, length(tile_matrix), tile_matrix
+            "#,
+        )
+        .bind(collection)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(collection, tile_matrix, requests, hits)| TileUsage {
+                collection,
+                tile_matrix,
+                requests: requests as u64,
+                hits: hits as u64,
+            })
+            .collect())
+    }
+}
+
+/// Layers of the features of a collection, with the condition of their
+/// features, split by the property of the tile layers unless it is hidden
+fn layers(collection: &Collection, hidden: &[String]) -> Vec<(String, String)> {
+    let Some(tile_layers) = collection
+        .tile_layers
+        .as_ref()
+        .filter(|t| !hidden.contains(&t.property))
+    else {
+        return vec![(collection.id.to_owned(), "TRUE".to_string())];
+    };
+
+    let value = format!("properties -> {}", literal(&tile_layers.property));
+    let values = |values: &mut dyn Iterator<Item = &Value>| {
+        values
+            .map(|v| format!("{}::jsonb", literal(&v.to_string())))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    let mut layers: Vec<(String, String)> = tile_layers
+        .layers
+        .iter()
+        .filter(|layer| !layer.values.is_empty())
+        .map(|layer| {
+            let filter = format!("{value} IN ({})", values(&mut layer.values.iter()));
+            (layer.name.to_owned(), filter)
+        })
+        .collect();
+
+    // features without property or with any other value
+    let all = values(&mut tile_layers.layers.iter().flat_map(|l| l.values.iter()));
+    let filter = if all.is_empty() {
+        "TRUE".to_string()
+    } else {
+        format!("NOT COALESCE({value} IN ({all}), FALSE)")
+    };
+    layers.push((collection.id.to_owned(), filter));
+
+    layers
+}
+
+/// Quoted string literal
+fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+impl Db {
+    /// Zoom level of the generalized geometries to render a tile of a zoom
+    /// level from, `None` for the full geometries
+    pub(super) async fn generalized_zoom(
+        &self,
+        collection: &str,
+        zoom: i32,
+    ) -> anyhow::Result<Option<i32>> {
+        let Some(z) = GENERALIZED_ZOOMS.into_iter().find(|z| *z >= zoom) else {
+            return Ok(None);
+        };
+
+        let exists: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT FROM information_schema.columns
+                WHERE table_schema = 'items' AND table_name = $1 AND column_name = $2
+            )
+            "#,
+        )
+        .bind(collection)
+        .bind(format!("geom_z{z}"))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists.then_some(z))
+    }
+
+    /// Add the generalized geometries to collections with lines or polygons,
+    /// and compute those of new and changed features
+    async fn generalize_collection(&self, collection: &str) -> anyhow::Result<()> {
+        let table = format!(r#"items."{collection}""#);
+
+        if self.generalized_zoom(collection, 0).await?.is_none() {
+            // points are not simplified, tell them apart by the first features
+            let lines_or_polygons: bool = sqlx::query_scalar(&format!(
+                r#"
+                SELECT EXISTS (
+                    SELECT FROM (SELECT geom FROM {table} LIMIT 10000) AS g
+                    WHERE ST_Dimension(geom) > 0
+                )
+                "#
+            ))
+            .fetch_one(&self.pool)
+            .await?;
+            if !lines_or_polygons {
+                return Ok(());
+            }
+
+            let mut tx = self.pool.begin().await?;
+            for z in GENERALIZED_ZOOMS {
+                sqlx::query(&format!(
+                    "ALTER TABLE {table} ADD COLUMN IF NOT EXISTS geom_z{z} geometry"
+                ))
+                .execute(&mut *tx)
+                .await?;
+            }
+            sqlx::query(&format!(
+                r#"
+                CREATE TRIGGER "reset_generalized" BEFORE UPDATE OF geom ON {table}
+                FOR EACH ROW EXECUTE FUNCTION meta.reset_generalized()
+                "#
+            ))
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+        }
+
+        // simplified by the size of a tile pixel at the zoom level
+        let columns: Vec<String> = GENERALIZED_ZOOMS
+            .iter()
+            .map(|z| {
+                let tolerance = WORLD_SIZE / 2f64.powi(*z) / 4096.0;
+                format!("geom_z{z} = ST_SimplifyPreserveTopology(s.geom, {tolerance})")
+            })
+            .collect();
+
+        loop {
+            let updated = sqlx::query(&format!(
+                r#"
+                UPDATE {table} SET {0}
+                FROM (
+                    SELECT id, ST_Transform(ST_Force2D(geom), 3857) AS geom
+                    FROM {table} WHERE geom_z{1} IS NULL
+                    LIMIT $1
+                ) AS s
+                WHERE {table}.id = s.id
+                "#,
+                columns.join(", "),
+                GENERALIZED_ZOOMS[0]
+            ))
+            .bind(GENERALIZE_BATCH)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+            if updated < GENERALIZE_BATCH as u64 {
+                return Ok(());
+            }
+        }
     }
 }
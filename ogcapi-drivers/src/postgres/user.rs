@@ -0,0 +1,114 @@
+use ogcapi_types::auth::{ApiKey, User};
+
+use crate::UserTransactions;
+
+use super::Db;
+
+#[async_trait::async_trait]
+impl UserTransactions for Db {
+    async fn create_user(&self, id: &str) -> anyhow::Result<String> {
+        let (id,): (String,) =
+            sqlx::query_as("INSERT INTO meta.users (id) VALUES ($1) RETURNING id")
+                .bind(id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(id)
+    }
+
+    async fn delete_user(&self, id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM meta.users WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_users(&self) -> anyhow::Result<Vec<User>> {
+        let users: Vec<sqlx::types::Json<User>> = sqlx::query_scalar(
+            r#"
+            SELECT json_build_object('id', id, 'created', created) as "user!"
+            FROM meta.users ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users.into_iter().map(|u| u.0).collect())
+    }
+
+    async fn create_key(&self, user: &str) -> anyhow::Result<ApiKey> {
+        let (id, secret): (String, String) = sqlx::query_as(
+            r#"
+            SELECT
+                left(replace(gen_random_uuid()::text, '-', ''), 12),
+                replace(gen_random_uuid()::text, '-', '') || replace(gen_random_uuid()::text, '-', '')
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let key: sqlx::types::Json<ApiKey> = sqlx::query_scalar(
+            r#"
+            INSERT INTO meta.api_keys (id, user_id, hash)
+            VALUES ($1, $2, encode(sha256(convert_to($3, 'UTF8')), 'hex'))
+            RETURNING json_build_object('id', id, 'user', user_id, 'created', created) as "key!"
+            "#,
+        )
+        .bind(&id)
+        .bind(user)
+        .bind(&secret)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(ApiKey {
+            key: Some(format!("{id}.{secret}")),
+            ..key.0
+        })
+    }
+
+    async fn delete_key(&self, id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM meta.api_keys WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_keys(&self, user: Option<&str>) -> anyhow::Result<Vec<ApiKey>> {
+        let keys: Vec<sqlx::types::Json<ApiKey>> = sqlx::query_scalar(
+            r#"
+            SELECT json_build_object('id', id, 'user', user_id, 'created', created) as "key!"
+            FROM meta.api_keys
+            WHERE $1::text IS NULL OR user_id = $1
+            ORDER BY created
+            "#,
+        )
+        .bind(user)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(keys.into_iter().map(|k| k.0).collect())
+    }
+
+    async fn verify_key(&self, key: &str) -> anyhow::Result<Option<String>> {
+        let Some((id, secret)) = key.split_once('.') else {
+            return Ok(None);
+        };
+
+        let user: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT user_id FROM meta.api_keys
+            WHERE id = $1 AND hash = encode(sha256(convert_to($2, 'UTF8')), 'hex')
+            "#,
+        )
+        .bind(id)
+        .bind(secret)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+}
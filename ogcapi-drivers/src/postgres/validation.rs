@@ -0,0 +1,181 @@
+use sqlx::PgConnection;
+
+use ogcapi_types::{
+    common::Crs,
+    features::{Feature, Rule, ValidationRule, Violation},
+};
+
+use super::Db;
+
+/// Maximum number of violations listed per rule
+const MAX_VIOLATIONS: i64 = 1000;
+
+impl Db {
+    pub(crate) async fn feature_violations(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+        rules: &[ValidationRule],
+    ) -> anyhow::Result<Vec<Violation>> {
+        if rules.is_empty() || features.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let storage_srid = self.storage_srid(collection).await?;
+        let features = features
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // shaped like the item table, numbered in order
+        sqlx::query(
+            r#"
+            CREATE TEMPORARY TABLE validated ON COMMIT DROP AS
+            SELECT
+                n - 1 AS index,
+                f ->> 'id' AS id,
+                f -> 'properties' AS properties,
+                ST_Transform(ST_SetSRID(ST_GeomFromGeoJSON(f -> 'geometry'), $2), $3) AS geom
+            FROM UNNEST($1::jsonb[]) WITH ORDINALITY AS t(f, n)
+            "#,
+        )
+        .bind(features)
+        .bind(crs.as_srid())
+        .bind(storage_srid)
+        .execute(&mut *tx)
+        .await?;
+
+        let table = format!(r#"items."{collection}""#);
+
+        let mut violations = Vec::new();
+        for rule in rules {
+            let sql = match &rule.rule {
+                Rule::ValidGeometry => invalid_geometries("validated"),
+                Rule::RequiredProperties { .. } => missing_properties("validated"),
+                // overlaps with stored features and preceding new ones
+                Rule::NoOverlap => format!(
+                    r#"
+                    SELECT * FROM (
+                        SELECT v.index, v.id, format('Overlaps feature `%s`', o.id)
+                        FROM validated v
+                        JOIN {table} o ON o.geom && v.geom AND o.id IS DISTINCT FROM v.id
+                        WHERE {} AND {} AND ST_Relate(v.geom, o.geom, '2********')
+                        UNION ALL
+                        SELECT v.index, v.id, format(
+                            'Overlaps feature %s',
+                            COALESCE('`' || o.id || '`', o.index::text)
+                        )
+                        FROM validated v
+                        JOIN validated o ON o.index < v.index AND o.geom && v.geom
+                        WHERE {} AND {} AND ST_Relate(v.geom, o.geom, '2********')
+                    ) overlaps
+                    ORDER BY 1, 3
+                    LIMIT {MAX_VIOLATIONS}
+                    "#,
+                    polygonal("v"),
+                    polygonal("o"),
+                    polygonal("v"),
+                    polygonal("o"),
+                ),
+            };
+            violations.extend(query_violations(&mut tx, &sql, rule).await?);
+        }
+
+        tx.commit().await?;
+
+        Ok(violations)
+    }
+
+    pub(crate) async fn collection_violations(
+        &self,
+        collection: &str,
+        rules: &[ValidationRule],
+    ) -> anyhow::Result<Vec<Violation>> {
+        let table = format!(r#"items."{collection}""#);
+        let source = format!("(SELECT NULL::bigint AS index, id, properties, geom FROM {table})");
+
+        let mut conn = self.pool.acquire().await?;
+
+        let mut violations = Vec::new();
+        for rule in rules {
+            let sql = match &rule.rule {
+                Rule::ValidGeometry => invalid_geometries(&source),
+                Rule::RequiredProperties { .. } => missing_properties(&source),
+                // each overlapping pair once
+                Rule::NoOverlap => format!(
+                    r#"
+                    SELECT NULL::bigint, v.id, format('Overlaps feature `%s`', o.id)
+                    FROM {table} v
+                    JOIN {table} o ON o.id > v.id AND o.geom && v.geom
+                    WHERE {} AND {} AND ST_Relate(v.geom, o.geom, '2********')
+                    ORDER BY 2, 3
+                    LIMIT {MAX_VIOLATIONS}
+                    "#,
+                    polygonal("v"),
+                    polygonal("o"),
+                ),
+            };
+            violations.extend(query_violations(&mut conn, &sql, rule).await?);
+        }
+
+        Ok(violations)
+    }
+}
+
+/// Violations of a rule listed by a query for the index and id of the
+/// features and the message, with the required properties as parameter
+async fn query_violations(
+    conn: &mut PgConnection,
+    sql: &str,
+    rule: &ValidationRule,
+) -> anyhow::Result<Vec<Violation>> {
+    let mut query = sqlx::query_as::<_, (Option<i64>, Option<String>, String)>(sql);
+    if let Rule::RequiredProperties { properties } = &rule.rule {
+        query = query.bind(properties);
+    }
+
+    let rows = query.fetch_all(conn).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(index, id, message)| Violation {
+            feature: id,
+            index: index.map(|i| i as usize),
+            rule: rule.rule.name().to_string(),
+            severity: rule.severity,
+            message,
+        })
+        .collect())
+}
+
+fn invalid_geometries(source: &str) -> String {
+    format!(
+        r#"
+        SELECT v.index, v.id, ST_IsValidReason(v.geom)
+        FROM {source} v
+        WHERE NOT ST_IsValid(v.geom)
+        ORDER BY 1, 2
+        LIMIT {MAX_VIOLATIONS}
+        "#
+    )
+}
+
+fn missing_properties(source: &str) -> String {
+    format!(
+        r#"
+        SELECT v.index, v.id, format('Missing property `%s`', key)
+        FROM {source} v, UNNEST($1::text[]) AS key
+        WHERE v.properties -> key IS NULL OR v.properties -> key = 'null'::jsonb
+        ORDER BY 1, 2
+        LIMIT {MAX_VIOLATIONS}
+        "#
+    )
+}
+
+/// Condition for valid polygons, invalid ones can't be compared
+fn polygonal(alias: &str) -> String {
+    format!("ST_Dimension({alias}.geom) = 2 AND ST_IsValid({alias}.geom)")
+}
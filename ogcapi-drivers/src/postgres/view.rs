@@ -0,0 +1,83 @@
+use ogcapi_types::views::View;
+
+use crate::ViewTransactions;
+
+use super::Db;
+
+#[async_trait::async_trait]
+impl ViewTransactions for Db {
+    async fn create_view(&self, view: &View) -> anyhow::Result<String> {
+        let id: String = sqlx::query_scalar(
+            r#"
+            INSERT INTO meta.views (owner, definition)
+            VALUES ($1, $2::jsonb - ARRAY['id', 'owner', 'created', 'links'])
+            RETURNING id
+            "#,
+        )
+        .bind(&view.owner)
+        .bind(sqlx::types::Json(view))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn read_view(&self, id: &str) -> anyhow::Result<Option<View>> {
+        let view: Option<sqlx::types::Json<View>> = sqlx::query_scalar(
+            r#"
+            SELECT definition || jsonb_strip_nulls(jsonb_build_object(
+                'id', id, 'owner', owner, 'created', created
+            )) as "view!"
+            FROM meta.views WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(view.map(|v| v.0))
+    }
+
+    async fn update_view(&self, view: &View) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE meta.views
+            SET definition = $2::jsonb - ARRAY['id', 'owner', 'created', 'links']
+            WHERE id = $1
+            "#,
+        )
+        .bind(&view.id)
+        .bind(sqlx::types::Json(view))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_view(&self, id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM meta.views WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_views(&self, owner: Option<&str>) -> anyhow::Result<Vec<View>> {
+        let views: Vec<sqlx::types::Json<View>> = sqlx::query_scalar(
+            r#"
+            SELECT definition || jsonb_strip_nulls(jsonb_build_object(
+                'id', id, 'owner', owner, 'created', created
+            )) as "view!"
+            FROM meta.views
+            WHERE owner IS NOT DISTINCT FROM $1
+            ORDER BY created
+            "#,
+        )
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(views.into_iter().map(|v| v.0).collect())
+    }
+}
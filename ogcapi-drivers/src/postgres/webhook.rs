@@ -0,0 +1,110 @@
+use ogcapi_types::webhooks::{Delivery, Webhook};
+
+use crate::WebhookTransactions;
+
+use super::Db;
+
+/// Number of deliveries kept per webhook
+const DELIVERY_LOG_SIZE: i64 = 100;
+
+#[async_trait::async_trait]
+impl WebhookTransactions for Db {
+    async fn create_webhook(&self, webhook: &Webhook) -> anyhow::Result<String> {
+        let id: String = sqlx::query_scalar(
+            r#"
+            INSERT INTO meta.webhooks (definition, secret)
+            VALUES ($1, $2)
+            RETURNING id
+            "#,
+        )
+        .bind(sqlx::types::Json(webhook))
+        .bind(&webhook.secret)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn read_webhook(&self, id: &str) -> anyhow::Result<Option<Webhook>> {
+        let webhook: Option<sqlx::types::Json<Webhook>> = sqlx::query_scalar(
+            r#"
+            SELECT definition || jsonb_build_object('id', id, 'secret', secret, 'created', created) as "webhook!"
+            FROM meta.webhooks WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(webhook.map(|w| w.0))
+    }
+
+    async fn delete_webhook(&self, id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM meta.webhooks WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_webhooks(&self) -> anyhow::Result<Vec<Webhook>> {
+        let webhooks: Vec<sqlx::types::Json<Webhook>> = sqlx::query_scalar(
+            r#"
+            SELECT definition || jsonb_build_object('id', id, 'secret', secret, 'created', created) as "webhook!"
+            FROM meta.webhooks ORDER BY created
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(webhooks.into_iter().map(|w| w.0).collect())
+    }
+
+    async fn log_delivery(&self, delivery: &Delivery) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("INSERT INTO meta.webhook_deliveries (webhook_id, delivery) VALUES ($1, $2)")
+            .bind(&delivery.webhook)
+            .bind(sqlx::types::Json(delivery))
+            .execute(&mut *tx)
+            .await?;
+
+        // truncate the log
+        sqlx::query(
+            r#"
+            DELETE FROM meta.webhook_deliveries
+            WHERE webhook_id = $1 AND created < (
+                SELECT created FROM meta.webhook_deliveries
+                WHERE webhook_id = $1
+                ORDER BY created DESC
+                OFFSET $2 LIMIT 1
+            )
+            "#,
+        )
+        .bind(&delivery.webhook)
+        .bind(DELIVERY_LOG_SIZE - 1)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn list_deliveries(&self, webhook: &str) -> anyhow::Result<Vec<Delivery>> {
+        let deliveries: Vec<sqlx::types::Json<Delivery>> = sqlx::query_scalar(
+            r#"
+            SELECT delivery as "delivery!"
+            FROM meta.webhook_deliveries
+            WHERE webhook_id = $1
+            ORDER BY created DESC
+            "#,
+        )
+        .bind(webhook)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deliveries.into_iter().map(|d| d.0).collect())
+    }
+}
@@ -0,0 +1,546 @@
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use gdal::{
+    raster::{reproject, Buffer, GdalDataType, RasterCreationOption},
+    spatial_ref::{CoordTransform, SpatialRef},
+    Dataset, DatasetOptions, DriverManager, Metadata,
+};
+
+use ogcapi_types::common::{
+    media_type::{COG, GEO_TIFF, JPEG, PNG},
+    Crs,
+};
+
+use crate::RasterSource;
+
+use super::{values, BandInfo, DataType, Grid, Raster, RasterRequest, MAX_SIDE, MAX_VALUES};
+
+/// File extensions of the supported formats
+const EXTENSIONS: [&str; 4] = ["tif", "tiff", "nc", "vrt"];
+
+/// Maximum number of pixels of Cloud Optimized GeoTIFFs with overviews
+const COG_OVERVIEW_PIXELS: usize = 1 << 22;
+
+/// Counter of the in-memory files of encoded rasters
+static ENCODED: AtomicU64 = AtomicU64::new(0);
+
+/// Raster source reading GeoTIFF, COG and NetCDF files with GDAL
+///
+/// Every raster of a directory becomes the coverage of the collection named
+/// after the file stem, every variable of a NetCDF file with several the one
+/// named `{stem}-{variable}`. Reads are windowed and resampled from the
+/// closest overview, so that only about the requested pixels are held in
+/// memory.
+#[derive(Clone, Default)]
+pub struct Gdal {
+    /// GDAL dataset names by collection id
+    sources: Arc<BTreeMap<String, String>>,
+}
+
+impl Gdal {
+    pub fn new() -> Self {
+        Gdal::default()
+    }
+
+    /// Load the rasters of a directory
+    pub async fn open(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let dir = dir.as_ref().to_owned();
+        let sources = tokio::task::spawn_blocking(move || scan(&dir)).await??;
+
+        Ok(Gdal {
+            sources: Arc::new(sources),
+        })
+    }
+
+    /// Add the raster of a collection, any dataset name GDAL can open, e.g.
+    /// `/vsicurl/https://example.com/cog.tif`
+    pub fn add(mut self, collection: impl ToString, name: impl ToString) -> Self {
+        Arc::make_mut(&mut self.sources).insert(collection.to_string(), name.to_string());
+        self
+    }
+
+    /// Ids of the collections with a raster
+    pub fn collections(&self) -> Vec<String> {
+        self.sources.keys().cloned().collect()
+    }
+
+    fn source(&self, collection: &str) -> anyhow::Result<String> {
+        self.sources
+            .get(collection)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No raster for collection `{collection}`"))
+    }
+}
+
+#[async_trait::async_trait]
+impl RasterSource for Gdal {
+    async fn grid(&self, collection: &str) -> anyhow::Result<Option<Grid>> {
+        let Some(name) = self.sources.get(collection).cloned() else {
+            return Ok(None);
+        };
+
+        tokio::task::spawn_blocking(move || grid(&Dataset::open(&name)?).map(Some)).await?
+    }
+
+    async fn read(&self, collection: &str, request: &RasterRequest) -> anyhow::Result<Raster> {
+        let name = self.source(collection)?;
+        let request = request.to_owned();
+
+        tokio::task::spawn_blocking(move || read(&name, &request)).await?
+    }
+
+    async fn encode(&self, raster: &Raster, media_type: &str) -> anyhow::Result<Vec<u8>> {
+        let raster = raster.to_owned();
+        let media_type = media_type.to_owned();
+
+        tokio::task::spawn_blocking(move || encode(&raster, &media_type)).await?
+    }
+}
+
+/// Dataset names of the rasters of a directory by collection id
+fn scan(dir: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut sources = BTreeMap::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        let (Some(stem), Some(extension)) = (
+            path.file_stem().and_then(|s| s.to_str()),
+            path.extension().and_then(|e| e.to_str()),
+        ) else {
+            continue;
+        };
+        if !EXTENSIONS.contains(&extension.to_lowercase().as_str()) {
+            continue;
+        }
+
+        let name = path.to_string_lossy().to_string();
+
+        // a broken file should not keep the others from being served
+        let dataset = match Dataset::open(&path) {
+            Ok(dataset) => dataset,
+            Err(e) => {
+                tracing::warn!("Failed to open `{name}`: {e}");
+                continue;
+            }
+        };
+
+        // variables of multidimensional formats are subdatasets
+        let subdatasets: Vec<String> = dataset
+            .metadata_domain("SUBDATASETS")
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|item| {
+                let (key, value) = item.split_once('=')?;
+                key.ends_with("_NAME").then(|| value.to_owned())
+            })
+            .collect();
+
+        if subdatasets.is_empty() {
+            sources.insert(stem.to_owned(), name);
+        } else {
+            for subdataset in subdatasets {
+                let variable = subdataset.rsplit(':').next().unwrap_or_default();
+                sources.insert(format!("{stem}-{variable}"), subdataset);
+            }
+        }
+    }
+
+    Ok(sources)
+}
+
+fn grid(dataset: &Dataset) -> anyhow::Result<Grid> {
+    let (width, height) = dataset.raster_size();
+
+    let gt = dataset.geo_transform()?;
+    anyhow::ensure!(
+        gt[2] == 0.0 && gt[4] == 0.0 && gt[1] > 0.0 && gt[5] < 0.0,
+        "Only north-up rasters are supported"
+    );
+    let (x0, x1) = (gt[0], gt[0] + gt[1] * width as f64);
+    let (y0, y1) = (gt[3], gt[3] + gt[5] * height as f64);
+
+    let mut srs = dataset.spatial_ref()?;
+    srs.auto_identify_epsg().ok();
+    let crs = Crs::from_srid(srs.auth_code()?);
+
+    let mut bands = Vec::new();
+    for i in 1..=dataset.raster_count() {
+        let band = dataset.rasterband(i)?;
+        bands.push(BandInfo {
+            name: band
+                .description()
+                .ok()
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| format!("band{i}")),
+            data_type: data_type(band.band_type()),
+            nodata: band.no_data_value(),
+            unit: Some(band.unit()).filter(|u| !u.is_empty()),
+        });
+    }
+
+    let mut overviews = Vec::new();
+    if dataset.raster_count() > 0 {
+        let band = dataset.rasterband(1)?;
+        for i in 0..band.overview_count()? {
+            overviews.push(band.overview(i as isize)?.size());
+        }
+    }
+
+    Ok(Grid {
+        width,
+        height,
+        bbox: [x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)],
+        crs,
+        bands,
+        overviews,
+    })
+}
+
+fn data_type(data_type: GdalDataType) -> DataType {
+    match data_type {
+        GdalDataType::UInt8 => DataType::Byte,
+        GdalDataType::UInt16 => DataType::UInt16,
+        GdalDataType::Int8 | GdalDataType::Int16 => DataType::Int16,
+        GdalDataType::UInt32 => DataType::UInt32,
+        GdalDataType::Int32 => DataType::Int32,
+        GdalDataType::Float32 => DataType::Float32,
+        _ => DataType::Float64,
+    }
+}
+
+fn read(name: &str, request: &RasterRequest) -> anyhow::Result<Raster> {
+    let dataset = Dataset::open(name)?;
+    let grid = grid(&dataset)?;
+
+    let bands = match &request.bands {
+        Some(bands) => bands.to_owned(),
+        None => (1..=grid.bands.len()).collect(),
+    };
+    if let Some(band) = bands.iter().find(|b| **b == 0 || **b > grid.bands.len()) {
+        anyhow::bail!("Unknown band {band}, the raster has {}", grid.bands.len());
+    }
+
+    let crs = request.crs.clone().unwrap_or_else(|| grid.crs.clone());
+    let bbox = match request.bbox {
+        Some(bbox) => {
+            anyhow::ensure!(
+                bbox[0] < bbox[2] && bbox[1] < bbox[3],
+                "Empty bbox requested"
+            );
+            transform_bounds(&bbox, request.bbox_crs.as_ref().unwrap_or(&crs), &crs)?
+        }
+        None => transform_bounds(&grid.bbox, &grid.crs, &crs)?,
+    };
+
+    let native = grid.native_size(&transform_bounds(&bbox, &crs, &grid.crs)?);
+    // all bands are warped into another crs
+    let count = if crs == grid.crs {
+        bands.len()
+    } else {
+        grid.bands.len()
+    };
+    let (width, height) = request.size(native, count);
+    anyhow::ensure!(width > 0 && height > 0, "Empty raster requested");
+    anyhow::ensure!(
+        width <= MAX_SIDE && height <= MAX_SIDE,
+        "Reads are limited to {MAX_SIDE} pixels per side"
+    );
+    anyhow::ensure!(
+        values(width, height, bands.len()).is_some_and(|v| v <= MAX_VALUES),
+        "Reads are limited to {MAX_VALUES} values"
+    );
+
+    let selected: Vec<&BandInfo> = bands.iter().map(|b| &grid.bands[b - 1]).collect();
+    let data_type = selected
+        .iter()
+        .map(|b| b.data_type)
+        .reduce(DataType::union)
+        .unwrap_or(DataType::Float64);
+    let nodata = selected.iter().find_map(|b| b.nodata);
+
+    let size = (width, height);
+    let values = if crs == grid.crs {
+        read_window(&dataset, &grid, &bbox, size, &bands, nodata)?
+    } else {
+        warp(name, dataset, &grid, &bbox, &crs, size, &bands, nodata)?
+    };
+
+    Ok(Raster {
+        width,
+        height,
+        bbox,
+        crs,
+        bands: values,
+        data_type,
+        nodata,
+    })
+}
+
+/// Read a window in the native crs, GDAL reads from the overview closest to
+/// the requested resolution
+fn read_window(
+    dataset: &Dataset,
+    grid: &Grid,
+    bbox: &[f64; 4],
+    (width, height): (usize, usize),
+    bands: &[usize],
+    nodata: Option<f64>,
+) -> anyhow::Result<Vec<Vec<f64>>> {
+    let fill = nodata.unwrap_or(0.0);
+    let mut values = vec![vec![fill; width * height]; bands.len()];
+
+    // pixel coordinates of the bbox, and of its part inside the raster,
+    // snapped to pixel edges against rounding errors of aligned windows
+    let (rx, ry) = grid.resolution();
+    let snap = |p: f64| {
+        if (p - p.round()).abs() < 1e-6 {
+            p.round()
+        } else {
+            p
+        }
+    };
+    let (px0, px1) = (
+        snap((bbox[0] - grid.bbox[0]) / rx),
+        snap((bbox[2] - grid.bbox[0]) / rx),
+    );
+    let (py0, py1) = (
+        snap((grid.bbox[3] - bbox[3]) / ry),
+        snap((grid.bbox[3] - bbox[1]) / ry),
+    );
+    let (ix0, ix1) = (px0.max(0.0).floor(), px1.min(grid.width as f64).ceil());
+    let (iy0, iy1) = (py0.max(0.0).floor(), py1.min(grid.height as f64).ceil());
+    if ix0 >= ix1 || iy0 >= iy1 {
+        return Ok(values);
+    }
+
+    // placement of the part in the result
+    let (sx, sy) = (width as f64 / (px1 - px0), height as f64 / (py1 - py0));
+    let ox0 = ((ix0 - px0) * sx).round().max(0.0) as usize;
+    let ox1 = (((ix1 - px0) * sx).round() as usize).min(width);
+    let oy0 = ((iy0 - py0) * sy).round().max(0.0) as usize;
+    let oy1 = (((iy1 - py0) * sy).round() as usize).min(height);
+    if ox0 >= ox1 || oy0 >= oy1 {
+        return Ok(values);
+    }
+    let (ow, oh) = (ox1 - ox0, oy1 - oy0);
+
+    for (band, values) in bands.iter().zip(values.iter_mut()) {
+        let buffer = dataset.rasterband(*band as isize)?.read_as::<f64>(
+            (ix0 as isize, iy0 as isize),
+            ((ix1 - ix0) as usize, (iy1 - iy0) as usize),
+            (ow, oh),
+            None,
+        )?;
+
+        for (row, chunk) in buffer.data.chunks(ow).enumerate() {
+            let start = (oy0 + row) * width + ox0;
+            values[start..start + ow].copy_from_slice(chunk);
+        }
+    }
+
+    Ok(values)
+}
+
+/// Reproject a window into another crs, from the overview closest to the
+/// requested resolution
+#[allow(clippy::too_many_arguments)]
+fn warp(
+    name: &str,
+    dataset: Dataset,
+    grid: &Grid,
+    bbox: &[f64; 4],
+    crs: &Crs,
+    (width, height): (usize, usize),
+    bands: &[usize],
+    nodata: Option<f64>,
+) -> anyhow::Result<Vec<Vec<f64>>> {
+    // all bands are warped
+    anyhow::ensure!(
+        values(width, height, grid.bands.len()).is_some_and(|v| v <= MAX_VALUES),
+        "Reprojected reads are limited to {MAX_VALUES} values over all bands"
+    );
+
+    // overview with at least the resolution of the result
+    let native = transform_bounds(bbox, crs, &grid.crs)?;
+    let factor = grid.native_size(&native).0 as f64 / width as f64;
+    let level = grid
+        .overviews
+        .iter()
+        .rposition(|(w, _)| grid.width as f64 / *w as f64 <= factor);
+
+    let source = match level {
+        Some(level) => {
+            let option = format!("OVERVIEW_LEVEL={level}");
+            Dataset::open_ex(
+                name,
+                DatasetOptions {
+                    open_options: Some(&[option.as_str()]),
+                    ..Default::default()
+                },
+            )?
+        }
+        None => dataset,
+    };
+
+    let mut target = DriverManager::get_driver_by_name("MEM")?.create_with_band_type::<f64, _>(
+        "",
+        width as isize,
+        height as isize,
+        grid.bands.len() as isize,
+    )?;
+    target.set_geo_transform(&[
+        bbox[0],
+        (bbox[2] - bbox[0]) / width as f64,
+        0.0,
+        bbox[3],
+        0.0,
+        -(bbox[3] - bbox[1]) / height as f64,
+    ])?;
+    target.set_spatial_ref(&spatial_ref(crs)?)?;
+
+    let fill = nodata.unwrap_or(0.0);
+    for i in 1..=grid.bands.len() {
+        let mut band = target.rasterband(i as isize)?;
+        band.set_no_data_value(nodata)?;
+        band.write(
+            (0, 0),
+            (width, height),
+            &Buffer::new((width, height), vec![fill; width * height]),
+        )?;
+    }
+
+    reproject(&source, &target)?;
+
+    bands
+        .iter()
+        .map(|band| {
+            let buffer = target.rasterband(*band as isize)?.read_as::<f64>(
+                (0, 0),
+                (width, height),
+                (width, height),
+                None,
+            )?;
+            Ok(buffer.data)
+        })
+        .collect()
+}
+
+/// Encode a raster as Cloud Optimized GeoTIFF, PNG or JPEG
+fn encode(raster: &Raster, media_type: &str) -> anyhow::Result<Vec<u8>> {
+    // overviews are only computed for rasters small enough to do so quickly
+    let overviews = if raster.width * raster.height <= COG_OVERVIEW_PIXELS {
+        "AUTO"
+    } else {
+        "NONE"
+    };
+
+    let (driver, extension, options) = match media_type {
+        COG | GEO_TIFF | "image/tiff" => (
+            "COG",
+            "tif",
+            vec![
+                RasterCreationOption {
+                    key: "COMPRESS",
+                    value: "DEFLATE",
+                },
+                RasterCreationOption {
+                    key: "BLOCKSIZE",
+                    value: "512",
+                },
+                RasterCreationOption {
+                    key: "OVERVIEWS",
+                    value: overviews,
+                },
+            ],
+        ),
+        PNG => ("PNG", "png", Vec::new()),
+        JPEG => ("JPEG", "jpg", Vec::new()),
+        _ => anyhow::bail!("Unsupported raster format `{media_type}`"),
+    };
+
+    let dataset = memory_dataset(raster)?;
+
+    let path = format!(
+        "/vsimem/ogcapi-{}-{}.{extension}",
+        std::process::id(),
+        ENCODED.fetch_add(1, Ordering::Relaxed)
+    );
+    dataset.create_copy(&DriverManager::get_driver_by_name(driver)?, &path, &options)?;
+
+    // georeferencing of image formats goes into a sidecar file
+    gdal::vsi::unlink_mem_file(format!("{path}.aux.xml")).ok();
+
+    Ok(gdal::vsi::get_vsi_mem_file_bytes_owned(&path)?)
+}
+
+/// Raster as in-memory dataset of its data type
+fn memory_dataset(raster: &Raster) -> anyhow::Result<Dataset> {
+    let driver = DriverManager::get_driver_by_name("MEM")?;
+    let (width, height, count) = (
+        raster.width as isize,
+        raster.height as isize,
+        raster.bands.len() as isize,
+    );
+
+    let mut dataset = match raster.data_type {
+        DataType::Byte => driver.create_with_band_type::<u8, _>("", width, height, count)?,
+        DataType::UInt16 => driver.create_with_band_type::<u16, _>("", width, height, count)?,
+        DataType::Int16 => driver.create_with_band_type::<i16, _>("", width, height, count)?,
+        DataType::UInt32 => driver.create_with_band_type::<u32, _>("", width, height, count)?,
+        DataType::Int32 => driver.create_with_band_type::<i32, _>("", width, height, count)?,
+        DataType::Float32 => driver.create_with_band_type::<f32, _>("", width, height, count)?,
+        DataType::Float64 => driver.create_with_band_type::<f64, _>("", width, height, count)?,
+    };
+
+    let [minx, miny, maxx, maxy] = raster.bbox;
+    dataset.set_geo_transform(&[
+        minx,
+        (maxx - minx) / raster.width as f64,
+        0.0,
+        maxy,
+        0.0,
+        -(maxy - miny) / raster.height as f64,
+    ])?;
+    dataset.set_spatial_ref(&spatial_ref(&raster.crs)?)?;
+
+    for (i, values) in raster.bands.iter().enumerate() {
+        let mut band = dataset.rasterband(i as isize + 1)?;
+        band.set_no_data_value(raster.nodata)?;
+        // values are converted to the data type of the band
+        band.write(
+            (0, 0),
+            (raster.width, raster.height),
+            &Buffer::new((raster.width, raster.height), values.to_owned()),
+        )?;
+    }
+
+    Ok(dataset)
+}
+
+fn spatial_ref(crs: &Crs) -> anyhow::Result<SpatialRef> {
+    anyhow::ensure!(crs.is_valid(), "Unsupported crs `{crs}`");
+
+    let srs = SpatialRef::from_epsg(crs.as_srid() as u32)?;
+    // x/y axis order, as for all coordinates of the API
+    srs.set_axis_mapping_strategy(0);
+
+    Ok(srs)
+}
+
+fn transform_bounds(bbox: &[f64; 4], from: &Crs, to: &Crs) -> anyhow::Result<[f64; 4]> {
+    if from == to {
+        return Ok(*bbox);
+    }
+
+    let transform = CoordTransform::new(&spatial_ref(from)?, &spatial_ref(to)?)?;
+
+    Ok(transform.transform_bounds(bbox, 21)?)
+}
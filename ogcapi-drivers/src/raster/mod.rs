@@ -0,0 +1,183 @@
+#[cfg(feature = "gdal")]
+mod gdal;
+
+#[cfg(feature = "gdal")]
+pub use self::gdal::Gdal;
+
+use ogcapi_types::common::Crs;
+
+/// Maximum number of values of a single read, keeping the memory of requests
+/// bounded
+pub const MAX_VALUES: usize = 1 << 23;
+
+/// Maximum width and height of a single read in pixels
+pub const MAX_SIDE: usize = 1 << 14;
+
+/// Grid of a raster in its native crs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    /// Outer bounds of the pixels
+    pub bbox: [f64; 4],
+    pub crs: Crs,
+    pub bands: Vec<BandInfo>,
+    /// Sizes of the overviews, from fine to coarse
+    pub overviews: Vec<(usize, usize)>,
+}
+
+/// Description of a band
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandInfo {
+    pub name: String,
+    pub data_type: DataType,
+    pub nodata: Option<f64>,
+    pub unit: Option<String>,
+}
+
+/// Data type of the values of a band
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Byte,
+    UInt16,
+    Int16,
+    UInt32,
+    Int32,
+    Float32,
+    Float64,
+}
+
+/// Window of a raster to read
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RasterRequest {
+    /// Bounds of the window, the whole raster if `None`
+    pub bbox: Option<[f64; 4]>,
+    /// Crs of the bounds, the crs of the result if `None`
+    pub bbox_crs: Option<Crs>,
+    /// Crs of the result, the native crs if `None`
+    pub crs: Option<Crs>,
+    /// Size of the result in pixels, a missing side follows from the aspect
+    /// ratio of the window and both from its native resolution
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    /// Bands to read, numbered from 1, all if `None`
+    pub bands: Option<Vec<usize>>,
+}
+
+/// Values read from a raster
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    /// Outer bounds of the pixels
+    pub bbox: [f64; 4],
+    pub crs: Crs,
+    /// Values per band, row by row from the top left
+    pub bands: Vec<Vec<f64>>,
+    /// Data type to encode the values with
+    pub data_type: DataType,
+    /// Value of pixels without data, also filling pixels outside the raster
+    pub nodata: Option<f64>,
+}
+
+impl Grid {
+    /// Size of a pixel in x and y direction
+    pub fn resolution(&self) -> (f64, f64) {
+        (
+            (self.bbox[2] - self.bbox[0]) / self.width as f64,
+            (self.bbox[3] - self.bbox[1]) / self.height as f64,
+        )
+    }
+
+    /// Size of a bbox in the native crs at full resolution
+    pub fn native_size(&self, bbox: &[f64; 4]) -> (usize, usize) {
+        let (x, y) = self.resolution();
+        (
+            ((bbox[2] - bbox[0]) / x).round().max(1.0) as usize,
+            ((bbox[3] - bbox[1]) / y).round().max(1.0) as usize,
+        )
+    }
+}
+
+impl DataType {
+    /// Name as used by GDAL
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataType::Byte => "Byte",
+            DataType::UInt16 => "UInt16",
+            DataType::Int16 => "Int16",
+            DataType::UInt32 => "UInt32",
+            DataType::Int32 => "Int32",
+            DataType::Float32 => "Float32",
+            DataType::Float64 => "Float64",
+        }
+    }
+
+    /// Smallest type holding the values of both types
+    pub fn union(self, other: DataType) -> DataType {
+        use DataType::*;
+
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Float64, _) | (_, Float64) => Float64,
+            (Float32, UInt32 | Int32) | (UInt32 | Int32, Float32) => Float64,
+            (Float32, _) | (_, Float32) => Float32,
+            (UInt32, Int16 | Int32) | (Int16 | Int32, UInt32) => Float64,
+            (UInt32, _) | (_, UInt32) => UInt32,
+            (Int32, _) | (_, Int32) => Int32,
+            (UInt16, Int16) | (Int16, UInt16) => Int32,
+            (UInt16, _) | (_, UInt16) => UInt16,
+            (Int16, _) | (_, Int16) => Int16,
+            _ => Byte,
+        }
+    }
+}
+
+impl RasterRequest {
+    /// Size of the result for a window of `native` pixels at full
+    /// resolution, sizes derived from the native resolution are scaled down
+    /// to hold at most [MAX_VALUES] values of `bands` bands and [MAX_SIDE]
+    /// pixels per side
+    pub fn size(&self, native: (usize, usize), bands: usize) -> (usize, usize) {
+        let aspect = native.1 as f64 / native.0 as f64;
+        let max = MAX_VALUES / bands.max(1);
+        let derived = |side: f64, other: usize| {
+            (side.round() as usize).clamp(1, MAX_SIDE.min(max / other.max(1)).max(1))
+        };
+
+        match (self.width, self.height) {
+            (Some(width), Some(height)) => (width, height),
+            (Some(width), None) => (width, derived(width as f64 * aspect, width)),
+            (None, Some(height)) => (derived(height as f64 / aspect, height), height),
+            (None, None) => {
+                let (width, height) = fit(native.0, native.1, max);
+                (width.min(MAX_SIDE), height.min(MAX_SIDE))
+            }
+        }
+    }
+}
+
+/// Number of values of a read, `None` on overflow
+pub fn values(width: usize, height: usize, bands: usize) -> Option<usize> {
+    width.checked_mul(height)?.checked_mul(bands)
+}
+
+/// Scale a size down to at most `max` pixels, keeping the aspect ratio
+pub fn fit(width: usize, height: usize, max: usize) -> (usize, usize) {
+    if width
+        .checked_mul(height)
+        .is_some_and(|pixels| pixels <= max)
+    {
+        return (width, height);
+    }
+
+    let scale = (max as f64 / (width as f64 * height as f64)).sqrt();
+    let width = ((width as f64 * scale).floor() as usize).max(1);
+    let height = ((height as f64 * scale).floor() as usize).max(1);
+
+    // a side of one pixel leaves the rest to the other
+    (
+        width.min(max / height).max(1),
+        height.min(max / width).max(1),
+    )
+}
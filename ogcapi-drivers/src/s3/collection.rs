@@ -1,6 +1,9 @@
 use aws_sdk_s3::{error::SdkError, operation::get_object::GetObjectError};
 
-use ogcapi_types::common::{media_type::JSON, Collection, Collections, Query};
+use ogcapi_types::{
+    common::{media_type::JSON, Collection, Collections, Query},
+    features::Queryables,
+};
 
 use crate::CollectionTransactions;
 
@@ -68,7 +71,7 @@ impl CollectionTransactions for S3 {
         Ok(())
     }
 
-    async fn list_collections(&self, _query: &Query) -> Result<Collections, anyhow::Error> {
+    async fn list_collections(&self, query: &Query) -> Result<Collections, anyhow::Error> {
         let mut collections = Vec::new();
 
         let resp = self
@@ -85,18 +88,55 @@ impl CollectionTransactions for S3 {
                         .get_object(self.bucket.clone().unwrap_or_default(), key)
                         .await?;
 
-                    let c = serde_json::from_slice(&r.body.collect().await?.into_bytes()[..])?;
+                    let c: Collection =
+                        serde_json::from_slice(&r.body.collect().await?.into_bytes()[..])?;
 
-                    collections.push(c);
+                    if query.matches(&c) {
+                        collections.push(c);
+                    }
                 }
             }
         }
 
-        let mut collections = Collections::new(collections);
-        collections
-            .number_returned
-            .clone_into(&mut collections.number_matched);
+        Ok(Collections::paginated(collections, query))
+    }
+
+    async fn read_queryables(&self, id: &str) -> Result<Option<Queryables>, anyhow::Error> {
+        let key = format!("collections/{}/queryables.json", id);
 
-        Ok(collections)
+        match self
+            .get_object(self.bucket.clone().unwrap_or_default(), &key)
+            .await
+        {
+            Ok(r) => Ok(Some(serde_json::from_slice(
+                &r.body.collect().await?.into_bytes(),
+            )?)),
+            Err(e) => match e {
+                SdkError::ServiceError(err) => match err.err() {
+                    GetObjectError::NoSuchKey(_) => Ok(None),
+                    _ => Err(anyhow::Error::new(err.into_err())),
+                },
+                _ => Err(anyhow::Error::new(e)),
+            },
+        }
+    }
+
+    async fn update_queryables(
+        &self,
+        id: &str,
+        queryables: &Queryables,
+    ) -> Result<(), anyhow::Error> {
+        let key = format!("collections/{}/queryables.json", id);
+        let data = serde_json::to_vec(&queryables)?;
+
+        self.put_object(
+            self.bucket.clone().unwrap_or_default(),
+            &key,
+            data,
+            Some(JSON.to_string()),
+        )
+        .await?;
+
+        Ok(())
     }
 }
@@ -1,11 +1,12 @@
 use aws_sdk_s3::{error::SdkError, operation::get_object::GetObjectError};
+use futures::{stream::BoxStream, StreamExt};
 
 use ogcapi_types::{
     common::{media_type::GEO_JSON, Crs},
     features::{Feature, FeatureCollection, Query},
 };
 
-use crate::FeatureTransactions;
+use crate::{transform::transformer, CollectionTransactions, FeatureTransactions};
 
 use super::S3;
 
@@ -30,11 +31,29 @@ impl FeatureTransactions for S3 {
         Ok(key)
     }
 
+    async fn create_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+    ) -> anyhow::Result<Vec<String>> {
+        let storage_crs = self.storage_crs(collection).await?;
+        let transform = transformer();
+
+        let mut ids = Vec::new();
+        for feature in features {
+            let mut feature = feature.to_owned();
+            transform.transform_feature(crs, &storage_crs, &mut feature)?;
+            ids.push(self.create_feature(&feature).await?);
+        }
+        Ok(ids)
+    }
+
     async fn read_feature(
         &self,
         collection: &str,
         id: &str,
-        _crs: &Crs,
+        crs: &Crs,
     ) -> anyhow::Result<Option<Feature>> {
         let key = format!("collections/{}/items/{}.json", collection, id);
 
@@ -42,9 +61,16 @@ impl FeatureTransactions for S3 {
             .get_object(self.bucket.clone().unwrap_or_default(), &key)
             .await
         {
-            Ok(r) => Ok(Some(serde_json::from_slice(
-                &r.body.collect().await?.into_bytes(),
-            )?)),
+            Ok(r) => {
+                let mut feature: Feature =
+                    serde_json::from_slice(&r.body.collect().await?.into_bytes())?;
+
+                // S3 can't reproject, transform the stored geometry
+                let storage_crs = self.storage_crs(collection).await?;
+                transformer().transform_feature(&storage_crs, crs, &mut feature)?;
+
+                Ok(Some(feature))
+            }
             Err(e) => match e {
                 SdkError::ServiceError(err) => match err.err() {
                     GetObjectError::NoSuchKey(_) => Ok(None),
@@ -89,4 +115,28 @@ impl FeatureTransactions for S3 {
     ) -> anyhow::Result<FeatureCollection> {
         unimplemented!()
     }
+
+    fn stream_items(
+        &self,
+        _collection: &str,
+        _query: &Query,
+    ) -> BoxStream<'static, anyhow::Result<Feature>> {
+        futures::stream::once(async {
+            Err(anyhow::anyhow!(
+                "Streaming items is not supported by the S3 driver"
+            ))
+        })
+        .boxed()
+    }
+}
+
+impl S3 {
+    /// Crs the features of a collection are stored in
+    async fn storage_crs(&self, collection: &str) -> anyhow::Result<Crs> {
+        Ok(self
+            .read_collection(collection)
+            .await?
+            .and_then(|c| c.storage_crs)
+            .unwrap_or_default())
+    }
 }
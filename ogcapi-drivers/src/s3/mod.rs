@@ -2,7 +2,7 @@ mod collection;
 mod feature;
 
 use aws_sdk_s3::{
-    error::SdkError,
+    error::{DisplayErrorContext, SdkError},
     operation::{
         delete_object::{DeleteObjectError, DeleteObjectOutput},
         get_object::{GetObjectError, GetObjectOutput},
@@ -13,6 +13,8 @@ use aws_sdk_s3::{
 
 pub use aws_sdk_s3::primitives::ByteStream;
 
+use crate::Diagnostic;
+
 /// S3 driver
 #[derive(Clone)]
 pub struct S3 {
@@ -98,4 +100,22 @@ impl S3 {
             .send()
             .await
     }
+
+    /// Check that a bucket is reachable with the configured credentials
+    pub async fn diagnose(&self, bucket: &str) -> Diagnostic {
+        match self.client.head_bucket().bucket(bucket).send().await {
+            Ok(_) => Diagnostic::ok("s3", format!("Bucket `{bucket}` is reachable")),
+            Err(e) => Diagnostic::error(
+                "s3",
+                format!(
+                    "Bucket `{bucket}` is not reachable: {}",
+                    DisplayErrorContext(e)
+                ),
+            )
+            .hint(
+                "Check the bucket and `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, \
+                 `AWS_REGION` and `AWS_CUSTOM_ENDPOINT`",
+            ),
+        }
+    }
 }
@@ -0,0 +1,129 @@
+mod native;
+#[cfg(feature = "proj")]
+mod proj;
+
+pub use native::NativeTransform;
+#[cfg(feature = "proj")]
+pub use proj::ProjTransform;
+
+use geojson::{Position, Value};
+
+use ogcapi_types::{
+    common::{Bbox, Crs},
+    features::{Feature, Geometry},
+};
+
+/// Number of points per edge used to approximate transformed bboxes
+const DENSIFY_POINTS: usize = 21;
+
+/// Trait for coordinate transformations between reference systems, for
+/// backing stores that can't reproject themselves
+///
+/// Coordinates are always in `x`/`y` (east/north) axis order, as in `GeoJSON`.
+pub trait Transform: Send + Sync {
+    /// Checks whether the transformation from or to the crs is supported
+    fn supports(&self, crs: &Crs) -> bool;
+
+    /// Transform coordinate pairs in place
+    fn transform_coords(
+        &self,
+        from: &Crs,
+        to: &Crs,
+        coords: &mut [(f64, f64)],
+    ) -> anyhow::Result<()>;
+
+    /// Transform all positions of a geometry in place, vertical coordinates
+    /// are left untouched
+    fn transform_geometry(
+        &self,
+        from: &Crs,
+        to: &Crs,
+        geometry: &mut Geometry,
+    ) -> anyhow::Result<()> {
+        if from == to {
+            return Ok(());
+        }
+
+        let mut positions = Vec::new();
+        collect_positions(&mut geometry.value, &mut positions);
+
+        let mut coords: Vec<(f64, f64)> = positions.iter().map(|p| (p[0], p[1])).collect();
+        self.transform_coords(from, to, &mut coords)?;
+
+        for (position, (x, y)) in positions.into_iter().zip(coords) {
+            position[0] = x;
+            position[1] = y;
+        }
+
+        geometry.bbox = None;
+
+        Ok(())
+    }
+
+    /// Transform the geometry of a feature in place
+    fn transform_feature(&self, from: &Crs, to: &Crs, feature: &mut Feature) -> anyhow::Result<()> {
+        self.transform_geometry(from, to, &mut feature.geometry)
+    }
+
+    /// Transform a bbox, densifying its edges to enclose the curved outline
+    /// of the transformed box
+    fn transform_bbox(&self, from: &Crs, to: &Crs, bbox: &Bbox) -> anyhow::Result<Bbox> {
+        if from == to {
+            return Ok(bbox.to_owned());
+        }
+
+        let [minx, miny, maxx, maxy] = bbox.to_2d();
+
+        let steps = (DENSIFY_POINTS - 1) as f64;
+        let mut coords = Vec::with_capacity(DENSIFY_POINTS * 4);
+        for i in 0..DENSIFY_POINTS {
+            let x = minx + (maxx - minx) * i as f64 / steps;
+            let y = miny + (maxy - miny) * i as f64 / steps;
+            coords.extend([(x, miny), (x, maxy), (minx, y), (maxx, y)]);
+        }
+
+        self.transform_coords(from, to, &mut coords)?;
+
+        let (minx, miny, maxx, maxy) = coords.iter().fold(
+            (
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(minx, miny, maxx, maxy), (x, y)| {
+                (minx.min(*x), miny.min(*y), maxx.max(*x), maxy.max(*y))
+            },
+        );
+
+        Ok(match bbox.z_range() {
+            Some((minz, maxz)) => Bbox::Bbox3D([minx, miny, minz, maxx, maxy, maxz]),
+            None => Bbox::Bbox2D([minx, miny, maxx, maxy]),
+        })
+    }
+}
+
+/// Default transformation, backed by `PROJ` if the `proj` feature is enabled
+pub fn transformer() -> Box<dyn Transform> {
+    #[cfg(feature = "proj")]
+    return Box::new(ProjTransform);
+
+    #[cfg(not(feature = "proj"))]
+    return Box::new(NativeTransform);
+}
+
+fn collect_positions<'a>(value: &'a mut Value, positions: &mut Vec<&'a mut Position>) {
+    match value {
+        Value::Point(p) => positions.push(p),
+        Value::MultiPoint(ps) | Value::LineString(ps) => positions.extend(ps.iter_mut()),
+        Value::MultiLineString(ls) | Value::Polygon(ls) => {
+            positions.extend(ls.iter_mut().flatten())
+        }
+        Value::MultiPolygon(polygons) => positions.extend(polygons.iter_mut().flatten().flatten()),
+        Value::GeometryCollection(geometries) => {
+            for geometry in geometries.iter_mut() {
+                collect_positions(&mut geometry.value, positions);
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use ogcapi_types::common::{Authority, Crs};
+
+use super::Transform;
+
+/// WGS 84 semi-major axis
+const A: f64 = 6378137.0;
+/// WGS 84 flattening
+const F: f64 = 1.0 / 298.257223563;
+
+/// Maximal latitude of the web mercator projection
+const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// UTM scale factor at the central meridian
+const K0: f64 = 0.9996;
+const FALSE_EASTING: f64 = 500000.0;
+const FALSE_NORTHING: f64 = 10000000.0;
+
+/// Pure Rust transformation between common WGS 84 based reference systems
+///
+/// Supports geographic coordinates (`CRS84`, `EPSG:4326`, `EPSG:4979`), web
+/// mercator (`EPSG:3857`) and UTM zones (`EPSG:326xx`, `EPSG:327xx`).
+/// Geographic coordinates are treated as longitude/latitude regardless of
+/// the axis order of the crs definition.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NativeTransform;
+
+/// Projection of a supported crs
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Projection {
+    Geographic,
+    WebMercator,
+    Utm { zone: u8, south: bool },
+}
+
+impl Projection {
+    fn of(crs: &Crs) -> Option<Self> {
+        match crs.authority {
+            Authority::OGC => match crs.code.as_str() {
+                "CRS84" | "CRS84h" => Some(Projection::Geographic),
+                _ => None,
+            },
+            Authority::EPSG => match crs.code.parse::<u32>().ok()? {
+                4326 | 4979 => Some(Projection::Geographic),
+                3857 | 900913 => Some(Projection::WebMercator),
+                code @ 32601..=32660 => Some(Projection::Utm {
+                    zone: (code - 32600) as u8,
+                    south: false,
+                }),
+                code @ 32701..=32760 => Some(Projection::Utm {
+                    zone: (code - 32700) as u8,
+                    south: true,
+                }),
+                _ => None,
+            },
+        }
+    }
+
+    /// Project longitude/latitude in degrees
+    fn forward(&self, (lon, lat): (f64, f64)) -> (f64, f64) {
+        match *self {
+            Projection::Geographic => (lon, lat),
+            Projection::WebMercator => {
+                let lat = lat.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+                (A * lon.to_radians(), A * (FRAC_PI_4 + lat / 2.0).tan().ln())
+            }
+            Projection::Utm { zone, south } => {
+                let (easting, northing) = TransverseMercator::new().forward(
+                    (lon - central_meridian(zone)).to_radians(),
+                    lat.to_radians(),
+                );
+                (
+                    FALSE_EASTING + easting,
+                    northing + if south { FALSE_NORTHING } else { 0.0 },
+                )
+            }
+        }
+    }
+
+    /// Unproject to longitude/latitude in degrees
+    fn inverse(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        match *self {
+            Projection::Geographic => (x, y),
+            Projection::WebMercator => (
+                (x / A).to_degrees(),
+                (2.0 * (y / A).exp().atan() - FRAC_PI_2).to_degrees(),
+            ),
+            Projection::Utm { zone, south } => {
+                let (lon, lat) = TransverseMercator::new().inverse(
+                    x - FALSE_EASTING,
+                    y - if south { FALSE_NORTHING } else { 0.0 },
+                );
+                (lon.to_degrees() + central_meridian(zone), lat.to_degrees())
+            }
+        }
+    }
+}
+
+impl Transform for NativeTransform {
+    fn supports(&self, crs: &Crs) -> bool {
+        Projection::of(crs).is_some()
+    }
+
+    fn transform_coords(
+        &self,
+        from: &Crs,
+        to: &Crs,
+        coords: &mut [(f64, f64)],
+    ) -> anyhow::Result<()> {
+        let unsupported = |crs: &Crs| anyhow::anyhow!("Transformation of `{crs}` not supported");
+
+        let source = Projection::of(from).ok_or_else(|| unsupported(from))?;
+        let target = Projection::of(to).ok_or_else(|| unsupported(to))?;
+
+        if source == target {
+            return Ok(());
+        }
+
+        for coord in coords.iter_mut() {
+            *coord = target.forward(source.inverse(*coord));
+        }
+
+        Ok(())
+    }
+}
+
+fn central_meridian(zone: u8) -> f64 {
+    zone as f64 * 6.0 - 183.0
+}
+
+/// Transverse mercator on the WGS 84 ellipsoid, using the Krüger series
+/// expansion to third order, which is accurate to below a millimeter within
+/// UTM zones
+struct TransverseMercator {
+    n: f64,
+    /// Rectifying radius scaled by the central scale factor
+    radius: f64,
+    alpha: [f64; 3],
+    beta: [f64; 3],
+    delta: [f64; 3],
+}
+
+impl TransverseMercator {
+    fn new() -> Self {
+        let n = F / (2.0 - F);
+        let (n2, n3) = (n * n, n * n * n);
+
+        TransverseMercator {
+            n,
+            radius: K0 * A / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0),
+            alpha: [
+                n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0,
+                13.0 * n2 / 48.0 - 3.0 * n3 / 5.0,
+                61.0 * n3 / 240.0,
+            ],
+            beta: [
+                n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0,
+                n2 / 48.0 + n3 / 15.0,
+                17.0 * n3 / 480.0,
+            ],
+            delta: [
+                2.0 * n - 2.0 * n2 / 3.0 - 2.0 * n3,
+                7.0 * n2 / 3.0 - 8.0 * n3 / 5.0,
+                56.0 * n3 / 15.0,
+            ],
+        }
+    }
+
+    /// Project longitude relative to the central meridian and latitude in
+    /// radians to easting and northing relative to the origin
+    fn forward(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let e = 2.0 * self.n.sqrt() / (1.0 + self.n);
+        let t = (lat.sin().atanh() - e * (e * lat.sin()).atanh()).sinh();
+
+        let xi = t.atan2(lon.cos());
+        let eta = (lon.sin() / (1.0 + t * t).sqrt()).atanh();
+
+        let (mut x, mut y) = (eta, xi);
+        for (j, alpha) in self.alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            x += alpha * (k * xi).cos() * (k * eta).sinh();
+            y += alpha * (k * xi).sin() * (k * eta).cosh();
+        }
+
+        (self.radius * x, self.radius * y)
+    }
+
+    /// Inverse of [`TransverseMercator::forward`]
+    fn inverse(&self, easting: f64, northing: f64) -> (f64, f64) {
+        let xi = northing / self.radius;
+        let eta = easting / self.radius;
+
+        let (mut xi_, mut eta_) = (xi, eta);
+        for (j, beta) in self.beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi_ -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_ -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+
+        let chi = (xi_.sin() / eta_.cosh()).asin();
+
+        let mut lat = chi;
+        for (j, delta) in self.delta.iter().enumerate() {
+            lat += delta * (2.0 * (j + 1) as f64 * chi).sin();
+        }
+
+        (eta_.sinh().atan2(xi_.cos()), lat)
+    }
+}
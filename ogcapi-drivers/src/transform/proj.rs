@@ -0,0 +1,35 @@
+use proj::Proj;
+
+use ogcapi_types::common::Crs;
+
+use super::Transform;
+
+/// Transformation backed by `PROJ`, supporting all reference systems known
+/// to its database
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProjTransform;
+
+impl Transform for ProjTransform {
+    fn supports(&self, crs: &Crs) -> bool {
+        Proj::new(&crs.as_known_crs()).is_ok()
+    }
+
+    fn transform_coords(
+        &self,
+        from: &Crs,
+        to: &Crs,
+        coords: &mut [(f64, f64)],
+    ) -> anyhow::Result<()> {
+        if from == to {
+            return Ok(());
+        }
+
+        // `PROJ` objects are not thread safe, create one per call. Known crs
+        // are normalized to `x`/`y` axis order.
+        let proj = Proj::new_known_crs(&from.as_known_crs(), &to.as_known_crs(), None)?;
+
+        proj.convert_array(coords)?;
+
+        Ok(())
+    }
+}
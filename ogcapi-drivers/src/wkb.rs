@@ -0,0 +1,423 @@
+//! `WKB` codec for `GeoJSON` geometries
+//!
+//! Geometries are decoded with `geozero` straight into `GeoJSON` values,
+//! without formatting and parsing them as text. Geometries of producers
+//! without `GeoJSON` are decoded from `WKT` and hex encoded `WKB` as well.
+
+use anyhow::bail;
+use geojson::{Geometry, LineStringType, PolygonType, Position, Value};
+use geozero::{
+    error::{GeozeroError, Result as GeozeroResult},
+    wkb::{Ewkb, GpkgWkb, Wkb},
+    wkt::Wkt,
+    CoordDimensions, GeomProcessor, GeozeroGeometry,
+};
+
+const POINT: u32 = 1;
+const LINE_STRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTI_POINT: u32 = 4;
+const MULTI_LINE_STRING: u32 = 5;
+const MULTI_POLYGON: u32 = 6;
+const GEOMETRY_COLLECTION: u32 = 7;
+
+/// Encode a geometry as `GeoPackage` binary (header and little endian `WKB`)
+pub fn to_gpkg(value: &Value, srs_id: i32) -> Vec<u8> {
+    // magic, version, flags (little endian, no envelope)
+    let mut buf = vec![b'G', b'P', 0, 0b0000_0001];
+    buf.extend_from_slice(&srs_id.to_le_bytes());
+
+    write_geometry(&mut buf, value, has_z(value));
+
+    buf
+}
+
+/// Decode a `GeoPackage` binary geometry, returning `None` for empty geometries
+pub fn from_gpkg(blob: &[u8]) -> anyhow::Result<Option<Value>> {
+    if blob.len() < 8 || &blob[..2] != b"GP" {
+        bail!("Invalid GeoPackage geometry header");
+    }
+
+    // the empty flag is not considered by the reader
+    if blob[3] & 0b0001_0000 != 0 {
+        return Ok(None);
+    }
+
+    let mut writer = ValueWriter::default();
+    GpkgWkb(blob).process_geom(&mut writer)?;
+
+    writer.finish().map(Some)
+}
+
+/// Decode a (`PostGIS`) extended `WKB` geometry
+pub fn from_ewkb(wkb: &[u8]) -> anyhow::Result<Geometry> {
+    let mut writer = ValueWriter::default();
+    Ewkb(wkb).process_geom(&mut writer)?;
+
+    writer.finish().map(Geometry::new)
+}
+
+/// Encode a geometry as little endian (ISO) `WKB`
+pub fn to_wkb(value: &Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_geometry(&mut buf, value, has_z(value));
+    buf
+}
+
+/// Decode an ISO or extended `WKB` geometry
+pub fn from_wkb(wkb: &[u8]) -> anyhow::Result<Geometry> {
+    let Some(header) = wkb.get(..5) else {
+        bail!("Invalid WKB geometry header");
+    };
+    let type_id = match header[0] {
+        0 => u32::from_be_bytes([header[1], header[2], header[3], header[4]]),
+        _ => u32::from_le_bytes([header[1], header[2], header[3], header[4]]),
+    };
+
+    // extended `WKB` flags its dimensions and SRID in the upper bits
+    if type_id & 0xE000_0000 != 0 {
+        return from_ewkb(wkb);
+    }
+
+    let mut writer = ValueWriter::default();
+    Wkb(wkb).process_geom(&mut writer)?;
+
+    writer.finish().map(Geometry::new)
+}
+
+/// Decode a `WKT` geometry, like `POINT (7.44 46.95)`
+pub fn from_wkt(wkt: &str) -> anyhow::Result<Geometry> {
+    let mut writer = ValueWriter::default();
+    Wkt(wkt).process_geom(&mut writer)?;
+
+    writer.finish().map(Geometry::new)
+}
+
+/// Decode a geometry given as text, either `WKT` or hex encoded `WKB`
+pub fn from_text(text: &str) -> anyhow::Result<Geometry> {
+    let text = text.trim();
+
+    match decode_hex(text) {
+        Some(wkb) => from_wkb(&wkb),
+        None => from_wkt(text),
+    }
+}
+
+/// Bytes of a hex string, `None` if it isn't one
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.is_empty() || !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn has_z(value: &Value) -> bool {
+    match value {
+        Value::Point(p) => p.len() > 2,
+        Value::MultiPoint(l) | Value::LineString(l) => l.first().is_some_and(|p| p.len() > 2),
+        Value::MultiLineString(p) | Value::Polygon(p) => p
+            .first()
+            .and_then(|l| l.first())
+            .is_some_and(|p| p.len() > 2),
+        Value::MultiPolygon(m) => m
+            .first()
+            .and_then(|p| p.first())
+            .and_then(|l| l.first())
+            .is_some_and(|p| p.len() > 2),
+        Value::GeometryCollection(g) => g.iter().any(|g| has_z(&g.value)),
+    }
+}
+
+fn write_geometry(buf: &mut Vec<u8>, value: &Value, z: bool) {
+    match value {
+        Value::Point(p) => {
+            write_header(buf, POINT, z);
+            write_position(buf, p, z);
+        }
+        Value::LineString(l) => {
+            write_header(buf, LINE_STRING, z);
+            write_positions(buf, l, z);
+        }
+        Value::Polygon(p) => {
+            write_header(buf, POLYGON, z);
+            write_rings(buf, p, z);
+        }
+        Value::MultiPoint(m) => {
+            write_header(buf, MULTI_POINT, z);
+            write_count(buf, m.len());
+            for p in m {
+                write_header(buf, POINT, z);
+                write_position(buf, p, z);
+            }
+        }
+        Value::MultiLineString(m) => {
+            write_header(buf, MULTI_LINE_STRING, z);
+            write_count(buf, m.len());
+            for l in m {
+                write_header(buf, LINE_STRING, z);
+                write_positions(buf, l, z);
+            }
+        }
+        Value::MultiPolygon(m) => {
+            write_header(buf, MULTI_POLYGON, z);
+            write_count(buf, m.len());
+            for p in m {
+                write_header(buf, POLYGON, z);
+                write_rings(buf, p, z);
+            }
+        }
+        Value::GeometryCollection(g) => {
+            write_header(buf, GEOMETRY_COLLECTION, z);
+            write_count(buf, g.len());
+            for g in g {
+                write_geometry(buf, &g.value, z);
+            }
+        }
+    }
+}
+
+/// Byte order (little endian) and ISO geometry type code
+fn write_header(buf: &mut Vec<u8>, r#type: u32, z: bool) {
+    buf.push(1);
+    buf.extend_from_slice(&(r#type + if z { 1000 } else { 0 }).to_le_bytes());
+}
+
+fn write_count(buf: &mut Vec<u8>, count: usize) {
+    buf.extend_from_slice(&(count as u32).to_le_bytes());
+}
+
+fn write_position(buf: &mut Vec<u8>, position: &[f64], z: bool) {
+    let dimensions = if z { 3 } else { 2 };
+    for i in 0..dimensions {
+        let ordinate = position.get(i).copied().unwrap_or(f64::NAN);
+        buf.extend_from_slice(&ordinate.to_le_bytes());
+    }
+}
+
+fn write_positions(buf: &mut Vec<u8>, positions: &[Vec<f64>], z: bool) {
+    write_count(buf, positions.len());
+    for p in positions {
+        write_position(buf, p, z);
+    }
+}
+
+fn write_rings(buf: &mut Vec<u8>, rings: &[Vec<Vec<f64>>], z: bool) {
+    write_count(buf, rings.len());
+    for r in rings {
+        write_positions(buf, r, z);
+    }
+}
+
+/// Processor assembling a `GeoJSON` value from the events of a reader
+#[derive(Default)]
+struct ValueWriter {
+    value: Option<Value>,
+    /// Members of the (nested) geometry collections in progress
+    collections: Vec<Vec<Geometry>>,
+    /// Polygons of a multi polygon in progress
+    polygons: Option<Vec<PolygonType>>,
+    /// Rings of a polygon or line strings of a multi line string in progress
+    lines: Option<Vec<LineStringType>>,
+    /// Positions of a point, multi point or line string in progress
+    positions: Option<Vec<Position>>,
+}
+
+impl ValueWriter {
+    fn finish(self) -> anyhow::Result<Value> {
+        match self.value {
+            Some(value) => Ok(value),
+            None => bail!("Incomplete geometry"),
+        }
+    }
+
+    fn push_value(&mut self, value: Value) {
+        match self.collections.last_mut() {
+            Some(collection) => collection.push(Geometry::new(value)),
+            None => self.value = Some(value),
+        }
+    }
+
+    fn push_position(&mut self, position: Position) -> GeozeroResult<()> {
+        self.positions
+            .as_mut()
+            .ok_or_else(|| GeozeroError::Geometry("Unexpected coordinate".to_string()))?
+            .push(position);
+        Ok(())
+    }
+
+    fn take<T>(part: &mut Option<T>) -> GeozeroResult<T> {
+        part.take()
+            .ok_or_else(|| GeozeroError::Geometry("Unbalanced geometry events".to_string()))
+    }
+
+    fn unsupported() -> GeozeroResult<()> {
+        Err(GeozeroError::Geometry(
+            "Curves and surfaces are not supported by GeoJSON".to_string(),
+        ))
+    }
+}
+
+impl GeomProcessor for ValueWriter {
+    fn dimensions(&self) -> CoordDimensions {
+        CoordDimensions::xyz()
+    }
+
+    fn xy(&mut self, x: f64, y: f64, _idx: usize) -> GeozeroResult<()> {
+        self.push_position(vec![x, y])
+    }
+
+    fn coordinate(
+        &mut self,
+        x: f64,
+        y: f64,
+        z: Option<f64>,
+        _m: Option<f64>,
+        _t: Option<f64>,
+        _tm: Option<u64>,
+        _idx: usize,
+    ) -> GeozeroResult<()> {
+        // measures are not supported by GeoJSON
+        match z {
+            Some(z) => self.push_position(vec![x, y, z]),
+            None => self.push_position(vec![x, y]),
+        }
+    }
+
+    fn empty_point(&mut self, _idx: usize) -> GeozeroResult<()> {
+        // skipped in multi points, otherwise an empty collection
+        if self.positions.is_none() {
+            self.push_value(Value::GeometryCollection(Vec::new()));
+        }
+        Ok(())
+    }
+
+    fn point_begin(&mut self, _idx: usize) -> GeozeroResult<()> {
+        self.positions = Some(Vec::with_capacity(1));
+        Ok(())
+    }
+
+    fn point_end(&mut self, _idx: usize) -> GeozeroResult<()> {
+        let position = Self::take(&mut self.positions)?
+            .pop()
+            .ok_or_else(|| GeozeroError::Geometry("Point without coordinate".to_string()))?;
+        self.push_value(Value::Point(position));
+        Ok(())
+    }
+
+    fn multipoint_begin(&mut self, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.positions = Some(Vec::with_capacity(size));
+        Ok(())
+    }
+
+    fn multipoint_end(&mut self, _idx: usize) -> GeozeroResult<()> {
+        let positions = Self::take(&mut self.positions)?;
+        self.push_value(Value::MultiPoint(positions));
+        Ok(())
+    }
+
+    fn linestring_begin(&mut self, _tagged: bool, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.positions = Some(Vec::with_capacity(size));
+        Ok(())
+    }
+
+    fn linestring_end(&mut self, tagged: bool, _idx: usize) -> GeozeroResult<()> {
+        let positions = Self::take(&mut self.positions)?;
+        if tagged {
+            self.push_value(Value::LineString(positions));
+        } else {
+            self.lines
+                .as_mut()
+                .ok_or_else(|| GeozeroError::Geometry("Untagged line string".to_string()))?
+                .push(positions);
+        }
+        Ok(())
+    }
+
+    fn multilinestring_begin(&mut self, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.lines = Some(Vec::with_capacity(size));
+        Ok(())
+    }
+
+    fn multilinestring_end(&mut self, _idx: usize) -> GeozeroResult<()> {
+        let lines = Self::take(&mut self.lines)?;
+        self.push_value(Value::MultiLineString(lines));
+        Ok(())
+    }
+
+    fn polygon_begin(&mut self, _tagged: bool, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.lines = Some(Vec::with_capacity(size));
+        Ok(())
+    }
+
+    fn polygon_end(&mut self, tagged: bool, _idx: usize) -> GeozeroResult<()> {
+        let rings = Self::take(&mut self.lines)?;
+        if tagged {
+            self.push_value(Value::Polygon(rings));
+        } else {
+            self.polygons
+                .as_mut()
+                .ok_or_else(|| GeozeroError::Geometry("Untagged polygon".to_string()))?
+                .push(rings);
+        }
+        Ok(())
+    }
+
+    fn multipolygon_begin(&mut self, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.polygons = Some(Vec::with_capacity(size));
+        Ok(())
+    }
+
+    fn multipolygon_end(&mut self, _idx: usize) -> GeozeroResult<()> {
+        let polygons = Self::take(&mut self.polygons)?;
+        self.push_value(Value::MultiPolygon(polygons));
+        Ok(())
+    }
+
+    fn geometrycollection_begin(&mut self, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.collections.push(Vec::with_capacity(size));
+        Ok(())
+    }
+
+    fn geometrycollection_end(&mut self, _idx: usize) -> GeozeroResult<()> {
+        let geometries = Self::take(&mut self.collections.pop())?;
+        self.push_value(Value::GeometryCollection(geometries));
+        Ok(())
+    }
+
+    fn circularstring_begin(&mut self, _size: usize, _idx: usize) -> GeozeroResult<()> {
+        Self::unsupported()
+    }
+
+    fn compoundcurve_begin(&mut self, _size: usize, _idx: usize) -> GeozeroResult<()> {
+        Self::unsupported()
+    }
+
+    fn curvepolygon_begin(&mut self, _size: usize, _idx: usize) -> GeozeroResult<()> {
+        Self::unsupported()
+    }
+
+    fn multicurve_begin(&mut self, _size: usize, _idx: usize) -> GeozeroResult<()> {
+        Self::unsupported()
+    }
+
+    fn multisurface_begin(&mut self, _size: usize, _idx: usize) -> GeozeroResult<()> {
+        Self::unsupported()
+    }
+
+    fn triangle_begin(&mut self, _tagged: bool, _size: usize, _idx: usize) -> GeozeroResult<()> {
+        Self::unsupported()
+    }
+
+    fn polyhedralsurface_begin(&mut self, _size: usize, _idx: usize) -> GeozeroResult<()> {
+        Self::unsupported()
+    }
+
+    fn tin_begin(&mut self, _size: usize, _idx: usize) -> GeozeroResult<()> {
+        Self::unsupported()
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use serde_json::json;
+
+    use ogcapi_drivers::{
+        postgres::Db, AccessFilterTransactions, CollectionTransactions, FeatureTransactions,
+        QuotaTransactions, UserTransactions,
+    };
+    use ogcapi_types::{
+        auth::{AccessFilter, KeyUsage, Quota},
+        common::{Bbox, Collection, Crs},
+        cql2::{eq, intersects},
+        features::{Feature, Query},
+    };
+
+    fn place(id: &str, canton: &str, x: f64, y: f64) -> Feature {
+        serde_json::from_value(json!({
+            "type": "Feature",
+            "id": id,
+            "properties": { "canton": canton },
+            "geometry": { "type": "Point", "coordinates": [x, y] }
+        }))
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn access_filters(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let collection = Collection {
+            id: "places".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+        db.create_user("alice").await.unwrap();
+
+        let places = [
+            place("bern", "BE", 7.4474, 46.948),
+            place("zurich", "ZH", 8.5417, 47.3769),
+        ];
+        db.create_features("places", &places, &Crs::default())
+            .await
+            .unwrap();
+
+        // restrict alice to a canton
+        db.set_access_filter(&AccessFilter {
+            collection: "places".to_string(),
+            user: Some("alice".to_string()),
+            filter: eq("canton", "BE"),
+            created: None,
+        })
+        .await
+        .unwrap();
+
+        let filter = db
+            .access_filter("places", Some("alice"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(filter, eq("canton", "BE"));
+        assert!(db.access_filter("places", None).await.unwrap().is_none());
+
+        let query = Query {
+            access_filter: Some(filter.to_owned()),
+            ..Default::default()
+        };
+        let fc = db.list_items("places", &query).await.unwrap();
+        assert_eq!(fc.number_matched, Some(1));
+        assert_eq!(fc.features[0].id.as_deref(), Some("bern"));
+
+        let matches = db
+            .match_filter("places", &places, &Crs::default(), &filter)
+            .await
+            .unwrap();
+        assert_eq!(matches, vec![true, false]);
+
+        // spatial filter for requests without api key
+        db.set_access_filter(&AccessFilter {
+            collection: "places".to_string(),
+            user: None,
+            filter: intersects("geometry", Bbox::from([8.0, 47.0, 9.0, 48.0])),
+            created: None,
+        })
+        .await
+        .unwrap();
+
+        let query = Query {
+            access_filter: db.access_filter("places", None).await.unwrap(),
+            ..Default::default()
+        };
+        let fc = db.list_items("places", &query).await.unwrap();
+        assert_eq!(fc.features[0].id.as_deref(), Some("zurich"));
+
+        assert_eq!(
+            db.list_access_filters(Some("places")).await.unwrap().len(),
+            2
+        );
+
+        db.delete_access_filter("places", Some("alice"))
+            .await
+            .unwrap();
+        assert!(db
+            .access_filter("places", Some("alice"))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[sqlx::test]
+    async fn quotas(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        db.create_user("alice").await.unwrap();
+        db.create_user("bob").await.unwrap();
+        let alice = db.create_key("alice").await.unwrap();
+        let bob = db.create_key("bob").await.unwrap();
+
+        // usage adds up per key and month
+        let usage = KeyUsage {
+            requests: 2,
+            features: 150,
+            job_seconds: 1.5,
+            ..KeyUsage::new(&alice.id, "2024-07")
+        };
+        db.record_key_usage(&[usage.clone(), KeyUsage::new(&bob.id, "2024-07")])
+            .await
+            .unwrap();
+        db.record_key_usage(&[usage, KeyUsage::new(&alice.id, "2024-08")])
+            .await
+            .unwrap();
+
+        let usage = db.key_usage(Some("alice"), None).await.unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].month, "2024-08");
+        assert_eq!(usage[1].requests, 4);
+        assert_eq!(usage[1].features, 300);
+        assert_eq!(usage[1].job_seconds, 3.0);
+        assert_eq!(db.key_usage(None, Some("2024-07")).await.unwrap().len(), 2);
+
+        let quota = Quota {
+            key: alice.id.to_owned(),
+            requests: Some(1000),
+            ..Default::default()
+        };
+        db.set_quota(&quota).await.unwrap();
+        assert_eq!(db.list_quotas(Some("alice")).await.unwrap(), [quota]);
+        assert!(db.list_quotas(Some("bob")).await.unwrap().is_empty());
+
+        // quotas go with their key, usage is kept for billing
+        db.delete_key(&alice.id).await.unwrap();
+        assert!(db.list_quotas(None).await.unwrap().is_empty());
+        assert_eq!(db.key_usage(None, Some("2024-07")).await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use serde_json::json;
+
+    use ogcapi_drivers::{postgres::Db, CollectionTransactions, FeatureTransactions};
+    use ogcapi_types::{
+        common::{Collection, Crs},
+        features::{AggregateQuery, Feature},
+    };
+
+    async fn collection(db: &Db, id: &str, features: serde_json::Value) {
+        let collection = Collection {
+            id: id.to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let features: Vec<Feature> = serde_json::from_value(features).unwrap();
+        db.create_features(id, &features, &Crs::default())
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test]
+    async fn aggregate(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        collection(
+            &db,
+            "stations",
+            json!([
+                {
+                    "type": "Feature",
+                    "id": "s1",
+                    "properties": { "pm10": 10 },
+                    "geometry": { "type": "Point", "coordinates": [0.5, 0.5] }
+                },
+                {
+                    "type": "Feature",
+                    "id": "s2",
+                    "properties": { "pm10": 20 },
+                    "geometry": { "type": "Point", "coordinates": [0.6, 0.6] }
+                },
+                {
+                    "type": "Feature",
+                    "id": "s3",
+                    "properties": { "pm10": "n/a" },
+                    "geometry": { "type": "Point", "coordinates": [5.5, 5.5] }
+                }
+            ]),
+        )
+        .await;
+        collection(
+            &db,
+            "regions",
+            json!([
+                {
+                    "type": "Feature",
+                    "id": "r1",
+                    "properties": {},
+                    "geometry": {
+                        "type": "Polygon",
+                        "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0]]]
+                    }
+                }
+            ]),
+        )
+        .await;
+
+        // polygons of another collection
+        let query: AggregateQuery = serde_json::from_value(json!({
+            "by": "collection:regions",
+            "agg": "count,avg(pm10),max(pm10)"
+        }))
+        .unwrap();
+        let fc = db.aggregate("stations", &query).await.unwrap().unwrap();
+        assert_eq!(fc.features.len(), 1);
+        let properties = fc.features[0].properties.as_ref().unwrap();
+        assert_eq!(fc.features[0].id.as_deref(), Some("r1"));
+        assert_eq!(properties["count"], json!(2));
+        assert_eq!(properties["avg(pm10)"], json!(15.0));
+        assert_eq!(properties["max(pm10)"], json!(20.0));
+
+        // cells of a grid, values other than numbers are not aggregated
+        let query: AggregateQuery = serde_json::from_value(json!({
+            "by": "square:100000",
+            "agg": "count,sum(pm10)"
+        }))
+        .unwrap();
+        let fc = db.aggregate("stations", &query).await.unwrap().unwrap();
+        assert_eq!(fc.features.len(), 2);
+        let counts: u64 = fc
+            .features
+            .iter()
+            .map(|f| f.properties.as_ref().unwrap()["count"].as_u64().unwrap())
+            .sum();
+        assert_eq!(counts, 3);
+
+        // too fine grids are refused
+        let query: AggregateQuery = serde_json::from_value(json!({ "by": "square:0.01" })).unwrap();
+        assert!(db.aggregate("stations", &query).await.is_err());
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use ogcapi_drivers::{postgres::Db, AttachmentTransactions, CollectionTransactions};
+    use ogcapi_types::{
+        common::{Collection, Crs},
+        features::Attachment,
+    };
+
+    #[sqlx::test]
+    async fn attachments(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let collection = Collection {
+            id: "hydrants".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let mut photo = Attachment {
+            title: Some("front.jpg".to_string()),
+            r#type: "image/jpeg".to_string(),
+            length: 2048,
+            ..Default::default()
+        };
+        photo.id = db
+            .create_attachment("hydrants", "h1", &photo)
+            .await
+            .unwrap();
+        let report = Attachment {
+            title: Some("inspection.pdf".to_string()),
+            r#type: "application/pdf".to_string(),
+            length: 4096,
+            ..Default::default()
+        };
+        db.create_attachment("hydrants", "h1", &report)
+            .await
+            .unwrap();
+
+        let attachments = db.list_attachments("hydrants", "h1").await.unwrap();
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0].title.as_deref(), Some("front.jpg"));
+        assert!(db
+            .list_attachments("hydrants", "h2")
+            .await
+            .unwrap()
+            .is_empty());
+
+        // replaced content
+        photo.length = 1024;
+        db.update_attachment("hydrants", "h1", &photo)
+            .await
+            .unwrap();
+        let read = db
+            .read_attachment("hydrants", "h1", &photo.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(read.length, 1024);
+        assert!(read.created.is_some());
+
+        // attachments belong to their feature
+        assert!(db
+            .read_attachment("hydrants", "h2", &photo.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        db.delete_attachment("hydrants", "h1", &photo.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            db.list_attachments("hydrants", "h1").await.unwrap().len(),
+            1
+        );
+
+        // removed with the collection
+        db.delete_collection("hydrants").await.unwrap();
+        assert!(db
+            .list_attachments("hydrants", "h1")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
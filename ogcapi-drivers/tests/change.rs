@@ -0,0 +1,137 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use serde_json::json;
+
+    use ogcapi_drivers::{
+        postgres::Db, CollectionTransactions, FeatureChanges, FeatureTransactions,
+    };
+    use ogcapi_types::{
+        common::{Collection, Crs},
+        features::{ChangeKind, Feature},
+    };
+
+    fn place(id: &str, name: &str) -> Feature {
+        serde_json::from_value(json!({
+            "type": "Feature",
+            "id": id,
+            "collection": "places",
+            "properties": { "name": name },
+            "geometry": { "type": "Point", "coordinates": [7.4474, 46.948] }
+        }))
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn change_log(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let collection = Collection {
+            id: "places".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let (changes, head) = db.changes_since("places", None, 10).await.unwrap().unwrap();
+        assert!(changes.is_empty());
+
+        db.create_features("places", &[place("bern", "Bern")], &Crs::default())
+            .await
+            .unwrap();
+        db.update_feature(&place("bern", "Berne")).await.unwrap();
+        db.delete_feature("places", "bern").await.unwrap();
+
+        let (changes, token) = db
+            .changes_since("places", Some(&head), 2)
+            .await
+            .unwrap()
+            .unwrap();
+        let kinds: Vec<ChangeKind> = changes.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, [ChangeKind::Create, ChangeKind::Update]);
+        assert!(changes.iter().all(|c| c.id == "bern"));
+
+        let (changes, token) = db
+            .changes_since("places", Some(&token), 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Delete);
+
+        // nothing after the last change, the token stays
+        let (changes, next) = db
+            .changes_since("places", Some(&token), 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(changes.is_empty());
+        assert_eq!(next, token);
+
+        // latest changes first, paged by time
+        let recent = db
+            .recent_changes("places", None, None, 10)
+            .await
+            .unwrap()
+            .unwrap();
+        let kinds: Vec<ChangeKind> = recent.iter().map(|c| c.change.kind).collect();
+        assert_eq!(
+            kinds,
+            [ChangeKind::Delete, ChangeKind::Update, ChangeKind::Create]
+        );
+        assert!(recent.windows(2).all(|w| w[0].changed >= w[1].changed));
+
+        let before = recent[0].changed;
+        let older = db
+            .recent_changes("places", Some(before), None, 10)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(older.iter().all(|c| c.changed < before));
+
+        // changes of one transaction share their time, pages continue after
+        // the position of their last change
+        let batch: Vec<Feature> = (0..5)
+            .map(|i| place(&format!("place-{i}"), &format!("Place {i}")))
+            .collect();
+        db.create_features("places", &batch, &Crs::default())
+            .await
+            .unwrap();
+
+        let mut listed = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = db
+                .recent_changes(
+                    "places",
+                    cursor.as_ref().map(|(changed, _)| *changed),
+                    cursor
+                        .as_ref()
+                        .map(|(_, position): &(_, String)| position.as_str()),
+                    2,
+                )
+                .await
+                .unwrap()
+                .unwrap();
+            let Some(last) = page.last() else {
+                break;
+            };
+            cursor = Some((last.changed, last.position.to_owned()));
+            listed.extend(page.into_iter().map(|c| c.change.id));
+        }
+        let created: Vec<&String> = listed
+            .iter()
+            .filter(|id| id.starts_with("place-"))
+            .collect();
+        assert_eq!(created.len(), 5);
+        assert_eq!(listed.len(), 8);
+
+        // replication positions
+        let source = "https://example.com/";
+        assert!(db.sync_token(source, "places").await.unwrap().is_none());
+        db.set_sync_token(source, "places", &token).await.unwrap();
+        assert_eq!(
+            db.sync_token(source, "places").await.unwrap(),
+            Some(token.to_owned())
+        );
+    }
+}
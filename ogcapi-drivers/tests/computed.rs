@@ -0,0 +1,69 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use serde_json::json;
+
+    use ogcapi_drivers::{postgres::Db, CollectionTransactions, FeatureTransactions};
+    use ogcapi_types::{
+        common::{Collection, Crs},
+        features::{ComputedProperty, Feature, Query},
+    };
+
+    fn computed(name: &str, expression: &str) -> ComputedProperty {
+        ComputedProperty {
+            name: name.to_string(),
+            expression: expression.to_string(),
+            description: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn computed_properties(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let collection = Collection {
+            id: "buildings".to_string(),
+            crs: vec![Crs::default()],
+            computed_properties: vec![
+                computed("area_km2", "round(ST_Area(geom) / 1e6, 1)"),
+                computed("height_ft", "height * 3.28084"),
+                computed("per_floor", "height / floors"),
+                computed("label", "upper(coalesce(name, 'unnamed'))"),
+            ],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let feature: Feature = serde_json::from_value(json!({
+            "type": "Feature",
+            "id": "b1",
+            "collection": "buildings",
+            "properties": { "height": 10, "floors": 0, "name": "town hall" },
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[[0.0, 0.0], [0.1, 0.0], [0.1, 0.1], [0.0, 0.1], [0.0, 0.0]]]
+            }
+        }))
+        .unwrap();
+        db.create_feature(&feature).await.unwrap();
+
+        let read = db
+            .read_feature("buildings", "b1", &Crs::default())
+            .await
+            .unwrap()
+            .unwrap();
+        let properties = read.properties.unwrap();
+        // ~11.1 km by ~11.1 km at the equator
+        assert!((properties["area_km2"].as_f64().unwrap() - 123.1).abs() < 0.2);
+        assert!((properties["height_ft"].as_f64().unwrap() - 32.8084).abs() < 1e-9);
+        // division by zero
+        assert_eq!(properties["per_floor"], json!(null));
+        assert_eq!(properties["label"], json!("TOWN HALL"));
+        assert_eq!(properties["height"], json!(10));
+
+        let items = db.list_items("buildings", &Query::default()).await.unwrap();
+        assert_eq!(
+            items.features[0].properties.as_ref().unwrap()["label"],
+            json!("TOWN HALL")
+        );
+    }
+}
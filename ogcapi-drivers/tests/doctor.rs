@@ -0,0 +1,29 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use ogcapi_drivers::{postgres::Db, Severity};
+
+    #[sqlx::test]
+    async fn inspect(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let diagnostics = db.inspect().await;
+        assert!(
+            diagnostics.iter().all(|d| d.severity == Severity::Ok),
+            "{diagnostics:?}"
+        );
+
+        sqlx::query("CREATE TABLE items.unindexed (id text, geom geometry)")
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let diagnostics = db.inspect().await;
+        let indexes = diagnostics.iter().find(|d| d.check == "indexes").unwrap();
+        assert_eq!(indexes.severity, Severity::Warning);
+        assert!(indexes.message.contains("unindexed"));
+        assert!(indexes
+            .hint
+            .as_ref()
+            .is_some_and(|hint| hint.contains(r#"items."unindexed" USING gist (geom)"#)));
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(feature = "files")]
+mod files {
+    use ogcapi_drivers::{files::Files, CollectionTransactions, FeatureTransactions};
+    use ogcapi_types::{
+        common::{Bbox, Crs, Query as CollectionQuery},
+        features::{LonLat, Query},
+    };
+
+    const PLACES: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "id": "bern",
+                "properties": { "name": "Bern", "population": 134794 },
+                "geometry": { "type": "Point", "coordinates": [7.4474, 46.948] }
+            },
+            {
+                "type": "Feature",
+                "properties": { "name": "Zurich", "population": 421878 },
+                "geometry": { "type": "Point", "coordinates": [8.5417, 47.3769] }
+            }
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn geojson_directory() {
+        let dir = std::env::temp_dir().join(format!("ogcapi-files-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("places.geojson"), PLACES).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a dataset").unwrap();
+
+        let files = Files::open(&dir).await.unwrap();
+
+        // collections
+        let collections = files
+            .list_collections(&CollectionQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(collections.collections.len(), 1);
+
+        let collection = files.read_collection("places").await.unwrap().unwrap();
+        assert_eq!(collection.storage_crs, Some(Crs::default()));
+        assert!(files.create_collection(&collection).await.is_err());
+
+        // features, those without id are numbered
+        let feature = files
+            .read_feature("places", "2", &Crs::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(feature.properties.unwrap()["name"], "Zurich");
+        assert!(files
+            .read_feature("places", "bern", &Crs::default())
+            .await
+            .unwrap()
+            .is_some());
+
+        // bbox
+        let query = Query {
+            bbox: Some(Bbox::Bbox2D([7.0, 46.5, 8.0, 47.0])),
+            ..Default::default()
+        };
+        let fc = files.list_items("places", &query).await.unwrap();
+        assert_eq!(fc.number_matched, Some(1));
+        assert_eq!(fc.features[0].id.as_deref(), Some("bern"));
+
+        // properties
+        let mut query = Query::default();
+        query
+            .additional_parameters
+            .insert("population".to_string(), "421878".to_string());
+        let fc = files.list_items("places", &query).await.unwrap();
+        assert_eq!(fc.number_matched, Some(1));
+
+        // ids, in the requested order
+        let query = Query {
+            ids: Some(vec![
+                "2".to_string(),
+                "unknown".to_string(),
+                "bern".to_string(),
+            ]),
+            ..Default::default()
+        };
+        let fc = files.list_items("places", &query).await.unwrap();
+        assert_eq!(fc.number_matched, Some(2));
+        let ids: Vec<_> = fc.features.iter().map(|f| f.id.as_deref()).collect();
+        assert_eq!(ids, [Some("2"), Some("bern")]);
+
+        // nearest first
+        let query = Query {
+            nearest: Some(LonLat {
+                lon: 8.5,
+                lat: 47.4,
+            }),
+            ..Default::default()
+        };
+        let fc = files.list_items("places", &query).await.unwrap();
+        let ids: Vec<_> = fc.features.iter().map(|f| f.id.as_deref()).collect();
+        assert_eq!(ids, [Some("2"), Some("bern")]);
+
+        // paging
+        let query = Query {
+            limit: Some(1),
+            offset: Some(1),
+            ..Default::default()
+        };
+        let fc = files.list_items("places", &query).await.unwrap();
+        assert_eq!(fc.number_matched, Some(2));
+        assert_eq!(fc.features.len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+
+    use ogcapi_drivers::{
+        postgres::Db, CollectionTransactions, Delta, FeatureTransactions, HarvestTransactions,
+        JobHandler,
+    };
+    use ogcapi_types::{
+        common::{Collection, Crs},
+        features::Feature,
+        harvest::{Harvest, HarvestSource},
+        processes::{JobQuery, StatusInfo},
+    };
+
+    #[sqlx::test]
+    async fn harvests(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let collection = Collection {
+            id: "stations".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let mut harvest = Harvest {
+            id: String::new(),
+            collection: "stations".to_string(),
+            source: HarvestSource::Features {
+                url: "https://example.com/collections/stations/items".to_string(),
+            },
+            schedule: "0 * * * *".to_string(),
+            id_property: None,
+            enabled: true,
+            created: None,
+            last_run: None,
+            links: Vec::new(),
+        };
+        let id = db.create_harvest(&harvest).await.unwrap();
+
+        let stored = db.read_harvest(&id).await.unwrap().unwrap();
+        assert_eq!(stored.id, id);
+        assert_eq!(stored.source, harvest.source);
+        assert!(stored.created.is_some());
+        assert!(stored.last_run.is_none());
+
+        // runs are claimed once
+        let time = Utc::now();
+        assert!(db.claim_run(&id, time).await.unwrap());
+        assert!(!db.claim_run(&id, time).await.unwrap());
+        assert!(!db.claim_run(&id, time - Duration::hours(1)).await.unwrap());
+        assert!(db.claim_run(&id, time + Duration::hours(1)).await.unwrap());
+
+        // updates keep the latest run
+        harvest.id = id.to_owned();
+        harvest.enabled = false;
+        harvest.last_run = None;
+        db.update_harvest(&harvest).await.unwrap();
+        let stored = db.read_harvest(&id).await.unwrap().unwrap();
+        assert!(!stored.enabled);
+        assert!(stored.last_run.is_some());
+
+        assert_eq!(db.list_harvests().await.unwrap().len(), 1);
+
+        // deleted with the collection
+        db.delete_collection("stations").await.unwrap();
+        assert!(db.read_harvest(&id).await.unwrap().is_none());
+    }
+
+    #[sqlx::test]
+    async fn upsert_features(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let collection = Collection {
+            id: "stations".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let station = |id: &str, level: f64| -> Feature {
+            serde_json::from_value(json!({
+                "type": "Feature",
+                "id": id,
+                "properties": { "level": level },
+                "geometry": { "type": "Point", "coordinates": [7.5, 47.5] }
+            }))
+            .unwrap()
+        };
+
+        db.upsert_features("stations", &[station("s1", 1.0)], &Crs::default())
+            .await
+            .unwrap();
+        let ids = db
+            .upsert_features(
+                "stations",
+                &[station("s1", 2.0), station("s2", 3.0)],
+                &Crs::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ids, ["s1", "s2"]);
+
+        let feature = db
+            .read_feature("stations", "s1", &Crs::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(feature.properties.unwrap()["level"], 2.0);
+    }
+
+    #[sqlx::test]
+    async fn apply_delta(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let collection = Collection {
+            id: "stations".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let station = |id: &str| -> Feature {
+            serde_json::from_value(json!({
+                "type": "Feature",
+                "id": id,
+                "properties": { "name": id },
+                "geometry": { "type": "Point", "coordinates": [7.5, 47.5] }
+            }))
+            .unwrap()
+        };
+
+        let delta = Delta {
+            upserts: vec![
+                (station("s1"), "a".to_string()),
+                (station("s2"), "b".to_string()),
+            ],
+            deletes: Vec::new(),
+        };
+        db.apply_delta("stations", &delta, &Crs::default())
+            .await
+            .unwrap();
+        let checksums = db.checksums("stations").await.unwrap();
+        assert_eq!(checksums.len(), 2);
+        assert_eq!(checksums["s1"], "a");
+
+        // features deleted otherwise lose their checksum
+        db.delete_feature("stations", "s2").await.unwrap();
+        assert!(!db.checksums("stations").await.unwrap().contains_key("s2"));
+
+        // deletes are applied after the upserts
+        let delta = Delta {
+            upserts: vec![(station("s1"), "c".to_string())],
+            deletes: vec!["s1".to_string()],
+        };
+        db.apply_delta("stations", &delta, &Crs::default())
+            .await
+            .unwrap();
+        assert!(db.checksums("stations").await.unwrap().is_empty());
+        assert!(db
+            .read_feature("stations", "s1", &Crs::default())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[sqlx::test]
+    async fn list_jobs(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        for (id, process) in [
+            ("j1", "harvest:a"),
+            ("j2", "harvest:b"),
+            ("j3", "harvest:a"),
+        ] {
+            let job = StatusInfo {
+                job_id: id.to_string(),
+                process_id: Some(process.to_string()),
+                ..Default::default()
+            };
+            db.register(&job).await.unwrap();
+        }
+
+        let query = JobQuery {
+            process_id: Some("harvest:a".to_string()),
+            ..Default::default()
+        };
+        let jobs = db.list_jobs(&query).await.unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs
+            .iter()
+            .all(|job| job.process_id.as_deref() == Some("harvest:a")));
+
+        let query = JobQuery {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(db.list_jobs(&query).await.unwrap().len(), 1);
+    }
+}
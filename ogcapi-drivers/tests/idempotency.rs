@@ -0,0 +1,49 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use ogcapi_drivers::{postgres::Db, Idempotency, IdempotencyKeys, StoredResponse};
+
+    #[sqlx::test]
+    async fn idempotency_keys(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+        let request = "POST /collections/parcels/items";
+
+        let claim = db.claim_key("alice", "k1", request, "a1").await.unwrap();
+        assert_eq!(claim, Idempotency::Claimed);
+
+        // keys are scoped
+        let claim = db.claim_key("bob", "k1", request, "b1").await.unwrap();
+        assert_eq!(claim, Idempotency::Claimed);
+
+        let claim = db.claim_key("alice", "k1", request, "a2").await.unwrap();
+        assert_eq!(
+            claim,
+            Idempotency::InProgress {
+                request: request.to_string(),
+                fingerprint: "a1".to_string()
+            }
+        );
+
+        let response = StoredResponse {
+            status: 201,
+            headers: vec![("location".to_string(), "/items/1".to_string())],
+            body: Vec::new(),
+        };
+        db.complete_key("alice", "k1", &response).await.unwrap();
+
+        // completed keys are not released
+        db.release_key("alice", "k1").await.unwrap();
+        let claim = db.claim_key("alice", "k1", request, "a1").await.unwrap();
+        assert_eq!(
+            claim,
+            Idempotency::Completed {
+                request: request.to_string(),
+                fingerprint: "a1".to_string(),
+                response
+            }
+        );
+
+        db.release_key("bob", "k1").await.unwrap();
+        let claim = db.claim_key("bob", "k1", request, "b1").await.unwrap();
+        assert_eq!(claim, Idempotency::Claimed);
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(feature = "postgres")]
 mod postgres {
-    use ogcapi_drivers::{postgres::Db, JobHandler};
+    use std::time::Duration;
+
+    use ogcapi_drivers::{postgres::Db, JobHandler, JobQueue, Leases};
     use ogcapi_types::processes::{StatusCode, StatusInfo};
 
     #[sqlx::test]
@@ -25,4 +27,63 @@ mod postgres {
 
         assert_eq!(info.unwrap().status, StatusCode::Dismissed)
     }
+
+    #[sqlx::test]
+    async fn job_queue(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let job = StatusInfo {
+            job_id: "queued-job".to_string(),
+            process_id: Some("greet".to_string()),
+            ..Default::default()
+        };
+        db.register(&job).await.unwrap();
+
+        let inputs = serde_json::json!({ "name": "World" });
+        db.enqueue(&job.job_id, "greet", &inputs, Some("k1"))
+            .await
+            .unwrap();
+
+        // claimed by one worker at a time
+        let lease = Duration::from_secs(60);
+        let claimed = db.claim_job("a", lease).await.unwrap().unwrap();
+        assert_eq!(claimed.job_id, job.job_id);
+        assert_eq!(claimed.inputs, inputs);
+        assert_eq!(claimed.attempts, 1);
+        assert_eq!(claimed.key.as_deref(), Some("k1"));
+        assert!(db.claim_job("b", lease).await.unwrap().is_none());
+
+        assert!(db.heartbeat(&job.job_id, "a", lease).await.unwrap());
+        assert!(!db.heartbeat(&job.job_id, "b", lease).await.unwrap());
+
+        // orphaned once the lease expired
+        db.heartbeat(&job.job_id, "a", Duration::ZERO)
+            .await
+            .unwrap();
+        let claimed = db.claim_job("b", lease).await.unwrap().unwrap();
+        assert_eq!(claimed.attempts, 2);
+        assert!(!db.heartbeat(&job.job_id, "a", lease).await.unwrap());
+
+        db.complete_job(&job.job_id).await.unwrap();
+        db.heartbeat(&job.job_id, "b", Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(db.claim_job("c", lease).await.unwrap().is_none());
+    }
+
+    #[sqlx::test]
+    async fn leases(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+        let lease = Duration::from_secs(60);
+
+        assert!(db.lead("dispatcher", "a", lease).await.unwrap());
+        assert!(!db.lead("dispatcher", "b", lease).await.unwrap());
+        assert!(db.lead("dispatcher", "a", lease).await.unwrap());
+        assert!(db.lead("other", "b", lease).await.unwrap());
+
+        // taken over once expired
+        db.lead("dispatcher", "a", Duration::ZERO).await.unwrap();
+        assert!(db.lead("dispatcher", "b", lease).await.unwrap());
+        assert!(!db.lead("dispatcher", "a", lease).await.unwrap());
+    }
 }
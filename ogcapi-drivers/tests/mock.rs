@@ -0,0 +1,162 @@
+#[cfg(feature = "mock")]
+mod mock {
+    use std::time::{Duration, Instant};
+
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    use ogcapi_drivers::{
+        mock::{Fault, Mock},
+        CollectionTransactions, FeatureTransactions, JobHandler,
+    };
+    use ogcapi_types::{
+        common::{Collection, Crs, Query as CollectionQuery},
+        features::{Feature, Query},
+        processes::{JobQuery, StatusCode, StatusInfo},
+    };
+
+    fn place(canton: &str) -> Feature {
+        serde_json::from_value(json!({
+            "type": "Feature",
+            "properties": { "canton": canton },
+            "geometry": { "type": "Point", "coordinates": [7.4474, 46.948] }
+        }))
+        .unwrap()
+    }
+
+    fn mock() -> Mock {
+        Mock::new()
+            .with_collection(Collection {
+                id: "places".to_string(),
+                ..Default::default()
+            })
+            .with_features("places", [place("BE"), place("ZH"), place("BE")])
+    }
+
+    #[tokio::test]
+    async fn canned_data() {
+        let mock = mock();
+
+        let collections = mock
+            .list_collections(&CollectionQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(collections.collections.len(), 1);
+
+        let mut query = Query {
+            limit: Some(1),
+            ..Default::default()
+        };
+        query
+            .additional_parameters
+            .insert("canton".to_string(), "BE".to_string());
+        let fc = mock.list_items("places", &query).await.unwrap();
+        assert_eq!(fc.number_matched, Some(2));
+        assert_eq!(fc.features.len(), 1);
+
+        let id = mock
+            .create_feature(&Feature {
+                collection: Some("places".to_string()),
+                ..place("GE")
+            })
+            .await
+            .unwrap();
+        let feature = mock
+            .read_feature("places", &id, &Crs::default())
+            .await
+            .unwrap();
+        assert!(feature.is_some());
+
+        let features: Vec<Feature> = mock
+            .stream_items("places", &Query::default())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(features.len(), 4);
+
+        let job = StatusInfo {
+            job_id: "job".to_string(),
+            status: StatusCode::Running,
+            ..Default::default()
+        };
+        mock.register(&job).await.unwrap();
+        let dismissed = mock.dismiss("job").await.unwrap().unwrap();
+        assert_eq!(dismissed.status, StatusCode::Dismissed);
+        assert!(mock.dismiss("job").await.unwrap().is_none());
+        assert_eq!(mock.list_jobs(&JobQuery::default()).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn versions() {
+        let mock = mock();
+
+        let mut feature = mock
+            .read_feature("places", "1", &Crs::default())
+            .await
+            .unwrap()
+            .unwrap();
+        feature.collection = Some("places".to_string());
+        feature.set_version(1);
+
+        // stored features without version count as version 0
+        assert!(!mock.update_feature_if_version(&feature, 1).await.unwrap());
+        assert!(mock.update_feature_if_version(&feature, 0).await.unwrap());
+
+        feature.set_version(2);
+        assert!(!mock.update_feature_if_version(&feature, 0).await.unwrap());
+        assert!(mock.update_feature_if_version(&feature, 1).await.unwrap());
+
+        let stored = mock
+            .read_feature("places", "1", &Crs::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.version(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn faults() {
+        let mock = mock();
+
+        // delays apply to the operation only
+        mock.inject(Fault::delay(Duration::from_millis(100)).on("list_items"));
+        let start = Instant::now();
+        mock.list_items("places", &Query::default()).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        let start = Instant::now();
+        mock.read_collection("places").await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(100));
+        mock.clear_faults();
+
+        // lost connection, once
+        mock.inject(Fault::connection_lost().on("read_feature").times(1));
+        let e = mock
+            .read_feature("places", "1", &Crs::default())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::ConnectionReset
+        );
+        assert!(mock
+            .read_feature("places", "1", &Crs::default())
+            .await
+            .unwrap()
+            .is_some());
+
+        // serialization errors surface in streams
+        mock.inject(Fault::serialization().on("stream_items"));
+        let e = mock
+            .stream_items("places", &Query::default())
+            .try_collect::<Vec<Feature>>()
+            .await
+            .unwrap_err();
+        assert!(e.downcast_ref::<serde_json::Error>().is_some());
+
+        // arbitrary errors on all operations
+        mock.clear_faults();
+        mock.inject(Fault::error("Backend unavailable"));
+        let e = mock.status("job").await.unwrap_err();
+        assert_eq!(e.to_string(), "Backend unavailable");
+    }
+}
@@ -0,0 +1,18 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use ogcapi_drivers::{postgres::Db, ModeTransactions};
+
+    #[sqlx::test]
+    async fn mode(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        assert_eq!(db.read_mode().await.unwrap(), None);
+
+        db.write_mode("read-only").await.unwrap();
+        db.write_mode("maintenance").await.unwrap();
+        assert_eq!(
+            db.read_mode().await.unwrap().as_deref(),
+            Some("maintenance")
+        );
+    }
+}
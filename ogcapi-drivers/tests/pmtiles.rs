@@ -0,0 +1,161 @@
+#[cfg(feature = "pmtiles")]
+mod pmtiles {
+    use serde_json::json;
+
+    use ogcapi_drivers::pmtiles::{serialize, tile_id, Archive, Entry};
+
+    fn read_varint(buf: &[u8], pos: &mut usize) -> u64 {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = buf[*pos];
+            *pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return value;
+            }
+            shift += 7;
+        }
+    }
+
+    fn deserialize(buf: &[u8]) -> Vec<Entry> {
+        let mut pos = 0;
+        let n = read_varint(buf, &mut pos) as usize;
+
+        let mut entries = vec![
+            Entry {
+                tile_id: 0,
+                offset: 0,
+                length: 0,
+                run_length: 0
+            };
+            n
+        ];
+        let mut last_id = 0;
+        for entry in entries.iter_mut() {
+            last_id += read_varint(buf, &mut pos);
+            entry.tile_id = last_id;
+        }
+        for entry in entries.iter_mut() {
+            entry.run_length = read_varint(buf, &mut pos) as u32;
+        }
+        for entry in entries.iter_mut() {
+            entry.length = read_varint(buf, &mut pos) as u32;
+        }
+        for i in 0..n {
+            let offset = read_varint(buf, &mut pos);
+            entries[i].offset = if offset == 0 {
+                entries[i - 1].offset + u64::from(entries[i - 1].length)
+            } else {
+                offset - 1
+            };
+        }
+
+        entries
+    }
+
+    fn u64_at(buf: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(buf[offset..offset + 8].try_into().unwrap())
+    }
+
+    #[test]
+    fn tile_ids() {
+        assert_eq!(tile_id(0, 0, 0), 0);
+        assert_eq!(tile_id(1, 0, 0), 1);
+        assert_eq!(tile_id(1, 0, 1), 2);
+        assert_eq!(tile_id(1, 1, 1), 3);
+        assert_eq!(tile_id(1, 1, 0), 4);
+        assert_eq!(tile_id(2, 0, 0), 5);
+        assert_eq!(tile_id(12, 3423, 1763), 19078479);
+    }
+
+    #[test]
+    fn directory_round_trip() {
+        let entries = vec![
+            Entry {
+                tile_id: 0,
+                offset: 0,
+                length: 120,
+                run_length: 1,
+            },
+            Entry {
+                tile_id: 3,
+                offset: 120,
+                length: 4000,
+                run_length: 1,
+            },
+            Entry {
+                tile_id: 300,
+                offset: 10_000,
+                length: 64,
+                run_length: 1,
+            },
+        ];
+
+        assert_eq!(deserialize(&serialize(&entries)), entries);
+    }
+
+    #[test]
+    fn archive() {
+        let mut archive = Archive::new(
+            0,
+            1,
+            [5.9, 45.8, 10.5, 47.8],
+            json!({ "vector_layers": [{ "id": "countries", "fields": {} }] }),
+        );
+        archive.add_tile(tile_id(0, 0, 0), 100).unwrap();
+        archive.add_tile(tile_id(1, 1, 0), 50).unwrap();
+        assert!(archive.add_tile(tile_id(1, 0, 0), 10).is_err());
+
+        let head = archive.head().unwrap();
+        assert_eq!(&head[..7], b"PMTiles");
+        assert_eq!(head[7], 3);
+
+        // sections follow each other up to the tile data
+        let (root_offset, root_length) = (u64_at(&head, 8), u64_at(&head, 16));
+        let (metadata_offset, metadata_length) = (u64_at(&head, 24), u64_at(&head, 32));
+        assert_eq!(root_offset, 127);
+        assert_eq!(metadata_offset, root_offset + root_length);
+        assert_eq!(u64_at(&head, 56), head.len() as u64);
+        assert_eq!(u64_at(&head, 64), 150);
+
+        let root = &head[root_offset as usize..(root_offset + root_length) as usize];
+        let entries = deserialize(root);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].tile_id, 4);
+        assert_eq!(entries[1].offset, 100);
+
+        let metadata: serde_json::Value = serde_json::from_slice(
+            &head[metadata_offset as usize..(metadata_offset + metadata_length) as usize],
+        )
+        .unwrap();
+        assert_eq!(metadata["vector_layers"][0]["id"], "countries");
+    }
+
+    #[test]
+    fn leaf_directories() {
+        let mut archive = Archive::new(0, 14, [-180.0, -85.0, 180.0, 85.0], json!({}));
+        for i in 0..100_000u64 {
+            archive.add_tile(i * 3, 1000 + (i % 500) as u32).unwrap();
+        }
+
+        let head = archive.head().unwrap();
+        let (root_length, leaves_offset, leaves_length) =
+            (u64_at(&head, 16), u64_at(&head, 40), u64_at(&head, 48));
+        assert!(127 + root_length <= 16_384);
+        assert!(leaves_length > 0);
+
+        // the root points to the leaves, covering all tiles
+        let root = deserialize(&head[127..127 + root_length as usize]);
+        assert!(root.iter().all(|e| e.run_length == 0));
+
+        let mut tiles = 0;
+        for entry in &root {
+            let start = (leaves_offset + entry.offset) as usize;
+            let leaf = deserialize(&head[start..start + entry.length as usize]);
+            assert_eq!(leaf[0].tile_id, entry.tile_id);
+            tiles += leaf.len();
+        }
+        assert_eq!(tiles, 100_000);
+    }
+}
@@ -0,0 +1,82 @@
+use ogcapi_drivers::raster::{fit, values, DataType, Grid, RasterRequest, MAX_SIDE, MAX_VALUES};
+use ogcapi_types::common::Crs;
+
+fn grid() -> Grid {
+    Grid {
+        width: 4000,
+        height: 2000,
+        bbox: [0.0, 0.0, 40.0, 20.0],
+        crs: Crs::default(),
+        bands: Vec::new(),
+        overviews: vec![(2000, 1000), (1000, 500)],
+    }
+}
+
+#[test]
+fn native_size() {
+    let grid = grid();
+    assert_eq!(grid.resolution(), (0.01, 0.01));
+    assert_eq!(grid.native_size(&[10.0, 10.0, 20.0, 15.0]), (1000, 500));
+    // windows smaller than a pixel still have one
+    assert_eq!(grid.native_size(&[0.0, 0.0, 0.001, 0.001]), (1, 1));
+}
+
+#[test]
+fn request_size() {
+    let request = RasterRequest {
+        width: Some(256),
+        ..Default::default()
+    };
+    assert_eq!(request.size((1000, 500), 1), (256, 128));
+
+    let request = RasterRequest {
+        height: Some(100),
+        ..Default::default()
+    };
+    assert_eq!(request.size((1000, 500), 1), (200, 100));
+
+    // the native size, scaled down to the limit over all bands
+    let request = RasterRequest::default();
+    assert_eq!(request.size((1000, 500), 3), (1000, 500));
+    let (width, height) = request.size((40000, 20000), 4);
+    assert!(width * height * 4 <= MAX_VALUES);
+    assert_eq!(width / height, 2);
+
+    // derived sides are capped
+    let request = RasterRequest {
+        width: Some(16),
+        ..Default::default()
+    };
+    assert_eq!(request.size((1, 100000), 1), (16, MAX_SIDE));
+    let request = RasterRequest {
+        height: Some(MAX_SIDE),
+        ..Default::default()
+    };
+    let (width, _) = request.size((100000, 1), 4);
+    assert!(width * MAX_SIDE * 4 <= MAX_VALUES);
+}
+
+#[test]
+fn values_overflow() {
+    assert_eq!(values(256, 256, 3), Some(196608));
+    assert_eq!(values(usize::MAX, 2, 1), None);
+    assert_eq!(values(1 << 40, 1 << 20, 1 << 10), None);
+}
+
+#[test]
+fn fit_size() {
+    assert_eq!(fit(100, 50, 10000), (100, 50));
+    assert_eq!(fit(200, 100, 5000), (100, 50));
+    assert_eq!(fit(10000, 1, 100), (100, 1));
+    assert_eq!(fit(usize::MAX, 2, 100), (100, 1));
+}
+
+#[test]
+fn data_type_union() {
+    assert_eq!(DataType::Byte.union(DataType::Byte), DataType::Byte);
+    assert_eq!(DataType::Byte.union(DataType::Int16), DataType::Int16);
+    assert_eq!(DataType::UInt16.union(DataType::Int16), DataType::Int32);
+    assert_eq!(DataType::Int32.union(DataType::Float32), DataType::Float64);
+    assert_eq!(DataType::UInt32.union(DataType::Int16), DataType::Float64);
+    assert_eq!(DataType::Float32.union(DataType::Byte), DataType::Float32);
+}
@@ -0,0 +1,152 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use serde_json::json;
+
+    use ogcapi_drivers::{
+        postgres::Db, CollectionTransactions, FeatureTransactions, TileTransactions,
+    };
+    use ogcapi_types::{
+        common::{Collection, Crs},
+        features::Feature,
+    };
+
+    #[sqlx::test]
+    async fn reproject(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let collection = Collection {
+            id: "stations".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let features: Vec<Feature> = serde_json::from_value(json!([
+            {
+                "type": "Feature",
+                "id": "bern",
+                "properties": {},
+                "geometry": { "type": "Point", "coordinates": [7.4474, 46.948] }
+            },
+            {
+                "type": "Feature",
+                "id": "zurich",
+                "properties": {},
+                "geometry": { "type": "Point", "coordinates": [8.5417, 47.3769] }
+            }
+        ]))
+        .unwrap();
+        db.create_features("stations", &features, &Crs::default())
+            .await
+            .unwrap();
+
+        let lv95 = Crs::from_epsg(2056);
+        assert_eq!(
+            db.reproject_features("stations", &lv95, 1).await.unwrap(),
+            (1, 2)
+        );
+
+        // written meanwhile, reprojected on the swap
+        let mut moved = features[0].clone();
+        moved.collection = Some("stations".to_string());
+        moved.geometry = serde_json::from_value(json!({
+            "type": "Point",
+            "coordinates": [7.44, 46.95]
+        }))
+        .unwrap();
+        db.update_feature(&moved).await.unwrap();
+
+        db.finish_reprojection("stations", &lv95).await.unwrap();
+
+        let collection = db.read_collection("stations").await.unwrap().unwrap();
+        assert_eq!(collection.storage_crs, Some(lv95.clone()));
+        assert!(collection.crs.contains(&lv95));
+
+        let stored = db
+            .read_feature("stations", "bern", &lv95)
+            .await
+            .unwrap()
+            .unwrap();
+        let geojson::Value::Point(position) = stored.geometry.value else {
+            panic!("point expected");
+        };
+        assert!((position[0] - 2_600_000.0).abs() < 1000.0);
+
+        let bern = db
+            .read_feature("stations", "bern", &Crs::default())
+            .await
+            .unwrap()
+            .unwrap();
+        let geojson::Value::Point(position) = bern.geometry.value else {
+            panic!("point expected");
+        };
+        assert!((position[0] - 7.44).abs() < 1e-6);
+    }
+
+    #[sqlx::test]
+    async fn reproject_generalized(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let collection = Collection {
+            id: "cantons".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+
+        let features: Vec<Feature> = serde_json::from_value(json!([
+            {
+                "type": "Feature",
+                "id": "bern",
+                "properties": {},
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[7.0, 46.5], [8.0, 46.5], [8.0, 47.2], [7.0, 47.2], [7.0, 46.5]]]
+                }
+            }
+        ]))
+        .unwrap();
+        db.create_features("cantons", &features, &Crs::default())
+            .await
+            .unwrap();
+
+        // adds the generalized columns with their trigger
+        db.generalize().await.unwrap();
+
+        let lv95 = Crs::from_epsg(2056);
+        db.reproject_features("cantons", &lv95, 10).await.unwrap();
+        db.finish_reprojection("cantons", &lv95).await.unwrap();
+
+        let collection = db.read_collection("cantons").await.unwrap().unwrap();
+        assert_eq!(collection.storage_crs, Some(lv95.clone()));
+
+        // reset by the swap, computed again from the reprojected geometries
+        let reset: i64 =
+            sqlx::query_scalar(r#"SELECT count(*) FROM items.cantons WHERE geom_z4 IS NULL"#)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(reset, 1);
+
+        db.generalize().await.unwrap();
+
+        let generalized: i64 =
+            sqlx::query_scalar(r#"SELECT count(*) FROM items.cantons WHERE geom_z4 IS NOT NULL"#)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(generalized, 1);
+
+        // changes reset them again
+        let mut moved = features[0].clone();
+        moved.collection = Some("cantons".to_string());
+        db.update_feature(&moved).await.unwrap();
+
+        let reset: i64 =
+            sqlx::query_scalar(r#"SELECT count(*) FROM items.cantons WHERE geom_z4 IS NULL"#)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(reset, 1);
+    }
+}
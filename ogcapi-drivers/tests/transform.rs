@@ -0,0 +1,89 @@
+use ogcapi_drivers::transform::{NativeTransform, Transform};
+use ogcapi_types::{
+    common::{Bbox, Crs},
+    features::Geometry,
+};
+
+fn assert_close(a: (f64, f64), b: (f64, f64), tolerance: f64) {
+    assert!(
+        (a.0 - b.0).abs() < tolerance && (a.1 - b.1).abs() < tolerance,
+        "{a:?} != {b:?}"
+    );
+}
+
+#[test]
+fn native_projections() {
+    let transform = NativeTransform;
+    let wgs84 = Crs::default();
+
+    // web mercator
+    let mut coords = [(180.0, 0.0), (0.0, 85.0511287798066)];
+    transform
+        .transform_coords(&wgs84, &Crs::from_epsg(3857), &mut coords)
+        .unwrap();
+    assert_close(coords[0], (20037508.342789244, 0.0), 1e-6);
+    assert_close(coords[1], (0.0, 20037508.342789244), 1e-3);
+
+    // utm zone 32N, northing at the central meridian is the scaled meridian arc
+    let mut coords = [(9.0, 0.0), (9.0, 45.0)];
+    transform
+        .transform_coords(&wgs84, &Crs::from_epsg(32632), &mut coords)
+        .unwrap();
+    assert_close(coords[0], (500000.0, 0.0), 1e-6);
+    assert_close(coords[1], (500000.0, 4982950.4), 0.1);
+
+    // round trip across projections
+    let original = [(7.4474, 46.948), (-70.6483, -33.4569)];
+    let mut coords = original;
+    transform
+        .transform_coords(&wgs84, &Crs::from_epsg(32719), &mut coords[1..])
+        .unwrap();
+    transform
+        .transform_coords(
+            &Crs::from_epsg(32719),
+            &Crs::from_epsg(3857),
+            &mut coords[1..],
+        )
+        .unwrap();
+    transform
+        .transform_coords(&Crs::from_epsg(3857), &wgs84, &mut coords[1..])
+        .unwrap();
+    assert_close(coords[1], original[1], 1e-7);
+
+    assert!(!transform.supports(&Crs::from_epsg(2056)));
+    assert!(transform
+        .transform_coords(&wgs84, &Crs::from_epsg(2056), &mut coords)
+        .is_err());
+}
+
+#[test]
+fn native_geometry_and_bbox() {
+    let transform = NativeTransform;
+    let wgs84 = Crs::default();
+    let mercator = Crs::from_epsg(3857);
+
+    let mut geometry = Geometry::new(geojson::Value::LineString(vec![
+        vec![0.0, 0.0, 10.0],
+        vec![180.0, 0.0, 20.0],
+    ]));
+    transform
+        .transform_geometry(&wgs84, &mercator, &mut geometry)
+        .unwrap();
+    let geojson::Value::LineString(line) = geometry.value else {
+        panic!("geometry type changed");
+    };
+    assert_close((line[1][0], line[1][1]), (20037508.342789244, 0.0), 1e-6);
+    assert_eq!((line[0][2], line[1][2]), (10.0, 20.0));
+
+    let bbox = transform
+        .transform_bbox(
+            &Crs::from_epsg(32632),
+            &wgs84,
+            &Bbox::Bbox2D([300000.0, 5000000.0, 700000.0, 5200000.0]),
+        )
+        .unwrap();
+    let [minx, miny, maxx, maxy] = bbox.to_2d();
+    // the western edge bulges out at the northern corner
+    assert!(minx < 6.4 && maxx > 11.6);
+    assert!(miny < 45.2 && maxy > 46.9);
+}
@@ -0,0 +1,131 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use serde_json::json;
+
+    use ogcapi_drivers::{postgres::Db, CollectionTransactions, FeatureTransactions};
+    use ogcapi_types::{
+        common::{Collection, Crs},
+        features::{Feature, Rule, Severity, ValidationRule},
+    };
+
+    fn parcel(id: Option<&str>, owner: Option<&str>, coordinates: serde_json::Value) -> Feature {
+        serde_json::from_value(json!({
+            "type": "Feature",
+            "id": id,
+            "collection": "parcels",
+            "properties": { "owner": owner },
+            "geometry": { "type": "Polygon", "coordinates": coordinates }
+        }))
+        .unwrap()
+    }
+
+    fn square(x: f64, y: f64) -> serde_json::Value {
+        json!([[
+            [x, y],
+            [x + 1.0, y],
+            [x + 1.0, y + 1.0],
+            [x, y + 1.0],
+            [x, y]
+        ]])
+    }
+
+    #[sqlx::test]
+    async fn validation_rules(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let collection = Collection {
+            id: "parcels".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        };
+        db.create_collection(&collection).await.unwrap();
+        db.create_features(
+            "parcels",
+            &[parcel(Some("p1"), Some("Anna"), square(0.0, 0.0))],
+            &Crs::default(),
+        )
+        .await
+        .unwrap();
+
+        let rules = vec![
+            ValidationRule {
+                rule: Rule::ValidGeometry,
+                severity: Severity::Error,
+            },
+            ValidationRule {
+                rule: Rule::NoOverlap,
+                severity: Severity::Warning,
+            },
+            ValidationRule {
+                rule: Rule::RequiredProperties {
+                    properties: vec!["owner".to_string()],
+                },
+                severity: Severity::Error,
+            },
+        ];
+
+        let bowtie = json!([[[0.0, 0.0], [1.0, 1.0], [1.0, 0.0], [0.0, 1.0], [0.0, 0.0]]]);
+        let features = [
+            // overlapping the stored parcel
+            parcel(Some("p2"), Some("Ben"), square(0.5, 0.5)),
+            parcel(None, None, square(10.0, 10.0)),
+            parcel(Some("p3"), Some("Cleo"), bowtie),
+            // overlapping the second new parcel
+            parcel(Some("p4"), Some("Dan"), square(10.5, 10.5)),
+            // touching the stored parcel only
+            parcel(Some("p5"), Some("Eve"), square(1.0, 0.0)),
+        ];
+
+        let violations = db
+            .validate_features("parcels", &features, &Crs::default(), &rules)
+            .await
+            .unwrap();
+
+        let found: Vec<(Option<usize>, &str, Severity)> = violations
+            .iter()
+            .map(|v| (v.index, v.rule.as_str(), v.severity))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Some(2), "validGeometry", Severity::Error),
+                (Some(0), "noOverlap", Severity::Warning),
+                (Some(3), "noOverlap", Severity::Warning),
+                (Some(1), "requiredProperties", Severity::Error),
+            ]
+        );
+        assert_eq!(violations[1].message, "Overlaps feature `p1`");
+        assert_eq!(violations[2].message, "Overlaps feature 1");
+        assert_eq!(violations[3].message, "Missing property `owner`");
+
+        // an update doesn't overlap its previous geometry
+        let update = parcel(Some("p1"), Some("Anna"), square(0.2, 0.0));
+        assert!(db
+            .validate_features("parcels", &[update], &Crs::default(), &rules)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // stored features
+        db.create_features(
+            "parcels",
+            &[parcel(Some("p2"), None, square(0.5, 0.5))],
+            &Crs::default(),
+        )
+        .await
+        .unwrap();
+        let violations = db.validate_collection("parcels", &rules).await.unwrap();
+        let found: Vec<(Option<&str>, &str)> = violations
+            .iter()
+            .map(|v| (v.feature.as_deref(), v.rule.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Some("p1"), "noOverlap"),
+                (Some("p2"), "requiredProperties")
+            ]
+        );
+        assert!(violations.iter().all(|v| v.index.is_none()));
+    }
+}
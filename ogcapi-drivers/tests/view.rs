@@ -0,0 +1,48 @@
+#[cfg(feature = "postgres")]
+mod postgres {
+    use ogcapi_drivers::{postgres::Db, ViewTransactions};
+    use ogcapi_types::views::View;
+
+    #[sqlx::test]
+    async fn views(pool: sqlx::PgPool) -> () {
+        let db = Db { pool };
+
+        let mut view = View {
+            title: Some("Large parcels".to_string()),
+            collection: "parcels".to_string(),
+            filter: Some("area > 1000".to_string()),
+            owner: Some("alice".to_string()),
+            ..Default::default()
+        };
+        let id = db.create_view(&view).await.unwrap();
+
+        let stored = db.read_view(&id).await.unwrap().unwrap();
+        assert_eq!(stored.id, id);
+        assert_eq!(stored.owner.as_deref(), Some("alice"));
+        assert_eq!(stored.filter, view.filter);
+        assert!(stored.created.is_some());
+
+        // views are listed per owner
+        db.create_view(&View {
+            collection: "parcels".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(db.list_views(Some("alice")).await.unwrap().len(), 1);
+        assert_eq!(db.list_views(Some("bob")).await.unwrap().len(), 0);
+        assert_eq!(db.list_views(None).await.unwrap().len(), 1);
+
+        // updates keep the owner
+        view.id = id.to_owned();
+        view.owner = Some("bob".to_string());
+        view.shared = true;
+        db.update_view(&view).await.unwrap();
+        let stored = db.read_view(&id).await.unwrap().unwrap();
+        assert!(stored.shared);
+        assert_eq!(stored.owner.as_deref(), Some("alice"));
+
+        db.delete_view(&id).await.unwrap();
+        assert!(db.read_view(&id).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(any(feature = "geopackage", feature = "postgres"))]
+mod wkb {
+    use geojson::{Geometry, Value};
+
+    use ogcapi_drivers::wkb;
+
+    fn decode_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn gpkg_round_trip() {
+        let values = [
+            Value::Point(vec![7.4474, 46.948, 540.0]),
+            Value::LineString(vec![vec![0.0, 0.0], vec![1.0, 1.0]]),
+            Value::Polygon(vec![vec![
+                vec![0.0, 0.0],
+                vec![1.0, 0.0],
+                vec![1.0, 1.0],
+                vec![0.0, 0.0],
+            ]]),
+            Value::MultiPoint(vec![vec![0.0, 0.0], vec![1.0, 1.0]]),
+            Value::MultiPolygon(vec![
+                vec![vec![
+                    vec![0.0, 0.0],
+                    vec![1.0, 0.0],
+                    vec![1.0, 1.0],
+                    vec![0.0, 0.0],
+                ]],
+                vec![vec![
+                    vec![2.0, 2.0],
+                    vec![3.0, 2.0],
+                    vec![3.0, 3.0],
+                    vec![2.0, 2.0],
+                ]],
+            ]),
+            Value::GeometryCollection(vec![
+                Geometry::new(Value::Point(vec![0.0, 0.0])),
+                Geometry::new(Value::MultiLineString(vec![
+                    vec![vec![0.0, 0.0], vec![1.0, 1.0]],
+                    vec![vec![2.0, 2.0], vec![3.0, 3.0]],
+                ])),
+            ]),
+        ];
+
+        for value in values {
+            let blob = wkb::to_gpkg(&value, 4326);
+            assert_eq!(wkb::from_gpkg(&blob).unwrap(), Some(value));
+        }
+
+        // empty flag
+        let mut blob = wkb::to_gpkg(&Value::Point(vec![0.0, 0.0]), 4326);
+        blob[3] |= 0b0001_0000;
+        assert_eq!(wkb::from_gpkg(&blob).unwrap(), None);
+
+        assert!(wkb::from_gpkg(b"not a geometry").is_err());
+    }
+
+    #[test]
+    fn ewkb() {
+        // SRID=4326;POINT(1 2)
+        let point = decode_hex("0101000020E6100000000000000000F03F0000000000000040");
+        assert_eq!(
+            wkb::from_ewkb(&point).unwrap(),
+            Geometry::new(Value::Point(vec![1.0, 2.0]))
+        );
+
+        // SRID=4326;POINT Z(1 2 3), big endian
+        let point =
+            decode_hex("00A0000001000010E63FF000000000000040000000000000004008000000000000");
+        assert_eq!(
+            wkb::from_ewkb(&point).unwrap(),
+            Geometry::new(Value::Point(vec![1.0, 2.0, 3.0]))
+        );
+
+        // CIRCULARSTRING(0 0, 1 1, 2 0)
+        let curve = decode_hex(
+            "01080000000300000000000000000000000000000000000000000000000000F03F000000000000F03F00000000000000400000000000000000",
+        );
+        assert!(wkb::from_ewkb(&curve).is_err());
+    }
+    #[test]
+    fn text() {
+        // WKT
+        assert_eq!(
+            wkb::from_text("POINT Z (1 2 3)").unwrap(),
+            Geometry::new(Value::Point(vec![1.0, 2.0, 3.0]))
+        );
+        assert_eq!(
+            wkb::from_text("LINESTRING (0 0, 1 1)").unwrap(),
+            Geometry::new(Value::LineString(vec![vec![0.0, 0.0], vec![1.0, 1.0]]))
+        );
+
+        // hex encoded ISO WKB, as encoded
+        let value = Value::Point(vec![7.4474, 46.948, 540.0]);
+        let hex: String = wkb::to_wkb(&value)
+            .iter()
+            .map(|b| format!("{b:02X}"))
+            .collect();
+        assert_eq!(wkb::from_text(&hex).unwrap(), Geometry::new(value));
+
+        // hex encoded extended WKB, SRID=4326;POINT(1 2)
+        assert_eq!(
+            wkb::from_text("0101000020E6100000000000000000F03F0000000000000040").unwrap(),
+            Geometry::new(Value::Point(vec![1.0, 2.0]))
+        );
+
+        assert!(wkb::from_text("POINT (1)").is_err());
+        assert!(wkb::from_text("0101").is_err());
+    }
+}
@@ -0,0 +1,12 @@
+#!/bin/sh
+# Download the Swagger UI and ReDoc bundles embedded with the `ui-assets` feature
+set -e
+
+SWAGGER_UI_VERSION=4.11.1
+REDOC_VERSION=2.1.5
+
+cd "$(dirname "$0")"
+
+curl -fsSL -o swagger-ui-bundle.js "https://unpkg.com/swagger-ui-dist@$SWAGGER_UI_VERSION/swagger-ui-bundle.js"
+curl -fsSL -o swagger-ui.css "https://unpkg.com/swagger-ui-dist@$SWAGGER_UI_VERSION/swagger-ui.css"
+curl -fsSL -o redoc.standalone.js "https://cdn.jsdelivr.net/npm/redoc@$REDOC_VERSION/bundles/redoc.standalone.js"
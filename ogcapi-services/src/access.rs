@@ -0,0 +1,154 @@
+//! Feature level access filters
+//!
+//! Collections may restrict the features accessible to a user, or to requests
+//! without api key, with a `CQL2` filter. The filter is combined with the
+//! queries of the user, features not matching it are hidden and may not be
+//! written. Routes not able to apply filters refuse restricted users.
+//!
+//! Share links replace the access filter of read requests to the shared
+//! collection with the filter of the link.
+//!
+//! Single properties may be hidden as well, by the redactions configured in
+//! the metadata of a collection. They are removed from served features.
+
+use axum::http::{header::AUTHORIZATION, HeaderMap, StatusCode};
+
+use ogcapi_drivers::UserTransactions;
+#[cfg(any(feature = "features", feature = "stac"))]
+use ogcapi_types::common::Collection;
+#[cfg(feature = "features")]
+use ogcapi_types::cql2::Expr;
+
+#[cfg(feature = "features")]
+use crate::extractors::ShareLink;
+use crate::{extractors::API_KEY, AppState, Error, Result};
+
+/// Api key of a request, passed in the `X-API-Key` header or as bearer token
+pub(crate) fn api_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(API_KEY)
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+        })
+        .map(str::trim)
+}
+
+/// User of the api key of a request, `None` without valid api key
+pub(crate) async fn request_user(state: &AppState, headers: &HeaderMap) -> Option<String> {
+    let key = api_key(headers)?;
+
+    match state.db.verify_key(key).await {
+        Ok(user) => user,
+        Err(e) => {
+            tracing::warn!("Failed to verify api key: {e}");
+            None
+        }
+    }
+}
+
+/// Refuse requests without api key of a user configured as admin, for the
+/// admin API
+pub(crate) async fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<()> {
+    let Some(user) = request_user(state, headers).await else {
+        return Err(Error::Exception(
+            StatusCode::UNAUTHORIZED,
+            "The admin API requires an api key".to_string(),
+        ));
+    };
+
+    if !state.admins.contains(&user) {
+        return Err(Error::Exception(
+            StatusCode::FORBIDDEN,
+            format!("User `{user}` is not an admin"),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Access filter of the requesting user for a collection
+#[cfg(feature = "features")]
+pub(crate) async fn access_filter(
+    state: &AppState,
+    headers: &HeaderMap,
+    collection_id: &str,
+) -> Result<Option<Expr>> {
+    let user = request_user(state, headers).await;
+
+    let filter = state
+        .drivers
+        .access
+        .access_filter(collection_id, user.as_deref())
+        .await?;
+
+    Ok(filter)
+}
+
+/// Access filter of a read request, the filter of a share link for the
+/// collection takes precedence
+#[cfg(feature = "features")]
+pub(crate) async fn read_filter(
+    state: &AppState,
+    headers: &HeaderMap,
+    share: &ShareLink,
+    collection_id: &str,
+) -> Result<Option<Expr>> {
+    match &share.share {
+        Some(share) if share.collection == collection_id => Ok(share.filter.to_owned()),
+        _ => access_filter(state, headers, collection_id).await,
+    }
+}
+
+/// Properties of a collection hidden from the requesting user
+#[cfg(any(feature = "features", feature = "stac"))]
+pub(crate) async fn hidden_properties(
+    state: &AppState,
+    headers: &HeaderMap,
+    collection: &Collection,
+) -> Vec<String> {
+    if collection.redactions.is_empty() {
+        return Vec::new();
+    }
+
+    let user = request_user(state, headers).await;
+    collection.hidden_properties(user.as_deref())
+}
+
+/// Refuse users restricted by an access filter on any of the collections,
+/// for routes that can't apply the filters
+#[cfg(any(
+    feature = "coverages",
+    feature = "edr",
+    feature = "features",
+    feature = "joins",
+    feature = "stac",
+    feature = "tiles"
+))]
+pub(crate) async fn deny_restricted(
+    state: &AppState,
+    headers: &HeaderMap,
+    collections: &[&str],
+) -> Result<()> {
+    let user = request_user(state, headers).await;
+
+    for collection in collections {
+        let filter = state
+            .drivers
+            .access
+            .access_filter(collection, user.as_deref())
+            .await?;
+
+        if filter.is_some() {
+            return Err(Error::Exception(
+                StatusCode::FORBIDDEN,
+                format!("Access to collection `{collection}` is restricted to its features"),
+            ));
+        }
+    }
+
+    Ok(())
+}
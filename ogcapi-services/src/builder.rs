@@ -0,0 +1,381 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use axum::{extract::DefaultBodyLimit, middleware, routing::get, Router};
+use tower_http::{decompression::RequestDecompressionLayer, limit::RequestBodyLimitLayer};
+
+use ogcapi_drivers::postgres::Db;
+use ogcapi_types::common::{Conformance, ConformanceReport};
+
+#[cfg(feature = "features")]
+use crate::extents::Extents;
+use crate::{
+    extractors, idempotency,
+    load::{self, Shedder},
+    maintenance, metering,
+    openapi::OPENAPI,
+    routes::{self, Module},
+    AppState, OpenAPI,
+};
+
+/// Builder for the router of an OGC API, to be embedded into an existing
+/// `axum` application
+///
+/// The landing page, conformance declaration, API definition, collections and
+/// catalogs are always served, further modules are opt-in. Middleware is left
+/// to the embedding application.
+///
+/// ```rust,ignore
+/// let geo = OgcApiBuilder::new(db).await.features().tiles().nest_at("/geo");
+///
+/// let app = Router::new()
+///     .route("/", get(index))
+///     .merge(geo)
+///     .layer(TraceLayer::new_for_http());
+/// ```
+pub struct OgcApiBuilder {
+    state: AppState,
+    modules: Vec<Module>,
+    mounted: BTreeSet<&'static str>,
+    report: Option<ConformanceReport>,
+}
+
+impl OgcApiBuilder {
+    /// Create a builder with the default state for a database
+    pub async fn new(db: Db) -> Self {
+        let state = AppState::new_with(db, OpenAPI::from_slice(OPENAPI)).await;
+        OgcApiBuilder::from_state(state)
+    }
+
+    /// Create a builder with a preconfigured state, e.g. with custom services
+    pub fn from_state(state: AppState) -> Self {
+        OgcApiBuilder {
+            state,
+            modules: Vec::new(),
+            mounted: BTreeSet::new(),
+            report: None,
+        }
+        .mount("collections", routes::collections::module)
+        .mount("catalogs", routes::catalogs::module)
+    }
+
+    /// Withhold the conformance classes which failed the tests of a report
+    pub fn conformance_report(mut self, report: ConformanceReport) -> Self {
+        self.report = Some(report);
+        self
+    }
+
+    /// Enable all modules compiled into the crate
+    pub fn all(self) -> Self {
+        #[cfg(feature = "stac")]
+        let builder = self.stac();
+        #[cfg(not(feature = "stac"))]
+        let builder = self;
+        #[cfg(feature = "features")]
+        let builder = builder.features();
+        #[cfg(feature = "aggregate")]
+        let builder = builder.aggregate();
+        #[cfg(feature = "attachments")]
+        let builder = builder.attachments();
+        #[cfg(feature = "coverages")]
+        let builder = builder.coverages();
+        #[cfg(feature = "maps")]
+        let builder = builder.maps();
+        #[cfg(feature = "zarr")]
+        let builder = builder.zarr();
+        #[cfg(feature = "edr")]
+        let builder = builder.edr();
+        #[cfg(feature = "harvest")]
+        let builder = builder.harvests();
+        #[cfg(feature = "import")]
+        let builder = builder.import();
+        #[cfg(feature = "joins")]
+        let builder = builder.joins();
+        #[cfg(feature = "styles")]
+        let builder = builder.styles();
+        #[cfg(feature = "tiles")]
+        let builder = builder.tiles();
+        #[cfg(feature = "processes")]
+        let builder = builder.processes();
+        #[cfg(feature = "search")]
+        let builder = builder.search();
+        #[cfg(feature = "uploads")]
+        let builder = builder.uploads();
+        #[cfg(feature = "views")]
+        let builder = builder.views();
+        #[cfg(feature = "webhooks")]
+        let builder = builder.webhooks();
+        builder.tasks().usage().maintenance()
+    }
+
+    /// Serve the STAC API, with the item search at `/search`
+    #[cfg(feature = "stac")]
+    pub fn stac(self) -> Self {
+        self.mount("stac", routes::stac::module)
+    }
+
+    #[cfg(feature = "features")]
+    pub fn features(self) -> Self {
+        Extents::watch(&self.state);
+        self.mount("features", routes::features::module)
+    }
+
+    /// Serve the aggregation of features into spatial groups
+    #[cfg(feature = "aggregate")]
+    pub fn aggregate(self) -> Self {
+        self.mount("aggregate", routes::aggregate::module)
+    }
+
+    /// Serve the files attached to features, stored in object storage
+    #[cfg(feature = "attachments")]
+    pub fn attachments(self) -> Self {
+        self.mount("attachments", routes::attachments::module)
+    }
+
+    /// Serve the coverages of the rasters, see [AppState::rasters]
+    #[cfg(feature = "coverages")]
+    pub fn coverages(self) -> Self {
+        self.mount("coverages", routes::coverages::module)
+    }
+
+    /// Serve maps of the rasters, as map tiles as well with the `tiles`
+    /// feature
+    #[cfg(feature = "maps")]
+    pub fn maps(self) -> Self {
+        self.mount("maps", routes::maps::module)
+    }
+
+    /// Serve the rasters as read-only Zarr stores
+    #[cfg(feature = "zarr")]
+    pub fn zarr(self) -> Self {
+        self.mount("zarr", routes::zarr::module)
+    }
+
+    #[cfg(feature = "edr")]
+    pub fn edr(self) -> Self {
+        self.mount("edr", routes::edr::module)
+    }
+
+    /// Serve the harvest configuration and start running the harvests on
+    /// schedule
+    #[cfg(feature = "harvest")]
+    pub fn harvests(self) -> Self {
+        if !self.mounted.contains("harvests") {
+            crate::harvest::spawn(&self.state);
+        }
+        self.mount("harvests", routes::harvests::module)
+    }
+
+    #[cfg(feature = "import")]
+    pub fn import(self) -> Self {
+        self.mount("import", routes::import::module)
+    }
+
+    #[cfg(feature = "joins")]
+    pub fn joins(self) -> Self {
+        self.mount("joins", routes::joins::module)
+    }
+
+    #[cfg(feature = "styles")]
+    pub fn styles(self) -> Self {
+        self.mount("styles", routes::styles::module)
+    }
+
+    #[cfg(feature = "tiles")]
+    pub fn tiles(self) -> Self {
+        #[cfg(feature = "features")]
+        Extents::watch(&self.state);
+        if !self.mounted.contains("tiles") {
+            crate::generalize::spawn(&self.state);
+            crate::usage::spawn(&self.state);
+        }
+        self.mount("tiles", routes::tiles::module)
+    }
+
+    /// Serve the processes and start the workers of the job queue, if enabled
+    #[cfg(feature = "processes")]
+    pub fn processes(self) -> Self {
+        if !self.mounted.contains("processes") {
+            crate::queue::spawn(&self.state);
+        }
+        self.mount("processes", routes::processes::module)
+    }
+
+    /// Serve the search across collections at `/search`, or at
+    /// `/feature-search` if the STAC item search is mounted at `/search`
+    ///
+    /// The path is chosen when the router is built, in whichever order the
+    /// two are mounted.
+    #[cfg(feature = "search")]
+    pub fn search(mut self) -> Self {
+        self.mounted.insert("search");
+        self
+    }
+
+    /// Serve resumable uploads, to be referenced by imports and file uploads
+    #[cfg(feature = "uploads")]
+    pub fn uploads(self) -> Self {
+        self.mount("uploads", routes::uploads::module)
+    }
+
+    /// Serve the saved views of the features of collections
+    #[cfg(feature = "views")]
+    pub fn views(self) -> Self {
+        self.mount("views", routes::views::module)
+    }
+
+    /// Serve the webhook registration and start delivering events
+    #[cfg(feature = "webhooks")]
+    pub fn webhooks(self) -> Self {
+        if !self.mounted.contains("webhooks") {
+            crate::webhooks::Dispatcher::spawn(&self.state);
+        }
+        self.mount("webhooks", routes::webhooks::module)
+    }
+
+    /// Serve the admin API of the background tasks
+    pub fn tasks(self) -> Self {
+        self.mount("tasks", routes::tasks::module)
+    }
+
+    /// Report the usage and quotas of the api keys of a user at
+    /// `/admin/usage`, metered with [AppState::metering]
+    pub fn usage(self) -> Self {
+        self.mount("usage", routes::usage::module)
+    }
+
+    /// Serve the admin API switching the service to read-only or maintenance
+    /// mode at `/admin/mode`
+    pub fn maintenance(self) -> Self {
+        self.mount("maintenance", routes::maintenance::module)
+    }
+
+    /// Build the router, with the state applied
+    pub fn build<S>(self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let (router, state) = self.into_parts();
+        router.with_state(state)
+    }
+
+    /// Build the router nested at `path`, ready to be merged into an
+    /// application router
+    pub fn nest_at<S>(self, path: &str) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        Router::new().nest(path, self.build())
+    }
+
+    /// Router and state with the landing page and conformance declaration
+    /// assembled from the mounted modules
+    pub(crate) fn into_parts(self) -> (Router<AppState>, AppState) {
+        let mut state = self.state;
+
+        let mut router = Router::new()
+            .route("/", get(routes::root))
+            .route("/api", get(routes::api::api))
+            .route("/redoc", get(routes::api::redoc))
+            .route("/swagger", get(routes::api::swagger))
+            .route("/conformance", get(routes::conformance));
+
+        #[cfg(feature = "ui-assets")]
+        {
+            router = router
+                .route(
+                    "/swagger/swagger-ui-bundle.js",
+                    get(routes::api::swagger_ui_js),
+                )
+                .route("/swagger/swagger-ui.css", get(routes::api::swagger_ui_css))
+                .route("/redoc/redoc.standalone.js", get(routes::api::redoc_js));
+        }
+
+        // the search across collections moves aside for the STAC item search
+        #[cfg(feature = "search")]
+        let search = self.mounted.contains("search").then(|| {
+            #[cfg(feature = "stac")]
+            if self.mounted.contains("stac") {
+                return routes::search::stac_module();
+            }
+            routes::search::module()
+        });
+        #[cfg(not(feature = "search"))]
+        let search: Option<Module> = None;
+
+        let mut uploads = Router::new();
+
+        let mut root = state.root.as_ref().to_owned();
+        let mut conformance = Conformance::default();
+
+        for module in self.modules.into_iter().chain(search) {
+            router = router.merge(module.router);
+            uploads = uploads.merge(module.uploads);
+            root.links.extend(module.links);
+            for class in module.conformance {
+                if !conformance.conforms_to.iter().any(|c| c == class) {
+                    conformance.conforms_to.push(class.to_string());
+                }
+            }
+        }
+
+        if let Some(report) = &self.report {
+            conformance.retain_passed(report);
+        }
+
+        state.root = Arc::new(root);
+        state.conformance = Arc::new(conformance);
+
+        // uploads may be compressed (`gzip`, `zstd`), the limit applies to the
+        // decompressed body
+        let uploads = uploads
+            .layer(RequestBodyLimitLayer::new(state.limits.upload))
+            .layer(RequestDecompressionLayer::new());
+
+        // shed load per route class before reading bodies or taking database
+        // connections
+        let shedder = Shedder::new(state.route_limits);
+        let router = router.layer(middleware::from_fn_with_state(shedder.clone(), load::shed));
+        let uploads = uploads.layer(middleware::from_fn_with_state(shedder, load::shed_uploads));
+
+        // limit body sizes per route class, replacing the default limit of
+        // the extractors
+        let router = router
+            .layer(RequestBodyLimitLayer::new(state.limits.body))
+            .merge(uploads)
+            .layer(DefaultBodyLimit::disable());
+
+        // complete the transactions of write requests
+        let router = router.layer(middleware::from_fn(extractors::transaction));
+
+        // replay responses to retried requests, after the transaction completed
+        let router = router.layer(middleware::from_fn_with_state(
+            state.clone(),
+            idempotency::idempotency,
+        ));
+
+        // meter requests with api key, refusing keys beyond their quota
+        metering::spawn(&state);
+        let router = router.layer(middleware::from_fn_with_state(
+            state.clone(),
+            metering::meter,
+        ));
+
+        // refuse requests in read-only or maintenance mode before anything
+        // else
+        maintenance::spawn(&state);
+        let router = router.layer(middleware::from_fn_with_state(
+            state.clone(),
+            maintenance::guard,
+        ));
+
+        (router, state)
+    }
+
+    fn mount(mut self, name: &'static str, module: fn() -> Module) -> Self {
+        // modules set up global state, so they are only mounted once
+        if self.mounted.insert(name) {
+            self.modules.push(module());
+        }
+        self
+    }
+}
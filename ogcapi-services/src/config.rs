@@ -1,4 +1,14 @@
-use clap::Parser;
+use std::time::Duration;
+
+use axum::http::{header::ACCESS_CONTROL_REQUEST_METHOD, HeaderName, HeaderValue, Method};
+use clap::{Args, Parser};
+use openapiv3::{Contact, Info};
+use serde_json::Value;
+use tower_http::cors::{AllowHeaders, AllowOrigin, CorsLayer, ExposeHeaders};
+
+use ogcapi_types::common::{link_rel::TERMS_OF_SERVICE, LandingPage, Link};
+
+use crate::{Mode, RouteLimit, RouteLimits, UnknownParameters};
 
 /// Application configuration
 #[derive(Parser, Debug)]
@@ -11,8 +21,413 @@ pub struct Config {
     pub host: String,
     /// Postgres database url
     #[clap(long, env, hide_env_values = true, value_parser)]
-    pub database_url: url::Url,
+    #[cfg_attr(not(feature = "files"), clap(required = true))]
+    #[cfg_attr(feature = "files", clap(required_unless_present = "data_dir"))]
+    pub database_url: Option<url::Url>,
+    /// Directory of `GeoJSON`, `FlatGeobuf` and `GeoPackage` files to serve
+    /// read-only as collections, without database
+    #[cfg(feature = "files")]
+    #[clap(long, env, value_parser)]
+    pub data_dir: Option<std::path::PathBuf>,
+    /// Directory of GeoTIFF, COG and NetCDF files to serve as coverages and
+    /// maps of the collections named after the files
+    #[cfg(feature = "coverages")]
+    #[clap(long, env, value_parser)]
+    pub raster_dir: Option<std::path::PathBuf>,
+    /// Directory of uploads and job outputs, shared by the replicas of the
+    /// service, defaults to the temporary directory
+    #[cfg(any(feature = "processes", feature = "uploads"))]
+    #[clap(long, env, value_parser)]
+    pub work_dir: Option<std::path::PathBuf>,
     /// OpenAPI definition
     #[clap(long, env, value_parser)]
     pub openapi: Option<std::path::PathBuf>,
+    /// Conformance report, as written by `ogcapi conformance`, classes which
+    /// failed their tests are not advertised
+    #[clap(long, env, value_parser)]
+    pub conformance_report: Option<std::path::PathBuf>,
+    /// Maximum size of request bodies in bytes
+    #[clap(long, env, default_value = "2097152")]
+    pub body_limit: usize,
+    /// Maximum size of uploads (imports, process inputs, ...) in bytes
+    #[clap(long, env, default_value = "1073741824")]
+    pub upload_limit: usize,
+    /// Maximum number of features per page
+    #[clap(long, env, default_value = "10000")]
+    pub max_limit: usize,
+    /// Maximum area of bbox queries in square degrees
+    #[clap(long, env)]
+    pub max_bbox_area: Option<f64>,
+    /// Maximum estimated cost of queries scanning a collection without index,
+    /// applies to requests without api key
+    #[clap(long, env)]
+    pub max_scan_cost: Option<f64>,
+    /// Handling of unknown query parameters, `standard` rejects them where the
+    /// OGC standards require it and ignores them elsewhere
+    #[clap(long, env, value_enum, default_value_t = UnknownParameters::Standard)]
+    pub unknown_parameters: UnknownParameters,
+    /// Time in seconds after which database statements are aborted
+    #[clap(long, env)]
+    pub statement_timeout: Option<u64>,
+    /// Switch all replicas to a mode at startup, `read-only` refuses writes
+    /// and `maintenance` all requests except the admin API, the stored mode
+    /// is kept if missing
+    #[clap(long, env, value_enum)]
+    pub mode: Option<Mode>,
+    /// Users whose api keys grant access to the admin API at `/admin`
+    #[clap(long, env, value_delimiter = ',')]
+    pub admin_users: Vec<String>,
+    /// Meter the usage of the api keys and enforce their quotas
+    #[clap(long, env)]
+    pub metering: bool,
+    /// Secret signing share links, which grant temporary read access to
+    /// collections
+    #[cfg(feature = "features")]
+    #[clap(long, env, hide_env_values = true)]
+    pub share_secret: Option<String>,
+    /// Run the jobs of processes through a queue in the database shared with
+    /// the other replicas
+    #[cfg(feature = "processes")]
+    #[clap(long, env)]
+    pub job_queue: bool,
+    /// Number of queued jobs run at once by this replica
+    #[cfg(feature = "processes")]
+    #[clap(long, env, default_value = "2")]
+    pub job_workers: usize,
+    /// Time in seconds after which jobs of unresponsive workers are run again
+    #[cfg(feature = "processes")]
+    #[clap(long, env, default_value = "60")]
+    pub job_lease: u64,
+    /// MQTT broker url for publishing events, e.g. `mqtt://localhost:1883?client_id=ogcapi`
+    #[cfg(feature = "pubsub")]
+    #[clap(long, env, value_parser)]
+    pub mqtt_url: Option<url::Url>,
+    /// Topic template of feature changes
+    #[cfg(feature = "pubsub")]
+    #[clap(long, env, default_value = crate::pubsub::COLLECTION_TOPIC)]
+    pub mqtt_collection_topic: String,
+    /// Topic template of job status updates
+    #[cfg(feature = "pubsub")]
+    #[clap(long, env, default_value = crate::pubsub::JOB_TOPIC)]
+    pub mqtt_job_topic: String,
+    #[clap(flatten)]
+    pub cors: CorsConfig,
+    #[clap(flatten)]
+    pub request_log: RequestLogConfig,
+    #[clap(flatten)]
+    pub load: LoadConfig,
+    #[clap(flatten)]
+    pub landing: LandingConfig,
+}
+
+/// Timeouts in seconds and concurrency limits per class of routes, `0`
+/// disables a limit
+#[derive(Args, Debug, Clone)]
+pub struct LoadConfig {
+    /// Timeout of routes not covered by the other classes
+    #[clap(long, env, default_value = "0")]
+    pub request_timeout: u64,
+    /// Concurrency limit of routes not covered by the other classes
+    #[clap(long, env, default_value = "0")]
+    pub request_concurrency: usize,
+    /// Timeout of tile requests
+    #[clap(long, env, default_value = "10")]
+    pub tiles_timeout: u64,
+    /// Concurrency limit of tile requests
+    #[clap(long, env, default_value = "32")]
+    pub tiles_concurrency: usize,
+    /// Timeout of feature exports as `GeoJSON` sequence, imports and uploads
+    #[clap(long, env, default_value = "0")]
+    pub bulk_timeout: u64,
+    /// Concurrency limit of feature exports as `GeoJSON` sequence, imports
+    /// and uploads
+    #[clap(long, env, default_value = "4")]
+    pub bulk_concurrency: usize,
+    /// Timeout of process executions
+    #[clap(long, env, default_value = "0")]
+    pub processes_timeout: u64,
+    /// Concurrency limit of process executions
+    #[clap(long, env, default_value = "8")]
+    pub processes_concurrency: usize,
+}
+
+impl LoadConfig {
+    pub fn route_limits(&self) -> RouteLimits {
+        let limit = |timeout: u64, concurrency: usize| RouteLimit {
+            timeout: (timeout > 0).then(|| Duration::from_secs(timeout)),
+            concurrency: (concurrency > 0).then_some(concurrency),
+        };
+
+        RouteLimits {
+            interactive: limit(self.request_timeout, self.request_concurrency),
+            tiles: limit(self.tiles_timeout, self.tiles_concurrency),
+            bulk: limit(self.bulk_timeout, self.bulk_concurrency),
+            processes: limit(self.processes_timeout, self.processes_concurrency),
+        }
+    }
+}
+
+/// Branding of the landing page, also replacing the corresponding details of
+/// the `info` of the OpenAPI definition
+#[derive(Args, Debug, Clone)]
+pub struct LandingConfig {
+    /// Title of the API, the title of the OpenAPI definition if omitted
+    #[clap(long = "title", env = "API_TITLE")]
+    pub title: Option<String>,
+    /// Description of the API, the description of the OpenAPI definition if
+    /// omitted
+    #[clap(long = "description", env = "API_DESCRIPTION")]
+    pub description: Option<String>,
+    /// Short attribution of the data, e.g. for a corner of a map, may contain
+    /// HTML markup
+    #[clap(long = "attribution", env = "API_ATTRIBUTION")]
+    pub attribution: Option<String>,
+    /// Name of the contact of the operator
+    #[clap(long = "contact-name", env = "API_CONTACT_NAME")]
+    pub contact_name: Option<String>,
+    /// Email address of the contact of the operator
+    #[clap(long = "contact-email", env = "API_CONTACT_EMAIL")]
+    pub contact_email: Option<String>,
+    /// Website of the contact of the operator
+    #[clap(long = "contact-url", env = "API_CONTACT_URL")]
+    pub contact_url: Option<url::Url>,
+    /// Terms of service of the API
+    #[clap(long = "terms-of-service", env = "API_TERMS_OF_SERVICE")]
+    pub terms_of_service: Option<url::Url>,
+    /// Additional links of the landing page as `{rel}={href}`, optionally
+    /// followed by a title, e.g. `license=https://creativecommons.org/licenses/by/4.0/ CC BY 4.0`
+    #[clap(
+        long = "landing-link",
+        env = "LANDING_LINKS",
+        value_delimiter = ';',
+        value_parser = parse_link
+    )]
+    pub links: Vec<Link>,
+}
+
+impl LandingConfig {
+    /// Landing page with the configured branding, completing the OpenAPI
+    /// `info` with it
+    pub fn landing_page(&self, info: &mut Info) -> LandingPage {
+        if let Some(title) = &self.title {
+            info.title = title.to_owned();
+        }
+        if let Some(description) = &self.description {
+            info.description = Some(description.to_owned());
+        }
+        if let Some(terms) = &self.terms_of_service {
+            info.terms_of_service = Some(terms.to_string());
+        }
+
+        let mut root = LandingPage::new(&info.title);
+        root.description = info.description.to_owned();
+        root.attribution = self.attribution.to_owned();
+
+        // contact and terms of the OpenAPI definition describe the software
+        // and are not inherited
+        let contact = self.contact_name.is_some()
+            || self.contact_email.is_some()
+            || self.contact_url.is_some();
+        if contact {
+            let contact = Contact {
+                name: self.contact_name.to_owned(),
+                url: self.contact_url.as_ref().map(ToString::to_string),
+                email: self.contact_email.to_owned(),
+                extensions: Default::default(),
+            };
+            root.additional_properties.insert(
+                "contact".to_string(),
+                serde_json::to_value(&contact).unwrap_or(Value::Null),
+            );
+            info.contact = Some(contact);
+        }
+        if let Some(terms) = &self.terms_of_service {
+            root.links
+                .push(Link::new(terms, TERMS_OF_SERVICE).title("Terms of service"));
+        }
+        root.links.extend(self.links.iter().cloned());
+
+        root
+    }
+}
+
+/// Link of the landing page from `{rel}={href} {title}`
+fn parse_link(value: &str) -> Result<Link, String> {
+    let (rel, rest) = value
+        .trim()
+        .split_once('=')
+        .ok_or_else(|| format!("Link `{value}` is not of the form `rel=href`"))?;
+    let (href, title) = match rest.split_once(char::is_whitespace) {
+        Some((href, title)) => (href, Some(title.trim())),
+        None => (rest, None),
+    };
+
+    let href = url::Url::parse(href).map_err(|e| format!("Invalid href of link `{value}`: {e}"))?;
+
+    let link = Link::new(href, rel.trim());
+    Ok(match title.filter(|t| !t.is_empty()) {
+        Some(title) => link.title(title),
+        None => link,
+    })
+}
+
+/// Structured logging of requests, with target `ogcapi::requests`
+#[derive(Args, Debug, Clone)]
+pub struct RequestLogConfig {
+    /// Log every request with sanitized parameters, sizes and timings
+    #[clap(long = "request-log", env = "REQUEST_LOG")]
+    pub enabled: bool,
+    /// Fraction of the logged requests whose payloads are logged as well
+    #[clap(
+        long = "request-log-sample-rate",
+        env = "REQUEST_LOG_SAMPLE_RATE",
+        default_value = "0"
+    )]
+    pub sample_rate: f64,
+    /// Maximum number of bytes logged per payload
+    #[clap(
+        long = "request-log-max-payload",
+        env = "REQUEST_LOG_MAX_PAYLOAD",
+        default_value = "4096"
+    )]
+    pub max_payload: usize,
+}
+
+/// Cross-origin resource sharing (CORS) policy
+#[derive(Args, Debug, Clone)]
+pub struct CorsConfig {
+    /// Origins allowed to access the API, `*` for any
+    #[clap(
+        long = "cors-allowed-origins",
+        env = "CORS_ALLOWED_ORIGINS",
+        value_delimiter = ',',
+        default_value = "*"
+    )]
+    pub allowed_origins: Vec<String>,
+    /// Origins allowed to call write endpoints (`POST`, `PUT`, `PATCH`,
+    /// `DELETE`), defaults to the allowed origins
+    #[clap(
+        long = "cors-write-origins",
+        env = "CORS_WRITE_ORIGINS",
+        value_delimiter = ','
+    )]
+    pub write_origins: Option<Vec<String>>,
+    /// Allowed methods
+    #[clap(
+        long = "cors-allowed-methods",
+        env = "CORS_ALLOWED_METHODS",
+        value_delimiter = ',',
+        default_value = "GET,HEAD,POST,PUT,PATCH,DELETE"
+    )]
+    pub allowed_methods: Vec<String>,
+    /// Allowed request headers, `*` for any
+    #[clap(
+        long = "cors-allowed-headers",
+        env = "CORS_ALLOWED_HEADERS",
+        value_delimiter = ',',
+        default_value = "*"
+    )]
+    pub allowed_headers: Vec<String>,
+    /// Response headers exposed to scripts, `*` for any
+    #[clap(
+        long = "cors-exposed-headers",
+        env = "CORS_EXPOSED_HEADERS",
+        value_delimiter = ',',
+        default_value = "*"
+    )]
+    pub exposed_headers: Vec<String>,
+    /// Allow requests with credentials (cookies, authorization headers)
+    #[clap(long = "cors-allow-credentials", env = "CORS_ALLOW_CREDENTIALS")]
+    pub allow_credentials: bool,
+    /// Time in seconds preflight responses may be cached
+    #[clap(long = "cors-max-age", env = "CORS_MAX_AGE")]
+    pub max_age: Option<u64>,
+}
+
+impl CorsConfig {
+    /// Build the layer enforcing the policy
+    ///
+    /// Wildcards are mirrored from the request if credentials are allowed, as
+    /// browsers reject `*` on credentialed requests.
+    pub fn layer(&self) -> anyhow::Result<CorsLayer> {
+        let origins = self.allowed_origins.clone();
+        let write_origins = self
+            .write_origins
+            .clone()
+            .unwrap_or_else(|| origins.clone());
+
+        let allow_origin = AllowOrigin::predicate(move |origin, parts| {
+            // preflight requests announce the method of the actual request
+            let method = parts
+                .headers
+                .get(ACCESS_CONTROL_REQUEST_METHOD)
+                .and_then(|m| Method::from_bytes(m.as_bytes()).ok())
+                .unwrap_or_else(|| parts.method.clone());
+
+            if method.is_safe() {
+                is_allowed(&origins, origin)
+            } else {
+                is_allowed(&write_origins, origin)
+            }
+        });
+
+        let methods = self
+            .allowed_methods
+            .iter()
+            .map(|m| m.trim().parse::<Method>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let allow_headers = if self.allowed_headers.iter().any(|h| h == "*") {
+            if self.allow_credentials {
+                AllowHeaders::mirror_request()
+            } else {
+                AllowHeaders::any()
+            }
+        } else {
+            AllowHeaders::list(header_names(&self.allowed_headers)?)
+        };
+
+        let expose_headers = if self.exposed_headers.iter().any(|h| h == "*") {
+            if self.allow_credentials {
+                // headers of interest to clients, e.g. after creating resources
+                ExposeHeaders::list([
+                    HeaderName::from_static("location"),
+                    HeaderName::from_static("link"),
+                    HeaderName::from_static("content-crs"),
+                    HeaderName::from_static("idempotent-replayed"),
+                    HeaderName::from_static("x-request-id"),
+                ])
+            } else {
+                ExposeHeaders::any()
+            }
+        } else {
+            ExposeHeaders::list(header_names(&self.exposed_headers)?)
+        };
+
+        let mut layer = CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(methods)
+            .allow_headers(allow_headers)
+            .expose_headers(expose_headers)
+            .allow_credentials(self.allow_credentials);
+
+        if let Some(max_age) = self.max_age {
+            layer = layer.max_age(Duration::from_secs(max_age));
+        }
+
+        Ok(layer)
+    }
+}
+
+fn is_allowed(origins: &[String], origin: &HeaderValue) -> bool {
+    origins
+        .iter()
+        .any(|allowed| allowed == "*" || allowed.as_bytes() == origin.as_bytes())
+}
+
+fn header_names(names: &[String]) -> anyhow::Result<Vec<HeaderName>> {
+    Ok(names
+        .iter()
+        .map(|name| name.trim().parse())
+        .collect::<Result<_, _>>()?)
 }
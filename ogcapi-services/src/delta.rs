@@ -0,0 +1,164 @@
+//! Delta detection for harvests and imports of complete sources
+//!
+//! The content of every feature is hashed and compared with the checksum
+//! recorded when it was written last, only new and changed features are
+//! upserted. Features recorded before but missing from the source are deleted
+//! once the source was read completely, features created otherwise are kept.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use ogcapi_drivers::{Delta, FeatureTransactions};
+use ogcapi_types::{
+    common::Crs,
+    features::{DeltaSummary, Feature},
+};
+
+/// Number of features written at once
+const BATCH_SIZE: usize = 1000;
+
+pub(crate) struct Differ<'a> {
+    driver: &'a dyn FeatureTransactions,
+    collection: String,
+    crs: Crs,
+    /// Checksums of the stored features
+    stored: HashMap<String, String>,
+    /// Ids of the features of the source read so far
+    seen: HashSet<String>,
+    /// Pending upserts, with the positions of the features by id
+    delta: Delta,
+    positions: HashMap<String, usize>,
+    summary: DeltaSummary,
+}
+
+impl<'a> Differ<'a> {
+    /// Compare the features of a source in `crs` with a collection
+    pub(crate) async fn new(
+        driver: &'a dyn FeatureTransactions,
+        collection: &str,
+        crs: &Crs,
+    ) -> anyhow::Result<Self> {
+        Ok(Differ {
+            stored: driver.checksums(collection).await?,
+            driver,
+            collection: collection.to_owned(),
+            crs: crs.to_owned(),
+            seen: HashSet::new(),
+            delta: Delta::default(),
+            positions: HashMap::new(),
+            summary: DeltaSummary::default(),
+        })
+    }
+
+    /// Compare a feature of the source, which needs an id
+    pub(crate) async fn push(&mut self, mut feature: Feature) -> anyhow::Result<()> {
+        let Some(id) = feature.id.to_owned() else {
+            anyhow::bail!("Feature without id");
+        };
+
+        feature.collection = Some(self.collection.to_owned());
+        feature.links = Default::default();
+        let checksum = checksum(&feature)?;
+
+        // a feature listed twice is written once, in its latest state
+        if let Some(i) = self.positions.get(&id) {
+            self.delta.upserts[*i] = (feature, checksum);
+            return Ok(());
+        }
+        let duplicate = !self.seen.insert(id.to_owned());
+
+        match self.stored.get(&id) {
+            Some(stored) if *stored == checksum => {
+                if !duplicate {
+                    self.summary.unchanged += 1;
+                }
+                return Ok(());
+            }
+            Some(_) if duplicate => {}
+            Some(_) => self.summary.updated += 1,
+            None => self.summary.inserted += 1,
+        }
+        self.positions.insert(id, self.delta.upserts.len());
+        self.delta.upserts.push((feature, checksum));
+
+        if self.delta.upserts.len() >= BATCH_SIZE {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the pending features, delete the features missing from the
+    /// source and summarize the changes
+    pub(crate) async fn finish(mut self) -> anyhow::Result<DeltaSummary> {
+        self.flush().await?;
+
+        let mut deletes: Vec<String> = self
+            .stored
+            .into_keys()
+            .filter(|id| !self.seen.contains(id))
+            .collect();
+        deletes.sort();
+        self.summary.deleted = deletes.len();
+
+        for ids in deletes.chunks(BATCH_SIZE) {
+            let delta = Delta {
+                upserts: Vec::new(),
+                deletes: ids.to_vec(),
+            };
+            self.driver
+                .apply_delta(&self.collection, &delta, &self.crs)
+                .await?;
+        }
+
+        Ok(self.summary)
+    }
+
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        if self.delta.upserts.is_empty() {
+            return Ok(());
+        }
+
+        self.driver
+            .apply_delta(&self.collection, &self.delta, &self.crs)
+            .await?;
+
+        for (feature, checksum) in self.delta.upserts.drain(..) {
+            self.stored.extend(feature.id.map(|id| (id, checksum)));
+        }
+        self.positions.clear();
+
+        Ok(())
+    }
+}
+
+/// Hex encoded SHA-256 of the geometry and properties of a feature, with the
+/// keys of the properties in order
+pub(crate) fn checksum(feature: &Feature) -> anyhow::Result<String> {
+    let properties = feature
+        .properties
+        .as_ref()
+        .map(|properties| sorted(&Value::Object(properties.to_owned())));
+
+    let content = serde_json::to_vec(&(&feature.geometry, properties))?;
+
+    Ok(format!("{:x}", Sha256::digest(content)))
+}
+
+fn sorted(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+            Value::Object(
+                keys.into_iter()
+                    .map(|key| (key.to_owned(), sorted(&object[key])))
+                    .collect::<Map<String, Value>>(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.iter().map(sorted).collect()),
+        value => value.to_owned(),
+    }
+}
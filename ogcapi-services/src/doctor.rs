@@ -0,0 +1,139 @@
+//! Self-check of the environment of the service
+//!
+//! The database, the object store, the directories and the MQTT broker of a
+//! configuration are checked at startup and by `ogcapi doctor`, so that
+//! problems are reported with a hint how to fix them instead of failing the
+//! first request depending on them. The checks don't change anything, e.g.
+//! migrations are reported as pending but not run.
+
+#[cfg(any(feature = "pubsub", feature = "processes", feature = "uploads"))]
+use std::time::Duration;
+
+use ogcapi_drivers::{postgres::Db, Diagnostic, Severity};
+
+use crate::Config;
+
+/// Time to wait for external services and volumes
+#[cfg(any(feature = "pubsub", feature = "processes", feature = "uploads"))]
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Check the environment of a configuration
+pub async fn doctor(config: &Config) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if let Some(url) = &config.database_url {
+        diagnostics.extend(Db::diagnose(url).await);
+    }
+
+    #[cfg(any(feature = "attachments", feature = "stac", feature = "snapshot"))]
+    diagnostics.push(s3().await);
+
+    #[cfg(any(feature = "processes", feature = "uploads"))]
+    diagnostics.push(
+        writable(
+            "work-dir",
+            &config.work_dir.clone().unwrap_or_else(std::env::temp_dir),
+        )
+        .await,
+    );
+
+    #[cfg(feature = "files")]
+    if let Some(dir) = &config.data_dir {
+        diagnostics.push(directory("data-dir", dir).await);
+    }
+
+    #[cfg(feature = "coverages")]
+    if let Some(dir) = &config.raster_dir {
+        diagnostics.push(directory("raster-dir", dir).await);
+    }
+
+    for (check, path) in [
+        ("openapi", &config.openapi),
+        ("conformance-report", &config.conformance_report),
+    ] {
+        if let Some(path) = path {
+            diagnostics.push(match tokio::fs::metadata(path).await {
+                Ok(_) => Diagnostic::ok(check, format!("Found `{}`", path.display())),
+                Err(e) => Diagnostic::error(check, format!("Can't read `{}`: {e}", path.display())),
+            });
+        }
+    }
+
+    #[cfg(feature = "pubsub")]
+    if let Some(url) = &config.mqtt_url {
+        diagnostics.push(mqtt(url).await);
+    }
+
+    diagnostics
+}
+
+/// Log the diagnostics, problems with their hint
+pub(crate) fn log(diagnostics: &[Diagnostic]) {
+    for d in diagnostics {
+        let hint = d
+            .hint
+            .as_ref()
+            .map(|hint| format!(" ({hint})"))
+            .unwrap_or_default();
+
+        match d.severity {
+            Severity::Ok => tracing::info!("Check `{}`: {}", d.check, d.message),
+            Severity::Warning => tracing::warn!("Check `{}`: {}{hint}", d.check, d.message),
+            Severity::Error => tracing::error!("Check `{}`: {}{hint}", d.check, d.message),
+        }
+    }
+}
+
+/// Check the credentials for the default bucket
+#[cfg(any(feature = "attachments", feature = "stac", feature = "snapshot"))]
+async fn s3() -> Diagnostic {
+    match std::env::var("AWS_S3_BUCKET_NAME") {
+        Ok(bucket) => ogcapi_drivers::s3::S3::new().await.diagnose(&bucket).await,
+        Err(_) => Diagnostic::warning("s3", "No default bucket")
+            .hint("Set `AWS_S3_BUCKET_NAME` to store attachments and snapshots"),
+    }
+}
+
+#[cfg(any(feature = "processes", feature = "uploads"))]
+async fn writable(check: &'static str, dir: &std::path::Path) -> Diagnostic {
+    let probe = dir.join(format!(".doctor-{}", std::process::id()));
+    let result = async {
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(&probe, b"").await?;
+        tokio::fs::remove_file(&probe).await
+    };
+
+    match tokio::time::timeout(TIMEOUT, result).await {
+        Ok(Ok(())) => Diagnostic::ok(check, format!("`{}` is writable", dir.display())),
+        Ok(Err(e)) => Diagnostic::error(check, format!("Can't write to `{}`: {e}", dir.display()))
+            .hint("Grant the service write access, replicas share the directory on a volume"),
+        Err(_) => Diagnostic::error(check, format!("Writing to `{}` timed out", dir.display()))
+            .hint("Check the volume of the directory"),
+    }
+}
+
+#[cfg(any(feature = "files", feature = "coverages"))]
+async fn directory(check: &'static str, dir: &std::path::Path) -> Diagnostic {
+    match tokio::fs::read_dir(dir).await {
+        Ok(_) => Diagnostic::ok(check, format!("`{}` is readable", dir.display())),
+        Err(e) => Diagnostic::error(check, format!("Can't read `{}`: {e}", dir.display())),
+    }
+}
+
+/// Check that the broker accepts connections
+#[cfg(feature = "pubsub")]
+async fn mqtt(url: &url::Url) -> Diagnostic {
+    let host = url.host_str().unwrap_or("localhost");
+    let port = url.port().unwrap_or(1883);
+
+    match tokio::time::timeout(TIMEOUT, tokio::net::TcpStream::connect((host, port))).await {
+        Ok(Ok(_)) => Diagnostic::ok("mqtt", format!("Broker `{host}:{port}` is reachable")),
+        Ok(Err(e)) => Diagnostic::error(
+            "mqtt",
+            format!("Broker `{host}:{port}` is not reachable: {e}"),
+        )
+        .hint("Check `MQTT_URL` and that the broker is running"),
+        Err(_) => Diagnostic::error("mqtt", format!("Connecting to `{host}:{port}` timed out"))
+            .hint("Check `MQTT_URL` and the firewall between the service and the broker"),
+    }
+}
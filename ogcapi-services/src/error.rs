@@ -35,6 +35,10 @@ pub enum Error {
     /// Custom Exception
     #[error("an ogcapi exception occurred")]
     Exception(StatusCode, String),
+
+    /// Return `400 Bad Request` listing the problems of a request body
+    #[error("the request body is invalid")]
+    Invalid(Vec<String>),
 }
 
 impl Error {
@@ -42,6 +46,7 @@ impl Error {
         match self {
             Self::NotFound => StatusCode::NOT_FOUND,
             Self::Exception(status, _) => *status,
+            Self::Invalid(_) => StatusCode::BAD_REQUEST,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -54,6 +59,8 @@ impl Error {
 /// to the client.
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
+        let mut errors = Vec::new();
+
         let (status, message) = match self {
             // Self::Sqlx(ref e) => {
             //     tracing::error!("SQLx error: {:?}", e);
@@ -76,9 +83,19 @@ impl IntoResponse for Error {
                 tracing::debug!("OGCAPI exception: {}", message);
                 (status, message)
             }
+            Self::Invalid(ref problems) => {
+                tracing::debug!("Invalid request body: {:?}", problems);
+                errors.clone_from(problems);
+                (self.status_code(), self.to_string())
+            }
         };
 
-        let exception = Exception::new(status.as_u16()).detail(message);
+        let mut exception = Exception::new(status.as_u16()).detail(message);
+        if !errors.is_empty() {
+            exception
+                .additional_properties
+                .insert("errors".to_string(), errors.into());
+        }
 
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, PROBLEM_JSON.parse().unwrap());
@@ -0,0 +1,61 @@
+//! Job status events, consumed by the publisher and webhooks
+
+use tokio::sync::broadcast;
+
+use ogcapi_drivers::JobHandler;
+use ogcapi_types::processes::{JobQuery, Results, StatusInfo};
+
+/// Number of job events buffered for lagging receivers
+pub(crate) const CAPACITY: usize = 256;
+
+/// Job handler broadcasting the status of registered and updated jobs
+pub(crate) struct BroadcastJobs {
+    pub(crate) jobs: Box<dyn JobHandler>,
+    pub(crate) sender: broadcast::Sender<StatusInfo>,
+}
+
+impl BroadcastJobs {
+    fn send(&self, job: &StatusInfo) {
+        // fails only without receivers
+        let _ = self.sender.send(job.to_owned());
+    }
+}
+
+#[axum::async_trait]
+impl JobHandler for BroadcastJobs {
+    async fn register(&self, job: &StatusInfo) -> anyhow::Result<String> {
+        let id = self.jobs.register(job).await?;
+        self.send(job);
+        Ok(id)
+    }
+
+    async fn status(&self, id: &str) -> anyhow::Result<Option<StatusInfo>> {
+        self.jobs.status(id).await
+    }
+
+    async fn update(&self, job: &StatusInfo) -> anyhow::Result<()> {
+        self.jobs.update(job).await?;
+        self.send(job);
+        Ok(())
+    }
+
+    async fn set_results(&self, id: &str, results: &Results) -> anyhow::Result<()> {
+        self.jobs.set_results(id, results).await
+    }
+
+    async fn dismiss(&self, id: &str) -> anyhow::Result<Option<StatusInfo>> {
+        let job = self.jobs.dismiss(id).await?;
+        if let Some(job) = &job {
+            self.send(job);
+        }
+        Ok(job)
+    }
+
+    async fn list_jobs(&self, query: &JobQuery) -> anyhow::Result<Vec<StatusInfo>> {
+        self.jobs.list_jobs(query).await
+    }
+
+    async fn results(&self, id: &str) -> anyhow::Result<Option<Results>> {
+        self.jobs.results(id).await
+    }
+}
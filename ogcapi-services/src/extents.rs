@@ -0,0 +1,126 @@
+//! Cache of the data extents of collections
+//!
+//! Queries with a bbox (or tiles) outside the extent of a collection can't
+//! match any feature, so they are answered without asking the driver. The
+//! cached extents are dropped when features of a collection change, either
+//! by a request of this service or as announced by the change feed.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use futures::StreamExt;
+
+use ogcapi_drivers::transform::transformer;
+use ogcapi_types::common::{Bbox, Collection, Crs};
+
+use crate::{services::CollectionService, AppState};
+
+/// Extents of collections in storage crs, `None` if unknown or empty
+#[derive(Clone, Default)]
+pub(crate) struct Extents(Arc<Inner>);
+
+#[derive(Default)]
+struct Inner {
+    extents: RwLock<HashMap<String, Option<Bbox>>>,
+    /// Incremented on every invalidation, so extents read concurrently to a
+    /// write are not cached
+    generation: AtomicU64,
+    /// Whether the change feed is followed, cached extents are only trusted
+    /// while it is
+    following: AtomicBool,
+}
+
+impl Extents {
+    /// Extent of a collection, read from the service if not cached
+    async fn get(&self, collections: &dyn CollectionService, id: &str) -> Option<Bbox> {
+        if let Some(extent) = self.0.extents.read().unwrap().get(id) {
+            return extent.to_owned();
+        }
+
+        let generation = self.0.generation.load(Ordering::Acquire);
+
+        let extent = collections.extent(id).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to read extent of collection `{id}`: {e}");
+            None
+        });
+
+        let mut extents = self.0.extents.write().unwrap();
+        if self.0.generation.load(Ordering::Acquire) == generation {
+            extents.insert(id.to_owned(), extent.to_owned());
+        }
+
+        extent
+    }
+
+    /// Checks whether a bbox lies outside the extent of a collection
+    ///
+    /// Only returns `true` if certain, i.e. the extent is known and the bbox
+    /// can be transformed to the storage crs.
+    pub(crate) async fn disjoint(
+        &self,
+        collections: &dyn CollectionService,
+        collection: &Collection,
+        bbox: &Bbox,
+        crs: &Crs,
+    ) -> bool {
+        if !self.0.following.load(Ordering::Acquire) || bbox.crosses_antimeridian() {
+            return false;
+        }
+
+        let Some(extent) = self.get(collections, &collection.id).await else {
+            return false;
+        };
+
+        let storage_crs = collection.storage_crs.to_owned().unwrap_or_default();
+
+        let transform = transformer();
+        if crs != &storage_crs && !(transform.supports(crs) && transform.supports(&storage_crs)) {
+            return false;
+        }
+
+        match transform.transform_bbox(crs, &storage_crs, bbox) {
+            Ok(bbox) => !bbox.intersects(&extent),
+            Err(_) => false,
+        }
+    }
+
+    /// Drop the cached extent of a collection after its features changed
+    pub(crate) fn invalidate(&self, id: &str) {
+        let mut extents = self.0.extents.write().unwrap();
+        self.0.generation.fetch_add(1, Ordering::AcqRel);
+        extents.remove(id);
+    }
+
+    /// Follow the change feed of the drivers, to catch writes by other
+    /// processes, e.g. imports with the CLI
+    pub(crate) fn watch(state: &AppState) {
+        let extents = state.extents.clone();
+        if extents.0.following.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let drivers = state.drivers.clone();
+        tokio::spawn(async move {
+            match drivers.changes.subscribe_all().await {
+                Ok(mut changes) => {
+                    while let Some(change) = changes.next().await {
+                        match change {
+                            Ok(change) => extents.invalidate(&change.collection),
+                            Err(e) => tracing::error!("Failed to receive feature change: {e}"),
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("Failed to subscribe to feature changes: {e}"),
+            }
+
+            // changes may be missed from now on
+            extents.0.following.store(false, Ordering::Release);
+            extents.0.extents.write().unwrap().clear();
+        });
+    }
+}
@@ -1,11 +1,28 @@
+#[cfg(feature = "features")]
+use std::collections::HashMap;
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
 use anyhow::Context;
 use axum::{
-    extract::{FromRequestParts, Host, OriginalUri},
+    extract::{FromRequestParts, Host, OriginalUri, Request},
     http::{request::Parts, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
 };
+use tokio::sync::{Mutex, OwnedMutexGuard};
 use url::Url;
 
-use crate::Error;
+use ogcapi_drivers::postgres::Transaction;
+
+#[cfg(feature = "features")]
+use crate::share::{Share, SHARE_PARAMETER};
+use crate::{AppState, Error, Result, UnknownParameters};
+
+/// Header carrying api keys
+pub(crate) const API_KEY: &str = "x-api-key";
 
 /// Extractor for the remote URL
 pub(crate) struct RemoteUrl(pub Url);
@@ -43,21 +60,322 @@ where
 }
 
 /// Extractor that deserializes query strings into some type `T` with [`serde_qs`]
+///
+/// Unknown parameters are ignored, unless the service is configured to
+/// reject them everywhere.
+#[cfg(any(
+    feature = "coverages",
+    feature = "features",
+    feature = "stac",
+    feature = "tiles"
+))]
 pub(crate) struct Qs<T>(pub(crate) T);
 
+#[cfg(any(
+    feature = "coverages",
+    feature = "features",
+    feature = "stac",
+    feature = "tiles"
+))]
 #[axum::async_trait]
-impl<S, T> FromRequestParts<S> for Qs<T>
+impl<T> FromRequestParts<AppState> for Qs<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let reject = state.unknown_parameters == UnknownParameters::Reject;
+        parse_query(parts, reject).map(Qs)
+    }
+}
+
+/// Extractor like [`Qs`] for endpoints whose standard requires unknown
+/// parameters to be rejected, unless the service is configured to ignore them
+pub(crate) struct StrictQs<T>(pub(crate) T);
+
+#[axum::async_trait]
+impl<T> FromRequestParts<AppState> for StrictQs<T>
 where
-    S: Send + Sync,
     T: serde::de::DeserializeOwned,
 {
     type Rejection = Error;
 
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let reject = state.unknown_parameters != UnknownParameters::Ignore;
+        parse_query(parts, reject).map(StrictQs)
+    }
+}
+
+/// Parameters accepted by every endpoint, read by other extractors
+const COMMON_PARAMETERS: [&str; 2] = ["dry-run", "share"];
+
+fn parse_query<T: serde::de::DeserializeOwned>(parts: &Parts, reject_unknown: bool) -> Result<T> {
+    let qs = parts.uri.query().unwrap_or("");
+
+    // types taking any parameter, e.g. property filters, have no fixed names
+    if let Some(fields) = reject_unknown.then(field_names::<T>).flatten() {
+        let mut unknown: Vec<String> = Vec::new();
+        for (key, _) in url::form_urlencoded::parse(qs.as_bytes()) {
+            // `serde_qs` nests values with brackets, e.g. `z[0]`
+            let name = key.split('[').next().unwrap_or_default();
+            if !fields.contains(&name)
+                && !COMMON_PARAMETERS.contains(&name)
+                && !unknown.iter().any(|u| u == name)
+            {
+                unknown.push(name.to_owned());
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Unknown query parameters `{}`, expected any of `{}`",
+                    unknown.join("`, `"),
+                    fields.join("`, `")
+                ),
+            ));
+        }
+    }
+
+    serde_qs::from_str(qs).map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+/// Names of the fields of a struct, `None` for other types and structs with
+/// flattened fields
+fn field_names<T: serde::de::DeserializeOwned>() -> Option<&'static [&'static str]> {
+    use serde::de::{value::Error, Deserializer, Error as _, Visitor};
+
+    /// Deserializer recording the fields a struct asks for
+    struct Fields(Option<&'static [&'static str]>);
+
+    impl<'de> Deserializer<'de> for &mut Fields {
+        type Error = Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+            Err(Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Error> {
+            self.0 = Some(fields);
+            Err(Error::custom("recorded fields"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields = Fields(None);
+    let _ = T::deserialize(&mut fields);
+    fields.0
+}
+
+/// Extractor for the `dry-run` query parameter of write requests
+///
+/// In dry-run mode, the request is checked as usual but nothing is written,
+/// instead a report of what would happen is returned.
+pub(crate) struct DryRun(pub(crate) bool);
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for DryRun
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let qs = parts.uri.query().unwrap_or("");
+        match url::form_urlencoded::parse(qs.as_bytes()).find(|(key, _)| key == "dry-run") {
+            Some((_, value)) => match value.as_ref() {
+                "true" => Ok(DryRun(true)),
+                "false" => Ok(DryRun(false)),
+                _ => Err(Error::Exception(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid value `{value}` for `dry-run`, expected `true` or `false`"),
+                )),
+            },
+            None => Ok(DryRun(false)),
+        }
+    }
+}
+
+/// Extractor for the `version` query parameter of feature replacements
+///
+/// Features of versioned collections are only replaced if the stored one
+/// still has this version, for clients which can't send headers.
+#[cfg(feature = "features")]
+pub(crate) struct IfVersion(pub(crate) Option<u64>);
+
+#[cfg(feature = "features")]
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for IfVersion
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
     async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
         let qs = parts.uri.query().unwrap_or("");
-        match serde_qs::from_str(qs) {
-            Ok(query) => Ok(Self(query)),
-            Err(e) => Err(Error::Exception(StatusCode::BAD_REQUEST, e.to_string())),
+        match url::form_urlencoded::parse(qs.as_bytes()).find(|(key, _)| key == "version") {
+            Some((_, value)) => value.parse().map(|v| IfVersion(Some(v))).map_err(|_| {
+                Error::Exception(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid value `{value}` for `version`, expected a number"),
+                )
+            }),
+            None => Ok(IfVersion(None)),
+        }
+    }
+}
+
+/// Extractor for the share link of a read request, see [`crate::share`]
+///
+/// Tokens are verified with the share secret, invalid or expired tokens as
+/// well as tokens passed to a service without secret are rejected.
+#[cfg(feature = "features")]
+pub(crate) struct ShareLink {
+    pub(crate) share: Option<Share>,
+    /// Raw token, kept for pagination links
+    pub(crate) token: Option<String>,
+}
+
+#[cfg(feature = "features")]
+#[axum::async_trait]
+impl FromRequestParts<AppState> for ShareLink {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let qs = parts.uri.query().unwrap_or("");
+        let Some((_, token)) =
+            url::form_urlencoded::parse(qs.as_bytes()).find(|(key, _)| key == SHARE_PARAMETER)
+        else {
+            return Ok(ShareLink {
+                share: None,
+                token: None,
+            });
+        };
+
+        let secret = state.share_secret.as_deref().ok_or_else(|| {
+            Error::Exception(
+                StatusCode::UNAUTHORIZED,
+                "Share links are not enabled".to_string(),
+            )
+        })?;
+
+        let share = Share::verify(&token, secret)
+            .map_err(|e| Error::Exception(StatusCode::UNAUTHORIZED, e.to_string()))?;
+
+        Ok(ShareLink {
+            share: Some(share),
+            token: Some(token.into_owned()),
+        })
+    }
+}
+
+#[cfg(feature = "features")]
+impl ShareLink {
+    /// Remove the token from query parameters, where it would be taken as
+    /// property filter
+    pub(crate) fn strip(&self, parameters: &mut HashMap<String, String>) {
+        if self.token.is_some() {
+            parameters.remove(SHARE_PARAMETER);
+        }
+    }
+}
+
+/// Extractor for a database transaction spanning the request
+///
+/// The transaction is begun on first extraction and committed after the
+/// handler returned a successful response, otherwise it is rolled back.
+/// Only available for requests with unsafe methods (`POST`, `PUT`, ...).
+pub struct Tx(OwnedMutexGuard<Option<Transaction>>);
+
+/// Transaction of a request, shared between the extractor and the middleware
+#[derive(Clone, Default)]
+struct TxSlot(Arc<Mutex<Option<Transaction>>>);
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for Tx {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let slot = parts
+            .extensions
+            .get::<TxSlot>()
+            .context("No transaction for safe request methods")?;
+
+        let mut tx = slot
+            .0
+            .clone()
+            .try_lock_owned()
+            .context("Transaction is already in use")?;
+
+        if tx.is_none() {
+            *tx = Some(state.db.pool.begin().await.context("Begin transaction")?);
+        }
+
+        Ok(Tx(tx))
+    }
+}
+
+impl Deref for Tx {
+    type Target = Transaction;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref().expect("begun transaction")
+    }
+}
+
+impl DerefMut for Tx {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.as_mut().expect("begun transaction")
+    }
+}
+
+/// Middleware completing the transaction of write requests depending on the
+/// response status
+pub(crate) async fn transaction(mut request: Request, next: Next) -> Response {
+    if request.method().is_safe() {
+        return next.run(request).await;
+    }
+
+    let slot = TxSlot::default();
+    request.extensions_mut().insert(slot.clone());
+
+    let response = next.run(request).await;
+
+    let Some(tx) = slot.0.lock().await.take() else {
+        return response;
+    };
+
+    if response.status().is_success() || response.status().is_redirection() {
+        if let Err(e) = tx.commit().await {
+            return Error::Anyhow(anyhow::Error::new(e).context("Commit transaction"))
+                .into_response();
         }
+    } else if let Err(e) = tx.rollback().await {
+        tracing::error!("Failed to roll back transaction: {e}");
     }
+
+    response
 }
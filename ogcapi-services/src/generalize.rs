@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use crate::{leader::Leader, AppState};
+
+/// Time between the refreshes of the generalized geometries
+const INTERVAL: Duration = Duration::from_secs(600);
+
+/// Refresh the generalized geometries of the tiles periodically, picking up
+/// new collections and changed features
+pub(crate) fn spawn(state: &AppState) {
+    let drivers = state.drivers.clone();
+    let leader = state.leader.clone();
+    Leader::spawn(state);
+
+    state.tasks.every(
+        "generalize",
+        "Refresh the generalized geometries of the tiles",
+        INTERVAL,
+        move || {
+            let drivers = drivers.clone();
+            let leading = leader.is_leader();
+            async move {
+                // the geometries are shared, one replica refreshes them
+                if !leading {
+                    return Ok(());
+                }
+                drivers.tiles.generalize().await
+            }
+        },
+    );
+}
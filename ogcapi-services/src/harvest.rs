@@ -0,0 +1,372 @@
+//! Scheduled harvests of remote sources into collections
+//!
+//! The harvests are checked every minute, a due run is claimed first so it
+//! runs once even with several instances. Missed runs, e.g. while no instance
+//! was up, are caught up with a single run. Runs are registered as jobs with
+//! the process id `harvest:{id}`.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::http::header::ACCEPT;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use url::Url;
+
+use ogcapi_types::{
+    common::{
+        link_rel::{CHILD, ITEM, ITEMS, NEXT},
+        media_type::{GEO_JSON, JSON},
+        Crs, Link,
+    },
+    features::{DeltaSummary, Feature},
+    harvest::{Harvest, HarvestSource},
+    processes::{InlineOrRefData, InputValueNoObject, Results, StatusCode, StatusInfo},
+};
+
+use crate::{delta::Differ, state::Drivers, AppState};
+
+/// Time between the checks for due runs
+const INTERVAL: Duration = Duration::from_secs(60);
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Number of features requested per page from a WFS
+const BATCH_SIZE: usize = 1000;
+
+/// Requests per run, guarding against pagination and catalog link cycles
+const MAX_REQUESTS: usize = 100_000;
+
+/// Parse the cron expression of a harvest, five fields are completed with
+/// `0` seconds
+pub(crate) fn schedule(expression: &str) -> anyhow::Result<cron::Schedule> {
+    let expression = expression.trim();
+    let expression = if expression.split_whitespace().count() == 5 {
+        format!("0 {expression}")
+    } else {
+        expression.to_owned()
+    };
+
+    cron::Schedule::from_str(&expression)
+        .map_err(|e| anyhow::anyhow!("Invalid schedule `{expression}`: {e}"))
+}
+
+/// Latest scheduled run of a harvest that is due at `now`
+fn due_run(harvest: &Harvest, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let schedule = schedule(&harvest.schedule).ok()?;
+    let after = harvest.last_run.or(harvest.created)?;
+
+    schedule
+        .after(&after)
+        .take_while(|time| *time <= now)
+        .last()
+}
+
+/// Start running the harvests on schedule
+pub(crate) fn spawn(state: &AppState) {
+    let harvester = Harvester::new(state.drivers.clone());
+
+    state.tasks.every(
+        "harvests",
+        "Start the runs of the harvests that are due",
+        INTERVAL,
+        move || {
+            let harvester = harvester.clone();
+            async move { harvester.run_due(Utc::now()).await }
+        },
+    );
+}
+
+#[derive(Clone)]
+pub(crate) struct Harvester {
+    client: reqwest::Client,
+    drivers: Arc<Drivers>,
+}
+
+impl Harvester {
+    pub(crate) fn new(drivers: Arc<Drivers>) -> Self {
+        Harvester {
+            client: reqwest::Client::builder()
+                .timeout(TIMEOUT)
+                .build()
+                .expect("build http client"),
+            drivers,
+        }
+    }
+
+    async fn run_due(&self, now: DateTime<Utc>) -> anyhow::Result<()> {
+        for harvest in self.drivers.harvests.list_harvests().await? {
+            let Some(time) = harvest.enabled.then(|| due_run(&harvest, now)).flatten() else {
+                continue;
+            };
+
+            if self.drivers.harvests.claim_run(&harvest.id, time).await? {
+                if let Err(e) = self.start(harvest.to_owned()).await {
+                    tracing::error!("Failed to start harvest `{}`: {e}", harvest.id);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Register a job for a run of the harvest and run it in the background
+    pub(crate) async fn start(&self, harvest: Harvest) -> anyhow::Result<StatusInfo> {
+        let mut job = StatusInfo {
+            process_id: Some(harvest.process_id()),
+            job_id: uuid::Uuid::new_v4().to_string(),
+            status: StatusCode::Accepted,
+            created: Some(Utc::now()),
+            ..Default::default()
+        };
+        self.drivers.jobs.register(&job).await?;
+
+        let info = job.clone();
+        let harvester = self.clone();
+        tokio::spawn(async move {
+            job.status = StatusCode::Running;
+            if let Err(e) = harvester.drivers.jobs.update(&job).await {
+                tracing::error!("Failed to update job `{}`: {e}", job.job_id);
+            }
+
+            match harvester.run(&harvest).await {
+                Ok((summary, skipped)) => {
+                    let results = HashMap::from([
+                        ("inserted".to_string(), count(summary.inserted)),
+                        ("updated".to_string(), count(summary.updated)),
+                        ("deleted".to_string(), count(summary.deleted)),
+                        ("unchanged".to_string(), count(summary.unchanged)),
+                        ("skipped".to_string(), count(skipped)),
+                    ]);
+                    if let Err(e) = harvester
+                        .drivers
+                        .jobs
+                        .set_results(&job.job_id, &Results { results })
+                        .await
+                    {
+                        tracing::error!("Failed to store results of job `{}`: {e}", job.job_id);
+                    }
+                    job.status = StatusCode::Successful;
+                    job.progress = Some(100);
+                    job.message = Some(format!(
+                        "Inserted {}, updated {}, deleted {} and kept {} unchanged features, skipped {} without id",
+                        summary.inserted, summary.updated, summary.deleted, summary.unchanged, skipped
+                    ));
+                }
+                Err(e) => {
+                    tracing::warn!("Harvest `{}` failed: {e:#}", harvest.id);
+                    job.status = StatusCode::Failed;
+                    job.message = Some(format!("{e:#}"));
+                }
+            }
+            job.finished = Some(Utc::now());
+
+            if let Err(e) = harvester.drivers.jobs.update(&job).await {
+                tracing::error!("Failed to update job `{}`: {e}", job.job_id);
+            }
+        });
+
+        Ok(info)
+    }
+
+    /// Fetch the features of the source and apply the changes, returns the
+    /// summary and the number of features skipped for lack of an id
+    async fn run(&self, harvest: &Harvest) -> anyhow::Result<(DeltaSummary, usize)> {
+        let mut batch = Batch {
+            differ: Differ::new(
+                self.drivers.features.as_ref(),
+                &harvest.collection,
+                &Crs::default(),
+            )
+            .await?,
+            id_property: harvest.id_property.to_owned(),
+            skipped: 0,
+        };
+        let mut requests = 0;
+
+        match &harvest.source {
+            HarvestSource::Features { url } => {
+                self.pages(Url::parse(url)?, &mut batch, &mut requests)
+                    .await?
+            }
+            HarvestSource::Wfs { url, type_name } => {
+                let mut start = 0;
+                loop {
+                    let mut url = Url::parse(url)?;
+                    url.query_pairs_mut()
+                        .append_pair("service", "WFS")
+                        .append_pair("version", "2.0.0")
+                        .append_pair("request", "GetFeature")
+                        .append_pair("typeNames", type_name)
+                        .append_pair("outputFormat", "application/json")
+                        .append_pair("srsName", "urn:ogc:def:crs:OGC:1.3:CRS84")
+                        .append_pair("count", &BATCH_SIZE.to_string())
+                        .append_pair("startIndex", &start.to_string());
+
+                    let page = self.fetch(&url, &mut requests).await?;
+                    let features = features(&page)?;
+                    let n = features.len();
+                    for feature in features {
+                        batch.push(feature).await?;
+                    }
+
+                    if n < BATCH_SIZE {
+                        break;
+                    }
+                    start += n;
+                }
+            }
+            HarvestSource::Stac { url } => {
+                self.walk(Url::parse(url)?, &mut batch, &mut requests)
+                    .await?
+            }
+            HarvestSource::File { url } => {
+                let document = self.fetch(&Url::parse(url)?, &mut requests).await?;
+                for feature in features(&document)? {
+                    batch.push(feature).await?;
+                }
+            }
+        }
+
+        Ok((batch.differ.finish().await?, batch.skipped))
+    }
+
+    /// Follow the `next` links of pages of features
+    async fn pages(
+        &self,
+        url: Url,
+        batch: &mut Batch<'_>,
+        requests: &mut usize,
+    ) -> anyhow::Result<()> {
+        let mut next = Some(url);
+        while let Some(url) = next {
+            let page = self.fetch(&url, requests).await?;
+            for feature in features(&page)? {
+                batch.push(feature).await?;
+            }
+            next = link(&page, &url, NEXT);
+        }
+
+        Ok(())
+    }
+
+    /// Walk a STAC catalog along its `child` and `item` links, items served by
+    /// an API are paged
+    async fn walk(
+        &self,
+        url: Url,
+        batch: &mut Batch<'_>,
+        requests: &mut usize,
+    ) -> anyhow::Result<()> {
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([url]);
+
+        while let Some(url) = queue.pop_front() {
+            if !visited.insert(url.to_string()) {
+                continue;
+            }
+
+            let document = self.fetch(&url, requests).await?;
+            match document.get("type").and_then(Value::as_str) {
+                Some("FeatureCollection") => {
+                    for feature in features(&document)? {
+                        batch.push(feature).await?;
+                    }
+                    queue.extend(link(&document, &url, NEXT));
+                }
+                Some("Feature") => batch.push(serde_json::from_value(document)?).await?,
+                _ => {
+                    if let Some(items) = link(&document, &url, ITEMS) {
+                        self.pages(items, batch, requests).await?;
+                    } else {
+                        queue.extend(links(&document, &url, ITEM));
+                    }
+                    queue.extend(links(&document, &url, CHILD));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn fetch(&self, url: &Url, requests: &mut usize) -> anyhow::Result<Value> {
+        *requests += 1;
+        if *requests > MAX_REQUESTS {
+            anyhow::bail!("Exceeded {MAX_REQUESTS} requests");
+        }
+
+        let response = self
+            .client
+            .get(url.to_owned())
+            .header(ACCEPT, format!("{GEO_JSON}, {JSON}"))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+}
+
+/// Features of the source compared with the collection
+struct Batch<'a> {
+    differ: Differ<'a>,
+    id_property: Option<String>,
+    skipped: usize,
+}
+
+impl Batch<'_> {
+    async fn push(&mut self, mut feature: Feature) -> anyhow::Result<()> {
+        if let Some(property) = &self.id_property {
+            feature.id = match feature.properties.as_ref().and_then(|p| p.get(property)) {
+                Some(Value::String(id)) => Some(id.to_owned()),
+                Some(Value::Number(id)) => Some(id.to_string()),
+                _ => None,
+            };
+        }
+        if feature.id.is_none() {
+            self.skipped += 1;
+            return Ok(());
+        }
+
+        self.differ.push(feature).await
+    }
+}
+
+/// Features of a feature collection or a single feature
+fn features(document: &Value) -> anyhow::Result<Vec<Feature>> {
+    match document.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => Ok(serde_json::from_value(
+            document
+                .get("features")
+                .cloned()
+                .unwrap_or(Value::Array(Vec::new())),
+        )?),
+        Some("Feature") => Ok(vec![serde_json::from_value(document.to_owned())?]),
+        _ => anyhow::bail!("Expected GeoJSON features"),
+    }
+}
+
+/// Urls of the links of a document with the relation, relative to `base`
+fn links(document: &Value, base: &Url, rel: &str) -> Vec<Url> {
+    let links: Vec<Link> = document
+        .get("links")
+        .and_then(|links| serde_json::from_value(links.to_owned()).ok())
+        .unwrap_or_default();
+
+    links
+        .iter()
+        .filter(|link| link.rel == rel)
+        .filter_map(|link| base.join(&link.href).ok())
+        .collect()
+}
+
+fn link(document: &Value, base: &Url, rel: &str) -> Option<Url> {
+    links(document, base, rel).into_iter().next()
+}
+
+fn count(n: usize) -> InlineOrRefData {
+    InlineOrRefData::InputValueNoObject(InputValueNoObject::Integer(n as i64))
+}
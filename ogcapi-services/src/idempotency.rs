@@ -0,0 +1,176 @@
+//! Idempotency keys of `POST` requests
+//!
+//! Clients with retrying HTTP stacks send an `Idempotency-Key` header with
+//! requests creating resources, e.g. features, bulk ingests or jobs. The
+//! first request with a key is processed and its response stored in the
+//! driver, retries with the same key get the stored response replayed instead
+//! of creating duplicates. Keys of failed requests are released.
+//!
+//! Keys are scoped to the user of the api key of a request, and bound to the
+//! target and body of the request they were first used for. Bodies of
+//! requests with key are limited to the size of regular requests.
+
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use ogcapi_drivers::{Idempotency, StoredResponse};
+
+use crate::{access::request_user, AppState, Error};
+
+/// Header carrying idempotency keys
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// Header marking replayed responses
+const IDEMPOTENT_REPLAYED: &str = "idempotent-replayed";
+
+/// Maximum length of keys
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Maximum size of stored responses in bytes, larger ones are not replayed
+const MAX_RESPONSE_SIZE: u64 = 1024 * 1024;
+
+/// Middleware claiming idempotency keys of `POST` requests and replaying the
+/// responses of completed ones
+pub(crate) async fn idempotency(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() != Method::POST {
+        return next.run(request).await;
+    }
+
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY) else {
+        return next.run(request).await;
+    };
+
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key.to_owned(),
+        _ => {
+            return Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid `Idempotency-Key`, expected up to {MAX_KEY_LENGTH} characters"),
+            )
+            .into_response()
+        }
+    };
+
+    // keys are bound to the request they were first used for
+    let target = format!("{} {}", request.method(), request.uri());
+    let scope = request_user(&state, request.headers())
+        .await
+        .unwrap_or_default();
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, state.limits.body).await {
+        Ok(body) => body,
+        Err(_) => {
+            return Error::Exception(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!(
+                    "Requests with `Idempotency-Key` are limited to {} bytes",
+                    state.limits.body
+                ),
+            )
+            .into_response()
+        }
+    };
+    let fingerprint = format!("{:x}", Sha256::digest(&body));
+    let request = Request::from_parts(parts, Body::from(body));
+
+    let keys = &state.drivers.idempotency;
+
+    match keys.claim_key(&scope, &key, &target, &fingerprint).await {
+        Ok(Idempotency::Claimed) => {}
+        Ok(
+            Idempotency::InProgress {
+                request,
+                fingerprint: used,
+            }
+            | Idempotency::Completed {
+                request,
+                fingerprint: used,
+                ..
+            },
+        ) if request != target || used != fingerprint => {
+            let message = if request != target {
+                format!("`Idempotency-Key` was already used for request `{request}`")
+            } else {
+                "`Idempotency-Key` was already used for a request with another body".to_string()
+            };
+            return Error::Exception(StatusCode::UNPROCESSABLE_ENTITY, message).into_response();
+        }
+        Ok(Idempotency::InProgress { .. }) => {
+            return Error::Exception(
+                StatusCode::CONFLICT,
+                "A request with this `Idempotency-Key` is still being processed".to_string(),
+            )
+            .into_response()
+        }
+        Ok(Idempotency::Completed { response, .. }) => return replay(response),
+        Err(e) => return Error::Anyhow(e.context("Claim idempotency key")).into_response(),
+    }
+
+    let response = next.run(request).await;
+
+    // responses of write requests are small, e.g. empty with a `Location`,
+    // others are passed through without being stored
+    let small = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|size| size <= MAX_RESPONSE_SIZE);
+    if !response.status().is_success() || !small {
+        if let Err(e) = keys.release_key(&scope, &key).await {
+            tracing::error!("Failed to release idempotency key `{key}`: {e}");
+        }
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, MAX_RESPONSE_SIZE as usize).await {
+        Ok(body) => body,
+        Err(e) => {
+            let _ = keys.release_key(&scope, &key).await;
+            return Error::Anyhow(anyhow::anyhow!("Buffer response body: {e}")).into_response();
+        }
+    };
+
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        headers: parts
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect(),
+        body: body.to_vec(),
+    };
+
+    if let Err(e) = keys.complete_key(&scope, &key, &stored).await {
+        tracing::error!("Failed to store response of idempotency key `{key}`: {e}");
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Response of an earlier request with the same key
+fn replay(stored: StoredResponse) -> Response {
+    let mut response = Response::new(Body::from(stored.body));
+    *response.status_mut() = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+
+    let headers = response.headers_mut();
+    for (name, value) in stored.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            headers.append(name, value);
+        }
+    }
+    headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+
+    response
+}
@@ -0,0 +1,80 @@
+//! Work done by a single replica of the service
+//!
+//! Replicas behind a load balancer share the database, but work triggered by
+//! the database itself, like forwarding the feature change feed to webhooks
+//! and MQTT, is to be done once. It is done by the replica holding the
+//! `primary` lease, which the `leadership` task renews. Another replica takes
+//! over once the lease expired.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::AppState;
+
+const ROLE: &str = "primary";
+
+/// Time the role is held without renewal
+const LEASE: Duration = Duration::from_secs(30);
+
+/// Time between the renewals of the lease
+const RENEWAL: Duration = Duration::from_secs(10);
+
+/// Whether this replica holds the primary role
+#[derive(Clone)]
+pub(crate) struct Leader(Arc<Inner>);
+
+struct Inner {
+    holder: String,
+    leading: AtomicBool,
+}
+
+impl Default for Leader {
+    fn default() -> Self {
+        Leader(Arc::new(Inner {
+            holder: uuid::Uuid::new_v4().to_string(),
+            leading: AtomicBool::new(false),
+        }))
+    }
+}
+
+impl Leader {
+    pub(crate) fn is_leader(&self) -> bool {
+        self.0.leading.load(Ordering::Acquire)
+    }
+
+    /// Compete for the primary role, started once per state
+    pub(crate) fn spawn(state: &AppState) {
+        let leader = state.leader.clone();
+        let drivers = state.drivers.clone();
+
+        state.tasks.every(
+            "leadership",
+            "Claim or renew the primary role among the replicas",
+            RENEWAL,
+            move || {
+                let leader = leader.clone();
+                let drivers = drivers.clone();
+                async move {
+                    let leading = drivers.leases.lead(ROLE, &leader.0.holder, LEASE).await;
+                    let was_leading = leader
+                        .0
+                        .leading
+                        .swap(*leading.as_ref().unwrap_or(&false), Ordering::AcqRel);
+
+                    match leading {
+                        Ok(true) if !was_leading => tracing::info!("Took the primary role"),
+                        Ok(false) if was_leading => tracing::warn!("Lost the primary role"),
+                        _ => {}
+                    }
+
+                    leading.map(|_| ())
+                }
+            },
+        );
+    }
+}
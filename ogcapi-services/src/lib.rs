@@ -1,22 +1,77 @@
+mod access;
+mod builder;
 mod config;
+#[cfg(any(feature = "harvest", feature = "import"))]
+mod delta;
+mod doctor;
 mod error;
+#[cfg(any(feature = "processes", feature = "joins"))]
+mod events;
+#[cfg(feature = "features")]
+mod extents;
 mod extractors;
+#[cfg(feature = "tiles")]
+mod generalize;
+#[cfg(feature = "harvest")]
+mod harvest;
+mod idempotency;
+#[cfg(any(feature = "pubsub", feature = "tiles", feature = "webhooks"))]
+mod leader;
+mod load;
+mod logging;
+mod maintenance;
+mod metering;
+mod normalize;
 mod openapi;
 #[cfg(feature = "processes")]
 mod processor;
+#[cfg(feature = "pubsub")]
+pub mod pubsub;
+#[cfg(feature = "processes")]
+mod queue;
 mod routes;
 mod service;
+pub mod services;
+#[cfg(feature = "features")]
+pub mod share;
 mod state;
+mod tasks;
 pub mod telemetry;
+#[cfg(feature = "uploads")]
+mod upload;
+#[cfg(feature = "tiles")]
+mod usage;
+#[cfg(feature = "webhooks")]
+mod webhooks;
 
+pub use builder::OgcApiBuilder;
 pub use config::Config;
+pub use doctor::doctor;
 pub use error::Error;
+pub use extractors::Tx;
+pub use metering::BillingHook;
 pub use openapi::OpenAPI;
 pub use service::Service;
-pub use state::AppState;
+pub use state::{
+    AppState, Guardrails, Limits, Mode, RouteLimit, RouteLimits, Services, UnknownParameters,
+};
 
+#[cfg(feature = "reproject")]
+pub use processor::CollectionReproject;
+#[cfg(feature = "print")]
+pub use processor::MapPrint;
+#[cfg(feature = "bundle")]
+pub use processor::OfflineBundle;
+#[cfg(feature = "geopackage")]
+pub use processor::{export_geopackage, import_geopackage, GeoPackageExport, GeoPackageImport};
+#[cfg(feature = "processes")]
+pub use processor::{queue_job, Greeter, Processor, TypedProcessor};
+#[cfg(feature = "snapshot")]
+pub use processor::{CollectionRestore, CollectionSnapshot};
+#[cfg(feature = "openeo")]
+pub use processor::{OpenEo, ProcessGraph, ProcessNode};
 #[cfg(feature = "processes")]
-pub use processor::{Greeter, Processor};
+pub use queue::JobWorkers;
 
 #[doc(hidden)]
 pub use clap::Parser as ConfigParser;
@@ -0,0 +1,198 @@
+//! Timeouts and concurrency limits per class of routes
+//!
+//! Requests beyond the concurrency limit of their class are shed right away
+//! and requests exceeding its timeout are aborted, both with `503` and a
+//! `Retry-After` header, so slow bulk requests can't starve interactive ones
+//! of database connections. Streamed response bodies hold on to their slot
+//! until they are sent completely, the timeout only covers the handler.
+
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{MatchedPath, Request, State},
+    http::{
+        header::{ACCEPT, RETRY_AFTER},
+        HeaderValue, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hyper::body::{Frame, SizeHint};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use ogcapi_types::common::media_type::GEO_JSON_SEQ;
+
+use crate::{
+    state::{RouteLimit, RouteLimits},
+    Error,
+};
+
+/// Seconds clients are asked to wait before retrying
+const RETRY_AFTER_SECS: u64 = 2;
+
+/// Classes of routes with their own limits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RouteClass {
+    Interactive,
+    Tiles,
+    /// Streamed exports and uploads
+    Bulk,
+    /// Process executions
+    Processes,
+}
+
+/// Slots of the route classes
+#[derive(Clone)]
+pub(crate) struct Shedder {
+    limits: RouteLimits,
+    interactive: Option<Arc<Semaphore>>,
+    tiles: Option<Arc<Semaphore>>,
+    bulk: Option<Arc<Semaphore>>,
+    processes: Option<Arc<Semaphore>>,
+}
+
+impl Shedder {
+    pub(crate) fn new(limits: RouteLimits) -> Self {
+        let slots = |limit: RouteLimit| limit.concurrency.map(|n| Arc::new(Semaphore::new(n)));
+
+        Shedder {
+            limits,
+            interactive: slots(limits.interactive),
+            tiles: slots(limits.tiles),
+            bulk: slots(limits.bulk),
+            processes: slots(limits.processes),
+        }
+    }
+
+    async fn run(&self, class: RouteClass, request: Request, next: Next) -> Response {
+        let (limit, slots) = match class {
+            RouteClass::Interactive => (self.limits.interactive, &self.interactive),
+            RouteClass::Tiles => (self.limits.tiles, &self.tiles),
+            RouteClass::Bulk => (self.limits.bulk, &self.bulk),
+            RouteClass::Processes => (self.limits.processes, &self.processes),
+        };
+
+        let permit = match slots {
+            Some(slots) => match slots.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    tracing::warn!("Shed {class:?} request to `{}`", request.uri().path());
+                    return unavailable("Too many concurrent requests, retry later");
+                }
+            },
+            None => None,
+        };
+
+        let response = match limit.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, next.run(request)).await {
+                Ok(response) => response,
+                Err(_) => {
+                    return unavailable(&format!("Request timed out after {}", seconds(timeout)))
+                }
+            },
+            None => next.run(request).await,
+        };
+
+        match permit {
+            Some(permit) => response.map(|body| {
+                Body::new(Permitted {
+                    body,
+                    _permit: permit,
+                })
+            }),
+            None => response,
+        }
+    }
+}
+
+/// Apply the limits to the routes of the router
+pub(crate) async fn shed(State(shedder): State<Shedder>, request: Request, next: Next) -> Response {
+    let class = classify(&request);
+    shedder.run(class, request, next).await
+}
+
+/// Apply the limits to the routes accepting uploads, which are bulk requests
+/// except for process executions and single features
+pub(crate) async fn shed_uploads(
+    State(shedder): State<Shedder>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let class = match request.extensions().get::<MatchedPath>() {
+        Some(path) if path.as_str().ends_with("/execution") => RouteClass::Processes,
+        Some(path) if path.as_str().ends_with("/items") => RouteClass::Interactive,
+        _ => RouteClass::Bulk,
+    };
+    shedder.run(class, request, next).await
+}
+
+fn classify(request: &Request) -> RouteClass {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str())
+        .unwrap_or_default();
+
+    if route.ends_with("/:row/:col") {
+        return RouteClass::Tiles;
+    }
+
+    // features streamed as sequence
+    let streamed = request
+        .headers()
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains(GEO_JSON_SEQ))
+        || url::form_urlencoded::parse(request.uri().query().unwrap_or_default().as_bytes())
+            .any(|(key, value)| key == "f" && value == "geojsonseq");
+    if route.ends_with("/items") && streamed {
+        return RouteClass::Bulk;
+    }
+
+    RouteClass::Interactive
+}
+
+fn unavailable(message: &str) -> Response {
+    let mut response =
+        Error::Exception(StatusCode::SERVICE_UNAVAILABLE, message.to_string()).into_response();
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECS));
+    response
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{}s", duration.as_secs_f64())
+}
+
+/// Response body holding the slot of its request until it is sent
+struct Permitted {
+    body: Body,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl HttpBody for Permitted {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.body).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
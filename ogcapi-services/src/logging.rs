@@ -0,0 +1,382 @@
+//! Structured request logging with payload sampling
+//!
+//! Requests are logged at `info` level with the target `ogcapi::requests`:
+//! method, route, sanitized query parameters, status, body sizes and the time
+//! spent routing, in database statements, in the handler and sending the
+//! response body. A sample of the requests is logged with the start of their
+//! payloads.
+//!
+//! The database time is summed up from the statement events of `sqlx`, seen
+//! by the [`statements`] layer of [`crate::telemetry::init`].
+
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{MatchedPath, Request, State},
+    http::header::CONTENT_LENGTH,
+    middleware::Next,
+    response::Response,
+};
+use hyper::body::{Frame, SizeHint};
+use tracing::{field::Visit, Subscriber};
+use tracing_subscriber::{
+    filter::filter_fn, layer::Context as LayerContext, registry::LookupSpan, Layer,
+};
+
+use crate::config::RequestLogConfig;
+
+/// Query parameters whose values are never logged
+const SECRET_PARAMETERS: [&str; 8] = [
+    "access_token",
+    "api-key",
+    "api_key",
+    "key",
+    "password",
+    "secret",
+    "share",
+    "token",
+];
+
+/// Whether statement times are recorded, off unless requests are logged
+static RECORD_STATEMENTS: AtomicBool = AtomicBool::new(false);
+
+tokio::task_local! {
+    /// Record of the request handled by the current task
+    static RECORD: Arc<Record>;
+}
+
+/// Settings of the request logging
+#[derive(Clone)]
+pub(crate) struct Logger {
+    config: RequestLogConfig,
+    requests: Arc<AtomicU64>,
+}
+
+impl Logger {
+    pub(crate) fn new(config: &RequestLogConfig) -> Self {
+        if config.enabled {
+            RECORD_STATEMENTS.store(true, Ordering::Relaxed);
+        }
+
+        Logger {
+            config: config.to_owned(),
+            requests: Default::default(),
+        }
+    }
+
+    /// Whether the payloads of the next request are logged, spreading the
+    /// samples evenly over the requests
+    fn sample(&self) -> bool {
+        let rate = self.config.sample_rate.clamp(0.0, 1.0);
+        let n = self.requests.fetch_add(1, Ordering::Relaxed) as f64;
+        (n * rate).floor() != ((n + 1.0) * rate).floor()
+    }
+}
+
+/// Measurements of a request
+struct Record {
+    method: String,
+    path: String,
+    query: String,
+    start: Instant,
+    /// Payloads are kept up to this size if the request is sampled
+    max_payload: Option<usize>,
+    routed: Mutex<Option<(Instant, String)>>,
+    db_micros: AtomicU64,
+    db_statements: AtomicUsize,
+    request_bytes: AtomicUsize,
+    request_payload: Mutex<Option<String>>,
+}
+
+impl Record {
+    fn routing(&self) -> Duration {
+        self.routed
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|(routed, _)| *routed - self.start)
+            .unwrap_or_default()
+    }
+
+    fn route(&self) -> String {
+        self.routed
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|(_, route)| route.to_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Log a request, outermost layer of the service
+pub(crate) async fn log(State(logger): State<Logger>, request: Request, next: Next) -> Response {
+    if !logger.config.enabled {
+        return next.run(request).await;
+    }
+
+    let record = Arc::new(Record {
+        method: request.method().to_string(),
+        path: request.uri().path().to_owned(),
+        query: sanitize(request.uri().query().unwrap_or_default()),
+        start: Instant::now(),
+        max_payload: logger.sample().then_some(logger.config.max_payload),
+        routed: Default::default(),
+        db_micros: Default::default(),
+        db_statements: Default::default(),
+        request_bytes: Default::default(),
+        request_payload: Default::default(),
+    });
+
+    let (mut parts, body) = request.into_parts();
+    parts.extensions.insert(record.clone());
+
+    // payloads of sampled requests are read upfront if small enough
+    let length = parts
+        .headers
+        .get(CONTENT_LENGTH)
+        .and_then(|length| length.to_str().ok()?.parse::<usize>().ok());
+    let body = match (record.max_payload, length) {
+        (Some(max), Some(length)) if length <= max => {
+            match axum::body::to_bytes(body, length).await {
+                Ok(bytes) => {
+                    record.request_bytes.store(bytes.len(), Ordering::Relaxed);
+                    *record.request_payload.lock().unwrap() = Some(payload(&bytes, bytes.len()));
+                    Body::from(bytes)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to read request payload: {e}");
+                    Body::empty()
+                }
+            }
+        }
+        _ => Body::new(Counted {
+            inner: body,
+            record: record.clone(),
+        }),
+    };
+
+    let response = RECORD
+        .scope(record.clone(), next.run(Request::from_parts(parts, body)))
+        .await;
+
+    let (parts, body) = response.into_parts();
+    let body = Logged {
+        inner: body,
+        record,
+        status: parts.status.as_u16(),
+        handled: Instant::now(),
+        bytes: 0,
+        payload: Vec::new(),
+    };
+
+    Response::from_parts(parts, Body::new(body))
+}
+
+/// Note the matched route, innermost layer of the router
+pub(crate) async fn routed(request: Request, next: Next) -> Response {
+    if let Some(record) = request.extensions().get::<Arc<Record>>() {
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_owned())
+            .unwrap_or_default();
+        *record.routed.lock().unwrap() = Some((Instant::now(), route));
+    }
+
+    next.run(request).await
+}
+
+/// Query string with the values of secret parameters replaced
+fn sanitize(query: &str) -> String {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if SECRET_PARAMETERS.contains(&key.to_lowercase().as_str()) => {
+                format!("{key}=***")
+            }
+            _ => pair.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Start of a payload of `len` bytes as text
+fn payload(start: &[u8], len: usize) -> String {
+    let mut text = String::from_utf8_lossy(start).into_owned();
+    if len > start.len() {
+        text.push('…');
+    }
+    text
+}
+
+/// Request body counting its bytes
+struct Counted {
+    inner: Body,
+    record: Arc<Record>,
+}
+
+impl HttpBody for Counted {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                self.record
+                    .request_bytes
+                    .fetch_add(data.len(), Ordering::Relaxed);
+            }
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Response body logging the request once it is sent or dropped
+struct Logged {
+    inner: Body,
+    record: Arc<Record>,
+    status: u16,
+    /// Time the handler returned the response
+    handled: Instant,
+    bytes: usize,
+    payload: Vec<u8>,
+}
+
+impl HttpBody for Logged {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        // streamed responses may still query the database
+        let record = self.record.clone();
+        let poll = RECORD.sync_scope(record, || Pin::new(&mut self.inner).poll_frame(cx));
+
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                let len = data.len();
+                if let Some(max) = self.record.max_payload {
+                    let take = max.saturating_sub(self.payload.len()).min(len);
+                    let data = data.slice(..take);
+                    self.payload.extend_from_slice(&data);
+                }
+                self.bytes += len;
+            }
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for Logged {
+    fn drop(&mut self) {
+        let record = &self.record;
+        let now = Instant::now();
+
+        let total = now - record.start;
+        let routing = record.routing();
+        let db = Duration::from_micros(record.db_micros.load(Ordering::Relaxed));
+        let handler = (self.handled - record.start).saturating_sub(routing + db);
+        let body = now - self.handled;
+
+        let request_payload = record.request_payload.lock().unwrap().take();
+        let response_payload = record
+            .max_payload
+            .map(|_| payload(&self.payload, self.bytes));
+
+        tracing::info!(
+            target: "ogcapi::requests",
+            method = %record.method,
+            path = %record.path,
+            route = %record.route(),
+            query = %record.query,
+            status = self.status,
+            request_bytes = record.request_bytes.load(Ordering::Relaxed),
+            response_bytes = self.bytes,
+            total_ms = millis(total),
+            routing_ms = millis(routing),
+            db_ms = millis(db),
+            db_statements = record.db_statements.load(Ordering::Relaxed),
+            handler_ms = millis(handler),
+            body_ms = millis(body),
+            request_payload = request_payload.as_deref(),
+            response_payload = response_payload.as_deref(),
+        );
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Layer adding the times of the database statements to the request records
+pub(crate) fn statements<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    Statements.with_filter(filter_fn(|metadata| {
+        metadata.target() == "sqlx::query" && RECORD_STATEMENTS.load(Ordering::Relaxed)
+    }))
+}
+
+/// Layer summing up the `sqlx` statement times, see [`statements`]
+struct Statements;
+
+impl<S: Subscriber> Layer<S> for Statements {
+    fn on_event(&self, event: &tracing::Event<'_>, _: LayerContext<'_, S>) {
+        let _ = RECORD.try_with(|record| {
+            let mut elapsed = Elapsed(None);
+            event.record(&mut elapsed);
+            if let Some(secs) = elapsed.0 {
+                record
+                    .db_micros
+                    .fetch_add((secs * 1e6) as u64, Ordering::Relaxed);
+                record.db_statements.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+    }
+}
+
+/// Visitor reading the `elapsed_secs` of a statement event
+struct Elapsed(Option<f64>);
+
+impl Visit for Elapsed {
+    fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _: &tracing::field::Field, _: &dyn std::fmt::Debug) {}
+}
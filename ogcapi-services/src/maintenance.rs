@@ -0,0 +1,118 @@
+//! Read-only and maintenance mode
+//!
+//! The service may be switched to [`Mode::ReadOnly`], refusing writes with
+//! `503` while serving reads, or to [`Mode::Maintenance`], refusing all
+//! requests with `503` and a maintenance page for browsers, e.g. during
+//! migrations. The admin API at `/admin` stays available in every mode, so
+//! the service can be switched back.
+//!
+//! The mode is stored with the driver and shared by all replicas, each
+//! refreshes it every [`REFRESH`].
+
+use std::time::Duration;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{
+        header::{ACCEPT, CONTENT_TYPE},
+        Method, StatusCode,
+    },
+    middleware::Next,
+    response::{Html, IntoResponse, Response},
+};
+
+use ogcapi_types::common::media_type::{FORM, HTML};
+
+use crate::{state::Mode, AppState, Error};
+
+/// Interval of reading the mode stored with the driver
+pub(crate) const REFRESH: Duration = Duration::from_secs(5);
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>Maintenance</title>
+</head>
+<body>
+  <h1>Down for maintenance</h1>
+  <p>The service is temporarily unavailable, please try again later.</p>
+</body>
+</html>
+"#;
+
+/// Refresh the mode from the driver in the background
+pub(crate) fn spawn(state: &AppState) {
+    let refreshed = state.clone();
+
+    state.tasks.every(
+        "mode",
+        "Read the mode of the service switched by any replica",
+        REFRESH,
+        move || {
+            let state = refreshed.clone();
+            async move { state.refresh_mode().await }
+        },
+    );
+}
+
+/// Refuse the requests not served in the current mode
+pub(crate) async fn guard(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let mode = state.current_mode();
+    if mode == Mode::Normal {
+        return next.run(request).await;
+    }
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str())
+        .unwrap_or_default();
+
+    // the admin API switches the mode back
+    if route.contains("/admin/") {
+        return next.run(request).await;
+    }
+
+    match mode {
+        Mode::Normal => next.run(request).await,
+        // searches and queries are posted but don't write
+        Mode::ReadOnly if request.method().is_safe() || is_query(&request, route) => {
+            next.run(request).await
+        }
+        Mode::ReadOnly => Error::Exception(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The service is read-only during maintenance".to_string(),
+        )
+        .into_response(),
+        Mode::Maintenance if accepts_html(&request) => {
+            (StatusCode::SERVICE_UNAVAILABLE, Html(PAGE)).into_response()
+        }
+        Mode::Maintenance => Error::Exception(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The service is down for maintenance".to_string(),
+        )
+        .into_response(),
+    }
+}
+
+/// Searches and item queries posted as form, e.g. for long lists of ids
+fn is_query(request: &Request, route: &str) -> bool {
+    let form = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with(FORM));
+
+    request.method() == Method::POST
+        && (route.ends_with("/search") || route.ends_with("/items") && form)
+}
+
+/// Whether the request is sent by a browser
+fn accepts_html(request: &Request) -> bool {
+    request
+        .headers()
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains(HTML))
+}
@@ -0,0 +1,298 @@
+//! Usage metering and quotas of api keys
+//!
+//! Requests with a valid api key are metered per key and calendar month: the
+//! requests, the features returned, the tiles rendered and the run time of the
+//! jobs started. The usage is added to the usage recorded by the driver
+//! periodically, shared by the replicas, and handed to the [`BillingHook`] if
+//! any. Keys which reached a limit of their [`Quota`] are refused with `429`
+//! until the next month.
+//!
+//! Jobs run by the workers of the job queue are metered by the replica of
+//! the worker, for the key queued with the job.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    extract::{Request, State},
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{Datelike, TimeZone, Utc};
+
+use ogcapi_types::auth::{KeyUsage, Quota};
+
+use crate::{access, AppState, Error};
+
+/// Time between the writes of the usage to the driver
+const INTERVAL: Duration = Duration::from_secs(60);
+
+tokio::task_local! {
+    /// Key of the request handled by the current task
+    static CURRENT: Metered;
+}
+
+/// Hook of an external billing system, e.g. to report usage to a payment
+/// provider
+///
+/// Failed reports are logged and not repeated, the usage stays recorded by
+/// the driver.
+#[axum::async_trait]
+pub trait BillingHook: Send + Sync {
+    /// Usage metered since the previous report, after it was recorded by the
+    /// driver
+    async fn report(&self, usage: &[KeyUsage]) -> anyhow::Result<()>;
+}
+
+/// Usage of the api keys in the current month
+#[derive(Clone, Default)]
+pub(crate) struct Meter {
+    inner: Arc<Mutex<Inner>>,
+    pub(crate) hook: Option<Arc<dyn BillingHook>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    month: String,
+    /// Usage recorded by the driver, of all replicas
+    recorded: HashMap<String, KeyUsage>,
+    /// Usage not yet written to the driver, by key and month
+    pending: HashMap<(String, String), KeyUsage>,
+    quotas: HashMap<String, Quota>,
+}
+
+impl Meter {
+    /// Add usage of a key in the current month
+    fn add(&self, key: &str, add: impl FnOnce(&mut KeyUsage)) {
+        let inner = &mut *self.inner.lock().unwrap();
+
+        let month = month();
+        if inner.month != month {
+            inner.recorded.clear();
+            inner.month.clone_from(&month);
+        }
+        let usage = inner
+            .pending
+            .entry((key.to_owned(), month.to_owned()))
+            .or_insert_with(|| KeyUsage::new(key, month));
+        add(usage);
+    }
+
+    /// Limits of the quota of a key reached in the current month
+    fn exceeded(&self, key: &str) -> Vec<&'static str> {
+        let inner = self.inner.lock().unwrap();
+
+        let Some(quota) = inner.quotas.get(key) else {
+            return Vec::new();
+        };
+
+        let month = month();
+        let mut usage = KeyUsage::new(key, &month);
+        let pending = inner.pending.get(&(key.to_owned(), month.to_owned()));
+        for used in [inner.recorded.get(key), pending]
+            .into_iter()
+            .flatten()
+            .filter(|used| used.month == month)
+        {
+            usage.add(used);
+        }
+        quota.exceeded(&usage)
+    }
+
+    /// Take the usage not yet written to the driver
+    fn take(&self) -> Vec<KeyUsage> {
+        let pending = std::mem::take(&mut self.inner.lock().unwrap().pending);
+        pending.into_values().collect()
+    }
+
+    /// Return usage failed to be written, to be written with the next one
+    fn restore(&self, usage: Vec<KeyUsage>) {
+        let mut inner = self.inner.lock().unwrap();
+
+        for u in usage {
+            inner
+                .pending
+                .entry((u.key.to_owned(), u.month.to_owned()))
+                .or_insert_with(|| KeyUsage::new(&u.key, &u.month))
+                .add(&u);
+        }
+    }
+
+    /// Replace the recorded usage and the quotas with the ones of the driver
+    fn refresh(&self, month: String, recorded: Vec<KeyUsage>, quotas: Vec<Quota>) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.month = month;
+        inner.recorded = recorded
+            .into_iter()
+            .map(|usage| (usage.key.to_owned(), usage))
+            .collect();
+        inner.quotas = quotas
+            .into_iter()
+            .map(|quota| (quota.key.to_owned(), quota))
+            .collect();
+    }
+}
+
+/// Metered api key of a request
+#[derive(Clone)]
+#[cfg_attr(
+    not(any(feature = "features", feature = "processes", feature = "tiles")),
+    allow(dead_code)
+)]
+pub(crate) struct Metered {
+    meter: Meter,
+    key: String,
+}
+
+impl Metered {
+    /// Count features returned
+    #[cfg(feature = "features")]
+    pub(crate) fn features(&self, count: u64) {
+        self.meter.add(&self.key, |usage| usage.features += count);
+    }
+
+    /// Count tiles rendered
+    #[cfg(feature = "tiles")]
+    pub(crate) fn tiles(&self, count: u64) {
+        self.meter.add(&self.key, |usage| usage.tiles += count);
+    }
+
+    /// Add the run time of a job
+    #[cfg(feature = "processes")]
+    pub(crate) fn job(&self, duration: Duration) {
+        self.meter.add(&self.key, |usage| {
+            usage.job_seconds += duration.as_secs_f64()
+        });
+    }
+
+    /// Id of the metered api key
+    #[cfg(feature = "processes")]
+    pub(crate) fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+/// Metered api key of a queued job, `None` without metering
+#[cfg(feature = "processes")]
+pub(crate) fn queued(state: &AppState, key: String) -> Option<Metered> {
+    state.meter.clone().map(|meter| Metered { meter, key })
+}
+
+/// Metered api key of the request handled by the current task, `None`
+/// without metering or valid api key
+#[cfg_attr(
+    not(any(feature = "features", feature = "processes", feature = "tiles")),
+    allow(dead_code)
+)]
+pub(crate) fn current() -> Option<Metered> {
+    CURRENT.try_with(Clone::clone).ok()
+}
+
+/// Meter the requests with api key, refusing keys beyond their quota
+pub(crate) async fn meter(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let Some(meter) = state.meter.clone() else {
+        return next.run(request).await;
+    };
+
+    // only verified keys are metered, the id is the part before the secret
+    let key = match access::api_key(request.headers()) {
+        Some(key)
+            if access::request_user(&state, request.headers())
+                .await
+                .is_some() =>
+        {
+            key.split_once('.').map(|(id, _)| id.to_owned())
+        }
+        _ => None,
+    };
+    let Some(key) = key else {
+        return next.run(request).await;
+    };
+
+    let exceeded = meter.exceeded(&key);
+    if !exceeded.is_empty() {
+        tracing::debug!("Quota of api key `{key}` exceeded: {exceeded:?}");
+        let mut response = Error::Exception(
+            StatusCode::TOO_MANY_REQUESTS,
+            format!(
+                "Monthly quota of `{}` exceeded for this api key",
+                exceeded.join("`, `")
+            ),
+        )
+        .into_response();
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(until_next_month()));
+        return response;
+    }
+
+    meter.add(&key, |usage| usage.requests += 1);
+
+    CURRENT
+        .scope(Metered { meter, key }, next.run(request))
+        .await
+}
+
+/// Write the usage to the driver periodically, reporting it to the billing
+/// hook and refreshing the usage of all replicas and the quotas
+pub(crate) fn spawn(state: &AppState) {
+    let Some(meter) = state.meter.clone() else {
+        return;
+    };
+    let drivers = state.drivers.clone();
+
+    state.tasks.every(
+        "key-usage",
+        "Write the usage of the api keys and refresh their quotas",
+        INTERVAL,
+        move || {
+            let drivers = drivers.clone();
+            let meter = meter.clone();
+            async move {
+                let pending = meter.take();
+                if !pending.is_empty() {
+                    if let Err(e) = drivers.quotas.record_key_usage(&pending).await {
+                        meter.restore(pending);
+                        return Err(e);
+                    }
+                    if let Some(hook) = &meter.hook {
+                        if let Err(e) = hook.report(&pending).await {
+                            tracing::warn!("Failed to report usage to billing: {e}");
+                        }
+                    }
+                }
+
+                let month = month();
+                let recorded = drivers.quotas.key_usage(None, Some(&month)).await?;
+                let quotas = drivers.quotas.list_quotas(None).await?;
+                meter.refresh(month, recorded, quotas);
+
+                Ok(())
+            }
+        },
+    );
+}
+
+/// Current month as `YYYY-MM`
+fn month() -> String {
+    Utc::now().format("%Y-%m").to_string()
+}
+
+/// Seconds until the start of the next month
+fn until_next_month() -> u64 {
+    let now = Utc::now();
+    let (year, month) = match now.month() {
+        12 => (now.year() + 1, 1),
+        month => (now.year(), month + 1),
+    };
+
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0)
+        .single()
+        .map(|next| (next - now).num_seconds().max(0) as u64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,124 @@
+//! Tolerant handling of sloppy request urls
+//!
+//! Trailing slashes are removed from the path and the known query parameters
+//! are renamed to their canonical form before routing, regardless of case and
+//! of the word separation, e.g. `bbox_crs` and the legacy `bboxCrs` become
+//! `bbox-crs`. Unknown parameters, e.g. property filters, are kept as sent.
+
+use axum::{
+    extract::Request,
+    http::{uri::PathAndQuery, Uri},
+};
+
+/// Canonical names of the query parameters
+const PARAMETERS: [&str; 33] = [
+    "bbox",
+    "bbox-crs",
+    "cell-size",
+    "collections",
+    "coords",
+    "corridor-height",
+    "corridor-width",
+    "crs",
+    "datetime",
+    "dry-run",
+    "f",
+    "filter",
+    "filter-crs",
+    "filter-lang",
+    "height-units",
+    "id-property",
+    "item-type",
+    "keyword",
+    "limit",
+    "offset",
+    "parameter-name",
+    "precision",
+    "processID",
+    "properties",
+    "q",
+    "resolution-x",
+    "resolution-y",
+    "resolution-z",
+    "sortby",
+    "storage-crs",
+    "width-units",
+    "within-units",
+    "z",
+];
+
+/// Rewrite the uri of a request to its canonical form
+pub(crate) async fn normalize(mut request: Request) -> Request {
+    let uri = request.uri();
+
+    let path = match uri.path().trim_end_matches('/') {
+        "" => "/",
+        path => path,
+    };
+    let query = uri
+        .query()
+        .map(|query| canonical_query(query).unwrap_or(query.to_owned()));
+
+    if path == uri.path() && query.as_deref() == uri.query() {
+        return request;
+    }
+
+    let path_and_query = match query {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_owned(),
+    };
+
+    let mut parts = uri.to_owned().into_parts();
+    parts.path_and_query = path_and_query.parse::<PathAndQuery>().ok();
+    if let Ok(uri) = Uri::from_parts(parts) {
+        *request.uri_mut() = uri;
+    }
+
+    request
+}
+
+/// Query string with the known parameters renamed, `None` if unchanged
+fn canonical_query(query: &str) -> Option<String> {
+    let pairs: Vec<(&str, Option<&str>)> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (key, Some(value)),
+            None => (pair, None),
+        })
+        .collect();
+
+    let mut changed = false;
+    let renamed: Vec<String> = pairs
+        .iter()
+        .map(|(key, value)| {
+            let key = match canonical(key) {
+                // a parameter also sent in canonical form is left to conflict
+                Some(name) if name != *key && !pairs.iter().any(|(k, _)| *k == name) => {
+                    changed = true;
+                    name
+                }
+                _ => key,
+            };
+            match value {
+                Some(value) => format!("{key}={value}"),
+                None => key.to_owned(),
+            }
+        })
+        .collect();
+
+    changed.then(|| renamed.join("&"))
+}
+
+/// Canonical name of a parameter, if known
+fn canonical(key: &str) -> Option<&'static str> {
+    let folded = fold(key);
+    PARAMETERS.into_iter().find(|name| fold(name) == folded)
+}
+
+/// Lowercase name without word separators
+fn fold(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
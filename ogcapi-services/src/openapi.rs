@@ -1,7 +1,9 @@
 use std::{fs, path::Path, str::FromStr};
 
+use serde_json::Value;
+
 #[doc(hidden)]
-pub static OPENAPI: &[u8; 29696] = include_bytes!("../assets/openapi/openapi.yaml");
+pub static OPENAPI: &[u8] = include_bytes!("../assets/openapi/openapi.yaml");
 
 #[derive(Default, Clone)]
 pub struct OpenAPI(pub openapiv3::OpenAPI);
@@ -26,3 +28,55 @@ impl OpenAPI {
         OpenAPI::from_str(&api)
     }
 }
+
+/// Default dialect of schemas in OpenAPI 3.1 documents
+pub const JSON_SCHEMA_DIALECT: &str = "https://spec.openapis.org/oas/3.1/dialect/base";
+
+impl OpenAPI {
+    /// Convert to an OpenAPI 3.1 document with schemas in the JSON Schema
+    /// 2020-12 dialect
+    pub fn to_v3_1(&self) -> Value {
+        let mut api = serde_json::to_value(&self.0).expect("serialize OpenAPI definition");
+
+        upgrade_schemas(&mut api);
+
+        api["openapi"] = Value::from("3.1.0");
+        api["jsonSchemaDialect"] = Value::from(JSON_SCHEMA_DIALECT);
+
+        api
+    }
+}
+
+/// Replace the 3.0 specific schema keywords `nullable` and boolean
+/// `exclusiveMinimum`/`exclusiveMaximum` throughout the document
+fn upgrade_schemas(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::Bool(nullable)) = object.remove("nullable") {
+                if let (true, Some(Value::String(r#type))) = (nullable, object.get("type")) {
+                    let r#type = Value::from(vec![r#type.to_owned(), "null".to_owned()]);
+                    object.insert("type".to_owned(), r#type);
+                }
+            }
+
+            for (exclusive, bound) in [
+                ("exclusiveMinimum", "minimum"),
+                ("exclusiveMaximum", "maximum"),
+            ] {
+                if let Some(Value::Bool(is_exclusive)) = object.get(exclusive).cloned() {
+                    object.remove(exclusive);
+                    // the bound becomes the value of the keyword
+                    if is_exclusive {
+                        if let Some(bound) = object.remove(bound) {
+                            object.insert(exclusive.to_owned(), bound);
+                        }
+                    }
+                }
+            }
+
+            object.values_mut().for_each(upgrade_schemas);
+        }
+        Value::Array(array) => array.iter_mut().for_each(upgrade_schemas),
+        _ => (),
+    }
+}
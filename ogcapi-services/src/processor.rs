@@ -1,12 +1,36 @@
-use axum::response::{IntoResponse, Response};
+#[cfg(feature = "bundle")]
+mod bundle;
+#[cfg(feature = "geopackage")]
+mod geopackage;
+#[cfg(feature = "openeo")]
+mod openeo;
+#[cfg(feature = "print")]
+mod print;
+#[cfg(feature = "reproject")]
+mod reproject;
+#[cfg(feature = "snapshot")]
+mod snapshot;
+
+use std::{collections::HashMap, future::Future, path::PathBuf, time::Instant};
+
+use axum::{
+    http::{header::LOCATION, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
 use dyn_clone::DynClone;
 use schemars::{schema_for, JsonSchema};
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 use url::Url;
 
-use ogcapi_types::processes::{Execute, Process};
+use ogcapi_types::{
+    common::{media_type::JSON, LinkBuilder},
+    processes::{Execute, InlineOrRefData, Process, Results, StatusCode as JobStatus, StatusInfo},
+};
 
-use crate::{AppState, Result};
+use crate::{metering, state::Drivers, AppState, Result};
 
 #[axum::async_trait]
 /// Trait for defining and executing a [Process]
@@ -17,12 +41,299 @@ pub trait Processor: Send + Sync + DynClone {
     /// Returns the Process description
     fn process(&self) -> Process;
 
+    /// Whether only admins may execute the process, e.g. processes rewriting
+    /// whole collections
+    fn admin(&self) -> bool {
+        false
+    }
+
     /// Executes the Process and returns a response
     async fn execute(&self, execute: Execute, state: &AppState, url: &Url) -> Result<Response>;
+
+    /// Runs a job submitted with [`queue_job`], on any replica of the service,
+    /// with the inputs it was queued with
+    async fn run_job(
+        &self,
+        _job_id: &str,
+        _inputs: Value,
+        _state: &AppState,
+    ) -> anyhow::Result<HashMap<String, InlineOrRefData>> {
+        anyhow::bail!("Process `{}` does not run queued jobs", self.id())
+    }
 }
 
 dyn_clone::clone_trait_object!(Processor);
 
+/// [Processor] defined by its typed inputs and outputs
+///
+/// The process description with the schemas of the inputs and outputs, the
+/// parsing of the inputs and the results of queued jobs are derived from the
+/// types, which leaves the execution itself to implement. Every
+/// `TypedProcessor` is a [Processor].
+///
+/// ```rust,ignore
+/// #[derive(Clone)]
+/// struct Echo;
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// struct EchoInputs {
+///     /// Text to echo
+///     text: String,
+/// }
+///
+/// #[axum::async_trait]
+/// impl TypedProcessor for Echo {
+///     const ID: &'static str = "echo";
+///     type Inputs = EchoInputs;
+///     type Outputs = String;
+///
+///     async fn run(&self, inputs: EchoInputs, _: &AppState, _: &Url) -> Result<Response> {
+///         Ok(inputs.text.into_response())
+///     }
+/// }
+/// ```
+#[axum::async_trait]
+pub trait TypedProcessor: Send + Sync + Clone + 'static {
+    /// Process id (must be unique)
+    const ID: &'static str;
+
+    /// Process version
+    const VERSION: &'static str = "0.1.0";
+
+    /// Whether only admins may execute the process, see [`Processor::admin`]
+    const ADMIN: bool = false;
+
+    /// Inputs of an execution, their schema is published as the inputs of the
+    /// process
+    type Inputs: DeserializeOwned + JsonSchema + Send;
+
+    /// Outputs of a job, their schema is published as the outputs of the
+    /// process
+    type Outputs: Serialize + JsonSchema + Send;
+
+    /// Amends the derived process description, e.g. with the job control
+    /// options
+    fn describe(&self, _process: &mut Process) {}
+
+    /// Executes the process with the parsed inputs and returns a response
+    async fn run(&self, inputs: Self::Inputs, state: &AppState, url: &Url) -> Result<Response>;
+
+    /// Runs a job submitted with [`queue_job`], see [`Processor::run_job`],
+    /// each field of the outputs is a result of the job
+    async fn run_queued(
+        &self,
+        _job_id: &str,
+        _inputs: Self::Inputs,
+        _state: &AppState,
+    ) -> anyhow::Result<Self::Outputs> {
+        anyhow::bail!("Process `{}` does not run queued jobs", Self::ID)
+    }
+}
+
+#[axum::async_trait]
+impl<P: TypedProcessor> Processor for P {
+    fn id(&self) -> String {
+        P::ID.to_string()
+    }
+
+    fn admin(&self) -> bool {
+        P::ADMIN
+    }
+
+    fn process(&self) -> Process {
+        let mut process = Process::new(
+            P::ID,
+            P::VERSION,
+            &serde_json::to_value(schema_for!(P::Inputs).schema).unwrap(),
+            &serde_json::to_value(schema_for!(P::Outputs).schema).unwrap(),
+        );
+        self.describe(&mut process);
+        process
+    }
+
+    async fn execute(&self, execute: Execute, state: &AppState, url: &Url) -> Result<Response> {
+        let inputs = parse_inputs(execute)?;
+        self.run(inputs, state, url).await
+    }
+
+    async fn run_job(
+        &self,
+        job_id: &str,
+        inputs: Value,
+        state: &AppState,
+    ) -> anyhow::Result<HashMap<String, InlineOrRefData>> {
+        let inputs = serde_json::from_value(inputs)?;
+        let outputs = self.run_queued(job_id, inputs, state).await?;
+
+        let Value::Object(outputs) = serde_json::to_value(outputs)? else {
+            anyhow::bail!("Outputs of process `{}` are not an object", P::ID);
+        };
+        // omitted optional outputs are no results
+        outputs
+            .into_iter()
+            .filter(|(_, output)| !output.is_null())
+            .map(|(id, output)| Ok((id, serde_json::from_value(output)?)))
+            .collect()
+    }
+}
+
+#[cfg(feature = "bundle")]
+pub use bundle::OfflineBundle;
+#[cfg(feature = "geopackage")]
+pub use geopackage::{export_geopackage, import_geopackage, GeoPackageExport, GeoPackageImport};
+#[cfg(feature = "openeo")]
+pub(crate) use openeo::wrap_request;
+#[cfg(feature = "openeo")]
+pub use openeo::{OpenEo, ProcessGraph, ProcessNode};
+#[cfg(feature = "print")]
+pub use print::MapPrint;
+#[cfg(feature = "reproject")]
+pub use reproject::CollectionReproject;
+#[cfg(feature = "snapshot")]
+pub use snapshot::{CollectionRestore, CollectionSnapshot};
+
+/// Submit a job of a process, run with [`Processor::run_job`]
+///
+/// With the job queue enabled, the job is run by a worker of any replica of
+/// the service, otherwise in the background of this one. Either way the inputs
+/// are the only state shared with the job.
+pub async fn queue_job<P>(
+    processor: &P,
+    inputs: Value,
+    state: &AppState,
+    url: &Url,
+) -> Result<Response>
+where
+    P: Processor + Clone + 'static,
+{
+    let job = StatusInfo {
+        process_id: Some(processor.id()),
+        job_id: uuid::Uuid::new_v4().to_string(),
+        status: JobStatus::Accepted,
+        created: Some(Utc::now()),
+        ..Default::default()
+    };
+    state.drivers.jobs.register(&job).await?;
+
+    let metered = metering::current();
+    if state.job_queue.is_some() {
+        state
+            .drivers
+            .queue
+            .enqueue(
+                &job.job_id,
+                &processor.id(),
+                &inputs,
+                metered.as_ref().map(|metered| metered.key()),
+            )
+            .await?;
+    } else {
+        let processor = processor.clone();
+        let job_state = state.clone();
+        let job = job.clone();
+        tokio::spawn(async move {
+            let start = Instant::now();
+            let drivers = job_state.drivers.clone();
+            run(&drivers, job, |job_id| async move {
+                processor.run_job(&job_id, inputs, &job_state).await
+            })
+            .await;
+            if let Some(metered) = metered {
+                metered.job(start.elapsed());
+            }
+        });
+    }
+
+    let location = url.join(&format!("../../jobs/{}", job.job_id))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    let info = StatusInfo {
+        links: vec![LinkBuilder::new(&location).mediatype(JSON).self_link()],
+        ..job
+    };
+
+    Ok((StatusCode::CREATED, headers, Json(info)).into_response())
+}
+
+/// Run the task of a job, keeping its status up to date
+pub(crate) async fn run<Fut>(
+    drivers: &Drivers,
+    mut job: StatusInfo,
+    task: impl FnOnce(String) -> Fut,
+) where
+    Fut: Future<Output = anyhow::Result<HashMap<String, InlineOrRefData>>>,
+{
+    job.status = JobStatus::Running;
+    if let Err(e) = drivers.jobs.update(&job).await {
+        tracing::error!("Failed to update job `{}`: {:?}", job.job_id, e);
+    }
+
+    match task(job.job_id.clone()).await {
+        Ok(results) => {
+            if let Err(e) = drivers
+                .jobs
+                .set_results(&job.job_id, &Results { results })
+                .await
+            {
+                tracing::error!("Failed to store results of job `{}`: {:?}", job.job_id, e);
+            }
+            job.status = JobStatus::Successful;
+            job.progress = Some(100);
+        }
+        Err(e) => {
+            tracing::error!("Job `{}` failed: {:?}", job.job_id, e);
+            job.status = JobStatus::Failed;
+            job.message = Some(e.to_string());
+        }
+    }
+    job.finished = Some(Utc::now());
+
+    if let Err(e) = drivers.jobs.update(&job).await {
+        tracing::error!("Failed to update job `{}`: {:?}", job.job_id, e);
+    }
+}
+
+/// Deserialize the inputs of an execution, invalid inputs are a bad request
+fn parse_inputs<T: for<'de> Deserialize<'de>>(execute: Execute) -> Result<T> {
+    let value = serde_json::to_value(execute.inputs).map_err(anyhow::Error::from)?;
+    serde_json::from_value(value)
+        .map_err(|e| crate::Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+/// Result linking to a file output of a job
+///
+/// The link is relative to the results of the job, it is resolved when they
+/// are served as the job may run on another replica than the request.
+#[cfg(any(feature = "bundle", feature = "geopackage", feature = "print"))]
+pub(crate) fn output_link(output: &str, media_type: &str) -> InlineOrRefData {
+    use ogcapi_types::common::{link_rel::ENCLOSURE, Link};
+
+    InlineOrRefData::Link(Link::new(format!("results/{output}"), ENCLOSURE).mediatype(media_type))
+}
+
+/// Location of a file output of a job, served at `/jobs/{jobId}/results/{output}`
+///
+/// Job ids and output names are single path segments, anything that could
+/// escape the directory of the job is rejected.
+pub(crate) fn output_path(job_id: &str, output: &str) -> anyhow::Result<PathBuf> {
+    for segment in [job_id, output] {
+        if segment.is_empty()
+            || segment == "."
+            || segment == ".."
+            || segment.contains(['/', '\\', '\0'])
+        {
+            anyhow::bail!("Invalid path segment `{segment}`");
+        }
+    }
+
+    Ok(crate::state::work_dir()
+        .join("jobs")
+        .join(job_id)
+        .join(output))
+}
+
 /// Example Processor
 ///
 /// ```bash
@@ -36,33 +347,37 @@ pub struct Greeter;
 
 /// Inputs for the `greet` process
 #[derive(Deserialize, Debug, JsonSchema)]
-struct GreeterInputs {
+pub struct GreeterInputs {
     /// Name to be greeted
-    name: String,
+    pub name: String,
 }
 
 /// Outputs for the `greet` process
-#[allow(dead_code)]
-#[derive(JsonSchema)]
-struct GreeterOutputs(String);
+#[derive(Serialize, JsonSchema)]
+pub struct GreeterOutputs {
+    /// Greeting of the name
+    pub greeting: String,
+}
 
 #[axum::async_trait]
-impl Processor for Greeter {
-    fn id(&self) -> String {
-        "greet".to_string()
-    }
-    fn process(&self) -> Process {
-        Process::new(
-            self.id(),
-            "0.1.0",
-            &serde_json::to_value(&schema_for!(GreeterInputs).schema).unwrap(),
-            &serde_json::to_value(&schema_for!(GreeterOutputs).schema).unwrap(),
-        )
-    }
+impl TypedProcessor for Greeter {
+    const ID: &'static str = "greet";
+
+    type Inputs = GreeterInputs;
+    type Outputs = GreeterOutputs;
 
-    async fn execute(&self, execute: Execute, _state: &AppState, _url: &Url) -> Result<Response> {
-        let value = serde_json::to_value(execute.inputs).unwrap();
-        let inputs: GreeterInputs = serde_json::from_value(value).unwrap();
+    async fn run(&self, inputs: GreeterInputs, _state: &AppState, _url: &Url) -> Result<Response> {
         Ok(format!("Hello, {}!\n", inputs.name).into_response())
     }
+
+    async fn run_queued(
+        &self,
+        _job_id: &str,
+        inputs: GreeterInputs,
+        _state: &AppState,
+    ) -> anyhow::Result<GreeterOutputs> {
+        Ok(GreeterOutputs {
+            greeting: format!("Hello, {}!", inputs.name),
+        })
+    }
 }
@@ -0,0 +1,428 @@
+//! Offline bundles of collections for field work
+//!
+//! A bundle is a zip archive covering an area of interest, with the vector
+//! tiles of the collections as `PMTiles` basemap (`basemap.pmtiles`), their
+//! features intersecting the area as `GeoPackage` per collection and a style
+//! (`style.json`) drawing the basemap, e.g. for field apps based on `MapLibre`.
+
+use std::{
+    collections::HashMap,
+    f64::consts::PI,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use axum::{http::StatusCode, response::Response};
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use url::Url;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use ogcapi_drivers::pmtiles::{tile_id, Archive};
+use ogcapi_types::{
+    common::{media_type::ZIP, Bbox, Collection},
+    features::Query,
+    processes::{Execute, InlineOrRefData, JobControlOptions, Process},
+};
+
+use crate::{routes::tiles::tile_matrix, AppState, Error, Result};
+
+use super::{geopackage::write_geopackage, output_link, parse_inputs, queue_job, Processor};
+
+/// Tile matrix set of the basemap
+const TMS_ID: &str = "WebMercatorQuad";
+
+/// Highest zoom level of the basemap
+const MAX_ZOOM: u8 = 18;
+
+/// Maximum number of tiles of the basemap
+const MAX_TILES: u64 = 100_000;
+
+/// Latitude limit of web mercator
+const MAX_LATITUDE: f64 = 85.051_128_78;
+
+/// Colors of the collections in the generated style, in order
+const PALETTE: [&str; 6] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b",
+];
+
+/// Package collections within an area of interest for offline use
+///
+/// ```bash
+/// curl http://localhost:8484/processes/offline-bundle/execution \
+///         -H 'Content-Type: application/json' \
+///         -d '{"inputs": {"collections": ["countries"], "bbox": [5.9, 45.8, 10.5, 47.8], "maxZoom": 10}}'
+/// ```
+#[derive(Clone)]
+pub struct OfflineBundle;
+
+/// Inputs for the `offline-bundle` process
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct BundleInputs {
+    /// Identifiers of the collections
+    collections: Vec<String>,
+    /// Area of interest in `CRS84`
+    bbox: [f64; 4],
+    /// Lowest zoom level of the basemap, defaults to 0
+    #[serde(default)]
+    min_zoom: u8,
+    /// Highest zoom level of the basemap, defaults to 14
+    #[serde(default = "default_max_zoom")]
+    max_zoom: u8,
+    /// Style to include instead of the generated one
+    style: Option<Value>,
+}
+
+fn default_max_zoom() -> u8 {
+    14
+}
+
+/// Outputs for the `offline-bundle` process
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+struct BundleOutputs {
+    /// Link to the zip archive
+    bundle: String,
+}
+
+#[axum::async_trait]
+impl Processor for OfflineBundle {
+    fn id(&self) -> String {
+        "offline-bundle".to_string()
+    }
+
+    fn process(&self) -> Process {
+        let mut process = Process::new(
+            self.id(),
+            "0.1.0",
+            &serde_json::to_value(&schema_for!(BundleInputs).schema).unwrap(),
+            &serde_json::to_value(&schema_for!(BundleOutputs).schema).unwrap(),
+        );
+        process.summary.job_control_options = vec![JobControlOptions::AsyncExecute];
+        process
+    }
+
+    async fn execute(&self, execute: Execute, state: &AppState, url: &Url) -> Result<Response> {
+        let inputs: BundleInputs = parse_inputs(execute)?;
+
+        if inputs.collections.is_empty() {
+            return Err(bad_request("Missing collections".to_string()));
+        }
+        let [minx, miny, maxx, maxy] = inputs.bbox;
+        if !(-180.0..=180.0).contains(&minx)
+            || !(-180.0..=180.0).contains(&maxx)
+            || !(-90.0..=90.0).contains(&miny)
+            || !(-90.0..=90.0).contains(&maxy)
+            || minx >= maxx
+            || miny >= maxy
+        {
+            return Err(bad_request(format!("Invalid bbox `{:?}`", inputs.bbox)));
+        }
+        if inputs.min_zoom > inputs.max_zoom || inputs.max_zoom > MAX_ZOOM {
+            return Err(bad_request(format!(
+                "Invalid zoom levels, expected `minZoom` up to `maxZoom` up to {MAX_ZOOM}"
+            )));
+        }
+
+        let count: u64 = (inputs.min_zoom..=inputs.max_zoom)
+            .map(|z| {
+                let (x0, y0, x1, y1) = tile_range(&inputs.bbox, z);
+                u64::from(x1 - x0 + 1) * u64::from(y1 - y0 + 1)
+            })
+            .sum();
+        if count > MAX_TILES {
+            return Err(bad_request(format!(
+                "Bundle covers {count} tiles, more than the maximum of {MAX_TILES}, \
+                reduce the area or the zoom levels"
+            )));
+        }
+
+        // bundles hold what is accessible without api key
+        for id in &inputs.collections {
+            if state
+                .drivers
+                .collections
+                .read_collection(id)
+                .await?
+                .is_none()
+            {
+                return Err(bad_request(format!("Unknown collection `{id}`")));
+            }
+            if state
+                .drivers
+                .access
+                .access_filter(id, None)
+                .await?
+                .is_some()
+            {
+                return Err(Error::Exception(
+                    StatusCode::FORBIDDEN,
+                    format!("Access to collection `{id}` is restricted to its features"),
+                ));
+            }
+        }
+
+        let inputs = serde_json::to_value(inputs).map_err(anyhow::Error::from)?;
+        queue_job(self, inputs, state, url).await
+    }
+
+    async fn run_job(
+        &self,
+        job_id: &str,
+        inputs: Value,
+        state: &AppState,
+    ) -> anyhow::Result<HashMap<String, InlineOrRefData>> {
+        let inputs: BundleInputs = serde_json::from_value(inputs)?;
+
+        let mut collections = Vec::new();
+        for id in &inputs.collections {
+            let Some(collection) = state.drivers.collections.read_collection(id).await? else {
+                anyhow::bail!("Unknown collection `{id}`");
+            };
+            if state
+                .drivers
+                .access
+                .access_filter(id, None)
+                .await?
+                .is_some()
+            {
+                anyhow::bail!("Access to collection `{id}` is restricted to its features");
+            }
+            collections.push(collection);
+        }
+
+        let dir = std::env::temp_dir().join(format!("{job_id}-bundle"));
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let bundle = async {
+            let mut files = vec![write_basemap(state, &dir, &collections, &inputs).await?];
+
+            let query = Query {
+                bbox: Some(Bbox::Bbox2D(inputs.bbox)),
+                ..Default::default()
+            };
+            for collection in &collections {
+                let path = dir.join(format!("{}.gpkg", collection.id));
+                let hidden = collection.hidden_properties(None);
+                write_geopackage(state, &path, &collection.id, &query, &hidden).await?;
+                files.push(path);
+            }
+
+            let style = match &inputs.style {
+                Some(style) => style.to_owned(),
+                None => style(&collections, &inputs),
+            };
+            let path = dir.join("style.json");
+            tokio::fs::write(&path, serde_json::to_vec_pretty(&style)?).await?;
+            files.push(path);
+
+            let output = super::output_path(job_id, "bundle")?;
+            tokio::fs::create_dir_all(output.parent().unwrap()).await?;
+            tokio::task::spawn_blocking(move || archive(&output, &files)).await?
+        }
+        .await;
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        bundle?;
+
+        Ok(HashMap::from([(
+            "bundle".to_string(),
+            output_link("bundle", ZIP),
+        )]))
+    }
+}
+
+/// Render the tiles of the collections within the area to a `PMTiles` archive
+async fn write_basemap(
+    state: &AppState,
+    dir: &Path,
+    collections: &[Collection],
+    inputs: &BundleInputs,
+) -> anyhow::Result<PathBuf> {
+    let Some((tms, _)) = tile_matrix(TMS_ID, "0") else {
+        anyhow::bail!("Missing tile matrix set `{TMS_ID}`");
+    };
+    let ids = collections
+        .iter()
+        .map(|c| c.id.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    // tiles in order of their id, as clients read them
+    let mut tiles = Vec::new();
+    for z in inputs.min_zoom..=inputs.max_zoom {
+        let (x0, y0, x1, y1) = tile_range(&inputs.bbox, z);
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                tiles.push((tile_id(z, x, y), z, x, y));
+            }
+        }
+    }
+    tiles.sort_unstable();
+
+    let layers: Vec<Value> = layer_names(collections)
+        .into_iter()
+        .map(|name| json!({ "id": name, "fields": {} }))
+        .collect();
+    let mut archive = Archive::new(
+        inputs.min_zoom,
+        inputs.max_zoom,
+        inputs.bbox,
+        json!({ "name": "basemap", "format": "pbf", "vector_layers": layers }),
+    );
+
+    let data_path = dir.join("tiles.bin");
+    let mut data = tokio::io::BufWriter::new(tokio::fs::File::create(&data_path).await?);
+    for (id, z, x, y) in tiles {
+        let tile = state
+            .drivers
+            .tiles
+            .tile(&ids, tms, &z.to_string(), y, x, None, false)
+            .await?;
+
+        // missing tiles are empty
+        if !tile.is_empty() {
+            data.write_all(&tile).await?;
+            archive.add_tile(id, tile.len() as u32)?;
+        }
+    }
+    data.flush().await?;
+    drop(data);
+
+    let path = dir.join("basemap.pmtiles");
+    let mut file = tokio::fs::File::create(&path).await?;
+    file.write_all(&archive.head()?).await?;
+    tokio::io::copy(&mut tokio::fs::File::open(&data_path).await?, &mut file).await?;
+    file.flush().await?;
+    tokio::fs::remove_file(&data_path).await?;
+
+    Ok(path)
+}
+
+/// Columns and rows of the tiles of a zoom level covering a bbox
+fn tile_range(bbox: &[f64; 4], z: u8) -> (u32, u32, u32, u32) {
+    let n = f64::from(1u32 << z);
+    let max = (1u32 << z) - 1;
+
+    let col = |lon: f64| (((lon + 180.0) / 360.0 * n).floor().max(0.0) as u32).min(max);
+    let row = |lat: f64| {
+        let lat = lat.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+        let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * n;
+        (y.floor().max(0.0) as u32).min(max)
+    };
+
+    (col(bbox[0]), row(bbox[3]), col(bbox[2]), row(bbox[1]))
+}
+
+/// Names of the tile layers of the collections
+fn layer_names(collections: &[Collection]) -> Vec<String> {
+    let mut names = Vec::new();
+    for collection in collections {
+        names.push(collection.id.to_owned());
+        if let Some(tile_layers) = &collection.tile_layers {
+            names.extend(tile_layers.layers.iter().map(|l| l.name.to_owned()));
+        }
+    }
+    names
+}
+
+/// Style drawing the polygons, lines and points of the basemap layers in a
+/// color per collection
+fn style(collections: &[Collection], inputs: &BundleInputs) -> Value {
+    let mut layers = vec![json!({
+        "id": "background",
+        "type": "background",
+        "paint": { "background-color": "#f8f4f0" }
+    })];
+
+    for (i, collection) in collections.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        for name in layer_names(std::slice::from_ref(collection)) {
+            layers.extend([
+                json!({
+                    "id": format!("{name}-fill"),
+                    "type": "fill",
+                    "source": "basemap",
+                    "source-layer": name,
+                    "filter": ["==", "$type", "Polygon"],
+                    "paint": { "fill-color": color, "fill-opacity": 0.4 }
+                }),
+                json!({
+                    "id": format!("{name}-line"),
+                    "type": "line",
+                    "source": "basemap",
+                    "source-layer": name,
+                    "filter": ["in", "$type", "LineString", "Polygon"],
+                    "paint": { "line-color": color, "line-width": 1.5 }
+                }),
+                json!({
+                    "id": format!("{name}-circle"),
+                    "type": "circle",
+                    "source": "basemap",
+                    "source-layer": name,
+                    "filter": ["==", "$type", "Point"],
+                    "paint": {
+                        "circle-color": color,
+                        "circle-radius": 4,
+                        "circle-stroke-color": "#ffffff",
+                        "circle-stroke-width": 1
+                    }
+                }),
+            ]);
+        }
+    }
+
+    let [minx, miny, maxx, maxy] = inputs.bbox;
+    json!({
+        "version": 8,
+        "name": "Offline bundle",
+        "center": [(minx + maxx) / 2.0, (miny + maxy) / 2.0],
+        "zoom": inputs.min_zoom,
+        "sources": {
+            "basemap": {
+                "type": "vector",
+                "url": "pmtiles://basemap.pmtiles",
+                "minzoom": inputs.min_zoom,
+                "maxzoom": inputs.max_zoom,
+                "bounds": inputs.bbox
+            }
+        },
+        "layers": layers
+    })
+}
+
+/// Zip the files, the `PMTiles` and `GeoPackage` files stored as they are to
+/// be read in place
+fn archive(output: &Path, files: &[PathBuf]) -> anyhow::Result<()> {
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(output)?));
+
+    for path in files {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow::anyhow!("Invalid file name `{}`", path.display()))?;
+        let method = if name.ends_with(".json") {
+            CompressionMethod::Deflated
+        } else {
+            CompressionMethod::Stored
+        };
+        let options = FileOptions::default()
+            .compression_method(method)
+            .large_file(true);
+
+        zip.start_file(name, options)?;
+        std::io::copy(&mut BufReader::new(File::open(path)?), &mut zip)?;
+    }
+
+    zip.finish()?.flush()?;
+
+    Ok(())
+}
+
+fn bad_request(message: String) -> Error {
+    Error::Exception(StatusCode::BAD_REQUEST, message)
+}
@@ -0,0 +1,543 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use axum::{http::StatusCode, response::Response};
+use base64::Engine;
+use futures::StreamExt;
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{sqlite::SqliteConnectOptions, Connection, SqliteConnection};
+use url::Url;
+
+use ogcapi_drivers::{geopackage, wkb};
+
+use ogcapi_types::{
+    common::{
+        media_type::{GEO_PACKAGE, JSON},
+        Bbox, Collection, Crs,
+    },
+    features::Query,
+    processes::{Execute, InlineOrRefData, JobControlOptions, Process},
+};
+
+use crate::{routes::features::check_hidden_parameters, AppState, Error, Result};
+
+use super::{output_link, parse_inputs, queue_job, Processor};
+
+/// Number of features inserted at once
+pub(super) const BATCH_SIZE: usize = 1000;
+
+/// `GeoPackage` application id (`GPKG`)
+const APPLICATION_ID: i32 = 0x4750_4B47;
+
+/// `GeoPackage` version 1.3
+const USER_VERSION: i32 = 10300;
+
+/// Import the feature tables of a GeoPackage into collections
+///
+/// ```bash
+/// curl http://localhost:8484/processes/geopackage-import/execution \
+///         -H 'Content-Type: application/json' \
+///         -d "{\"inputs\": {\"geopackage\": \"$(base64 -w0 data.gpkg)\"}}"
+/// ```
+#[derive(Clone)]
+pub struct GeoPackageImport;
+
+/// Inputs for the `geopackage-import` process
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+struct ImportInputs {
+    /// Base64 encoded GeoPackage
+    geopackage: String,
+    /// Feature tables to import, defaults to all
+    tables: Option<Vec<String>>,
+}
+
+/// Outputs for the `geopackage-import` process
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+struct ImportOutputs {
+    /// Identifiers of the created or updated collections
+    collections: Vec<String>,
+}
+
+#[axum::async_trait]
+impl Processor for GeoPackageImport {
+    fn id(&self) -> String {
+        "geopackage-import".to_string()
+    }
+
+    fn process(&self) -> Process {
+        let mut process = Process::new(
+            self.id(),
+            "0.1.0",
+            &serde_json::to_value(&schema_for!(ImportInputs).schema).unwrap(),
+            &serde_json::to_value(&schema_for!(ImportOutputs).schema).unwrap(),
+        );
+        process.summary.job_control_options = vec![JobControlOptions::AsyncExecute];
+        process
+    }
+
+    async fn execute(&self, execute: Execute, state: &AppState, url: &Url) -> Result<Response> {
+        let inputs: ImportInputs = parse_inputs(execute)?;
+
+        base64::engine::general_purpose::STANDARD
+            .decode(inputs.geopackage.trim())
+            .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+        let inputs = serde_json::to_value(inputs).map_err(anyhow::Error::from)?;
+        queue_job(self, inputs, state, url).await
+    }
+
+    async fn run_job(
+        &self,
+        job_id: &str,
+        inputs: Value,
+        state: &AppState,
+    ) -> anyhow::Result<HashMap<String, InlineOrRefData>> {
+        let inputs: ImportInputs = serde_json::from_value(inputs)?;
+        let data = base64::engine::general_purpose::STANDARD.decode(inputs.geopackage.trim())?;
+
+        let path = std::env::temp_dir().join(format!("{job_id}-import.gpkg"));
+        tokio::fs::write(&path, data).await?;
+
+        let collections = import_geopackage(state, &path, inputs.tables.as_deref()).await;
+
+        tokio::fs::remove_file(&path).await?;
+
+        Ok(HashMap::from([(
+            "collections".to_string(),
+            serde_json::from_value(Value::from(collections?))?,
+        )]))
+    }
+}
+
+/// Export a collection as GeoPackage
+///
+/// ```bash
+/// curl http://localhost:8484/processes/geopackage-export/execution \
+///         -H 'Content-Type: application/json' \
+///         -d '{"inputs": {"collection": "countries", "bbox": [5.9, 45.8, 10.5, 47.8]}}'
+/// ```
+#[derive(Clone)]
+pub struct GeoPackageExport;
+
+/// Inputs for the `geopackage-export` process
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct ExportInputs {
+    /// Identifier of the collection to export
+    collection: String,
+    /// Bounding box filter in `CRS84`
+    bbox: Option<[f64; 4]>,
+    /// `CQL2` text filter
+    filter: Option<String>,
+    /// Crs of the exported geometries, defaults to `CRS84`
+    crs: Option<String>,
+}
+
+/// Outputs for the `geopackage-export` process
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+struct ExportOutputs {
+    /// Link to the GeoPackage
+    geopackage: String,
+}
+
+#[axum::async_trait]
+impl Processor for GeoPackageExport {
+    fn id(&self) -> String {
+        "geopackage-export".to_string()
+    }
+
+    fn process(&self) -> Process {
+        let mut process = Process::new(
+            self.id(),
+            "0.1.0",
+            &serde_json::to_value(&schema_for!(ExportInputs).schema).unwrap(),
+            &serde_json::to_value(&schema_for!(ExportOutputs).schema).unwrap(),
+        );
+        process.summary.job_control_options = vec![JobControlOptions::AsyncExecute];
+        process
+    }
+
+    async fn execute(&self, execute: Execute, state: &AppState, url: &Url) -> Result<Response> {
+        let inputs: ExportInputs = parse_inputs(execute)?;
+
+        export_query(state, &inputs).await?;
+
+        let inputs = serde_json::to_value(inputs).map_err(anyhow::Error::from)?;
+        queue_job(self, inputs, state, url).await
+    }
+
+    async fn run_job(
+        &self,
+        job_id: &str,
+        inputs: Value,
+        state: &AppState,
+    ) -> anyhow::Result<HashMap<String, InlineOrRefData>> {
+        let inputs: ExportInputs = serde_json::from_value(inputs)?;
+        let (query, hidden) = export_query(state, &inputs).await.map_err(job_error)?;
+
+        let path = super::output_path(job_id, "geopackage")?;
+        tokio::fs::create_dir_all(path.parent().unwrap()).await?;
+
+        write_geopackage(state, &path, &inputs.collection, &query, &hidden).await?;
+
+        Ok(HashMap::from([(
+            "geopackage".to_string(),
+            output_link("geopackage", GEO_PACKAGE),
+        )]))
+    }
+}
+
+/// Query of an export with the properties hidden from it, jobs run without
+/// the user of the request
+async fn export_query(state: &AppState, inputs: &ExportInputs) -> Result<(Query, Vec<String>)> {
+    let Some(collection) = state
+        .drivers
+        .collections
+        .read_collection(&inputs.collection)
+        .await?
+    else {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Unknown collection `{}`", inputs.collection),
+        ));
+    };
+
+    let query = Query {
+        bbox: inputs.bbox.map(Bbox::Bbox2D),
+        filter: inputs.filter.to_owned(),
+        crs: match &inputs.crs {
+            Some(crs) => crs
+                .parse()
+                .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e))?,
+            None => Crs::default(),
+        },
+        access_filter: state
+            .drivers
+            .access
+            .access_filter(&inputs.collection, None)
+            .await?,
+        ..Default::default()
+    };
+    let hidden = collection.hidden_properties(None);
+    check_hidden_parameters(&query, &hidden)?;
+
+    Ok((query, hidden))
+}
+
+/// Error of a job for an error of a request, with the message of exceptions
+fn job_error(error: Error) -> anyhow::Error {
+    match error {
+        Error::Exception(_, message) => anyhow::Error::msg(message),
+        Error::Invalid(problems) => anyhow::Error::msg(problems.join(", ")),
+        error => error.into(),
+    }
+}
+
+/// Import the feature tables of a `GeoPackage` into collections of the same name
+pub async fn import_geopackage(
+    state: &AppState,
+    path: &PathBuf,
+    tables: Option<&[String]>,
+) -> anyhow::Result<Vec<String>> {
+    let mut conn =
+        SqliteConnection::connect_with(&SqliteConnectOptions::new().filename(path).read_only(true))
+            .await?;
+
+    let feature_tables = geopackage::feature_tables(&mut conn).await?;
+
+    let mut collections = Vec::new();
+
+    for table in feature_tables {
+        if tables.is_some_and(|t| !t.contains(&table.table_name)) {
+            continue;
+        }
+
+        let crs = table.crs();
+
+        let collection_id = table.table_name.to_owned();
+
+        if state
+            .drivers
+            .collections
+            .read_collection(&collection_id)
+            .await?
+            .is_none()
+        {
+            let collection = Collection {
+                id: collection_id.to_owned(),
+                crs: vec![Crs::default(), crs.clone()],
+                storage_crs: Some(crs.clone()),
+                ..Default::default()
+            };
+            state
+                .drivers
+                .collections
+                .create_collection(&collection)
+                .await?;
+        }
+
+        let pk = table.primary_key(&mut conn).await?;
+
+        let sql = format!(r#"SELECT * FROM "{}""#, table.table_name);
+        let mut rows = sqlx::query(&sql).fetch(&mut conn);
+
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        while let Some(row) = rows.next().await {
+            let row = row?;
+            if let Some(feature) =
+                geopackage::read_feature(&row, &collection_id, &table.column_name, pk.as_deref())?
+            {
+                batch.push(feature);
+            }
+
+            if batch.len() >= BATCH_SIZE {
+                state
+                    .drivers
+                    .features
+                    .create_features(&collection_id, &batch, &crs)
+                    .await?;
+                batch.clear();
+            }
+        }
+
+        if !batch.is_empty() {
+            state
+                .drivers
+                .features
+                .create_features(&collection_id, &batch, &crs)
+                .await?;
+        }
+
+        collections.push(collection_id);
+    }
+
+    conn.close().await?;
+
+    Ok(collections)
+}
+
+/// Export the items of a collection matching the query to a `GeoPackage`,
+/// with all their properties
+pub async fn export_geopackage(
+    state: &AppState,
+    path: &Path,
+    collection: &str,
+    query: &Query,
+) -> anyhow::Result<()> {
+    write_geopackage(state, path, collection, query, &[]).await
+}
+
+/// Export the items of a collection matching the query to a `GeoPackage`,
+/// leaving out the hidden properties
+pub(super) async fn write_geopackage(
+    state: &AppState,
+    path: &Path,
+    collection: &str,
+    query: &Query,
+    hidden: &[String],
+) -> anyhow::Result<()> {
+    let _ = tokio::fs::remove_file(path).await;
+
+    let mut conn = SqliteConnection::connect_with(
+        &SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true),
+    )
+    .await?;
+
+    let srs_id = query.crs.as_srid();
+
+    let statements = [
+        format!("PRAGMA application_id = {APPLICATION_ID}"),
+        format!("PRAGMA user_version = {USER_VERSION}"),
+        r#"
+        CREATE TABLE gpkg_spatial_ref_sys (
+            srs_name TEXT NOT NULL,
+            srs_id INTEGER PRIMARY KEY,
+            organization TEXT NOT NULL,
+            organization_coordsys_id INTEGER NOT NULL,
+            definition TEXT NOT NULL,
+            description TEXT
+        )
+        "#
+        .to_string(),
+        r#"
+        INSERT INTO gpkg_spatial_ref_sys VALUES
+            ('Undefined cartesian SRS', -1, 'NONE', -1, 'undefined', NULL),
+            ('Undefined geographic SRS', 0, 'NONE', 0, 'undefined', NULL),
+            ('WGS 84 geodetic', 4326, 'EPSG', 4326, 'GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563,AUTHORITY["EPSG","7030"]],AUTHORITY["EPSG","6326"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AXIS["Latitude",NORTH],AXIS["Longitude",EAST],AUTHORITY["EPSG","4326"]]', NULL)
+        "#
+        .to_string(),
+        r#"
+        CREATE TABLE gpkg_contents (
+            table_name TEXT NOT NULL PRIMARY KEY,
+            data_type TEXT NOT NULL,
+            identifier TEXT UNIQUE,
+            description TEXT DEFAULT '',
+            last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+            min_x DOUBLE, min_y DOUBLE, max_x DOUBLE, max_y DOUBLE,
+            srs_id INTEGER REFERENCES gpkg_spatial_ref_sys(srs_id)
+        )
+        "#
+        .to_string(),
+        r#"
+        CREATE TABLE gpkg_extensions (
+            table_name TEXT,
+            column_name TEXT,
+            extension_name TEXT NOT NULL,
+            definition TEXT NOT NULL,
+            scope TEXT NOT NULL,
+            CONSTRAINT ge_tce UNIQUE (table_name, column_name, extension_name)
+        )
+        "#
+        .to_string(),
+        r#"
+        INSERT INTO gpkg_extensions VALUES
+            ('gpkg_data_columns', NULL, 'gpkg_schema', 'http://www.geopackage.org/spec/#extension_schema', 'read-write')
+        "#
+        .to_string(),
+        r#"
+        CREATE TABLE gpkg_data_columns (
+            table_name TEXT NOT NULL,
+            column_name TEXT NOT NULL,
+            name TEXT,
+            title TEXT,
+            description TEXT,
+            mime_type TEXT,
+            constraint_name TEXT,
+            CONSTRAINT pk_gdc PRIMARY KEY (table_name, column_name)
+        )
+        "#
+        .to_string(),
+        r#"
+        CREATE TABLE gpkg_geometry_columns (
+            table_name TEXT NOT NULL REFERENCES gpkg_contents(table_name),
+            column_name TEXT NOT NULL,
+            geometry_type_name TEXT NOT NULL,
+            srs_id INTEGER NOT NULL REFERENCES gpkg_spatial_ref_sys (srs_id),
+            z TINYINT NOT NULL,
+            m TINYINT NOT NULL,
+            PRIMARY KEY (table_name, column_name)
+        )
+        "#
+        .to_string(),
+        format!(
+            r#"
+            CREATE TABLE "{collection}" (
+                fid INTEGER PRIMARY KEY AUTOINCREMENT,
+                id TEXT,
+                geom GEOMETRY
+            )
+            "#
+        ),
+    ];
+
+    for statement in statements {
+        sqlx::query(&statement).execute(&mut conn).await?;
+    }
+
+    if srs_id != 4326 {
+        sqlx::query(
+            "INSERT INTO gpkg_spatial_ref_sys VALUES ($1, $2, 'EPSG', $2, 'undefined', NULL)",
+        )
+        .bind(query.crs.as_known_crs())
+        .bind(srs_id)
+        .execute(&mut conn)
+        .await?;
+    }
+
+    sqlx::query("INSERT INTO gpkg_contents (table_name, data_type, identifier, srs_id) VALUES ($1, 'features', $1, $2)")
+        .bind(collection)
+        .bind(srs_id)
+        .execute(&mut conn)
+        .await?;
+
+    sqlx::query("INSERT INTO gpkg_geometry_columns VALUES ($1, 'geom', 'GEOMETRY', $2, 2, 0)")
+        .bind(collection)
+        .bind(srs_id)
+        .execute(&mut conn)
+        .await?;
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut features = state.drivers.features.stream_items(collection, query);
+
+    let mut tx = conn.begin().await?;
+    while let Some(feature) = features.next().await {
+        let feature = feature?;
+        let mut properties = feature.properties.unwrap_or_default();
+        properties.retain(|key, _| !hidden.contains(key));
+
+        // add columns as they appear, typed by their first value
+        for (key, value) in properties.iter() {
+            if !columns.contains(key) {
+                let r#type = match value {
+                    Value::Bool(_) => "BOOLEAN",
+                    Value::Number(n) if n.is_f64() => "DOUBLE",
+                    Value::Number(_) => "INTEGER",
+                    _ => "TEXT",
+                };
+                sqlx::query(&format!(
+                    r#"ALTER TABLE "{collection}" ADD COLUMN "{}" {}"#,
+                    key.replace('"', "\"\""),
+                    r#type
+                ))
+                .execute(&mut *tx)
+                .await?;
+                // objects and arrays are stored as JSON text
+                if value.is_object() || value.is_array() {
+                    sqlx::query("INSERT INTO gpkg_data_columns (table_name, column_name, name, mime_type) VALUES ($1, $2, $2, $3)")
+                        .bind(collection)
+                        .bind(key)
+                        .bind(JSON)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                columns.push(key.to_owned());
+            }
+        }
+
+        let names: Vec<String> = properties
+            .keys()
+            .map(|k| format!(r#", "{}""#, k.replace('"', "\"\"")))
+            .collect();
+        let placeholders: Vec<String> = (0..properties.len())
+            .map(|i| format!(", ${}", i + 3))
+            .collect();
+
+        let sql = format!(
+            r#"INSERT INTO "{collection}" (id, geom{}) VALUES ($1, $2{})"#,
+            names.concat(),
+            placeholders.concat()
+        );
+
+        let mut insert = sqlx::query(&sql)
+            .bind(feature.id)
+            .bind(wkb::to_gpkg(&feature.geometry.value, srs_id));
+
+        for value in properties.into_values() {
+            insert = match value {
+                Value::Null => insert.bind(None::<String>),
+                Value::Bool(b) => insert.bind(b),
+                Value::Number(n) => match n.as_i64() {
+                    Some(i) => insert.bind(i),
+                    None => insert.bind(n.as_f64()),
+                },
+                Value::String(s) => insert.bind(s),
+                v => insert.bind(v.to_string()),
+            };
+        }
+
+        insert.execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+
+    conn.close().await?;
+
+    Ok(())
+}
@@ -0,0 +1,617 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use axum::{
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use schemars::{schema_for, JsonSchema};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use url::Url;
+
+use ogcapi_types::{
+    common::{media_type::GEO_JSON, Bbox, Datetime},
+    features::{FeatureCollection, Query},
+    processes::{Execute, Input, Process},
+};
+
+use crate::{AppState, Error, Result};
+
+use super::Processor;
+
+/// Processes of openEO evaluated by the translation layer, other process ids
+/// are mapped onto the registered processors
+pub const SUPPORTED: [&str; 5] = [
+    "load_collection",
+    "filter_bbox",
+    "filter_temporal",
+    "reduce_dimension",
+    "save_result",
+];
+
+/// Reducers of `reduce_dimension`
+const REDUCERS: [&str; 6] = ["count", "max", "mean", "median", "min", "sum"];
+
+/// Evaluate openEO process graphs
+///
+/// Accepts a subset of openEO, `load_collection` loads features of a
+/// collection, which `filter_bbox` and `filter_temporal` narrow down and
+/// `reduce_dimension` aggregates, either over all features (`geometries`) or
+/// per feature over its properties (`bands`). A result node with the id of a
+/// registered process is executed by that processor, with its arguments as
+/// inputs.
+///
+/// ```bash
+/// curl http://localhost:8484/processes/openeo/execution \
+///         -H 'Content-Type: application/json' \
+///         -d '{"process": {"process_graph": {
+///             "load": {
+///                 "process_id": "load_collection",
+///                 "arguments": {"id": "countries", "bands": ["pop_est"]}
+///             },
+///             "clip": {
+///                 "process_id": "filter_bbox",
+///                 "arguments": {
+///                     "data": {"from_node": "load"},
+///                     "extent": {"west": 5.9, "south": 45.8, "east": 10.5, "north": 47.8}
+///                 }
+///             },
+///             "total": {
+///                 "process_id": "reduce_dimension",
+///                 "arguments": {
+///                     "data": {"from_node": "clip"},
+///                     "dimension": "geometries",
+///                     "reducer": {"process_graph": {"sum": {
+///                         "process_id": "sum",
+///                         "arguments": {"data": {"from_parameter": "data"}},
+///                         "result": true
+///                     }}}
+///                 },
+///                 "result": true
+///             }
+///         }}}'
+/// ```
+#[derive(Clone)]
+pub struct OpenEo;
+
+/// Inputs for the `openeo` process
+#[derive(Deserialize, Debug, JsonSchema)]
+struct OpenEoInputs {
+    /// Process graph, as `value` of a qualified input
+    process_graph: Qualified,
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+struct Qualified {
+    value: ProcessGraph,
+}
+
+/// Outputs for the `openeo` process
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+struct OpenEoOutputs(Value);
+
+/// Nodes of an openEO process graph by id
+pub type ProcessGraph = BTreeMap<String, ProcessNode>;
+
+/// Node of an openEO process graph
+#[derive(Deserialize, Debug, Clone, JsonSchema)]
+pub struct ProcessNode {
+    pub process_id: String,
+    #[serde(default)]
+    pub arguments: Map<String, Value>,
+    #[serde(default)]
+    pub result: bool,
+}
+
+/// Body of synchronous openEO requests
+#[derive(Deserialize, Debug)]
+struct OpenEoRequest {
+    process: OpenEoProcess,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenEoProcess {
+    process_graph: Value,
+}
+
+/// Wrap an openEO request, `{"process": {"process_graph": ...}}`, into the
+/// inputs of the `openeo` process, other bodies are returned unchanged
+pub(crate) fn wrap_request(body: Value) -> Value {
+    match serde_json::from_value::<OpenEoRequest>(body.clone()) {
+        Ok(request) => serde_json::json!({
+            "inputs": {
+                "process_graph": { "value": request.process.process_graph }
+            }
+        }),
+        Err(_) => body,
+    }
+}
+
+#[axum::async_trait]
+impl Processor for OpenEo {
+    fn id(&self) -> String {
+        "openeo".to_string()
+    }
+
+    fn process(&self) -> Process {
+        let mut process = Process::new(
+            self.id(),
+            "0.1.0",
+            &serde_json::to_value(&schema_for!(OpenEoInputs).schema).unwrap(),
+            &serde_json::to_value(&schema_for!(OpenEoOutputs).schema).unwrap(),
+        );
+        process.summary.description_type.title = Some("openEO process graphs".to_string());
+        process.summary.description_type.description = Some(format!(
+            "Evaluates openEO process graphs using {} and the registered processes",
+            SUPPORTED.join(", ")
+        ));
+        process
+    }
+
+    async fn execute(&self, execute: Execute, state: &AppState, url: &Url) -> Result<Response> {
+        let value = serde_json::to_value(execute.inputs).map_err(anyhow::Error::from)?;
+        let inputs: OpenEoInputs = serde_json::from_value(value).map_err(invalid)?;
+        let graph = inputs.process_graph.value;
+
+        let order = evaluation_order(&graph)?;
+        let result = order.last().cloned().unwrap_or_default();
+
+        // the result node may be a registered process
+        let node = &graph[&result];
+        if !SUPPORTED.contains(&node.process_id.as_str()) {
+            let processor = state
+                .processors
+                .read()
+                .unwrap()
+                .get(&node.process_id)
+                .filter(|p| p.id() != self.id())
+                .cloned()
+                .ok_or_else(|| invalid(format!("Unsupported process `{}`", node.process_id)))?;
+
+            let mut data = HashMap::new();
+            for id in &order[..order.len() - 1] {
+                let value = evaluate(&graph[id], &data, state).await?;
+                data.insert(id.to_owned(), value);
+            }
+
+            let execute = Execute {
+                inputs: inputs_of(node, &data, state).await?,
+                outputs: HashMap::new(),
+                response: Default::default(),
+                subscriber: None,
+            };
+            return processor.execute(execute, state, url).await;
+        }
+
+        let mut data = HashMap::new();
+        for id in &order {
+            let value = evaluate(&graph[id], &data, state).await?;
+            data.insert(id.to_owned(), value);
+        }
+
+        match data.remove(&result) {
+            Some(Data::Value(value)) => Ok(Json(value).into_response()),
+            Some(data) => {
+                let fc = data.features(state).await?;
+                Ok(([(CONTENT_TYPE, GEO_JSON)], Json(fc)).into_response())
+            }
+            None => Err(invalid("Empty process graph")),
+        }
+    }
+}
+
+/// Intermediate result of a node
+#[derive(Debug, Clone)]
+enum Data {
+    /// Features of a collection, loaded once reduced or returned
+    Cube(Cube),
+    Features(FeatureCollection),
+    Value(Value),
+}
+
+#[derive(Debug, Clone)]
+struct Cube {
+    collection: String,
+    bbox: Option<[f64; 4]>,
+    datetime: Option<Datetime>,
+    bands: Option<Vec<String>>,
+    /// Disjoint filters, no feature matches
+    empty: bool,
+}
+
+impl Data {
+    /// Features of the data, at most the page limit of the guardrails
+    async fn features(self, state: &AppState) -> Result<FeatureCollection> {
+        let cube = match self {
+            Data::Cube(cube) => cube,
+            Data::Features(fc) => return Ok(fc),
+            Data::Value(_) => return Err(invalid("Expected features, got a value")),
+        };
+
+        if cube.empty {
+            return Ok(FeatureCollection::new(Vec::new()));
+        }
+
+        let max = state.guardrails.max_limit;
+        let query = Query {
+            limit: Some(max + 1),
+            bbox: cube.bbox.map(Bbox::Bbox2D),
+            datetime: cube.datetime,
+            ..Default::default()
+        };
+
+        let mut fc = state
+            .services
+            .features
+            .list_items(&cube.collection, &query)
+            .await?;
+
+        if fc.features.len() > max {
+            return Err(invalid(format!(
+                "More than {max} features of `{}` match, narrow the extents",
+                cube.collection
+            )));
+        }
+
+        if let Some(bands) = &cube.bands {
+            for feature in fc.features.iter_mut() {
+                if let Some(properties) = feature.properties.as_mut() {
+                    properties.retain(|key, _| bands.contains(key));
+                }
+            }
+        }
+        fc.links.clear();
+
+        Ok(fc)
+    }
+}
+
+/// Node ids in evaluation order, the result node last
+fn evaluation_order(graph: &ProcessGraph) -> Result<Vec<String>> {
+    let results: Vec<&String> = graph
+        .iter()
+        .filter(|(_, node)| node.result)
+        .map(|(id, _)| id)
+        .collect();
+
+    let [result] = results[..] else {
+        return Err(invalid("Process graph requires exactly one result node"));
+    };
+
+    fn visit(
+        id: &str,
+        graph: &ProcessGraph,
+        visiting: &mut HashSet<String>,
+        order: &mut Vec<String>,
+    ) -> Result<()> {
+        if order.iter().any(|o| o == id) {
+            return Ok(());
+        }
+        if !visiting.insert(id.to_owned()) {
+            return Err(invalid(format!("Process graph contains a cycle at `{id}`")));
+        }
+
+        let node = graph
+            .get(id)
+            .ok_or_else(|| invalid(format!("Unknown node `{id}`")))?;
+        for value in node.arguments.values() {
+            if let Some(dependency) = from_node(value) {
+                visit(dependency, graph, visiting, order)?;
+            }
+        }
+
+        visiting.remove(id);
+        order.push(id.to_owned());
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    visit(result, graph, &mut HashSet::new(), &mut order)?;
+
+    Ok(order)
+}
+
+fn from_node(value: &Value) -> Option<&str> {
+    value.get("from_node").and_then(Value::as_str)
+}
+
+async fn evaluate(
+    node: &ProcessNode,
+    data: &HashMap<String, Data>,
+    state: &AppState,
+) -> Result<Data> {
+    let argument = |name: &str| -> Result<Data> {
+        let value = node
+            .arguments
+            .get(name)
+            .ok_or_else(|| invalid(format!("`{}` requires `{name}`", node.process_id)))?;
+        match from_node(value) {
+            Some(id) => Ok(data[id].to_owned()),
+            None => Ok(Data::Value(value.to_owned())),
+        }
+    };
+
+    let cube = || -> Result<Cube> {
+        match argument("data")? {
+            Data::Cube(cube) => Ok(cube),
+            _ => Err(invalid(format!(
+                "`{}` requires data loaded by `load_collection`",
+                node.process_id
+            ))),
+        }
+    };
+
+    match node.process_id.as_str() {
+        "load_collection" => {
+            let Data::Value(Value::String(id)) = argument("id")? else {
+                return Err(invalid("`load_collection` requires a collection `id`"));
+            };
+            if state
+                .services
+                .collections
+                .read_collection(&id)
+                .await?
+                .is_none()
+            {
+                return Err(invalid(format!("Unknown collection `{id}`")));
+            }
+
+            let mut cube = Cube {
+                collection: id,
+                bbox: None,
+                datetime: None,
+                bands: None,
+                empty: false,
+            };
+            if let Some(extent) = node
+                .arguments
+                .get("spatial_extent")
+                .filter(|v| !v.is_null())
+            {
+                cube.bbox = Some(spatial_extent(extent)?);
+            }
+            if let Some(extent) = node
+                .arguments
+                .get("temporal_extent")
+                .filter(|v| !v.is_null())
+            {
+                cube.datetime = Some(temporal_extent(extent)?);
+            }
+            if let Some(bands) = node.arguments.get("bands").filter(|v| !v.is_null()) {
+                cube.bands = Some(serde_json::from_value(bands.to_owned()).map_err(invalid)?);
+            }
+            Ok(Data::Cube(cube))
+        }
+        "filter_bbox" => {
+            let mut cube = cube()?;
+            let Data::Value(extent) = argument("extent")? else {
+                return Err(invalid("`filter_bbox` requires an `extent`"));
+            };
+            let extent = spatial_extent(&extent)?;
+            match cube.bbox {
+                Some(bbox) => match intersection(&bbox, &extent) {
+                    Some(bbox) => cube.bbox = Some(bbox),
+                    None => cube.empty = true,
+                },
+                None => cube.bbox = Some(extent),
+            }
+            Ok(Data::Cube(cube))
+        }
+        "filter_temporal" => {
+            let mut cube = cube()?;
+            let Data::Value(extent) = argument("extent")? else {
+                return Err(invalid("`filter_temporal` requires an `extent`"));
+            };
+            if cube.datetime.is_some() {
+                return Err(invalid("Data is already filtered by a temporal extent"));
+            }
+            cube.datetime = Some(temporal_extent(&extent)?);
+            Ok(Data::Cube(cube))
+        }
+        "reduce_dimension" => {
+            let fc = argument("data")?.features(state).await?;
+            let Data::Value(Value::String(dimension)) = argument("dimension")? else {
+                return Err(invalid("`reduce_dimension` requires a `dimension`"));
+            };
+            let Data::Value(reducer) = argument("reducer")? else {
+                return Err(invalid("`reduce_dimension` requires a `reducer`"));
+            };
+            let reducer = reducer_of(&reducer)?;
+
+            reduce(fc, &dimension, &reducer)
+        }
+        "save_result" => {
+            if let Some(Value::String(format)) = node.arguments.get("format") {
+                if !["geojson", "json"].contains(&format.to_lowercase().as_str()) {
+                    return Err(invalid(format!(
+                        "Unsupported format `{format}`, use `GeoJSON` or `JSON`"
+                    )));
+                }
+            }
+            argument("data")
+        }
+        process_id => Err(invalid(format!(
+            "Process `{process_id}` is only supported as result node"
+        ))),
+    }
+}
+
+/// Arguments of a node calling a registered process, as its inputs
+async fn inputs_of(
+    node: &ProcessNode,
+    data: &HashMap<String, Data>,
+    state: &AppState,
+) -> Result<HashMap<String, Input>> {
+    let mut inputs = HashMap::new();
+
+    for (name, value) in &node.arguments {
+        let value = match from_node(value) {
+            Some(id) => match data[id].to_owned() {
+                Data::Value(value) => value,
+                data => serde_json::to_value(data.features(state).await?)
+                    .map_err(anyhow::Error::from)?,
+            },
+            None => value.to_owned(),
+        };
+
+        // objects are only valid as qualified values
+        let value = if value.is_object() {
+            serde_json::json!({ "value": value })
+        } else {
+            value
+        };
+
+        let input = serde_json::from_value(value)
+            .map_err(|e| invalid(format!("Invalid argument `{name}`: {e}")))?;
+        inputs.insert(name.to_owned(), input);
+    }
+
+    Ok(inputs)
+}
+
+/// Bounding box of an openEO extent in WGS 84
+fn spatial_extent(extent: &Value) -> Result<[f64; 4]> {
+    #[derive(Deserialize)]
+    struct Extent {
+        west: f64,
+        south: f64,
+        east: f64,
+        north: f64,
+        crs: Option<Value>,
+    }
+
+    let extent: Extent = serde_json::from_value(extent.to_owned()).map_err(invalid)?;
+
+    if extent
+        .crs
+        .as_ref()
+        .is_some_and(|crs| crs != 4326 && crs != "EPSG:4326")
+    {
+        return Err(invalid("Only extents in EPSG:4326 are supported"));
+    }
+
+    Ok([extent.west, extent.south, extent.east, extent.north])
+}
+
+/// Interval of an openEO temporal extent, `[start, end]` with `null` for
+/// open ends
+fn temporal_extent(extent: &Value) -> Result<Datetime> {
+    let interval: [Option<String>; 2] =
+        serde_json::from_value(extent.to_owned()).map_err(invalid)?;
+
+    let instant = |d: &Option<String>| match d {
+        // dates are the start of the day
+        Some(d) if d.len() == 10 => format!("{d}T00:00:00Z"),
+        Some(d) => d.to_owned(),
+        None => "..".to_string(),
+    };
+
+    format!("{}/{}", instant(&interval[0]), instant(&interval[1]))
+        .parse()
+        .map_err(invalid)
+}
+
+fn intersection(a: &[f64; 4], b: &[f64; 4]) -> Option<[f64; 4]> {
+    let bbox = [
+        a[0].max(b[0]),
+        a[1].max(b[1]),
+        a[2].min(b[2]),
+        a[3].min(b[3]),
+    ];
+    (bbox[0] <= bbox[2] && bbox[1] <= bbox[3]).then_some(bbox)
+}
+
+/// Process id of a reducer consisting of a single node
+fn reducer_of(reducer: &Value) -> Result<String> {
+    let graph: ProcessGraph = reducer
+        .get("process_graph")
+        .map(|graph| serde_json::from_value(graph.to_owned()))
+        .transpose()
+        .map_err(invalid)?
+        .unwrap_or_default();
+
+    match graph.values().collect::<Vec<_>>()[..] {
+        [node] if REDUCERS.contains(&node.process_id.as_str()) => Ok(node.process_id.to_owned()),
+        _ => Err(invalid(format!(
+            "Reducers must consist of one of {}",
+            REDUCERS.join(", ")
+        ))),
+    }
+}
+
+/// Reduce over all features (`geometries`) or the properties of each feature
+/// (`bands`), only numbers are taken into account
+fn reduce(mut fc: FeatureCollection, dimension: &str, reducer: &str) -> Result<Data> {
+    match dimension {
+        "geometries" => {
+            let mut values: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+            for properties in fc.features.iter().filter_map(|f| f.properties.as_ref()) {
+                for (key, value) in properties {
+                    if let Some(value) = value.as_f64() {
+                        values.entry(key.to_owned()).or_default().push(value);
+                    }
+                }
+            }
+
+            let reduced: Map<String, Value> = values
+                .into_iter()
+                .map(|(key, values)| (key, apply(reducer, values)))
+                .collect();
+
+            Ok(Data::Value(Value::Object(reduced)))
+        }
+        "bands" | "properties" => {
+            for feature in fc.features.iter_mut() {
+                let values = feature
+                    .properties
+                    .iter()
+                    .flat_map(|p| p.values())
+                    .filter_map(Value::as_f64)
+                    .collect();
+                let mut properties = Map::new();
+                properties.insert("value".to_string(), apply(reducer, values));
+                feature.properties = Some(properties);
+            }
+
+            Ok(Data::Features(fc))
+        }
+        dimension => Err(invalid(format!(
+            "Unsupported dimension `{dimension}`, use `geometries` or `bands`"
+        ))),
+    }
+}
+
+fn apply(reducer: &str, mut values: Vec<f64>) -> Value {
+    if reducer == "count" {
+        return Value::from(values.len());
+    }
+    if values.is_empty() {
+        return Value::Null;
+    }
+
+    let n = values.len() as f64;
+    let value = match reducer {
+        "max" => values.iter().cloned().fold(f64::MIN, f64::max),
+        "min" => values.iter().cloned().fold(f64::MAX, f64::min),
+        "sum" => values.iter().sum(),
+        "mean" => values.iter().sum::<f64>() / n,
+        "median" => {
+            values.sort_by(f64::total_cmp);
+            let mid = values.len() / 2;
+            if values.len().is_multiple_of(2) {
+                (values[mid - 1] + values[mid]) / 2.0
+            } else {
+                values[mid]
+            }
+        }
+        _ => unreachable!("unknown reducer `{reducer}`"),
+    };
+
+    Value::from(value)
+}
+
+fn invalid(e: impl ToString) -> Error {
+    Error::Exception(StatusCode::BAD_REQUEST, e.to_string())
+}
@@ -0,0 +1,877 @@
+use std::collections::HashMap;
+
+use axum::{http::StatusCode, response::Response};
+use futures::StreamExt;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tiny_skia::{FillRule, LineCap, LineJoin, Mask, Paint, PathBuilder, Pixmap, Stroke, Transform};
+use url::Url;
+
+use ogcapi_drivers::transform::transformer;
+use ogcapi_types::{
+    common::{
+        media_type::{PDF, PNG},
+        Bbox, Crs,
+    },
+    features::Query,
+    processes::{Execute, InlineOrRefData, JobControlOptions, Process},
+};
+
+use crate::{AppState, Error, Result};
+
+use super::{output_link, parse_inputs, queue_job, Processor};
+
+/// Size of sheets without requested paper size, A4 landscape in millimeters
+const PAPER: [f64; 2] = [297.0, 210.0];
+
+/// Margin around the map in millimeters
+const MARGIN: f64 = 10.0;
+
+/// Space reserved for the title above the map in millimeters
+const TITLE_HEIGHT: f64 = 12.0;
+
+/// Resolution of PNG sheets without requested resolution
+const DPI: u32 = 300;
+
+/// Maximum number of pixels of PNG sheets
+const MAX_PIXELS: f64 = (1u64 << 26) as f64;
+
+/// Maximum number of features drawn on a sheet
+const MAX_FEATURES: usize = 1_000_000;
+
+/// Colors of the collections without style, in drawing order
+const PALETTE: [[u8; 3]; 6] = [
+    [31, 119, 180],
+    [255, 127, 14],
+    [44, 160, 44],
+    [214, 39, 40],
+    [148, 103, 189],
+    [140, 86, 75],
+];
+
+/// Render the features of collections to a print-ready map sheet
+///
+/// ```bash
+/// curl http://localhost:8484/processes/map-print/execution \
+///         -H 'Content-Type: application/json' \
+///         -d '{"inputs": {"collections": ["countries"], "bbox": [5.9, 45.8, 10.5, 47.8], "scale": 2000000, "overlays": {"title": "Switzerland", "scaleBar": true}}}'
+/// ```
+#[derive(Clone)]
+pub struct MapPrint;
+
+/// Inputs for the `map-print` process
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct PrintInputs {
+    /// Identifiers of the collections, drawn from bottom to top
+    collections: Vec<String>,
+    /// Area of the map in `CRS84`, centered on the sheet
+    bbox: [f64; 4],
+    /// Crs of the map, defaults to `EPSG:3857`
+    crs: Option<String>,
+    /// Scale denominator, e.g. `25000` for 1:25000, the bbox is fit to the
+    /// sheet if omitted
+    scale: Option<f64>,
+    /// Width and height of the sheet in millimeters, defaults to A4 landscape
+    paper: Option<[f64; 2]>,
+    /// Resolution of PNG sheets in dots per inch, defaults to 300
+    dpi: Option<u32>,
+    /// Format of the sheet, `pdf` (default) or `png`
+    #[serde(default)]
+    format: PrintFormat,
+    /// Styles of the collections by identifier
+    #[serde(default)]
+    style: HashMap<String, LayerStyle>,
+    /// Elements drawn on top of the map
+    #[serde(default)]
+    overlays: Overlays,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum PrintFormat {
+    #[default]
+    Pdf,
+    Png,
+}
+
+/// Style of the features of a collection
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct LayerStyle {
+    /// Fill color of polygons and points as `#rrggbb` or `#rrggbbaa`
+    fill: Option<String>,
+    /// Color of lines and outlines as `#rrggbb` or `#rrggbbaa`
+    stroke: Option<String>,
+    /// Width of lines and outlines in millimeters
+    stroke_width: Option<f64>,
+    /// Radius of points in millimeters
+    point_radius: Option<f64>,
+}
+
+/// Elements drawn on top of the map
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct Overlays {
+    /// Title above the map, only rendered in PDFs
+    title: Option<String>,
+    /// Scale bar in the lower left corner of projected maps, labeled in PDFs
+    #[serde(default)]
+    scale_bar: bool,
+    /// North arrow in the upper right corner
+    #[serde(default)]
+    north_arrow: bool,
+}
+
+/// Outputs for the `map-print` process
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+struct PrintOutputs {
+    /// Link to the map sheet
+    map: String,
+}
+
+#[axum::async_trait]
+impl Processor for MapPrint {
+    fn id(&self) -> String {
+        "map-print".to_string()
+    }
+
+    fn process(&self) -> Process {
+        let mut process = Process::new(
+            self.id(),
+            "0.1.0",
+            &serde_json::to_value(&schema_for!(PrintInputs).schema).unwrap(),
+            &serde_json::to_value(&schema_for!(PrintOutputs).schema).unwrap(),
+        );
+        process.summary.job_control_options = vec![JobControlOptions::AsyncExecute];
+        process
+    }
+
+    async fn execute(&self, execute: Execute, state: &AppState, url: &Url) -> Result<Response> {
+        let inputs: PrintInputs = parse_inputs(execute)?;
+
+        for id in &inputs.collections {
+            if state
+                .drivers
+                .collections
+                .read_collection(id)
+                .await?
+                .is_none()
+            {
+                return Err(bad_request(format!("Unknown collection `{id}`")));
+            }
+        }
+        plan(&inputs).map_err(bad_request)?;
+
+        let inputs = serde_json::to_value(inputs).map_err(anyhow::Error::from)?;
+        queue_job(self, inputs, state, url).await
+    }
+
+    async fn run_job(
+        &self,
+        job_id: &str,
+        inputs: Value,
+        state: &AppState,
+    ) -> anyhow::Result<HashMap<String, InlineOrRefData>> {
+        let inputs: PrintInputs = serde_json::from_value(inputs)?;
+        let plan = plan(&inputs).map_err(anyhow::Error::msg)?;
+        let sheet = &plan.sheet;
+
+        let mut canvas: Box<dyn Canvas + Send> = match inputs.format {
+            PrintFormat::Pdf => Box::new(PdfCanvas::new(plan.paper)),
+            PrintFormat::Png => Box::new(PngCanvas::new(plan.paper, plan.dpi)?),
+        };
+
+        canvas.clip(Some(sheet.frame));
+        let mut count = 0;
+        for (collection, symbol) in &plan.layers {
+            // features accessible without api key
+            let access_filter = state.drivers.access.access_filter(collection, None).await?;
+            let query = Query {
+                bbox: Some(Bbox::Bbox2D(sheet.extent)),
+                bbox_crs: plan.crs.clone(),
+                crs: plan.crs.clone(),
+                access_filter,
+                ..Default::default()
+            };
+
+            let mut features = state.drivers.features.stream_items(collection, &query);
+            while let Some(feature) = features.next().await {
+                count += 1;
+                if count > MAX_FEATURES {
+                    anyhow::bail!("Sheets are limited to {MAX_FEATURES} features");
+                }
+                draw(canvas.as_mut(), sheet, &feature?.geometry.value, symbol);
+            }
+        }
+        canvas.clip(None);
+
+        overlays(
+            canvas.as_mut(),
+            sheet,
+            &inputs.overlays,
+            (!plan.geographic).then_some(plan.units_per_meter),
+        );
+
+        let path = super::output_path(job_id, "map")?;
+        tokio::fs::create_dir_all(path.parent().unwrap()).await?;
+        tokio::fs::write(&path, canvas.finish()?).await?;
+
+        let media_type = match inputs.format {
+            PrintFormat::Pdf => PDF,
+            PrintFormat::Png => PNG,
+        };
+
+        Ok(HashMap::from([(
+            "map".to_string(),
+            output_link("map", media_type),
+        )]))
+    }
+}
+
+/// Layers and layout of a sheet, derived from the inputs
+struct Plan {
+    layers: Vec<(String, Symbol)>,
+    crs: Crs,
+    geographic: bool,
+    paper: [f64; 2],
+    dpi: u32,
+    sheet: Sheet,
+    units_per_meter: f64,
+}
+
+/// Check the inputs and lay out the sheet
+fn plan(inputs: &PrintInputs) -> std::result::Result<Plan, String> {
+    let mut layers = Vec::new();
+    for (i, id) in inputs.collections.iter().enumerate() {
+        let symbol = Symbol::new(inputs.style.get(id), PALETTE[i % PALETTE.len()])?;
+        layers.push((id.to_owned(), symbol));
+    }
+    if layers.is_empty() {
+        return Err("At least one collection is required".to_string());
+    }
+
+    let crs = match &inputs.crs {
+        Some(crs) => match crs.parse::<Crs>() {
+            Ok(crs) if crs.is_valid() && transformer().supports(&crs) => crs,
+            _ => return Err(format!("Unsupported crs `{crs}`")),
+        },
+        None => Crs::from_epsg(3857),
+    };
+    let geographic = crs.as_srid() == 4326;
+
+    let [minx, miny, maxx, maxy] = inputs.bbox;
+    if !(minx < maxx && miny < maxy) {
+        return Err(format!("Invalid bbox `{:?}`", inputs.bbox));
+    }
+    let bbox = match transformer().transform_bbox(&Crs::default(), &crs, &Bbox::Bbox2D(inputs.bbox))
+    {
+        Ok(Bbox::Bbox2D(bbox)) => bbox,
+        Ok(Bbox::Bbox3D(bbox)) => [bbox[0], bbox[1], bbox[3], bbox[4]],
+        Err(e) => return Err(format!("Invalid bbox: {e}")),
+    };
+
+    if inputs
+        .scale
+        .is_some_and(|scale| geographic || !(scale.is_finite() && scale > 0.0))
+    {
+        return Err("A scale requires a positive number and a projected crs".to_string());
+    }
+
+    let paper = inputs.paper.unwrap_or(PAPER);
+    if paper.iter().any(|side| !(50.0..=2000.0).contains(side)) {
+        return Err("Paper sides have to be between 50 and 2000 millimeters".to_string());
+    }
+
+    let dpi = inputs.dpi.unwrap_or(DPI);
+    let pixels = paper[0] * paper[1] * (f64::from(dpi) / 25.4).powi(2);
+    if !(72..=1200).contains(&dpi) || (inputs.format == PrintFormat::Png && pixels > MAX_PIXELS) {
+        return Err(format!(
+            "Resolution has to be between 72 and 1200 dpi, with at most {MAX_PIXELS} pixels"
+        ));
+    }
+
+    // web mercator stretches distances by the inverse cosine of the latitude
+    let units_per_meter = if crs == Crs::from_epsg(3857) {
+        1.0 / ((miny + maxy) / 2.0).to_radians().cos()
+    } else {
+        1.0
+    };
+    let sheet = Sheet::new(
+        paper,
+        inputs.overlays.title.is_some() && inputs.format == PrintFormat::Pdf,
+        bbox,
+        inputs.scale.map(|scale| scale * units_per_meter),
+    );
+
+    Ok(Plan {
+        layers,
+        crs,
+        geographic,
+        paper,
+        dpi,
+        sheet,
+        units_per_meter,
+    })
+}
+
+fn bad_request(message: String) -> Error {
+    Error::Exception(StatusCode::BAD_REQUEST, message)
+}
+
+/// Layout of a sheet, in millimeters from the top left corner of the paper
+struct Sheet {
+    /// Left, top, width and height of the map
+    frame: [f64; 4],
+    /// Bounds of the map in the crs of the map
+    extent: [f64; 4],
+}
+
+impl Sheet {
+    /// Sheet centered on a bbox, at a scale in units of the crs or fitting
+    /// the bbox
+    fn new(paper: [f64; 2], title: bool, bbox: [f64; 4], scale: Option<f64>) -> Self {
+        let top = MARGIN + if title { TITLE_HEIGHT } else { 0.0 };
+        let frame = [
+            MARGIN,
+            top,
+            paper[0] - 2.0 * MARGIN,
+            paper[1] - top - MARGIN,
+        ];
+
+        let units_per_mm = match scale {
+            Some(scale) => scale / 1000.0,
+            None => ((bbox[2] - bbox[0]) / frame[2]).max((bbox[3] - bbox[1]) / frame[3]),
+        };
+        let (cx, cy) = ((bbox[0] + bbox[2]) / 2.0, (bbox[1] + bbox[3]) / 2.0);
+        let (w, h) = (frame[2] * units_per_mm, frame[3] * units_per_mm);
+
+        Sheet {
+            frame,
+            extent: [cx - w / 2.0, cy - h / 2.0, cx + w / 2.0, cy + h / 2.0],
+        }
+    }
+
+    /// Position of a coordinate on the sheet
+    fn point(&self, coordinate: &[f64]) -> [f64; 2] {
+        let ([left, top, width, height], [minx, miny, maxx, maxy]) = (self.frame, self.extent);
+        [
+            left + (coordinate[0] - minx) / (maxx - minx) * width,
+            top + (maxy - coordinate[1]) / (maxy - miny) * height,
+        ]
+    }
+
+    fn points(&self, coordinates: &[Vec<f64>]) -> Vec<[f64; 2]> {
+        coordinates.iter().map(|c| self.point(c)).collect()
+    }
+
+    /// Units of the crs per millimeter on the sheet
+    fn units_per_mm(&self) -> f64 {
+        (self.extent[2] - self.extent[0]) / self.frame[2]
+    }
+}
+
+type Color = [u8; 4];
+
+const BLACK: Color = [0, 0, 0, 255];
+const WHITE: Color = [255, 255, 255, 255];
+
+/// Symbol of features, sizes in millimeters
+struct Symbol {
+    fill: Option<Color>,
+    stroke: Option<Color>,
+    stroke_width: f64,
+    point_radius: f64,
+}
+
+impl Symbol {
+    /// Symbol of a style, with translucent fills and outlines of a color of
+    /// the palette by default
+    fn new(style: Option<&LayerStyle>, [r, g, b]: [u8; 3]) -> std::result::Result<Self, String> {
+        let color = |color: Option<&String>, default: Color| match color {
+            Some(color) => parse_color(color).ok_or_else(|| format!("Invalid color `{color}`")),
+            None => Ok(default),
+        };
+
+        let size = |size: Option<f64>, default: f64| match size {
+            Some(size) if (0.0..=50.0).contains(&size) => Ok(size),
+            Some(size) => Err(format!("Invalid size `{size}`")),
+            None => Ok(default),
+        };
+
+        Ok(Symbol {
+            fill: Some(color(style.and_then(|s| s.fill.as_ref()), [r, g, b, 102])?),
+            stroke: Some(color(
+                style.and_then(|s| s.stroke.as_ref()),
+                [r, g, b, 255],
+            )?),
+            stroke_width: size(style.and_then(|s| s.stroke_width), 0.3)?,
+            point_radius: size(style.and_then(|s| s.point_radius), 0.8)?,
+        })
+    }
+}
+
+/// Color of a hex string, `#rrggbb` or `#rrggbbaa`
+fn parse_color(color: &str) -> Option<Color> {
+    let hex = color.strip_prefix('#')?;
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut rgba = [255; 4];
+    for (i, channel) in rgba.iter_mut().enumerate().take(hex.len() / 2) {
+        *channel = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+
+    Some(rgba)
+}
+
+/// Draw a geometry with a symbol, lines are stroked only
+fn draw(canvas: &mut dyn Canvas, sheet: &Sheet, geometry: &geojson::Value, symbol: &Symbol) {
+    match geometry {
+        geojson::Value::Point(point) => canvas.circle(sheet.point(point), symbol),
+        geojson::Value::MultiPoint(points) => {
+            for point in points {
+                canvas.circle(sheet.point(point), symbol);
+            }
+        }
+        geojson::Value::LineString(line) => canvas.path(&[sheet.points(line)], false, symbol),
+        geojson::Value::MultiLineString(lines) => {
+            let lines: Vec<_> = lines.iter().map(|line| sheet.points(line)).collect();
+            canvas.path(&lines, false, symbol);
+        }
+        geojson::Value::Polygon(rings) => {
+            let rings: Vec<_> = rings.iter().map(|ring| sheet.points(ring)).collect();
+            canvas.path(&rings, true, symbol);
+        }
+        geojson::Value::MultiPolygon(polygons) => {
+            for rings in polygons {
+                let rings: Vec<_> = rings.iter().map(|ring| sheet.points(ring)).collect();
+                canvas.path(&rings, true, symbol);
+            }
+        }
+        geojson::Value::GeometryCollection(geometries) => {
+            for geometry in geometries {
+                draw(canvas, sheet, &geometry.value, symbol);
+            }
+        }
+    }
+}
+
+/// Draw the frame of the map and the requested overlays
+fn overlays(
+    canvas: &mut dyn Canvas,
+    sheet: &Sheet,
+    overlays: &Overlays,
+    units_per_meter: Option<f64>,
+) {
+    let [left, top, width, height] = sheet.frame;
+    let (right, bottom) = (left + width, top + height);
+
+    let outline = Symbol {
+        fill: None,
+        stroke: Some(BLACK),
+        stroke_width: 0.3,
+        point_radius: 0.0,
+    };
+    let frame = vec![[left, top], [right, top], [right, bottom], [left, bottom]];
+    canvas.path(&[frame], true, &outline);
+
+    if let Some(title) = &overlays.title {
+        canvas.text([left, MARGIN + TITLE_HEIGHT / 2.0], 16.0, title);
+    }
+
+    if let Some(units_per_meter) = units_per_meter.filter(|_| overlays.scale_bar) {
+        let meters_per_mm = sheet.units_per_mm() / units_per_meter;
+        let length = round_length(width / 4.0 * meters_per_mm);
+        let bar = length / meters_per_mm;
+
+        let (x, y) = (left + 5.0, bottom - 7.0);
+        for (i, fill) in [BLACK, WHITE].into_iter().enumerate() {
+            let x = x + i as f64 * bar / 2.0;
+            let segment = vec![
+                [x, y],
+                [x + bar / 2.0, y],
+                [x + bar / 2.0, y + 1.5],
+                [x, y + 1.5],
+            ];
+            let symbol = Symbol {
+                fill: Some(fill),
+                ..outline
+            };
+            canvas.path(&[segment], true, &symbol);
+        }
+        canvas.text([x + bar + 2.0, y + 1.5], 8.0, &format_length(length));
+    }
+
+    if overlays.north_arrow {
+        let (x, y) = (right - 8.0, top + 5.0);
+        let arrow = vec![[x, y], [x + 3.0, y + 9.0], [x, y + 7.0], [x - 3.0, y + 9.0]];
+        let symbol = Symbol {
+            fill: Some(BLACK),
+            ..outline
+        };
+        canvas.path(&[arrow], true, &symbol);
+        canvas.text([x - 1.2, y + 13.5], 9.0, "N");
+    }
+}
+
+/// Largest length of 1, 2 or 5 times a power of ten up to a length in meters
+fn round_length(max: f64) -> f64 {
+    let power = 10f64.powf(max.log10().floor());
+    [5.0, 2.0, 1.0]
+        .into_iter()
+        .map(|f| f * power)
+        .find(|length| *length <= max)
+        .unwrap_or(power)
+}
+
+fn format_length(meters: f64) -> String {
+    if meters >= 1000.0 {
+        format!("{} km", meters / 1000.0)
+    } else {
+        format!("{meters} m")
+    }
+}
+
+/// Drawing surface, positions and sizes in millimeters from the top left
+/// corner of the paper
+trait Canvas {
+    /// Restrict drawing to a rectangle of left, top, width and height, or
+    /// lift the restriction
+    fn clip(&mut self, rect: Option<[f64; 4]>);
+
+    /// Draw lines, or polygons of the rings if closed
+    fn path(&mut self, rings: &[Vec<[f64; 2]>], closed: bool, symbol: &Symbol);
+
+    fn circle(&mut self, center: [f64; 2], symbol: &Symbol);
+
+    /// Draw black text from its baseline, in points
+    fn text(&mut self, at: [f64; 2], size: f64, text: &str);
+
+    /// Encoded sheet
+    fn finish(self: Box<Self>) -> anyhow::Result<Vec<u8>>;
+}
+
+/// Raster sheet, encoded as PNG without text
+struct PngCanvas {
+    pixmap: Pixmap,
+    /// Pixels per millimeter
+    scale: f64,
+    mask: Option<Mask>,
+}
+
+impl PngCanvas {
+    fn new(paper: [f64; 2], dpi: u32) -> anyhow::Result<Self> {
+        let scale = f64::from(dpi) / 25.4;
+        let mut pixmap = Pixmap::new(
+            (paper[0] * scale).round() as u32,
+            (paper[1] * scale).round() as u32,
+        )
+        .ok_or_else(|| anyhow::anyhow!("Invalid size of the sheet"))?;
+        pixmap.fill(tiny_skia::Color::WHITE);
+
+        Ok(PngCanvas {
+            pixmap,
+            scale,
+            mask: None,
+        })
+    }
+
+    fn paint(color: Color) -> Paint<'static> {
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(color[0], color[1], color[2], color[3]);
+        paint.anti_alias = true;
+        paint
+    }
+
+    fn draw(&mut self, path: &tiny_skia::Path, fill: Option<Color>, symbol: &Symbol) {
+        if let Some(fill) = fill {
+            self.pixmap.fill_path(
+                path,
+                &Self::paint(fill),
+                FillRule::EvenOdd,
+                Transform::identity(),
+                self.mask.as_ref(),
+            );
+        }
+        if let Some(color) = symbol.stroke.filter(|_| symbol.stroke_width > 0.0) {
+            let stroke = Stroke {
+                width: (symbol.stroke_width * self.scale) as f32,
+                line_cap: LineCap::Round,
+                line_join: LineJoin::Round,
+                ..Default::default()
+            };
+            self.pixmap.stroke_path(
+                path,
+                &Self::paint(color),
+                &stroke,
+                Transform::identity(),
+                self.mask.as_ref(),
+            );
+        }
+    }
+}
+
+impl Canvas for PngCanvas {
+    fn clip(&mut self, rect: Option<[f64; 4]>) {
+        self.mask = rect.and_then(|[x, y, w, h]| {
+            let rect = tiny_skia::Rect::from_xywh(
+                (x * self.scale) as f32,
+                (y * self.scale) as f32,
+                (w * self.scale) as f32,
+                (h * self.scale) as f32,
+            )?;
+            let mut mask = Mask::new(self.pixmap.width(), self.pixmap.height())?;
+            mask.fill_path(
+                &PathBuilder::from_rect(rect),
+                FillRule::Winding,
+                false,
+                Transform::identity(),
+            );
+            Some(mask)
+        });
+    }
+
+    fn path(&mut self, rings: &[Vec<[f64; 2]>], closed: bool, symbol: &Symbol) {
+        let mut builder = PathBuilder::new();
+        for ring in rings.iter().filter(|ring| ring.len() > 1) {
+            builder.move_to(
+                (ring[0][0] * self.scale) as f32,
+                (ring[0][1] * self.scale) as f32,
+            );
+            for [x, y] in &ring[1..] {
+                builder.line_to((x * self.scale) as f32, (y * self.scale) as f32);
+            }
+            if closed {
+                builder.close();
+            }
+        }
+
+        if let Some(path) = builder.finish() {
+            self.draw(&path, symbol.fill.filter(|_| closed), symbol);
+        }
+    }
+
+    fn circle(&mut self, [x, y]: [f64; 2], symbol: &Symbol) {
+        if let Some(path) = PathBuilder::from_circle(
+            (x * self.scale) as f32,
+            (y * self.scale) as f32,
+            (symbol.point_radius * self.scale) as f32,
+        ) {
+            self.draw(&path, symbol.fill, symbol);
+        }
+    }
+
+    fn text(&mut self, _at: [f64; 2], _size: f64, _text: &str) {}
+
+    fn finish(self: Box<Self>) -> anyhow::Result<Vec<u8>> {
+        Ok(self.pixmap.encode_png()?)
+    }
+}
+
+/// Vector sheet of a single PDF page, with the standard Helvetica font
+struct PdfCanvas {
+    content: Content,
+    paper: [f64; 2],
+    /// Fill and stroke opacities of the graphics states `G{index}`
+    opacities: Vec<(u8, u8)>,
+    clipped: bool,
+}
+
+/// Points per millimeter
+const PT: f64 = 72.0 / 25.4;
+
+/// Magic number of cubic Bézier curves approximating quarter circles
+const KAPPA: f64 = 0.552_284_75;
+
+impl PdfCanvas {
+    fn new(paper: [f64; 2]) -> Self {
+        PdfCanvas {
+            content: Content::new(),
+            paper,
+            opacities: Vec::new(),
+            clipped: false,
+        }
+    }
+
+    /// Position in points from the bottom left corner
+    fn point(&self, [x, y]: [f64; 2]) -> (f32, f32) {
+        ((x * PT) as f32, ((self.paper[1] - y) * PT) as f32)
+    }
+
+    /// Set the colors and width of a symbol, with a graphics state for
+    /// translucent colors
+    fn style(&mut self, fill: Option<Color>, symbol: &Symbol) {
+        let opacity = (
+            fill.map_or(255, |c| c[3]),
+            symbol.stroke.map_or(255, |c| c[3]),
+        );
+        if opacity != (255, 255) {
+            let index = match self.opacities.iter().position(|o| *o == opacity) {
+                Some(index) => index,
+                None => {
+                    self.opacities.push(opacity);
+                    self.opacities.len() - 1
+                }
+            };
+            self.content
+                .set_parameters(Name(format!("G{index}").as_bytes()));
+        }
+
+        if let Some([r, g, b, _]) = fill {
+            self.content
+                .set_fill_rgb(channel(r), channel(g), channel(b));
+        }
+        if let Some([r, g, b, _]) = symbol.stroke {
+            self.content
+                .set_stroke_rgb(channel(r), channel(g), channel(b))
+                .set_line_width((symbol.stroke_width * PT) as f32);
+        }
+    }
+
+    /// Paint the current path
+    fn paint(&mut self, fill: bool, stroke: bool) {
+        match (fill, stroke) {
+            (true, true) => self.content.fill_even_odd_and_stroke(),
+            (true, false) => self.content.fill_even_odd(),
+            (false, true) => self.content.stroke(),
+            (false, false) => self.content.end_path(),
+        };
+    }
+}
+
+fn channel(value: u8) -> f32 {
+    f32::from(value) / 255.0
+}
+
+impl Canvas for PdfCanvas {
+    fn clip(&mut self, rect: Option<[f64; 4]>) {
+        if self.clipped {
+            self.content.restore_state();
+            self.clipped = false;
+        }
+        if let Some([x, y, w, h]) = rect {
+            let (x, y) = self.point([x, y + h]);
+            self.content
+                .save_state()
+                .rect(x, y, (w * PT) as f32, (h * PT) as f32)
+                .clip_nonzero()
+                .end_path();
+            self.clipped = true;
+        }
+    }
+
+    fn path(&mut self, rings: &[Vec<[f64; 2]>], closed: bool, symbol: &Symbol) {
+        let fill = symbol.fill.filter(|_| closed);
+        let stroke = symbol.stroke.is_some() && symbol.stroke_width > 0.0;
+
+        self.content.save_state();
+        self.style(fill, symbol);
+        for ring in rings.iter().filter(|ring| ring.len() > 1) {
+            let (x, y) = self.point(ring[0]);
+            self.content.move_to(x, y);
+            for point in &ring[1..] {
+                let (x, y) = self.point(*point);
+                self.content.line_to(x, y);
+            }
+            if closed {
+                self.content.close_path();
+            }
+        }
+        self.paint(fill.is_some(), stroke);
+        self.content.restore_state();
+    }
+
+    fn circle(&mut self, center: [f64; 2], symbol: &Symbol) {
+        let (x, y) = self.point(center);
+        let r = (symbol.point_radius * PT) as f32;
+        let k = r * KAPPA as f32;
+        let stroke = symbol.stroke.is_some() && symbol.stroke_width > 0.0;
+
+        self.content.save_state();
+        self.style(symbol.fill, symbol);
+        self.content
+            .move_to(x + r, y)
+            .cubic_to(x + r, y + k, x + k, y + r, x, y + r)
+            .cubic_to(x - k, y + r, x - r, y + k, x - r, y)
+            .cubic_to(x - r, y - k, x - k, y - r, x, y - r)
+            .cubic_to(x + k, y - r, x + r, y - k, x + r, y)
+            .close_path();
+        self.paint(symbol.fill.is_some(), stroke);
+        self.content.restore_state();
+    }
+
+    fn text(&mut self, at: [f64; 2], size: f64, text: &str) {
+        // WinAnsi covers Latin-1, other characters are replaced
+        let text: Vec<u8> = text
+            .chars()
+            .map(|c| match u8::try_from(c) {
+                Ok(c) if c >= 0x20 && c != 0x7f => c,
+                _ => b'?',
+            })
+            .collect();
+
+        let (x, y) = self.point(at);
+        self.content
+            .save_state()
+            .set_fill_gray(0.0)
+            .begin_text()
+            .set_font(Name(b"F1"), size as f32)
+            .next_line(x, y)
+            .show(Str(&text))
+            .end_text()
+            .restore_state();
+    }
+
+    fn finish(mut self: Box<Self>) -> anyhow::Result<Vec<u8>> {
+        self.clip(None);
+
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let page_id = Ref::new(3);
+        let content_id = Ref::new(4);
+        let font_id = Ref::new(5);
+        let state_id = |index: usize| Ref::new(6 + index as i32);
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id).kids([page_id]).count(1);
+
+        let mut page = pdf.page(page_id);
+        page.parent(page_tree_id)
+            .media_box(Rect::new(
+                0.0,
+                0.0,
+                (self.paper[0] * PT) as f32,
+                (self.paper[1] * PT) as f32,
+            ))
+            .contents(content_id);
+        let mut resources = page.resources();
+        resources.fonts().pair(Name(b"F1"), font_id);
+        let mut states = resources.ext_g_states();
+        for index in 0..self.opacities.len() {
+            states.pair(Name(format!("G{index}").as_bytes()), state_id(index));
+        }
+        states.finish();
+        resources.finish();
+        page.finish();
+
+        pdf.type1_font(font_id)
+            .base_font(Name(b"Helvetica"))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+        for (index, (fill, stroke)) in self.opacities.iter().enumerate() {
+            pdf.ext_graphics(state_id(index))
+                .non_stroking_alpha(channel(*fill))
+                .stroking_alpha(channel(*stroke));
+        }
+
+        let content = std::mem::replace(&mut self.content, Content::new()).finish();
+        pdf.stream(content_id, &content);
+
+        Ok(pdf.finish())
+    }
+}
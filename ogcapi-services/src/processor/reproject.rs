@@ -0,0 +1,144 @@
+//! Reprojection of the stored geometries of collections
+//!
+//! The geometries are reprojected in batches next to the stored ones, which
+//! keeps the collection readable and writable meanwhile. Once all are done,
+//! they replace the stored ones together with the storage crs in the
+//! collection document in a single transaction, e.g. after a national crs
+//! changed or data was loaded with the wrong one. Only admins may execute it.
+
+use axum::{http::StatusCode, response::Response};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use ogcapi_types::{
+    common::Crs,
+    processes::{JobControlOptions, Process, StatusCode as JobStatus},
+};
+
+use crate::{AppState, Error, Result};
+
+use super::{queue_job, TypedProcessor};
+
+/// Number of features reprojected at once
+const BATCH_SIZE: usize = 10_000;
+
+/// Reproject a collection to another storage crs
+///
+/// ```bash
+/// curl http://localhost:8484/processes/collection-reproject/execution \
+///         -H "X-API-Key: $KEY" \
+///         -H 'Content-Type: application/json' \
+///         -d '{"inputs": {"collection": "parcels", "crs": "http://www.opengis.net/def/crs/EPSG/0/2056"}}'
+/// ```
+#[derive(Clone)]
+pub struct CollectionReproject;
+
+/// Inputs for the `collection-reproject` process
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct ReprojectInputs {
+    /// Identifier of the collection
+    collection: String,
+    /// New storage crs of the collection
+    crs: String,
+}
+
+/// Outputs for the `collection-reproject` process
+#[derive(Serialize, JsonSchema)]
+pub struct ReprojectOutputs {
+    /// Identifier of the reprojected collection
+    collection: String,
+    /// Number of reprojected features
+    features: u64,
+}
+
+#[axum::async_trait]
+impl TypedProcessor for CollectionReproject {
+    const ID: &'static str = "collection-reproject";
+
+    const ADMIN: bool = true;
+
+    type Inputs = ReprojectInputs;
+    type Outputs = ReprojectOutputs;
+
+    fn describe(&self, process: &mut Process) {
+        process.summary.job_control_options = vec![JobControlOptions::AsyncExecute];
+    }
+
+    async fn run(&self, inputs: ReprojectInputs, state: &AppState, url: &Url) -> Result<Response> {
+        let Some(collection) = state
+            .drivers
+            .collections
+            .read_collection(&inputs.collection)
+            .await?
+        else {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Unknown collection `{}`", inputs.collection),
+            ));
+        };
+
+        let crs: Crs = inputs
+            .crs
+            .parse()
+            .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e))?;
+        if !crs.is_valid() {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid crs `{crs}`"),
+            ));
+        }
+        if collection.storage_crs.unwrap_or_default() == crs {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Collection `{}` is stored in `{crs}`", inputs.collection),
+            ));
+        }
+
+        let inputs = serde_json::to_value(inputs).map_err(anyhow::Error::from)?;
+        queue_job(self, inputs, state, url).await
+    }
+
+    async fn run_queued(
+        &self,
+        job_id: &str,
+        inputs: ReprojectInputs,
+        state: &AppState,
+    ) -> anyhow::Result<ReprojectOutputs> {
+        let crs: Crs = inputs.crs.parse().map_err(anyhow::Error::msg)?;
+        let features = &state.drivers.features;
+
+        let mut previous = None;
+        let total = loop {
+            let (done, total) = features
+                .reproject_features(&inputs.collection, &crs, BATCH_SIZE)
+                .await?;
+
+            if let Some(mut job) = state.drivers.jobs.status(job_id).await? {
+                // the reprojected geometries are kept to resume from
+                if job.status == JobStatus::Dismissed {
+                    anyhow::bail!("Reprojection of `{}` dismissed", inputs.collection);
+                }
+                // done once swapped in
+                job.progress = Some((done * 99).checked_div(total).unwrap_or(99) as i8);
+                state.drivers.jobs.update(&job).await?;
+            }
+
+            // features locked by writers are left to the swap
+            if done >= total || previous == Some(done) {
+                break total;
+            }
+            previous = Some(done);
+        };
+
+        features
+            .finish_reprojection(&inputs.collection, &crs)
+            .await?;
+        state.extents.invalidate(&inputs.collection);
+
+        Ok(ReprojectOutputs {
+            collection: inputs.collection,
+            features: total,
+        })
+    }
+}
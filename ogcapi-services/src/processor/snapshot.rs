@@ -0,0 +1,390 @@
+//! Snapshots of collections in object storage
+//!
+//! A snapshot is stored under a key prefix of an S3 bucket as the collection
+//! document `collection.json` next to its items as `items.gpkg`, a `GeoPackage`
+//! in the storage crs of the collection. Restoring a snapshot recreates the
+//! collection in the same or another deployment with access to the bucket, e.g.
+//! for backups or to promote a collection from staging to production.
+//!
+//! Both processes only share object storage with their jobs, which therefore
+//! run through the job queue on any replica, see [`crate::queue`]. Only admins
+//! may execute them.
+
+use axum::{http::StatusCode, response::Response};
+use chrono::Utc;
+use futures::StreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{sqlite::SqliteConnectOptions, Connection, SqliteConnection};
+use url::Url;
+
+use ogcapi_drivers::{geopackage, s3::ByteStream};
+use ogcapi_types::{
+    common::{
+        media_type::{GEO_PACKAGE, JSON},
+        Collection,
+    },
+    features::{Feature, Query},
+    processes::{JobControlOptions, Process},
+};
+
+use crate::{AppState, Error, Result};
+
+use super::{export_geopackage, geopackage::BATCH_SIZE, queue_job, TypedProcessor};
+
+/// Key of the collection document below the prefix of a snapshot
+const COLLECTION_KEY: &str = "collection.json";
+
+/// Key of the items below the prefix of a snapshot
+const ITEMS_KEY: &str = "items.gpkg";
+
+/// Snapshot a collection with its items to object storage
+///
+/// ```bash
+/// curl http://localhost:8484/processes/collection-snapshot/execution \
+///         -H "X-API-Key: $KEY" \
+///         -H 'Content-Type: application/json' \
+///         -d '{"inputs": {"collection": "countries"}}'
+/// ```
+#[derive(Clone)]
+pub struct CollectionSnapshot;
+
+/// Inputs for the `collection-snapshot` process
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct SnapshotInputs {
+    /// Identifier of the collection
+    collection: String,
+    /// Bucket of the snapshot, defaults to `AWS_S3_BUCKET_NAME`
+    bucket: Option<String>,
+    /// Key prefix of the snapshot, defaults to `snapshots/{collection}/{timestamp}`
+    prefix: Option<String>,
+}
+
+/// Outputs for the `collection-snapshot` process
+#[derive(Serialize, JsonSchema)]
+pub struct SnapshotOutputs {
+    /// Bucket of the snapshot
+    bucket: String,
+    /// Key prefix of the snapshot
+    prefix: String,
+}
+
+#[axum::async_trait]
+impl TypedProcessor for CollectionSnapshot {
+    const ID: &'static str = "collection-snapshot";
+
+    const ADMIN: bool = true;
+
+    type Inputs = SnapshotInputs;
+    type Outputs = SnapshotOutputs;
+
+    fn describe(&self, process: &mut Process) {
+        process.summary.job_control_options = vec![JobControlOptions::AsyncExecute];
+    }
+
+    async fn run(&self, inputs: SnapshotInputs, state: &AppState, url: &Url) -> Result<Response> {
+        if state
+            .drivers
+            .collections
+            .read_collection(&inputs.collection)
+            .await?
+            .is_none()
+        {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Unknown collection `{}`", inputs.collection),
+            ));
+        }
+
+        // resolved before queueing, for the job to run anywhere alike
+        let inputs = SnapshotInputs {
+            bucket: Some(bucket(state, inputs.bucket)?),
+            prefix: Some(match inputs.prefix {
+                Some(prefix) => prefix.trim_end_matches('/').to_owned(),
+                None => format!(
+                    "snapshots/{}/{}",
+                    inputs.collection,
+                    Utc::now().format("%Y%m%dT%H%M%SZ")
+                ),
+            }),
+            collection: inputs.collection,
+        };
+
+        let inputs = serde_json::to_value(inputs).map_err(anyhow::Error::from)?;
+        queue_job(self, inputs, state, url).await
+    }
+
+    async fn run_queued(
+        &self,
+        job_id: &str,
+        inputs: SnapshotInputs,
+        state: &AppState,
+    ) -> anyhow::Result<SnapshotOutputs> {
+        let (Some(bucket), Some(prefix)) = (inputs.bucket, inputs.prefix) else {
+            anyhow::bail!("Snapshot without bucket or prefix");
+        };
+
+        let Some(collection) = state
+            .drivers
+            .collections
+            .read_collection(&inputs.collection)
+            .await?
+        else {
+            anyhow::bail!("Unknown collection `{}`", inputs.collection);
+        };
+
+        let path = std::env::temp_dir().join(format!("{job_id}-snapshot.gpkg"));
+
+        // items in the storage crs, to be restored without transformation
+        let query = Query {
+            crs: collection.storage_crs.clone().unwrap_or_default(),
+            ..Default::default()
+        };
+        let snapshot = async {
+            export_geopackage(state, &path, &collection.id, &query).await?;
+
+            state
+                .s3
+                .client
+                .put_object()
+                .bucket(&bucket)
+                .key(format!("{prefix}/{ITEMS_KEY}"))
+                .body(ByteStream::from_path(&path).await?)
+                .content_type(GEO_PACKAGE)
+                .send()
+                .await?;
+
+            // the document last, a snapshot without it is incomplete
+            state
+                .s3
+                .put_object(
+                    &bucket,
+                    format!("{prefix}/{COLLECTION_KEY}"),
+                    serde_json::to_vec(&collection)?,
+                    Some(JSON.to_string()),
+                )
+                .await?;
+
+            anyhow::Ok(())
+        }
+        .await;
+
+        let _ = tokio::fs::remove_file(&path).await;
+        snapshot?;
+
+        Ok(SnapshotOutputs { bucket, prefix })
+    }
+}
+
+/// Restore a collection with its items from a snapshot in object storage
+///
+/// ```bash
+/// curl http://localhost:8484/processes/collection-restore/execution \
+///         -H "X-API-Key: $KEY" \
+///         -H 'Content-Type: application/json' \
+///         -d '{"inputs": {"prefix": "snapshots/countries/20240705T081500Z", "replace": true}}'
+/// ```
+#[derive(Clone)]
+pub struct CollectionRestore;
+
+/// Inputs for the `collection-restore` process
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RestoreInputs {
+    /// Bucket of the snapshot, defaults to `AWS_S3_BUCKET_NAME`
+    bucket: Option<String>,
+    /// Key prefix of the snapshot
+    prefix: String,
+    /// Identifier of the restored collection, defaults to the one of the snapshot
+    collection: Option<String>,
+    /// Whether to replace an existing collection, otherwise the restore fails
+    #[serde(default)]
+    replace: bool,
+}
+
+/// Outputs for the `collection-restore` process
+#[derive(Serialize, JsonSchema)]
+pub struct RestoreOutputs {
+    /// Identifier of the restored collection
+    collection: String,
+}
+
+#[axum::async_trait]
+impl TypedProcessor for CollectionRestore {
+    const ID: &'static str = "collection-restore";
+
+    const ADMIN: bool = true;
+
+    type Inputs = RestoreInputs;
+    type Outputs = RestoreOutputs;
+
+    fn describe(&self, process: &mut Process) {
+        process.summary.job_control_options = vec![JobControlOptions::AsyncExecute];
+    }
+
+    async fn run(&self, inputs: RestoreInputs, state: &AppState, url: &Url) -> Result<Response> {
+        // resolved before queueing, for the job to run anywhere alike
+        let inputs = RestoreInputs {
+            bucket: Some(bucket(state, inputs.bucket)?),
+            prefix: inputs.prefix.trim_end_matches('/').to_owned(),
+            ..inputs
+        };
+
+        let inputs = serde_json::to_value(inputs).map_err(anyhow::Error::from)?;
+        queue_job(self, inputs, state, url).await
+    }
+
+    async fn run_queued(
+        &self,
+        job_id: &str,
+        inputs: RestoreInputs,
+        state: &AppState,
+    ) -> anyhow::Result<RestoreOutputs> {
+        let Some(bucket) = inputs.bucket else {
+            anyhow::bail!("Restore without bucket");
+        };
+        let prefix = inputs.prefix;
+
+        let document = state
+            .s3
+            .get_object(&bucket, format!("{prefix}/{COLLECTION_KEY}"))
+            .await?
+            .body
+            .collect()
+            .await?
+            .into_bytes();
+        let mut collection: Collection = serde_json::from_slice(&document)?;
+        if let Some(id) = inputs.collection {
+            collection.id = id;
+        }
+
+        let collections = &state.drivers.collections;
+        if collections.read_collection(&collection.id).await?.is_some() {
+            if !inputs.replace {
+                anyhow::bail!("Collection `{}` exists", collection.id);
+            }
+            collections.delete_collection(&collection.id).await?;
+        }
+        collections.create_collection(&collection).await?;
+
+        let items = state
+            .s3
+            .get_object(&bucket, format!("{prefix}/{ITEMS_KEY}"))
+            .await?
+            .body
+            .collect()
+            .await?
+            .into_bytes();
+
+        let path = std::env::temp_dir().join(format!("{job_id}-restore.gpkg"));
+        tokio::fs::write(&path, items).await?;
+
+        let restored = restore_items(state, &path, &collection.id).await;
+
+        tokio::fs::remove_file(&path).await?;
+        restored?;
+
+        Ok(RestoreOutputs {
+            collection: collection.id,
+        })
+    }
+}
+
+/// Bucket of a snapshot, the requested or the default one
+fn bucket(state: &AppState, bucket: Option<String>) -> Result<String> {
+    bucket
+        .or_else(|| state.s3.bucket.clone())
+        .or_else(|| std::env::var("AWS_S3_BUCKET_NAME").ok())
+        .ok_or_else(|| {
+            Error::Exception(
+                StatusCode::BAD_REQUEST,
+                "Missing `bucket` without `AWS_S3_BUCKET_NAME`".to_string(),
+            )
+        })
+}
+
+/// Insert the items of the `GeoPackage` of a snapshot into a collection
+///
+/// Feature ids are read from the `id` column and property types from the
+/// declared `BOOLEAN` columns and the JSON columns of `gpkg_data_columns`.
+async fn restore_items(
+    state: &AppState,
+    path: &std::path::Path,
+    collection_id: &str,
+) -> anyhow::Result<()> {
+    let mut conn =
+        SqliteConnection::connect_with(&SqliteConnectOptions::new().filename(path).read_only(true))
+            .await?;
+
+    let Some(table) = geopackage::feature_tables(&mut conn).await?.pop() else {
+        anyhow::bail!("Snapshot without feature table");
+    };
+    let crs = table.crs();
+
+    let booleans: Vec<String> =
+        sqlx::query_scalar("SELECT name FROM pragma_table_info($1) WHERE type = 'BOOLEAN'")
+            .bind(&table.table_name)
+            .fetch_all(&mut conn)
+            .await?;
+    let json: Vec<String> = sqlx::query_scalar(
+        "SELECT column_name FROM gpkg_data_columns WHERE table_name = $1 AND mime_type = $2",
+    )
+    .bind(&table.table_name)
+    .bind(JSON)
+    .fetch_all(&mut conn)
+    .await?;
+
+    let sql = format!(
+        r#"SELECT * FROM "{}""#,
+        table.table_name.replace('"', "\"\"")
+    );
+    let mut rows = sqlx::query(&sql).fetch(&mut conn);
+
+    let mut batch: Vec<Feature> = Vec::with_capacity(BATCH_SIZE);
+    while let Some(row) = rows.next().await {
+        let Some(mut feature) =
+            geopackage::read_feature(&row?, collection_id, &table.column_name, Some("id"))?
+        else {
+            continue;
+        };
+
+        if let Some(properties) = feature.properties.as_mut() {
+            properties.remove("fid");
+            for (key, value) in properties.iter_mut() {
+                if booleans.contains(key) {
+                    if let Some(i) = value.as_i64() {
+                        *value = Value::Bool(i != 0);
+                    }
+                } else if json.contains(key) {
+                    if let Some(parsed) = value.as_str().and_then(|s| serde_json::from_str(s).ok())
+                    {
+                        *value = parsed;
+                    }
+                }
+            }
+        }
+        batch.push(feature);
+
+        if batch.len() >= BATCH_SIZE {
+            state
+                .drivers
+                .features
+                .create_features(collection_id, &batch, &crs)
+                .await?;
+            batch.clear();
+        }
+    }
+
+    if !batch.is_empty() {
+        state
+            .drivers
+            .features
+            .create_features(collection_id, &batch, &crs)
+            .await?;
+    }
+
+    drop(rows);
+    conn.close().await?;
+
+    Ok(())
+}
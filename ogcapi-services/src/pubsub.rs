@@ -0,0 +1,138 @@
+//! Event publishing to an MQTT broker, following the OGC API - Pub/Sub
+//! building block
+//!
+//! Feature changes are published to the collection topic, job status updates
+//! to the job topic. Topics are URI templates with the variables
+//! `collectionId` and `jobId`/`processId` respectively.
+
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use rumqttc::{AsyncClient, MqttOptions, QoS};
+use serde::Serialize;
+
+#[cfg(any(feature = "processes", feature = "joins"))]
+use ogcapi_types::processes::StatusInfo;
+use ogcapi_types::{common::uri_template, features::FeatureChange};
+#[cfg(any(feature = "processes", feature = "joins"))]
+use tokio::sync::broadcast;
+
+use crate::{leader::Leader, state::Drivers};
+
+/// Default topic of feature changes
+pub const COLLECTION_TOPIC: &str = "collections/{collectionId}/items";
+/// Default topic of job status updates
+pub const JOB_TOPIC: &str = "jobs/{jobId}";
+
+/// Publisher of events to an MQTT broker
+#[derive(Clone)]
+pub struct Publisher {
+    client: AsyncClient,
+    collection_topic: Arc<str>,
+    job_topic: Arc<str>,
+}
+
+impl Publisher {
+    /// Connect to a broker, e.g. `mqtt://localhost:1883?client_id=ogcapi`
+    ///
+    /// The connection is driven in the background and reestablished on errors.
+    pub fn connect(url: &url::Url) -> anyhow::Result<Self> {
+        let options = MqttOptions::parse_url(url.as_str())?;
+        let (client, mut eventloop) = AsyncClient::new(options, 64);
+
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = eventloop.poll().await {
+                    tracing::error!("MQTT connection error: {e}");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        });
+
+        Ok(Publisher {
+            client,
+            collection_topic: COLLECTION_TOPIC.into(),
+            job_topic: JOB_TOPIC.into(),
+        })
+    }
+
+    /// Set the topic template of feature changes
+    pub fn collection_topic(mut self, template: &str) -> Self {
+        self.collection_topic = template.into();
+        self
+    }
+
+    /// Set the topic template of job status updates
+    pub fn job_topic(mut self, template: &str) -> Self {
+        self.job_topic = template.into();
+        self
+    }
+
+    pub async fn publish_feature_change(&self, change: &FeatureChange) -> anyhow::Result<()> {
+        let topic = uri_template::expand(
+            &self.collection_topic,
+            &[("collectionId", &change.collection)],
+        );
+        self.publish(topic, change).await
+    }
+
+    #[cfg(any(feature = "processes", feature = "joins"))]
+    pub async fn publish_job_status(&self, job: &StatusInfo) -> anyhow::Result<()> {
+        let mut variables = vec![("jobId", job.job_id.as_str())];
+        if let Some(process_id) = &job.process_id {
+            variables.push(("processId", process_id));
+        }
+        let topic = uri_template::expand(&self.job_topic, &variables);
+        self.publish(topic, job).await
+    }
+
+    /// Publish the changes of the feature change feed until it ends, on the
+    /// primary replica
+    pub(crate) async fn forward_changes(self, drivers: Arc<Drivers>, leader: Leader) {
+        let mut changes = match drivers.changes.subscribe_all().await {
+            Ok(changes) => changes,
+            Err(e) => {
+                tracing::error!("Failed to subscribe to feature changes: {e}");
+                return;
+            }
+        };
+
+        while let Some(change) = changes.next().await {
+            if !leader.is_leader() {
+                continue;
+            }
+            let result = match change {
+                Ok(change) => self.publish_feature_change(&change).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::error!("Failed to publish feature change: {e}");
+            }
+        }
+    }
+
+    /// Publish job status updates until the sender is dropped
+    #[cfg(any(feature = "processes", feature = "joins"))]
+    pub(crate) async fn forward_jobs(self, mut jobs: broadcast::Receiver<StatusInfo>) {
+        loop {
+            match jobs.recv().await {
+                Ok(job) => {
+                    if let Err(e) = self.publish_job_status(&job).await {
+                        tracing::error!("Failed to publish status of job `{}`: {e}", job.job_id);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!("Skipped publishing {n} job status updates");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    async fn publish(&self, topic: String, payload: &impl Serialize) -> anyhow::Result<()> {
+        self.client
+            .publish(topic, QoS::AtLeastOnce, false, serde_json::to_vec(payload)?)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+//! Workers of the job queue shared by the replicas of the service
+//!
+//! Jobs submitted with [`crate::processor::queue_job`] are queued in the
+//! database and claimed by the workers of any replica with `SKIP LOCKED`. A
+//! claim is a lease, extended by heartbeats while the job runs, jobs of
+//! crashed workers are claimed again once their lease expired.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use ogcapi_drivers::QueuedJob;
+use ogcapi_types::processes::{StatusCode as JobStatus, StatusInfo};
+
+use crate::{metering, processor, state::Drivers, AppState};
+
+/// Time between polls of an idle worker
+const POLL: Duration = Duration::from_secs(2);
+
+/// Number of claims after which a job is considered to crash its workers
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Workers running the queued jobs of a replica
+#[derive(Clone, Copy, Debug)]
+pub struct JobWorkers {
+    /// Number of jobs run at once, `0` for replicas only queueing jobs
+    pub count: usize,
+    /// Time a claim of a job is valid without heartbeat
+    pub lease: Duration,
+}
+
+impl Default for JobWorkers {
+    fn default() -> Self {
+        JobWorkers {
+            count: 2,
+            lease: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Start the workers of the job queue, if enabled
+pub(crate) fn spawn(state: &AppState) {
+    let Some(workers) = state.job_queue else {
+        return;
+    };
+
+    for _ in 0..workers.count {
+        let state = state.clone();
+        let worker = uuid::Uuid::new_v4().to_string();
+
+        tokio::spawn(async move {
+            loop {
+                match state.drivers.queue.claim_job(&worker, workers.lease).await {
+                    Ok(Some(job)) => execute(&state, &worker, workers.lease, job).await,
+                    Ok(None) => tokio::time::sleep(POLL).await,
+                    Err(e) => {
+                        tracing::warn!("Failed to claim queued job: {e}");
+                        tokio::time::sleep(POLL).await;
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Run a claimed job, renewing the lease until it is finished
+async fn execute(state: &AppState, worker: &str, lease: Duration, job: QueuedJob) {
+    let drivers = state.drivers.clone();
+
+    let status = StatusInfo {
+        process_id: Some(job.process_id.to_owned()),
+        job_id: job.job_id.to_owned(),
+        status: JobStatus::Running,
+        ..Default::default()
+    };
+
+    let processor = state
+        .processors
+        .read()
+        .unwrap()
+        .get(&job.process_id)
+        .cloned();
+
+    let failure = match processor {
+        _ if job.attempts > MAX_ATTEMPTS => {
+            Some(format!("Job abandoned after {MAX_ATTEMPTS} attempts"))
+        }
+        None => Some(format!("No process with id `{}`", job.process_id)),
+        Some(processor) => {
+            let heartbeat = tokio::spawn(heartbeat(
+                drivers.clone(),
+                job.job_id.to_owned(),
+                worker.to_owned(),
+                lease,
+            ));
+
+            let start = Instant::now();
+            processor::run(&drivers, status.clone(), |job_id| async move {
+                processor.run_job(&job_id, job.inputs, state).await
+            })
+            .await;
+
+            heartbeat.abort();
+
+            // metered for the key of the request the job was submitted with
+            if let Some(metered) = job.key.and_then(|key| metering::queued(state, key)) {
+                metered.job(start.elapsed());
+            }
+            None
+        }
+    };
+
+    if let Some(message) = failure {
+        let status = StatusInfo {
+            status: JobStatus::Failed,
+            message: Some(message),
+            finished: Some(Utc::now()),
+            ..status
+        };
+        if let Err(e) = drivers.jobs.update(&status).await {
+            tracing::error!("Failed to update job `{}`: {:?}", status.job_id, e);
+        }
+    }
+
+    if let Err(e) = drivers.queue.complete_job(&job.job_id).await {
+        tracing::error!("Failed to dequeue job `{}`: {:?}", job.job_id, e);
+    }
+}
+
+/// Extend the lease of a job periodically
+async fn heartbeat(drivers: Arc<Drivers>, job_id: String, worker: String, lease: Duration) {
+    let mut interval = tokio::time::interval(lease / 3);
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        match drivers.queue.heartbeat(&job_id, &worker, lease).await {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::warn!("Lost the lease of job `{job_id}`");
+                return;
+            }
+            Err(e) => tracing::warn!("Failed to renew the lease of job `{job_id}`: {e}"),
+        }
+    }
+}
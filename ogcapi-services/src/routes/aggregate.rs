@@ -0,0 +1,140 @@
+use axum::{
+    extract::{Path, State},
+    http::{
+        header::{ACCEPT, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde_json::Value;
+
+use ogcapi_types::{
+    common::{
+        link_rel::{COLLECTION, ROOT},
+        media_type::{CSV, GEO_JSON},
+        LinkBuilder, Linked,
+    },
+    features::{Aggregate, AggregateQuery, FeatureCollection, Grouping},
+};
+
+use crate::{
+    access::{deny_restricted, hidden_properties},
+    extractors::{Qs, RemoteUrl},
+    routes::Module,
+    AppState, Error, Result,
+};
+
+/// Aggregates of the features of a collection per spatial group, as `GeoJSON`
+/// with the geometries of the groups or as `CSV` by group id
+async fn aggregate(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(collection_id): Path<String>,
+    Qs(query): Qs<AggregateQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    tracing::debug!("{:#?}", query);
+
+    let collection = state
+        .services
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // aggregates are computed over all features
+    deny_restricted(&state, &headers, &[&collection_id]).await?;
+
+    let hidden = hidden_properties(&state, &headers, &collection).await;
+    if let Some(property) = query
+        .agg
+        .iter()
+        .filter_map(Aggregate::property)
+        .find(|property| hidden.iter().any(|h| h == property))
+    {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Property `{property}` is not queryable"),
+        ));
+    }
+
+    if let Grouping::Collection(groups) = &query.by {
+        state
+            .services
+            .collections
+            .read_collection(groups)
+            .await?
+            .ok_or_else(|| {
+                Error::Exception(
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown collection `{groups}`"),
+                )
+            })?;
+        deny_restricted(&state, &headers, &[groups]).await?;
+    }
+
+    let mut fc = state
+        .services
+        .features
+        .aggregate(&collection_id, &query)
+        .await?
+        .ok_or_else(|| {
+            Error::Exception(
+                StatusCode::NOT_IMPLEMENTED,
+                format!("Grouping by `{}` is not supported by the backend", query.by),
+            )
+        })?;
+
+    let csv = query.f.as_deref() == Some("csv")
+        || headers
+            .get(ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .is_some_and(|accept| accept.contains(CSV));
+    if csv {
+        let body = to_csv(&fc, &query.aggregates())?;
+        return Ok(([(CONTENT_TYPE, CSV)], body).into_response());
+    }
+
+    let links = LinkBuilder::new(&url).mediatype(GEO_JSON);
+    fc.links.insert_or_update(&[
+        links.self_link(),
+        links.link("../..", ROOT)?,
+        links.link(".", COLLECTION)?,
+    ]);
+
+    Ok(([(CONTENT_TYPE, GEO_JSON)], Json(fc)).into_response())
+}
+
+/// Rows of the groups with their id and aggregates, without geometry
+fn to_csv(fc: &FeatureCollection, aggregates: &[Aggregate]) -> anyhow::Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    let mut header = vec!["id".to_string()];
+    header.extend(aggregates.iter().map(ToString::to_string));
+    writer.write_record(&header)?;
+
+    for feature in &fc.features {
+        let mut record = vec![feature.id.to_owned().unwrap_or_default()];
+        for aggregate in aggregates {
+            let value = feature
+                .properties
+                .as_ref()
+                .and_then(|p| p.get(&aggregate.to_string()));
+            record.push(match value {
+                None | Some(Value::Null) => String::new(),
+                Some(value) => value.to_string(),
+            });
+        }
+        writer.write_record(&record)?;
+    }
+
+    Ok(writer.into_inner()?)
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new().route("/collections/:collection_id/aggregate", get(aggregate));
+
+    Module::new(router)
+}
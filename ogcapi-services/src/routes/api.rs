@@ -1,18 +1,144 @@
-use axum::{extract::State, http::header::CONTENT_TYPE, response::Html, Json};
+use axum::{
+    extract::{Query, State},
+    http::header::{ACCEPT, CONTENT_TYPE, VARY},
+    response::{Html, IntoResponse, Response},
+    Json,
+};
 use hyper::HeaderMap;
-use openapiv3::OpenAPI;
+use serde::Deserialize;
 
-use ogcapi_types::common::media_type::OPEN_API_JSON;
+use ogcapi_types::common::{
+    media_type::{OPEN_API_JSON, OPEN_API_JSON_3_1},
+    Query as CollectionQuery,
+};
 
 use crate::{AppState, Result};
 
-pub(crate) async fn api(State(state): State<AppState>) -> (HeaderMap, Json<OpenAPI>) {
+#[derive(Deserialize, Debug)]
+pub(crate) struct ApiQuery {
+    /// OpenAPI version of the document, `3.0` or `3.1`
+    version: Option<String>,
+}
+
+/// Serve the API definition, as OpenAPI 3.0 by default or as OpenAPI 3.1
+/// with the queryables and properties schemas of the collections as schema
+/// components if requested with the `version` parameter or the `Accept` header
+pub(crate) async fn api(
+    State(state): State<AppState>,
+    Query(query): Query<ApiQuery>,
+    request_headers: HeaderMap,
+) -> Result<Response> {
+    let v3_1 = match query.version.as_deref() {
+        Some(version) => version.starts_with("3.1"),
+        None => request_headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| v.contains("version=3.1")),
+    };
+
     let mut headers = HeaderMap::new();
-    headers.insert(CONTENT_TYPE, OPEN_API_JSON.parse().unwrap());
+    headers.insert(VARY, ACCEPT.into());
+
+    if !v3_1 {
+        headers.insert(CONTENT_TYPE, OPEN_API_JSON.parse().unwrap());
+        return Ok((headers, Json(state.openapi.0)).into_response());
+    }
+
+    let mut api = state.openapi.to_v3_1();
+
+    let collections = state
+        .services
+        .collections
+        .list_collections(&CollectionQuery::default())
+        .await?;
 
-    (headers, Json(state.openapi.0))
+    for collection in collections.collections {
+        let mut queryables = state
+            .services
+            .collections
+            .read_queryables(&collection.id)
+            .await?
+            .unwrap_or_default();
+        if let Some(schema) = &collection.properties_schema {
+            queryables.merge_schema(schema);
+        }
+
+        // component names are restricted to `^[a-zA-Z0-9\.\-_]+$`
+        let name: String = collection
+            .id
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+                _ => '_',
+            })
+            .collect();
+
+        api["components"]["schemas"][format!("{name}.queryables")] =
+            serde_json::to_value(queryables).map_err(anyhow::Error::from)?;
+        if let Some(schema) = collection.properties_schema {
+            api["components"]["schemas"][format!("{name}.properties")] = schema.0.into();
+        }
+    }
+
+    headers.insert(CONTENT_TYPE, OPEN_API_JSON_3_1.parse().unwrap());
+
+    Ok((headers, Json(api)).into_response())
 }
 
+#[cfg(not(feature = "ui-assets"))]
+mod ui {
+    pub(super) const SWAGGER_UI_JS: &str =
+        "https://unpkg.com/swagger-ui-dist@4.11.1/swagger-ui-bundle.js";
+    pub(super) const SWAGGER_UI_CSS: &str =
+        "https://unpkg.com/swagger-ui-dist@4.11.1/swagger-ui.css";
+    pub(super) const REDOC_JS: &str =
+        "https://cdn.jsdelivr.net/npm/redoc@next/bundles/redoc.standalone.js";
+}
+
+/// Bundles served from the binary, for deployments without internet access
+#[cfg(feature = "ui-assets")]
+mod ui {
+    use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+
+    pub(super) const SWAGGER_UI_JS: &str = "swagger/swagger-ui-bundle.js";
+    pub(super) const SWAGGER_UI_CSS: &str = "swagger/swagger-ui.css";
+    pub(super) const REDOC_JS: &str = "redoc/redoc.standalone.js";
+
+    type Asset = ([(axum::http::HeaderName, &'static str); 2], &'static [u8]);
+
+    fn asset(media_type: &'static str, content: &'static [u8]) -> Asset {
+        (
+            [
+                (CONTENT_TYPE, media_type),
+                (CACHE_CONTROL, "public, max-age=86400"),
+            ],
+            content,
+        )
+    }
+
+    pub(crate) async fn swagger_ui_js() -> Asset {
+        asset(
+            "text/javascript",
+            include_bytes!("../../assets/ui/swagger-ui-bundle.js"),
+        )
+    }
+
+    pub(crate) async fn swagger_ui_css() -> Asset {
+        asset("text/css", include_bytes!("../../assets/ui/swagger-ui.css"))
+    }
+
+    pub(crate) async fn redoc_js() -> Asset {
+        asset(
+            "text/javascript",
+            include_bytes!("../../assets/ui/redoc.standalone.js"),
+        )
+    }
+}
+
+#[cfg(feature = "ui-assets")]
+pub(crate) use ui::{redoc_js, swagger_ui_css, swagger_ui_js};
+
 pub(crate) async fn redoc() -> Result<Html<String>> {
     Ok(Html(
         r#"
@@ -23,7 +149,6 @@ pub(crate) async fn redoc() -> Result<Html<String>> {
             <!-- needed for adaptive design -->
             <meta charset="utf-8"/>
             <meta name="viewport" content="width=device-width, initial-scale=1">
-            <link href="https://fonts.googleapis.com/css?family=Montserrat:300,400,700|Roboto:300,400,700" rel="stylesheet">
             <style>
                 body {
                     margin: 0;
@@ -33,10 +158,11 @@ pub(crate) async fn redoc() -> Result<Html<String>> {
         </head>
         <body>
             <redoc spec-url="api"></redoc>
-            <script src="https://cdn.jsdelivr.net/npm/redoc@next/bundles/redoc.standalone.js"></script>
+            <script src="%REDOC_JS%"></script>
         </body>
         </html>
-        "#.to_string(),
+        "#
+        .replace("%REDOC_JS%", ui::REDOC_JS),
     ))
 }
 
@@ -50,11 +176,11 @@ pub(crate) async fn swagger() -> Result<Html<String>> {
             <meta name="viewport" content="width=device-width, initial-scale=1" />
             <meta name="description" content="SwaggerIU" />
             <title>SwaggerUI</title>
-            <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@4.11.1/swagger-ui.css" />
+            <link rel="stylesheet" href="%SWAGGER_UI_CSS%" />
         </head>
         <body>
             <div id="swagger-ui"></div>
-            <script src="https://unpkg.com/swagger-ui-dist@4.11.1/swagger-ui-bundle.js" crossorigin></script>
+            <script src="%SWAGGER_UI_JS%" crossorigin></script>
             <script>
             window.onload = () => {
                 window.ui = SwaggerUIBundle({
@@ -65,6 +191,8 @@ pub(crate) async fn swagger() -> Result<Html<String>> {
             </script>
         </body>
         </html>
-        "#.to_string(),
+        "#
+        .replace("%SWAGGER_UI_CSS%", ui::SWAGGER_UI_CSS)
+        .replace("%SWAGGER_UI_JS%", ui::SWAGGER_UI_JS),
     ))
 }
@@ -0,0 +1,375 @@
+//! Files attached to features, e.g. photos or documents
+//!
+//! Attachments are uploaded like other files, as raw request body with its
+//! `Content-Type` and the file name in the `Content-Disposition` header, as
+//! file of a `multipart/form-data` body or as resumable upload. The content is
+//! stored in the S3 bucket `AWS_S3_BUCKET_NAME`, the features link to their
+//! attachments.
+//!
+//! ```bash
+//! curl -i http://localhost:8484/collections/hydrants/items/h1/attachments \
+//!         -F file=@front.jpg
+//! ```
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE, LOCATION},
+        HeaderMap, StatusCode,
+    },
+    response::Response,
+    routing::{get, post, put},
+    Json, Router,
+};
+use url::Url;
+
+use ogcapi_drivers::s3::ByteStream;
+use ogcapi_types::{
+    common::{
+        link_rel::{ENCLOSURE, ROOT},
+        media_type::JSON,
+        Link, LinkBuilder, Linked,
+    },
+    cql2::Expr,
+    features::{Attachment, Attachments},
+};
+
+use crate::{
+    access::{access_filter, read_filter},
+    extractors::{RemoteUrl, ShareLink},
+    routes::{features::is_accessible, Module},
+    upload::Upload,
+    AppState, Error, Result,
+};
+
+/// Media type of attachments uploaded without one
+const OCTET_STREAM: &str = "application/octet-stream";
+
+/// List the attachments of a feature
+async fn attachments(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path((collection_id, id)): Path<(String, String)>,
+    share: ShareLink,
+    headers: HeaderMap,
+) -> Result<Json<Attachments>> {
+    let filter = read_filter(&state, &headers, &share, &collection_id).await?;
+    check_feature(&state, &collection_id, &id, filter.as_ref()).await?;
+
+    let base = base_url(&url)?;
+    let mut attachments = state
+        .drivers
+        .attachments
+        .list_attachments(&collection_id, &id)
+        .await?;
+    for attachment in attachments.iter_mut() {
+        attachment.links = vec![link(&base, attachment)?];
+    }
+
+    let links = LinkBuilder::new(&url).mediatype(JSON);
+    let mut attachments = Attachments {
+        attachments,
+        links: vec![links.self_link(), links.link("../../../../..", ROOT)?],
+    };
+    attachments.links.resolve_relative_links();
+
+    Ok(Json(attachments))
+}
+
+/// Attach a file to a feature
+async fn create(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path((collection_id, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    upload: Upload,
+) -> Result<(StatusCode, HeaderMap)> {
+    let filter = access_filter(&state, &headers, &collection_id).await?;
+    check_feature(&state, &collection_id, &id, filter.as_ref()).await?;
+
+    let mut attachment = attachment(&upload).await?;
+    attachment.id = state
+        .drivers
+        .attachments
+        .create_attachment(&collection_id, &id, &attachment)
+        .await?;
+
+    if let Err(e) = put_content(&state, &collection_id, &attachment, &upload).await {
+        state
+            .drivers
+            .attachments
+            .delete_attachment(&collection_id, &id, &attachment.id)
+            .await?;
+        return Err(e);
+    }
+
+    let location = base_url(&url)?.join(&attachment.id)?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    Ok((StatusCode::CREATED, headers))
+}
+
+/// Content of an attachment
+async fn read(
+    State(state): State<AppState>,
+    Path((collection_id, id, attachment_id)): Path<(String, String, String)>,
+    share: ShareLink,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let filter = read_filter(&state, &headers, &share, &collection_id).await?;
+    check_feature(&state, &collection_id, &id, filter.as_ref()).await?;
+
+    let attachment = state
+        .drivers
+        .attachments
+        .read_attachment(&collection_id, &id, &attachment_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let content = state
+        .s3
+        .get_object(bucket(&state)?, key(&collection_id, &attachment.id))
+        .await
+        .map_err(anyhow::Error::from)?
+        .body
+        .collect()
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_bytes();
+
+    let mut response = Response::builder()
+        .header(CONTENT_TYPE, &attachment.r#type)
+        .header(CONTENT_LENGTH, content.len());
+    if let Some(title) = &attachment.title {
+        response = response.header(
+            CONTENT_DISPOSITION,
+            format!(r#"inline; filename="{}""#, title.replace('"', "")),
+        );
+    }
+
+    Ok(response.body(Body::from(content)).unwrap())
+}
+
+/// Replace the content of an attachment
+async fn update(
+    State(state): State<AppState>,
+    Path((collection_id, id, attachment_id)): Path<(String, String, String)>,
+    headers: HeaderMap,
+    upload: Upload,
+) -> Result<StatusCode> {
+    let filter = access_filter(&state, &headers, &collection_id).await?;
+    check_feature(&state, &collection_id, &id, filter.as_ref()).await?;
+
+    let existing = state
+        .drivers
+        .attachments
+        .read_attachment(&collection_id, &id, &attachment_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let mut attachment = attachment(&upload).await?;
+    attachment.id = existing.id;
+    attachment.title = attachment.title.or(existing.title);
+
+    put_content(&state, &collection_id, &attachment, &upload).await?;
+    state
+        .drivers
+        .attachments
+        .update_attachment(&collection_id, &id, &attachment)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn remove(
+    State(state): State<AppState>,
+    Path((collection_id, id, attachment_id)): Path<(String, String, String)>,
+    headers: HeaderMap,
+) -> Result<StatusCode> {
+    let filter = access_filter(&state, &headers, &collection_id).await?;
+    check_feature(&state, &collection_id, &id, filter.as_ref()).await?;
+
+    let attachment = state
+        .drivers
+        .attachments
+        .read_attachment(&collection_id, &id, &attachment_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    delete(&state, &collection_id, &id, &attachment).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Links to the attachments of a feature, added to the feature
+pub(crate) async fn feature_links(
+    state: &AppState,
+    feature_url: &Url,
+    collection_id: &str,
+    id: &str,
+) -> Result<Vec<Link>> {
+    let base = feature_url.join(&format!("{id}/attachments/"))?;
+
+    state
+        .drivers
+        .attachments
+        .list_attachments(collection_id, id)
+        .await?
+        .iter()
+        .map(|attachment| link(&base, attachment))
+        .collect()
+}
+
+/// Remove the attachments of a deleted feature
+pub(crate) async fn remove_all(state: &AppState, collection_id: &str, id: &str) -> Result<()> {
+    let attachments = state
+        .drivers
+        .attachments
+        .list_attachments(collection_id, id)
+        .await?;
+
+    for attachment in attachments {
+        delete(state, collection_id, id, &attachment).await?;
+    }
+
+    Ok(())
+}
+
+/// Checks that the collection and feature exist and that the feature matches
+/// the access filter, if any
+async fn check_feature(
+    state: &AppState,
+    collection_id: &str,
+    id: &str,
+    filter: Option<&Expr>,
+) -> Result<()> {
+    state
+        .services
+        .collections
+        .read_collection(collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if !is_accessible(state, collection_id, id, filter).await? {
+        return Err(Error::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Attachment of an upload
+async fn attachment(upload: &Upload) -> Result<Attachment> {
+    let metadata = tokio::fs::metadata(upload.path())
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    Ok(Attachment {
+        title: upload.file_name.to_owned(),
+        r#type: upload
+            .media_type
+            .to_owned()
+            .unwrap_or_else(|| OCTET_STREAM.to_string()),
+        length: metadata.len() as i64,
+        ..Default::default()
+    })
+}
+
+/// Store the content of an attachment
+async fn put_content(
+    state: &AppState,
+    collection_id: &str,
+    attachment: &Attachment,
+    upload: &Upload,
+) -> Result<()> {
+    let body = ByteStream::from_path(upload.path())
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    state
+        .s3
+        .client
+        .put_object()
+        .bucket(bucket(state)?)
+        .key(key(collection_id, &attachment.id))
+        .body(body)
+        .content_type(&attachment.r#type)
+        .send()
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    Ok(())
+}
+
+/// Delete an attachment with its content
+async fn delete(
+    state: &AppState,
+    collection_id: &str,
+    id: &str,
+    attachment: &Attachment,
+) -> Result<()> {
+    state
+        .s3
+        .delete_object(bucket(state)?, key(collection_id, &attachment.id))
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    state
+        .drivers
+        .attachments
+        .delete_attachment(collection_id, id, &attachment.id)
+        .await?;
+
+    Ok(())
+}
+
+/// Bucket of the attachments
+fn bucket(state: &AppState) -> Result<String> {
+    state
+        .s3
+        .bucket
+        .clone()
+        .or_else(|| std::env::var("AWS_S3_BUCKET_NAME").ok())
+        .ok_or_else(|| anyhow::anyhow!("Missing bucket for attachments").into())
+}
+
+/// Object key of the content of an attachment
+fn key(collection_id: &str, id: &str) -> String {
+    format!("attachments/{collection_id}/{id}")
+}
+
+/// Url of the attachments, with a trailing slash to resolve the attachments
+/// against
+fn base_url(url: &Url) -> Result<Url> {
+    let mut url = url.to_owned();
+    url.set_query(None);
+    Ok(format!("{}/", url.as_str().trim_end_matches('/')).parse()?)
+}
+
+fn link(base: &Url, attachment: &Attachment) -> Result<Link> {
+    let mut link = LinkBuilder::new(base)
+        .link(&attachment.id, ENCLOSURE)?
+        .mediatype(&attachment.r#type)
+        .length(attachment.length);
+    if let Some(title) = &attachment.title {
+        link = link.title(title);
+    }
+
+    Ok(link)
+}
+
+pub(crate) fn module() -> Module {
+    let path = "/collections/:collection_id/items/:id/attachments";
+
+    let router = Router::new()
+        .route(path, get(attachments))
+        .route(&format!("{path}/:attachment_id"), get(read).delete(remove));
+
+    let uploads = Router::new()
+        .route(path, post(create))
+        .route(&format!("{path}/:attachment_id"), put(update));
+
+    Module::new(router).uploads(uploads)
+}
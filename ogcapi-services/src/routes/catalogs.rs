@@ -0,0 +1,268 @@
+use axum::{
+    extract::{Path, State},
+    http::{header::LOCATION, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+    {routing::get, Router},
+};
+use hyper::HeaderMap;
+
+use ogcapi_types::common::{
+    link_rel::{CHILD, DATA, PARENT, ROOT, SELF},
+    media_type::JSON,
+    Catalog, Catalogs, Link, LinkBuilder, Linked,
+};
+use url::Url;
+
+use crate::{extractors::RemoteUrl, routes::Module, AppState, Error, Result};
+
+/// Create a catalog grouping collections
+async fn create(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Json(catalog): Json<Catalog>,
+) -> Result<Response> {
+    catalog.validate().map_err(Error::Invalid)?;
+
+    if state
+        .services
+        .collections
+        .read_catalog(&catalog.id)
+        .await?
+        .is_some()
+    {
+        return Err(Error::Exception(
+            StatusCode::CONFLICT,
+            format!("Catalog with id `{}` already exists.", catalog.id),
+        ));
+    }
+
+    check_references(&state, &catalog).await?;
+
+    state.services.collections.upsert_catalog(&catalog).await?;
+
+    let location = url.join(&format!("catalogs/{}", catalog.id))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    Ok((StatusCode::CREATED, headers).into_response())
+}
+
+/// Get a catalog with links to its nested catalogs and collections
+async fn read(
+    State(state): State<AppState>,
+    Path(catalog_id): Path<String>,
+    RemoteUrl(url): RemoteUrl,
+) -> Result<Json<Catalog>> {
+    let catalogs = state.services.collections.list_catalogs().await?;
+
+    let mut catalog = catalogs
+        .iter()
+        .find(|c| c.id == catalog_id)
+        .cloned()
+        .ok_or(Error::NotFound)?;
+
+    link_catalog(&mut catalog, &catalogs, &url)?;
+
+    Ok(Json(catalog))
+}
+
+/// Replace a catalog
+async fn update(
+    State(state): State<AppState>,
+    Path(catalog_id): Path<String>,
+    Json(mut catalog): Json<Catalog>,
+) -> Result<StatusCode> {
+    catalog.id = catalog_id;
+    catalog.validate().map_err(Error::Invalid)?;
+
+    let catalogs = state.services.collections.list_catalogs().await?;
+    if !catalogs.iter().any(|c| c.id == catalog.id) {
+        return Err(Error::NotFound);
+    }
+
+    if catalog.creates_cycle(&catalogs) {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Catalog `{}` can not be nested into one of its descendants",
+                catalog.id
+            ),
+        ));
+    }
+
+    check_references(&state, &catalog).await?;
+
+    state.services.collections.upsert_catalog(&catalog).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete a catalog, its nested catalogs move up to its parent
+async fn remove(
+    State(state): State<AppState>,
+    Path(catalog_id): Path<String>,
+) -> Result<StatusCode> {
+    let catalogs = state.services.collections.list_catalogs().await?;
+
+    let catalog = catalogs
+        .iter()
+        .find(|c| c.id == catalog_id)
+        .ok_or(Error::NotFound)?;
+
+    for child in catalogs
+        .iter()
+        .filter(|c| c.parent.as_ref() == Some(&catalog.id))
+    {
+        let mut child = child.to_owned();
+        child.parent = catalog.parent.to_owned();
+        state.services.collections.upsert_catalog(&child).await?;
+    }
+
+    state
+        .services
+        .collections
+        .delete_catalog(&catalog_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List all catalogs, nested ones included
+async fn catalogs(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+) -> Result<Json<Catalogs>> {
+    let catalogs = state.services.collections.list_catalogs().await?;
+
+    let links = LinkBuilder::new(&url).mediatype(JSON);
+
+    let mut listed = catalogs.to_owned();
+    for catalog in listed.iter_mut() {
+        catalog.links = vec![
+            links
+                .link(&format!("catalogs/{}", catalog.id), SELF)?
+                .mediatype(JSON),
+            links.link(".", ROOT)?,
+        ];
+        if let Some(parent) = &catalog.parent {
+            catalog
+                .links
+                .push(links.link(&format!("catalogs/{parent}"), PARENT)?);
+        }
+        catalog.links.resolve_relative_links();
+    }
+
+    Ok(Json(Catalogs {
+        links: vec![
+            links.self_link().title("this document"),
+            links.link(".", ROOT)?,
+        ],
+        catalogs: listed,
+    }))
+}
+
+/// Checks that the parent and the grouped collections exist
+async fn check_references(state: &AppState, catalog: &Catalog) -> Result<()> {
+    let mut problems = Vec::new();
+
+    if let Some(parent) = &catalog.parent {
+        if state
+            .services
+            .collections
+            .read_catalog(parent)
+            .await?
+            .is_none()
+        {
+            problems.push(format!("Unknown parent catalog `{parent}`"));
+        }
+    }
+
+    for collection in &catalog.collections {
+        if state
+            .services
+            .collections
+            .read_collection(collection)
+            .await?
+            .is_none()
+        {
+            problems.push(format!("Unknown collection `{collection}`"));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Invalid(problems))
+    }
+}
+
+/// Links of a catalog at `url` to its parent, nested catalogs and collections
+fn link_catalog(catalog: &mut Catalog, catalogs: &[Catalog], url: &Url) -> Result<()> {
+    let links = LinkBuilder::new(url).mediatype(JSON);
+
+    catalog
+        .links
+        .insert_or_update(&[links.self_link(), links.link("..", ROOT)?]);
+
+    if let Some(parent) = &catalog.parent {
+        catalog
+            .links
+            .insert_or_update(&[links.link(parent, PARENT)?]);
+    }
+
+    for child in catalogs
+        .iter()
+        .filter(|c| c.parent.as_ref() == Some(&catalog.id))
+    {
+        let mut link = links.link(&child.id, CHILD)?;
+        if let Some(title) = &child.title {
+            link = link.title(title);
+        }
+        catalog.links.push(link);
+    }
+
+    for collection in &catalog.collections {
+        catalog
+            .links
+            .push(links.link(&format!("../collections/{collection}"), CHILD)?);
+    }
+
+    catalog.links.resolve_relative_links();
+
+    Ok(())
+}
+
+/// Links of the landing page at `url` to the top level catalogs
+pub(crate) async fn root_links(state: &AppState, url: &Url) -> Result<Vec<Link>> {
+    let catalogs = state.services.collections.list_catalogs().await?;
+
+    let links = LinkBuilder::new(url).mediatype(JSON);
+
+    let mut root_links = Vec::new();
+    for catalog in catalogs.iter().filter(|c| c.parent.is_none()) {
+        let mut link = links.link(&format!("catalogs/{}", catalog.id), CHILD)?;
+        if let Some(title) = &catalog.title {
+            link = link.title(title);
+        }
+        root_links.push(link);
+    }
+
+    Ok(root_links)
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
+        .route("/catalogs", get(catalogs).post(create))
+        .route(
+            "/catalogs/:catalog_id",
+            get(read).put(update).delete(remove),
+        );
+
+    Module::new(router).link(
+        Link::new("catalogs", DATA)
+            .title("Catalogs organizing the collections into groups")
+            .mediatype(JSON),
+    )
+}
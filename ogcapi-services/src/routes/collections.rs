@@ -1,19 +1,29 @@
 use axum::{
-    extract::{Path, State},
-    http::{header::LOCATION, StatusCode},
+    extract::{Path, Query, State},
+    http::{
+        header::{ACCEPT, CONTENT_TYPE, LOCATION},
+        StatusCode,
+    },
+    response::{IntoResponse, Response},
     Json,
     {routing::get, Router},
 };
+use chrono::Utc;
 use hyper::HeaderMap;
+use url::Url;
 
-use ogcapi_types::common::{
-    link_rel::{DATA, ITEMS, ROOT, SELF},
-    media_type::{GEO_JSON, JSON},
-    Collection, Collections, Crs, Link, Linked, Query,
+use ogcapi_types::{
+    common::{
+        link_rel::{ATERNATE, DATA, DESCRIBEDBY, ITEMS, NEXT, PREV, ROOT, SELF},
+        media_type::{JSON, JSON_LD, TURTLE, XML},
+        Collection, Collections, Crs, Link, LinkBuilder, Linked, Query as CollectionQuery,
+    },
+    jsonld, metadata,
 };
 
 use crate::{
-    extractors::{Qs, RemoteUrl},
+    extractors::{DryRun, RemoteUrl, StrictQs},
+    routes::{wants_json_ld, DryRunReport, Format, Module},
     AppState, Error, Result,
 };
 
@@ -27,10 +37,14 @@ const CONFORMANCE: [&str; 3] = [
 async fn create(
     State(state): State<AppState>,
     RemoteUrl(url): RemoteUrl,
-    Json(collection): Json<Collection>,
-) -> Result<(StatusCode, HeaderMap)> {
+    DryRun(dry_run): DryRun,
+    Json(mut collection): Json<Collection>,
+) -> Result<Response> {
+    collection.normalize_crs();
+    collection.validate().map_err(Error::Invalid)?;
+
     if state
-        .drivers
+        .services
         .collections
         .read_collection(&collection.id)
         .await?
@@ -42,8 +56,17 @@ async fn create(
         ));
     }
 
+    if dry_run {
+        let location = url.join(&format!("collections/{}", collection.id))?;
+
+        let mut report = DryRunReport::new("create", StatusCode::CREATED);
+        report.location = Some(location.to_string());
+
+        return Ok(report.into_response());
+    }
+
     let id = state
-        .drivers
+        .services
         .collections
         .create_collection(&collection)
         .await?;
@@ -53,7 +76,7 @@ async fn create(
     let mut headers = HeaderMap::new();
     headers.insert(LOCATION, location.as_str().parse().unwrap());
 
-    Ok((StatusCode::CREATED, headers))
+    Ok((StatusCode::CREATED, headers).into_response())
 }
 
 /// Get collection metadata
@@ -61,116 +84,276 @@ async fn read(
     State(state): State<AppState>,
     Path(collection_id): Path<String>,
     RemoteUrl(url): RemoteUrl,
-) -> Result<Json<Collection>> {
+    Query(format): Query<Format>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let mut collection = linked_collection(&state, &collection_id, &url).await?;
+
+    let links = LinkBuilder::new(&url);
+
+    collection
+        .links
+        .insert_or_update(&[links.query(ATERNATE, Some("f=jsonld")).mediatype(JSON_LD)]);
+
+    // both records share the relation
+    for (f, mediatype, title) in [
+        ("dcat", TURTLE, "DCAT-AP record"),
+        ("iso19139", XML, "ISO 19139 record"),
+    ] {
+        let link = links
+            .link(&format!("{collection_id}/metadata?f={f}"), DESCRIBEDBY)?
+            .mediatype(mediatype)
+            .title(title);
+        if !collection.links.iter().any(|l| l.href == link.href) {
+            collection.links.push(link);
+        }
+    }
+
+    if wants_json_ld(&headers, format.f.as_deref()) {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, JSON_LD.parse().unwrap());
+        return Ok((headers, Json(jsonld::collection(&collection))).into_response());
+    }
+
+    Ok(Json(collection).into_response())
+}
+
+/// Get the collection metadata as catalog record, `f=dcat` for DCAT-AP as
+/// Turtle or `f=iso19139` for ISO 19115 as XML
+async fn metadata(
+    State(state): State<AppState>,
+    Path(collection_id): Path<String>,
+    RemoteUrl(url): RemoteUrl,
+    Query(format): Query<Format>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let accepts = |media_type: &str| {
+        headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| v.split(',').any(|m| m.trim().starts_with(media_type)))
+    };
+    let iso19139 = match format.f.as_deref() {
+        Some("dcat") => false,
+        Some("iso19139") => true,
+        Some(f) => {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Unknown metadata format `{f}`, expected `dcat` or `iso19139`"),
+            ))
+        }
+        None => !accepts(TURTLE) && (accepts(XML) || accepts("text/xml")),
+    };
+
+    let collection_url = url.join(&format!("../{collection_id}"))?;
+    let collection = linked_collection(&state, &collection_id, &collection_url).await?;
+
+    let mut headers = HeaderMap::new();
+    if iso19139 {
+        headers.insert(CONTENT_TYPE, XML.parse().unwrap());
+        Ok((headers, metadata::iso19139(&collection, Utc::now())).into_response())
+    } else {
+        headers.insert(CONTENT_TYPE, TURTLE.parse().unwrap());
+        Ok((headers, metadata::dcat(&collection)).into_response())
+    }
+}
+
+/// Read a collection with its links resolved against the collection `url`
+async fn linked_collection(state: &AppState, collection_id: &str, url: &Url) -> Result<Collection> {
     let mut collection = state
-        .drivers
+        .services
         .collections
-        .read_collection(&collection_id)
+        .read_collection(collection_id)
         .await?
         .ok_or(Error::NotFound)?;
 
-    collection.links.insert_or_update(&[
-        Link::new(&url, SELF),
-        Link::new(url.join("..")?, ROOT).mediatype(JSON),
-    ]);
+    let links = LinkBuilder::new(url);
+
+    collection
+        .links
+        .insert_or_update(&[links.self_link(), links.link("..", ROOT)?]);
 
     #[cfg(not(feature = "stac"))]
-    collection.links.insert_or_update(&[Link::new(
-        &url.join(&format!("{}/items", collection.id))?,
-        ITEMS,
-    )
-    .mediatype(GEO_JSON)]);
+    collection
+        .links
+        .insert_or_update(&[links.link(&format!("{}/items", collection.id), ITEMS)?]);
+
+    #[cfg(feature = "features")]
+    collection.links.insert_or_update(&[links.link(
+        &format!("{}/queryables", collection.id),
+        ogcapi_types::common::link_rel::QUERYABLES,
+    )?]);
+
+    #[cfg(feature = "coverages")]
+    if state.drivers.rasters.grid(&collection.id).await?.is_some() {
+        collection.links.insert_or_update(&[links.link(
+            &format!("{}/coverage", collection.id),
+            ogcapi_types::common::link_rel::COVERAGE,
+        )?]);
+        #[cfg(feature = "maps")]
+        collection.links.insert_or_update(&[links.link(
+            &format!("{}/map", collection.id),
+            ogcapi_types::common::link_rel::MAP,
+        )?]);
+    }
 
     #[cfg(feature = "stac")]
     if collection.r#type == "Collection" {
-        collection.links.insert_or_update(&[Link::new(
-            url.join(&format!("{}/items", collection.id))?,
-            ITEMS,
-        )
-        .mediatype(GEO_JSON)]);
+        collection
+            .links
+            .insert_or_update(&[links.link(&format!("{}/items", collection.id), ITEMS)?]);
     }
 
     collection.links.resolve_relative_links();
 
-    Ok(Json(collection))
+    Ok(collection)
 }
 
 /// Update collection metadata
 async fn update(
     State(state): State<AppState>,
     Path(collection_id): Path<String>,
+    DryRun(dry_run): DryRun,
     Json(mut collection): Json<Collection>,
-) -> Result<StatusCode> {
+) -> Result<Response> {
     collection.id = collection_id;
+    collection.normalize_crs();
+    collection.validate().map_err(Error::Invalid)?;
+
+    let current = state
+        .services
+        .collections
+        .read_collection(&collection.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // geometries are stored in the storage crs
+    if collection.storage_crs != current.storage_crs {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            "The storage crs of a collection can not be changed".to_string(),
+        ));
+    }
+
+    if dry_run {
+        return Ok(DryRunReport::new("replace", StatusCode::NO_CONTENT).into_response());
+    }
 
     state
-        .drivers
+        .services
         .collections
         .update_collection(&collection)
         .await?;
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
 
 /// Delete collection metadata
 async fn remove(
     Path(collection_id): Path<String>,
     State(state): State<AppState>,
-) -> Result<StatusCode> {
+    DryRun(dry_run): DryRun,
+) -> Result<Response> {
+    if dry_run {
+        state
+            .services
+            .collections
+            .read_collection(&collection_id)
+            .await?
+            .ok_or(Error::NotFound)?;
+
+        return Ok(DryRunReport::new("delete", StatusCode::NO_CONTENT).into_response());
+    }
+
     state
-        .drivers
+        .services
         .collections
         .delete_collection(&collection_id)
         .await?;
 
-    Ok(StatusCode::NO_CONTENT)
+    // ungroup the collection
+    for mut catalog in state.services.collections.list_catalogs().await? {
+        if catalog.collections.contains(&collection_id) {
+            catalog.collections.retain(|c| c != &collection_id);
+            state.services.collections.upsert_catalog(&catalog).await?;
+        }
+    }
+
+    #[cfg(feature = "features")]
+    state.extents.invalidate(&collection_id);
+
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
 
 async fn collections(
-    Qs(query): Qs<Query>,
+    StrictQs(mut query): StrictQs<CollectionQuery>,
     RemoteUrl(url): RemoteUrl,
     State(state): State<AppState>,
 ) -> Result<Json<Collections>> {
-    let mut collections = state.drivers.collections.list_collections(&query).await?;
+    if query.limit.is_some_and(|l| l < 1) || query.offset.is_some_and(|o| o < 0) {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            "`limit` has to be positive and `offset` not negative".to_string(),
+        ));
+    }
+
+    // page large numbers of collections like features
+    let max_limit = state.guardrails.max_limit as isize;
+    query.limit = Some(query.limit.map_or(100, |limit| limit.min(max_limit)));
+    let (limit, offset) = (query.limit.unwrap_or(100), query.offset.unwrap_or(0));
+
+    let mut collections = state.services.collections.list_collections(&query).await?;
+
+    let links = LinkBuilder::new(&url).mediatype(JSON);
 
     for collection in collections.collections.iter_mut() {
         collection.links.insert_or_update(&[
-            Link::new(url.join(&format!("collections/{}", collection.id))?, SELF).mediatype(JSON),
-            Link::new(url.join(".")?, ROOT).mediatype(JSON),
-            Link::new(
-                url.join(&format!("collections/{}/items", collection.id))?,
-                ITEMS,
-            )
-            .mediatype(GEO_JSON),
+            links
+                .link(&format!("collections/{}", collection.id), SELF)?
+                .mediatype(JSON),
+            links.link(".", ROOT)?,
+            links.link(&format!("collections/{}/items", collection.id), ITEMS)?,
         ]);
 
         collection.links.resolve_relative_links()
     }
 
     collections.links = vec![
-        Link::new(&url, SELF).mediatype(JSON).title("this document"),
-        Link::new(url.join(".")?, ROOT).mediatype(JSON),
+        links.self_link().title("this document"),
+        links.link(".", ROOT)?,
     ];
 
+    // pagination
+    if offset != 0 {
+        let previous = links.page(PREV, (offset - limit).max(0) as usize, limit as usize);
+        collections.links.push(previous);
+    }
+    if collections
+        .number_matched
+        .is_some_and(|matched| matched > (offset + limit) as u64)
+    {
+        let next = links.page(NEXT, (offset + limit) as usize, limit as usize);
+        collections.links.push(next);
+    }
+
     collections.crs = vec![Crs::default(), Crs::from_epsg(3857)];
 
     Ok(Json(collections))
 }
 
-pub(crate) fn router(state: &AppState) -> Router<AppState> {
-    let mut root = state.root.write().unwrap();
-    root.links.push(
-        Link::new("collections", DATA)
-            .title("Metadata about the resource collections")
-            .mediatype(JSON),
-    );
-
-    state.conformance.write().unwrap().extend(&CONFORMANCE);
-
-    Router::new()
+pub(crate) fn module() -> Module {
+    let router = Router::new()
         .route("/collections", get(collections).post(create))
         .route(
             "/collections/:collection_id",
             get(read).put(update).delete(remove),
         )
+        .route("/collections/:collection_id/metadata", get(metadata));
+
+    Module::new(router).conformance(&CONFORMANCE).link(
+        Link::new("collections", DATA)
+            .title("Metadata about the resource collections")
+            .mediatype(JSON),
+    )
 }
@@ -0,0 +1,285 @@
+use axum::{
+    extract::{Path, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use ogcapi_drivers::raster::{values, Grid, RasterRequest, MAX_SIDE, MAX_VALUES};
+use ogcapi_types::common::{
+    media_type::{COG, JPEG, PNG},
+    Bbox, Crs,
+};
+
+use crate::{access::deny_restricted, extractors::Qs, routes::Module, AppState, Error, Result};
+
+const CONFORMANCE: [&str; 4] = [
+    "http://www.opengis.net/spec/ogcapi-coverages-1/1.0/conf/core",
+    "http://www.opengis.net/spec/ogcapi-coverages-1/1.0/conf/geodata-coverage",
+    "http://www.opengis.net/spec/ogcapi-coverages-1/1.0/conf/geotiff",
+    "http://www.opengis.net/spec/ogcapi-coverages-1/1.0/conf/png",
+];
+
+/// Parameters of a coverage subset
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct CoverageQuery {
+    /// Bounds of the subset, in `bbox-crs`
+    pub(crate) bbox: Option<String>,
+    /// Crs of the bounds, defaults to `CRS84`
+    pub(crate) bbox_crs: Option<String>,
+    /// Crs of the result, defaults to the native crs
+    pub(crate) crs: Option<String>,
+    /// Size of the result in pixels
+    pub(crate) width: Option<usize>,
+    pub(crate) height: Option<usize>,
+    /// Comma separated names of the bands, all if omitted
+    pub(crate) properties: Option<String>,
+    /// Output format, `tif` (default, Cloud Optimized GeoTIFF), `png` or `jpeg`
+    pub(crate) f: Option<String>,
+}
+
+impl CoverageQuery {
+    /// Read request for the raster of a grid
+    pub(crate) fn raster_request(&self, grid: &Grid) -> Result<RasterRequest> {
+        let bbox = match &self.bbox {
+            Some(bbox) => match bbox.parse::<Bbox>() {
+                Ok(Bbox::Bbox2D(bbox)) if bbox[0] < bbox[2] && bbox[1] < bbox[3] => Some(bbox),
+                Ok(Bbox::Bbox3D(bbox)) if bbox[0] < bbox[3] && bbox[1] < bbox[4] => {
+                    Some([bbox[0], bbox[1], bbox[3], bbox[4]])
+                }
+                _ => return Err(bad_request(format!("Invalid bbox `{bbox}`"))),
+            },
+            None => None,
+        };
+
+        let bands = match &self.properties {
+            Some(properties) => {
+                let mut bands = Vec::new();
+                for name in properties.split(',').map(str::trim) {
+                    let band = grid
+                        .bands
+                        .iter()
+                        .position(|b| b.name == name)
+                        .ok_or_else(|| bad_request(format!("Unknown property `{name}`")))?;
+                    bands.push(band + 1);
+                }
+                Some(bands)
+            }
+            None => None,
+        };
+
+        let request = RasterRequest {
+            bbox,
+            bbox_crs: bbox
+                .map(|_| parse_crs(self.bbox_crs.as_deref()))
+                .transpose()?
+                .map(Option::unwrap_or_default),
+            crs: parse_crs(self.crs.as_deref())?,
+            width: self.width,
+            height: self.height,
+            bands,
+        };
+
+        // explicit sizes are checked upfront, derived ones fit the limit
+        let count = request.bands.as_ref().map_or(grid.bands.len(), Vec::len);
+        let warped = request.crs.as_ref().is_some_and(|crs| *crs != grid.crs);
+        let values = values(
+            self.width.unwrap_or(1),
+            self.height.unwrap_or(1),
+            if warped { grid.bands.len() } else { count },
+        );
+        if [self.width, self.height]
+            .iter()
+            .flatten()
+            .any(|side| !(1..=MAX_SIDE).contains(side))
+            || values.filter(|values| *values <= MAX_VALUES).is_none()
+        {
+            return Err(bad_request(format!(
+                "Requested size exceeds the limit of {MAX_SIDE} pixels per side \
+                or {MAX_VALUES} values"
+            )));
+        }
+
+        Ok(request)
+    }
+
+    /// Media type of the requested format
+    fn media_type(&self) -> Result<&'static str> {
+        match self.f.as_deref() {
+            None | Some("tif" | "tiff" | "geotiff" | "cog") => Ok(COG),
+            Some("png") => Ok(PNG),
+            Some("jpg" | "jpeg") => Ok(JPEG),
+            Some(f) => Err(bad_request(format!("Unsupported format `{f}`"))),
+        }
+    }
+}
+
+/// Subset of the coverage of a collection
+async fn coverage(
+    State(state): State<AppState>,
+    Path(collection_id): Path<String>,
+    Qs(query): Qs<CoverageQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+    let grid = read_grid(&state, &collection_id, &headers).await?;
+
+    let media_type = query.media_type()?;
+    let request = query.raster_request(&grid)?;
+
+    let raster = state.drivers.rasters.read(&collection_id, &request).await?;
+    let bytes = state.drivers.rasters.encode(&raster, media_type).await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, media_type.parse().unwrap());
+    headers.insert("Content-Crs", raster.crs.to_string().parse().unwrap());
+    // saved with a name GIS applications recognize
+    if media_type == COG {
+        if let Ok(value) = format!("attachment; filename=\"{collection_id}.tif\"").parse() {
+            headers.insert(CONTENT_DISPOSITION, value);
+        }
+    }
+
+    Ok((headers, bytes))
+}
+
+/// Grid of the coverage of a collection
+async fn domain_set(
+    State(state): State<AppState>,
+    Path(collection_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Value>> {
+    let grid = read_grid(&state, &collection_id, &headers).await?;
+
+    let (rx, ry) = grid.resolution();
+    let labels = if grid.crs == Crs::default() {
+        ["Lon", "Lat"]
+    } else {
+        ["E", "N"]
+    };
+
+    Ok(Json(json!({
+        "type": "DomainSet",
+        "generalGrid": {
+            "type": "GeneralGridCoverage",
+            "srsName": grid.crs.to_string(),
+            "axisLabels": labels,
+            "axis": [
+                {
+                    "type": "RegularAxis",
+                    "axisLabel": labels[0],
+                    "lowerBound": grid.bbox[0],
+                    "upperBound": grid.bbox[2],
+                    "resolution": rx,
+                },
+                {
+                    "type": "RegularAxis",
+                    "axisLabel": labels[1],
+                    "lowerBound": grid.bbox[1],
+                    "upperBound": grid.bbox[3],
+                    "resolution": -ry,
+                }
+            ],
+            "gridLimits": {
+                "type": "GridLimits",
+                "srsName": "http://www.opengis.net/def/crs/OGC/0/Index2D",
+                "axisLabels": ["i", "j"],
+                "axis": [
+                    {
+                        "type": "IndexAxis",
+                        "axisLabel": "i",
+                        "lowerBound": 0,
+                        "upperBound": grid.width.saturating_sub(1),
+                    },
+                    {
+                        "type": "IndexAxis",
+                        "axisLabel": "j",
+                        "lowerBound": 0,
+                        "upperBound": grid.height.saturating_sub(1),
+                    }
+                ]
+            }
+        }
+    })))
+}
+
+/// Bands of the coverage of a collection
+async fn range_type(
+    State(state): State<AppState>,
+    Path(collection_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Value>> {
+    let grid = read_grid(&state, &collection_id, &headers).await?;
+
+    let fields: Vec<Value> = grid
+        .bands
+        .iter()
+        .map(|band| {
+            let mut field = json!({
+                "type": "Quantity",
+                "id": band.name,
+                "name": band.name,
+                "dataType": band.data_type.name(),
+            });
+            if let Some(unit) = &band.unit {
+                field["uom"] = json!({ "type": "UnitReference", "code": unit });
+            }
+            if let Some(nodata) = band.nodata {
+                field["nilValues"] = json!([{ "value": nodata, "reason": "nodata" }]);
+            }
+            field
+        })
+        .collect();
+
+    Ok(Json(json!({ "type": "DataRecord", "field": fields })))
+}
+
+/// Grid of the raster of a collection, refusing users restricted to features
+pub(crate) async fn read_grid(
+    state: &AppState,
+    collection_id: &str,
+    headers: &HeaderMap,
+) -> Result<Grid> {
+    // rasters can't be restricted to the accessible features
+    deny_restricted(state, headers, &[collection_id]).await?;
+
+    state
+        .drivers
+        .rasters
+        .grid(collection_id)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+fn parse_crs(crs: Option<&str>) -> Result<Option<Crs>> {
+    crs.map(|crs| match crs.parse::<Crs>() {
+        Ok(crs) if crs.is_valid() => Ok(crs),
+        _ => Err(bad_request(format!("Invalid crs `{crs}`"))),
+    })
+    .transpose()
+}
+
+fn bad_request(message: String) -> Error {
+    Error::Exception(StatusCode::BAD_REQUEST, message)
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
+        .route("/collections/:collection_id/coverage", get(coverage))
+        .route(
+            "/collections/:collection_id/coverage/domainset",
+            get(domain_set),
+        )
+        .route(
+            "/collections/:collection_id/coverage/rangetype",
+            get(range_type),
+        );
+
+    Module::new(router).conformance(&CONFORMANCE)
+}
@@ -7,13 +7,15 @@ use axum::{
 use hyper::HeaderMap;
 
 use ogcapi_types::{
-    common::{link_rel::SELF, media_type::GEO_JSON, Link},
+    common::{link_rel::SELF, media_type::GEO_JSON, LinkBuilder},
     edr::{Query, QueryType},
     features::FeatureCollection,
 };
 
 use crate::{
-    extractors::{Qs, RemoteUrl},
+    access::deny_restricted,
+    extractors::{RemoteUrl, StrictQs},
+    routes::Module,
     AppState, Result,
 };
 
@@ -31,27 +33,33 @@ const CONFORMANCE: [&str; 8] = [
 
 async fn query(
     Path((collection_id, query_type)): Path<(String, QueryType)>,
-    Qs(query): Qs<Query>,
+    StrictQs(query): StrictQs<Query>,
     RemoteUrl(url): RemoteUrl,
     State(state): State<AppState>,
+    request_headers: HeaderMap,
 ) -> Result<(HeaderMap, Json<FeatureCollection>)> {
     tracing::debug!("{:#?}", query);
 
+    deny_restricted(&state, &request_headers, &[&collection_id]).await?;
+
     let mut fc = state
-        .drivers
+        .services
         .edr
         .query(&collection_id, &query_type, &query)
         .await?;
 
+    let links = LinkBuilder::new(&url);
+
     for feature in fc.features.iter_mut() {
-        feature.links = vec![Link::new(
-            url.join(&format!(
-                "items/{}",
-                feature.id.as_ref().expect("Feature should have id")
-            ))?,
-            SELF,
-        )
-        .mediatype(GEO_JSON)]
+        feature.links = vec![links
+            .link(
+                &format!(
+                    "items/{}",
+                    feature.id.as_ref().expect("Feature should have id")
+                ),
+                SELF,
+            )?
+            .mediatype(GEO_JSON)]
     }
 
     let mut headers = HeaderMap::new();
@@ -65,11 +73,11 @@ async fn query(
 
 // async fn instance() {}
 
-pub(crate) fn router(state: &AppState) -> Router<AppState> {
-    state.conformance.write().unwrap().extend(&CONFORMANCE);
-
-    Router::new().route("/collections/:collection_id/:query_type", get(query))
+pub(crate) fn module() -> Module {
+    let router = Router::new().route("/collections/:collection_id/:query_type", get(query));
     // .route("/collections/:collection_id/instances", get(instances))
     // .route("/collections/:collection_id/instances/:instance_id", get(instance))
     // .route("/collections/:collection_id/instances/:instance_id/:query_type", get(instance))
+
+    Module::new(router).conformance(&CONFORMANCE)
 }
@@ -1,79 +1,450 @@
 use anyhow::Context;
 use axum::{
-    extract::{Path, State},
+    body::{Body, Bytes},
+    extract::{FromRequest, Path, Request, State},
     http::{
-        header::{CONTENT_TYPE, LOCATION},
-        HeaderMap, StatusCode,
+        header::{ACCEPT, CONTENT_TYPE, LOCATION},
+        HeaderMap, HeaderName, StatusCode, Uri,
     },
-    routing::get,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
     Json, Router,
 };
+use chrono::{DateTime, Utc};
+use futures::{stream::BoxStream, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::mpsc;
+use url::Url;
 
+use ogcapi_drivers::{transform::transformer, wkb};
 use ogcapi_types::{
     common::{
-        link_rel::{COLLECTION, NEXT, PREV, ROOT, SELF},
-        media_type::{GEO_JSON, JSON},
-        Collection, Crs, Link, Linked,
+        link_rel::{ATERNATE, COLLECTION, NEXT, PREV, RELATED, ROOT, SELF},
+        media_type::{ATOM, FORM, GEO_JSON, GEO_JSON_SEQ, JSON, JSON_LD, SCHEMA_JSON, WKB},
+        Bbox, Collection, Crs, LinkBuilder, Linked,
+    },
+    cql2::{self, Expr},
+    features::{
+        Changeset, CollectionStats, Feature, FeatureCollection, LoggedChange, PropertyValues,
+        Query, Queryables, Relation, ResultType, Severity, StatsQuery, ValidationReport,
+        ValuesQuery, Violation,
     },
-    features::{Feature, FeatureCollection, Query},
+    feeds, jsonld,
 };
 
 use crate::{
-    extractors::{Qs, RemoteUrl},
+    access::{access_filter, deny_restricted, hidden_properties, read_filter, request_user},
+    extractors::{DryRun, IfVersion, Qs, RemoteUrl, ShareLink},
+    metering,
+    routes::{wants_json_ld, DryRunReport, Module},
     AppState, Error, Result,
 };
 
-const CONFORMANCE: [&str; 4] = [
+const CONFORMANCE: [&str; 5] = [
     "http://www.opengis.net/spec/ogcapi-features-1/1.0/conf/core",
     "http://www.opengis.net/spec/ogcapi-features-1/1.0/conf/oas30",
     "http://www.opengis.net/spec/ogcapi-features-1/1.0/conf/geojson",
     "http://www.opengis.net/spec/ogcapi-features-2/1.0/conf/crs",
+    "http://www.opengis.net/spec/ogcapi-features-3/1.0/conf/queryables",
 ];
 
+/// Number of features checked at once on bulk ingest
+const BATCH_SIZE: usize = 1000;
+
+/// Number of changes listed without `limit`
+const CHANGES_LIMIT: usize = 100;
+
+/// Header with the number of validation warnings of accepted features
+const VALIDATION_WARNINGS: HeaderName = HeaderName::from_static("validation-warnings");
+
+/// RFC 8142 record separator
+const RS: u8 = 0x1e;
+
 async fn create(
     State(state): State<AppState>,
     RemoteUrl(url): RemoteUrl,
     Path(collection_id): Path<String>,
-    Json(mut feature): Json<Feature>,
-) -> Result<(StatusCode, HeaderMap)> {
-    feature.collection = Some(collection_id);
+    DryRun(dry_run): DryRun,
+    share: ShareLink,
+    request: Request,
+) -> Result<Response> {
+    // query posted as form, e.g. for lists of ids too long for the url
+    if has_media_type(request.headers(), CONTENT_TYPE, FORM) {
+        let headers = request.headers().to_owned();
+        let body = Bytes::from_request(request, &state)
+            .await
+            .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.body_text()))?;
+        let query: Query = serde_qs::from_bytes(&body)
+            .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+        let has_crs = url::form_urlencoded::parse(&body).any(|(key, _)| key == "crs");
+        // pages are linked with the posted parameters
+        let mut url = url;
+        url.set_query(std::str::from_utf8(&body).ok());
+        return list_items(&state, url, &collection_id, query, share, &headers, has_crs).await;
+    }
+
+    let filter = access_filter(&state, request.headers(), &collection_id).await?;
+
+    if has_media_type(request.headers(), CONTENT_TYPE, GEO_JSON_SEQ) {
+        let body = request.into_body();
+        return ingest(&state, &collection_id, body, filter.as_ref(), dry_run).await;
+    }
+
+    let Json(value) = Json::<serde_json::Value>::from_request(request, &state)
+        .await
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.body_text()))?;
+    let mut feature = parse_feature(value)?;
+
+    feature.collection = Some(collection_id.to_owned());
+    if is_versioned(&state, &collection_id).await? {
+        feature.set_version(1);
+    }
+
+    if let Some(filter) = filter.as_ref() {
+        check_access(
+            &state,
+            &collection_id,
+            std::slice::from_ref(&feature),
+            filter,
+        )
+        .await?;
+    }
+
+    let warnings = validate(&state, &collection_id, std::slice::from_ref(&feature)).await?;
+
+    if dry_run {
+        check_writable(&state, &collection_id).await?;
 
-    let id = state.drivers.features.create_feature(&feature).await?;
+        if let Some(id) = feature.id.as_ref() {
+            if is_accessible(&state, &collection_id, id, None).await? {
+                return Err(Error::Exception(
+                    StatusCode::CONFLICT,
+                    format!("Feature with id `{id}` already exists."),
+                ));
+            }
+        }
+
+        let mut report = DryRunReport::new("create", StatusCode::CREATED);
+        if let Some(id) = feature.id.as_ref() {
+            report.location = Some(url.join(&format!("items/{}", id))?.to_string());
+        }
+
+        return Ok(report.into_response());
+    }
+
+    let id = state.services.features.create_feature(&feature).await?;
+    state.extents.invalidate(&collection_id);
 
     let location = url.join(&format!("items/{}", id))?;
 
     let mut headers = HeaderMap::new();
     headers.insert(LOCATION, location.as_str().parse().unwrap());
+    if warnings > 0 {
+        headers.insert(VALIDATION_WARNINGS, warnings.into());
+    }
+
+    Ok((StatusCode::CREATED, headers).into_response())
+}
+
+/// Bulk ingest of a `GeoJSON` text sequence
+///
+/// Records are separated by the record separator `RS`, features are checked
+/// and inserted in batches as the body streams in, all within one
+/// transaction. A sequence with an invalid feature is rejected as a whole.
+///
+/// The body may be compressed with `gzip` or `zstd`:
+///
+/// ```bash
+/// gzip -c countries.geojsonseq | curl http://localhost:8484/collections/countries/items \
+///         -H 'Content-Type: application/geo+json-seq' \
+///         -H 'Content-Encoding: gzip' \
+///         --data-binary @-
+/// ```
+///
+/// In dry-run mode, the whole sequence is parsed and counted without
+/// inserting anything.
+async fn ingest(
+    state: &AppState,
+    collection_id: &str,
+    body: Body,
+    filter: Option<&Expr>,
+    dry_run: bool,
+) -> Result<Response> {
+    if dry_run {
+        check_writable(state, collection_id).await?;
+    }
+
+    let versioned = is_versioned(state, collection_id).await?;
+
+    let (sender, receiver) = mpsc::channel(1);
+
+    let read = async {
+        let result = read_batches(state, collection_id, body, filter, versioned, &sender).await;
+        if result.is_err() {
+            // roll back the batches inserted so far
+            let _ = sender.send(Err(anyhow::anyhow!("Ingest aborted"))).await;
+        }
+        drop(sender);
+        result
+    };
+
+    let batches = futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|batch| (batch, receiver))
+    });
+
+    let create = async {
+        if dry_run {
+            batches.for_each(|_| async {}).await;
+            Ok(0)
+        } else {
+            state
+                .services
+                .features
+                .create_feature_batches(collection_id, batches.boxed(), &Crs::default())
+                .await
+        }
+    };
+
+    let (read, created) = tokio::join!(read, create);
+    let (count, warnings) = read?;
+    created?;
+
+    if dry_run {
+        let mut report = DryRunReport::new("create", StatusCode::CREATED);
+        report.number_created = Some(count);
+        return Ok(report.into_response());
+    }
+
+    if count > 0 {
+        state.extents.invalidate(collection_id);
+    }
+
+    let mut headers = HeaderMap::new();
+    if warnings > 0 {
+        headers.insert(VALIDATION_WARNINGS, warnings.into());
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        headers,
+        Json(json!({ "numberCreated": count })),
+    )
+        .into_response())
+}
+
+/// Read the records of a `GeoJSON` text sequence, sending the features in
+/// checked batches of [BATCH_SIZE], returns the number of features and
+/// validation warnings
+async fn read_batches(
+    state: &AppState,
+    collection_id: &str,
+    body: Body,
+    filter: Option<&Expr>,
+    versioned: bool,
+    sender: &mpsc::Sender<anyhow::Result<Vec<Feature>>>,
+) -> Result<(usize, usize)> {
+    let mut stream = body.into_data_stream();
+
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut batch = Vec::new();
+    let mut count = 0;
+    let mut warnings = 0;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(pos) = buffer.iter().position(|b| *b == RS) {
+            let record: Vec<u8> = buffer.drain(..=pos).collect();
+            if let Some(mut feature) = parse_record(&record, collection_id)? {
+                if versioned {
+                    feature.set_version(1);
+                }
+                batch.push(feature);
+            }
+
+            if batch.len() >= BATCH_SIZE {
+                warnings += check_batch(state, collection_id, &batch, filter).await?;
+                count += batch.len();
+                // the receiver is only gone if inserting failed, which is
+                // reported instead
+                if sender.send(Ok(std::mem::take(&mut batch))).await.is_err() {
+                    return Ok((count, warnings));
+                }
+            }
+        }
+    }
 
-    Ok((StatusCode::CREATED, headers))
+    if let Some(mut feature) = parse_record(&buffer, collection_id)? {
+        if versioned {
+            feature.set_version(1);
+        }
+        batch.push(feature);
+    }
+
+    if !batch.is_empty() {
+        warnings += check_batch(state, collection_id, &batch, filter).await?;
+        count += batch.len();
+        let _ = sender.send(Ok(batch)).await;
+    }
+
+    Ok((count, warnings))
+}
+
+/// Whether the features of a collection carry versions
+async fn is_versioned(state: &AppState, collection_id: &str) -> Result<bool> {
+    let collection = state
+        .services
+        .collections
+        .read_collection(collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(collection.versioned)
+}
+
+fn parse_record(record: &[u8], collection_id: &str) -> Result<Option<Feature>> {
+    let text = std::str::from_utf8(record)
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?
+        .trim_matches(|c: char| c.is_whitespace() || c == RS as char);
+
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let value = serde_json::from_str(text)
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+    let mut feature = parse_feature(value)?;
+    feature.collection = Some(collection_id.to_owned());
+
+    Ok(Some(feature))
+}
+
+/// Parse a feature whose geometry may be given as `WKT` or hex encoded `WKB`
+/// string instead of `GeoJSON`, e.g. by producers without `GeoJSON` support
+fn parse_feature(mut value: serde_json::Value) -> Result<Feature> {
+    if let Some(geometry) = value.get_mut("geometry") {
+        if let Some(text) = geometry.as_str() {
+            let decoded = wkb::from_text(text).map_err(|e| {
+                Error::Exception(StatusCode::BAD_REQUEST, format!("Invalid geometry: {e}"))
+            })?;
+            *geometry = serde_json::to_value(decoded).map_err(anyhow::Error::from)?;
+        }
+    }
+
+    serde_json::from_value(value)
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+/// Check a batch of features against the access filter and the schema,
+/// returning the number of validation warnings
+async fn check_batch(
+    state: &AppState,
+    collection_id: &str,
+    batch: &[Feature],
+    filter: Option<&Expr>,
+) -> Result<usize> {
+    if batch.is_empty() {
+        return Ok(0);
+    }
+
+    if let Some(filter) = filter {
+        check_access(state, collection_id, batch, filter).await?;
+    }
+
+    validate(state, collection_id, batch).await
 }
 
 async fn read(
     State(state): State<AppState>,
     RemoteUrl(url): RemoteUrl,
     Path((collection_id, id)): Path<(String, String)>,
-    Qs(query): Qs<Query>,
-) -> Result<(HeaderMap, Json<Feature>)> {
+    Qs(mut query): Qs<Query>,
+    share: ShareLink,
+    uri: Uri,
+    request_headers: HeaderMap,
+) -> Result<Response> {
     let collection = state
-        .drivers
+        .services
         .collections
         .read_collection(&collection_id)
         .await?
         .ok_or(Error::NotFound)?;
-    is_supported_crs(&collection, &query.crs).await?;
+    if !has_parameter(&uri, "crs") {
+        query.crs = collection.default_crs();
+    }
+    check_crs(&collection, &query)?;
+    query.precision = query.precision.or(collection.precision);
 
     let mut feature = state
-        .drivers
+        .services
         .features
         .read_feature(&collection_id, &id, &query.crs)
         .await?
         .ok_or(Error::NotFound)?;
 
+    // features outside the access filter are hidden
+    if let Some(filter) = read_filter(&state, &request_headers, &share, &collection_id).await? {
+        let matches = state
+            .services
+            .features
+            .match_filter(
+                &collection_id,
+                std::slice::from_ref(&feature),
+                &query.crs,
+                &filter,
+            )
+            .await?;
+        if !matches.first().copied().unwrap_or_default() {
+            return Err(Error::NotFound);
+        }
+    }
+
+    if query.crs.is_northing_first() {
+        feature.swap_axes();
+    }
+    if let Some(precision) = query.precision {
+        feature.round_coordinates(precision);
+    }
+
+    feature.remove_properties(&hidden_properties(&state, &request_headers, &collection).await);
+
+    // only the geometry, for clients reading well-known binary
+    if query.f.as_deref() == Some("wkb") || has_media_type(&request_headers, ACCEPT, WKB) {
+        let headers = [
+            ("Content-Crs", query.crs.to_string()),
+            (CONTENT_TYPE.as_str(), WKB.to_string()),
+        ];
+        return Ok((headers, wkb::to_wkb(&feature.geometry.value)).into_response());
+    }
+
+    let links = LinkBuilder::new(&url).mediatype(GEO_JSON);
     feature.links.insert_or_update(&[
-        Link::new(&url, SELF).mediatype(GEO_JSON),
-        Link::new(url.join("../../..")?, ROOT).mediatype(JSON),
-        Link::new(url.join(&format!("../../{}", collection_id))?, COLLECTION).mediatype(JSON),
+        links.self_link(),
+        links.link("../../..", ROOT)?,
+        links.link(&format!("../../{}", collection_id), COLLECTION)?,
+        links.query(ATERNATE, Some("f=jsonld")).mediatype(JSON_LD),
     ]);
+    for relation in &collection.relations {
+        let mut link = links
+            .link(
+                &format!("{id}/related/{}", relation.collection),
+                relation.rel.as_deref().unwrap_or(RELATED),
+            )?
+            .mediatype(GEO_JSON);
+        if let Some(title) = &relation.title {
+            link = link.title(title);
+        }
+        feature.links.push(link);
+    }
+    #[cfg(feature = "attachments")]
+    feature
+        .links
+        .extend(super::attachments::feature_links(&state, &url, &collection_id, &id).await?);
     feature.links.resolve_relative_links();
 
     let mut headers = HeaderMap::new();
@@ -85,138 +456,1231 @@ async fn read(
             .parse()
             .context("Unable to parse `Content-Crs` header value")?,
     );
+
+    if wants_json_ld(&request_headers, query.f.as_deref()) {
+        headers.insert(CONTENT_TYPE, JSON_LD.parse().unwrap());
+        return Ok((headers, Json(jsonld::feature(&feature, &query.crs))).into_response());
+    }
+
     headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
 
-    Ok((headers, Json(feature)))
+    Ok((headers, Json(feature)).into_response())
 }
 
+/// Features of another collection related to a feature, by a property of
+/// the feature or of the related features as declared by the relations of
+/// either collection
+async fn related(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path((collection_id, id, related_id)): Path<(String, String, String)>,
+    Qs(mut query): Qs<Query>,
+    share: ShareLink,
+    request_headers: HeaderMap,
+    uri: Uri,
+) -> Result<(HeaderMap, Json<FeatureCollection>)> {
+    share.strip(&mut query.additional_parameters);
+
+    let collection = state
+        .services
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let related = state
+        .services
+        .collections
+        .read_collection(&related_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let filter = read_filter(&state, &request_headers, &share, &collection_id).await?;
+    if !is_accessible(&state, &collection_id, &id, filter.as_ref()).await? {
+        return Err(Error::NotFound);
+    }
+
+    let condition = if let Some(relation) = collection
+        .relations
+        .iter()
+        .find(|r| r.collection == related_id)
+    {
+        // ids of the related features held by the feature
+        let hidden = hidden_properties(&state, &request_headers, &collection).await;
+        if hidden.contains(&relation.property) {
+            return Err(Error::NotFound);
+        }
+        let feature = state
+            .services
+            .features
+            .read_feature(&collection_id, &id, &Crs::default())
+            .await?
+            .ok_or(Error::NotFound)?;
+        let ids = feature
+            .properties
+            .as_ref()
+            .and_then(|p| p.get(&relation.property))
+            .map(Relation::ids)
+            .unwrap_or_default();
+        if ids.is_empty() {
+            None
+        } else {
+            Some(cql2::in_list("id", ids))
+        }
+    } else if let Some(relation) = related
+        .relations
+        .iter()
+        .find(|r| r.collection == collection_id)
+    {
+        // related features holding the id of the feature
+        let hidden = hidden_properties(&state, &request_headers, &related).await;
+        if hidden.contains(&relation.property) {
+            return Err(Error::NotFound);
+        }
+        Some(cql2::eq(&relation.property, id.as_str()))
+    } else {
+        return Err(Error::Exception(
+            StatusCode::NOT_FOUND,
+            format!("Collection `{collection_id}` isn't related to `{related_id}`"),
+        ));
+    };
+
+    query.limit = Some(query.limit.unwrap_or(100).min(state.guardrails.max_limit));
+    if !has_parameter(&uri, "crs") {
+        query.crs = related.default_crs();
+    }
+    check_crs(&related, &query)?;
+    query.precision = query.precision.or(related.precision);
+    swap_bbox_axes(&mut query);
+
+    let mut fc = match condition {
+        Some(condition) => {
+            query.access_filter =
+                match read_filter(&state, &request_headers, &share, &related_id).await? {
+                    Some(filter) => Some(condition.and(filter)),
+                    None => Some(condition),
+                };
+            state
+                .services
+                .features
+                .list_items(&related_id, &query)
+                .await?
+        }
+        None => {
+            let mut fc = FeatureCollection::new(Vec::new());
+            fc.number_matched = Some(0);
+            fc
+        }
+    };
+
+    if query.crs.is_northing_first() {
+        fc.swap_axes();
+    }
+    if let Some(precision) = query.precision {
+        fc.round_coordinates(precision);
+    }
+    fc.remove_properties(&hidden_properties(&state, &request_headers, &related).await);
+
+    let links = LinkBuilder::new(&url).mediatype(GEO_JSON);
+    fc.links.insert_or_update(&[
+        links.self_link(),
+        links.link("../../../../..", ROOT)?,
+        links.link(&format!("../../../../{related_id}"), COLLECTION)?,
+    ]);
+
+    paginate(&mut fc, &links, &query);
+
+    for feature in fc.features.iter_mut() {
+        feature.links.insert_or_update(&[
+            links
+                .link(
+                    &format!(
+                        "../../../../{related_id}/items/{}",
+                        feature.id.as_ref().unwrap()
+                    ),
+                    SELF,
+                )?
+                .mediatype(GEO_JSON),
+            links.link("../../../../..", ROOT)?,
+            links.link(&format!("../../../../{related_id}"), COLLECTION)?,
+        ])
+    }
+    fc.links.resolve_relative_links();
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Crs", query.crs.to_string().parse().unwrap());
+    headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
+
+    Ok((headers, Json(fc)))
+}
+
+/// Replace a feature, or create it if missing and the collection allows upserts
 async fn update(
     State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
     Path((collection_id, id)): Path<(String, String)>,
-    Json(mut feature): Json<Feature>,
-) -> Result<StatusCode> {
-    feature.id = Some(id);
-    feature.collection = Some(collection_id);
+    DryRun(dry_run): DryRun,
+    IfVersion(if_version): IfVersion,
+    request_headers: HeaderMap,
+    Json(value): Json<serde_json::Value>,
+) -> Result<Response> {
+    let mut feature = parse_feature(value)?;
+    feature.id = Some(id.to_owned());
+    feature.collection = Some(collection_id.to_owned());
 
-    state.drivers.features.update_feature(&feature).await?;
+    let collection = state
+        .services
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
 
-    Ok(StatusCode::NO_CONTENT)
+    let exists = is_accessible(&state, &collection_id, &id, None).await?;
+    if !exists && !collection.upsert {
+        return Err(Error::NotFound);
+    }
+
+    // only accessible features may be replaced, and only by accessible ones
+    let filter = access_filter(&state, &request_headers, &collection_id).await?;
+    if let Some(filter) = filter.as_ref() {
+        if exists && !is_accessible(&state, &collection_id, &id, Some(filter)).await? {
+            return Err(Error::NotFound);
+        }
+        check_access(
+            &state,
+            &collection_id,
+            std::slice::from_ref(&feature),
+            filter,
+        )
+        .await?;
+    }
+
+    // the version of the stored feature, which is replaced only if unchanged
+    let version = if collection.versioned {
+        let stored = if exists {
+            let stored = state
+                .services
+                .features
+                .read_feature(&collection_id, &id, &Crs::default())
+                .await?;
+            Some(stored.and_then(|stored| stored.version()).unwrap_or(0))
+        } else {
+            None
+        };
+        match (if_version, stored) {
+            (Some(expected), Some(stored)) if expected != stored => {
+                return Err(Error::Exception(
+                    StatusCode::PRECONDITION_FAILED,
+                    format!("Feature `{id}` has version {stored}, not {expected}"),
+                ));
+            }
+            (Some(expected), None) => {
+                return Err(Error::Exception(
+                    StatusCode::PRECONDITION_FAILED,
+                    format!("Feature `{id}` does not exist in version {expected}"),
+                ));
+            }
+            _ => {}
+        }
+        feature.set_version(stored.map_or(1, |stored| stored + 1));
+        stored
+    } else if if_version.is_some() {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Features of collection `{collection_id}` have no versions"),
+        ));
+    } else {
+        None
+    };
+
+    let warnings = validate(&state, &collection_id, std::slice::from_ref(&feature)).await?;
+
+    let mut location = url;
+    location.set_query(None);
+
+    if dry_run {
+        check_writable(&state, &collection_id).await?;
+
+        let report = if exists {
+            DryRunReport::new("replace", StatusCode::NO_CONTENT)
+        } else {
+            let mut report = DryRunReport::new("create", StatusCode::CREATED);
+            report.location = Some(location.to_string());
+            report
+        };
+
+        return Ok(report.into_response());
+    }
+
+    let mut headers = HeaderMap::new();
+    if warnings > 0 {
+        headers.insert(VALIDATION_WARNINGS, warnings.into());
+    }
+
+    let status = if let Some(version) = version {
+        // another request may have replaced the feature in the meantime
+        let replaced = state
+            .services
+            .features
+            .update_feature_if_version(&feature, version)
+            .await?;
+        if !replaced {
+            return Err(Error::Exception(
+                StatusCode::PRECONDITION_FAILED,
+                format!("Feature `{id}` was changed concurrently"),
+            ));
+        }
+        StatusCode::NO_CONTENT
+    } else if exists {
+        state.services.features.update_feature(&feature).await?;
+        StatusCode::NO_CONTENT
+    } else {
+        state.services.features.create_feature(&feature).await?;
+        headers.insert(LOCATION, location.as_str().parse().unwrap());
+        StatusCode::CREATED
+    };
+    state.extents.invalidate(&collection_id);
+
+    Ok((status, headers).into_response())
 }
 
 async fn remove(
     State(state): State<AppState>,
     Path((collection_id, id)): Path<(String, String)>,
-) -> Result<StatusCode> {
+    DryRun(dry_run): DryRun,
+    request_headers: HeaderMap,
+) -> Result<Response> {
+    let filter = access_filter(&state, &request_headers, &collection_id).await?;
+    if filter.is_some() && !is_accessible(&state, &collection_id, &id, filter.as_ref()).await? {
+        return Err(Error::NotFound);
+    }
+
+    if dry_run {
+        if !is_accessible(&state, &collection_id, &id, None).await? {
+            return Err(Error::NotFound);
+        }
+
+        return Ok(DryRunReport::new("delete", StatusCode::NO_CONTENT).into_response());
+    }
+
     state
-        .drivers
+        .services
         .features
         .delete_feature(&collection_id, &id)
         .await?;
+    state.extents.invalidate(&collection_id);
+
+    #[cfg(feature = "attachments")]
+    super::attachments::remove_all(&state, &collection_id, &id).await?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Validate features against the properties schema and the rules of their
+/// collection, rejecting them on violations of severity `error`, returning
+/// the number of warnings
+async fn validate(state: &AppState, collection_id: &str, features: &[Feature]) -> Result<usize> {
+    let collection = state
+        .services
+        .collections
+        .read_collection(collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if let Some(schema) = &collection.properties_schema {
+        let errors: Vec<String> = features
+            .iter()
+            .enumerate()
+            .flat_map(|(index, feature)| {
+                schema
+                    .check(feature.properties.as_ref())
+                    .into_iter()
+                    .map(move |message| Violation {
+                        feature: feature.id.to_owned(),
+                        index: Some(index),
+                        rule: "propertiesSchema".to_string(),
+                        severity: Severity::Error,
+                        message,
+                    })
+            })
+            .map(|violation| violation.to_string())
+            .collect();
+        if !errors.is_empty() {
+            return Err(Error::Invalid(errors));
+        }
+    }
+
+    if collection.validation.is_empty() {
+        return Ok(0);
+    }
+
+    let violations = state
+        .services
+        .features
+        .validate_features(
+            collection_id,
+            features,
+            &Crs::default(),
+            &collection.validation,
+        )
+        .await?;
+
+    let (errors, warnings): (Vec<_>, Vec<_>) = violations
+        .into_iter()
+        .partition(|v| v.severity == Severity::Error);
+    if !errors.is_empty() {
+        return Err(Error::Invalid(
+            errors.iter().map(ToString::to_string).collect(),
+        ));
+    }
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(warnings.len())
+}
+
+/// Checks of a dry run that the collection exists and accepts features in
+/// the default crs
+async fn check_writable(state: &AppState, collection_id: &str) -> Result<()> {
+    let collection = state
+        .services
+        .collections
+        .read_collection(collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    is_supported_crs(&collection, &Crs::default()).await
+}
+
+/// Checks whether a feature exists and matches the access filter, if any
+pub(crate) async fn is_accessible(
+    state: &AppState,
+    collection_id: &str,
+    id: &str,
+    filter: Option<&Expr>,
+) -> Result<bool> {
+    let feature = state
+        .services
+        .features
+        .read_feature(collection_id, id, &Crs::default())
+        .await?;
+
+    match (feature, filter) {
+        (Some(feature), Some(filter)) => {
+            let matches = state
+                .services
+                .features
+                .match_filter(collection_id, &[feature], &Crs::default(), filter)
+                .await?;
+            Ok(matches.first().copied().unwrap_or_default())
+        }
+        (feature, _) => Ok(feature.is_some()),
+    }
+}
+
+/// Reject writes of features not matching the access filter
+async fn check_access(
+    state: &AppState,
+    collection_id: &str,
+    features: &[Feature],
+    filter: &Expr,
+) -> Result<()> {
+    let matches = state
+        .services
+        .features
+        .match_filter(collection_id, features, &Crs::default(), filter)
+        .await?;
+
+    match matches.iter().position(|m| !m) {
+        Some(i) => Err(Error::Exception(
+            StatusCode::FORBIDDEN,
+            match features[i].id.as_ref() {
+                Some(id) => format!("Feature `{id}` is outside the accessible features"),
+                None => "Feature is outside the accessible features".to_string(),
+            },
+        )),
+        None => Ok(()),
+    }
 }
 
 async fn items(
     State(state): State<AppState>,
-    RemoteUrl(mut url): RemoteUrl,
+    RemoteUrl(url): RemoteUrl,
     Path(collection_id): Path<String>,
-    Qs(mut query): Qs<Query>,
-) -> Result<(HeaderMap, Json<FeatureCollection>)> {
+    Qs(query): Qs<Query>,
+    share: ShareLink,
+    request_headers: HeaderMap,
+    uri: Uri,
+) -> Result<Response> {
+    let has_crs = has_parameter(&uri, "crs");
+    list_items(
+        &state,
+        url,
+        &collection_id,
+        query,
+        share,
+        &request_headers,
+        has_crs,
+    )
+    .await
+}
+
+/// Features of a collection matching the query, `has_crs` tells whether the
+/// response crs was requested explicitly
+async fn list_items(
+    state: &AppState,
+    url: url::Url,
+    collection_id: &str,
+    mut query: Query,
+    share: ShareLink,
+    request_headers: &HeaderMap,
+    has_crs: bool,
+) -> Result<Response> {
     tracing::debug!("{:#?}", query);
 
-    // Limit
+    share.strip(&mut query.additional_parameters);
+
+    // Limit, features requested by id are returned at once
     if let Some(limit) = query.limit {
-        if limit > 10000 {
-            query.limit = Some(10000);
+        if limit > state.guardrails.max_limit {
+            query.limit = Some(state.guardrails.max_limit);
         }
     } else {
-        query.limit = Some(100);
+        query.limit = match query.ids.as_ref() {
+            Some(ids) if ids.len() > 100 => Some(ids.len().min(state.guardrails.max_limit)),
+            _ => Some(100),
+        };
+    }
+    if query.resulttype == Some(ResultType::Hits) {
+        query.limit = Some(0);
     }
 
     let collection = state
-        .drivers
+        .services
         .collections
-        .read_collection(&collection_id)
+        .read_collection(collection_id)
         .await?
         .ok_or(Error::NotFound)?;
-    is_supported_crs(&collection, &query.crs).await?;
+    if !has_crs {
+        query.crs = collection.default_crs();
+    }
+    check_crs(&collection, &query)?;
+    query.precision = query.precision.or(collection.precision);
+    query.access_filter = read_filter(state, request_headers, &share, collection_id).await?;
 
     // TODO: validate additional parameters
+    let hidden = hidden_properties(state, request_headers, &collection).await;
+    check_hidden_parameters(&query, &hidden)?;
 
-    let mut fc = state
-        .drivers
-        .features
-        .list_items(&collection_id, &query)
-        .await?;
+    swap_bbox_axes(&mut query);
+
+    // boxes outside the extent of the collection can't match any feature
+    let disjoint = match query.bbox.as_ref() {
+        Some(bbox) => {
+            state
+                .extents
+                .disjoint(
+                    state.services.collections.as_ref(),
+                    &collection,
+                    bbox,
+                    &query.bbox_crs,
+                )
+                .await
+        }
+        None => false,
+    };
+
+    if !disjoint {
+        check_guardrails(state, request_headers, collection_id, &query).await?;
+    }
+
+    if query.f.as_deref() == Some("geojsonseq")
+        || has_media_type(request_headers, ACCEPT, GEO_JSON_SEQ)
+    {
+        let features = if disjoint {
+            futures::stream::empty().boxed()
+        } else {
+            state.services.features.stream_items(collection_id, &query)
+        };
+        return Ok(stream_items(features, url, collection_id, &query, hidden));
+    }
 
+    let mut fc = if disjoint {
+        let mut fc = FeatureCollection::new(Vec::new());
+        fc.number_matched = Some(0);
+        fc
+    } else {
+        state
+            .services
+            .features
+            .list_items(collection_id, &query)
+            .await?
+    };
+
+    if query.crs.is_northing_first() {
+        fc.swap_axes();
+    }
+    if let Some(precision) = query.precision {
+        fc.round_coordinates(precision);
+    }
+    fc.remove_properties(&hidden);
+
+    if let Some(metered) = metering::current() {
+        metered.features(fc.features.len() as u64);
+    }
+
+    let links = LinkBuilder::new(&url).mediatype(GEO_JSON);
     fc.links.insert_or_update(&[
-        Link::new(&url, SELF).mediatype(GEO_JSON),
-        Link::new(url.join("../..")?, ROOT).mediatype(JSON),
-        Link::new(url.join(".")?, COLLECTION).mediatype(JSON),
+        links.self_link(),
+        links.link("../..", ROOT)?,
+        links.link(".", COLLECTION)?,
     ]);
 
-    // pagination
-    if let Some(limit) = query.limit {
-        if query.offset.is_none() {
-            query.offset = Some(0);
+    paginate(&mut fc, &links, &query);
+
+    for feature in fc.features.iter_mut() {
+        feature.links.insert_or_update(&[
+            links
+                .link(&format!("items/{}", feature.id.as_ref().unwrap()), SELF)?
+                .mediatype(GEO_JSON),
+            links.link("../..", ROOT)?,
+            links.link(&format!("../{}", collection.id), COLLECTION)?,
+        ])
+    }
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Crs", query.crs.to_string().parse().unwrap());
+
+    if wants_json_ld(request_headers, query.f.as_deref()) {
+        headers.insert(CONTENT_TYPE, JSON_LD.parse().unwrap());
+        return Ok((headers, Json(jsonld::feature_collection(&fc, &query.crs))).into_response());
+    }
+
+    headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
+
+    Ok((headers, Json(fc)).into_response())
+}
+
+/// Add links to the previous and next page
+///
+/// Responses with only the number of matches have no pages.
+fn paginate(fc: &mut FeatureCollection, links: &LinkBuilder, query: &Query) {
+    if let Some(limit) = query.limit.filter(|limit| *limit > 0) {
+        let offset = query.offset.unwrap_or(0);
+
+        if offset != 0 && offset >= limit {
+            fc.links
+                .insert_or_update(&[links.page(PREV, offset - limit, limit)]);
         }
 
-        if let Some(offset) = query.offset {
-            if offset != 0 && offset >= limit {
-                query.offset = Some(offset - limit);
-                url.set_query(serde_qs::to_string(&query).ok().as_deref());
-                let previous = Link::new(&url, PREV).mediatype(GEO_JSON);
-                fc.links.insert_or_update(&[previous]);
+        if let Some(number_matched) = fc.number_matched {
+            if number_matched > (offset + limit) as u64 {
+                fc.links
+                    .insert_or_update(&[links.page(NEXT, offset + limit, limit)]);
             }
+        }
+    }
+}
 
-            if let Some(number_matched) = fc.number_matched {
-                if number_matched > (offset + limit) as u64 {
-                    query.offset = Some(offset + limit);
-                    url.set_query(serde_qs::to_string(&query).ok().as_deref());
-                    let next = Link::new(&url, NEXT).mediatype(GEO_JSON);
-                    fc.links.insert_or_update(&[next]);
-                }
+/// Respond with a `GeoJSON` text sequence of streamed features
+fn stream_items(
+    features: BoxStream<'static, anyhow::Result<Feature>>,
+    url: url::Url,
+    collection_id: &str,
+    query: &Query,
+    hidden: Vec<String>,
+) -> Response {
+    let collection_id = collection_id.to_owned();
+
+    let links = LinkBuilder::new(&url);
+    let precision = query.precision;
+    let swap_axes = query.crs.is_northing_first();
+    // the body is streamed after the request was handled
+    let metered = metering::current();
+
+    let stream = features.map(move |feature| {
+        let mut feature = feature?;
+
+        if let Some(metered) = &metered {
+            metered.features(1);
+        }
+
+        if swap_axes {
+            feature.swap_axes();
+        }
+        if let Some(precision) = precision {
+            feature.round_coordinates(precision);
+        }
+        feature.remove_properties(&hidden);
+
+        if let Some(id) = feature.id.as_ref() {
+            feature.links.insert_or_update(&[
+                links
+                    .link(&format!("items/{}", id), SELF)?
+                    .mediatype(GEO_JSON),
+                links.link("../..", ROOT)?,
+                links.link(&format!("../{}", collection_id), COLLECTION)?,
+            ]);
+        }
+
+        let mut record = vec![RS];
+        serde_json::to_writer(&mut record, &feature)?;
+        record.push(b'\n');
+
+        Ok::<_, anyhow::Error>(record)
+    });
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Crs", query.crs.to_string().parse().unwrap());
+    headers.insert(CONTENT_TYPE, GEO_JSON_SEQ.parse().unwrap());
+
+    (headers, Body::from_stream(stream)).into_response()
+}
+
+/// Refuse filtering on hidden properties, which would reveal their values
+pub(crate) fn check_hidden_parameters(query: &Query, hidden: &[String]) -> Result<()> {
+    match query
+        .additional_parameters
+        .keys()
+        .find(|name| hidden.contains(name))
+    {
+        Some(name) => Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Property `{name}` is not queryable"),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Checks whether a header lists the given media type
+fn has_media_type(
+    headers: &HeaderMap,
+    name: impl axum::http::header::AsHeaderName,
+    mime: &str,
+) -> bool {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(',').any(|m| m.trim().starts_with(mime)))
+        .unwrap_or(false)
+}
+
+/// Queryable properties of a collection as JSON Schema
+async fn queryables(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(collection_id): Path<String>,
+    request_headers: HeaderMap,
+) -> Result<(HeaderMap, Json<Queryables>)> {
+    let collection = state
+        .services
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let mut queryables = state
+        .services
+        .collections
+        .read_queryables(&collection_id)
+        .await?
+        .unwrap_or_default();
+
+    if let Some(schema) = &collection.properties_schema {
+        queryables.merge_schema(schema);
+    }
+
+    for name in hidden_properties(&state, &request_headers, &collection).await {
+        queryables.properties.remove(&name);
+    }
+
+    queryables.id = Some(url.to_string());
+    queryables.title = queryables.title.or(collection.title);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, SCHEMA_JSON.parse().unwrap());
+
+    Ok((headers, Json(queryables)))
+}
+
+/// Feature count, geometry types, value distributions of the properties and
+/// extent of a collection
+async fn stats(
+    State(state): State<AppState>,
+    Path(collection_id): Path<String>,
+    Qs(query): Qs<StatsQuery>,
+    headers: HeaderMap,
+) -> Result<Json<CollectionStats>> {
+    let collection = state
+        .services
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // statistics are computed over all features
+    deny_restricted(&state, &headers, &[&collection_id]).await?;
+
+    let mut stats = state
+        .services
+        .features
+        .stats(&collection_id, &query)
+        .await?
+        .ok_or_else(|| {
+            Error::Exception(
+                StatusCode::NOT_IMPLEMENTED,
+                "Statistics are not supported by the backend".to_string(),
+            )
+        })?;
+
+    for name in hidden_properties(&state, &headers, &collection).await {
+        stats.properties.remove(&name);
+    }
+
+    Ok(Json(stats))
+}
+
+/// Distinct values of a property with their counts, e.g. for the options of
+/// filters in web clients
+async fn values(
+    State(state): State<AppState>,
+    Path((collection_id, property)): Path<(String, String)>,
+    Qs(query): Qs<ValuesQuery>,
+    headers: HeaderMap,
+) -> Result<Json<PropertyValues>> {
+    let collection = state
+        .services
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // values are counted over all features
+    deny_restricted(&state, &headers, &[&collection_id]).await?;
+
+    if hidden_properties(&state, &headers, &collection)
+        .await
+        .contains(&property)
+    {
+        return Err(Error::NotFound);
+    }
+
+    let values = state
+        .services
+        .features
+        .property_values(&collection_id, &property, &query)
+        .await?
+        .ok_or_else(|| {
+            Error::Exception(
+                StatusCode::NOT_IMPLEMENTED,
+                "Distinct values are not supported by the backend".to_string(),
+            )
+        })?;
+
+    Ok(Json(values))
+}
+
+/// Server-sent events about created, updated and deleted features of a collection
+async fn notifications(
+    State(state): State<AppState>,
+    Path(collection_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>> {
+    state
+        .services
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // changes of deleted features can't be filtered
+    deny_restricted(&state, &headers, &[&collection_id]).await?;
+
+    let changes = state.drivers.changes.subscribe(&collection_id).await?;
+
+    let events = changes.filter_map(|change| async move {
+        match change {
+            Ok(change) => Some(Event::default().json_data(change)),
+            Err(e) => {
+                tracing::error!("Failed to receive feature change: {e}");
+                None
             }
         }
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Parameters of a page of the change log
+#[derive(Deserialize, Debug)]
+struct ChangesQuery {
+    /// Token of the last replicated change, the end of the log if omitted
+    since: Option<String>,
+    limit: Option<usize>,
+    /// `atom` for a feed of the latest changes
+    f: Option<String>,
+    /// Time the changes of a feed page were made before, now if omitted
+    before: Option<DateTime<Utc>>,
+    /// Position of the last change of the previous feed page, made at `before`
+    position: Option<String>,
+}
+
+/// Ordered changes of the features of a collection after a token, each with
+/// the current state of the feature, to replicate the collection elsewhere
+///
+/// With `f=atom` the latest changes are listed as Atom feed instead.
+async fn changes(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(collection_id): Path<String>,
+    Qs(query): Qs<ChangesQuery>,
+    request_headers: HeaderMap,
+) -> Result<Response> {
+    let collection = state
+        .services
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // replicas copy the whole collection
+    deny_restricted(&state, &request_headers, &[&collection_id]).await?;
+
+    if query.f.as_deref() == Some("atom") || has_media_type(&request_headers, ACCEPT, ATOM) {
+        return feed(&state, url, collection, &query, &request_headers).await;
     }
 
-    for feature in fc.features.iter_mut() {
-        feature.links.insert_or_update(&[
-            Link::new(
-                url.join(&format!("items/{}", feature.id.as_ref().unwrap()))?,
-                SELF,
+    if let Some(since) = query.since.as_deref() {
+        if !is_position(since) {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid token `{since}`"),
+            ));
+        }
+    }
+    let limit = query
+        .limit
+        .unwrap_or(CHANGES_LIMIT)
+        .clamp(1, state.guardrails.max_limit);
+
+    let (changes, token) = state
+        .drivers
+        .changes
+        .changes_since(&collection_id, query.since.as_deref(), limit)
+        .await?
+        .ok_or_else(|| {
+            Error::Exception(
+                StatusCode::NOT_IMPLEMENTED,
+                "Change logs are not supported by the backend".to_string(),
             )
-            .mediatype(GEO_JSON),
-            Link::new(url.join("../..")?, ROOT).mediatype(JSON),
-            Link::new(url.join(&format!("../{}", collection.id))?, COLLECTION).mediatype(JSON),
-        ])
+        })?;
+    let full = changes.len() == limit;
+
+    // the state of the feature rather than the kind of the change counts, as
+    // changes of concurrent transactions may be listed out of order
+    let hidden = hidden_properties(&state, &request_headers, &collection).await;
+    let mut logged = Vec::with_capacity(changes.len());
+    for change in changes {
+        let mut feature = state
+            .services
+            .features
+            .read_feature(&collection_id, &change.id, &Crs::default())
+            .await?;
+        if let Some(feature) = feature.as_mut() {
+            feature.remove_properties(&hidden);
+        }
+        logged.push(LoggedChange { change, feature });
     }
 
-    let mut headers = HeaderMap::new();
-    headers.insert("Content-Crs", query.crs.to_string().parse().unwrap());
-    headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
+    let links = LinkBuilder::new(&url).mediatype(JSON);
+    let mut changeset = Changeset {
+        changes: logged,
+        token,
+        links: Default::default(),
+    };
+    changeset.links.insert_or_update(&[
+        links.self_link(),
+        links.link("../..", ROOT)?,
+        links.link(".", COLLECTION)?,
+    ]);
+    if full {
+        let next = links.replace_query(
+            NEXT,
+            &[("since", &changeset.token), ("limit", &limit.to_string())],
+        );
+        changeset.links.insert_or_update(&[next]);
+    }
 
-    Ok((headers, Json(fc)))
+    Ok(Json(changeset).into_response())
 }
 
-async fn is_supported_crs(collection: &Collection, crs: &Crs) -> Result<(), Error> {
+/// Atom feed of the latest changes of a collection, newest first, with pages
+/// of older changes `before` the time of the last listed one
+async fn feed(
+    state: &AppState,
+    url: Url,
+    collection: Collection,
+    query: &ChangesQuery,
+    request_headers: &HeaderMap,
+) -> Result<Response> {
+    if let Some(position) = query.position.as_deref() {
+        if !is_position(position) {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid position `{position}`"),
+            ));
+        }
+    }
+    let limit = query
+        .limit
+        .unwrap_or(CHANGES_LIMIT)
+        .clamp(1, state.guardrails.max_limit);
+
+    let changes = state
+        .drivers
+        .changes
+        .recent_changes(
+            &collection.id,
+            query.before,
+            query.position.as_deref(),
+            limit,
+        )
+        .await?
+        .ok_or_else(|| {
+            Error::Exception(
+                StatusCode::NOT_IMPLEMENTED,
+                "Change logs are not supported by the backend".to_string(),
+            )
+        })?;
+
+    // changes of a transaction share their time, the next page continues
+    // after the position of the last change
+    let next = (changes.len() == limit).then(|| {
+        let last = &changes[limit - 1];
+        (last.changed, last.position.to_owned())
+    });
+
+    let hidden = hidden_properties(state, request_headers, &collection).await;
+    let mut entries = Vec::with_capacity(changes.len());
+    for change in changes {
+        let mut feature = state
+            .services
+            .features
+            .read_feature(&collection.id, &change.change.id, &Crs::default())
+            .await?;
+        if let Some(feature) = feature.as_mut() {
+            feature.remove_properties(&hidden);
+        }
+        entries.push((change, feature));
+    }
+
+    let items = url.join("items")?;
+    let links = LinkBuilder::new(&url).mediatype(ATOM);
+    let mut feed_links = vec![links.self_link(), links.link(".", COLLECTION)?];
+    if let Some((changed, position)) = next {
+        feed_links.push(links.replace_query(
+            NEXT,
+            &[
+                ("before", &changed.to_rfc3339()),
+                ("position", &position),
+                ("limit", &limit.to_string()),
+            ],
+        ));
+    }
+
+    let feed = feeds::atom(&collection, &feed_links, items.as_str(), &entries);
+
+    Ok(([(CONTENT_TYPE, ATOM)], feed).into_response())
+}
+
+/// Whether a token is a position `{transaction}-{sequence}` in the change log
+fn is_position(token: &str) -> bool {
+    token.split_once('-').is_some_and(|(tx, seq)| {
+        [tx, seq]
+            .iter()
+            .all(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+    })
+}
+
+/// Violations of the validation rules of a collection by its features
+async fn validation(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(collection_id): Path<String>,
+    request_headers: HeaderMap,
+) -> Result<Json<ValidationReport>> {
+    let collection = state
+        .services
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // the report lists features regardless of access filters
+    deny_restricted(&state, &request_headers, &[&collection_id]).await?;
+
+    let violations = if collection.validation.is_empty() {
+        Vec::new()
+    } else {
+        state
+            .services
+            .features
+            .validate_collection(&collection_id, &collection.validation)
+            .await?
+    };
+
+    let links = LinkBuilder::new(&url).mediatype(JSON);
+    let mut report = ValidationReport::new(&collection_id, violations);
+    report.links.insert_or_update(&[
+        links.self_link(),
+        links.link("../..", ROOT)?,
+        links.link(".", COLLECTION)?,
+    ]);
+
+    Ok(Json(report))
+}
+
+/// Whether the query string contains a parameter, to tell defaults apart
+fn has_parameter(uri: &Uri, name: &str) -> bool {
+    uri.query().is_some_and(|query| {
+        url::form_urlencoded::parse(query.as_bytes()).any(|(key, _)| key == name)
+    })
+}
+
+/// Reject queries exceeding the guardrails, with guidance on narrowing them
+pub(crate) async fn check_guardrails(
+    state: &AppState,
+    headers: &HeaderMap,
+    collection_id: &str,
+    query: &Query,
+) -> Result<()> {
+    let guardrails = &state.guardrails;
+
+    if let (Some(max), Some(bbox)) = (guardrails.max_bbox_area, query.bbox.as_ref()) {
+        if let Some(area) = bbox_area(bbox, &query.bbox_crs).filter(|area| *area > max) {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Bbox covers {area:.2} square degrees, more than the maximum of {max}, \
+                    split the request into smaller boxes"
+                ),
+            ));
+        }
+    }
+
+    if let Some(max) = guardrails.max_scan_cost {
+        if request_user(state, headers).await.is_none() {
+            let plan = state
+                .services
+                .features
+                .plan_items(collection_id, query)
+                .await?;
+
+            if plan.is_some_and(|plan| plan.full_scan && plan.cost > max) {
+                return Err(Error::Exception(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    "Query scans the whole collection, narrow it down with a `bbox` or \
+                    filters on indexed properties or authenticate with an api key"
+                        .to_string(),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Area of a bbox in square degrees, `None` if not transformable to `CRS84`
+fn bbox_area(bbox: &Bbox, crs: &Crs) -> Option<f64> {
+    let bbox = transformer()
+        .transform_bbox(crs, &Crs::default(), bbox)
+        .ok()?;
+
+    let [minx, miny, maxx, maxy] = bbox.to_2d();
+    let width = if bbox.crosses_antimeridian() {
+        maxx + 360.0 - minx
+    } else {
+        maxx - minx
+    };
+
+    Some(width * (maxy - miny))
+}
+
+pub(crate) async fn is_supported_crs(collection: &Collection, crs: &Crs) -> Result<(), Error> {
     if collection.crs.contains(crs) {
         Ok(())
     } else {
         Err(Error::Exception(
             StatusCode::BAD_REQUEST,
-            format!("Unsuported CRS `{}`", crs),
+            format!("Unsupported CRS `{}`", crs),
         ))
     }
 }
 
-pub(crate) fn router(state: &AppState) -> Router<AppState> {
-    state.conformance.write().unwrap().extend(&CONFORMANCE);
+/// Check the `crs` and `bbox-crs` of a query against the crs advertised by
+/// the collection
+pub(crate) fn check_crs(collection: &Collection, query: &Query) -> Result<()> {
+    let mut errors = Vec::new();
+    for (parameter, crs) in [("crs", &query.crs), ("bbox-crs", &query.bbox_crs)] {
+        if parameter == "bbox-crs" && query.bbox.is_none() {
+            continue;
+        }
+        if !collection.crs.contains(crs) {
+            errors.push(format!(
+                "Unsupported `{parameter}` `{crs}`, the collection supports {}",
+                collection
+                    .crs
+                    .iter()
+                    .map(|crs| format!("`{crs}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
 
-    Router::new()
-        .route("/collections/:collection_id/items", get(items).post(create))
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Invalid(errors))
+    }
+}
+
+/// Swap the axes of the bbox of a query in a crs with northing first, e.g.
+/// `EPSG:4326`, between the axis order of the crs and the `x`/`y` order of
+/// the drivers
+pub(crate) fn swap_bbox_axes(query: &mut Query) {
+    if query.bbox_crs.is_northing_first() {
+        if let Some(bbox) = query.bbox.as_mut() {
+            bbox.swap_axes();
+        }
+    }
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
+        .route("/collections/:collection_id/items", get(items))
         .route(
             "/collections/:collection_id/items/:id",
             get(read).put(update).delete(remove),
         )
+        .route(
+            "/collections/:collection_id/items/:id/related/:related_id",
+            get(related),
+        )
+        .route("/collections/:collection_id/queryables", get(queryables))
+        .route("/collections/:collection_id/stats", get(stats))
+        .route(
+            "/collections/:collection_id/properties/:property",
+            get(values),
+        )
+        .route(
+            "/collections/:collection_id/notifications",
+            get(notifications),
+        )
+        .route("/collections/:collection_id/changes", get(changes))
+        .route("/collections/:collection_id/validation", get(validation));
+
+    // bulk ingest of feature sequences
+    let uploads = Router::new().route("/collections/:collection_id/items", post(create));
+
+    Module::new(router)
+        .uploads(uploads)
+        .conformance(&CONFORMANCE)
 }
@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Path, State},
+    http::{header::LOCATION, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use url::Url;
+
+use ogcapi_types::{
+    common::{link_rel::JOB_LIST, media_type::JSON, Link, LinkBuilder},
+    harvest::Harvest,
+};
+
+use crate::{
+    extractors::RemoteUrl,
+    harvest::{self, Harvester},
+    routes::Module,
+    AppState, Error, Result,
+};
+
+/// List harvests
+async fn harvests(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+) -> Result<Json<Vec<Harvest>>> {
+    let mut harvests = state.drivers.harvests.list_harvests().await?;
+
+    for harvest in harvests.iter_mut() {
+        link(harvest, &url.join(&format!("harvests/{}", harvest.id))?)?;
+    }
+
+    Ok(Json(harvests))
+}
+
+/// Create a harvest of a remote source into a collection
+async fn create(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Json(harvest): Json<Harvest>,
+) -> Result<(StatusCode, HeaderMap)> {
+    validate(&state, &harvest).await?;
+
+    let id = state.drivers.harvests.create_harvest(&harvest).await?;
+
+    let location = url.join(&format!("harvests/{id}"))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    Ok((StatusCode::CREATED, headers))
+}
+
+async fn read(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(id): Path<String>,
+) -> Result<Json<Harvest>> {
+    let mut harvest = state
+        .drivers
+        .harvests
+        .read_harvest(&id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    link(&mut harvest, &url)?;
+
+    Ok(Json(harvest))
+}
+
+/// Replace the configuration of a harvest
+async fn update(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(mut harvest): Json<Harvest>,
+) -> Result<StatusCode> {
+    state
+        .drivers
+        .harvests
+        .read_harvest(&id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    harvest.id = id;
+    validate(&state, &harvest).await?;
+
+    state.drivers.harvests.update_harvest(&harvest).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn remove(State(state): State<AppState>, Path(id): Path<String>) -> Result<StatusCode> {
+    state.drivers.harvests.delete_harvest(&id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Start a run of a harvest now, independent of its schedule
+async fn run(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(id): Path<String>,
+) -> Result<Response> {
+    let harvest = state
+        .drivers
+        .harvests
+        .read_harvest(&id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let mut job = Harvester::new(state.drivers.clone()).start(harvest).await?;
+
+    let location = url.join(&format!("../../jobs/{}", job.job_id))?;
+    job.links = vec![LinkBuilder::new(&location).mediatype(JSON).self_link()];
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    Ok((StatusCode::CREATED, headers, Json(job)).into_response())
+}
+
+async fn validate(state: &AppState, harvest: &Harvest) -> Result<()> {
+    let mut problems = harvest.validate();
+    if let Err(e) = harvest::schedule(&harvest.schedule) {
+        problems.push(e.to_string());
+    }
+    if !problems.is_empty() {
+        return Err(Error::Invalid(problems));
+    }
+
+    if state
+        .drivers
+        .collections
+        .read_collection(&harvest.collection)
+        .await?
+        .is_none()
+    {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("No collection with id `{}`", harvest.collection),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Link a harvest at `url` to itself and the jobs of its runs
+fn link(harvest: &mut Harvest, url: &Url) -> Result<()> {
+    let links = LinkBuilder::new(url).mediatype(JSON);
+
+    let mut jobs = url.join("../jobs")?;
+    jobs.query_pairs_mut()
+        .append_pair("processID", &harvest.process_id());
+
+    harvest.links = vec![
+        links.self_link(),
+        Link::new(jobs, JOB_LIST)
+            .mediatype(JSON)
+            .title("Runs of the harvest"),
+    ];
+
+    Ok(())
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
+        .route("/harvests", get(harvests).post(create))
+        .route("/harvests/:id", get(read).put(update).delete(remove))
+        .route("/harvests/:id/runs", post(run));
+
+    Module::new(router)
+}
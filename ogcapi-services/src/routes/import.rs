@@ -0,0 +1,355 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Cursor, Read, Seek},
+};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::LOCATION, HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use shapefile::{dbase::FieldValue, PointZ, PolygonRing, Shape};
+
+use ogcapi_types::{
+    common::{Collection, Crs},
+    features::Feature,
+};
+
+use crate::{
+    delta::Differ, extractors::RemoteUrl, routes::Module, upload::Upload, AppState, Error, Result,
+};
+
+/// Number of features inserted at once
+const BATCH_SIZE: usize = 1000;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct ImportQuery {
+    /// Crs of the uploaded data, overrides the `.prj` file
+    crs: Option<Crs>,
+    /// Storage crs of the collection, if it has to be created
+    storage_crs: Option<Crs>,
+    /// Name of the layer (`.shp` file) to import if the archive contains multiple
+    layer: Option<String>,
+    /// Attribute holding the feature ids, reimports then only apply the
+    /// changes and delete the features missing from the layer
+    id_property: Option<String>,
+}
+
+/// Content of the sidecar files belonging to a shapefile
+struct Layer {
+    shp: Vec<u8>,
+    dbf: Vec<u8>,
+    prj: Option<String>,
+}
+
+/// Import a zipped ESRI Shapefile into a collection
+///
+/// The archive is sent as body, as `multipart/form-data` or referenced as
+/// completed resumable upload with the `upload` parameter.
+///
+/// ```bash
+/// curl http://localhost:8484/collections/countries/import \
+///         -H 'Content-Type: application/zip' \
+///         --data-binary @ne_110m_admin_0_countries.zip
+/// ```
+async fn import(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(collection_id): Path<String>,
+    Query(query): Query<ImportQuery>,
+    upload: Upload,
+) -> Result<(StatusCode, HeaderMap, Json<Value>)> {
+    let file = File::open(upload.path()).map_err(anyhow::Error::from)?;
+    let layer = unzip(file, query.layer.as_deref())?;
+
+    let crs = match query.crs {
+        Some(crs) => crs,
+        None => layer.prj.as_deref().and_then(crs_from_prj).ok_or_else(|| {
+            Error::Exception(
+                StatusCode::BAD_REQUEST,
+                "Unable to determine the crs of the shapefile, specify it with the `crs` parameter"
+                    .to_string(),
+            )
+        })?,
+    };
+
+    if state
+        .services
+        .collections
+        .read_collection(&collection_id)
+        .await?
+        .is_none()
+    {
+        let mut collection = Collection {
+            id: collection_id.to_owned(),
+            storage_crs: Some(query.storage_crs.unwrap_or_default()),
+            ..Default::default()
+        };
+        collection.normalize_crs();
+        state
+            .services
+            .collections
+            .create_collection(&collection)
+            .await?;
+    }
+
+    let features = read_features(layer, &collection_id)?;
+
+    let body = match query.id_property {
+        Some(property) => {
+            let mut differ =
+                Differ::new(state.services.features.driver(), &collection_id, &crs).await?;
+            for mut feature in features {
+                feature.id = match feature.properties.as_ref().and_then(|p| p.get(&property)) {
+                    Some(Value::String(id)) if !id.is_empty() => Some(id.to_owned()),
+                    Some(Value::Number(id)) => Some(id.to_string()),
+                    _ => {
+                        return Err(Error::Exception(
+                            StatusCode::BAD_REQUEST,
+                            format!("Feature without `{property}`"),
+                        ))
+                    }
+                };
+                differ.push(feature).await?;
+            }
+            let summary = differ.finish().await?;
+
+            json!({ "numberCreated": summary.inserted, "delta": summary })
+        }
+        None => {
+            let mut count = 0;
+            for batch in features.chunks(BATCH_SIZE) {
+                count += state
+                    .services
+                    .features
+                    .create_features(&collection_id, batch, &crs)
+                    .await?
+                    .len();
+            }
+
+            json!({ "numberCreated": count })
+        }
+    };
+    state.extents.invalidate(&collection_id);
+
+    let location = url.join(&format!("../collections/{}/items", collection_id))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    Ok((StatusCode::CREATED, headers, Json(body)))
+}
+
+/// Extract the `.shp`, `.dbf` and `.prj` files of a layer from a zip archive
+fn unzip(data: impl Read + Seek, layer: Option<&str>) -> Result<Layer> {
+    let mut archive = zip::ZipArchive::new(data)
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let shapes: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.to_lowercase().ends_with(".shp"))
+        .map(|name| name[..name.len() - 4].to_owned())
+        .collect();
+
+    let stem = match (layer, shapes.as_slice()) {
+        (Some(layer), _) => shapes
+            .iter()
+            .find(|s| s.rsplit('/').next() == Some(layer))
+            .ok_or_else(|| {
+                Error::Exception(
+                    StatusCode::BAD_REQUEST,
+                    format!("Layer `{layer}` not found in archive"),
+                )
+            })?
+            .to_owned(),
+        (None, [stem]) => stem.to_owned(),
+        (None, []) => {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                "No `.shp` file found in archive".to_string(),
+            ))
+        }
+        (None, _) => {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Found multiple layers, use the `layer` parameter to specify one of: {}",
+                    shapes.join(", ")
+                ),
+            ))
+        }
+    };
+
+    let mut files: HashMap<String, Vec<u8>> = HashMap::new();
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+        let name = file.name().to_owned();
+        if let Some((file_stem, extension)) = name.rsplit_once('.') {
+            if file_stem == stem {
+                let mut buf = Vec::new();
+                file.read_to_end(&mut buf)
+                    .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+                files.insert(extension.to_lowercase(), buf);
+            }
+        }
+    }
+
+    let dbf = files.remove("dbf").ok_or_else(|| {
+        Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Missing `{stem}.dbf` in archive"),
+        )
+    })?;
+
+    Ok(Layer {
+        shp: files.remove("shp").unwrap_or_default(),
+        dbf,
+        prj: files
+            .remove("prj")
+            .map(|prj| String::from_utf8_lossy(&prj).into_owned()),
+    })
+}
+
+/// Read the shapes and records of a layer as features
+fn read_features(layer: Layer, collection_id: &str) -> Result<Vec<Feature>> {
+    let shape_reader = shapefile::ShapeReader::new(Cursor::new(layer.shp))
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+    let dbase_reader = shapefile::dbase::Reader::new(Cursor::new(layer.dbf))
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let mut reader = shapefile::Reader::new(shape_reader, dbase_reader);
+
+    let mut features = Vec::new();
+    for result in reader.iter_shapes_and_records() {
+        let (shape, record) =
+            result.map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+        // shapes with height are converted directly, `geo-types` is 2D only
+        let geometry = match shape_with_height(&shape) {
+            Some(value) => value,
+            None => {
+                let geometry = geo_types::Geometry::<f64>::try_from(shape)
+                    .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+                geojson::Value::from(&geometry)
+            }
+        };
+
+        let properties: Map<String, Value> = HashMap::<String, FieldValue>::from(record)
+            .into_iter()
+            .map(|(field, value)| (field, field_value(value)))
+            .collect();
+
+        let feature = serde_json::from_value(json!({
+            "type": "Feature",
+            "collection": collection_id,
+            "properties": properties,
+            "geometry": geojson::Geometry::new(geometry),
+        }))
+        .map_err(anyhow::Error::from)?;
+
+        features.push(feature);
+    }
+
+    Ok(features)
+}
+
+/// Convert a shape with `z` coordinates to a `GeoJSON` geometry
+fn shape_with_height(shape: &Shape) -> Option<geojson::Value> {
+    fn position(point: &PointZ) -> Vec<f64> {
+        vec![point.x, point.y, point.z]
+    }
+
+    let value = match shape {
+        Shape::PointZ(point) => geojson::Value::Point(position(point)),
+        Shape::MultipointZ(multipoint) => {
+            geojson::Value::MultiPoint(multipoint.points().iter().map(position).collect())
+        }
+        Shape::PolylineZ(polyline) => geojson::Value::MultiLineString(
+            polyline
+                .parts()
+                .iter()
+                .map(|part| part.iter().map(position).collect())
+                .collect(),
+        ),
+        Shape::PolygonZ(polygon) => {
+            // inner rings belong to the preceding outer ring
+            let mut polygons: Vec<Vec<Vec<Vec<f64>>>> = Vec::new();
+            for ring in polygon.rings() {
+                let positions = ring.points().iter().map(position).collect();
+                match (ring, polygons.last_mut()) {
+                    (PolygonRing::Inner(_), Some(polygon)) => polygon.push(positions),
+                    _ => polygons.push(vec![positions]),
+                }
+            }
+            geojson::Value::MultiPolygon(polygons)
+        }
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Convert a `dBASE` field value to json
+fn field_value(value: FieldValue) -> Value {
+    match value {
+        FieldValue::Character(s) => s.map(|s| Value::from(s.trim())).unwrap_or_default(),
+        FieldValue::Memo(s) => Value::from(s),
+        FieldValue::Numeric(n) => n.map(Value::from).unwrap_or_default(),
+        FieldValue::Float(f) => f.map(Value::from).unwrap_or_default(),
+        FieldValue::Double(d) | FieldValue::Currency(d) => Value::from(d),
+        FieldValue::Integer(i) => Value::from(i),
+        FieldValue::Logical(b) => b.map(Value::from).unwrap_or_default(),
+        FieldValue::Date(d) => d
+            .map(|d| Value::from(format!("{:04}-{:02}-{:02}", d.year(), d.month(), d.day())))
+            .unwrap_or_default(),
+        FieldValue::DateTime(dt) => {
+            let (d, t) = (dt.date(), dt.time());
+            Value::from(format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+                d.year(),
+                d.month(),
+                d.day(),
+                t.hours(),
+                t.minutes(),
+                t.seconds()
+            ))
+        }
+    }
+}
+
+/// Look up the `EPSG` code of a `.prj` file
+///
+/// Only the authority of the root element is considered, `ESRI` style
+/// definitions without authority are matched against WGS 84.
+fn crs_from_prj(prj: &str) -> Option<Crs> {
+    let prj = prj.trim();
+
+    if let Some(start) = prj.rfind("AUTHORITY[\"EPSG\"") {
+        let code: String = prj[start + 16..]
+            .chars()
+            .skip_while(|c| !c.is_ascii_digit())
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        return code.parse().ok().map(Crs::from_epsg);
+    }
+
+    if prj.starts_with("GEOGCS[\"GCS_WGS_1984\"") || prj.starts_with("GEOGCS[\"WGS 84\"") {
+        return Some(Crs::default());
+    }
+
+    None
+}
+
+pub(crate) fn module() -> Module {
+    let uploads = Router::new().route("/collections/:collection_id/import", post(import));
+
+    Module::new(Router::new()).uploads(uploads)
+}
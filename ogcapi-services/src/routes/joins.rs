@@ -0,0 +1,349 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::LOCATION, HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use ogcapi_types::{
+    common::{
+        link_rel::{COLLECTION, FILES, JOINS, SELF},
+        media_type::{CSV, JSON},
+        Link, LinkBuilder,
+    },
+    joins::{DataFile, DataFiles, Join, Joins},
+    processes::{StatusCode as JobStatus, StatusInfo},
+};
+
+use crate::{
+    access::deny_restricted, extractors::RemoteUrl, routes::Module, upload::Upload, AppState,
+    Error, Result,
+};
+
+const CONFORMANCE: [&str; 4] = [
+    "http://www.opengis.net/spec/ogcapi-joins-1/1.0/conf/core",
+    "http://www.opengis.net/spec/ogcapi-joins-1/1.0/conf/data-joining",
+    "http://www.opengis.net/spec/ogcapi-joins-1/1.0/conf/file-joining",
+    "http://www.opengis.net/spec/ogcapi-joins-1/1.0/conf/join-delete",
+];
+
+#[derive(Deserialize, Debug)]
+struct FileQuery {
+    /// Name of the uploaded file
+    name: Option<String>,
+}
+
+async fn files(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+) -> Result<Json<DataFiles>> {
+    let mut files = state.drivers.joins.list_files().await?;
+
+    let links = LinkBuilder::new(&url).mediatype(JSON);
+
+    for file in files.iter_mut() {
+        file.links = vec![links
+            .link(&format!("files/{}", file.id), SELF)?
+            .mediatype(JSON)];
+    }
+
+    Ok(Json(DataFiles {
+        files,
+        links: vec![links.self_link()],
+    }))
+}
+
+/// Upload a `CSV` attribute file
+async fn upload(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Query(query): Query<FileQuery>,
+    upload: Upload,
+) -> Result<(StatusCode, HeaderMap)> {
+    let mut reader = csv::Reader::from_path(upload.path())
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let fields: Vec<String> = reader
+        .headers()
+        .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?
+        .iter()
+        .map(|f| f.trim().to_owned())
+        .collect();
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record =
+            record.map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+        let row: Map<String, Value> = fields
+            .iter()
+            .zip(record.iter())
+            .map(|(field, value)| (field.to_owned(), parse_value(value)))
+            .collect();
+
+        rows.push(row);
+    }
+
+    let file = DataFile {
+        name: query.name,
+        fields,
+        number_of_rows: Some(rows.len() as u64),
+        ..Default::default()
+    };
+
+    let id = state.drivers.joins.create_file(&file, &rows).await?;
+
+    let location = url.join(&format!("files/{id}"))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    Ok((StatusCode::CREATED, headers))
+}
+
+async fn file(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(id): Path<String>,
+) -> Result<Json<DataFile>> {
+    let mut file = state
+        .drivers
+        .joins
+        .read_file(&id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    file.links = vec![LinkBuilder::new(&url).mediatype(JSON).self_link()];
+
+    Ok(Json(file))
+}
+
+async fn delete_file(State(state): State<AppState>, Path(id): Path<String>) -> Result<StatusCode> {
+    state.drivers.joins.delete_file(&id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn joins(State(state): State<AppState>, RemoteUrl(url): RemoteUrl) -> Result<Json<Joins>> {
+    let mut joins = state.drivers.joins.list_joins().await?;
+
+    let links = LinkBuilder::new(&url).mediatype(JSON);
+
+    for join in joins.iter_mut() {
+        join.links = vec![links
+            .link(&format!("joins/{}", join.id), SELF)?
+            .mediatype(JSON)];
+    }
+
+    Ok(Json(Joins {
+        joins,
+        links: vec![links.self_link()],
+    }))
+}
+
+/// Create a new join, executed asynchronously and tracked as job
+async fn create(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    request_headers: HeaderMap,
+    Json(join): Json<Join>,
+) -> Result<(StatusCode, HeaderMap, Json<StatusInfo>)> {
+    let problems = join.validate();
+    if !problems.is_empty() {
+        return Err(Error::Invalid(problems));
+    }
+
+    // the joined collection is served without access filter
+    deny_restricted(&state, &request_headers, &[&join.collection_id]).await?;
+    if state
+        .drivers
+        .access
+        .access_filter(&join.collection_id, None)
+        .await?
+        .is_some()
+    {
+        return Err(Error::Exception(
+            StatusCode::FORBIDDEN,
+            format!(
+                "Access to collection `{}` is restricted to its features",
+                join.collection_id
+            ),
+        ));
+    }
+
+    if state
+        .services
+        .collections
+        .read_collection(&join.collection_id)
+        .await?
+        .is_none()
+    {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Unknown collection `{}`", join.collection_id),
+        ));
+    }
+
+    match state.drivers.joins.read_file(&join.file_id).await? {
+        Some(file) if file.fields.contains(&join.file_key) => {}
+        Some(_) => {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Unknown field `{}` in file `{}`",
+                    join.file_key, join.file_id
+                ),
+            ))
+        }
+        None => {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Unknown file `{}`", join.file_id),
+            ))
+        }
+    }
+
+    if let Some(target) = &join.target_collection_id {
+        if state
+            .services
+            .collections
+            .read_collection(target)
+            .await?
+            .is_some()
+        {
+            return Err(Error::Exception(
+                StatusCode::CONFLICT,
+                format!("Collection with id `{}` already exists.", target),
+            ));
+        }
+    }
+
+    let id = state.drivers.joins.create_join(&join).await?;
+    let join = Join { id, ..join };
+
+    let mut job = StatusInfo {
+        process_id: Some("join".to_string()),
+        job_id: join.id.to_owned(),
+        status: JobStatus::Accepted,
+        ..Default::default()
+    };
+    state.drivers.jobs.register(&job).await?;
+
+    let job_state = state.clone();
+    let job_join = join.clone();
+    tokio::spawn(async move {
+        let drivers = &job_state.drivers;
+
+        job.status = JobStatus::Running;
+        if let Err(e) = drivers.jobs.update(&job).await {
+            tracing::error!("Failed to update job `{}`: {:?}", job.job_id, e);
+        }
+
+        match drivers.joins.execute_join(&job_join).await {
+            Ok(()) => {
+                job.status = JobStatus::Successful;
+                job.progress = Some(100);
+            }
+            Err(e) => {
+                tracing::error!("Join `{}` failed: {:?}", job_join.id, e);
+                job.status = JobStatus::Failed;
+                job.message = Some(e.to_string());
+            }
+        }
+        job.finished = Some(Utc::now());
+
+        if let Err(e) = drivers.jobs.update(&job).await {
+            tracing::error!("Failed to update job `{}`: {:?}", job.job_id, e);
+        }
+    });
+
+    let location = url.join(&format!("joins/{}", join.id))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    let info = StatusInfo {
+        process_id: Some("join".to_string()),
+        job_id: join.id,
+        status: JobStatus::Accepted,
+        links: vec![LinkBuilder::new(&location).mediatype(JSON).self_link()],
+        ..Default::default()
+    };
+
+    Ok((StatusCode::CREATED, headers, Json(info)))
+}
+
+async fn read(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(id): Path<String>,
+) -> Result<Json<Join>> {
+    let mut join = state
+        .drivers
+        .joins
+        .read_join(&id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let links = LinkBuilder::new(&url).mediatype(JSON);
+
+    join.links = vec![
+        links.self_link(),
+        links.link(&format!("../collections/{}", join.target()), COLLECTION)?,
+    ];
+
+    #[cfg(feature = "processes")]
+    join.links.push(links.link(
+        &format!("../jobs/{}", join.id),
+        ogcapi_types::common::link_rel::STATUS,
+    )?);
+
+    Ok(Json(join))
+}
+
+async fn remove(State(state): State<AppState>, Path(id): Path<String>) -> Result<StatusCode> {
+    state.drivers.joins.delete_join(&id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Interpret numeric cells as numbers, everything else as strings
+fn parse_value(value: &str) -> Value {
+    let value = value.trim();
+    if value.is_empty() {
+        Value::Null
+    } else if let Ok(i) = value.parse::<i64>() {
+        Value::from(i)
+    } else if let Ok(f) = value.parse::<f64>() {
+        Value::from(f)
+    } else {
+        Value::from(value)
+    }
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
+        .route("/files", get(files))
+        .route("/files/:id", get(file).delete(delete_file))
+        .route("/joins", get(joins).post(create))
+        .route("/joins/:id", get(read).delete(remove));
+
+    let uploads = Router::new().route("/files", post(upload));
+
+    Module::new(router)
+        .uploads(uploads)
+        .conformance(&CONFORMANCE)
+        .link(
+            Link::new("joins", JOINS)
+                .title("Joins of feature collections with attribute files")
+                .mediatype(JSON),
+        )
+        .link(
+            Link::new("files", FILES)
+                .title(format!("Attribute files (`{CSV}`) available for joins"))
+                .mediatype(JSON),
+        )
+}
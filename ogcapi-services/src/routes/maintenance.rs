@@ -0,0 +1,39 @@
+use axum::{extract::State, http::HeaderMap, routing::get, Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::{access, routes::Module, state::Mode, AppState, Result};
+
+/// Availability of the service
+#[derive(Serialize, Deserialize, Debug)]
+struct ModeBody {
+    mode: Mode,
+}
+
+/// Current mode of the service
+async fn mode(State(state): State<AppState>, headers: HeaderMap) -> Result<Json<ModeBody>> {
+    access::require_admin(&state, &headers).await?;
+
+    Ok(Json(ModeBody {
+        mode: state.current_mode(),
+    }))
+}
+
+/// Switch the mode of all replicas
+async fn switch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<ModeBody>,
+) -> Result<Json<ModeBody>> {
+    access::require_admin(&state, &headers).await?;
+
+    tracing::info!("Switching to mode `{:?}`", body.mode);
+    state.set_mode(body.mode).await?;
+
+    Ok(Json(body))
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new().route("/admin/mode", get(mode).put(switch));
+
+    Module::new(router)
+}
@@ -0,0 +1,343 @@
+use axum::{
+    extract::{Path, State},
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+
+use ogcapi_drivers::raster::{DataType, Raster, RasterRequest};
+#[cfg(feature = "tiles")]
+use ogcapi_types::tiles::CornerOfOrigin;
+use ogcapi_types::{
+    common::media_type::{JPEG, PNG},
+    styles::RasterStyle,
+};
+
+use crate::{
+    extractors::Qs,
+    routes::{
+        coverages::{read_grid, CoverageQuery},
+        Module,
+    },
+    AppState, Error, Result,
+};
+
+const CONFORMANCE: [&str; 6] = [
+    "http://www.opengis.net/spec/ogcapi-maps-1/1.0/conf/core",
+    "http://www.opengis.net/spec/ogcapi-maps-1/1.0/conf/scaling",
+    "http://www.opengis.net/spec/ogcapi-maps-1/1.0/conf/spatial-subsetting",
+    "http://www.opengis.net/spec/ogcapi-maps-1/1.0/conf/crs",
+    "http://www.opengis.net/spec/ogcapi-maps-1/1.0/conf/png",
+    "http://www.opengis.net/spec/ogcapi-maps-1/1.0/conf/jpeg",
+];
+
+/// Width of maps without requested size
+const DEFAULT_WIDTH: usize = 1024;
+
+/// Parameters of a map
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct MapQuery {
+    bbox: Option<String>,
+    bbox_crs: Option<String>,
+    crs: Option<String>,
+    width: Option<usize>,
+    height: Option<usize>,
+    /// Whether pixels without data are transparent, ignored for JPEG
+    #[serde(default = "default_transparent")]
+    transparent: bool,
+    /// Stored style rendering the coverage, `default` for the stretch of the
+    /// values, defaults to the style with the id of the collection if any
+    style: Option<String>,
+    /// Output format, `png` (default) or `jpeg`
+    f: Option<String>,
+}
+
+fn default_transparent() -> bool {
+    true
+}
+
+#[cfg(feature = "tiles")]
+#[derive(Deserialize, Debug)]
+struct MapTileParams {
+    collection_id: String,
+    tms_id: String,
+    matrix: String,
+    row: u32,
+    col: u32,
+}
+
+/// Map of the coverage of a collection
+async fn map(
+    State(state): State<AppState>,
+    Path(collection_id): Path<String>,
+    Qs(query): Qs<MapQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+    let grid = read_grid(&state, &collection_id, &headers).await?;
+
+    let media_type = media_type(query.f.as_deref())?;
+    let request = CoverageQuery {
+        bbox: query.bbox,
+        bbox_crs: query.bbox_crs,
+        crs: query.crs,
+        width: query
+            .width
+            .or(query.height.is_none().then_some(DEFAULT_WIDTH)),
+        height: query.height,
+        ..Default::default()
+    }
+    .raster_request(&grid)?;
+
+    render(
+        &state,
+        &collection_id,
+        &request,
+        media_type,
+        query.transparent,
+        query.style.as_deref(),
+    )
+    .await
+}
+
+/// Map tile of the coverage of a collection
+#[cfg(feature = "tiles")]
+async fn map_tile(
+    State(state): State<AppState>,
+    Path(params): Path<MapTileParams>,
+    Qs(query): Qs<MapQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+    read_grid(&state, &params.collection_id, &headers).await?;
+
+    let (tms, tile_matrix) =
+        super::tiles::tile_matrix(&params.tms_id, &params.matrix).ok_or(Error::NotFound)?;
+    if u64::from(params.row) >= tile_matrix.matrix_height.get()
+        || u64::from(params.col) >= tile_matrix.matrix_width.get()
+    {
+        return Err(Error::NotFound);
+    }
+
+    let (width, height) = (
+        usize::from(tile_matrix.tile_width.get()),
+        usize::from(tile_matrix.tile_height.get()),
+    );
+    let (w, h) = (
+        tile_matrix.cell_size * width as f64,
+        tile_matrix.cell_size * height as f64,
+    );
+    let [x, y] = tile_matrix.point_of_origin;
+    let (row, col) = (f64::from(params.row), f64::from(params.col));
+
+    let (minx, maxx) = (x + col * w, x + (col + 1.0) * w);
+    let (miny, maxy) = match tile_matrix.corner_of_origin.as_ref() {
+        Some(CornerOfOrigin::BottomLeft) => (y + row * h, y + (row + 1.0) * h),
+        _ => (y - (row + 1.0) * h, y - row * h),
+    };
+
+    let request = RasterRequest {
+        bbox: Some([minx, miny, maxx, maxy]),
+        crs: Some(tms.crs.to_owned()),
+        width: Some(width),
+        height: Some(height),
+        ..Default::default()
+    };
+
+    let media_type = media_type(query.f.as_deref())?;
+
+    render(
+        &state,
+        &params.collection_id,
+        &request,
+        media_type,
+        query.transparent,
+        query.style.as_deref(),
+    )
+    .await
+}
+
+async fn render(
+    state: &AppState,
+    collection_id: &str,
+    request: &RasterRequest,
+    media_type: &'static str,
+    transparent: bool,
+    style: Option<&str>,
+) -> Result<impl IntoResponse> {
+    let style = raster_style(state, collection_id, style).await?;
+
+    let raster = state.drivers.rasters.read(collection_id, request).await?;
+    let alpha = transparent && media_type == PNG;
+    let image = match style {
+        Some(style) => colorize(&raster, &style, alpha),
+        None => stretch(&raster, alpha),
+    };
+    let bytes = state.drivers.rasters.encode(&image, media_type).await?;
+
+    Ok(([(CONTENT_TYPE, media_type)], bytes))
+}
+
+/// Raster style of the requested stored style, or else of the style with the
+/// id of the collection, `None` to stretch the values
+async fn raster_style(
+    state: &AppState,
+    collection_id: &str,
+    style: Option<&str>,
+) -> Result<Option<RasterStyle>> {
+    let stylesheet = match style {
+        Some("default") => return Ok(None),
+        Some(id) => Some(state.services.styles.read_style(id).await?.ok_or_else(|| {
+            Error::Exception(StatusCode::BAD_REQUEST, format!("Unknown style `{id}`"))
+        })?),
+        None => state.services.styles.read_style(collection_id).await?,
+    };
+
+    // styles of vector layers only fall back to the stretch
+    Ok(stylesheet.and_then(|stylesheet| RasterStyle::from_stylesheet(&stylesheet, collection_id)))
+}
+
+/// Byte image of a raster rendered with a style, the first band by its color
+/// map if any and else as by [stretch], as RGB with alpha if requested
+fn colorize(raster: &Raster, style: &RasterStyle, alpha: bool) -> Raster {
+    let pixels = raster.width * raster.height;
+
+    let rgba: Vec<[u8; 4]> = match (&style.color_map, raster.bands.first()) {
+        (Some(color_map), Some(values)) => values
+            .iter()
+            .map(|v| {
+                let valid = !v.is_nan() && raster.nodata.is_none_or(|nodata| *v != nodata);
+                valid
+                    .then(|| color_map.color(*v))
+                    .flatten()
+                    .unwrap_or([0; 4])
+            })
+            .collect(),
+        _ => {
+            let image = stretch(raster, true);
+            let [gray_or_red, .., mask] = &image.bands[..] else {
+                return image;
+            };
+            let (green, blue) = match &image.bands[..] {
+                [_, green, blue, _] => (green, blue),
+                _ => (gray_or_red, gray_or_red),
+            };
+            (0..pixels)
+                .map(|i| [gray_or_red[i], green[i], blue[i], mask[i]].map(|channel| channel as u8))
+                .collect()
+        }
+    };
+
+    let rgba: Vec<[u8; 4]> = rgba.into_iter().map(|pixel| style.adjust(pixel)).collect();
+
+    let channels = if alpha { 4 } else { 3 };
+    let bands = (0..channels)
+        .map(|channel| {
+            rgba.iter()
+                .map(|pixel| match alpha {
+                    true => f64::from(pixel[channel]),
+                    // without alpha pixels are blended over white
+                    false => {
+                        let a = f64::from(pixel[3]) / 255.0;
+                        (f64::from(pixel[channel]) * a + 255.0 * (1.0 - a)).round()
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    Raster {
+        width: raster.width,
+        height: raster.height,
+        bbox: raster.bbox,
+        crs: raster.crs.to_owned(),
+        bands,
+        data_type: DataType::Byte,
+        nodata: None,
+    }
+}
+
+/// Byte image of a raster, one band as grayscale and three or more as RGB,
+/// each stretched between its minimum and maximum unless already bytes
+fn stretch(raster: &Raster, alpha: bool) -> Raster {
+    let bands: Vec<&Vec<f64>> = if raster.bands.len() >= 3 {
+        raster.bands.iter().take(3).collect()
+    } else {
+        raster.bands.iter().take(1).collect()
+    };
+
+    let valid = |v: f64| !v.is_nan() && raster.nodata.is_none_or(|nodata| v != nodata);
+
+    let mut image: Vec<Vec<f64>> = bands
+        .iter()
+        .map(|values| {
+            let (min, max) = values
+                .iter()
+                .filter(|v| valid(**v))
+                .fold((f64::MAX, f64::MIN), |(min, max), v| {
+                    (min.min(*v), max.max(*v))
+                });
+            let scale = if raster.data_type == DataType::Byte {
+                None
+            } else {
+                Some((min, (max - min).max(f64::EPSILON)))
+            };
+
+            values
+                .iter()
+                .map(|v| match scale {
+                    _ if !valid(*v) => 0.0,
+                    Some((min, range)) => ((v - min) / range * 255.0).round(),
+                    None => *v,
+                })
+                .collect()
+        })
+        .collect();
+
+    if alpha {
+        let mask = (0..raster.width * raster.height)
+            .map(|i| {
+                if bands.iter().all(|values| valid(values[i])) {
+                    255.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        image.push(mask);
+    }
+
+    Raster {
+        width: raster.width,
+        height: raster.height,
+        bbox: raster.bbox,
+        crs: raster.crs.to_owned(),
+        bands: image,
+        data_type: DataType::Byte,
+        nodata: None,
+    }
+}
+
+fn media_type(f: Option<&str>) -> Result<&'static str> {
+    match f {
+        None | Some("png") => Ok(PNG),
+        Some("jpg" | "jpeg") => Ok(JPEG),
+        Some(f) => Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported format `{f}`"),
+        )),
+    }
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new().route("/collections/:collection_id/map", get(map));
+
+    #[cfg(feature = "tiles")]
+    let router = router.route(
+        "/collections/:collection_id/map/tiles/:tms_id/:matrix/:row/:col",
+        get(map_tile),
+    );
+
+    Module::new(router).conformance(&CONFORMANCE)
+}
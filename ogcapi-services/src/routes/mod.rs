@@ -1,64 +1,194 @@
+#[cfg(feature = "aggregate")]
+pub(crate) mod aggregate;
 pub(crate) mod api;
+#[cfg(feature = "attachments")]
+pub(crate) mod attachments;
+pub(crate) mod catalogs;
 pub(crate) mod collections;
+#[cfg(feature = "coverages")]
+pub(crate) mod coverages;
 #[cfg(feature = "edr")]
 pub(crate) mod edr;
 #[cfg(feature = "features")]
 pub(crate) mod features;
+#[cfg(feature = "harvest")]
+pub(crate) mod harvests;
+#[cfg(feature = "import")]
+pub(crate) mod import;
+#[cfg(feature = "joins")]
+pub(crate) mod joins;
+pub(crate) mod maintenance;
+#[cfg(feature = "maps")]
+pub(crate) mod maps;
 #[cfg(feature = "processes")]
 pub(crate) mod processes;
+#[cfg(feature = "search")]
+pub(crate) mod search;
 #[cfg(feature = "stac")]
 pub(crate) mod stac;
 #[cfg(feature = "styles")]
 pub(crate) mod styles;
+pub(crate) mod tasks;
 #[cfg(feature = "tiles")]
 pub(crate) mod tiles;
+#[cfg(feature = "uploads")]
+pub(crate) mod uploads;
+pub(crate) mod usage;
+#[cfg(feature = "views")]
+pub(crate) mod views;
+#[cfg(feature = "webhooks")]
+pub(crate) mod webhooks;
+#[cfg(feature = "zarr")]
+pub(crate) mod zarr;
 
-use axum::{extract::State, Json};
+use axum::{
+    extract::State,
+    http::{header::ACCEPT, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
 
-#[cfg(feature = "stac")]
-use ogcapi_types::common::link_rel::SEARCH;
 use ogcapi_types::common::{
-    link_rel::{CONFORMANCE, ROOT, SELF, SERVICE_DESC, SERVICE_DOC},
-    media_type::{HTML, JSON, OPEN_API_JSON},
-    Conformance, LandingPage, Link, Linked,
+    link_rel::{CONFORMANCE, ROOT, SERVICE_DESC, SERVICE_DOC},
+    media_type::{JSON, JSON_LD},
+    Conformance, LandingPage, Link, LinkBuilder, Linked,
 };
 
 use crate::{extractors::RemoteUrl, AppState, Result};
 
+/// Routes of an API module, together with the conformance classes it
+/// implements and the links it adds to the landing page
+///
+/// Modules are registered when mounted, so that the landing page and the
+/// conformance declaration only advertise what is actually served.
+/// Routes accepting uploads are kept apart, as they are subject to the
+/// upload instead of the regular body size limit.
+pub(crate) struct Module {
+    pub(crate) router: Router<AppState>,
+    pub(crate) uploads: Router<AppState>,
+    pub(crate) conformance: Vec<&'static str>,
+    pub(crate) links: Vec<Link>,
+}
+
+impl Module {
+    pub(crate) fn new(router: Router<AppState>) -> Self {
+        Module {
+            router,
+            uploads: Router::new(),
+            conformance: Vec::new(),
+            links: Vec::new(),
+        }
+    }
+
+    /// Add routes accepting uploads
+    #[cfg(any(
+        feature = "features",
+        feature = "joins",
+        feature = "processes",
+        feature = "uploads"
+    ))]
+    pub(crate) fn uploads(mut self, router: Router<AppState>) -> Self {
+        self.uploads = self.uploads.merge(router);
+        self
+    }
+
+    /// Declare conformance classes implemented by the module
+    pub(crate) fn conformance(mut self, classes: &[&'static str]) -> Self {
+        self.conformance.extend_from_slice(classes);
+        self
+    }
+
+    /// Add a link to the landing page, relative to the root
+    pub(crate) fn link(mut self, link: Link) -> Self {
+        self.links.push(link);
+        self
+    }
+}
+
+/// Output format requested with the `f` parameter
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct Format {
+    pub(crate) f: Option<String>,
+}
+
+/// Whether JSON-LD is requested with `f=jsonld` or the `Accept` header
+pub(crate) fn wants_json_ld(headers: &HeaderMap, f: Option<&str>) -> bool {
+    f == Some("jsonld")
+        || headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| v.split(',').any(|m| m.trim().starts_with(JSON_LD)))
+}
+
+/// What a write request would have done, returned instead in dry-run mode
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DryRunReport {
+    pub(crate) dry_run: bool,
+    /// `create`, `replace` or `delete`
+    pub(crate) action: &'static str,
+    /// Status the request would have been answered with
+    pub(crate) status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) number_created: Option<usize>,
+}
+
+impl DryRunReport {
+    pub(crate) fn new(action: &'static str, status: StatusCode) -> Self {
+        DryRunReport {
+            dry_run: true,
+            action,
+            status: status.as_u16(),
+            location: None,
+            number_created: None,
+        }
+    }
+}
+
+impl IntoResponse for DryRunReport {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
 pub(crate) async fn root(
     RemoteUrl(url): RemoteUrl,
     State(state): State<AppState>,
 ) -> Result<Json<LandingPage>> {
-    let mut root = state.root.read().unwrap().to_owned();
+    let mut root = state.root.as_ref().to_owned();
+
+    let url = format!("{}/", url.as_str().trim_end_matches('/')).parse()?;
+    let links = LinkBuilder::new(&url).mediatype(JSON);
 
     root.links.insert_or_update(&[
-        Link::new(format!("{}/", url.as_str().trim_end_matches('/')), SELF).mediatype(JSON),
-        Link::new(".", ROOT).mediatype(JSON),
-        Link::new("api", SERVICE_DESC)
-            .title("The Open API definition")
-            .mediatype(OPEN_API_JSON),
-        Link::new("swagger", SERVICE_DOC)
-            .title("The Open API definition (Swagger UI)")
-            .mediatype(HTML),
-        // Link::new("redoc", SERVICE_DOC)
-        //     .title("The Open API definition (Redoc")
-        //     .mediatype(HTML),
-        Link::new("conformance", CONFORMANCE)
-            .title("Conformance classes implemented by this API")
-            .mediatype(JSON),
-        #[cfg(feature = "stac")]
-        Link::new("search", SEARCH)
-            .title("URI for the STAC API - Item Search endpoint")
-            .mediatype(JSON),
+        links.self_link(),
+        links.link(".", ROOT)?,
+        links
+            .link("api", SERVICE_DESC)?
+            .title("The Open API definition"),
+        links
+            .link("swagger", SERVICE_DOC)?
+            .title("The Open API definition (Swagger UI)"),
+        // links
+        //     .link("redoc", SERVICE_DOC)?
+        //     .title("The Open API definition (Redoc"),
+        links
+            .link("conformance", CONFORMANCE)?
+            .title("Conformance classes implemented by this API"),
     ]);
+    root.links.extend(catalogs::root_links(&state, &url).await?);
     root.links.resolve_relative_links();
 
     #[cfg(feature = "stac")]
-    let root = root.conforms_to(&state.conformance.read().unwrap().conforms_to[..]);
+    let root = root.conforms_to(&state.conformance.conforms_to[..]);
 
     Ok(Json(root))
 }
 
 pub(crate) async fn conformance(State(state): State<AppState>) -> Json<Conformance> {
-    Json(state.conformance.read().unwrap().to_owned())
+    Json(state.conformance.as_ref().to_owned())
 }
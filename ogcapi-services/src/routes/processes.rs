@@ -1,38 +1,45 @@
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
-use url::Position;
+use serde_json::Value;
+use tokio_util::io::ReaderStream;
 
 use ogcapi_types::{
     common::{
-        link_rel::{NEXT, PREV, PROCESSES, SELF},
-        media_type::JSON,
-        Link,
+        link_rel::{JOB_LIST, NEXT, PREV, PROCESSES, SELF, STATUS},
+        media_type::{JSON, OCTET_STREAM},
+        Link, LinkBuilder,
+    },
+    processes::{
+        Execute, InlineOrRefData, JobList, JobQuery, Process, ProcessList, ProcessQuery,
+        ProcessSummary,
     },
-    processes::{Execute, Process, ProcessList, ProcessQuery, ProcessSummary},
 };
 
-use crate::{extractors::RemoteUrl, AppState, Error, Result};
+use crate::{
+    access::require_admin, extractors::RemoteUrl, routes::Module, AppState, Error, Result,
+};
 
-const CONFORMANCE: [&str; 4] = [
+const CONFORMANCE: [&str; 5] = [
     "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/core",
     "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/ogc-process-description",
     "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/json",
     // "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/html",
     // "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/oas30",
-    // "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/job-list",
+    "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/job-list",
     // "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/callback",
     "http://www.opengis.net/spec/ogcapi-processes-1/1.0/conf/dismiss",
 ];
 
 async fn processes(
     State(state): State<AppState>,
-    RemoteUrl(mut url): RemoteUrl,
-    Query(mut query): Query<ProcessQuery>,
+    RemoteUrl(url): RemoteUrl,
+    Query(query): Query<ProcessQuery>,
 ) -> Result<Json<ProcessList>> {
     let limit = query
         .limit
@@ -50,33 +57,26 @@ async fn processes(
         .map(|(_id, p)| p.process().summary)
         .collect();
 
-    let mut links = vec![Link::new(&url, SELF).mediatype(JSON)];
+    let builder = LinkBuilder::new(&url).mediatype(JSON);
+
+    let mut links = vec![builder.self_link()];
 
     if query.limit.is_some() {
         if offset != 0 && offset >= limit {
-            query.offset = Some(offset - limit);
-            let query_string = serde_qs::to_string(&query)?;
-            url.set_query(Some(&query_string));
-            let previous = Link::new(&url, PREV).mediatype(JSON);
-            links.push(previous);
+            links.push(builder.page(PREV, offset - limit, limit));
         }
 
         if summaries.len() == limit {
-            query.offset = Some(offset + limit);
-            let query_string = serde_qs::to_string(&query)?;
-            url.set_query(Some(&query_string));
-            let next = Link::new(&url, NEXT).mediatype(JSON);
-            links.push(next);
+            links.push(builder.page(NEXT, offset + limit, limit));
         }
     }
 
-    summaries.iter_mut().for_each(|p| {
-        p.links = vec![
-            Link::new(format!("{}/{}", &url[..Position::AfterPath], p.id), SELF)
-                .mediatype(JSON)
-                .title("process description"),
-        ];
-    });
+    for p in summaries.iter_mut() {
+        p.links = vec![builder
+            .link(&format!("processes/{}", p.id), SELF)?
+            .mediatype(JSON)
+            .title("process description")];
+    }
 
     let process_list = ProcessList {
         processes: summaries,
@@ -95,7 +95,7 @@ async fn process(
         Some(processor) => {
             let mut process = processor.process();
 
-            process.summary.links = vec![Link::new(url, SELF).mediatype(JSON)];
+            process.summary.links = vec![LinkBuilder::new(&url).mediatype(JSON).self_link()];
 
             Ok(Json(process))
         }
@@ -110,12 +110,32 @@ async fn execution(
     State(state): State<AppState>,
     RemoteUrl(url): RemoteUrl,
     Path(id): Path<String>,
-    Json(execute): Json<Execute>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
 ) -> Result<Response> {
+    // openEO clients post process graphs instead of inputs
+    #[cfg(feature = "openeo")]
+    let body = crate::processor::wrap_request(body);
+
+    let execute: Execute = serde_json::from_value(body)
+        .map_err(|e| Error::Exception(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+
     let processors = state.processors.read().unwrap().clone();
     let processor = processors.get(&id);
     match processor {
-        Some(processor) => processor.execute(execute, &state, &url).await,
+        Some(processor) => {
+            if processor.admin() {
+                require_admin(&state, &headers).await?;
+            }
+
+            // reject malformed inputs before processors get to see them
+            let violations = processor.process().check(&execute);
+            if !violations.is_empty() {
+                return Err(Error::Invalid(violations));
+            }
+
+            processor.execute(execute, &state, &url).await
+        }
         None => Err(Error::Exception(
             StatusCode::NOT_FOUND,
             format!("No process with id `{}`", id),
@@ -123,8 +143,37 @@ async fn execution(
     }
 }
 
-async fn jobs() {
-    todo!()
+/// List jobs, latest first, optionally of a single process
+async fn jobs(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Query(query): Query<JobQuery>,
+) -> Result<Json<JobList>> {
+    let mut jobs = state.drivers.jobs.list_jobs(&query).await?;
+
+    let builder = LinkBuilder::new(&url).mediatype(JSON);
+
+    let mut links = vec![builder.self_link()];
+
+    if let Some(limit) = query.limit {
+        let offset = query.offset.unwrap_or(0);
+
+        if offset != 0 {
+            links.push(builder.page(PREV, offset.saturating_sub(limit), limit));
+        }
+
+        if jobs.len() == limit {
+            links.push(builder.page(NEXT, offset + limit, limit));
+        }
+    }
+
+    for job in jobs.iter_mut() {
+        job.links = vec![builder
+            .link(&format!("jobs/{}", job.job_id), STATUS)?
+            .mediatype(JSON)];
+    }
+
+    Ok(Json(JobList { jobs, links }))
 }
 
 async fn status(
@@ -136,7 +185,7 @@ async fn status(
 
     match status {
         Some(mut info) => {
-            info.links = vec![Link::new(url, SELF).mediatype(JSON)];
+            info.links = vec![LinkBuilder::new(&url).mediatype(JSON).self_link()];
 
             Ok(Json(info).into_response())
         }
@@ -161,13 +210,25 @@ async fn delete(State(state): State<AppState>, Path(id): Path<String>) -> Result
     }
 }
 
-async fn results(State(state): State<AppState>, Path(id): Path<String>) -> Result<Response> {
+async fn results(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(id): Path<String>,
+) -> Result<Response> {
     let results = state.drivers.jobs.results(&id).await?;
 
     // TODO: check if job is finished
 
     match results {
-        Some(results) => Ok(Json(results).into_response()),
+        Some(mut results) => {
+            // links to file outputs are relative to the results
+            for result in results.results.values_mut() {
+                if let InlineOrRefData::Link(link) = result {
+                    link.href = url.join(&link.href)?.to_string();
+                }
+            }
+            Ok(Json(results).into_response())
+        }
         None => Err(Error::Exception(
             StatusCode::NOT_FOUND,
             format!("No job with id `{}`", id),
@@ -175,24 +236,63 @@ async fn results(State(state): State<AppState>, Path(id): Path<String>) -> Resul
     }
 }
 
-pub(crate) fn router(state: &AppState) -> Router<AppState> {
-    let mut root = state.root.write().unwrap();
-    root.links.append(&mut vec![
-        Link::new("processes", PROCESSES)
-            .mediatype(JSON)
-            .title("Metadata about the processes"),
-        // Link::new("jobs", JOB_LIST)
-        //     .mediatype(JSON)
-        //     .title("The endpoint for job monitoring"),
-    ]);
+/// Download a file output of a job, only outputs listed in the results of
+/// the job are served
+async fn output(
+    State(state): State<AppState>,
+    Path((id, output)): Path<(String, String)>,
+) -> Result<Response> {
+    let media_type = match state
+        .drivers
+        .jobs
+        .results(&id)
+        .await?
+        .and_then(|mut r| r.results.remove(&output))
+    {
+        Some(InlineOrRefData::Link(link)) => {
+            link.r#type.unwrap_or_else(|| OCTET_STREAM.to_string())
+        }
+        _ => return Err(Error::NotFound),
+    };
 
-    state.conformance.write().unwrap().extend(&CONFORMANCE);
+    let path = crate::processor::output_path(&id, &output).map_err(|_| Error::NotFound)?;
 
-    Router::new()
+    let file = match tokio::fs::File::open(&path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(Error::NotFound),
+        Err(e) => return Err(Error::Anyhow(e.into())),
+    };
+
+    Ok((
+        [(CONTENT_TYPE, media_type)],
+        Body::from_stream(ReaderStream::new(file)),
+    )
+        .into_response())
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
         .route("/processes", get(processes))
         .route("/processes/:id", get(process))
-        .route("/processes/:id/execution", post(execution))
         .route("/jobs", get(jobs))
         .route("/jobs/:id", get(status).delete(delete))
         .route("/jobs/:id/results", get(results))
+        .route("/jobs/:id/results/:output", get(output));
+
+    // process inputs may be large, e.g. inline GeoPackages
+    let uploads = Router::new().route("/processes/:id/execution", post(execution));
+
+    Module::new(router)
+        .uploads(uploads)
+        .conformance(&CONFORMANCE)
+        .link(
+            Link::new("processes", PROCESSES)
+                .mediatype(JSON)
+                .title("Metadata about the processes"),
+        )
+        .link(
+            Link::new("jobs", JOB_LIST)
+                .mediatype(JSON)
+                .title("The endpoint for job monitoring"),
+        )
 }
@@ -0,0 +1,237 @@
+use axum::{
+    extract::State,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use futures::{stream, StreamExt, TryStreamExt};
+
+use ogcapi_types::{
+    common::{
+        link_rel::{COLLECTION, NEXT, PREV, ROOT, SEARCH, SELF},
+        media_type::{GEO_JSON, JSON},
+        Link, LinkBuilder, Linked,
+    },
+    features::{FeatureCollection, Query, ResultType},
+};
+
+use crate::{
+    access::{hidden_properties, read_filter},
+    extractors::{Qs, RemoteUrl, ShareLink},
+    routes::{
+        features::{check_crs, check_guardrails, check_hidden_parameters, swap_bbox_axes},
+        Module,
+    },
+    AppState, Error, Result,
+};
+
+/// Number of collections queried at once
+const CONCURRENCY: usize = 8;
+
+/// Search features of several collections at once
+///
+/// The collections are queried concurrently with the same parameters, the
+/// `limit` and `offset` apply per collection. Features are returned in the
+/// order of the requested collections, each attributed to its collection.
+async fn search(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Qs(mut query): Qs<Query>,
+    share: ShareLink,
+    request_headers: HeaderMap,
+) -> Result<(HeaderMap, Json<FeatureCollection>)> {
+    tracing::debug!("{:#?}", query);
+
+    share.strip(&mut query.additional_parameters);
+
+    let collections = query
+        .additional_parameters
+        .remove("collections")
+        .unwrap_or_default();
+
+    let mut ids: Vec<String> = Vec::new();
+    for id in collections.split(',').map(str::trim) {
+        if !id.is_empty() && !ids.iter().any(|i| i == id) {
+            ids.push(id.to_owned());
+        }
+    }
+    if ids.is_empty() {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            "query parameter `collections` required".to_string(),
+        ));
+    }
+
+    // Limit
+    if let Some(limit) = query.limit {
+        if limit > state.guardrails.max_limit {
+            query.limit = Some(state.guardrails.max_limit);
+        }
+    } else {
+        query.limit = Some(100);
+    }
+    if query.resulttype == Some(ResultType::Hits) {
+        query.limit = Some(0);
+    }
+    let limit = query.limit.unwrap_or_default();
+    let offset = *query.offset.get_or_insert(0);
+
+    let share = &share;
+    let results: Vec<FeatureCollection> = stream::iter(ids.to_owned())
+        .map(|id| {
+            let (state, query, headers) = (state.clone(), query.clone(), request_headers.clone());
+            async move { search_collection(&state, &id, &query, &headers, share).await }
+        })
+        .buffered(CONCURRENCY)
+        .try_collect()
+        .await?;
+
+    let mut features = Vec::new();
+    let mut number_matched = Some(0);
+    let mut more = false;
+
+    for fc in results {
+        number_matched = number_matched.zip(fc.number_matched).map(|(a, b)| a + b);
+        more |= limit > 0
+            && fc
+                .number_matched
+                .is_some_and(|n| n > (offset + limit) as u64);
+        features.extend(fc.features);
+    }
+
+    let mut fc = FeatureCollection::new(features);
+    fc.number_matched = number_matched;
+
+    let links = LinkBuilder::new(&url).mediatype(GEO_JSON);
+    fc.links
+        .insert_or_update(&[links.self_link(), links.link(".", ROOT)?]);
+
+    // pagination
+    if offset != 0 && limit > 0 {
+        let previous = links.page(PREV, offset.saturating_sub(limit), limit);
+        fc.links.insert_or_update(&[previous]);
+    }
+
+    if more {
+        fc.links
+            .insert_or_update(&[links.page(NEXT, offset + limit, limit)]);
+    }
+
+    for feature in fc.features.iter_mut() {
+        let collection = feature.collection.as_ref().unwrap();
+        feature.links.insert_or_update(&[
+            links
+                .link(
+                    &format!(
+                        "collections/{}/items/{}",
+                        collection,
+                        feature.id.as_ref().unwrap()
+                    ),
+                    SELF,
+                )?
+                .mediatype(GEO_JSON),
+            links.link(".", ROOT)?,
+            links.link(&format!("collections/{}", collection), COLLECTION)?,
+        ])
+    }
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Crs", query.crs.to_string().parse().unwrap());
+    headers.insert(CONTENT_TYPE, GEO_JSON.parse().unwrap());
+
+    Ok((headers, Json(fc)))
+}
+
+/// Query the features of a single collection
+async fn search_collection(
+    state: &AppState,
+    collection_id: &str,
+    query: &Query,
+    headers: &HeaderMap,
+    share: &ShareLink,
+) -> Result<FeatureCollection> {
+    let collection = state
+        .services
+        .collections
+        .read_collection(collection_id)
+        .await?
+        .ok_or_else(|| {
+            Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Unknown collection `{collection_id}`"),
+            )
+        })?;
+    check_crs(&collection, query)?;
+
+    let mut query = query.to_owned();
+    query.precision = query.precision.or(collection.precision);
+    swap_bbox_axes(&mut query);
+    query.access_filter = read_filter(state, headers, share, &collection.id).await?;
+
+    let hidden = hidden_properties(state, headers, &collection).await;
+    check_hidden_parameters(&query, &hidden)?;
+
+    // boxes outside the extent of the collection can't match any feature
+    let disjoint = match query.bbox.as_ref() {
+        Some(bbox) => {
+            state
+                .extents
+                .disjoint(
+                    state.services.collections.as_ref(),
+                    &collection,
+                    bbox,
+                    &query.bbox_crs,
+                )
+                .await
+        }
+        None => false,
+    };
+
+    let mut fc = if disjoint {
+        let mut fc = FeatureCollection::new(Vec::new());
+        fc.number_matched = Some(0);
+        fc
+    } else {
+        check_guardrails(state, headers, &collection.id, &query).await?;
+
+        state
+            .services
+            .features
+            .list_items(&collection.id, &query)
+            .await?
+    };
+
+    if query.crs.is_northing_first() {
+        fc.swap_axes();
+    }
+    if let Some(precision) = query.precision {
+        fc.round_coordinates(precision);
+    }
+    fc.remove_properties(&hidden);
+
+    for feature in fc.features.iter_mut() {
+        feature.collection = Some(collection.id.to_owned());
+    }
+
+    Ok(fc)
+}
+
+pub(crate) fn module() -> Module {
+    module_at("/search")
+}
+
+/// Search across collections next to the STAC API, which serves `/search`
+#[cfg(feature = "stac")]
+pub(crate) fn stac_module() -> Module {
+    module_at("/feature-search")
+}
+
+fn module_at(path: &str) -> Module {
+    let router = Router::new().route(path, get(search));
+
+    Module::new(router).link(
+        Link::new(path.trim_start_matches('/'), SEARCH)
+            .title("Search features across collections")
+            .mediatype(JSON),
+    )
+}
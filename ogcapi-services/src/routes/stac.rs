@@ -1,15 +1,18 @@
+use std::collections::HashMap;
+
 use axum::{
     extract::State,
     http::{HeaderMap, StatusCode},
-    Json,
+    routing::get,
+    Json, Router,
 };
 use hyper::header::CONTENT_TYPE;
 use ogcapi_drivers::StacSeach;
 use ogcapi_types::{
     common::{
-        link_rel::{COLLECTION, NEXT, PREV, ROOT, SELF},
+        link_rel::{COLLECTION, NEXT, PREV, ROOT, SEARCH, SELF},
         media_type::{GEO_JSON, JSON},
-        Bbox, Link, Linked,
+        Link, LinkBuilder, Linked, Query as CollectionQuery,
     },
     features::FeatureCollection,
     stac::{SearchBody, SearchParams},
@@ -17,30 +20,46 @@ use ogcapi_types::{
 use url::Url;
 
 use crate::{
+    access::{deny_restricted, hidden_properties, request_user},
     extractors::{Qs, RemoteUrl},
+    routes::Module,
     AppState, Error, Result,
 };
 
-pub(crate) async fn search_get(
+const CONFORMANCE: [&str; 5] = [
+    "https://api.stacspec.org/v1.0.0-rc.1/core",
+    "https://api.stacspec.org/v1.0.0-rc.1/item-search",
+    "https://api.stacspec.org/v1.0.0-rc.1/collections",
+    "https://api.stacspec.org/v1.0.0-rc.1/ogcapi-features",
+    "https://api.stacspec.org/v1.0.0-rc.1/browseable",
+];
+
+async fn search_get(
     State(state): State<AppState>,
     Qs(params): Qs<SearchParams>,
     RemoteUrl(url): RemoteUrl,
+    headers: HeaderMap,
 ) -> Result<(HeaderMap, Json<FeatureCollection>)> {
-    search(params, url, state).await
+    search(params, url, state, &headers).await
 }
 
-pub(crate) async fn search_post(
+async fn search_post(
     State(state): State<AppState>,
-    RemoteUrl(url): RemoteUrl,
+    RemoteUrl(mut url): RemoteUrl,
+    headers: HeaderMap,
     Json(params): Json<SearchBody>,
 ) -> Result<(HeaderMap, Json<FeatureCollection>)> {
-    search(params.into(), url, state).await
+    let params: SearchParams = params.into();
+    // pages are linked as `GET` requests with the posted parameters
+    url.set_query(serde_qs::to_string(&params).ok().as_deref());
+    search(params, url, state, &headers).await
 }
 
-pub(crate) async fn search(
+async fn search(
     mut params: SearchParams,
-    mut url: Url,
+    url: Url,
     state: AppState,
+    request_headers: &HeaderMap,
 ) -> Result<(HeaderMap, Json<FeatureCollection>)> {
     tracing::debug!("{:#?}", params);
 
@@ -59,32 +78,71 @@ pub(crate) async fn search(
 
     // Bbox
     if let Some(bbox) = params.bbox.as_ref() {
-        match bbox {
-            Bbox::Bbox2D(bbox) => {
-                if bbox[0] > bbox[2] || bbox[1] > bbox[3] {
-                    return Err(Error::Exception(
-                        StatusCode::BAD_REQUEST,
-                        "query parameter `bbox` not valid".to_string(),
-                    ));
-                }
-            }
-            Bbox::Bbox3D(bbox) => {
-                if bbox[0] > bbox[3] || bbox[1] > bbox[4] || bbox[2] > bbox[5] {
-                    return Err(Error::Exception(
-                        StatusCode::BAD_REQUEST,
-                        "query parameter `bbox` not valid".to_string(),
-                    ));
+        if !bbox.is_valid() {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                "query parameter `bbox` not valid".to_string(),
+            ));
+        }
+    }
+
+    // the search can't apply access filters, restricted collections are
+    // refused when requested and left out otherwise
+    match &params.collections {
+        Some(collections) => {
+            let collections: Vec<&str> = collections.iter().map(String::as_str).collect();
+            deny_restricted(&state, request_headers, &collections).await?;
+        }
+        None => {
+            let user = request_user(&state, request_headers).await;
+            let all = state
+                .drivers
+                .collections
+                .list_collections(&CollectionQuery::default())
+                .await?;
+
+            let mut collections = Vec::new();
+            for collection in all.collections {
+                if state
+                    .drivers
+                    .access
+                    .access_filter(&collection.id, user.as_deref())
+                    .await?
+                    .is_none()
+                {
+                    collections.push(collection.id);
                 }
             }
+            params.collections = Some(collections);
         }
     }
 
     let mut fc = state.db.search(&params).await?;
 
-    fc.links.insert_or_update(&[
-        Link::new(&url, SELF).mediatype(GEO_JSON),
-        Link::new(url.join("../..")?, ROOT).mediatype(JSON),
-    ]);
+    // properties hidden by the redactions of the collections
+    let mut hidden = HashMap::new();
+    for feature in fc.features.iter_mut() {
+        let Some(collection) = feature.collection.to_owned() else {
+            continue;
+        };
+        if !hidden.contains_key(&collection) {
+            let properties = match state
+                .drivers
+                .collections
+                .read_collection(&collection)
+                .await?
+            {
+                Some(c) => hidden_properties(&state, request_headers, &c).await,
+                None => Vec::new(),
+            };
+            hidden.insert(collection.to_owned(), properties);
+        }
+        feature.remove_properties(&hidden[&collection]);
+    }
+
+    let links = LinkBuilder::new(&url).mediatype(GEO_JSON);
+    fc.links
+        .insert_or_update(&[links.self_link(), links.link("../..", ROOT)?]);
 
     // pagination
     if let Some(limit) = params.limit {
@@ -94,17 +152,13 @@ pub(crate) async fn search(
 
         if let Some(offset) = params.offset {
             if offset != 0 && offset >= limit {
-                params.offset = Some(offset - limit);
-                url.set_query(serde_qs::to_string(&params).ok().as_deref());
-                let previous = Link::new(&url, PREV).mediatype(GEO_JSON);
+                let previous = links.page(PREV, (offset - limit) as usize, limit as usize);
                 fc.links.insert_or_update(&[previous]);
             }
 
             if let Some(number_matched) = fc.number_matched {
                 if number_matched > offset + limit {
-                    params.offset = Some(offset + limit);
-                    url.set_query(serde_qs::to_string(&params).ok().as_deref());
-                    let next = Link::new(&url, NEXT).mediatype(GEO_JSON);
+                    let next = links.page(NEXT, (offset + limit) as usize, limit as usize);
                     fc.links.insert_or_update(&[next]);
                 }
             }
@@ -114,21 +168,18 @@ pub(crate) async fn search(
     for feature in fc.features.iter_mut() {
         let collection = feature.collection.as_ref().unwrap();
         feature.links.insert_or_update(&[
-            Link::new(
-                url.join(&format!(
-                    "collections/{}/items/{}",
-                    collection,
-                    feature.id.as_ref().unwrap()
-                ))?,
-                SELF,
-            )
-            .mediatype(GEO_JSON),
-            Link::new(url.join(".")?, ROOT).mediatype(JSON),
-            Link::new(
-                url.join(&format!("collections/{}", collection))?,
-                COLLECTION,
-            )
-            .mediatype(JSON),
+            links
+                .link(
+                    &format!(
+                        "collections/{}/items/{}",
+                        collection,
+                        feature.id.as_ref().unwrap()
+                    ),
+                    SELF,
+                )?
+                .mediatype(GEO_JSON),
+            links.link(".", ROOT)?,
+            links.link(&format!("collections/{}", collection), COLLECTION)?,
         ])
     }
 
@@ -137,3 +188,13 @@ pub(crate) async fn search(
 
     Ok((headers, Json(fc)))
 }
+
+pub(crate) fn module() -> Module {
+    let router = Router::new().route("/search", get(search_get).post(search_post));
+
+    Module::new(router).conformance(&CONFORMANCE).link(
+        Link::new("search", SEARCH)
+            .title("URI for the STAC API - Item Search endpoint")
+            .mediatype(JSON),
+    )
+}
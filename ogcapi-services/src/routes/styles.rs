@@ -1,27 +1,326 @@
+use std::fmt::Write;
+
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::{
+        header::{ACCEPT, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
     routing::get,
     Json, Router,
 };
+use serde::Deserialize;
 use serde_json::Value;
+use tiny_skia::{FillRule, LineCap, Paint, PathBuilder, Pixmap, Rect, Stroke, Transform};
+
+use ogcapi_types::{
+    common::{
+        media_type::{PNG, SVG},
+        Query as CollectionQuery,
+    },
+    features::Queryables,
+    styles::{
+        parse_color, Legend, LegendEntry, LegendSymbol, StyleReference, StyleValidation,
+        StyleWarning, Styles,
+    },
+    xml::escape,
+};
+
+use crate::{routes::Module, AppState, Error, Result};
+
+/// Height of a legend entry in pixels
+const ROW: f64 = 24.0;
 
-use ogcapi_types::styles::Styles;
+/// Size of the symbols of legend entries in pixels
+const SYMBOL: f64 = 16.0;
 
-use crate::{AppState, Error, Result};
+/// Parameters of a legend
+#[derive(Deserialize, Debug)]
+struct LegendQuery {
+    /// Output format, `json` (default), `svg` or `png`
+    f: Option<String>,
+}
 
 async fn styles(State(state): State<AppState>) -> Result<Json<Styles>> {
-    let styles = state.drivers.styles.list_styles().await?;
+    let styles = state.services.styles.list_styles().await?;
     Ok(Json(styles))
 }
 
 async fn read_style(Path(id): Path<String>, State(state): State<AppState>) -> Result<Json<Value>> {
-    let style = state.drivers.styles.read_style(&id).await?;
+    let style = state.services.styles.read_style(&id).await?;
 
     style.map(Json).ok_or(Error::NotFound)
 }
 
-pub(crate) fn router(_state: &AppState) -> Router<AppState> {
-    Router::new()
+/// Legend of a style, as JSON entries derived from its layers or rules, or as
+/// SVG or PNG graphic (the latter without labels)
+async fn legend(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    Query(query): Query<LegendQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let style = state
+        .services
+        .styles
+        .read_style(&id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let legend = Legend::from_stylesheet(&style);
+
+    let accept = headers
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .unwrap_or_default();
+    let format = match query.f.as_deref() {
+        Some(f) => f,
+        None if accept.contains(SVG) => "svg",
+        None if accept.contains(PNG) => "png",
+        None => "json",
+    };
+
+    match format {
+        "json" => Ok(Json(legend).into_response()),
+        "svg" => Ok(([(CONTENT_TYPE, SVG)], svg(&legend)).into_response()),
+        "png" => Ok(([(CONTENT_TYPE, PNG)], png(&legend)?).into_response()),
+        f => Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported format `{f}`"),
+        )),
+    }
+}
+
+/// Warnings about the source layers and properties referenced by a style
+/// that are not tile layers of a collection or not in its schema
+///
+/// Properties are only checked against collections with a schema or
+/// queryables that exclude additional properties.
+async fn validation(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<StyleValidation>> {
+    let style = state
+        .services
+        .styles
+        .read_style(&id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let collections = state
+        .services
+        .collections
+        .list_collections(&CollectionQuery::default())
+        .await?
+        .collections;
+
+    // queryables by collection id, read once per collection
+    let mut schemas: Vec<(String, Queryables)> = Vec::new();
+    let mut warnings = Vec::new();
+
+    for reference in StyleReference::from_stylesheet(&style) {
+        let Some(source_layer) = &reference.source_layer else {
+            continue;
+        };
+
+        let Some(collection) = collections.iter().find(|collection| {
+            collection.id == *source_layer
+                || collection
+                    .tile_layers
+                    .as_ref()
+                    .is_some_and(|t| t.layers.iter().any(|l| l.name == *source_layer))
+        }) else {
+            warnings.push(StyleWarning {
+                layer: reference.layer,
+                message: format!(
+                    "Source layer `{source_layer}` is neither a collection nor a tile layer of one"
+                ),
+            });
+            continue;
+        };
+
+        if !schemas.iter().any(|(id, _)| *id == collection.id) {
+            let mut queryables = state
+                .services
+                .collections
+                .read_queryables(&collection.id)
+                .await?
+                .unwrap_or_default();
+            if let Some(schema) = &collection.properties_schema {
+                queryables.merge_schema(schema);
+            }
+            schemas.push((collection.id.to_owned(), queryables));
+        }
+        let Some((_, queryables)) = schemas
+            .iter()
+            .find(|(id, q)| *id == collection.id && !q.additional_properties)
+        else {
+            continue;
+        };
+
+        for property in &reference.properties {
+            if !queryables.properties.contains_key(property) {
+                warnings.push(StyleWarning {
+                    layer: reference.layer.to_owned(),
+                    message: format!(
+                        "Property `{property}` is not in the schema of collection `{}`",
+                        collection.id
+                    ),
+                });
+            }
+        }
+    }
+
+    Ok(Json(StyleValidation::new(warnings)))
+}
+
+/// Legend as SVG, the symbols followed by their labels
+fn svg(legend: &Legend) -> String {
+    let chars = legend
+        .entries
+        .iter()
+        .map(|entry| entry.label.chars().count())
+        .max()
+        .unwrap_or_default();
+    let width = ROW + 7.0 * chars as f64 + 8.0;
+    let height = (ROW * legend.entries.len() as f64).max(1.0);
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="12">"#
+    );
+    if let Some(title) = &legend.title {
+        let _ = write!(svg, "<title>{}</title>", escape(title));
+    }
+
+    for (i, entry) in legend.entries.iter().enumerate() {
+        let y = ROW * i as f64;
+        let (x0, y0, x1, y1) = symbol_box(y);
+
+        let paint =
+            |attribute: &str, color: Option<&String>| match color.and_then(|c| parse_color(c)) {
+                Some([r, g, b, a]) => format!(
+                    r#" {attribute}="rgb({r},{g},{b})" {attribute}-opacity="{}""#,
+                    f64::from(a) / 255.0
+                ),
+                None => format!(r#" {attribute}="none""#),
+            };
+        let stroke = format!(
+            r#"{} stroke-width="{}""#,
+            paint("stroke", entry.stroke.as_ref()),
+            stroke_width(entry)
+        );
+
+        let _ = match entry.symbol {
+            LegendSymbol::Fill | LegendSymbol::Raster => write!(
+                svg,
+                r#"<rect x="{x0}" y="{y0}" width="{SYMBOL}" height="{SYMBOL}"{}{stroke}/>"#,
+                paint("fill", entry.fill.as_ref())
+            ),
+            LegendSymbol::Line => write!(
+                svg,
+                r#"<line x1="{x0}" y1="{cy}" x2="{x1}" y2="{cy}"{stroke} stroke-linecap="round"/>"#,
+                cy = (y0 + y1) / 2.0
+            ),
+            LegendSymbol::Point => write!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}"{}{stroke}/>"#,
+                (x0 + x1) / 2.0,
+                (y0 + y1) / 2.0,
+                SYMBOL / 3.0,
+                paint("fill", entry.fill.as_ref())
+            ),
+        };
+
+        let _ = write!(
+            svg,
+            r#"<text x="{}" y="{}" dominant-baseline="middle">{}</text>"#,
+            ROW + 4.0,
+            y + ROW / 2.0,
+            escape(&entry.label)
+        );
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Legend as PNG, the symbols only as no fonts are available to render the
+/// labels
+fn png(legend: &Legend) -> Result<Vec<u8>> {
+    let height = (ROW * legend.entries.len() as f64).max(1.0);
+    let mut pixmap =
+        Pixmap::new(ROW as u32, height as u32).ok_or_else(|| anyhow::anyhow!("Empty legend"))?;
+
+    let paint = |color: Option<&String>| {
+        let [r, g, b, a] = color.and_then(|c| parse_color(c))?;
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(r, g, b, a);
+        paint.anti_alias = true;
+        Some(paint)
+    };
+
+    for (i, entry) in legend.entries.iter().enumerate() {
+        let (x0, y0, x1, y1) = symbol_box(ROW * i as f64);
+
+        let path = match entry.symbol {
+            LegendSymbol::Fill | LegendSymbol::Raster => {
+                Rect::from_ltrb(x0 as f32, y0 as f32, x1 as f32, y1 as f32)
+                    .map(PathBuilder::from_rect)
+            }
+            LegendSymbol::Line => {
+                let mut builder = PathBuilder::new();
+                builder.move_to(x0 as f32, ((y0 + y1) / 2.0) as f32);
+                builder.line_to(x1 as f32, ((y0 + y1) / 2.0) as f32);
+                builder.finish()
+            }
+            LegendSymbol::Point => PathBuilder::from_circle(
+                ((x0 + x1) / 2.0) as f32,
+                ((y0 + y1) / 2.0) as f32,
+                (SYMBOL / 3.0) as f32,
+            ),
+        };
+        let Some(path) = path else {
+            continue;
+        };
+
+        let fill = match entry.symbol {
+            LegendSymbol::Line => None,
+            _ => paint(entry.fill.as_ref()),
+        };
+        if let Some(fill) = fill {
+            pixmap.fill_path(&path, &fill, FillRule::Winding, Transform::identity(), None);
+        }
+        if let Some(color) = paint(entry.stroke.as_ref()) {
+            let stroke = Stroke {
+                width: stroke_width(entry) as f32,
+                line_cap: LineCap::Round,
+                ..Default::default()
+            };
+            pixmap.stroke_path(&path, &color, &stroke, Transform::identity(), None);
+        }
+    }
+
+    Ok(pixmap.encode_png().map_err(anyhow::Error::from)?)
+}
+
+/// Bounds of the symbol of the entry at a vertical offset
+fn symbol_box(y: f64) -> (f64, f64, f64, f64) {
+    let margin = (ROW - SYMBOL) / 2.0;
+    (margin, y + margin, margin + SYMBOL, y + margin + SYMBOL)
+}
+
+/// Stroke width of an entry, limited to the size of the symbol
+fn stroke_width(entry: &LegendEntry) -> f64 {
+    entry.stroke_width.unwrap_or(1.0).clamp(0.0, SYMBOL / 2.0)
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
         .route("/styles", get(styles))
         .route("/styles/:id", get(read_style))
+        .route("/styles/:id/legend", get(legend))
+        .route("/styles/:id/validation", get(validation));
+
+    Module::new(router)
 }
@@ -0,0 +1,108 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use url::Url;
+
+use ogcapi_types::{
+    common::{media_type::JSON, LinkBuilder},
+    tasks::{Task, Tasks},
+};
+
+use crate::{access, extractors::RemoteUrl, routes::Module, AppState, Error, Result};
+
+/// List the background tasks
+async fn tasks(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    headers: HeaderMap,
+) -> Result<Json<Tasks>> {
+    access::require_admin(&state, &headers).await?;
+
+    let mut tasks = state.tasks.list();
+    for task in tasks.iter_mut() {
+        link(task, &url.join(&format!("tasks/{}", task.id))?);
+    }
+
+    Ok(Json(Tasks {
+        tasks,
+        links: vec![LinkBuilder::new(&url).mediatype(JSON).self_link()],
+    }))
+}
+
+async fn task(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Task>> {
+    access::require_admin(&state, &headers).await?;
+
+    let mut task = state.tasks.get(&id).ok_or(Error::NotFound)?;
+    link(&mut task, &url);
+
+    Ok(Json(task))
+}
+
+/// Start a run of a task now, independent of its schedule
+async fn run(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode> {
+    access::require_admin(&state, &headers).await?;
+
+    if !state.tasks.trigger(&id) {
+        return Err(Error::NotFound);
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Suspend the runs of a task on schedule
+async fn pause(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode> {
+    access::require_admin(&state, &headers).await?;
+
+    if !state.tasks.pause(&id, true) {
+        return Err(Error::NotFound);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Resume the runs of a task on schedule
+async fn resume(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode> {
+    access::require_admin(&state, &headers).await?;
+
+    if !state.tasks.pause(&id, false) {
+        return Err(Error::NotFound);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Link a task at `url` to itself
+fn link(task: &mut Task, url: &Url) {
+    task.links = vec![LinkBuilder::new(url).mediatype(JSON).self_link()];
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
+        .route("/admin/tasks", get(tasks))
+        .route("/admin/tasks/:id", get(task))
+        .route("/admin/tasks/:id/runs", post(run))
+        .route("/admin/tasks/:id/pause", post(pause))
+        .route("/admin/tasks/:id/resume", post(resume));
+
+    Module::new(router)
+}
@@ -2,7 +2,8 @@ use std::{collections::HashMap, sync::OnceLock};
 
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    response::IntoResponse,
     routing::get,
     Json, Router,
 };
@@ -11,14 +12,22 @@ use serde::Deserialize;
 use ogcapi_types::{
     common::{
         link_rel::{TILESETS_VECTOR, TILING_SCHEME},
-        media_type::JSON,
-        Link,
+        media_type::{JSON, PROMETHEUS},
+        Link, LinkBuilder,
     },
     tiles::{Query, TileMatrix, TileMatrixSet, TileMatrixSetItem, TileMatrixSets, TileSets},
 };
+#[cfg(feature = "features")]
+use ogcapi_types::{
+    common::{Bbox, Crs},
+    tiles::CornerOfOrigin,
+};
 
 use crate::{
+    access::{deny_restricted, request_user},
     extractors::{Qs, RemoteUrl},
+    metering,
+    routes::Module,
     AppState, Error, Result,
 };
 
@@ -61,16 +70,15 @@ pub struct TileParams {
 async fn tile_matrix_sets(RemoteUrl(url): RemoteUrl) -> Result<Json<TileMatrixSets>> {
     let tms = TMS.get().expect("TMS cell to be inizialized");
 
+    let links = LinkBuilder::new(&url);
+
     let mut tile_matrix_sets = Vec::new();
 
     for tms in tms.values() {
         let item = TileMatrixSetItem {
             id: Some(tms.id.to_owned()),
             title: tms.title_description_keywords.title.to_owned(),
-            links: vec![Link::new(
-                url.join(&format!("tileMatrixSets/{}", &tms.id))?,
-                TILING_SCHEME,
-            )],
+            links: vec![links.link(&format!("tileMatrixSets/{}", &tms.id), TILING_SCHEME)?],
             ..Default::default()
         };
 
@@ -103,56 +111,198 @@ async fn tile(
     Path(params): Path<TileParams>,
     Qs(query): Qs<Query>,
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<Vec<u8>> {
     let tms = TMS
         .get()
         .and_then(|tms| tms.get(&params.tms_id))
         .expect("Get tms from TMS");
 
+    let collections = params
+        .collection_id
+        .or_else(|| query.collections.to_owned())
+        .unwrap();
+
+    // tiles can't be restricted to the accessible features
+    let ids: Vec<&str> = collections.split(',').map(str::trim).collect();
+    deny_restricted(&state, &headers, &ids).await?;
+
+    // tiles outside the extents of all collections are empty
+    #[cfg(feature = "features")]
+    if let Some(tile_matrix) = TM
+        .get()
+        .and_then(|tm| tm.get(&params.tms_id))
+        .and_then(|tm| tm.get(&params.matrix))
+    {
+        let bbox = tile_bbox(tile_matrix, params.row, params.col);
+        if outside_extents(&state, &collections, &bbox, &tms.crs).await? {
+            state.tile_usage.record(&ids, &params.matrix, true);
+            return Ok(Vec::new());
+        }
+    }
+
+    state.tile_usage.record(&ids, &params.matrix, false);
+    if let Some(metered) = metering::current() {
+        metered.tiles(1);
+    }
+
+    // properties redacted for the user are left out
+    let user = request_user(&state, &headers).await;
+
+    if let Some(aggregation) = query.aggregation() {
+        if let Some(parameter) = &aggregation.parameter {
+            for id in &ids {
+                let Some(collection) = state.services.collections.read_collection(id).await? else {
+                    continue;
+                };
+                if collection
+                    .hidden_properties(user.as_deref())
+                    .contains(parameter)
+                {
+                    return Err(Error::Exception(
+                        StatusCode::BAD_REQUEST,
+                        format!("Unknown parameter `{parameter}`"),
+                    ));
+                }
+            }
+        }
+
+        return state
+            .services
+            .tiles
+            .aggregated_tile(
+                &collections,
+                tms,
+                &params.matrix,
+                params.row,
+                params.col,
+                &aggregation,
+            )
+            .await?
+            .ok_or_else(|| {
+                Error::Exception(
+                    StatusCode::NOT_IMPLEMENTED,
+                    "Aggregated tiles are not supported by the backend".to_string(),
+                )
+            });
+    }
+
     let tiles = state
-        .drivers
+        .services
         .tiles
         .tile(
-            &params.collection_id.or(query.collections).unwrap(),
+            &collections,
             tms,
             &params.matrix,
             params.row,
             params.col,
+            user.as_deref(),
+            query.labels,
         )
         .await?;
 
     Ok(tiles)
 }
 
-pub(crate) fn router(state: &AppState) -> Router<AppState> {
-    let mut root = state.root.write().unwrap();
-    root.links.push(
-        Link::new("tiles", TILESETS_VECTOR)
-            .title("List of available vector features tilesets for the dataset")
-            .mediatype(JSON),
-    );
-
-    state.conformance.write().unwrap().extend(&CONFORMANCE);
-
-    // Setup tile matrix sets
-    let mut tms_map = HashMap::new();
-    let web_mercartor_quad: TileMatrixSet =
-        serde_json::from_slice(WEB_MERCARTOR_QUAD).expect("parse tms");
-    tms_map.insert(web_mercartor_quad.id.to_owned(), web_mercartor_quad);
-
-    let mut tm = HashMap::new();
-    for tms in tms_map.values() {
-        tm.insert(tms.id.to_owned(), HashMap::new());
-        for tile_matrix in &tms.tile_matrices {
-            tm.get_mut(&tms.id).and_then(|tm_map| {
-                tm_map.insert(tile_matrix.id.to_owned(), tile_matrix.to_owned())
-            });
+/// Counts of the tile requests since the start of the service as Prometheus
+/// metrics
+async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    ([(CONTENT_TYPE, PROMETHEUS)], state.tile_usage.metrics())
+}
+
+/// Bounds of a tile, including the buffer of the rendered geometries
+#[cfg(feature = "features")]
+fn tile_bbox(tile_matrix: &TileMatrix, row: u32, col: u32) -> Bbox {
+    // geometries are clipped with a buffer of 64 in 4096 units
+    let buffer = 64.0 / 4096.0;
+
+    let width = tile_matrix.cell_size * f64::from(tile_matrix.tile_width.get());
+    let height = tile_matrix.cell_size * f64::from(tile_matrix.tile_height.get());
+    let [x, y] = tile_matrix.point_of_origin;
+    let (row, col) = (f64::from(row), f64::from(col));
+
+    let (minx, maxx) = (x + (col - buffer) * width, x + (col + 1.0 + buffer) * width);
+    let (miny, maxy) = match tile_matrix.corner_of_origin.as_ref() {
+        Some(CornerOfOrigin::BottomLeft) => (
+            y + (row - buffer) * height,
+            y + (row + 1.0 + buffer) * height,
+        ),
+        _ => (
+            y - (row + 1.0 + buffer) * height,
+            y - (row - buffer) * height,
+        ),
+    };
+
+    Bbox::Bbox2D([minx, miny, maxx, maxy])
+}
+
+/// Checks whether a bbox lies outside the extents of all collections
+#[cfg(feature = "features")]
+async fn outside_extents(
+    state: &AppState,
+    collections: &str,
+    bbox: &Bbox,
+    crs: &Crs,
+) -> Result<bool> {
+    for id in collections.split(',') {
+        let Some(collection) = state.services.collections.read_collection(id).await? else {
+            continue;
+        };
+
+        if !state
+            .extents
+            .disjoint(state.services.collections.as_ref(), &collection, bbox, crs)
+            .await
+        {
+            return Ok(false);
         }
     }
-    TMS.set(tms_map).expect("set `TMS` once cell content");
-    TM.set(tm).expect("set `TM` once cell content");
 
-    Router::new()
+    Ok(true)
+}
+
+/// Tile matrix set and tile matrix by their ids, e.g. for the map tiles
+#[cfg(any(feature = "maps", feature = "bundle"))]
+pub(crate) fn tile_matrix(
+    tms_id: &str,
+    matrix: &str,
+) -> Option<(&'static TileMatrixSet, &'static TileMatrix)> {
+    init();
+
+    let tms = TMS.get()?.get(tms_id)?;
+    let tile_matrix = TM.get()?.get(tms_id)?.get(matrix)?;
+
+    Some((tms, tile_matrix))
+}
+
+/// Setup the tile matrix sets once
+fn init() {
+    TMS.get_or_init(|| {
+        let mut tms_map = HashMap::new();
+        let web_mercartor_quad: TileMatrixSet =
+            serde_json::from_slice(WEB_MERCARTOR_QUAD).expect("parse tms");
+        tms_map.insert(web_mercartor_quad.id.to_owned(), web_mercartor_quad);
+        tms_map
+    });
+
+    TM.get_or_init(|| {
+        let mut tm = HashMap::new();
+        for tms in TMS.get().expect("TMS cell to be inizialized").values() {
+            tm.insert(tms.id.to_owned(), HashMap::new());
+            for tile_matrix in &tms.tile_matrices {
+                tm.get_mut(&tms.id).and_then(|tm_map| {
+                    tm_map.insert(tile_matrix.id.to_owned(), tile_matrix.to_owned())
+                });
+            }
+        }
+        tm
+    });
+}
+
+pub(crate) fn module() -> Module {
+    init();
+
+    let router = Router::new()
         .route("/tileMatrixSets", get(tile_matrix_sets))
         .route("/tileMatrixSets/:tms_id", get(tile_matrix_set))
         .route("/tiles", get(tiles))
@@ -163,4 +313,11 @@ pub(crate) fn router(state: &AppState) -> Router<AppState> {
             "/collections/:collection_id/tiles/:tms_id/:matrix/:row/:col",
             get(tile),
         )
+        .route("/metrics", get(metrics));
+
+    Module::new(router).conformance(&CONFORMANCE).link(
+        Link::new("tiles", TILESETS_VECTOR)
+            .title("List of available vector features tilesets for the dataset")
+            .mediatype(JSON),
+    )
 }
@@ -0,0 +1,134 @@
+//! Resumable uploads, following a subset of the tus protocol
+//!
+//! An upload is created with its total length, then the data is appended in
+//! chunks. After an interruption, the client asks for the current offset and
+//! continues from there. Completed uploads are consumed by referencing them
+//! with the `upload` query parameter, e.g. on imports.
+//!
+//! ```bash
+//! curl -i -X POST http://localhost:8484/uploads -H 'Upload-Length: 123456789'
+//! curl -X PATCH http://localhost:8484/uploads/{id} \
+//!         -H 'Upload-Offset: 0' \
+//!         -H 'Content-Type: application/offset+octet-stream' \
+//!         --data-binary @countries.zip
+//! curl -X POST 'http://localhost:8484/collections/countries/import?upload={id}'
+//! ```
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{
+        header::{CACHE_CONTROL, LOCATION},
+        HeaderMap, HeaderName, StatusCode,
+    },
+    routing::{head, patch, post},
+    Router,
+};
+
+use crate::{
+    extractors::RemoteUrl,
+    routes::Module,
+    upload::{self, UploadStatus},
+    AppState, Error, Result,
+};
+
+const UPLOAD_LENGTH: HeaderName = HeaderName::from_static("upload-length");
+const UPLOAD_OFFSET: HeaderName = HeaderName::from_static("upload-offset");
+
+/// Create an upload of the length given by the `Upload-Length` header
+async fn create(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    headers: HeaderMap,
+) -> Result<(StatusCode, HeaderMap)> {
+    let length = header_value(&headers, &UPLOAD_LENGTH)?;
+
+    if length > state.limits.upload as u64 {
+        return Err(Error::Exception(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Uploads are limited to {} bytes", state.limits.upload),
+        ));
+    }
+
+    let id = upload::create(length).await?;
+
+    let location = url.join(&format!("uploads/{id}"))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+    headers.insert(UPLOAD_OFFSET, 0.into());
+
+    Ok((StatusCode::CREATED, headers))
+}
+
+/// Report the progress of an upload
+async fn status(Path(id): Path<String>) -> Result<HeaderMap> {
+    let status = UploadStatus::read(&id).await?.ok_or(Error::NotFound)?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(UPLOAD_OFFSET, status.offset.into());
+    headers.insert(UPLOAD_LENGTH, status.length.into());
+    headers.insert(CACHE_CONTROL, "no-store".parse().unwrap());
+
+    Ok(headers)
+}
+
+/// Append a chunk at the offset given by the `Upload-Offset` header
+async fn append(
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<(StatusCode, HeaderMap)> {
+    let status = UploadStatus::read(&id).await?.ok_or(Error::NotFound)?;
+
+    let offset = header_value(&headers, &UPLOAD_OFFSET)?;
+    if offset != status.offset {
+        return Err(Error::Exception(
+            StatusCode::CONFLICT,
+            format!(
+                "Offset {offset} does not match the current offset {}",
+                status.offset
+            ),
+        ));
+    }
+
+    let offset = upload::append(&id, &status, body.into_data_stream()).await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(UPLOAD_OFFSET, offset.into());
+
+    Ok((StatusCode::NO_CONTENT, headers))
+}
+
+async fn delete(Path(id): Path<String>) -> Result<StatusCode> {
+    UploadStatus::read(&id).await?.ok_or(Error::NotFound)?;
+
+    upload::remove(&id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Parse a numeric header
+fn header_value(headers: &HeaderMap, name: &HeaderName) -> Result<u64> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| {
+            Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Missing or invalid `{name}` header"),
+            )
+        })
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
+        .route("/uploads", post(create))
+        .route("/uploads/:id", head(status).delete(delete));
+
+    // chunks are subject to the upload limit
+    let uploads = Router::new().route("/uploads/:id", patch(append));
+
+    Module::new(router).uploads(uploads)
+}
@@ -0,0 +1,50 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+
+use ogcapi_types::{
+    auth::UsageReport,
+    common::{media_type::JSON, LinkBuilder},
+};
+
+use crate::{access, extractors::RemoteUrl, routes::Module, AppState, Error, Result};
+
+#[derive(Deserialize, Debug)]
+struct UsageQuery {
+    /// Only report the usage of this month, as `YYYY-MM`
+    month: Option<String>,
+}
+
+/// Usage and quotas of the api keys of the requesting user
+async fn usage(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    headers: HeaderMap,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageReport>> {
+    let Some(user) = access::request_user(&state, &headers).await else {
+        return Err(Error::Exception(
+            StatusCode::UNAUTHORIZED,
+            "Usage reports require an api key".to_string(),
+        ));
+    };
+
+    let quotas = &state.drivers.quotas;
+    let usage = quotas
+        .key_usage(Some(&user), query.month.as_deref())
+        .await?;
+
+    Ok(Json(UsageReport {
+        usage,
+        quotas: quotas.list_quotas(Some(&user)).await?,
+        links: vec![LinkBuilder::new(&url).mediatype(JSON).self_link()],
+    }))
+}
+
+pub(crate) fn module() -> Module {
+    Module::new(Router::new().route("/admin/usage", get(usage)))
+}
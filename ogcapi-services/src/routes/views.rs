@@ -0,0 +1,226 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{
+        header::{ACCEPT, LOCATION},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+    Json, Router,
+};
+use url::Url;
+
+use ogcapi_types::{
+    common::{
+        link_rel::{DESCRIBEDBY, ITEMS},
+        media_type::{GEO_JSON, JSON},
+        Link, LinkBuilder,
+    },
+    views::View,
+};
+
+use crate::{
+    access::request_user,
+    extractors::RemoteUrl,
+    routes::{Format, Module},
+    AppState, Error, Result,
+};
+
+/// List the views of the requesting user
+async fn views(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    headers: HeaderMap,
+) -> Result<Json<Vec<View>>> {
+    let user = request_user(&state, &headers).await;
+
+    let mut views = state.drivers.views.list_views(user.as_deref()).await?;
+
+    for view in views.iter_mut() {
+        link(view, &url.join(&format!("views/{}", view.id))?)?;
+    }
+
+    Ok(Json(views))
+}
+
+/// Save a view, owned by the user of the api key of the request
+async fn create(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    headers: HeaderMap,
+    Json(mut view): Json<View>,
+) -> Result<(StatusCode, HeaderMap)> {
+    let owner = require_user(&state, &headers).await?;
+    validate(&state, &view).await?;
+
+    view.owner = Some(owner);
+    let id = state.drivers.views.create_view(&view).await?;
+
+    let location = url.join(&format!("views/{id}"))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    Ok((StatusCode::CREATED, headers))
+}
+
+/// Open a view, redirecting to the features it selects unless the view
+/// itself is requested as JSON
+async fn read(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    Path(id): Path<String>,
+    Query(format): Query<Format>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let mut view = visible_view(&state, &id, &headers).await?;
+
+    let json = format.f.as_deref() == Some("json")
+        || headers
+            .get(ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .is_some_and(|accept| accept.contains(JSON));
+    if !json {
+        let items = items(&view, &url)?;
+        return Ok(Redirect::to(items.as_str()).into_response());
+    }
+
+    link(&mut view, &url)?;
+
+    Ok(Json(view).into_response())
+}
+
+/// Replace the definition of a view of the requesting user
+async fn update(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(mut view): Json<View>,
+) -> Result<StatusCode> {
+    owned_view(&state, &id, &headers).await?;
+
+    view.id = id;
+    validate(&state, &view).await?;
+
+    state.drivers.views.update_view(&view).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete a view of the requesting user
+async fn remove(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<StatusCode> {
+    owned_view(&state, &id, &headers).await?;
+
+    state.drivers.views.delete_view(&id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// View the requesting user may open, not found if unshared views of other
+/// users
+async fn visible_view(state: &AppState, id: &str, headers: &HeaderMap) -> Result<View> {
+    let user = request_user(state, headers).await;
+
+    state
+        .drivers
+        .views
+        .read_view(id)
+        .await?
+        .filter(|view| view.visible_to(user.as_deref()))
+        .ok_or(Error::NotFound)
+}
+
+/// View owned by the requesting user, shared views of other users may be
+/// opened but not changed
+async fn owned_view(state: &AppState, id: &str, headers: &HeaderMap) -> Result<View> {
+    let user = require_user(state, headers).await?;
+
+    let view = visible_view(state, id, headers).await?;
+    if view.owner.as_ref() != Some(&user) {
+        return Err(Error::Exception(
+            StatusCode::FORBIDDEN,
+            "Only the owner may change a view".to_string(),
+        ));
+    }
+
+    Ok(view)
+}
+
+/// User of the api key of the request, views are only saved and changed with
+/// one
+async fn require_user(state: &AppState, headers: &HeaderMap) -> Result<String> {
+    request_user(state, headers).await.ok_or_else(|| {
+        Error::Exception(
+            StatusCode::UNAUTHORIZED,
+            "Saving or changing views requires an api key".to_string(),
+        )
+    })
+}
+
+async fn validate(state: &AppState, view: &View) -> Result<()> {
+    let problems = view.validate();
+    if !problems.is_empty() {
+        return Err(Error::Invalid(problems));
+    }
+
+    if state
+        .services
+        .collections
+        .read_collection(&view.collection)
+        .await?
+        .is_none()
+    {
+        return Err(Error::Exception(
+            StatusCode::BAD_REQUEST,
+            format!("No collection with id `{}`", view.collection),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Items of the collection selected by a view at `url`
+fn items(view: &View, url: &Url) -> Result<Url> {
+    let mut items = url.join(&format!("../collections/{}/items", view.collection))?;
+    items.set_query(None);
+
+    let parameters = view.parameters();
+    if !parameters.is_empty() {
+        items.query_pairs_mut().extend_pairs(parameters);
+    }
+
+    Ok(items)
+}
+
+/// Link a view at `url` to itself, its features and its style
+fn link(view: &mut View, url: &Url) -> Result<()> {
+    let mut url = url.to_owned();
+    url.set_query(None);
+
+    let mut links = vec![
+        LinkBuilder::new(&url).mediatype(JSON).self_link(),
+        Link::new(items(view, &url)?, ITEMS).mediatype(GEO_JSON),
+    ];
+    if let Some(style) = &view.style {
+        links.push(
+            Link::new(url.join(&format!("../styles/{style}"))?, DESCRIBEDBY)
+                .mediatype(JSON)
+                .title("Style of the view"),
+        );
+    }
+    view.links = links;
+
+    Ok(())
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
+        .route("/views", get(views).post(create))
+        .route("/views/:id", get(read).put(update).delete(remove));
+
+    Module::new(router)
+}
@@ -0,0 +1,124 @@
+use axum::{
+    extract::{Path, State},
+    http::{header::LOCATION, HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+
+use ogcapi_types::webhooks::{Delivery, Webhook};
+
+use crate::{access::request_user, extractors::RemoteUrl, routes::Module, AppState, Error, Result};
+
+/// List the webhooks registered by the requesting user
+async fn webhooks(State(state): State<AppState>, headers: HeaderMap) -> Result<Json<Vec<Webhook>>> {
+    let user = require_user(&state, &headers).await?;
+
+    let webhooks = state
+        .drivers
+        .webhooks
+        .list_webhooks()
+        .await?
+        .into_iter()
+        .filter(|webhook| webhook.owner.as_ref() == Some(&user))
+        .collect();
+
+    Ok(Json(webhooks))
+}
+
+/// Register a webhook
+async fn create(
+    State(state): State<AppState>,
+    RemoteUrl(url): RemoteUrl,
+    headers: HeaderMap,
+    Json(mut webhook): Json<Webhook>,
+) -> Result<(StatusCode, HeaderMap)> {
+    let user = require_user(&state, &headers).await?;
+
+    match url::Url::parse(&webhook.url) {
+        Ok(target) if ["http", "https"].contains(&target.scheme()) => {}
+        _ => {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid webhook url `{}`", webhook.url),
+            ))
+        }
+    }
+
+    webhook.owner = Some(user);
+    let id = state.drivers.webhooks.create_webhook(&webhook).await?;
+
+    let location = url.join(&format!("webhooks/{id}"))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, location.as_str().parse().unwrap());
+
+    Ok((StatusCode::CREATED, headers))
+}
+
+async fn read(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Webhook>> {
+    let webhook = owned_webhook(&state, &id, &headers).await?;
+
+    Ok(Json(webhook))
+}
+
+async fn remove(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<StatusCode> {
+    owned_webhook(&state, &id, &headers).await?;
+
+    state.drivers.webhooks.delete_webhook(&id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Log of the latest delivery attempts
+async fn deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Delivery>>> {
+    owned_webhook(&state, &id, &headers).await?;
+
+    let deliveries = state.drivers.webhooks.list_deliveries(&id).await?;
+
+    Ok(Json(deliveries))
+}
+
+/// Webhook registered by the requesting user, the ones of other users are
+/// not found
+async fn owned_webhook(state: &AppState, id: &str, headers: &HeaderMap) -> Result<Webhook> {
+    let user = require_user(state, headers).await?;
+
+    state
+        .drivers
+        .webhooks
+        .read_webhook(id)
+        .await?
+        .filter(|webhook| webhook.owner.as_ref() == Some(&user))
+        .ok_or(Error::NotFound)
+}
+
+/// User of the api key of the request, webhooks are only managed with one
+async fn require_user(state: &AppState, headers: &HeaderMap) -> Result<String> {
+    request_user(state, headers).await.ok_or_else(|| {
+        Error::Exception(
+            StatusCode::UNAUTHORIZED,
+            "Managing webhooks requires an api key".to_string(),
+        )
+    })
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new()
+        .route("/webhooks", get(webhooks).post(create))
+        .route("/webhooks/:id", get(read).delete(remove))
+        .route("/webhooks/:id/deliveries", get(deliveries));
+
+    Module::new(router)
+}
@@ -0,0 +1,347 @@
+//! Read-only Zarr store of the coverages
+//!
+//! Every band of the raster of a collection is a two dimensional array
+//! (`y`, `x`) of the group at `/collections/{collectionId}/zarr`, next to the
+//! coordinate arrays `x` and `y` of the pixel centers. The store can be opened
+//! as Zarr v2 (with consolidated metadata) and v3, chunks are read from the
+//! raster on request and served uncompressed.
+
+use axum::{
+    extract::{Path, State},
+    http::{header::CONTENT_TYPE, HeaderMap},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde_json::{json, Value};
+
+use ogcapi_drivers::raster::{BandInfo, DataType, Grid, RasterRequest};
+use ogcapi_types::common::media_type::OCTET_STREAM;
+
+use crate::{routes::coverages::read_grid, routes::Module, AppState, Error, Result};
+
+/// Side length of the chunks in pixels
+const CHUNK: usize = 512;
+
+/// Array of the store
+enum Array<'a> {
+    Band(usize, &'a BandInfo),
+    X,
+    Y,
+}
+
+impl<'a> Array<'a> {
+    fn find(grid: &'a Grid, name: &str) -> Option<Self> {
+        match name {
+            "x" => Some(Array::X),
+            "y" => Some(Array::Y),
+            _ => grid
+                .bands
+                .iter()
+                .enumerate()
+                .find(|(_, band)| band.name == name)
+                .map(|(i, band)| Array::Band(i + 1, band)),
+        }
+    }
+
+    fn shape(&self, grid: &Grid) -> Vec<usize> {
+        match self {
+            Array::Band(..) => vec![grid.height, grid.width],
+            Array::X => vec![grid.width],
+            Array::Y => vec![grid.height],
+        }
+    }
+
+    fn chunks(&self) -> Vec<usize> {
+        match self {
+            Array::Band(..) => vec![CHUNK, CHUNK],
+            Array::X | Array::Y => vec![CHUNK],
+        }
+    }
+
+    fn dimensions(&self) -> Vec<&'static str> {
+        match self {
+            Array::Band(..) => vec!["y", "x"],
+            Array::X => vec!["x"],
+            Array::Y => vec!["y"],
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            Array::Band(_, band) => band.data_type,
+            Array::X | Array::Y => DataType::Float64,
+        }
+    }
+
+    fn fill_value(&self) -> Option<f64> {
+        match self {
+            Array::Band(_, band) => band.nodata,
+            Array::X | Array::Y => None,
+        }
+    }
+
+    fn attributes(&self) -> Value {
+        match self {
+            Array::Band(_, band) => match &band.unit {
+                Some(unit) => json!({ "units": unit }),
+                None => json!({}),
+            },
+            Array::X => json!({ "standard_name": "projection_x_coordinate" }),
+            Array::Y => json!({ "standard_name": "projection_y_coordinate" }),
+        }
+    }
+
+    /// Array metadata of Zarr v2
+    fn zarray(&self, grid: &Grid) -> Value {
+        json!({
+            "zarr_format": 2,
+            "shape": self.shape(grid),
+            "chunks": self.chunks(),
+            "dtype": dtype_v2(self.data_type()),
+            "compressor": null,
+            "fill_value": self.fill_value().map(|v| fill_value(v, self.data_type())),
+            "order": "C",
+            "filters": null,
+            "dimension_separator": ".",
+        })
+    }
+
+    /// Array attributes of Zarr v2, with the dimension names of xarray
+    fn zattrs(&self) -> Value {
+        let mut attributes = self.attributes();
+        attributes["_ARRAY_DIMENSIONS"] = json!(self.dimensions());
+        attributes
+    }
+
+    /// Array metadata of Zarr v3
+    fn zarr_json(&self, grid: &Grid) -> Value {
+        json!({
+            "zarr_format": 3,
+            "node_type": "array",
+            "shape": self.shape(grid),
+            "data_type": dtype_v3(self.data_type()),
+            "chunk_grid": {
+                "name": "regular",
+                "configuration": { "chunk_shape": self.chunks() }
+            },
+            "chunk_key_encoding": {
+                "name": "default",
+                "configuration": { "separator": "/" }
+            },
+            "fill_value": fill_value(self.fill_value().unwrap_or(0.0), self.data_type()),
+            "codecs": [{ "name": "bytes", "configuration": { "endian": "little" } }],
+            "dimension_names": self.dimensions(),
+            "attributes": self.attributes(),
+        })
+    }
+}
+
+/// Key of the store, as metadata document or chunk of an array
+async fn key(
+    State(state): State<AppState>,
+    Path((collection_id, key)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let grid = read_grid(&state, &collection_id, &headers).await?;
+
+    let (array, key) = match key.split_once('/') {
+        Some((array, key)) => (Some(array), key),
+        None => (None, key.as_str()),
+    };
+
+    let Some(name) = array else {
+        return match key {
+            ".zgroup" => Ok(Json(json!({ "zarr_format": 2 })).into_response()),
+            ".zattrs" => Ok(Json(group_attributes(&grid)).into_response()),
+            ".zmetadata" => Ok(Json(consolidated(&grid)).into_response()),
+            "zarr.json" => Ok(Json(json!({
+                "zarr_format": 3,
+                "node_type": "group",
+                "attributes": group_attributes(&grid),
+            }))
+            .into_response()),
+            _ => Err(Error::NotFound),
+        };
+    };
+
+    let array = Array::find(&grid, name).ok_or(Error::NotFound)?;
+
+    match key {
+        ".zarray" => Ok(Json(array.zarray(&grid)).into_response()),
+        ".zattrs" => Ok(Json(array.zattrs()).into_response()),
+        "zarr.json" => Ok(Json(array.zarr_json(&grid)).into_response()),
+        _ => {
+            // `j.i` of v2 or `c/j/i` of v3
+            let index: Option<Vec<usize>> = match key.strip_prefix("c/") {
+                Some(key) => key.split('/').map(|i| i.parse().ok()).collect(),
+                None => key.split('.').map(|i| i.parse().ok()).collect(),
+            };
+            let index = index.ok_or(Error::NotFound)?;
+
+            let bytes = chunk(&state, &collection_id, &grid, &array, &index).await?;
+
+            Ok(([(CONTENT_TYPE, OCTET_STREAM)], bytes).into_response())
+        }
+    }
+}
+
+/// Values of a chunk, padded with the fill value at the edges of the array
+async fn chunk(
+    state: &AppState,
+    collection_id: &str,
+    grid: &Grid,
+    array: &Array<'_>,
+    index: &[usize],
+) -> Result<Vec<u8>> {
+    let (rx, ry) = grid.resolution();
+    let offset = |i: usize, size: usize| -> Result<(usize, usize)> {
+        let start = i
+            .checked_mul(CHUNK)
+            .filter(|start| *start < size)
+            .ok_or(Error::NotFound)?;
+        Ok((start, CHUNK.min(size - start)))
+    };
+
+    let values = match (array, index) {
+        (Array::X, [i]) => {
+            let (start, len) = offset(*i, grid.width)?;
+            let mut values: Vec<f64> = (start..start + len)
+                .map(|x| grid.bbox[0] + (x as f64 + 0.5) * rx)
+                .collect();
+            values.resize(CHUNK, f64::NAN);
+            values
+        }
+        (Array::Y, [j]) => {
+            let (start, len) = offset(*j, grid.height)?;
+            let mut values: Vec<f64> = (start..start + len)
+                .map(|y| grid.bbox[3] - (y as f64 + 0.5) * ry)
+                .collect();
+            values.resize(CHUNK, f64::NAN);
+            values
+        }
+        (Array::Band(band, info), [j, i]) => {
+            let (x, width) = offset(*i, grid.width)?;
+            let (y, height) = offset(*j, grid.height)?;
+
+            // pixel window in the native crs, read without resampling
+            let request = RasterRequest {
+                bbox: Some([
+                    grid.bbox[0] + x as f64 * rx,
+                    grid.bbox[3] - (y + height) as f64 * ry,
+                    grid.bbox[0] + (x + width) as f64 * rx,
+                    grid.bbox[3] - y as f64 * ry,
+                ]),
+                bbox_crs: Some(grid.crs.to_owned()),
+                width: Some(width),
+                height: Some(height),
+                bands: Some(vec![*band]),
+                ..Default::default()
+            };
+            let raster = state.drivers.rasters.read(collection_id, &request).await?;
+
+            let fill = info.nodata.unwrap_or(0.0);
+            let mut values = vec![fill; CHUNK * CHUNK];
+            for (row, chunk) in raster.bands[0].chunks(width).enumerate() {
+                values[row * CHUNK..row * CHUNK + width].copy_from_slice(chunk);
+            }
+            values
+        }
+        _ => return Err(Error::NotFound),
+    };
+
+    Ok(encode(&values, array.data_type()))
+}
+
+/// Little endian bytes of values of a data type
+fn encode(values: &[f64], data_type: DataType) -> Vec<u8> {
+    match data_type {
+        DataType::Byte => values.iter().map(|v| *v as u8).collect(),
+        DataType::UInt16 => values
+            .iter()
+            .flat_map(|v| (*v as u16).to_le_bytes())
+            .collect(),
+        DataType::Int16 => values
+            .iter()
+            .flat_map(|v| (*v as i16).to_le_bytes())
+            .collect(),
+        DataType::UInt32 => values
+            .iter()
+            .flat_map(|v| (*v as u32).to_le_bytes())
+            .collect(),
+        DataType::Int32 => values
+            .iter()
+            .flat_map(|v| (*v as i32).to_le_bytes())
+            .collect(),
+        DataType::Float32 => values
+            .iter()
+            .flat_map(|v| (*v as f32).to_le_bytes())
+            .collect(),
+        DataType::Float64 => values.iter().flat_map(|v| v.to_le_bytes()).collect(),
+    }
+}
+
+fn group_attributes(grid: &Grid) -> Value {
+    json!({
+        "crs": grid.crs.to_string(),
+        "bbox": grid.bbox,
+    })
+}
+
+/// Consolidated metadata of Zarr v2, read by xarray in a single request
+fn consolidated(grid: &Grid) -> Value {
+    let mut metadata = serde_json::Map::new();
+    metadata.insert(".zgroup".to_string(), json!({ "zarr_format": 2 }));
+    metadata.insert(".zattrs".to_string(), group_attributes(grid));
+
+    let names = ["x", "y"]
+        .into_iter()
+        .chain(grid.bands.iter().map(|band| band.name.as_str()));
+    for name in names {
+        if let Some(array) = Array::find(grid, name) {
+            metadata.insert(format!("{name}/.zarray"), array.zarray(grid));
+            metadata.insert(format!("{name}/.zattrs"), array.zattrs());
+        }
+    }
+
+    json!({ "zarr_consolidated_format": 1, "metadata": metadata })
+}
+
+fn dtype_v2(data_type: DataType) -> &'static str {
+    match data_type {
+        DataType::Byte => "|u1",
+        DataType::UInt16 => "<u2",
+        DataType::Int16 => "<i2",
+        DataType::UInt32 => "<u4",
+        DataType::Int32 => "<i4",
+        DataType::Float32 => "<f4",
+        DataType::Float64 => "<f8",
+    }
+}
+
+fn dtype_v3(data_type: DataType) -> &'static str {
+    match data_type {
+        DataType::Byte => "uint8",
+        DataType::UInt16 => "uint16",
+        DataType::Int16 => "int16",
+        DataType::UInt32 => "uint32",
+        DataType::Int32 => "int32",
+        DataType::Float32 => "float32",
+        DataType::Float64 => "float64",
+    }
+}
+
+/// Fill value as JSON, integers for integer types and `NaN` as string
+fn fill_value(value: f64, data_type: DataType) -> Value {
+    match data_type {
+        _ if value.is_nan() => json!("NaN"),
+        DataType::Float32 | DataType::Float64 => json!(value),
+        _ => json!(value as i64),
+    }
+}
+
+pub(crate) fn module() -> Module {
+    let router = Router::new().route("/collections/:collection_id/zarr/*key", get(key));
+
+    Module::new(router)
+}
@@ -4,18 +4,21 @@ use axum::{
     body::Body,
     http::{
         header::{AUTHORIZATION, CONTENT_TYPE, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE},
-        Response, StatusCode,
+        HeaderName, Response, StatusCode,
     },
     response::IntoResponse,
-    routing::get,
-    Router,
+    Router, ServiceExt,
+};
+#[cfg(feature = "files")]
+use axum::{
+    extract::Request,
+    middleware::{self, Next},
 };
 use tokio::net::TcpListener;
-use tower::ServiceBuilder;
+use tower::{Layer, ServiceBuilder};
 use tower_http::{
     catch_panic::CatchPanicLayer,
     compression::CompressionLayer,
-    cors::CorsLayer,
     request_id::MakeRequestUuid,
     sensitive_headers::SetSensitiveRequestHeadersLayer,
     trace::{DefaultMakeSpan, TraceLayer},
@@ -24,13 +27,18 @@ use tower_http::{
 
 use ogcapi_types::common::Exception;
 
-use crate::{routes, AppState, Config, ConfigParser, Error};
+use crate::{
+    extractors::API_KEY,
+    logging::{self, Logger},
+    normalize, AppState, Config, ConfigParser, Error, OgcApiBuilder,
+};
 
 /// OGC API Services
 pub struct Service {
     pub state: AppState,
     pub router: Router<AppState>,
     listener: TcpListener,
+    logger: Logger,
 }
 
 impl Service {
@@ -46,39 +54,34 @@ impl Service {
 
     pub async fn new_with(config: &Config, state: AppState) -> Self {
         // router
-        let router = Router::new()
-            .route("/", get(routes::root))
-            .route("/api", get(routes::api::api))
-            .route("/redoc", get(routes::api::redoc))
-            .route("/swagger", get(routes::api::swagger))
-            .route("/conformance", get(routes::conformance));
-
-        let router = router.merge(routes::collections::router(&state));
-
-        #[cfg(feature = "stac")]
-        let router = router.route(
-            "/search",
-            get(routes::stac::search_get).post(routes::stac::search_post),
-        );
-
-        #[cfg(feature = "features")]
-        let router = router.merge(routes::features::router(&state));
-
-        #[cfg(feature = "edr")]
-        let router = router.merge(routes::edr::router(&state));
-
-        #[cfg(feature = "styles")]
-        let router = router.merge(routes::styles::router(&state));
-
-        #[cfg(feature = "tiles")]
-        let router = router.merge(routes::tiles::router(&state));
-
-        #[cfg(feature = "processes")]
-        let router = router.merge(routes::processes::router(&state));
+        let builder = OgcApiBuilder::from_state(state);
+        let builder = match &config.conformance_report {
+            Some(path) => {
+                let report = std::fs::read(path).expect("read conformance report");
+                builder.conformance_report(
+                    serde_json::from_slice(&report).expect("parse conformance report"),
+                )
+            }
+            None => builder,
+        };
+
+        #[cfg(feature = "files")]
+        let (router, state) = if config.data_dir.is_some() {
+            // static datasets are served read-only, without database backed modules
+            let (router, state) = builder.features().into_parts();
+            (router.layer(middleware::from_fn(read_only)), state)
+        } else {
+            builder.all().into_parts()
+        };
+        #[cfg(not(feature = "files"))]
+        let (router, state) = builder.all().into_parts();
 
         // add a fallback service for handling routes to unknown paths
         let router = router.fallback(handler_404);
 
+        // note the matched route for the request log
+        let router = router.layer(axum::middleware::from_fn(logging::routed));
+
         // middleware stack
         let router = router.layer(
             ServiceBuilder::new()
@@ -88,10 +91,11 @@ impl Service {
                     PROXY_AUTHORIZATION,
                     COOKIE,
                     SET_COOKIE,
+                    HeaderName::from_static(API_KEY),
                 ]))
                 .layer(TraceLayer::new_for_http().make_span_with(DefaultMakeSpan::new()))
                 .layer(CompressionLayer::new())
-                .layer(CorsLayer::permissive())
+                .layer(config.cors.layer().expect("valid CORS configuration"))
                 .layer(CatchPanicLayer::custom(handle_panic))
                 .propagate_x_request_id(),
         );
@@ -104,6 +108,7 @@ impl Service {
             state,
             router,
             listener,
+            logger: Logger::new(&config.request_log),
         }
     }
 
@@ -112,13 +117,19 @@ impl Service {
         // add state
         let router = self.router.with_state(self.state);
 
+        // normalize paths and parameters, before the request is routed
+        let app = axum::middleware::map_request(normalize::normalize).layer(router);
+
+        // log requests including the time spent routing
+        let app = axum::middleware::from_fn_with_state(self.logger, logging::log).layer(app);
+
         // serve
         tracing::info!(
             "listening on http://{}",
             self.listener.local_addr().unwrap()
         );
 
-        axum::serve::serve(self.listener, router)
+        axum::serve::serve(self.listener, app.into_make_service())
             .with_graceful_shutdown(shutdown_signal())
             .await
             .unwrap()
@@ -136,6 +147,20 @@ async fn handler_404() -> impl IntoResponse {
     Error::NotFound
 }
 
+/// Reject write requests to static datasets
+#[cfg(feature = "files")]
+async fn read_only(request: Request, next: Next) -> Response<Body> {
+    if request.method().is_safe() {
+        return next.run(request).await;
+    }
+
+    Error::Exception(
+        StatusCode::METHOD_NOT_ALLOWED,
+        "Static datasets are read-only".to_string(),
+    )
+    .into_response()
+}
+
 /// Custom panic handler
 fn handle_panic(err: Box<dyn Any + Send + 'static>) -> Response<Body> {
     let details = if let Some(s) = err.downcast_ref::<String>() {
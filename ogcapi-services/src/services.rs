@@ -0,0 +1,364 @@
+//! Service traits between the route handlers and the drivers
+//!
+//! Every method has a default implementation forwarding to the driver, so a
+//! custom service only overrides what it wants to change, e.g. to validate or
+//! enrich resources before they are stored:
+//!
+//! ```rust,ignore
+//! use ogcapi_drivers::CollectionTransactions;
+//! use ogcapi_services::services::CollectionService;
+//! use ogcapi_types::common::Collection;
+//!
+//! struct Licensed<T>(T);
+//!
+//! #[axum::async_trait]
+//! impl<T: CollectionTransactions> CollectionService for Licensed<T> {
+//!     fn driver(&self) -> &dyn CollectionTransactions {
+//!         &self.0
+//!     }
+//!
+//!     async fn create_collection(&self, collection: &Collection) -> anyhow::Result<String> {
+//!         anyhow::ensure!(collection.license.is_some(), "Collection requires a license");
+//!         self.driver().create_collection(collection).await
+//!     }
+//! }
+//!
+//! let state = state.collection_service(Licensed(db));
+//! ```
+
+#[cfg(feature = "features")]
+use futures::stream::BoxStream;
+
+use ogcapi_drivers::CollectionTransactions;
+#[cfg(feature = "edr")]
+use ogcapi_drivers::EdrQuerier;
+#[cfg(feature = "styles")]
+use ogcapi_drivers::StyleTransactions;
+#[cfg(feature = "tiles")]
+use ogcapi_drivers::TileTransactions;
+#[cfg(feature = "features")]
+use ogcapi_drivers::{FeatureTransactions, QueryPlan};
+#[cfg(feature = "edr")]
+use ogcapi_types::edr::{Query as EdrQuery, QueryType};
+#[cfg(any(feature = "features", feature = "edr"))]
+use ogcapi_types::features::FeatureCollection;
+#[cfg(feature = "styles")]
+use ogcapi_types::styles::Styles;
+#[cfg(feature = "tiles")]
+use ogcapi_types::tiles::{Aggregation, TileMatrixSet};
+#[cfg(feature = "features")]
+use ogcapi_types::{
+    common::Crs,
+    cql2::Expr,
+    features::{
+        AggregateQuery, CollectionStats, Feature, PropertyValues, Query as FeatureQuery,
+        StatsQuery, ValidationRule, ValuesQuery, Violation,
+    },
+};
+use ogcapi_types::{
+    common::{Bbox, Catalog, Collection, Collections, Query as CollectionQuery},
+    features::Queryables,
+};
+
+/// Service for `Collection` resources
+#[axum::async_trait]
+pub trait CollectionService: Send + Sync {
+    /// Driver backing the default implementations
+    fn driver(&self) -> &dyn CollectionTransactions;
+
+    async fn create_collection(&self, collection: &Collection) -> anyhow::Result<String> {
+        self.driver().create_collection(collection).await
+    }
+
+    async fn read_collection(&self, id: &str) -> anyhow::Result<Option<Collection>> {
+        self.driver().read_collection(id).await
+    }
+
+    async fn update_collection(&self, collection: &Collection) -> anyhow::Result<()> {
+        self.driver().update_collection(collection).await
+    }
+
+    async fn delete_collection(&self, id: &str) -> anyhow::Result<()> {
+        self.driver().delete_collection(id).await
+    }
+
+    async fn list_collections(&self, query: &CollectionQuery) -> anyhow::Result<Collections> {
+        self.driver().list_collections(query).await
+    }
+
+    async fn read_queryables(&self, id: &str) -> anyhow::Result<Option<Queryables>> {
+        self.driver().read_queryables(id).await
+    }
+
+    async fn extent(&self, id: &str) -> anyhow::Result<Option<Bbox>> {
+        self.driver().extent(id).await
+    }
+
+    async fn upsert_catalog(&self, catalog: &Catalog) -> anyhow::Result<()> {
+        self.driver().upsert_catalog(catalog).await
+    }
+
+    async fn read_catalog(&self, id: &str) -> anyhow::Result<Option<Catalog>> {
+        self.driver().read_catalog(id).await
+    }
+
+    async fn delete_catalog(&self, id: &str) -> anyhow::Result<()> {
+        self.driver().delete_catalog(id).await
+    }
+
+    async fn list_catalogs(&self) -> anyhow::Result<Vec<Catalog>> {
+        self.driver().list_catalogs().await
+    }
+}
+
+/// Service for `Feature` resources
+#[cfg(feature = "features")]
+#[axum::async_trait]
+pub trait FeatureService: Send + Sync {
+    /// Driver backing the default implementations
+    fn driver(&self) -> &dyn FeatureTransactions;
+
+    async fn create_feature(&self, feature: &Feature) -> anyhow::Result<String> {
+        self.driver().create_feature(feature).await
+    }
+
+    async fn create_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+    ) -> anyhow::Result<Vec<String>> {
+        self.driver()
+            .create_features(collection, features, crs)
+            .await
+    }
+
+    async fn create_feature_batches(
+        &self,
+        collection: &str,
+        batches: BoxStream<'_, anyhow::Result<Vec<Feature>>>,
+        crs: &Crs,
+    ) -> anyhow::Result<usize> {
+        self.driver()
+            .create_feature_batches(collection, batches, crs)
+            .await
+    }
+
+    async fn read_feature(
+        &self,
+        collection: &str,
+        id: &str,
+        crs: &Crs,
+    ) -> anyhow::Result<Option<Feature>> {
+        self.driver().read_feature(collection, id, crs).await
+    }
+
+    async fn update_feature(&self, feature: &Feature) -> anyhow::Result<()> {
+        self.driver().update_feature(feature).await
+    }
+
+    async fn update_feature_if_version(
+        &self,
+        feature: &Feature,
+        version: u64,
+    ) -> anyhow::Result<bool> {
+        self.driver()
+            .update_feature_if_version(feature, version)
+            .await
+    }
+
+    async fn delete_feature(&self, collection: &str, id: &str) -> anyhow::Result<()> {
+        self.driver().delete_feature(collection, id).await
+    }
+
+    async fn list_items(
+        &self,
+        collection: &str,
+        query: &FeatureQuery,
+    ) -> anyhow::Result<FeatureCollection> {
+        self.driver().list_items(collection, query).await
+    }
+
+    fn stream_items(
+        &self,
+        collection: &str,
+        query: &FeatureQuery,
+    ) -> BoxStream<'static, anyhow::Result<Feature>> {
+        self.driver().stream_items(collection, query)
+    }
+
+    async fn plan_items(
+        &self,
+        collection: &str,
+        query: &FeatureQuery,
+    ) -> anyhow::Result<Option<QueryPlan>> {
+        self.driver().plan_items(collection, query).await
+    }
+
+    async fn stats(
+        &self,
+        collection: &str,
+        query: &StatsQuery,
+    ) -> anyhow::Result<Option<CollectionStats>> {
+        self.driver().stats(collection, query).await
+    }
+
+    async fn property_values(
+        &self,
+        collection: &str,
+        property: &str,
+        query: &ValuesQuery,
+    ) -> anyhow::Result<Option<PropertyValues>> {
+        self.driver()
+            .property_values(collection, property, query)
+            .await
+    }
+
+    async fn aggregate(
+        &self,
+        collection: &str,
+        query: &AggregateQuery,
+    ) -> anyhow::Result<Option<FeatureCollection>> {
+        self.driver().aggregate(collection, query).await
+    }
+
+    async fn match_filter(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+        filter: &Expr,
+    ) -> anyhow::Result<Vec<bool>> {
+        self.driver()
+            .match_filter(collection, features, crs, filter)
+            .await
+    }
+
+    async fn validate_features(
+        &self,
+        collection: &str,
+        features: &[Feature],
+        crs: &Crs,
+        rules: &[ValidationRule],
+    ) -> anyhow::Result<Vec<Violation>> {
+        self.driver()
+            .validate_features(collection, features, crs, rules)
+            .await
+    }
+
+    async fn validate_collection(
+        &self,
+        collection: &str,
+        rules: &[ValidationRule],
+    ) -> anyhow::Result<Vec<Violation>> {
+        self.driver().validate_collection(collection, rules).await
+    }
+}
+
+/// Service for `EDR` queries
+#[cfg(feature = "edr")]
+#[axum::async_trait]
+pub trait EdrService: Send + Sync {
+    /// Driver backing the default implementations
+    fn driver(&self) -> &dyn EdrQuerier;
+
+    async fn query(
+        &self,
+        collection_id: &str,
+        query_type: &QueryType,
+        query: &EdrQuery,
+    ) -> anyhow::Result<FeatureCollection> {
+        self.driver().query(collection_id, query_type, query).await
+    }
+}
+
+/// Service for `Style` resources
+#[cfg(feature = "styles")]
+#[axum::async_trait]
+pub trait StyleService: Send + Sync {
+    /// Driver backing the default implementations
+    fn driver(&self) -> &dyn StyleTransactions;
+
+    async fn list_styles(&self) -> anyhow::Result<Styles> {
+        self.driver().list_styles().await
+    }
+
+    async fn read_style(&self, id: &str) -> anyhow::Result<Option<serde_json::Value>> {
+        self.driver().read_style(id).await
+    }
+}
+
+/// Service for `Tile` resources
+#[cfg(feature = "tiles")]
+#[axum::async_trait]
+pub trait TileService: Send + Sync {
+    /// Driver backing the default implementations
+    fn driver(&self) -> &dyn TileTransactions;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn tile(
+        &self,
+        collections: &str,
+        tms: &TileMatrixSet,
+        matrix: &str,
+        row: u32,
+        col: u32,
+        user: Option<&str>,
+        labels: bool,
+    ) -> anyhow::Result<Vec<u8>> {
+        self.driver()
+            .tile(collections, tms, matrix, row, col, user, labels)
+            .await
+    }
+
+    async fn aggregated_tile(
+        &self,
+        collections: &str,
+        tms: &TileMatrixSet,
+        matrix: &str,
+        row: u32,
+        col: u32,
+        aggregation: &Aggregation,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        self.driver()
+            .aggregated_tile(collections, tms, matrix, row, col, aggregation)
+            .await
+    }
+}
+
+/// Default service, forwarding to the driver without additional logic
+pub struct DriverService<T>(pub T);
+
+impl<T: CollectionTransactions> CollectionService for DriverService<T> {
+    fn driver(&self) -> &dyn CollectionTransactions {
+        &self.0
+    }
+}
+
+#[cfg(feature = "features")]
+impl<T: FeatureTransactions> FeatureService for DriverService<T> {
+    fn driver(&self) -> &dyn FeatureTransactions {
+        &self.0
+    }
+}
+
+#[cfg(feature = "edr")]
+impl<T: EdrQuerier> EdrService for DriverService<T> {
+    fn driver(&self) -> &dyn EdrQuerier {
+        &self.0
+    }
+}
+
+#[cfg(feature = "styles")]
+impl<T: StyleTransactions> StyleService for DriverService<T> {
+    fn driver(&self) -> &dyn StyleTransactions {
+        &self.0
+    }
+}
+
+#[cfg(feature = "tiles")]
+impl<T: TileTransactions> TileService for DriverService<T> {
+    fn driver(&self) -> &dyn TileTransactions {
+        &self.0
+    }
+}
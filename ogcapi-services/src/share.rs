@@ -0,0 +1,80 @@
+//! Share links
+//!
+//! Capability urls granting temporary read access to the features of a
+//! collection, optionally restricted by a `CQL2` filter. The grant is passed
+//! in the `share` query parameter as base64url encoded JSON, signed with
+//! HMAC-SHA256 and the share secret of the service. For the shared collection
+//! it replaces the access filter of the request.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use ogcapi_types::cql2::Expr;
+
+/// Query parameter carrying share tokens
+pub const SHARE_PARAMETER: &str = "share";
+
+/// Read access to the features of a collection, until it expires
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Share {
+    pub collection: String,
+    /// Filter restricting the shared features, all features if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Expr>,
+    /// Expiry as unix timestamp in seconds
+    pub expires: i64,
+}
+
+impl Share {
+    pub fn new(collection: impl ToString, filter: Option<Expr>, valid_for: Duration) -> Self {
+        Share {
+            collection: collection.to_string(),
+            filter,
+            expires: Utc::now().timestamp() + valid_for.as_secs() as i64,
+        }
+    }
+
+    /// Encode and sign the share as token
+    pub fn sign(&self, secret: &str) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).expect("serialize share"));
+        let signature = URL_SAFE_NO_PAD.encode(mac(secret, &payload).finalize().into_bytes());
+
+        format!("{payload}.{signature}")
+    }
+
+    /// Decode a token, checking its signature and expiry
+    pub fn verify(token: &str, secret: &str) -> anyhow::Result<Share> {
+        let (payload, signature) = token.split_once('.').context("Malformed share token")?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .context("Malformed share token")?;
+
+        mac(secret, payload)
+            .verify_slice(&signature)
+            .map_err(|_| anyhow!("Invalid share token"))?;
+
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload)
+            .context("Malformed share token")?;
+        let share: Share = serde_json::from_slice(&payload).context("Malformed share token")?;
+
+        if share.expires <= Utc::now().timestamp() {
+            bail!("Share link expired");
+        }
+
+        Ok(share)
+    }
+}
+
+fn mac(secret: &str, payload: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+    mac
+}
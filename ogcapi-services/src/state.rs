@@ -1,50 +1,318 @@
-use std::sync::{Arc, RwLock};
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
+#[cfg(feature = "files")]
+use ogcapi_drivers::files::Files;
+#[cfg(feature = "mock")]
+use ogcapi_drivers::mock::Mock;
+#[cfg(feature = "attachments")]
+use ogcapi_drivers::AttachmentTransactions;
 #[cfg(feature = "edr")]
 use ogcapi_drivers::EdrQuerier;
-#[cfg(feature = "features")]
-use ogcapi_drivers::FeatureTransactions;
-#[cfg(feature = "processes")]
+#[cfg(feature = "harvest")]
+use ogcapi_drivers::HarvestTransactions;
+#[cfg(any(feature = "processes", feature = "joins"))]
 use ogcapi_drivers::JobHandler;
+#[cfg(feature = "processes")]
+use ogcapi_drivers::JobQueue;
+#[cfg(feature = "joins")]
+use ogcapi_drivers::JoinTransactions;
+#[cfg(any(feature = "pubsub", feature = "tiles", feature = "webhooks"))]
+use ogcapi_drivers::Leases;
 #[cfg(feature = "styles")]
 use ogcapi_drivers::StyleTransactions;
 #[cfg(feature = "tiles")]
 use ogcapi_drivers::TileTransactions;
+#[cfg(feature = "views")]
+use ogcapi_drivers::ViewTransactions;
+#[cfg(feature = "webhooks")]
+use ogcapi_drivers::WebhookTransactions;
+#[cfg(feature = "coverages")]
+use ogcapi_drivers::{raster::Gdal, RasterSource};
+#[cfg(feature = "features")]
+use ogcapi_drivers::{FeatureChanges, FeatureTransactions};
+#[cfg(any(feature = "processes", feature = "joins"))]
+use ogcapi_types::processes::StatusInfo;
+#[cfg(any(feature = "processes", feature = "joins"))]
+use tokio::sync::broadcast;
 
-use ogcapi_drivers::{postgres::Db, CollectionTransactions};
+use ogcapi_drivers::{
+    postgres::Db, AccessFilterTransactions, CollectionTransactions, IdempotencyKeys,
+    ModeTransactions, QuotaTransactions,
+};
 use ogcapi_types::common::{Conformance, LandingPage};
+use serde::{Deserialize, Serialize};
 
+#[cfg(any(feature = "processes", feature = "joins"))]
+use crate::events::BroadcastJobs;
+#[cfg(feature = "features")]
+use crate::extents::Extents;
+#[cfg(any(feature = "pubsub", feature = "tiles", feature = "webhooks"))]
+use crate::leader::Leader;
+#[cfg(feature = "pubsub")]
+use crate::pubsub::Publisher;
+#[cfg(feature = "edr")]
+use crate::services::EdrService;
+#[cfg(feature = "features")]
+use crate::services::FeatureService;
+#[cfg(feature = "styles")]
+use crate::services::StyleService;
+#[cfg(feature = "tiles")]
+use crate::services::TileService;
+#[cfg(feature = "tiles")]
+use crate::usage::Usage;
+use crate::{
+    doctor,
+    metering::{BillingHook, Meter},
+    openapi::OPENAPI,
+    services::{CollectionService, DriverService},
+    tasks::Scheduler,
+    Config, ConfigParser, OpenAPI,
+};
 #[cfg(feature = "processes")]
-use crate::Processor;
-use crate::{openapi::OPENAPI, Config, ConfigParser, OpenAPI};
+use crate::{JobWorkers, Processor};
+
+/// Directory of uploads and job outputs, see [`AppState::work_dir`]
+#[cfg(any(feature = "processes", feature = "uploads"))]
+static WORK_DIR: std::sync::OnceLock<std::path::PathBuf> = std::sync::OnceLock::new();
+
+/// Directory of uploads and job outputs, in the temporary directory unless
+/// configured
+#[cfg(any(feature = "processes", feature = "uploads"))]
+pub(crate) fn work_dir() -> std::path::PathBuf {
+    WORK_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join("ogcapi"))
+}
 
 /// Application state
 #[derive(Clone)]
 pub struct AppState {
-    pub root: Arc<RwLock<LandingPage>>,
-    pub conformance: Arc<RwLock<Conformance>>,
+    /// Landing page, completed with the links of the mounted modules
+    pub root: Arc<LandingPage>,
+    /// Conformance classes of the mounted modules
+    pub conformance: Arc<Conformance>,
     pub openapi: OpenAPI,
     pub drivers: Arc<Drivers>,
+    #[cfg(any(feature = "processes", feature = "joins"))]
+    pub(crate) job_events: broadcast::Sender<StatusInfo>,
+    pub services: Services,
+    /// Cached extents of collections
+    #[cfg(feature = "features")]
+    pub(crate) extents: Extents,
+    /// Counts of the tile requests
+    #[cfg(feature = "tiles")]
+    pub(crate) tile_usage: Usage,
+    /// Background tasks of the mounted modules
+    pub(crate) tasks: Scheduler,
+    /// Usage of the api keys, not metered without
+    pub(crate) meter: Option<Meter>,
+    /// Primary role among the replicas
+    #[cfg(any(feature = "pubsub", feature = "tiles", feature = "webhooks"))]
+    pub(crate) leader: Leader,
+    /// Request body size limits
+    pub limits: Limits,
+    /// Limits of feature queries
+    pub guardrails: Guardrails,
+    /// Handling of query parameters unknown to an endpoint
+    pub unknown_parameters: UnknownParameters,
+    /// Timeouts and concurrency limits per class of routes
+    pub route_limits: RouteLimits,
+    /// Availability of the service, switched at runtime at `/admin/mode` and
+    /// refreshed from the driver
+    pub(crate) mode: Arc<RwLock<Mode>>,
+    /// Users whose api keys grant access to the admin API
+    pub admins: Arc<[String]>,
+    /// Secret signing share links, share links are rejected without
+    #[cfg(feature = "features")]
+    pub share_secret: Option<Arc<str>>,
     pub db: Db,
-    #[cfg(feature = "stac")]
+    #[cfg(any(feature = "attachments", feature = "stac", feature = "snapshot"))]
     pub s3: ogcapi_drivers::s3::S3,
+    #[cfg(feature = "pubsub")]
+    pub publisher: Option<Publisher>,
     #[cfg(feature = "processes")]
-    pub processors: Arc<RwLock<std::collections::HashMap<String, Box<dyn Processor>>>>,
+    pub processors: Arc<std::sync::RwLock<std::collections::HashMap<String, Box<dyn Processor>>>>,
+    /// Workers of the shared job queue, jobs run on the submitting replica
+    /// without
+    #[cfg(feature = "processes")]
+    pub job_queue: Option<JobWorkers>,
 }
 
-// TODO: Introduce service trait
 pub struct Drivers {
     pub collections: Box<dyn CollectionTransactions>,
     #[cfg(feature = "features")]
     pub features: Box<dyn FeatureTransactions>,
+    #[cfg(feature = "features")]
+    pub changes: Box<dyn FeatureChanges>,
+    #[cfg(feature = "attachments")]
+    pub attachments: Box<dyn AttachmentTransactions>,
     #[cfg(feature = "edr")]
     pub edr: Box<dyn EdrQuerier>,
-    #[cfg(feature = "processes")]
+    #[cfg(any(feature = "processes", feature = "joins"))]
     pub jobs: Box<dyn JobHandler>,
+    #[cfg(feature = "processes")]
+    pub queue: Box<dyn JobQueue>,
+    #[cfg(feature = "joins")]
+    pub joins: Box<dyn JoinTransactions>,
+    #[cfg(feature = "coverages")]
+    pub rasters: Box<dyn RasterSource>,
     #[cfg(feature = "styles")]
     pub styles: Box<dyn StyleTransactions>,
     #[cfg(feature = "tiles")]
     pub tiles: Box<dyn TileTransactions>,
+    #[cfg(feature = "views")]
+    pub views: Box<dyn ViewTransactions>,
+    #[cfg(feature = "webhooks")]
+    pub webhooks: Box<dyn WebhookTransactions>,
+    #[cfg(feature = "harvest")]
+    pub harvests: Box<dyn HarvestTransactions>,
+    pub idempotency: Box<dyn IdempotencyKeys>,
+    #[cfg(any(feature = "pubsub", feature = "tiles", feature = "webhooks"))]
+    pub leases: Box<dyn Leases>,
+    pub access: Box<dyn AccessFilterTransactions>,
+    pub quotas: Box<dyn QuotaTransactions>,
+    pub mode: Box<dyn ModeTransactions>,
+}
+
+/// Services used by the route handlers
+#[derive(Clone)]
+pub struct Services {
+    pub collections: Arc<dyn CollectionService>,
+    #[cfg(feature = "features")]
+    pub features: Arc<dyn FeatureService>,
+    #[cfg(feature = "edr")]
+    pub edr: Arc<dyn EdrService>,
+    #[cfg(feature = "styles")]
+    pub styles: Arc<dyn StyleService>,
+    #[cfg(feature = "tiles")]
+    pub tiles: Arc<dyn TileService>,
+}
+
+/// Maximum request body sizes in bytes
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    /// Limit of regular requests
+    pub body: usize,
+    /// Limit of routes accepting uploads, e.g. imports or process inputs
+    pub upload: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            body: 2 * 1024 * 1024,
+            upload: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// Limits of feature queries, keeping single requests from saturating the
+/// database
+#[derive(Clone, Copy, Debug)]
+pub struct Guardrails {
+    /// Maximum number of features per page, larger limits are reduced
+    pub max_limit: usize,
+    /// Maximum area of bbox queries in square degrees
+    pub max_bbox_area: Option<f64>,
+    /// Maximum estimated cost of queries scanning a table without index, for
+    /// requests without api key
+    pub max_scan_cost: Option<f64>,
+}
+
+impl Default for Guardrails {
+    fn default() -> Self {
+        Guardrails {
+            max_limit: 10000,
+            max_bbox_area: None,
+            max_scan_cost: None,
+        }
+    }
+}
+
+/// Timeout and concurrency limit of a class of routes, requests beyond them
+/// are answered with `503 Service Unavailable`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RouteLimit {
+    /// Time the handler may take until the response starts
+    pub timeout: Option<Duration>,
+    /// Maximum number of requests handled at once
+    pub concurrency: Option<usize>,
+}
+
+/// Limits per class of routes, keeping slow requests from starving the
+/// interactive ones of database connections
+#[derive(Clone, Copy, Debug)]
+pub struct RouteLimits {
+    /// Routes not covered by the other classes
+    pub interactive: RouteLimit,
+    /// Tiles
+    pub tiles: RouteLimit,
+    /// Feature exports as `GeoJSON` sequence, imports and other uploads
+    pub bulk: RouteLimit,
+    /// Process executions
+    pub processes: RouteLimit,
+}
+
+impl Default for RouteLimits {
+    fn default() -> Self {
+        RouteLimits {
+            interactive: RouteLimit::default(),
+            tiles: RouteLimit {
+                timeout: Some(Duration::from_secs(10)),
+                concurrency: Some(32),
+            },
+            bulk: RouteLimit {
+                timeout: None,
+                concurrency: Some(4),
+            },
+            processes: RouteLimit {
+                timeout: None,
+                concurrency: Some(8),
+            },
+        }
+    }
+}
+
+/// Availability of the service, e.g. during migrations
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    /// Serve all requests
+    #[default]
+    Normal,
+    /// Refuse writes with `503 Service Unavailable`, serve reads
+    ReadOnly,
+    /// Refuse all requests with `503 Service Unavailable`, with a maintenance
+    /// page for browsers
+    Maintenance,
+}
+
+impl Mode {
+    /// Name of the mode, as in the configuration
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Normal => "normal",
+            Mode::ReadOnly => "read-only",
+            Mode::Maintenance => "maintenance",
+        }
+    }
+}
+
+/// Handling of query parameters unknown to an endpoint
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum UnknownParameters {
+    /// Reject them where the standards require it, i.e. when listing
+    /// collections and on EDR queries, and ignore them elsewhere
+    #[default]
+    Standard,
+    /// Reject them on all endpoints with a fixed set of parameters
+    Reject,
+    /// Ignore them on all endpoints
+    Ignore,
 }
 
 impl AppState {
@@ -54,60 +322,380 @@ impl AppState {
     }
 
     pub async fn new_from(config: &Config) -> Self {
-        let openapi = if let Some(path) = &config.openapi {
+        // report problems of the environment before they fail requests
+        doctor::log(&doctor::doctor(config).await);
+
+        let mut openapi = if let Some(path) = &config.openapi {
             OpenAPI::from_path(path).unwrap()
         } else {
             OpenAPI::from_slice(OPENAPI)
         };
+        let root = config.landing.landing_page(&mut openapi.0.info);
+
+        let db = match &config.database_url {
+            Some(url) => Db::setup_with(url, config.statement_timeout.map(Duration::from_secs))
+                .await
+                .unwrap(),
+            // only the modules backed by the data directory are mounted
+            None => Db::lazy(),
+        };
+
+        let state = AppState::new_with(db, openapi)
+            .await
+            .root(root)
+            .limits(Limits {
+                body: config.body_limit,
+                upload: config.upload_limit,
+            })
+            .guardrails(Guardrails {
+                max_limit: config.max_limit,
+                max_bbox_area: config.max_bbox_area,
+                max_scan_cost: config.max_scan_cost,
+            })
+            .unknown_parameters(config.unknown_parameters)
+            .route_limits(config.load.route_limits())
+            .admins(&config.admin_users);
+
+        let state = match config.metering {
+            true => state.metering(),
+            false => state,
+        };
+
+        #[cfg(feature = "features")]
+        let state = match &config.share_secret {
+            Some(secret) => state.share_secret(secret),
+            None => state,
+        };
 
-        let db = Db::setup(&config.database_url).await.unwrap();
+        #[cfg(feature = "processes")]
+        let state = match config.job_queue {
+            true => state.job_queue(JobWorkers {
+                count: config.job_workers,
+                lease: Duration::from_secs(config.job_lease),
+            }),
+            false => state,
+        };
+
+        #[cfg(any(feature = "processes", feature = "uploads"))]
+        let state = match &config.work_dir {
+            Some(dir) => state.work_dir(dir),
+            None => state,
+        };
 
-        AppState::new_with(db, openapi).await
+        #[cfg(feature = "files")]
+        let state = match &config.data_dir {
+            Some(dir) => state.files(
+                Files::open(dir)
+                    .await
+                    .expect("load data directory")
+                    .watch()
+                    .expect("watch data directory"),
+            ),
+            None => state,
+        };
+
+        #[cfg(feature = "coverages")]
+        let state = match &config.raster_dir {
+            Some(dir) => state.rasters(Gdal::open(dir).await.expect("load raster directory")),
+            None => state,
+        };
+
+        if let Some(mode) = config.mode {
+            if let Err(e) = state.set_mode(mode).await {
+                tracing::warn!("Failed to store mode `{mode:?}`, switching this replica only: {e}");
+                *state.mode.write().unwrap() = mode;
+            }
+        }
+
+        #[cfg(feature = "pubsub")]
+        if let Some(url) = &config.mqtt_url {
+            let publisher = Publisher::connect(url)
+                .expect("connect to MQTT broker")
+                .collection_topic(&config.mqtt_collection_topic)
+                .job_topic(&config.mqtt_job_topic);
+
+            return state.publisher(publisher);
+        }
+
+        state
     }
 
     pub async fn new_with(db: Db, openapi: OpenAPI) -> Self {
-        // conformance
-        #[allow(unused_mut)]
-        let mut conformace = Conformance::default();
-        #[cfg(feature = "stac")]
-        conformace.extend(&[
-            "https://api.stacspec.org/v1.0.0-rc.1/core",
-            "https://api.stacspec.org/v1.0.0-rc.1/item-search",
-            "https://api.stacspec.org/v1.0.0-rc.1/collections",
-            "https://api.stacspec.org/v1.0.0-rc.1/ogcapi-features",
-            "https://api.stacspec.org/v1.0.0-rc.1/browseable",
-        ]);
-
         // drivers
+        #[cfg(any(feature = "processes", feature = "joins"))]
+        let (job_events, _) = broadcast::channel(crate::events::CAPACITY);
+
         let drivers = Drivers {
             collections: Box::new(db.clone()),
             #[cfg(feature = "features")]
             features: Box::new(db.clone()),
+            #[cfg(feature = "features")]
+            changes: Box::new(db.clone()),
+            #[cfg(feature = "attachments")]
+            attachments: Box::new(db.clone()),
             #[cfg(feature = "edr")]
             edr: Box::new(db.clone()),
+            #[cfg(any(feature = "processes", feature = "joins"))]
+            jobs: Box::new(BroadcastJobs {
+                jobs: Box::new(db.clone()),
+                sender: job_events.clone(),
+            }),
             #[cfg(feature = "processes")]
-            jobs: Box::new(db.clone()),
+            queue: Box::new(db.clone()),
+            #[cfg(feature = "joins")]
+            joins: Box::new(db.clone()),
+            #[cfg(feature = "coverages")]
+            rasters: Box::new(Gdal::new()),
             #[cfg(feature = "styles")]
             styles: Box::new(db.clone()),
             #[cfg(feature = "tiles")]
             tiles: Box::new(db.clone()),
+            #[cfg(feature = "views")]
+            views: Box::new(db.clone()),
+            #[cfg(feature = "webhooks")]
+            webhooks: Box::new(db.clone()),
+            #[cfg(feature = "harvest")]
+            harvests: Box::new(db.clone()),
+            idempotency: Box::new(db.clone()),
+            #[cfg(any(feature = "pubsub", feature = "tiles", feature = "webhooks"))]
+            leases: Box::new(db.clone()),
+            access: Box::new(db.clone()),
+            quotas: Box::new(db.clone()),
+            mode: Box::new(db.clone()),
+        };
+
+        // services
+        let services = Services {
+            collections: Arc::new(DriverService(db.clone())),
+            #[cfg(feature = "features")]
+            features: Arc::new(DriverService(db.clone())),
+            #[cfg(feature = "edr")]
+            edr: Arc::new(DriverService(db.clone())),
+            #[cfg(feature = "styles")]
+            styles: Arc::new(DriverService(db.clone())),
+            #[cfg(feature = "tiles")]
+            tiles: Arc::new(DriverService(db.clone())),
         };
 
         AppState {
-            root: Arc::new(RwLock::new(LandingPage::new("root").description("root"))),
-            conformance: Arc::new(RwLock::new(conformace)),
+            root: Arc::new(LandingPage::new("root").description("root")),
+            conformance: Default::default(),
             openapi,
             drivers: Arc::new(drivers),
+            #[cfg(any(feature = "processes", feature = "joins"))]
+            job_events,
+            services,
+            #[cfg(feature = "features")]
+            extents: Default::default(),
+            #[cfg(feature = "tiles")]
+            tile_usage: Default::default(),
+            tasks: Default::default(),
+            meter: None,
+            #[cfg(any(feature = "pubsub", feature = "tiles", feature = "webhooks"))]
+            leader: Default::default(),
+            limits: Limits::default(),
+            guardrails: Guardrails::default(),
+            unknown_parameters: UnknownParameters::default(),
+            route_limits: RouteLimits::default(),
+            mode: Default::default(),
+            admins: Arc::new([]),
+            #[cfg(feature = "features")]
+            share_secret: None,
             db,
-            #[cfg(feature = "stac")]
+            #[cfg(any(feature = "attachments", feature = "stac", feature = "snapshot"))]
             s3: ogcapi_drivers::s3::S3::new().await,
+            #[cfg(feature = "pubsub")]
+            publisher: None,
             #[cfg(feature = "processes")]
             processors: Default::default(),
+            #[cfg(feature = "processes")]
+            job_queue: None,
         }
     }
 
     pub fn root(mut self, root: LandingPage) -> Self {
-        self.root = Arc::new(RwLock::new(root));
+        self.root = Arc::new(root);
+        self
+    }
+
+    pub fn collection_service(mut self, service: impl CollectionService + 'static) -> Self {
+        self.services.collections = Arc::new(service);
+        self
+    }
+
+    #[cfg(feature = "features")]
+    pub fn feature_service(mut self, service: impl FeatureService + 'static) -> Self {
+        self.services.features = Arc::new(service);
+        self
+    }
+
+    #[cfg(feature = "edr")]
+    pub fn edr_service(mut self, service: impl EdrService + 'static) -> Self {
+        self.services.edr = Arc::new(service);
+        self
+    }
+
+    #[cfg(feature = "styles")]
+    pub fn style_service(mut self, service: impl StyleService + 'static) -> Self {
+        self.services.styles = Arc::new(service);
+        self
+    }
+
+    #[cfg(feature = "tiles")]
+    pub fn tile_service(mut self, service: impl TileService + 'static) -> Self {
+        self.services.tiles = Arc::new(service);
+        self
+    }
+
+    /// Serve the collections and features of a data directory
+    ///
+    /// Has to be called before the drivers are shared, e.g. by a publisher.
+    #[cfg(feature = "files")]
+    pub fn files(mut self, files: Files) -> Self {
+        let drivers = Arc::get_mut(&mut self.drivers).expect("drivers are not shared yet");
+        drivers.collections = Box::new(files.clone());
+        drivers.features = Box::new(files.clone());
+        drivers.changes = Box::new(files.clone());
+        drivers.access = Box::new(files.clone());
+        drivers.mode = Box::new(files.clone());
+        #[cfg(feature = "attachments")]
+        {
+            drivers.attachments = Box::new(files.clone());
+        }
+
+        self.services.collections = Arc::new(DriverService(files.clone()));
+        self.services.features = Arc::new(DriverService(files));
+        self
+    }
+
+    /// Serve the collections and features of an in-memory mock driver, e.g.
+    /// to run the abstract tests of the conformance classes without database
+    ///
+    /// Has to be called before the drivers are shared, e.g. by a publisher.
+    #[cfg(feature = "mock")]
+    pub fn mock(mut self, mock: Mock) -> Self {
+        let drivers = Arc::get_mut(&mut self.drivers).expect("drivers are not shared yet");
+        drivers.collections = Box::new(mock.clone());
+        drivers.features = Box::new(mock.clone());
+        drivers.changes = Box::new(mock.clone());
+        drivers.access = Box::new(mock.clone());
+        drivers.mode = Box::new(mock.clone());
+        #[cfg(feature = "attachments")]
+        {
+            drivers.attachments = Box::new(mock.clone());
+        }
+
+        self.services.collections = Arc::new(DriverService(mock.clone()));
+        self.services.features = Arc::new(DriverService(mock));
+        self
+    }
+
+    /// Serve coverages and maps of the rasters of a source
+    ///
+    /// Has to be called before the drivers are shared, e.g. by a publisher.
+    #[cfg(feature = "coverages")]
+    pub fn rasters(mut self, rasters: impl RasterSource + 'static) -> Self {
+        let drivers = Arc::get_mut(&mut self.drivers).expect("drivers are not shared yet");
+        drivers.rasters = Box::new(rasters);
+        self
+    }
+
+    /// Subscribe to the status updates of jobs
+    #[cfg(any(feature = "processes", feature = "joins"))]
+    pub fn subscribe_jobs(&self) -> broadcast::Receiver<StatusInfo> {
+        self.job_events.subscribe()
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Meter the usage of the api keys and enforce their quotas
+    pub fn metering(mut self) -> Self {
+        self.meter.get_or_insert_with(Meter::default);
+        self
+    }
+
+    /// Report the metered usage of the api keys to a billing system, enables
+    /// metering
+    pub fn billing(mut self, hook: impl BillingHook + 'static) -> Self {
+        self.meter.get_or_insert_with(Meter::default).hook = Some(Arc::new(hook));
+        self
+    }
+
+    pub fn guardrails(mut self, guardrails: Guardrails) -> Self {
+        self.guardrails = guardrails;
+        self
+    }
+
+    pub fn unknown_parameters(mut self, policy: UnknownParameters) -> Self {
+        self.unknown_parameters = policy;
+        self
+    }
+
+    pub fn route_limits(mut self, limits: RouteLimits) -> Self {
+        self.route_limits = limits;
+        self
+    }
+
+    /// Grant the api keys of users access to the admin API
+    pub fn admins(mut self, users: &[String]) -> Self {
+        self.admins = users.into();
+        self
+    }
+
+    /// Current availability of the service, as last read from the driver
+    pub fn current_mode(&self) -> Mode {
+        *self.mode.read().unwrap()
+    }
+
+    /// Switch the availability of all replicas of the service, the admin API
+    /// at `/admin/mode` stays available in every mode
+    ///
+    /// The mode is stored with the driver, the other replicas pick it up
+    /// within five seconds.
+    pub async fn set_mode(&self, mode: Mode) -> anyhow::Result<()> {
+        self.drivers.mode.write_mode(mode.name()).await?;
+        *self.mode.write().unwrap() = mode;
+        Ok(())
+    }
+
+    /// Read the mode stored with the driver, keeping the current one if none
+    /// was stored
+    pub(crate) async fn refresh_mode(&self) -> anyhow::Result<()> {
+        if let Some(name) = self.drivers.mode.read_mode().await? {
+            let mode = <Mode as clap::ValueEnum>::from_str(&name, true)
+                .map_err(|e| anyhow::anyhow!("Unknown stored mode `{name}`: {e}"))?;
+            *self.mode.write().unwrap() = mode;
+        }
+        Ok(())
+    }
+
+    /// Run jobs submitted with [`crate::queue_job`] through the job queue
+    /// shared with the other replicas
+    #[cfg(feature = "processes")]
+    pub fn job_queue(mut self, workers: JobWorkers) -> Self {
+        self.job_queue = Some(workers);
+        self
+    }
+
+    /// Keep uploads and job outputs in a directory, which replicas behind a
+    /// load balancer share to serve them alike
+    ///
+    /// The directory is set once per process.
+    #[cfg(any(feature = "processes", feature = "uploads"))]
+    pub fn work_dir(self, dir: &std::path::Path) -> Self {
+        if WORK_DIR.set(dir.to_owned()).is_err() {
+            tracing::warn!("Work directory set already");
+        }
+        self
+    }
+
+    /// Accept share links signed with the secret
+    #[cfg(feature = "features")]
+    pub fn share_secret(mut self, secret: &str) -> Self {
+        self.share_secret = Some(Arc::from(secret));
         self
     }
 
@@ -116,12 +704,29 @@ impl AppState {
         self
     }
 
-    #[cfg(feature = "stac")]
+    #[cfg(any(feature = "attachments", feature = "stac", feature = "snapshot"))]
     pub async fn s3_client(mut self, client: ogcapi_drivers::s3::S3) -> Self {
         self.s3 = client;
         self
     }
 
+    /// Publish feature changes and job status updates
+    #[cfg(feature = "pubsub")]
+    pub fn publisher(mut self, publisher: Publisher) -> Self {
+        #[cfg(any(feature = "processes", feature = "joins"))]
+        tokio::spawn(publisher.clone().forward_jobs(self.subscribe_jobs()));
+
+        Leader::spawn(&self);
+        tokio::spawn(
+            publisher
+                .clone()
+                .forward_changes(self.drivers.clone(), self.leader.clone()),
+        );
+
+        self.publisher = Some(publisher);
+        self
+    }
+
     #[cfg(feature = "processes")]
     pub fn processors(self, processors: Vec<Box<dyn Processor>>) -> Self {
         for p in processors {
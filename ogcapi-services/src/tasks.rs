@@ -0,0 +1,190 @@
+//! Scheduler of the background tasks
+//!
+//! Periodic work of the modules, e.g. the refresh of the generalized tile
+//! geometries or the scheduled harvests, is registered as named task instead
+//! of being spawned as loose future. The tasks are listed and controlled with
+//! the admin API at `/admin/tasks`, see [`crate::routes::tasks`]: runs can be
+//! started at once and the runs on schedule paused and resumed.
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use tokio::{sync::Notify, time::MissedTickBehavior};
+
+use ogcapi_types::tasks::Task;
+
+/// Registered background tasks by id
+#[derive(Clone, Default)]
+pub(crate) struct Scheduler(Arc<RwLock<BTreeMap<&'static str, Arc<Entry>>>>);
+
+struct Entry {
+    description: &'static str,
+    interval: Duration,
+    paused: AtomicBool,
+    /// Notified to start a run at once
+    trigger: Notify,
+    status: Mutex<Status>,
+}
+
+#[derive(Default)]
+struct Status {
+    running: bool,
+    runs: u64,
+    failures: u64,
+    last_run: Option<DateTime<Utc>>,
+    last_duration: Option<Duration>,
+    last_error: Option<String>,
+    next_run: Option<DateTime<Utc>>,
+}
+
+impl Scheduler {
+    /// Run a task every `interval`, starting now
+    ///
+    /// Tasks are registered once by id, later registrations are ignored.
+    pub(crate) fn every<F, Fut>(
+        &self,
+        id: &'static str,
+        description: &'static str,
+        interval: Duration,
+        task: F,
+    ) where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let entry = Arc::new(Entry {
+            description,
+            interval,
+            paused: AtomicBool::new(false),
+            trigger: Notify::new(),
+            status: Default::default(),
+        });
+
+        {
+            let mut tasks = self.0.write().unwrap();
+            if tasks.contains_key(id) {
+                return;
+            }
+            tasks.insert(id, entry.clone());
+        }
+
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(entry.interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                let scheduled = tokio::select! {
+                    _ = ticks.tick() => {
+                        entry.status.lock().unwrap().next_run =
+                            chrono::Duration::from_std(entry.interval)
+                                .ok()
+                                .map(|interval| Utc::now() + interval);
+                        true
+                    }
+                    _ = entry.trigger.notified() => false,
+                };
+
+                if scheduled && entry.paused.load(Ordering::Acquire) {
+                    continue;
+                }
+
+                entry.run(id, &task).await;
+            }
+        });
+    }
+
+    /// State of the tasks, ordered by id
+    pub(crate) fn list(&self) -> Vec<Task> {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(id, entry)| entry.task(id))
+            .collect()
+    }
+
+    pub(crate) fn get(&self, id: &str) -> Option<Task> {
+        self.0.read().unwrap().get(id).map(|entry| entry.task(id))
+    }
+
+    /// Start a run of a task at once, after the current run if it is running,
+    /// `false` if there is no such task
+    pub(crate) fn trigger(&self, id: &str) -> bool {
+        match self.0.read().unwrap().get(id) {
+            Some(entry) => {
+                entry.trigger.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pause or resume the runs of a task on schedule, `false` if there is no
+    /// such task
+    pub(crate) fn pause(&self, id: &str, paused: bool) -> bool {
+        match self.0.read().unwrap().get(id) {
+            Some(entry) => {
+                entry.paused.store(paused, Ordering::Release);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Entry {
+    async fn run<F, Fut>(&self, id: &str, task: &F)
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = anyhow::Result<()>>,
+    {
+        self.status.lock().unwrap().running = true;
+
+        let start = Instant::now();
+        let last_run = Utc::now();
+        let result = task().await;
+
+        let mut status = self.status.lock().unwrap();
+        status.running = false;
+        status.runs += 1;
+        status.last_run = Some(last_run);
+        status.last_duration = Some(start.elapsed());
+        status.last_error = match result {
+            Ok(()) => None,
+            Err(e) => {
+                tracing::warn!("Task `{id}` failed: {e}");
+                status.failures += 1;
+                Some(e.to_string())
+            }
+        };
+    }
+
+    fn task(&self, id: &str) -> Task {
+        let status = self.status.lock().unwrap();
+        let paused = self.paused.load(Ordering::Acquire);
+
+        Task {
+            id: id.to_owned(),
+            description: self.description.to_owned(),
+            interval: self.interval.as_secs(),
+            paused,
+            running: status.running,
+            runs: status.runs,
+            failures: status.failures,
+            last_run: status.last_run,
+            last_duration: status
+                .last_duration
+                .map(|duration| duration.as_millis() as u64),
+            last_error: status.last_error.to_owned(),
+            next_run: status.next_run.filter(|_| !paused),
+            links: Vec::new(),
+        }
+    }
+}
@@ -2,7 +2,11 @@ use tracing_subscriber::prelude::*;
 
 pub fn init() {
     tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .with(tracing_subscriber::fmt::layer().pretty())
+        .with(
+            tracing_subscriber::fmt::layer()
+                .pretty()
+                .with_filter(tracing_subscriber::EnvFilter::from_default_env()),
+        )
+        .with(crate::logging::statements())
         .init();
 }
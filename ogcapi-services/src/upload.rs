@@ -0,0 +1,315 @@
+//! Uploads of large files, spooled to disk
+//!
+//! Data is uploaded either as raw request body, as the file of a
+//! `multipart/form-data` body or in chunks as resumable upload, see
+//! [`crate::routes::uploads`], which is then referenced with the `upload`
+//! query parameter.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Multipart, Query, Request},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap, HeaderName, StatusCode,
+    },
+};
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use tokio::{fs, io::AsyncWriteExt};
+
+use crate::Error;
+
+/// Uploaded data, stored in a file that is removed on drop
+pub(crate) struct Upload {
+    path: PathBuf,
+    /// File name, as given for the multipart file or by the
+    /// `Content-Disposition` header
+    #[cfg_attr(not(feature = "attachments"), allow(dead_code))]
+    pub(crate) file_name: Option<String>,
+    /// Media type of the file, as given for the multipart file or by the
+    /// `Content-Type` header
+    #[cfg_attr(not(feature = "attachments"), allow(dead_code))]
+    pub(crate) media_type: Option<String>,
+}
+
+impl Upload {
+    fn new() -> anyhow::Result<Self> {
+        let dir = uploads_dir();
+        std::fs::create_dir_all(&dir).context("Create upload directory")?;
+
+        Ok(Upload {
+            path: dir.join(uuid::Uuid::new_v4().to_string()),
+            file_name: None,
+            media_type: None,
+        })
+    }
+
+    /// Take a completed resumable upload
+    async fn resumable(id: &str) -> Result<Self, Error> {
+        let status = UploadStatus::read(id).await?.ok_or(Error::NotFound)?;
+
+        if status.offset != status.length {
+            return Err(Error::Exception(
+                StatusCode::CONFLICT,
+                format!(
+                    "Upload `{id}` is incomplete ({} of {} bytes)",
+                    status.offset, status.length
+                ),
+            ));
+        }
+
+        // the length file is no longer needed
+        fs::remove_file(length_path(id)?).await.ok();
+
+        Ok(Upload {
+            path: data_path(id)?,
+            file_name: None,
+            media_type: None,
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write a stream of chunks to the file
+    async fn write<E>(
+        &self,
+        mut chunks: impl Stream<Item = Result<Bytes, E>> + Unpin,
+    ) -> Result<(), Error>
+    where
+        E: std::fmt::Display,
+    {
+        let mut file = fs::File::create(&self.path)
+            .await
+            .context("Create upload file")?;
+
+        while let Some(chunk) = chunks.next().await {
+            let chunk =
+                chunk.map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+            file.write_all(&chunk).await.context("Write upload")?;
+        }
+
+        file.flush().await.context("Write upload")?;
+
+        Ok(())
+    }
+}
+
+impl Drop for Upload {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to remove upload `{}`: {e}", self.path.display());
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct UploadQuery {
+    upload: Option<String>,
+}
+
+#[axum::async_trait]
+impl<S> FromRequest<S> for Upload
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = header(req.headers(), CONTENT_TYPE);
+        let file_name =
+            header(req.headers(), CONTENT_DISPOSITION).and_then(|v| disposition_file_name(&v));
+
+        if let Some(id) = Query::<UploadQuery>::try_from_uri(req.uri())
+            .ok()
+            .and_then(|q| q.0.upload)
+        {
+            let mut upload = Upload::resumable(&id).await?;
+            upload.file_name = file_name;
+            upload.media_type = content_type;
+            return Ok(upload);
+        }
+
+        let mut upload = Upload::new()?;
+
+        let multipart = content_type
+            .as_deref()
+            .is_some_and(|v| v.starts_with("multipart/form-data"));
+
+        if multipart {
+            let mut multipart = Multipart::from_request(req, state)
+                .await
+                .map_err(|e| Error::Exception(e.status(), e.body_text()))?;
+
+            while let Some(field) = multipart
+                .next_field()
+                .await
+                .map_err(|e| Error::Exception(e.status(), e.body_text()))?
+            {
+                if field.file_name().is_some() || field.name() == Some("file") {
+                    upload.file_name = field.file_name().map(ToOwned::to_owned);
+                    upload.media_type = field.content_type().map(ToOwned::to_owned);
+                    upload.write(field).await?;
+                    return Ok(upload);
+                }
+            }
+
+            Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                "Missing file in multipart body".to_string(),
+            ))
+        } else {
+            upload.file_name = file_name;
+            upload.media_type = content_type;
+            upload.write(req.into_body().into_data_stream()).await?;
+            Ok(upload)
+        }
+    }
+}
+
+fn header(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(ToOwned::to_owned)
+}
+
+/// File name of a `Content-Disposition` header, e.g. `attachment; filename="a.pdf"`
+fn disposition_file_name(disposition: &str) -> Option<String> {
+    disposition.split(';').find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        (key.trim().eq_ignore_ascii_case("filename"))
+            .then(|| value.trim().trim_matches('"').to_owned())
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// Progress of a resumable upload
+pub(crate) struct UploadStatus {
+    pub(crate) offset: u64,
+    pub(crate) length: u64,
+}
+
+impl UploadStatus {
+    pub(crate) async fn read(id: &str) -> Result<Option<Self>, Error> {
+        let Ok(length) = fs::read_to_string(length_path(id)?).await else {
+            return Ok(None);
+        };
+
+        let metadata = fs::metadata(data_path(id)?)
+            .await
+            .context("Read upload metadata")?;
+
+        Ok(Some(UploadStatus {
+            offset: metadata.len(),
+            length: length.trim().parse().context("Parse upload length")?,
+        }))
+    }
+}
+
+/// Create an empty resumable upload of the given length, returning its id
+pub(crate) async fn create(length: u64) -> Result<String, Error> {
+    let dir = uploads_dir();
+    fs::create_dir_all(&dir)
+        .await
+        .context("Create upload directory")?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+
+    fs::File::create(data_path(&id)?)
+        .await
+        .context("Create upload file")?;
+    fs::write(length_path(&id)?, length.to_string())
+        .await
+        .context("Create upload file")?;
+
+    Ok(id)
+}
+
+/// Append a chunk to a resumable upload, returning the new offset
+///
+/// Fails if the chunk would exceed the declared length, in which case the
+/// upload is truncated to its previous offset.
+pub(crate) async fn append<E>(
+    id: &str,
+    status: &UploadStatus,
+    mut chunks: impl Stream<Item = Result<Bytes, E>> + Unpin,
+) -> Result<u64, Error>
+where
+    E: std::fmt::Display,
+{
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(data_path(id)?)
+        .await
+        .context("Open upload file")?;
+
+    let mut offset = status.offset;
+    let mut result = Ok(());
+
+    while let Some(chunk) = chunks.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            // keep what was received so far, the client resumes from there
+            Err(e) => {
+                tracing::debug!("Upload `{id}` interrupted: {e}");
+                break;
+            }
+        };
+
+        if offset + chunk.len() as u64 > status.length {
+            result = Err(Error::Exception(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!(
+                    "Upload exceeds the declared length of {} bytes",
+                    status.length
+                ),
+            ));
+            break;
+        }
+
+        file.write_all(&chunk).await.context("Write upload")?;
+        offset += chunk.len() as u64;
+    }
+
+    file.flush().await.context("Write upload")?;
+
+    if result.is_err() {
+        file.set_len(status.offset)
+            .await
+            .context("Truncate upload")?;
+    }
+
+    result.map(|_| offset)
+}
+
+/// Remove a resumable upload
+pub(crate) async fn remove(id: &str) -> Result<(), Error> {
+    fs::remove_file(length_path(id)?).await.ok();
+    fs::remove_file(data_path(id)?).await.ok();
+
+    Ok(())
+}
+
+fn uploads_dir() -> PathBuf {
+    crate::state::work_dir().join("uploads")
+}
+
+fn data_path(id: &str) -> Result<PathBuf, Error> {
+    // ids are uuids, reject anything that could escape the directory
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+        return Err(Error::NotFound);
+    }
+
+    Ok(uploads_dir().join(id))
+}
+
+fn length_path(id: &str) -> Result<PathBuf, Error> {
+    Ok(data_path(id)?.with_extension("length"))
+}
@@ -0,0 +1,156 @@
+//! Usage analytics of the tiles
+//!
+//! Tile requests are counted per collection and tile matrix (zoom level),
+//! together with the hits answered from cached data without rendering the
+//! tile, i.e. tiles outside the cached extents of the collections. The counts
+//! since the start of the service are exposed as Prometheus metrics, and are
+//! added to the usage recorded by the driver periodically, as listed by the
+//! `ogcapi tiles usage` command.
+
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use ogcapi_types::tiles::TileUsage;
+
+use crate::AppState;
+
+/// Time between the writes of the counts to the driver
+const INTERVAL: Duration = Duration::from_secs(60);
+
+/// Maximum number of counted collection and tile matrix pairs, requests of
+/// further pairs (e.g. unknown collections) are not counted
+const MAX_ENTRIES: usize = 10_000;
+
+/// Counts of requests and hits per collection and tile matrix
+type Counts = HashMap<(String, String), (u64, u64)>;
+
+#[derive(Clone, Default)]
+pub(crate) struct Usage(Arc<Mutex<Inner>>);
+
+#[derive(Default)]
+struct Inner {
+    /// Counts since the start of the service
+    total: Counts,
+    /// Counts not yet written to the driver
+    pending: Counts,
+}
+
+impl Usage {
+    /// Count a tile request of the collections
+    pub(crate) fn record(&self, collections: &[&str], tile_matrix: &str, hit: bool) {
+        let inner = &mut *self.0.lock().unwrap();
+
+        for collection in collections {
+            let key = (collection.to_string(), tile_matrix.to_string());
+            if !inner.total.contains_key(&key) && inner.total.len() >= MAX_ENTRIES {
+                continue;
+            }
+            for counts in [&mut inner.total, &mut inner.pending] {
+                let (requests, hits) = counts.entry(key.clone()).or_default();
+                *requests += 1;
+                *hits += u64::from(hit);
+            }
+        }
+    }
+
+    /// Counts since the start of the service in the Prometheus text format
+    pub(crate) fn metrics(&self) -> String {
+        let inner = self.0.lock().unwrap();
+
+        let mut counts: Vec<_> = inner.total.iter().collect();
+        counts.sort();
+
+        let mut metrics = String::new();
+        for (name, help, hits) in [
+            ("ogcapi_tile_requests_total", "Tile requests", false),
+            (
+                "ogcapi_tile_cache_hits_total",
+                "Tile requests answered from cached data",
+                true,
+            ),
+        ] {
+            let _ = writeln!(metrics, "# HELP {name} {help}");
+            let _ = writeln!(metrics, "# TYPE {name} counter");
+            for ((collection, tile_matrix), (requests, cache_hits)) in &counts {
+                let _ = writeln!(
+                    metrics,
+                    "{name}{{collection=\"{}\",tile_matrix=\"{}\"}} {}",
+                    escape(collection),
+                    escape(tile_matrix),
+                    if hits { cache_hits } else { requests }
+                );
+            }
+        }
+
+        metrics
+    }
+
+    /// Take the counts not yet written to the driver
+    fn take(&self) -> Vec<TileUsage> {
+        let pending = std::mem::take(&mut self.0.lock().unwrap().pending);
+
+        pending
+            .into_iter()
+            .map(|((collection, tile_matrix), (requests, hits))| TileUsage {
+                collection,
+                tile_matrix,
+                requests,
+                hits,
+            })
+            .collect()
+    }
+
+    /// Return counts failed to be written, to be written with the next ones
+    fn restore(&self, usage: Vec<TileUsage>) {
+        let mut inner = self.0.lock().unwrap();
+
+        for u in usage {
+            let (requests, hits) = inner
+                .pending
+                .entry((u.collection, u.tile_matrix))
+                .or_default();
+            *requests += u.requests;
+            *hits += u.hits;
+        }
+    }
+}
+
+/// Label value with backslashes, quotes and line feeds escaped
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Write the counts of the tile requests to the driver periodically
+pub(crate) fn spawn(state: &AppState) {
+    let drivers = state.drivers.clone();
+    let usage = state.tile_usage.clone();
+
+    state.tasks.every(
+        "tile-usage",
+        "Write the counts of the tile requests",
+        INTERVAL,
+        move || {
+            let drivers = drivers.clone();
+            let usage = usage.clone();
+            async move {
+                let pending = usage.take();
+                if pending.is_empty() {
+                    return Ok(());
+                }
+
+                let result = drivers.tiles.record_usage(&pending).await;
+                if result.is_err() {
+                    usage.restore(pending);
+                }
+                result
+            }
+        },
+    );
+}
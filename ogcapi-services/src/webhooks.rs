@@ -0,0 +1,230 @@
+//! Delivery of data and job events to registered webhooks
+//!
+//! Deliveries are `POST` requests with the event as JSON body. If the webhook
+//! has a secret, the body is signed with HMAC-SHA256 in the
+//! `X-Ogcapi-Signature-256` header as `sha256={hex digest}`.
+//!
+//! Feature events carry the id of the changed feature, not its properties.
+//! They are only delivered to webhooks of owners with unrestricted access to
+//! the collection, as the access filters can't be applied to changes of
+//! deleted features.
+
+use std::{sync::Arc, time::Duration};
+
+use axum::http::header::CONTENT_TYPE;
+use chrono::Utc;
+use futures::StreamExt;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+#[cfg(any(feature = "processes", feature = "joins"))]
+use tokio::sync::broadcast;
+
+#[cfg(any(feature = "processes", feature = "joins"))]
+use ogcapi_types::processes::StatusInfo;
+use ogcapi_types::{
+    common::media_type::JSON,
+    features::{ChangeKind, FeatureChange},
+    webhooks::{Delivery, Event, EventType, Webhook},
+};
+
+use crate::{leader::Leader, state::Drivers, AppState};
+
+const SIGNATURE_HEADER: &str = "X-Ogcapi-Signature-256";
+const EVENT_HEADER: &str = "X-Ogcapi-Event";
+
+/// Attempts per delivery, with exponential backoff in between
+const MAX_ATTEMPTS: u32 = 5;
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Dispatcher of events to the webhooks accepting them
+#[derive(Clone)]
+pub(crate) struct Dispatcher {
+    client: reqwest::Client,
+    drivers: Arc<Drivers>,
+    leader: Leader,
+}
+
+impl Dispatcher {
+    /// Start dispatching the feature changes and job events of the state
+    pub(crate) fn spawn(state: &AppState) {
+        let dispatcher = Dispatcher {
+            client: reqwest::Client::builder()
+                .timeout(TIMEOUT)
+                .build()
+                .expect("build http client"),
+            drivers: state.drivers.clone(),
+            leader: state.leader.clone(),
+        };
+        Leader::spawn(state);
+
+        #[cfg(any(feature = "processes", feature = "joins"))]
+        tokio::spawn(dispatcher.clone().forward_jobs(state.subscribe_jobs()));
+
+        tokio::spawn(dispatcher.forward_changes());
+    }
+
+    /// Dispatch the changes of the feature change feed, on the primary
+    /// replica
+    async fn forward_changes(self) {
+        let mut changes = match self.drivers.changes.subscribe_all().await {
+            Ok(changes) => changes,
+            Err(e) => {
+                tracing::error!("Failed to subscribe to feature changes: {e}");
+                return;
+            }
+        };
+
+        while let Some(change) = changes.next().await {
+            if !self.leader.is_leader() {
+                continue;
+            }
+            match change.and_then(|change| feature_event(&change)) {
+                Ok(event) => self.dispatch(event).await,
+                Err(e) => tracing::error!("Failed to receive feature change: {e}"),
+            }
+        }
+    }
+
+    #[cfg(any(feature = "processes", feature = "joins"))]
+    async fn forward_jobs(self, mut jobs: broadcast::Receiver<StatusInfo>) {
+        loop {
+            match jobs.recv().await {
+                Ok(job) => match serde_json::to_value(&job) {
+                    Ok(data) => self.dispatch(event(EventType::JobStatus, None, data)).await,
+                    Err(e) => tracing::error!("Failed to serialize job `{}`: {e}", job.job_id),
+                },
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!("Skipped dispatching {n} job events");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    async fn dispatch(&self, event: Event) {
+        let webhooks = match self.drivers.webhooks.list_webhooks().await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                tracing::error!("Failed to list webhooks: {e}");
+                return;
+            }
+        };
+
+        for webhook in webhooks.into_iter().filter(|w| w.accepts(&event)) {
+            if self.readable(&webhook, &event).await {
+                tokio::spawn(self.clone().deliver(webhook, event.clone()));
+            }
+        }
+    }
+
+    /// Whether the owner of a webhook may receive an event, feature events are
+    /// withheld from owners restricted by an access filter on the collection
+    async fn readable(&self, webhook: &Webhook, event: &Event) -> bool {
+        let Some(collection) = &event.collection else {
+            return true;
+        };
+
+        match self
+            .drivers
+            .access
+            .access_filter(collection, webhook.owner.as_deref())
+            .await
+        {
+            Ok(filter) => filter.is_none(),
+            Err(e) => {
+                tracing::error!("Failed to read the access filter of `{collection}`: {e}");
+                false
+            }
+        }
+    }
+
+    /// Deliver an event, retrying on failure and logging every attempt
+    async fn deliver(self, webhook: Webhook, event: Event) {
+        let body = match serde_json::to_vec(&event) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::error!("Failed to serialize event `{}`: {e}", event.id);
+                return;
+            }
+        };
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            let mut request = self
+                .client
+                .post(&webhook.url)
+                .header(CONTENT_TYPE, JSON)
+                .header(EVENT_HEADER, event.r#type.as_str())
+                .body(body.clone());
+
+            if let Some(secret) = &webhook.secret {
+                request =
+                    request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &body)));
+            }
+
+            let (status, error) = match request.send().await {
+                Ok(response) => (Some(response.status().as_u16()), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+
+            let delivery = Delivery {
+                webhook: webhook.id.to_owned(),
+                event: event.id.to_owned(),
+                r#type: event.r#type,
+                attempt,
+                status,
+                error,
+                time: Utc::now(),
+            };
+
+            if let Err(e) = self.drivers.webhooks.log_delivery(&delivery).await {
+                tracing::error!("Failed to log delivery to webhook `{}`: {e}", webhook.id);
+            }
+
+            if delivery.is_success() {
+                return;
+            }
+
+            if attempt < MAX_ATTEMPTS {
+                tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+            }
+        }
+
+        tracing::warn!(
+            "Giving up delivering event `{}` to webhook `{}`",
+            event.id,
+            webhook.id
+        );
+    }
+}
+
+fn feature_event(change: &FeatureChange) -> anyhow::Result<Event> {
+    let r#type = match change.kind {
+        ChangeKind::Create => EventType::FeatureCreate,
+        ChangeKind::Update => EventType::FeatureUpdate,
+        ChangeKind::Delete => EventType::FeatureDelete,
+    };
+
+    Ok(event(
+        r#type,
+        Some(change.collection.to_owned()),
+        serde_json::to_value(change)?,
+    ))
+}
+
+fn event(r#type: EventType, collection: Option<String>, data: serde_json::Value) -> Event {
+    Event {
+        id: uuid::Uuid::new_v4().to_string(),
+        r#type,
+        time: Utc::now(),
+        collection,
+        data,
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of the body
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
@@ -0,0 +1,66 @@
+//! Abstract tests of the conformance classes, run against the features of
+//! the in-memory mock driver without database
+//!
+//! The outcomes are printed as matrix of class, abstract test and outcome.
+//! With `CONFORMANCE_REPORT` set, the report is written to that path, to be
+//! passed to `serve --conformance-report` by builds deploying the service:
+//!
+//! ```bash
+//! CONFORMANCE_REPORT=conformance.json cargo test -p ogcapi-services --features mock --test abstract_tests -- --nocapture
+//! ```
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn abstract_tests() -> anyhow::Result<()> {
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use ogcapi_client::{conformance::TESTED_CLASSES, FeaturesClient};
+    use ogcapi_drivers::{mock::Mock, postgres::Db};
+    use ogcapi_services::{AppState, OgcApiBuilder, OpenAPI};
+    use ogcapi_types::{
+        common::{Collection, Crs},
+        features::Feature,
+    };
+
+    // setup app with a feature to test on
+    let feature: Feature = serde_json::from_value(json!({
+        "type": "Feature",
+        "properties": { "name": "Bern" },
+        "geometry": {
+            "type": "Point",
+            "coordinates": [7.4474, 46.948]
+        }
+    }))?;
+    let mock = Mock::new()
+        .with_collection(Collection {
+            id: "conformance".to_string(),
+            crs: vec![Crs::default()],
+            ..Default::default()
+        })
+        .with_features("conformance", [feature]);
+
+    let openapi = OpenAPI::from_slice(include_bytes!("../assets/openapi/openapi.yaml"));
+    let state = AppState::new_with(Db::lazy(), openapi).await.mock(mock);
+    let router = OgcApiBuilder::from_state(state).features().build();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    // run the embedded abstract tests
+    let features = FeaturesClient::new(&format!("http://{addr}/"))?;
+    let report = features.test_conformance().await?;
+
+    println!("{}", report.matrix());
+    if let Ok(path) = std::env::var("CONFORMANCE_REPORT") {
+        std::fs::write(path, serde_json::to_vec_pretty(&report)?)?;
+    }
+
+    assert_eq!(report.results.len(), TESTED_CLASSES.len());
+    for result in &report.results {
+        assert!(result.passed(), "{}: {:#?}", result.class, result.failures);
+    }
+
+    Ok(())
+}
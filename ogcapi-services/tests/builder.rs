@@ -0,0 +1,214 @@
+//! Routes of all modules, as mounted with all features against the in-memory
+//! mock driver
+//!
+//! ```bash
+//! cargo test -p ogcapi-services --features full,mock --test builder
+//! ```
+
+#[cfg(all(feature = "full", feature = "mock"))]
+#[tokio::test]
+async fn stac_and_search() -> anyhow::Result<()> {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+    use tokio::net::TcpListener;
+
+    use ogcapi_drivers::{mock::Mock, postgres::Db};
+    use ogcapi_services::{AppState, OgcApiBuilder, OpenAPI};
+
+    let openapi = OpenAPI::from_slice(include_bytes!("../assets/openapi/openapi.yaml"));
+    let state = AppState::new_with(Db::lazy(), openapi)
+        .await
+        .mock(Mock::new());
+    // both are mounted regardless of the order of the builder calls
+    for builder in [
+        OgcApiBuilder::from_state(state.clone()).all(),
+        OgcApiBuilder::from_state(state).search().stac(),
+    ] {
+        let router = builder.build();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let client = Client::builder(TokioExecutor::new()).build_http();
+
+        // the STAC item search is served at `/search`
+        let response = client
+            .request(Request::get(format!("http://{addr}/search?limit=0")).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await?.to_bytes();
+        assert!(String::from_utf8_lossy(&body).contains("`limit`"));
+
+        // the search across collections next to it
+        let response = client
+            .request(Request::get(format!("http://{addr}/feature-search")).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await?.to_bytes();
+        assert!(String::from_utf8_lossy(&body).contains("`collections`"));
+    }
+
+    Ok(())
+}
+
+#[cfg(all(feature = "full", feature = "mock"))]
+#[tokio::test]
+async fn webhooks_require_api_key() -> anyhow::Result<()> {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+    use tokio::net::TcpListener;
+
+    use ogcapi_drivers::{mock::Mock, postgres::Db};
+    use ogcapi_services::{AppState, OgcApiBuilder, OpenAPI};
+
+    let openapi = OpenAPI::from_slice(include_bytes!("../assets/openapi/openapi.yaml"));
+    let state = AppState::new_with(Db::lazy(), openapi)
+        .await
+        .mock(Mock::new());
+    let router = OgcApiBuilder::from_state(state).all().build();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    for request in [
+        Request::get(format!("http://{addr}/webhooks")).body(Body::empty())?,
+        Request::post(format!("http://{addr}/webhooks"))
+            .header("Content-Type", "application/json")
+            .body(Body::from(r#"{"url": "https://example.com/hook"}"#))?,
+        Request::get(format!("http://{addr}/webhooks/1/deliveries")).body(Body::empty())?,
+        Request::delete(format!("http://{addr}/webhooks/1")).body(Body::empty())?,
+    ] {
+        let response = client.request(request).await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(())
+}
+
+#[cfg(all(feature = "full", feature = "mock"))]
+#[tokio::test]
+async fn admin_processes_require_admin() -> anyhow::Result<()> {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+    use tokio::net::TcpListener;
+
+    use ogcapi_drivers::{mock::Mock, postgres::Db};
+    use ogcapi_services::{
+        AppState, CollectionReproject, CollectionRestore, CollectionSnapshot, OgcApiBuilder,
+        OpenAPI,
+    };
+
+    let openapi = OpenAPI::from_slice(include_bytes!("../assets/openapi/openapi.yaml"));
+    let state = AppState::new_with(Db::lazy(), openapi)
+        .await
+        .mock(Mock::new())
+        .processors(vec![
+            Box::new(CollectionSnapshot),
+            Box::new(CollectionRestore),
+            Box::new(CollectionReproject),
+        ]);
+    let router = OgcApiBuilder::from_state(state).all().build();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    // refused before the inputs are looked at
+    for process in [
+        "collection-snapshot",
+        "collection-restore",
+        "collection-reproject",
+    ] {
+        let response = client
+            .request(
+                Request::post(format!("http://{addr}/processes/{process}/execution"))
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(r#"{"inputs": {"collection": "countries"}}"#))?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(())
+}
+
+#[cfg(all(feature = "full", feature = "mock"))]
+#[tokio::test]
+async fn ingest_in_batches() -> anyhow::Result<()> {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+    use tokio::net::TcpListener;
+
+    use ogcapi_drivers::{mock::Mock, postgres::Db, CollectionTransactions, FeatureTransactions};
+    use ogcapi_services::{AppState, OgcApiBuilder, OpenAPI};
+    use ogcapi_types::{
+        common::{media_type::GEO_JSON_SEQ, Collection},
+        features::Query,
+    };
+
+    let mock = Mock::new();
+    mock.create_collection(&Collection {
+        id: "places".to_string(),
+        ..Default::default()
+    })
+    .await?;
+
+    let openapi = OpenAPI::from_slice(include_bytes!("../assets/openapi/openapi.yaml"));
+    let state = AppState::new_with(Db::lazy(), openapi)
+        .await
+        .mock(mock.clone());
+    let router = OgcApiBuilder::from_state(state).all().build();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    // more features than fit into one batch
+    let body: String = (0..2500)
+        .map(|i| {
+            format!(
+                "\x1e{{\"type\": \"Feature\", \"id\": \"{i}\", \"properties\": {{}}, \
+                \"geometry\": {{\"type\": \"Point\", \"coordinates\": [7.0, 47.0]}}}}\n"
+            )
+        })
+        .collect();
+
+    let response = client
+        .request(
+            Request::post(format!("http://{addr}/collections/places/items"))
+                .header("Content-Type", GEO_JSON_SEQ)
+                .body(Body::from(body))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = response.into_body().collect().await?.to_bytes();
+    let created: serde_json::Value = serde_json::from_slice(&body)?;
+    assert_eq!(created["numberCreated"], 2500);
+
+    let items = mock.list_items("places", &Query::default()).await?;
+    assert_eq!(items.number_matched, Some(2500));
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+mod setup;
+
+use axum::{body::Body, http::Request};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use serde_json::json;
+
+use ogcapi_client::{conformance::TESTED_CLASSES, FeaturesClient};
+use ogcapi_types::{
+    common::{media_type::JSON, Collection, Crs},
+    features::Feature,
+};
+
+#[tokio::test]
+async fn declared_classes_pass() -> anyhow::Result<()> {
+    // setup app with a feature to test on
+    let (addr, _) = setup::spawn_app().await?;
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let collection = Collection {
+        id: "conformance".to_string(),
+        crs: vec![Crs::default()],
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+    assert_eq!(201, res.status());
+
+    let features = FeaturesClient::new(&format!("http://{}/", addr))?;
+
+    let feature: Feature = serde_json::from_value(json!({
+        "type": "Feature",
+        "properties": { "name": "Bern" },
+        "geometry": {
+            "type": "Point",
+            "coordinates": [7.4474, 46.948]
+        }
+    }))?;
+    features.create_item(&collection.id, &feature).await?;
+
+    // run the embedded abstract tests
+    let report = features.test_conformance().await?;
+
+    assert_eq!(report.results.len(), TESTED_CLASSES.len());
+    for result in &report.results {
+        assert!(result.passed(), "{}: {:#?}", result.class, result.failures);
+    }
+
+    Ok(())
+}
@@ -126,3 +126,63 @@ async fn minimal_feature_crud() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn upsert_feature() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let collection = Collection {
+        id: "upserts".to_string(),
+        crs: vec![Crs::default()],
+        upsert: true,
+        ..Default::default()
+    };
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::POST)
+                .uri(format!("http://{}/collections", addr))
+                .header("Content-Type", JSON)
+                .body(Body::from(serde_json::to_string(&collection)?))?,
+        )
+        .await?;
+    assert_eq!(201, res.status());
+
+    let feature = json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "Point",
+            "coordinates": [7.428959, 1.513394]
+        },
+        "properties": {}
+    });
+    let uri = format!("http://{}/collections/{}/items/f1", addr, collection.id);
+
+    // created when missing, replaced afterwards
+    for status in [201, 204] {
+        let res = client
+            .request(
+                Request::builder()
+                    .method(axum::http::Method::PUT)
+                    .uri(&uri)
+                    .header("Content-Type", JSON)
+                    .body(Body::from(feature.to_string()))?,
+            )
+            .await?;
+        assert_eq!(status, res.status());
+    }
+
+    let res = client
+        .request(
+            Request::builder()
+                .method(axum::http::Method::DELETE)
+                .uri(format!("http://{}/collections/{}", addr, &collection.id))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(204, res.status());
+
+    Ok(())
+}
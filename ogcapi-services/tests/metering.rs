@@ -0,0 +1,145 @@
+mod setup;
+
+#[cfg(feature = "processes")]
+#[tokio::test]
+async fn queued_job_counts_against_quota() -> anyhow::Result<()> {
+    use std::time::Duration;
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        response::Response,
+    };
+    use http_body_util::BodyExt;
+    use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+    use url::Url;
+    use uuid::Uuid;
+
+    use ogcapi_drivers::{QuotaTransactions, UserTransactions};
+    use ogcapi_services::{
+        queue_job, AppState, Config, ConfigParser, Result, Service, TypedProcessor,
+    };
+    use ogcapi_types::{
+        auth::Quota,
+        common::media_type::JSON,
+        processes::{StatusCode as JobStatus, StatusInfo},
+    };
+
+    /// Process waiting in a queued job
+    #[derive(Clone)]
+    struct Wait;
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    struct WaitInputs {
+        millis: u64,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    struct WaitOutputs {
+        millis: u64,
+    }
+
+    #[axum::async_trait]
+    impl TypedProcessor for Wait {
+        const ID: &'static str = "wait";
+
+        type Inputs = WaitInputs;
+        type Outputs = WaitOutputs;
+
+        async fn run(&self, inputs: WaitInputs, state: &AppState, url: &Url) -> Result<Response> {
+            let inputs = serde_json::to_value(inputs).map_err(anyhow::Error::from)?;
+            queue_job(self, inputs, state, url).await
+        }
+
+        async fn run_queued(
+            &self,
+            _job_id: &str,
+            inputs: WaitInputs,
+            _state: &AppState,
+        ) -> anyhow::Result<WaitOutputs> {
+            tokio::time::sleep(Duration::from_millis(inputs.millis)).await;
+            Ok(WaitOutputs {
+                millis: inputs.millis,
+            })
+        }
+    }
+
+    // setup app with metering and the job queue
+    dotenvy::dotenv().ok();
+
+    let mut config = Config::parse();
+    let mut database_url = config.database_url.take().expect("database url");
+    database_url.set_path(&Uuid::new_v4().to_string());
+    config.database_url = Some(database_url);
+    config.port = 0;
+    config.metering = true;
+    config.job_queue = true;
+
+    let state = AppState::new_from(&config)
+        .await
+        .processors(vec![Box::new(Wait)]);
+
+    state.db.create_user("metered").await?;
+    let key = state.db.create_key("metered").await?;
+    let secret = key.key.expect("plain key");
+    state
+        .db
+        .set_quota(&Quota {
+            key: key.id,
+            job_seconds: Some(0.1),
+            ..Default::default()
+        })
+        .await?;
+
+    let service = Service::new_with(&config, state).await;
+    let addr = service.local_addr()?;
+    tokio::spawn(async move {
+        service.serve().await;
+    });
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    // queued, within the quota
+    let response = client
+        .request(
+            Request::post(format!("http://{addr}/processes/wait/execution"))
+                .header("Content-Type", JSON)
+                .header("x-api-key", &secret)
+                .body(Body::from(r#"{"inputs": {"millis": 200}}"#))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = response.into_body().collect().await?.to_bytes();
+    let job: StatusInfo = serde_json::from_slice(&body)?;
+
+    // run by a worker
+    let mut status = JobStatus::Accepted;
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let response = client
+            .request(
+                Request::get(format!("http://{addr}/jobs/{}", job.job_id)).body(Body::empty())?,
+            )
+            .await?;
+        let body = response.into_body().collect().await?.to_bytes();
+        status = serde_json::from_slice::<StatusInfo>(&body)?.status;
+        if status == JobStatus::Successful {
+            break;
+        }
+    }
+    assert_eq!(status, JobStatus::Successful);
+
+    // the run time of the job exhausted the quota of the key
+    let response = client
+        .request(
+            Request::get(format!("http://{addr}/processes"))
+                .header("x-api-key", &secret)
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    Ok(())
+}
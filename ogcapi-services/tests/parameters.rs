@@ -0,0 +1,65 @@
+mod setup;
+
+use axum::{body::Body, http::Request};
+use http_body_util::BodyExt;
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use serde_json::Value;
+
+#[tokio::test]
+async fn sloppy_urls() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    // trailing slash and legacy parameter names
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "http://{}/collections/?LIMIT=1&bboxCrs=http://www.opengis.net/def/crs/OGC/1.3/CRS84",
+                    addr
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(200, res.status());
+
+    let body = res.into_body().collect().await?.to_bytes();
+    let collections: Value = serde_json::from_slice(&body)?;
+    let self_link = collections["links"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|link| link["rel"] == "self")
+        .unwrap();
+    assert!(self_link["href"].as_str().unwrap().contains("bbox-crs="));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unknown_parameters() -> anyhow::Result<()> {
+    let (addr, _) = setup::spawn_app().await?;
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    // rejected when listing collections, as required by OGC API Common
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/collections?foo=bar", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(400, res.status());
+
+    // ignored elsewhere
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/conformance?foo=bar", addr))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(200, res.status());
+
+    Ok(())
+}
@@ -12,7 +12,9 @@ pub async fn spawn_app() -> anyhow::Result<(SocketAddr, Url)> {
     // ogcapi_services::telemetry::init();
 
     let mut config = Config::parse();
-    config.database_url.set_path(&Uuid::new_v4().to_string());
+    let mut database_url = config.database_url.take().expect("database url");
+    database_url.set_path(&Uuid::new_v4().to_string());
+    config.database_url = Some(database_url.clone());
     config.port = 0;
 
     let state = ogcapi_services::AppState::new_from(&config).await;
@@ -25,5 +27,5 @@ pub async fn spawn_app() -> anyhow::Result<(SocketAddr, Url)> {
         service.serve().await;
     });
 
-    Ok((addr, config.database_url))
+    Ok((addr, database_url))
 }
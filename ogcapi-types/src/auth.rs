@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{common::Links, cql2::Expr};
+
+/// User of the api, owner of api keys
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct User {
+    pub id: String,
+    pub created: Option<DateTime<Utc>>,
+}
+
+/// Api key of a user
+///
+/// Only the hash of the secret is stored, the plain key is returned once on creation.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ApiKey {
+    pub id: String,
+    pub user: String,
+    pub created: Option<DateTime<Utc>>,
+    /// Plain key in the form `{id}.{secret}`
+    pub key: Option<String>,
+}
+
+/// Filter restricting the features of a collection accessible to a user
+///
+/// The filter is combined with every query of the user and checked on
+/// writes, features not matching it are hidden.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AccessFilter {
+    pub collection: String,
+    /// User the filter applies to, requests without api key if `None`
+    pub user: Option<String>,
+    /// `CQL2` expression matching the accessible features
+    pub filter: Expr,
+    pub created: Option<DateTime<Utc>>,
+}
+
+/// Metered usage of an api key within a calendar month
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct KeyUsage {
+    /// Key id
+    pub key: String,
+    /// Month of the usage as `YYYY-MM`, in UTC
+    pub month: String,
+    /// Number of requests
+    #[serde(default)]
+    pub requests: u64,
+    /// Number of features returned
+    #[serde(default)]
+    pub features: u64,
+    /// Number of tiles rendered
+    #[serde(default)]
+    pub tiles: u64,
+    /// Run time of the jobs in seconds
+    #[serde(default)]
+    pub job_seconds: f64,
+}
+
+impl KeyUsage {
+    pub fn new(key: impl ToString, month: impl ToString) -> Self {
+        KeyUsage {
+            key: key.to_string(),
+            month: month.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Add the counts of other usage
+    pub fn add(&mut self, other: &KeyUsage) {
+        self.requests += other.requests;
+        self.features += other.features;
+        self.tiles += other.tiles;
+        self.job_seconds += other.job_seconds;
+    }
+}
+
+/// Monthly limits of the usage of an api key, each unlimited if `None`
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Quota {
+    /// Key id
+    pub key: String,
+    pub requests: Option<u64>,
+    pub features: Option<u64>,
+    pub tiles: Option<u64>,
+    pub job_seconds: Option<f64>,
+}
+
+impl Quota {
+    /// Names of the limits reached by the usage
+    pub fn exceeded(&self, usage: &KeyUsage) -> Vec<&'static str> {
+        let mut exceeded = Vec::new();
+
+        if self.requests.is_some_and(|limit| usage.requests >= limit) {
+            exceeded.push("requests");
+        }
+        if self.features.is_some_and(|limit| usage.features >= limit) {
+            exceeded.push("features");
+        }
+        if self.tiles.is_some_and(|limit| usage.tiles >= limit) {
+            exceeded.push("tiles");
+        }
+        if self
+            .job_seconds
+            .is_some_and(|limit| usage.job_seconds >= limit)
+        {
+            exceeded.push("jobSeconds");
+        }
+
+        exceeded
+    }
+}
+
+/// Usage report of the api keys of a user
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct UsageReport {
+    pub usage: Vec<KeyUsage>,
+    pub quotas: Vec<Quota>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Links,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exceeded_quota() {
+        let quota = Quota {
+            key: "k1".to_string(),
+            requests: Some(100),
+            tiles: Some(10),
+            job_seconds: Some(60.0),
+            ..Default::default()
+        };
+
+        let mut usage = KeyUsage::new("k1", "2024-07");
+        usage.add(&KeyUsage {
+            requests: 99,
+            features: 1_000_000,
+            tiles: 10,
+            ..KeyUsage::new("k1", "2024-07")
+        });
+        assert_eq!(quota.exceeded(&usage), ["tiles"]);
+
+        usage.add(&KeyUsage {
+            requests: 1,
+            job_seconds: 60.5,
+            ..KeyUsage::new("k1", "2024-07")
+        });
+        assert_eq!(usage.requests, 100);
+        assert_eq!(quota.exceeded(&usage), ["requests", "tiles", "jobSeconds"]);
+    }
+}
@@ -5,13 +5,132 @@ use serde::{Deserialize, Serialize};
 type Bbox2D = [f64; 4];
 type Bbox3D = [f64; 6];
 
+/// Bounding box as `[minx, miny, maxx, maxy]` or `[minx, miny, minz, maxx, maxy, maxz]`
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum Bbox {
     Bbox2D(Bbox2D),
     Bbox3D(Bbox3D),
 }
 
+impl Bbox {
+    /// Number of dimensions, either 2 or 3
+    pub fn dimensions(&self) -> usize {
+        match self {
+            Bbox::Bbox2D(_) => 2,
+            Bbox::Bbox3D(_) => 3,
+        }
+    }
+
+    /// Horizontal extent as `[minx, miny, maxx, maxy]`
+    pub fn to_2d(&self) -> Bbox2D {
+        match self {
+            Bbox::Bbox2D(bbox) => *bbox,
+            Bbox::Bbox3D(bbox) => [bbox[0], bbox[1], bbox[3], bbox[4]],
+        }
+    }
+
+    /// Vertical extent as `(minz, maxz)` of three dimensional boxes
+    pub fn z_range(&self) -> Option<(f64, f64)> {
+        match self {
+            Bbox::Bbox2D(_) => None,
+            Bbox::Bbox3D(bbox) => Some((bbox[2], bbox[5])),
+        }
+    }
+
+    /// Swap the first two axes, between `x`/`y` order and the axis order of
+    /// crs with northing first
+    pub fn swap_axes(&mut self) {
+        match self {
+            Bbox::Bbox2D([minx, miny, maxx, maxy]) => {
+                std::mem::swap(minx, miny);
+                std::mem::swap(maxx, maxy);
+            }
+            Bbox::Bbox3D([minx, miny, _, maxx, maxy, _]) => {
+                std::mem::swap(minx, miny);
+                std::mem::swap(maxx, maxy);
+            }
+        }
+    }
+
+    /// Checks that the lower corner does not exceed the upper corner on any
+    /// axis and all coordinates are finite
+    pub fn is_valid(&self) -> bool {
+        let [minx, miny, maxx, maxy] = self.to_2d();
+        let (minz, maxz) = self.z_range().unwrap_or_default();
+
+        [minx, miny, maxx, maxy, minz, maxz]
+            .iter()
+            .all(|n| n.is_finite())
+            && minx <= maxx
+            && miny <= maxy
+            && minz <= maxz
+    }
+
+    /// Checks whether a geographic bbox spans the antimeridian, which is
+    /// indicated by `minx > maxx`
+    pub fn crosses_antimeridian(&self) -> bool {
+        let [minx, _, maxx, _] = self.to_2d();
+        minx > maxx
+    }
+
+    /// Checks whether the boxes share at least one point, vertical extents
+    /// are only compared if both boxes are three dimensional
+    pub fn intersects(&self, other: &Bbox) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Checks whether the other box lies completely within this box
+    pub fn contains(&self, other: &Bbox) -> bool {
+        let [minx, miny, maxx, maxy] = self.to_2d();
+        let [ominx, ominy, omaxx, omaxy] = other.to_2d();
+
+        let z = match (self.z_range(), other.z_range()) {
+            (Some((minz, maxz)), Some((ominz, omaxz))) => minz <= ominz && omaxz <= maxz,
+            _ => true,
+        };
+
+        minx <= ominx && miny <= ominy && omaxx <= maxx && omaxy <= maxy && z
+    }
+
+    /// Shared extent of both boxes, three dimensional only if both are
+    pub fn intersection(&self, other: &Bbox) -> Option<Bbox> {
+        let [minx, miny, maxx, maxy] = self.to_2d();
+        let [ominx, ominy, omaxx, omaxy] = other.to_2d();
+
+        let (minx, miny) = (minx.max(ominx), miny.max(ominy));
+        let (maxx, maxy) = (maxx.min(omaxx), maxy.min(omaxy));
+        if minx > maxx || miny > maxy {
+            return None;
+        }
+
+        match (self.z_range(), other.z_range()) {
+            (Some((minz, maxz)), Some((ominz, omaxz))) => {
+                let (minz, maxz) = (minz.max(ominz), maxz.min(omaxz));
+                (minz <= maxz).then_some(Bbox::Bbox3D([minx, miny, minz, maxx, maxy, maxz]))
+            }
+            _ => Some(Bbox::Bbox2D([minx, miny, maxx, maxy])),
+        }
+    }
+
+    /// Smallest box covering both boxes, three dimensional only if both are
+    pub fn union(&self, other: &Bbox) -> Bbox {
+        let [minx, miny, maxx, maxy] = self.to_2d();
+        let [ominx, ominy, omaxx, omaxy] = other.to_2d();
+
+        let (minx, miny) = (minx.min(ominx), miny.min(ominy));
+        let (maxx, maxy) = (maxx.max(omaxx), maxy.max(omaxy));
+
+        match (self.z_range(), other.z_range()) {
+            (Some((minz, maxz)), Some((ominz, omaxz))) => {
+                Bbox::Bbox3D([minx, miny, minz.min(ominz), maxx, maxy, maxz.max(omaxz)])
+            }
+            _ => Bbox::Bbox2D([minx, miny, maxx, maxy]),
+        }
+    }
+}
+
 impl fmt::Display for Bbox {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -99,6 +218,25 @@ mod tests {
         let _bbox: Bbox = Bbox::from_str(s).unwrap();
     }
 
+    #[test]
+    fn relations() {
+        let a = Bbox::from([0.0, 0.0, 10.0, 10.0]);
+        let b = Bbox::from([5.0, 5.0, 0.0, 15.0, 15.0, 100.0]);
+        let c = Bbox::from([20.0, 20.0, 30.0, 30.0]);
+
+        assert!(a.is_valid() && b.is_valid());
+        assert!(!Bbox::from([10.0, 0.0, 0.0, 10.0]).is_valid());
+        assert!(Bbox::from([170.0, 0.0, -170.0, 10.0]).crosses_antimeridian());
+
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&c));
+        assert_eq!(a.intersection(&b), Some(Bbox::from([5.0, 5.0, 10.0, 10.0])));
+        assert_eq!(a.union(&c), Bbox::from([0.0, 0.0, 30.0, 30.0]));
+        assert!(a.union(&c).contains(&a));
+        assert!(!a.contains(&b));
+        assert_eq!(b.to_2d(), [5.0, 5.0, 15.0, 15.0]);
+    }
+
     #[test]
     fn serde_json() {
         let s = "[ 160.6, -55.95, -170, -25.89 ]";
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use super::{collection::is_valid_id, Links};
+
+/// Group of collections, e.g. a theme, optionally nested into a parent
+/// catalog to organize large numbers of collections
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Catalog {
+    pub id: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// Parent catalog, top level if omitted
+    pub parent: Option<String>,
+    /// Ids of the collections grouped in the catalog
+    #[serde(default)]
+    pub collections: Vec<String>,
+    #[serde(default)]
+    pub links: Links,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Catalogs {
+    #[serde(default)]
+    pub links: Links,
+    pub catalogs: Vec<Catalog>,
+}
+
+impl Catalog {
+    pub fn new(id: impl ToString) -> Self {
+        Catalog {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        if !is_valid_id(&self.id) {
+            problems.push(format!(
+                "Id `{}` has to start with a letter or digit, followed by up to 62 letters, \
+                digits, `_`, `-` or `.`",
+                self.id
+            ));
+        }
+
+        if self.parent.as_ref() == Some(&self.id) {
+            problems.push(format!("Catalog `{}` can not be its own parent", self.id));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Whether nesting the catalog into its parent closes a cycle with
+    /// the existing catalogs
+    pub fn creates_cycle(&self, catalogs: &[Catalog]) -> bool {
+        let mut parent = self.parent.as_deref();
+        let mut visited = Vec::new();
+
+        while let Some(id) = parent {
+            if id == self.id || visited.contains(&id) {
+                return true;
+            }
+            visited.push(id);
+            parent = catalogs
+                .iter()
+                .find(|c| c.id == id)
+                .and_then(|c| c.parent.as_deref());
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles() {
+        let nested = |id: &str, parent: Option<&str>| Catalog {
+            parent: parent.map(ToOwned::to_owned),
+            ..Catalog::new(id)
+        };
+
+        let catalogs = vec![
+            nested("environment", None),
+            nested("water", Some("environment")),
+            nested("rivers", Some("water")),
+        ];
+
+        assert!(!nested("lakes", Some("water")).creates_cycle(&catalogs));
+        assert!(nested("environment", Some("rivers")).creates_cycle(&catalogs));
+        assert!(nested("water", Some("water")).validate().is_err());
+    }
+}
@@ -2,14 +2,21 @@ use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use serde_with::DisplayFromStr;
 
-use crate::common::{Crs, Extent, Links};
+use crate::{
+    common::{Bbox, Crs, Extent, Links, Provider},
+    features::{ComputedProperty, PropertiesSchema, Relation, Rule, ValidationRule},
+};
 
 pub const CRS_REF: &str = "#/crs";
 
+/// Known types of the items of a collection
+pub const ITEM_TYPES: [&str; 2] = ["feature", "record"];
+
 /// A body of resources that belong or are used together. An aggregate, set, or group of related resources.
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Collection {
     /// Must be set to `Collection` to be a valid Collection.
@@ -23,6 +30,14 @@ pub struct Collection {
     pub keywords: Vec<String>,
     /// Attribution for the collection.
     pub attribution: Option<String>,
+    /// License of the data, preferably a SPDX License identifier
+    #[cfg(not(feature = "stac"))]
+    pub license: Option<String>,
+    /// Organizations capturing, processing or hosting the data
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<Provider>,
+    /// Digital Object Identifier of the data, e.g. `10.5281/zenodo.1234`
+    pub doi: Option<String>,
     pub extent: Option<Extent>,
     /// An indicator about the type of the items in the collection.
     pub item_type: Option<String>,
@@ -34,8 +49,33 @@ pub struct Collection {
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub storage_crs: Option<Crs>,
     pub storage_crs_coordinate_epoch: Option<f32>,
+    /// Default number of decimals of coordinates in responses
+    pub precision: Option<u32>,
     #[serde(default)]
     pub links: Links,
+    /// Feature properties hidden from some users when features are served
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redactions: Vec<Redaction>,
+    /// Vector tile layers the features are split into by a property
+    pub tile_layers: Option<TileLayers>,
+    /// Rules the features are validated against when written
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validation: Vec<ValidationRule>,
+    /// JSON Schema the properties of the features have to conform to
+    pub properties_schema: Option<PropertiesSchema>,
+    /// Properties computed from the stored ones when features are read
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub computed_properties: Vec<ComputedProperty>,
+    /// Relations of the features to those of other collections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relations: Vec<Relation>,
+    /// Whether replacing a missing feature with `PUT` creates it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub upsert: bool,
+    /// Whether features carry a `version` property, set on creation and
+    /// incremented on every replacement, to replace them only if unchanged
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub versioned: bool,
     /// Detailed information relevant to individual query types
     #[cfg(feature = "edr")]
     #[serde(rename = "data_queries")]
@@ -72,10 +112,6 @@ pub struct Collection {
     /// multiple licenses apply or `proprietary` for all other cases.
     #[cfg(feature = "stac")]
     pub license: String,
-    /// A list of providers, which may include all organizations capturing or processing the data or the hosting provider.
-    #[cfg(feature = "stac")]
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub providers: Vec<crate::stac::Provider>,
     /// A map of property summaries, either a set of values, a range of values or a JSON Schema.
     #[cfg(feature = "stac")]
     #[serde(default, skip_serializing_if = "Map::is_empty")]
@@ -88,6 +124,61 @@ pub struct Collection {
     pub additional_properties: Map<String, Value>,
 }
 
+/// Visibility rule of a feature property, the property is removed from the
+/// features served to everyone outside the audience
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Redaction {
+    pub property: String,
+    pub visible_to: Audience,
+}
+
+/// Split of the features of a collection into vector tile layers by the
+/// value of a property, e.g. roads by their class
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TileLayers {
+    pub property: String,
+    /// Layers with the values of their features, features with other values
+    /// remain in the layer named after the collection
+    pub layers: Vec<TileLayer>,
+}
+
+/// Vector tile layer of the features with one of the values
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TileLayer {
+    pub name: String,
+    pub values: Vec<Value>,
+}
+
+/// Requests allowed to see a redacted property
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum Audience {
+    /// Hidden from every request
+    Nobody,
+    /// Requests with a valid api key
+    Authenticated,
+    /// Requests with the api key of one of the users
+    Users(Vec<String>),
+}
+
+impl Audience {
+    /// Whether the audience includes a user, `None` for requests without api key
+    pub fn includes(&self, user: Option<&str>) -> bool {
+        match self {
+            Audience::Nobody => false,
+            Audience::Authenticated => user.is_some(),
+            Audience::Users(users) => user.is_some_and(|u| users.iter().any(|user| user == u)),
+        }
+    }
+}
+
 #[cfg(feature = "stac")]
 fn collection() -> String {
     "Collection".to_string()
@@ -104,12 +195,25 @@ impl Default for Collection {
             description: Default::default(),
             keywords: Default::default(),
             attribution: Default::default(),
+            #[cfg(not(feature = "stac"))]
+            license: Default::default(),
+            providers: Default::default(),
+            doi: Default::default(),
             extent: Default::default(),
             item_type: Default::default(),
             crs: vec![Crs::default()],
             storage_crs: Default::default(),
             storage_crs_coordinate_epoch: Default::default(),
+            precision: Default::default(),
             links: Default::default(),
+            redactions: Default::default(),
+            tile_layers: Default::default(),
+            validation: Default::default(),
+            properties_schema: Default::default(),
+            computed_properties: Default::default(),
+            relations: Default::default(),
+            upsert: Default::default(),
+            versioned: Default::default(),
             #[cfg(feature = "edr")]
             data_queries: Default::default(),
             #[cfg(feature = "edr")]
@@ -123,8 +227,6 @@ impl Default for Collection {
             #[cfg(feature = "stac")]
             license: "various".to_string(),
             #[cfg(feature = "stac")]
-            providers: Default::default(),
-            #[cfg(feature = "stac")]
             summaries: Default::default(),
             #[cfg(feature = "stac")]
             assets: Default::default(),
@@ -132,3 +234,423 @@ impl Default for Collection {
         }
     }
 }
+
+impl Collection {
+    /// Crs of responses if none is requested, the storage crs if declared
+    pub fn default_crs(&self) -> Crs {
+        self.storage_crs
+            .clone()
+            .or_else(|| self.crs.first().cloned())
+            .unwrap_or_default()
+    }
+
+    /// Properties hidden from a user, `None` for requests without api key
+    pub fn hidden_properties(&self, user: Option<&str>) -> Vec<String> {
+        let mut hidden: Vec<String> = self
+            .redactions
+            .iter()
+            .filter(|r| !r.visible_to.includes(user))
+            .map(|r| r.property.to_owned())
+            .collect();
+        hidden.sort();
+        hidden.dedup();
+        hidden
+    }
+
+    /// Whether the spatial extent has a vertical dimension
+    pub fn has_height(&self) -> bool {
+        self.extent
+            .as_ref()
+            .and_then(|extent| extent.spatial.as_ref())
+            .is_some_and(|spatial| spatial.bbox.iter().any(|bbox| bbox.dimensions() == 3))
+    }
+
+    /// List the storage crs first, as default crs, followed by `CRS84`
+    /// which is always supported, and `CRS84h` for collections with height
+    pub fn normalize_crs(&mut self) {
+        if !self.crs.contains(&Crs::default()) {
+            self.crs.insert(0, Crs::default());
+        }
+        if self.has_height() && !self.crs.contains(&Crs::crs84h()) {
+            let position = self.crs.iter().position(|crs| crs == &Crs::default());
+            self.crs
+                .insert(position.map_or(0, |p| p + 1), Crs::crs84h());
+        }
+        if let Some(storage_crs) = &self.storage_crs {
+            self.crs.retain(|crs| crs != storage_crs);
+            self.crs.insert(0, storage_crs.clone());
+        }
+    }
+
+    /// Check the metadata for consistency, listing all problems found
+    ///
+    /// The id is used as path segment and table name, so it is restricted to
+    /// at most 63 alphanumeric characters, `_`, `-` and `.`.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        if !is_valid_id(&self.id) {
+            problems.push(format!(
+                "Id `{}` has to start with a letter or digit, followed by up to 62 letters, \
+                digits, `_`, `-` or `.`",
+                self.id
+            ));
+        }
+
+        if let Some(item_type) = &self.item_type {
+            if !ITEM_TYPES.iter().any(|t| t.eq_ignore_ascii_case(item_type)) {
+                problems.push(format!(
+                    "Unknown item type `{item_type}`, expected one of `{}`",
+                    ITEM_TYPES.join("`, `")
+                ));
+            }
+        }
+
+        for crs in self.crs.iter().chain(self.storage_crs.as_ref()) {
+            if !crs.is_valid() {
+                problems.push(format!("Unknown crs `{crs}`"));
+            }
+        }
+
+        if self
+            .storage_crs_coordinate_epoch
+            .is_some_and(|epoch| !epoch.is_finite())
+        {
+            problems.push("Storage crs coordinate epoch is not a number".to_string());
+        }
+
+        if let Some(spatial) = self.extent.as_ref().and_then(|e| e.spatial.as_ref()) {
+            if !spatial.crs.is_valid() {
+                problems.push(format!("Unknown crs `{}` of spatial extent", spatial.crs));
+            }
+            for bbox in &spatial.bbox {
+                if !is_valid_extent(bbox, &spatial.crs) {
+                    problems.push(format!("Invalid spatial extent `{bbox}`"));
+                }
+            }
+        }
+
+        if let Some(temporal) = self.extent.as_ref().and_then(|e| e.temporal.as_ref()) {
+            for interval in &temporal.interval {
+                match interval.as_slice() {
+                    [Some(start), Some(end)] if start > end => problems.push(format!(
+                        "Temporal extent starts at {start} after its end at {end}"
+                    )),
+                    [_, _] => {}
+                    _ => problems
+                        .push("Temporal extents have to consist of a start and an end".to_string()),
+                }
+            }
+        }
+
+        for link in &self.links {
+            if link.href.trim().is_empty() || link.rel.trim().is_empty() {
+                problems.push(format!(
+                    "Link `{}` (`{}`) needs a `href` and a `rel`",
+                    link.href, link.rel
+                ));
+            }
+        }
+
+        if let Some(doi) = &self.doi {
+            if !is_valid_doi(doi) {
+                problems.push(format!(
+                    "DOI `{doi}` has to consist of a prefix starting with `10.` and a suffix, \
+                    e.g. `10.5281/zenodo.1234`"
+                ));
+            }
+        }
+
+        for provider in &self.providers {
+            if provider.name.trim().is_empty() {
+                problems.push("Providers need a `name`".to_string());
+            }
+        }
+
+        for redaction in &self.redactions {
+            if redaction.property.trim().is_empty() {
+                problems.push("Redactions need a `property`".to_string());
+            } else if ["id", "geometry"].contains(&redaction.property.as_str()) {
+                problems.push(format!(
+                    "Property `{}` can't be redacted",
+                    redaction.property
+                ));
+            }
+        }
+
+        if let Some(tile_layers) = &self.tile_layers {
+            if tile_layers.property.trim().is_empty() {
+                problems.push("Tile layers need a `property`".to_string());
+            }
+            let mut names = vec![self.id.as_str()];
+            for layer in &tile_layers.layers {
+                if layer.name.trim().is_empty() {
+                    problems.push("Tile layers need a `name`".to_string());
+                } else if names.contains(&layer.name.as_str()) {
+                    problems.push(format!("Tile layer `{}` is not unique", layer.name));
+                } else {
+                    names.push(&layer.name);
+                }
+                if layer.values.is_empty() {
+                    problems.push(format!("Tile layer `{}` needs `values`", layer.name));
+                }
+            }
+        }
+
+        for rule in &self.validation {
+            if let Rule::RequiredProperties { properties } = &rule.rule {
+                if properties.is_empty() || properties.iter().any(|p| p.trim().is_empty()) {
+                    problems.push("Required properties need a `properties` list".to_string());
+                }
+            }
+        }
+
+        if let Some(schema) = &self.properties_schema {
+            problems.extend(schema.problems());
+        }
+
+        let mut names = Vec::new();
+        for property in &self.computed_properties {
+            if property.name.trim().is_empty()
+                || ["id", "geometry"].contains(&property.name.as_str())
+            {
+                problems.push(format!(
+                    "Invalid computed property name `{}`",
+                    property.name
+                ));
+            } else if names.contains(&property.name.as_str()) {
+                problems.push(format!(
+                    "Computed property `{}` is not unique",
+                    property.name
+                ));
+            } else {
+                names.push(&property.name);
+            }
+            match property.parse() {
+                Ok(expression) => {
+                    // computed values are visible to all users
+                    for name in expression.properties() {
+                        if self.redactions.iter().any(|r| r.property == name) {
+                            problems.push(format!(
+                                "Computed property `{}` reads the hidden property `{name}`",
+                                property.name
+                            ));
+                        }
+                    }
+                }
+                Err(e) => problems.push(e),
+            }
+        }
+
+        let mut related = Vec::new();
+        for relation in &self.relations {
+            if relation.collection.trim().is_empty() || relation.property.trim().is_empty() {
+                problems.push("Relations need a `collection` and a `property`".to_string());
+            } else if related.contains(&relation.collection.as_str()) {
+                problems.push(format!(
+                    "Relation to collection `{}` is not unique",
+                    relation.collection
+                ));
+            } else {
+                related.push(&relation.collection);
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+}
+
+pub(crate) fn is_valid_id(id: &str) -> bool {
+    id.len() <= 63
+        && id.chars().next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn is_valid_doi(doi: &str) -> bool {
+    doi.split_once('/').is_some_and(|(prefix, suffix)| {
+        prefix.strip_prefix("10.").is_some_and(|registrant| {
+            !registrant.is_empty() && registrant.chars().all(|c| c.is_ascii_digit() || c == '.')
+        }) && !suffix.trim().is_empty()
+    })
+}
+
+/// Checks the order and range of a spatial extent, geographic extents may
+/// span the antimeridian
+fn is_valid_extent(bbox: &Bbox, crs: &Crs) -> bool {
+    let geographic = crs == &Crs::default() || crs == &Crs::crs84h();
+
+    let [minx, miny, maxx, maxy] = bbox.to_2d();
+    let (minz, maxz) = bbox.z_range().unwrap_or_default();
+
+    [minx, miny, maxx, maxy, minz, maxz]
+        .iter()
+        .all(|n| n.is_finite())
+        && (minx <= maxx || geographic)
+        && miny <= maxy
+        && minz <= maxz
+        && (!geographic
+            || [minx, maxx].iter().all(|x| (-180.0..=180.0).contains(x))
+                && [miny, maxy].iter().all(|y| (-90.0..=90.0).contains(y)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{Bbox, SpatialExtent};
+
+    use super::*;
+
+    #[test]
+    fn storage_crs_is_default() {
+        let mut collection = Collection {
+            crs: vec![Crs::from_epsg(3857), Crs::from_epsg(2056)],
+            storage_crs: Some(Crs::from_epsg(2056)),
+            ..Default::default()
+        };
+        collection.normalize_crs();
+
+        assert_eq!(
+            collection.crs,
+            vec![Crs::from_epsg(2056), Crs::default(), Crs::from_epsg(3857)]
+        );
+        assert_eq!(collection.default_crs(), Crs::from_epsg(2056));
+
+        let collection = Collection::default();
+        assert_eq!(collection.default_crs(), Crs::default());
+    }
+
+    #[test]
+    fn crs84h_for_height() {
+        let mut collection = Collection {
+            extent: Some(Extent {
+                spatial: Some(SpatialExtent {
+                    bbox: vec![Bbox::Bbox3D([7.0, 46.0, 400.0, 8.0, 47.0, 4000.0])],
+                    crs: Crs::crs84h(),
+                }),
+                temporal: None,
+            }),
+            ..Default::default()
+        };
+        assert!(collection.has_height());
+
+        collection.normalize_crs();
+        assert_eq!(collection.crs, vec![Crs::default(), Crs::crs84h()]);
+    }
+    #[test]
+    fn validate() {
+        let collection = Collection {
+            id: "roads_2024".to_string(),
+            item_type: Some("Feature".to_string()),
+            extent: Some(Extent {
+                spatial: Some(SpatialExtent {
+                    // spanning the antimeridian
+                    bbox: vec![Bbox::Bbox2D([170.0, -20.0, -170.0, 20.0])],
+                    crs: Crs::default(),
+                }),
+                temporal: None,
+            }),
+            doi: Some("10.5281/zenodo.1234".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(collection.validate(), Ok(()));
+
+        let collection = Collection {
+            id: "roads\"; DROP TABLE".to_string(),
+            item_type: Some("thing".to_string()),
+            crs: vec![Crs::new(crate::common::Authority::EPSG, "0", "abc")],
+            extent: Some(Extent {
+                spatial: Some(SpatialExtent {
+                    bbox: vec![Bbox::Bbox2D([7.0, 47.0, 8.0, 46.0])],
+                    crs: Crs::default(),
+                }),
+                temporal: Some(crate::common::TemporalExtent {
+                    interval: vec![vec![None]],
+                    ..Default::default()
+                }),
+            }),
+            links: vec![crate::common::Link::new("", "self")],
+            doi: Some("https://doi.org/".to_string()),
+            validation: vec![ValidationRule {
+                rule: Rule::RequiredProperties { properties: vec![] },
+                severity: Default::default(),
+            }],
+            properties_schema: serde_json::from_value(serde_json::json!({ "type": "array" }))
+                .unwrap(),
+            computed_properties: vec![ComputedProperty {
+                name: "id".to_string(),
+                expression: "area(geom) /".to_string(),
+                description: None,
+            }],
+            relations: vec![Relation::default()],
+            ..Default::default()
+        };
+        assert_eq!(collection.validate().unwrap_err().len(), 12);
+    }
+
+    #[test]
+    fn hidden_properties() {
+        let redactions = serde_json::from_value(serde_json::json!([
+            { "property": "owner_email", "visibleTo": "authenticated" },
+            { "property": "owner", "visibleTo": { "users": ["alice"] } },
+            { "property": "price", "visibleTo": "nobody" }
+        ]))
+        .unwrap();
+        let collection = Collection {
+            id: "parcels".to_string(),
+            redactions,
+            ..Default::default()
+        };
+        assert_eq!(collection.validate(), Ok(()));
+
+        assert_eq!(
+            collection.hidden_properties(None),
+            vec!["owner", "owner_email", "price"]
+        );
+        assert_eq!(
+            collection.hidden_properties(Some("bob")),
+            vec!["owner", "price"]
+        );
+        assert_eq!(collection.hidden_properties(Some("alice")), vec!["price"]);
+
+        let collection = Collection {
+            computed_properties: vec![ComputedProperty {
+                name: "price_per_m2".to_string(),
+                expression: "round(price / area(geom), 2)".to_string(),
+                description: None,
+            }],
+            ..collection
+        };
+        assert_eq!(
+            collection.validate().unwrap_err(),
+            vec!["Computed property `price_per_m2` reads the hidden property `price`"]
+        );
+    }
+
+    #[test]
+    fn tile_layers() {
+        let tile_layers = serde_json::from_value(serde_json::json!({
+            "property": "class",
+            "layers": [
+                { "name": "highways", "values": ["motorway", "trunk"] },
+                { "name": "paths", "values": ["path", "footway"] }
+            ]
+        }))
+        .unwrap();
+        let mut collection = Collection {
+            id: "roads".to_string(),
+            tile_layers: Some(tile_layers),
+            ..Default::default()
+        };
+        assert_eq!(collection.validate(), Ok(()));
+
+        let tile_layers = collection.tile_layers.as_mut().unwrap();
+        tile_layers.layers[1].name = "roads".to_string();
+        tile_layers.layers[0].values.clear();
+        assert_eq!(collection.validate().unwrap_err().len(), 2);
+    }
+}
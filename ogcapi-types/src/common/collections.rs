@@ -4,11 +4,12 @@ use serde_with::DisplayFromStr;
 
 use crate::common::{Crs, Links};
 
-use super::Collection;
+use super::{Collection, Query};
 
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Collections {
     #[serde(default)]
@@ -34,4 +35,20 @@ impl Collections {
             crs: Vec::new(),
         }
     }
+
+    /// Page of matching collections selected by `offset` and `limit` of a
+    /// query, counting all matching collections
+    pub fn paginated(matching: Vec<Collection>, query: &Query) -> Self {
+        let number_matched = matching.len();
+
+        let offset = query.offset.unwrap_or_default().max(0) as usize;
+        let limit = query
+            .limit
+            .map_or(usize::MAX, |limit| limit.max(0) as usize);
+
+        let mut collections =
+            Collections::new(matching.into_iter().skip(offset).take(limit).collect());
+        collections.number_matched = Some(number_matched as u64);
+        collections
+    }
 }
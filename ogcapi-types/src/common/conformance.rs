@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// The Conformance declaration states the conformance classes from standards or community
@@ -5,6 +6,7 @@ use serde::{Deserialize, Serialize};
 /// required to use this information. Accessing the Conformance declaration using HTTP GET
 /// returns the list of URIs of conformance classes implemented by the server.
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Conformance {
     pub conforms_to: Vec<String>,
@@ -22,4 +24,128 @@ impl Conformance {
         self.conforms_to
             .extend(classes.iter().map(|c| c.to_string()))
     }
+
+    /// Withhold the classes failing the tests of a conformance report
+    pub fn retain_passed(&mut self, report: &ConformanceReport) {
+        self.conforms_to.retain(|class| !report.failed(class));
+    }
+}
+
+/// Outcome of testing an endpoint against the abstract tests of the
+/// conformance classes it declares
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConformanceReport {
+    /// Tested endpoint
+    pub endpoint: String,
+    pub created: Option<DateTime<Utc>>,
+    /// Results of the tested classes, declared classes without tests are omitted
+    pub results: Vec<ConformanceResult>,
+}
+
+/// Result of the tests of a conformance class
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConformanceResult {
+    pub class: String,
+    /// Number of assertions checked
+    pub assertions: usize,
+    /// Assertions which failed, the class passed if there are none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
+    /// Outcomes of the abstract tests run for the class
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<AbstractTest>,
+}
+
+/// Outcome of an abstract test of a conformance class, identified as in the
+/// abstract test suite of the standard, e.g. `/ats/core/fc-bbox-response`
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AbstractTest {
+    pub id: String,
+    pub passed: bool,
+}
+
+impl ConformanceReport {
+    /// Whether a class was tested and failed
+    pub fn failed(&self, class: &str) -> bool {
+        self.results
+            .iter()
+            .any(|result| result.class == class && !result.passed())
+    }
+
+    /// Whether all tested classes passed
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(ConformanceResult::passed)
+    }
+
+    /// Outcomes of the abstract tests as tab separated lines of class, test
+    /// and `passed` or `failed`
+    pub fn matrix(&self) -> String {
+        let mut lines = Vec::new();
+        for result in &self.results {
+            for test in &result.tests {
+                let outcome = if test.passed { "passed" } else { "failed" };
+                lines.push(format!("{}\t{}\t{outcome}", result.class, test.id));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl ConformanceResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retain_passed() {
+        let core = "http://www.opengis.net/spec/ogcapi-features-1/1.0/conf/core";
+        let crs = "http://www.opengis.net/spec/ogcapi-features-2/1.0/conf/crs";
+        let tiles = "http://www.opengis.net/spec/ogcapi-tiles-1/1.0/conf/core";
+
+        let report = ConformanceReport {
+            endpoint: "http://localhost:8484/".to_string(),
+            created: None,
+            results: vec![
+                ConformanceResult {
+                    class: core.to_string(),
+                    assertions: 12,
+                    failures: vec![],
+                    tests: vec![AbstractTest {
+                        id: "/ats/core/root-success".to_string(),
+                        passed: true,
+                    }],
+                },
+                ConformanceResult {
+                    class: crs.to_string(),
+                    assertions: 3,
+                    failures: vec!["Missing `Content-Crs` header".to_string()],
+                    tests: vec![AbstractTest {
+                        id: "/conf/crs/ogc-crs-header".to_string(),
+                        passed: false,
+                    }],
+                },
+            ],
+        };
+        assert!(!report.passed());
+        assert_eq!(
+            report.matrix(),
+            format!(
+                "{core}\t/ats/core/root-success\tpassed\n{crs}\t/conf/crs/ogc-crs-header\tfailed"
+            )
+        );
+
+        // untested classes stay advertised
+        let mut conformance = Conformance::new(&[core, crs, tiles]);
+        conformance.retain_passed(&report);
+        assert_eq!(conformance, Conformance::new(&[core, tiles]));
+    }
 }
@@ -6,10 +6,11 @@ use serde::{Deserialize, Serialize};
 pub const OGC_CRS84: &str = "http://www.opengis.net/def/crs/OGC/1.3/CRS84";
 
 /// Default CRS for coordinates with height
-// pub const OGC_CRS84H: &str = "http://www.opengis.net/def/crs/OGC/0/CRS84h";
+pub const OGC_CRS84H: &str = "http://www.opengis.net/def/crs/OGC/0/CRS84h";
 
 /// Coordinate Reference System (CRS)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Crs {
     pub authority: Authority,
     pub version: String,
@@ -25,6 +26,11 @@ impl Crs {
         }
     }
 
+    /// `CRS84h`, the default crs for coordinates with ellipsoidal height
+    pub fn crs84h() -> Self {
+        Crs::new(Authority::OGC, "0", "CRS84h")
+    }
+
     pub fn from_epsg(code: i32) -> Self {
         Crs::new(Authority::EPSG, "0", code)
     }
@@ -60,7 +66,7 @@ impl Crs {
         match self.authority {
             Authority::OGC => match self.code.as_str() {
                 "CRS84h" => Some(4979),
-                _ => panic!("Unable to extract epsg code from `{}`", self),
+                _ => None,
             },
             Authority::EPSG => self.code.parse().ok(),
         }
@@ -81,6 +87,36 @@ impl Crs {
     pub fn as_known_crs(&self) -> String {
         format!("{}:{}", self.authority, self.code)
     }
+
+    /// Whether the first axis is latitude or northing, as for the geographic
+    /// `EPSG` crs like `EPSG:4326`, unlike `CRS84` with longitude first
+    ///
+    /// `GeoJSON` and the drivers use `x`/`y` order, coordinates in such crs
+    /// are swapped at the API. Besides the geographic crs, only common
+    /// projected crs with northing first are known.
+    pub fn is_northing_first(&self) -> bool {
+        match self.authority {
+            Authority::OGC => false,
+            Authority::EPSG => match self.code.parse::<u32>() {
+                // projected and geocentric crs among the geographic ones
+                Ok(4087 | 4088 | 4328 | 4978) => false,
+                Ok(4000..=4999) => true,
+                // ETRS89 LAEA and LCC Europe, SWEREF99 TM, PUWG 1992 and
+                // Gauss-Krüger zones
+                Ok(2180 | 3006 | 3034 | 3035 | 31466..=31469) => true,
+                _ => false,
+            },
+        }
+    }
+
+    /// Checks that the code exists for the authority, i.e. `CRS84` or
+    /// `CRS84h` for `OGC` and a positive number for `EPSG`
+    pub fn is_valid(&self) -> bool {
+        match self.authority {
+            Authority::OGC => matches!(self.code.as_str(), "CRS84" | "CRS84h"),
+            Authority::EPSG => self.code.parse::<i32>().is_ok_and(|code| code > 0),
+        }
+    }
 }
 
 impl fmt::Display for Crs {
@@ -95,23 +131,59 @@ impl fmt::Display for Crs {
     }
 }
 
+/// Parses the URI (`http://www.opengis.net/def/crs/EPSG/0/4326`), URN
+/// (`urn:ogc:def:crs:EPSG::4326`), safe CURIE (`[EPSG:4326]`) and short
+/// (`EPSG:4326`, `CRS84`, `4326`) forms
 impl str::FromStr for Crs {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = if s.starts_with("urn") {
-            s.trim_start_matches("urn:ogc:def:crs:")
-                .split(':')
-                .collect()
-        } else {
-            s.trim_start_matches("http://www.opengis.net/def/crs/")
-                .split('/')
-                .collect()
+        let trimmed = s.trim();
+        let trimmed = trimmed
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .unwrap_or(trimmed);
+
+        let uri = trimmed
+            .strip_prefix("http://www.opengis.net/def/crs/")
+            .or_else(|| trimmed.strip_prefix("https://www.opengis.net/def/crs/"));
+        let urn = trimmed
+            .get(..16)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("urn:ogc:def:crs:"))
+            .map(|_| &trimmed[16..]);
+
+        let parts: Vec<&str> = match (uri, urn) {
+            (Some(uri), _) => uri.split('/').collect(),
+            (None, Some(urn)) => urn.split(':').collect(),
+            (None, None) => trimmed.split(':').collect(),
         };
-        match parts.len() {
-            3 => Ok(Crs::new(Authority::from_str(parts[0])?, parts[1], parts[2])),
-            _ => Err(format!("Unable to parse CRS from `{s}`!")),
-        }
+
+        let (authority, version, code) = match parts.as_slice() {
+            [authority, version, code] => (*authority, *version, *code),
+            [authority, code] => (*authority, "", *code),
+            [code] if code.eq_ignore_ascii_case("CRS84") || code.eq_ignore_ascii_case("CRS84h") => {
+                ("OGC", "", *code)
+            }
+            [code] if !code.is_empty() && code.chars().all(|c| c.is_ascii_digit()) => {
+                ("EPSG", "", *code)
+            }
+            _ => return Err(format!("Unable to parse CRS from `{s}`!")),
+        };
+
+        let authority = Authority::from_str(authority)?;
+        let code = match authority {
+            // normalize casing of `CRS84` and `CRS84h`
+            Authority::OGC if code.eq_ignore_ascii_case("CRS84") => "CRS84",
+            Authority::OGC if code.eq_ignore_ascii_case("CRS84h") => "CRS84h",
+            _ => code,
+        };
+        let version = match (version, &authority) {
+            ("", Authority::OGC) if code == "CRS84" => "1.3",
+            ("", _) => "0",
+            (version, _) => version,
+        };
+
+        Ok(Crs::new(authority, version, code))
     }
 }
 
@@ -127,6 +199,7 @@ impl Default for Crs {
 
 /// CRS Authorities
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum Authority {
     OGC,
     EPSG,
@@ -145,7 +218,7 @@ impl str::FromStr for Authority {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
+        match s.to_uppercase().as_str() {
             "OGC" => Ok(Authority::OGC),
             "EPSG" => Ok(Authority::EPSG),
             _ => Err("Unknown crs authority!"),
@@ -157,12 +230,16 @@ impl str::FromStr for Authority {
 mod tests {
     use std::str::FromStr;
 
-    use crate::common::{Crs, OGC_CRS84};
+    use crate::common::{Crs, OGC_CRS84, OGC_CRS84H};
 
     #[test]
     fn parse_crs() {
         let crs = Crs::from_str(OGC_CRS84).unwrap();
-        assert_eq!(format!("{:#}", crs), OGC_CRS84)
+        assert_eq!(format!("{:#}", crs), OGC_CRS84);
+
+        let crs = Crs::from_str(OGC_CRS84H).unwrap();
+        assert_eq!(crs, Crs::crs84h());
+        assert_eq!(crs.to_string(), OGC_CRS84H);
     }
 
     #[test]
@@ -187,6 +264,47 @@ mod tests {
         )
     }
 
+    #[test]
+    fn axis_order() {
+        assert!(!Crs::default().is_northing_first());
+        assert!(!Crs::crs84h().is_northing_first());
+        assert!("urn:ogc:def:crs:EPSG::4326"
+            .parse::<Crs>()
+            .unwrap()
+            .is_northing_first());
+        assert!(Crs::from_epsg(4979).is_northing_first());
+        assert!(Crs::from_epsg(3035).is_northing_first());
+        assert!(!Crs::from_epsg(3857).is_northing_first());
+        assert!(!Crs::from_epsg(2056).is_northing_first());
+    }
+
+    #[test]
+    fn parse_forms() {
+        let epsg = Crs::from_epsg(2056);
+        for s in [
+            "http://www.opengis.net/def/crs/EPSG/0/2056",
+            "https://www.opengis.net/def/crs/EPSG/0/2056",
+            "urn:ogc:def:crs:EPSG::2056",
+            "urn:ogc:def:crs:EPSG:0:2056",
+            "[EPSG:2056]",
+            "epsg:2056",
+            "2056",
+        ] {
+            assert_eq!(Crs::from_str(s), Ok(epsg.clone()), "{s}");
+        }
+
+        for s in ["urn:ogc:def:crs:OGC:1.3:CRS84", "OGC:CRS84", "crs84"] {
+            assert_eq!(Crs::from_str(s), Ok(Crs::default()), "{s}");
+        }
+
+        assert_eq!(
+            Crs::from_str("OGC:CRS84h").unwrap().to_string(),
+            "http://www.opengis.net/def/crs/OGC/0/CRS84h"
+        );
+        assert!(Crs::from_str("FOO:1").is_err());
+        assert_eq!(Crs::default().as_epsg(), None);
+    }
+
     #[test]
     fn to_epsg() {
         let crs = Crs::from_str("http://www.opengis.net/def/crs/EPSG/0/4979").unwrap();
@@ -5,6 +5,7 @@ use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum Datetime {
     Datetime(DateTime<Utc>),
     Interval {
@@ -14,6 +15,7 @@ pub enum Datetime {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum IntervalDatetime {
     Datetime(DateTime<Utc>),
     Open,
@@ -34,7 +36,7 @@ impl fmt::Display for IntervalDatetime {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             IntervalDatetime::Datetime(d) => {
-                write!(f, "{}", d.to_rfc3339_opts(SecondsFormat::Secs, true))
+                write!(f, "{}", d.to_rfc3339_opts(SecondsFormat::AutoSi, true))
             }
             IntervalDatetime::Open => write!(f, ".."),
         }
@@ -45,7 +47,11 @@ impl fmt::Display for Datetime {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Datetime::Datetime(datetime) => {
-                write!(f, "{}", datetime.to_rfc3339_opts(SecondsFormat::Secs, true))
+                write!(
+                    f,
+                    "{}",
+                    datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                )
             }
             Datetime::Interval { from, to } => write!(f, "{}/{}", from, to),
         }
@@ -69,6 +75,120 @@ impl FromStr for Datetime {
     }
 }
 
+impl IntervalDatetime {
+    /// Instant of a closed end, `None` if open
+    pub fn as_datetime(&self) -> Option<DateTime<Utc>> {
+        match self {
+            IntervalDatetime::Datetime(d) => Some(*d),
+            IntervalDatetime::Open => None,
+        }
+    }
+}
+
+impl Datetime {
+    /// Normalize to an interval, instants become intervals of zero length
+    pub fn interval(&self) -> TemporalInterval {
+        match self {
+            Datetime::Datetime(d) => TemporalInterval::instant(*d),
+            Datetime::Interval { from, to } => TemporalInterval {
+                start: from.as_datetime(),
+                end: to.as_datetime(),
+            },
+        }
+    }
+}
+
+/// Closed temporal interval, `None` marks an open (unbounded) end
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TemporalInterval {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TemporalInterval {
+    pub fn new(start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) -> Self {
+        TemporalInterval { start, end }
+    }
+
+    /// Interval of zero length
+    pub fn instant(datetime: DateTime<Utc>) -> Self {
+        TemporalInterval {
+            start: Some(datetime),
+            end: Some(datetime),
+        }
+    }
+
+    /// Checks that the start is not after the end
+    pub fn is_valid(&self) -> bool {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => start <= end,
+            _ => true,
+        }
+    }
+
+    pub fn contains_instant(&self, datetime: &DateTime<Utc>) -> bool {
+        self.start.is_none_or(|start| start <= *datetime)
+            && self.end.is_none_or(|end| *datetime <= end)
+    }
+
+    /// Checks whether the other interval lies completely within this interval
+    pub fn contains(&self, other: &TemporalInterval) -> bool {
+        let start = match (self.start, other.start) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(a), Some(b)) => a <= b,
+        };
+        let end = match (self.end, other.end) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(a), Some(b)) => b <= a,
+        };
+        start && end
+    }
+
+    pub fn intersects(&self, other: &TemporalInterval) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Shared part of both intervals
+    pub fn intersection(&self, other: &TemporalInterval) -> Option<TemporalInterval> {
+        let start = match (self.start, other.start) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        let end = match (self.end, other.end) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        let interval = TemporalInterval { start, end };
+        interval.is_valid().then_some(interval)
+    }
+
+    /// Length of the interval, `None` if unbounded
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        Some(self.end? - self.start?)
+    }
+}
+
+impl From<&Datetime> for TemporalInterval {
+    fn from(datetime: &Datetime) -> Self {
+        datetime.interval()
+    }
+}
+
+impl From<TemporalInterval> for Datetime {
+    fn from(interval: TemporalInterval) -> Self {
+        match (interval.start, interval.end) {
+            (Some(start), Some(end)) if start == end => Datetime::Datetime(start),
+            (start, end) => Datetime::Interval {
+                from: start.map_or(IntervalDatetime::Open, IntervalDatetime::Datetime),
+                to: end.map_or(IntervalDatetime::Open, IntervalDatetime::Datetime),
+            },
+        }
+    }
+}
+
 impl PartialOrd for IntervalDatetime {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match self {
@@ -83,9 +203,35 @@ impl PartialOrd for IntervalDatetime {
 
 #[cfg(test)]
 mod tests {
-    use super::Datetime;
+    use super::{Datetime, TemporalInterval};
     use std::str::FromStr;
 
+    #[test]
+    fn interval_arithmetic() {
+        let a = Datetime::from_str("2020-01-01T00:00:00Z/2020-12-31T00:00:00Z")
+            .unwrap()
+            .interval();
+        let b = Datetime::from_str("2020-06-01T00:00:00Z/..")
+            .unwrap()
+            .interval();
+        let c = Datetime::from_str("2021-06-01T00:00:00Z")
+            .unwrap()
+            .interval();
+
+        let ab = a.intersection(&b).unwrap();
+        assert_eq!(
+            Datetime::from(ab).to_string(),
+            "2020-06-01T00:00:00Z/2020-12-31T00:00:00Z"
+        );
+        assert!(a.contains(&ab) && b.contains(&ab));
+        assert!(!a.contains(&b));
+        assert!(b.contains(&c));
+        assert!(!a.intersects(&c));
+        assert_eq!(c.duration(), Some(chrono::Duration::zero()));
+        assert_eq!(b.duration(), None);
+        assert!(TemporalInterval::default().contains(&a));
+    }
+
     #[test]
     fn parse_datetime() {
         let datetime_str = "2018-02-12T23:20:52Z";
@@ -107,4 +253,15 @@ mod tests {
         let datetime = Datetime::from_str(interval_str).unwrap();
         assert_eq!(format!("{:#}", datetime), interval_str)
     }
+
+    #[test]
+    fn fractional_seconds() {
+        let interval_str = "2018-02-12T00:00:00.5Z/2018-03-18T12:31:12.123456Z";
+        let datetime = Datetime::from_str(interval_str).unwrap();
+        assert_eq!(
+            datetime.to_string(),
+            "2018-02-12T00:00:00.500Z/2018-03-18T12:31:12.123456Z"
+        );
+        assert_eq!(Datetime::from_str(&datetime.to_string()).unwrap(), datetime);
+    }
 }
@@ -6,6 +6,7 @@ use crate::common::{Bbox, Crs};
 
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Extent {
     pub spatial: Option<SpatialExtent>,
     pub temporal: Option<TemporalExtent>,
@@ -22,6 +23,7 @@ impl Default for Extent {
 
 #[serde_with::serde_as]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SpatialExtent {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub bbox: Vec<Bbox>,
@@ -41,6 +43,7 @@ impl Default for SpatialExtent {
 
 #[serde_with::serde_as]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TemporalExtent {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(serialize_with = "serialize_interval")]
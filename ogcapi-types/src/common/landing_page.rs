@@ -17,6 +17,7 @@ use super::Links;
 /// * the Collections (path `/collections`, link relation `data`).
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct LandingPage {
     /// Set to `Catalog` if this Catalog only implements the Catalog spec.
     #[cfg(feature = "stac")]
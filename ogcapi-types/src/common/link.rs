@@ -1,8 +1,16 @@
 use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    link_rel::*,
+    media_type::{GEO_JSON, HTML, JSON, OPEN_API_JSON, SCHEMA_JSON},
+    uri_template,
+};
 
 /// Hyperlink to enable Hypermedia Access
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Link {
     /// Supplies the URI to a remote resource (or resource fragment).
     pub href: String,
@@ -18,6 +26,8 @@ pub struct Link {
     /// human-readable identifier.
     pub title: Option<String>,
     pub length: Option<i64>,
+    /// Marks the href as URI template (RFC 6570) to be expanded by the client.
+    pub templated: Option<bool>,
 }
 
 impl Link {
@@ -30,6 +40,7 @@ impl Link {
             hreflang: None,
             title: None,
             length: None,
+            templated: None,
         }
     }
 
@@ -56,4 +67,194 @@ impl Link {
         self.length = Some(length);
         self
     }
+
+    /// Marks the href of the Link as URI template and returns the Value
+    pub fn templated(mut self) -> Link {
+        self.templated = Some(true);
+        self
+    }
+
+    /// Expands the URI template of the href with the given variables
+    pub fn expand(&self, variables: &[(&str, &str)]) -> Link {
+        Link {
+            href: uri_template::expand(&self.href, variables),
+            templated: None,
+            ..self.to_owned()
+        }
+    }
+
+    /// Rewrites an href starting with the base url `from` to start with `to`
+    /// instead, e.g. for links stored behind another proxy or prefix
+    pub fn rebase(mut self, from: &Url, to: &Url) -> Link {
+        if let Some(path) = self.href.strip_prefix(from.as_str()) {
+            self.href = format!("{to}{path}");
+        }
+        self
+    }
+}
+
+/// Builder for the links of a resource, resolving relative hrefs against
+/// the url of the resource and presetting media types by relation
+///
+/// ```rust
+/// use ogcapi_types::common::{link_rel::{ITEMS, ROOT}, media_type::JSON, LinkBuilder};
+///
+/// let url = "http://localhost:8484/collections/roads".parse().unwrap();
+/// let links = LinkBuilder::new(&url).mediatype(JSON);
+///
+/// assert_eq!(links.self_link().href, "http://localhost:8484/collections/roads");
+/// assert_eq!(links.link("..", ROOT).unwrap().href, "http://localhost:8484/");
+///
+/// let items = links.link("roads/items", ITEMS).unwrap();
+/// assert_eq!(items.r#type.as_deref(), Some("application/geo+json"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LinkBuilder {
+    url: Url,
+    mediatype: Option<String>,
+}
+
+impl LinkBuilder {
+    /// Constructs a new LinkBuilder for the resource at the given url
+    pub fn new(url: &Url) -> LinkBuilder {
+        LinkBuilder {
+            url: url.to_owned(),
+            mediatype: None,
+        }
+    }
+
+    /// Sets the media type of the resource, used for `self` and pagination links
+    pub fn mediatype(mut self, mime: impl ToString) -> LinkBuilder {
+        self.mediatype = Some(mime.to_string());
+        self
+    }
+
+    /// Url of the resource
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Link to the resource itself
+    pub fn self_link(&self) -> Link {
+        Link {
+            r#type: self.mediatype.to_owned(),
+            ..Link::new(&self.url, SELF)
+        }
+    }
+
+    /// Link to the resource with another query string, e.g. for `next` and
+    /// `prev` pages
+    pub fn query(&self, rel: &str, query: Option<&str>) -> Link {
+        let mut url = self.url.to_owned();
+        url.set_query(query.filter(|q| !q.is_empty()));
+
+        Link {
+            r#type: self.mediatype.to_owned(),
+            ..Link::new(url, rel)
+        }
+    }
+
+    /// Link to the resource with the query string of the request, in which
+    /// the given parameters are set and all others are kept verbatim
+    pub fn replace_query(&self, rel: &str, parameters: &[(&str, &str)]) -> Link {
+        let mut url = self.url.to_owned();
+        let kept: Vec<(String, String)> = self
+            .url
+            .query_pairs()
+            .filter(|(key, _)| !parameters.iter().any(|(k, _)| k == key))
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(kept)
+            .extend_pairs(parameters);
+
+        Link {
+            r#type: self.mediatype.to_owned(),
+            ..Link::new(url, rel)
+        }
+    }
+
+    /// Link to another page of the resource, e.g. `next` or `prev`, with the
+    /// parameters of the request except for `offset` and `limit`
+    pub fn page(&self, rel: &str, offset: usize, limit: usize) -> Link {
+        self.replace_query(
+            rel,
+            &[
+                ("offset", &offset.to_string()),
+                ("limit", &limit.to_string()),
+            ],
+        )
+    }
+
+    /// Link to an href relative to the resource, with the media type preset
+    /// for the relation
+    pub fn link(&self, href: &str, rel: &str) -> Result<Link, url::ParseError> {
+        Ok(Link {
+            r#type: mediatype_of(rel).map(ToString::to_string),
+            ..Link::new(self.url.join(href)?, rel)
+        })
+    }
+
+    /// Templated link relative to the resource, the template expressions are
+    /// kept unencoded
+    pub fn template(&self, template: &str, rel: &str) -> Result<Link, url::ParseError> {
+        let (path, expressions) = match template.find('{') {
+            Some(i) => template.split_at(i),
+            None => (template, ""),
+        };
+
+        let mut link = self.link(path, rel)?;
+        link.href.push_str(expressions);
+
+        Ok(link.templated())
+    }
+}
+
+/// Media type usually served for the target of a relation
+fn mediatype_of(rel: &str) -> Option<&'static str> {
+    match rel {
+        ROOT | COLLECTION | CONFORMANCE | DATA | PARENT | CHILD | PROCESSES | JOB_LIST | JOINS
+        | FILES | STATUS | RESULTS | TILESETS_VECTOR | TILING_SCHEME => Some(JSON),
+        ITEM | ITEMS | SEARCH => Some(GEO_JSON),
+        QUERYABLES => Some(SCHEMA_JSON),
+        SERVICE_DESC => Some(OPEN_API_JSON),
+        SERVICE_DOC => Some(HTML),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page() {
+        let url = "http://localhost:8484/collections/roads/items?filter=name%20LIKE%20%27A%25%27&sortby=-length&crs=http://www.opengis.net/def/crs/EPSG/0/2056&datetime=2020-01-01T00:00:00.250Z/..&f=json&offset=10&limit=10"
+            .parse()
+            .unwrap();
+        let links = LinkBuilder::new(&url).mediatype(GEO_JSON);
+
+        let next = links.page(NEXT, 20, 10);
+        let next: Url = next.href.parse().unwrap();
+        let pairs: Vec<(String, String)> = next.query_pairs().into_owned().collect();
+        let get = |key: &str| {
+            pairs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(get("filter"), Some("name LIKE 'A%'"));
+        assert_eq!(get("sortby"), Some("-length"));
+        assert_eq!(
+            get("crs"),
+            Some("http://www.opengis.net/def/crs/EPSG/0/2056")
+        );
+        assert_eq!(get("datetime"), Some("2020-01-01T00:00:00.250Z/.."));
+        assert_eq!(get("f"), Some("json"));
+        assert_eq!(get("offset"), Some("20"));
+        assert_eq!(get("limit"), Some("10"));
+        assert_eq!(pairs.len(), 7);
+    }
 }
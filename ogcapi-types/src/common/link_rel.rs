@@ -17,6 +17,11 @@ pub const COLLECTION: &str = "collection";
 /// See: <http://www.opengis.net/def/rel/ogc/1.0/conformance>
 pub const CONFORMANCE: &str = "conformance";
 
+/// The target IRI points to the coverage of the link’s context.
+///
+/// See: <http://www.opengis.net/def/rel/ogc/1.0/coverage>
+pub const COVERAGE: &str = "coverage";
+
 pub const DATA: &str = "data";
 
 /// Identifies general metadata for the context (dataset or collection) that is primarily intended for consumption by machines.
@@ -27,6 +32,9 @@ pub const DATA_META: &str = "data-meta";
 /// Refers to a resource providing information about the link’s context.
 pub const DESCRIBEDBY: &str = "describedby";
 
+/// Identifies a related resource that is potentially large and might require special handling.
+pub const ENCLOSURE: &str = "enclosure";
+
 /// The target URI points to exceptions of a failed process.
 ///
 /// See: <http://www.opengis.net/def/rel/ogc/1.0/exceptions>
@@ -37,6 +45,9 @@ pub const EXCEPTIONS: &str = "exceptions";
 /// See: <http://www.opengis.net/def/rel/ogc/1.0/execute>
 pub const EXECUTE: &str = "execute";
 
+/// The target URI points to the list of uploaded attribute files.
+pub const FILES: &str = "files";
+
 pub const FIRST: &str = "first";
 
 pub const ITEM: &str = "item";
@@ -48,11 +59,19 @@ pub const ITEMS: &str = "items";
 /// See: <http://www.opengis.net/def/rel/ogc/1.0/job-list>
 pub const JOB_LIST: &str = "job-list";
 
+/// The target URI points to the list of joins.
+pub const JOINS: &str = "joins";
+
 pub const LAST: &str = "last";
 
 /// Refers to a license associated with the link’s context.
 pub const LICENSE: &str = "license";
 
+/// The target IRI points to a map of the link’s context.
+///
+/// See: <http://www.opengis.net/def/rel/ogc/1.0/map>
+pub const MAP: &str = "map";
+
 pub const METADATA: &str = "metadata";
 
 pub const NEXT: &str = "next";
@@ -66,6 +85,11 @@ pub const PREV: &str = "prev";
 /// See: <http://www.opengis.net/def/rel/ogc/1.0/processes>
 pub const PROCESSES: &str = "processes";
 
+/// The target URI points to the queryables of a collection.
+///
+/// See: <http://www.opengis.net/def/rel/ogc/1.0/queryables>
+pub const QUERYABLES: &str = "queryables";
+
 pub const RELATED: &str = "related";
 
 /// The target URI points to the results of a job.
@@ -94,6 +118,9 @@ pub const START: &str = "start";
 /// Identifies a resource that represents the context’s status.
 pub const STATUS: &str = "status";
 
+/// Refers to the terms of service associated with the link’s context.
+pub const TERMS_OF_SERVICE: &str = "terms-of-service";
+
 /// An asset that represents a thumbnail of the Item.
 pub const THUMBNAIL: &str = "thumbnail";
 
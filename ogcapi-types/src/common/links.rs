@@ -12,6 +12,9 @@ pub trait Linked {
     fn resolve_relative_links(&mut self);
 
     fn insert_or_update(&mut self, other: &[Link]);
+
+    /// Rewrite hrefs starting with the base url `from` to start with `to`
+    fn rebase(&mut self, from: &Url, to: &Url);
 }
 
 impl Linked for Links {
@@ -50,4 +53,10 @@ impl Linked for Links {
                 .unwrap_or_else(|| self.push(link.to_owned()));
         }
     }
+
+    fn rebase(&mut self, from: &Url, to: &Url) {
+        for link in self.iter_mut() {
+            *link = link.to_owned().rebase(from, to);
+        }
+    }
 }
@@ -1,34 +1,92 @@
 //! Media Type definitions used in the OGC API standards
 
+/// Media Type for `application/atom+xml`
+pub const ATOM: &str = "application/atom+xml";
+
+/// Media Type for `image/tiff; application=geotiff; profile=cloud-optimized`
+pub const COG: &str = "image/tiff; application=geotiff; profile=cloud-optimized";
+
 /// Media Type for `application/prs.coverage+json`
 pub const COVERAGE_JSON: &str = "application/prs.coverage+json";
 
+/// Media Type for `text/csv`
+pub const CSV: &str = "text/csv";
+
+/// Media Type for `application/x-www-form-urlencoded`, query parameters in
+/// the body of a request
+pub const FORM: &str = "application/x-www-form-urlencoded";
+
 /// Media Type for `application/geo+json`
 pub const GEO_JSON: &str = "application/geo+json";
 
+/// Media Type for `application/geo+json-seq`
+pub const GEO_JSON_SEQ: &str = "application/geo+json-seq";
+
+/// Media Type for `application/geopackage+sqlite3`
+pub const GEO_PACKAGE: &str = "application/geopackage+sqlite3";
+
+/// Media Type for `image/tiff; application=geotiff`
+pub const GEO_TIFF: &str = "image/tiff; application=geotiff";
+
 /// Media Type for `text/html`
 pub const HTML: &str = "text/html";
 
+/// Media Type for `image/jpeg`
+pub const JPEG: &str = "image/jpeg";
+
 /// Media Type for `application/json`
 pub const JSON: &str = "application/json";
 
+/// Media Type for `application/ld+json`
+pub const JSON_LD: &str = "application/ld+json";
+
+/// Media Type for `application/octet-stream`
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
 /// Media Type for `application/vnd.oai.openapi;version=3.0`
 pub const OPEN_API: &str = "application/vnd.oai.openapi;version=3.0";
 
 /// Media Type for `application/vnd.oai.openapi+json;version=3.0`
 pub const OPEN_API_JSON: &str = "application/vnd.oai.openapi+json;version=3.0";
 
+/// Media Type for `application/vnd.oai.openapi+json;version=3.1`
+pub const OPEN_API_JSON_3_1: &str = "application/vnd.oai.openapi+json;version=3.1";
+
 /// Media Type for `application/vnd.oai.openapi+yaml;version=3.0`
 pub const OPEN_API_YAML: &str = "application/vnd.oai.openapi+yaml;version=3.0";
 
 /// Media Type for `application/vnd.mapbox.style+json`
 pub const MAPBOX_STYLE: &str = "application/vnd.mapbox.style+json";
 
+/// Media Type for `application/pdf`
+pub const PDF: &str = "application/pdf";
+
 /// Media Type for `image/png`
 pub const PNG: &str = "image/png";
 
+/// Media Type for `application/zip`
+pub const ZIP: &str = "application/zip";
+
+/// Media Type for `text/plain; version=0.0.4`, the Prometheus text format
+pub const PROMETHEUS: &str = "text/plain; version=0.0.4";
+
 /// Media Type for `application/problem+json`
 pub const PROBLEM_JSON: &str = "application/problem+json";
 
+/// Media Type for `application/schema+json`
+pub const SCHEMA_JSON: &str = "application/schema+json";
+
+/// Media Type for `text/turtle`
+pub const TURTLE: &str = "text/turtle";
+
+/// Media Type for `application/wkb`, well-known binary geometries
+pub const WKB: &str = "application/wkb";
+
+/// Media Type for `application/xml`
+pub const XML: &str = "application/xml";
+
 /// Media Type for `application/vnd.ogc.sld+xml;version=1.0`
 pub const SLD: &str = "application/vnd.ogc.sld+xml;version=1.0";
+
+/// Media Type for `image/svg+xml`
+pub const SVG: &str = "image/svg+xml";
@@ -1,4 +1,5 @@
 mod bbox;
+mod catalog;
 mod collection;
 mod collections;
 mod conformance;
@@ -11,17 +12,21 @@ mod link;
 pub mod link_rel;
 mod links;
 pub mod media_type;
+mod provider;
 mod query;
+pub mod uri_template;
 
 pub use bbox::Bbox;
+pub use catalog::{Catalog, Catalogs};
 pub use collection::*;
 pub use collections::Collections;
-pub use conformance::Conformance;
+pub use conformance::{AbstractTest, Conformance, ConformanceReport, ConformanceResult};
 pub use crs::*;
-pub use datetime::{Datetime, IntervalDatetime};
+pub use datetime::{Datetime, IntervalDatetime, TemporalInterval};
 pub use exception::Exception;
 pub use extent::*;
 pub use landing_page::LandingPage;
-pub use link::Link;
+pub use link::{Link, LinkBuilder};
 pub use links::{Linked, Links};
+pub use provider::{Provider, ProviderRole};
 pub use query::Query;
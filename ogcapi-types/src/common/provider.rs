@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 /// of the collection and therefore influences the data offered by this collection.
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Provider {
     pub name: String,
     pub description: Option<String>,
@@ -12,6 +13,7 @@ pub struct Provider {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum ProviderRole {
     Licensor,
@@ -19,3 +21,14 @@ pub enum ProviderRole {
     Processor,
     Host,
 }
+
+impl Provider {
+    pub fn new(name: impl ToString) -> Self {
+        Provider {
+            name: name.to_string(),
+            description: None,
+            roles: None,
+            url: None,
+        }
+    }
+}
@@ -1,11 +1,17 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_with::DisplayFromStr;
 
-use crate::common::{Bbox, Crs, Datetime};
+use crate::common::{Bbox, Collection, Crs, Datetime, TemporalInterval};
 
 #[serde_with::serde_as]
-#[derive(Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "CollectionQuery")
+)]
+#[serde(rename_all = "kebab-case")]
 pub struct Query {
     #[serde(default)]
     #[serde_as(as = "Option<DisplayFromStr>")]
@@ -19,4 +25,201 @@ pub struct Query {
     pub limit: Option<isize>,
     pub offset: Option<isize>,
     pub f: Option<String>,
+    /// Comma separated search terms, matched against title, description
+    /// and keywords
+    pub q: Option<String>,
+    /// Comma separated keywords, collections with any of them match
+    pub keyword: Option<String>,
+    /// Name or part of the name of a provider
+    pub provider: Option<String>,
+    /// Type of the items of the collections, e.g. `feature`
+    #[serde(alias = "itemType")]
+    pub item_type: Option<String>,
+}
+
+impl Query {
+    /// Lowercase search terms of `q`
+    pub fn terms(&self) -> Vec<String> {
+        split(self.q.as_deref())
+    }
+
+    /// Lowercase keywords of `keyword`
+    pub fn keywords(&self) -> Vec<String> {
+        split(self.keyword.as_deref())
+    }
+
+    /// Whether a collection matches all filters of the query
+    pub fn matches(&self, collection: &Collection) -> bool {
+        self.matches_metadata(collection) && self.matches_extent(collection)
+    }
+
+    /// Whether the metadata of a collection matches `q`, `keyword`,
+    /// `provider` and `item-type`, all case insensitive
+    pub fn matches_metadata(&self, collection: &Collection) -> bool {
+        let terms = self.terms();
+        let keywords = self.keywords();
+
+        let matches_terms = terms.is_empty()
+            || terms.iter().any(|term| {
+                collection
+                    .title
+                    .iter()
+                    .chain(collection.description.iter())
+                    .chain(collection.keywords.iter())
+                    .any(|text| text.to_lowercase().contains(term))
+            });
+
+        let matches_keywords = keywords.is_empty()
+            || collection
+                .keywords
+                .iter()
+                .any(|k| keywords.contains(&k.to_lowercase()));
+
+        let matches_provider = self.provider.as_ref().is_none_or(|provider| {
+            let provider = provider.to_lowercase();
+            collection
+                .providers
+                .iter()
+                .any(|p| p.name.to_lowercase().contains(&provider))
+        });
+
+        let matches_item_type = self.item_type.as_ref().is_none_or(|item_type| {
+            collection
+                .item_type
+                .as_ref()
+                .is_some_and(|t| t.eq_ignore_ascii_case(item_type))
+        });
+
+        matches_terms && matches_keywords && matches_provider && matches_item_type
+    }
+
+    /// Whether the extent of a collection intersects `bbox` and `datetime`,
+    /// collections without extent don't match
+    ///
+    /// Spatial extents are only compared if they are declared in the crs of
+    /// the `bbox`.
+    pub fn matches_extent(&self, collection: &Collection) -> bool {
+        let extent = collection.extent.as_ref();
+
+        let matches_bbox = self.bbox.as_ref().is_none_or(|bbox| {
+            let crs = self.bbox_crs.clone().unwrap_or_default();
+            extent
+                .and_then(|extent| extent.spatial.as_ref())
+                .filter(|spatial| spatial.crs == crs)
+                .is_some_and(|spatial| {
+                    spatial.bbox.iter().any(|extent| {
+                        split_antimeridian(extent)
+                            .iter()
+                            .any(|e| split_antimeridian(bbox).iter().any(|b| e.intersects(b)))
+                    })
+                })
+        });
+
+        let matches_datetime = self.datetime.as_ref().is_none_or(|datetime| {
+            let interval = datetime.interval();
+            extent
+                .and_then(|extent| extent.temporal.as_ref())
+                .is_some_and(|temporal| {
+                    temporal
+                        .interval
+                        .iter()
+                        .any(|extent| match extent.as_slice() {
+                            [start, end] => {
+                                TemporalInterval::new(*start, *end).intersects(&interval)
+                            }
+                            _ => false,
+                        })
+                })
+        });
+
+        matches_bbox && matches_datetime
+    }
+}
+
+/// Boxes crossing the antimeridian split into an eastern and a western part
+fn split_antimeridian(bbox: &Bbox) -> Vec<Bbox> {
+    if !bbox.crosses_antimeridian() {
+        return vec![bbox.to_owned()];
+    }
+
+    let [minx, miny, maxx, maxy] = bbox.to_2d();
+    vec![
+        Bbox::Bbox2D([minx, miny, 180.0, maxy]),
+        Bbox::Bbox2D([-180.0, miny, maxx, maxy]),
+    ]
+}
+
+fn split(list: Option<&str>) -> Vec<String> {
+    list.into_iter()
+        .flat_map(|list| list.split(','))
+        .map(|term| term.trim().to_lowercase())
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::common::{Extent, Provider, SpatialExtent, TemporalExtent};
+
+    use super::*;
+
+    #[test]
+    fn matches_metadata() {
+        let collection = Collection {
+            id: "rivers".to_string(),
+            title: Some("Rivers of Switzerland".to_string()),
+            keywords: vec!["Hydrography".to_string(), "water".to_string()],
+            providers: vec![Provider::new("Federal Office for the Environment")],
+            ..Default::default()
+        };
+
+        let query = |q: Option<&str>, keyword: Option<&str>, provider: Option<&str>| Query {
+            q: q.map(ToOwned::to_owned),
+            keyword: keyword.map(ToOwned::to_owned),
+            provider: provider.map(ToOwned::to_owned),
+            ..Default::default()
+        };
+
+        assert!(query(None, None, None).matches_metadata(&collection));
+        assert!(query(Some("lakes, switzerland"), None, None).matches_metadata(&collection));
+        assert!(query(Some("hydro"), None, None).matches_metadata(&collection));
+        assert!(!query(Some("lakes"), None, None).matches_metadata(&collection));
+        assert!(query(None, Some("hydrography"), None).matches_metadata(&collection));
+        assert!(!query(None, Some("hydro"), None).matches_metadata(&collection));
+        assert!(query(None, None, Some("environment")).matches_metadata(&collection));
+        assert!(!query(Some("rivers"), None, Some("swisstopo")).matches_metadata(&collection));
+    }
+
+    #[test]
+    fn matches_extent() {
+        let collection = Collection {
+            id: "fiji".to_string(),
+            item_type: Some("feature".to_string()),
+            extent: Some(Extent {
+                spatial: Some(SpatialExtent {
+                    bbox: vec![Bbox::Bbox2D([177.0, -19.0, -179.0, -16.0])],
+                    crs: Crs::default(),
+                }),
+                temporal: Some(TemporalExtent {
+                    interval: vec![vec!["2020-01-01T00:00:00Z".parse().ok(), None]],
+                    ..Default::default()
+                }),
+            }),
+            ..Default::default()
+        };
+
+        let query = |query: serde_json::Value| serde_json::from_value::<Query>(query).unwrap();
+
+        assert!(query(json!({
+            "bbox": "-179.5,-18,-179,-17",
+            "datetime": "2024-01-01T00:00:00Z",
+            "itemType": "Feature"
+        }))
+        .matches(&collection));
+        assert!(!query(json!({ "bbox": "0,0,1,1" })).matches(&collection));
+        assert!(!query(json!({ "datetime": "../2019-12-31T00:00:00Z" })).matches(&collection));
+        assert!(!query(json!({ "item-type": "record" })).matches(&collection));
+    }
 }
@@ -0,0 +1,174 @@
+//! Expansion of URI templates ([RFC 6570](https://www.rfc-editor.org/rfc/rfc6570))
+//! with string values, up to level 3 plus prefix modifiers.
+
+use std::fmt::Write;
+
+/// Characters allowed unencoded by the `+` and `#` operators
+const RESERVED: &str = ":/?#[]@!$&'()*+,;=";
+
+/// Expands the expressions of a template with the given variables,
+/// undefined variables are omitted.
+///
+/// ```rust
+/// use ogcapi_types::common::uri_template::expand;
+///
+/// let href = expand(
+///     "/collections/{collectionId}/items{?bbox,limit}",
+///     &[("collectionId", "roads"), ("limit", "10")],
+/// );
+/// assert_eq!(href, "/collections/roads/items?limit=10");
+/// ```
+pub fn expand(template: &str, variables: &[(&str, &str)]) -> String {
+    let mut expanded = String::with_capacity(template.len());
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            // unterminated expression, keep as is
+            break;
+        };
+
+        expand_expression(&rest[start + 1..end], variables, &mut expanded);
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    expanded
+}
+
+/// Names of the variables of a template in order of appearance
+pub fn variables(template: &str) -> Vec<&str> {
+    template
+        .split('{')
+        .skip(1)
+        .filter_map(|s| s.split_once('}').map(|(expression, _)| expression))
+        .flat_map(|expression| {
+            expression
+                .trim_start_matches(['+', '#', '.', '/', ';', '?', '&'])
+                .split(',')
+                .map(|spec| spec.split(':').next().unwrap_or(spec).trim_end_matches('*'))
+        })
+        .collect()
+}
+
+fn expand_expression(expression: &str, variables: &[(&str, &str)], expanded: &mut String) {
+    let (operator, list) = match expression.chars().next() {
+        Some(c @ ('+' | '#' | '.' | '/' | ';' | '?' | '&')) => (Some(c), &expression[1..]),
+        _ => (None, expression),
+    };
+
+    // (first, separator, named, if empty, allow reserved)
+    let (first, separator, named, if_empty, reserved) = match operator {
+        None => ("", ",", false, "", false),
+        Some('+') => ("", ",", false, "", true),
+        Some('#') => ("#", ",", false, "", true),
+        Some('.') => (".", ".", false, "", false),
+        Some('/') => ("/", "/", false, "", false),
+        Some(';') => (";", ";", true, "", false),
+        Some('?') => ("?", "&", true, "=", false),
+        _ => ("&", "&", true, "=", false),
+    };
+
+    let mut defined = 0;
+    for spec in list.split(',') {
+        let (name, prefix) = match spec.split_once(':') {
+            Some((name, length)) => (name, length.parse::<usize>().ok()),
+            None => (spec.trim_end_matches('*'), None),
+        };
+
+        let Some(value) = variables
+            .iter()
+            .find(|(variable, _)| *variable == name)
+            .map(|(_, value)| *value)
+        else {
+            continue;
+        };
+
+        expanded.push_str(if defined == 0 { first } else { separator });
+        defined += 1;
+
+        if named {
+            expanded.push_str(name);
+            if value.is_empty() {
+                expanded.push_str(if_empty);
+                continue;
+            }
+            expanded.push('=');
+        }
+
+        let value = match prefix {
+            Some(length) => value.chars().take(length).collect(),
+            None => value.to_owned(),
+        };
+        encode(&value, reserved, expanded);
+    }
+}
+
+fn encode(value: &str, reserved: bool, expanded: &mut String) {
+    for (i, c) in value.char_indices() {
+        let allowed = c.is_ascii_alphanumeric()
+            || "-._~".contains(c)
+            || reserved && (RESERVED.contains(c) || c == '%' && is_pct_encoded(&value[i..]));
+
+        if allowed {
+            expanded.push(c);
+        } else {
+            let mut buf = [0; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                write!(expanded, "%{b:02X}").unwrap();
+            }
+        }
+    }
+}
+
+fn is_pct_encoded(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    bytes.len() >= 3 && bytes[1].is_ascii_hexdigit() && bytes[2].is_ascii_hexdigit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, variables};
+
+    #[test]
+    fn rfc_examples() {
+        let vars = [
+            ("var", "value"),
+            ("hello", "Hello World!"),
+            ("path", "/foo/bar"),
+            ("x", "1024"),
+            ("y", "768"),
+            ("empty", ""),
+        ];
+
+        for (template, expected) in [
+            ("{var}", "value"),
+            ("{hello}", "Hello%20World%21"),
+            ("{+hello}", "Hello%20World!"),
+            ("{+path}/here", "/foo/bar/here"),
+            ("here?ref={+path}", "here?ref=/foo/bar"),
+            ("{#path,x}/here", "#/foo/bar,1024/here"),
+            ("X{.var}", "X.value"),
+            ("{/var,x}/here", "/value/1024/here"),
+            ("{;x,y,empty}", ";x=1024;y=768;empty"),
+            ("{?x,y,empty}", "?x=1024&y=768&empty="),
+            ("?fixed=yes{&x}", "?fixed=yes&x=1024"),
+            ("{var:3}", "val"),
+            ("{?undef}", ""),
+            ("{?undef,x}", "?x=1024"),
+            ("{x,undef,y}", "1024,768"),
+        ] {
+            assert_eq!(expand(template, &vars), expected, "{template}");
+        }
+    }
+
+    #[test]
+    fn template_variables() {
+        assert_eq!(
+            variables("tiles/{tileMatrix}/{tileRow}/{tileCol}{?f,datetime:4}"),
+            vec!["tileMatrix", "tileRow", "tileCol", "f", "datetime"]
+        );
+    }
+}
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Coverage {
     pub r#type: CoverageType,
     pub domain: Domain,
@@ -12,6 +13,7 @@ pub struct Coverage {
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Domain {
     pub r#type: String,
     pub domain_type: Option<DomainType>,
@@ -20,6 +22,7 @@ pub struct Domain {
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum CoverageType {
     Domain,
     NdArray,
@@ -29,6 +32,7 @@ pub enum CoverageType {
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum DomainType {
     Grid,
     VerticalProfile,
@@ -0,0 +1,121 @@
+//! Functions to build `CQL2` expressions
+
+use super::Expr;
+
+/// Reference a property by name
+pub fn property(name: impl ToString) -> Expr {
+    Expr::Property {
+        property: name.to_string(),
+    }
+}
+
+/// Timestamp literal, e.g. `2024-01-01T00:00:00Z`
+pub fn timestamp(timestamp: impl ToString) -> Expr {
+    Expr::Timestamp {
+        timestamp: timestamp.to_string(),
+    }
+}
+
+/// Date literal, e.g. `2024-01-01`
+pub fn date(date: impl ToString) -> Expr {
+    Expr::Date {
+        date: date.to_string(),
+    }
+}
+
+/// Interval literal between two instants, use `..` for open ends
+pub fn interval(start: impl ToString, end: impl ToString) -> Expr {
+    Expr::Interval {
+        interval: vec![start.to_string().into(), end.to_string().into()],
+    }
+}
+
+/// Combine all expressions using `and`
+pub fn and(exprs: impl IntoIterator<Item = Expr>) -> Expr {
+    Expr::op("and", exprs)
+}
+
+/// Combine all expressions using `or`
+pub fn or(exprs: impl IntoIterator<Item = Expr>) -> Expr {
+    Expr::op("or", exprs)
+}
+
+/// Negate an expression
+pub fn not(expr: Expr) -> Expr {
+    expr.not()
+}
+
+/// Case insensitive comparison of a string or property
+pub fn casei(expr: impl Into<Expr>) -> Expr {
+    Expr::op("casei", [expr.into()])
+}
+
+macro_rules! binary {
+    ($($(#[$meta:meta])* $name:ident => $op:literal),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            pub fn $name(name: &str, value: impl Into<Expr>) -> Expr {
+                Expr::op($op, [property(name), value.into()])
+            }
+        )*
+    };
+}
+
+binary! {
+    /// Property is equal to the value
+    eq => "=",
+    /// Property is not equal to the value
+    neq => "<>",
+    /// Property is less than the value
+    lt => "<",
+    /// Property is less than or equal to the value
+    lte => "<=",
+    /// Property is greater than the value
+    gt => ">",
+    /// Property is greater than or equal to the value
+    gte => ">=",
+    /// Property matches the pattern, with `%` and `_` as wildcards
+    like => "like",
+    /// Geometry property intersects the geometry
+    intersects => "s_intersects",
+    /// Geometry property is disjoint to the geometry
+    disjoint => "s_disjoint",
+    /// Geometry property contains the geometry
+    contains => "s_contains",
+    /// Geometry property is within the geometry
+    within => "s_within",
+    /// Geometry property touches the geometry
+    touches => "s_touches",
+    /// Geometry property overlaps the geometry
+    overlaps => "s_overlaps",
+    /// Geometry property crosses the geometry
+    crosses => "s_crosses",
+    /// Geometry property is equal to the geometry
+    equals => "s_equals",
+    /// Temporal property is after the instant or interval
+    after => "t_after",
+    /// Temporal property is before the instant or interval
+    before => "t_before",
+    /// Temporal property is during the interval
+    during => "t_during",
+    /// Temporal property intersects the instant or interval
+    t_intersects => "t_intersects",
+    /// Temporal property is equal to the instant or interval
+    t_equals => "t_equals",
+}
+
+/// Property is between the lower and upper bound (inclusive)
+pub fn between(name: &str, lower: impl Into<Expr>, upper: impl Into<Expr>) -> Expr {
+    Expr::op("between", [property(name), lower.into(), upper.into()])
+}
+
+/// Property is one of the values
+pub fn in_list(name: &str, values: impl IntoIterator<Item = impl Into<Expr>>) -> Expr {
+    let values = Expr::Array(values.into_iter().map(Into::into).collect());
+    Expr::op("in", [property(name), values])
+}
+
+/// Property is null or missing
+pub fn is_null(name: &str) -> Expr {
+    Expr::op("isNull", [property(name)])
+}
@@ -0,0 +1,356 @@
+//! Abstract syntax tree of `CQL2` expressions with a typed builder.
+//!
+//! Expressions serialize to `CQL2-JSON` through `serde` and to `CQL2-Text`
+//! through [Display](std::fmt::Display). Both encodings are parsed into the
+//! same tree with [parse_text] and [parse_json], which allows to convert
+//! between them and to [validate](Expr::validate) filters.
+//!
+//! # Example:
+//!
+//! ```rust
+//! use ogcapi_types::cql2::{eq, intersects};
+//!
+//! let point = geojson::Geometry::new(geojson::Value::Point(vec![7.0, 46.0]));
+//! let filter = eq("name", "Bern").and(intersects("geometry", point));
+//!
+//! assert_eq!(
+//!     filter.to_string(),
+//!     "name = 'Bern' AND S_INTERSECTS(geometry, POINT(7 46))"
+//! );
+//!
+//! let parsed = ogcapi_types::cql2::parse_text(&filter.to_string()).unwrap();
+//! assert_eq!(parsed, filter);
+//! ```
+
+mod builder;
+mod text;
+mod validate;
+
+pub use builder::*;
+pub use text::parse_text;
+pub use validate::Validator;
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::Bbox;
+
+/// `CQL2` expression
+///
+/// The variants mirror the `CQL2-JSON` encoding, operations and function
+/// calls alike are represented by `op` with `args`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum Expr {
+    Operation {
+        op: String,
+        args: Vec<Expr>,
+    },
+    Property {
+        property: String,
+    },
+    Interval {
+        interval: Vec<Expr>,
+    },
+    Timestamp {
+        timestamp: String,
+    },
+    Date {
+        date: String,
+    },
+    BBox {
+        bbox: Vec<f64>,
+    },
+    Geometry(
+        #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::Geometry"))]
+        geojson::Geometry,
+    ),
+    Array(Vec<Expr>),
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+    Null,
+}
+
+impl Expr {
+    /// Create an operation or function call
+    pub fn op(op: impl ToString, args: impl IntoIterator<Item = impl Into<Expr>>) -> Self {
+        Expr::Operation {
+            op: op.to_string(),
+            args: args.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Combine with another expression using `and`
+    pub fn and(self, other: impl Into<Expr>) -> Self {
+        self.combine("and", other.into())
+    }
+
+    /// Combine with another expression using `or`
+    pub fn or(self, other: impl Into<Expr>) -> Self {
+        self.combine("or", other.into())
+    }
+
+    /// Negate the expression
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Expr::op("not", [self])
+    }
+
+    /// Encode as `CQL2-JSON`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("Serialize CQL2 expression")
+    }
+
+    /// Flatten nested operations of the same kind, e.g. `(a AND b) AND c`
+    fn combine(self, op: &str, other: Expr) -> Self {
+        let mut args = Vec::new();
+        for expr in [self, other] {
+            match expr {
+                Expr::Operation { op: o, args: a } if o == op => args.extend(a),
+                expr => args.push(expr),
+            }
+        }
+        Expr::Operation {
+            op: op.to_string(),
+            args,
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_text(s)
+    }
+}
+
+/// Parse a `CQL2-JSON` expression
+pub fn parse_json(input: &str) -> Result<Expr, Error> {
+    serde_json::from_str(input).map_err(|e| Error::new(e.to_string()))
+}
+
+/// Error parsing or validating a `CQL2` expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub message: String,
+    /// Character offset in the `CQL2-Text` input
+    pub position: Option<usize>,
+}
+
+impl Error {
+    pub fn new(message: impl ToString) -> Self {
+        Error {
+            message: message.to_string(),
+            position: None,
+        }
+    }
+
+    pub(crate) fn at(message: impl ToString, position: usize) -> Self {
+        Error {
+            message: message.to_string(),
+            position: Some(position),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{} at position {position}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<&str> for Expr {
+    fn from(value: &str) -> Self {
+        Expr::String(value.to_string())
+    }
+}
+
+impl From<String> for Expr {
+    fn from(value: String) -> Self {
+        Expr::String(value)
+    }
+}
+
+impl From<bool> for Expr {
+    fn from(value: bool) -> Self {
+        Expr::Bool(value)
+    }
+}
+
+macro_rules! impl_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Expr {
+                fn from(value: $t) -> Self {
+                    Expr::Number(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_number!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl From<f64> for Expr {
+    fn from(value: f64) -> Self {
+        serde_json::Number::from_f64(value)
+            .map(Expr::Number)
+            .unwrap_or(Expr::Null)
+    }
+}
+
+impl From<geojson::Geometry> for Expr {
+    fn from(value: geojson::Geometry) -> Self {
+        Expr::Geometry(value)
+    }
+}
+
+impl From<geojson::Value> for Expr {
+    fn from(value: geojson::Value) -> Self {
+        Expr::Geometry(geojson::Geometry::new(value))
+    }
+}
+
+impl From<Bbox> for Expr {
+    fn from(value: Bbox) -> Self {
+        let bbox = match value {
+            Bbox::Bbox2D(bbox) => bbox.to_vec(),
+            Bbox::Bbox3D(bbox) => bbox.to_vec(),
+        };
+        Expr::BBox { bbox }
+    }
+}
+
+impl<T: Into<Expr>> From<Vec<T>> for Expr {
+    fn from(value: Vec<T>) -> Self {
+        Expr::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn comparison() {
+        let expr = eq("name", "O'Hare")
+            .and(gte("count", 10))
+            .or(is_null("count"));
+
+        assert_eq!(
+            expr.to_string(),
+            "(name = 'O''Hare' AND count >= 10) OR count IS NULL"
+        );
+        assert_eq!(
+            expr.to_json(),
+            json!({
+                "op": "or",
+                "args": [
+                    {
+                        "op": "and",
+                        "args": [
+                            { "op": "=", "args": [{ "property": "name" }, "O'Hare"] },
+                            { "op": ">=", "args": [{ "property": "count" }, 10] }
+                        ]
+                    },
+                    { "op": "isNull", "args": [{ "property": "count" }] }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn predicates() {
+        let expr = like("name", "Ber%")
+            .and(between("population", 1000, 5000))
+            .and(in_list("canton", vec!["BE", "ZH"]))
+            .and(eq("name", "x").not());
+
+        assert_eq!(
+            expr.to_string(),
+            "name LIKE 'Ber%' AND population BETWEEN 1000 AND 5000 AND canton IN ('BE', 'ZH') AND NOT (name = 'x')"
+        );
+    }
+
+    #[test]
+    fn spatial_temporal() {
+        let expr = intersects("geometry", Bbox::from([7.0, 46.0, 8.0, 47.0]))
+            .and(after("updated", timestamp("2024-01-01T00:00:00Z")))
+            .and(during("event", interval("2024-01-01", "..")));
+
+        assert_eq!(
+            expr.to_string(),
+            "S_INTERSECTS(geometry, BBOX(7, 46, 8, 47)) AND T_AFTER(updated, TIMESTAMP('2024-01-01T00:00:00Z')) AND T_DURING(event, INTERVAL('2024-01-01', '..'))"
+        );
+        assert_eq!(
+            expr.to_json()["args"][0],
+            json!({ "op": "s_intersects", "args": [{ "property": "geometry" }, { "bbox": [7.0, 46.0, 8.0, 47.0] }] })
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let filters = [
+            "name = 'O''Hare' AND (count >= 10 OR count IS NULL)",
+            "NOT (\"the name\" LIKE 'Ber%') AND population BETWEEN 1000 AND 5000.5",
+            "canton IN ('BE', 'ZH') AND S_WITHIN(geometry, POLYGON((0 0, 1 0, 1 1, 0 0)))",
+            "T_DURING(event, INTERVAL('2024-01-01', '..')) AND CASEI(name) = CASEI('bern')",
+            "A_CONTAINS(tags, ('a', 'b')) AND S_INTERSECTS(geometry, MULTIPOINT(1 2, 3 4 5))",
+            "myFunction(x, TIMESTAMP('2024-01-01T00:00:00Z')) AND open = TRUE",
+        ];
+
+        for text in filters {
+            let expr = parse_text(text).unwrap();
+            let json = serde_json::to_string(&expr).unwrap();
+            assert_eq!(parse_json(&json).unwrap(), expr, "{text}");
+            assert_eq!(parse_text(&expr.to_string()).unwrap(), expr, "{text}");
+        }
+
+        let expr: Expr = "a IS NOT NULL AND b NOT IN (1, 2)".parse().unwrap();
+        assert_eq!(expr, is_null("a").not().and(in_list("b", [1, 2]).not()));
+
+        let err = parse_text("name = 'x' AND").unwrap_err();
+        assert_eq!(err.position, Some(11));
+    }
+
+    #[test]
+    fn validate() {
+        let queryables: crate::features::Queryables = serde_json::from_value(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "count": { "type": "integer" },
+                "geometry": { "format": "geometry-any" }
+            },
+            "additionalProperties": false
+        }))
+        .unwrap();
+
+        let valid = eq("name", "x")
+            .and(in_list("count", [1, 2]))
+            .and(intersects("geometry", Bbox::from([0.0, 0.0, 1.0, 1.0])));
+        assert!(valid.validate(&queryables).is_ok());
+
+        assert!(eq("other", 1).validate(&queryables).is_err());
+        assert!(eq("count", "x").validate(&queryables).is_err());
+        assert!(eq("count", 1.5).validate(&queryables).is_err());
+        assert!(intersects("name", Bbox::from([0.0, 0.0, 1.0, 1.0]))
+            .validate(&queryables)
+            .is_err());
+        assert!(Expr::op("between", [property("count")])
+            .validate(&queryables)
+            .is_err());
+    }
+}
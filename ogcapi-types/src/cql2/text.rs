@@ -0,0 +1,710 @@
+//! `CQL2-Text` encoding
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use geojson::Value;
+
+use super::{Error, Expr};
+
+/// Reserved words which have to be quoted when used as property name
+const KEYWORDS: [&str; 11] = [
+    "AND", "BETWEEN", "FALSE", "IN", "IS", "LIKE", "NOT", "NULL", "OR", "TRUE", "INTERVAL",
+];
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Operation { op, args } => write_operation(f, op, args),
+            Expr::Property { property } => write_property(f, property),
+            Expr::Interval { interval } => {
+                f.write_str("INTERVAL(")?;
+                write_list(f, interval)?;
+                f.write_char(')')
+            }
+            Expr::Timestamp { timestamp } => write!(f, "TIMESTAMP('{timestamp}')"),
+            Expr::Date { date } => write!(f, "DATE('{date}')"),
+            Expr::BBox { bbox } => {
+                f.write_str("BBOX(")?;
+                for (i, n) in bbox.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{n}")?;
+                }
+                f.write_char(')')
+            }
+            Expr::Geometry(geometry) => write_wkt(f, &geometry.value),
+            Expr::Array(items) => {
+                f.write_char('(')?;
+                write_list(f, items)?;
+                f.write_char(')')
+            }
+            Expr::Bool(b) => f.write_str(if *b { "TRUE" } else { "FALSE" }),
+            Expr::Number(n) => write!(f, "{n}"),
+            Expr::String(s) => write!(f, "'{}'", s.replace('\'', "''")),
+            Expr::Null => f.write_str("NULL"),
+        }
+    }
+}
+
+fn write_operation(f: &mut Formatter<'_>, op: &str, args: &[Expr]) -> fmt::Result {
+    match (op, args) {
+        ("and" | "or", args) => {
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", op.to_uppercase())?;
+                }
+                write_operand(f, arg)?;
+            }
+            Ok(())
+        }
+        ("not", [arg]) => write!(f, "NOT ({arg})"),
+        ("=" | "<>" | "<" | "<=" | ">" | ">=", [a, b]) => {
+            write_operand(f, a)?;
+            write!(f, " {op} ")?;
+            write_operand(f, b)
+        }
+        ("like", [a, b]) => {
+            write_operand(f, a)?;
+            f.write_str(" LIKE ")?;
+            write_operand(f, b)
+        }
+        ("between", [a, b, c]) => {
+            write_operand(f, a)?;
+            f.write_str(" BETWEEN ")?;
+            write_operand(f, b)?;
+            f.write_str(" AND ")?;
+            write_operand(f, c)
+        }
+        ("in", [a, list]) => {
+            write_operand(f, a)?;
+            f.write_str(" IN ")?;
+            match list {
+                Expr::Array(_) => write!(f, "{list}"),
+                list => write!(f, "({list})"),
+            }
+        }
+        ("isNull", [a]) => {
+            write_operand(f, a)?;
+            f.write_str(" IS NULL")
+        }
+        (op, args) => {
+            // standardized functions are written in upper case
+            if is_standard_function(&op.to_uppercase()) {
+                f.write_str(&op.to_uppercase())?;
+            } else {
+                f.write_str(op)?;
+            }
+            f.write_char('(')?;
+            write_list(f, args)?;
+            f.write_char(')')
+        }
+    }
+}
+
+/// Write an operand, enclosing logical operations in parentheses
+fn write_operand(f: &mut Formatter<'_>, expr: &Expr) -> fmt::Result {
+    match expr {
+        Expr::Operation { op, .. } if op == "and" || op == "or" => write!(f, "({expr})"),
+        expr => write!(f, "{expr}"),
+    }
+}
+
+fn write_list(f: &mut Formatter<'_>, items: &[Expr]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{item}")?;
+    }
+    Ok(())
+}
+
+fn write_property(f: &mut Formatter<'_>, property: &str) -> fmt::Result {
+    let mut chars = property.chars();
+    let plain = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':'))
+        && !KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(property));
+
+    if plain {
+        f.write_str(property)
+    } else {
+        write!(f, "\"{}\"", property.replace('"', "\"\""))
+    }
+}
+
+fn write_wkt(f: &mut Formatter<'_>, value: &Value) -> fmt::Result {
+    match value {
+        Value::Point(p) => {
+            write_tag(f, "POINT", p.len() > 2)?;
+            f.write_char('(')?;
+            write_position(f, p)?;
+            f.write_char(')')
+        }
+        Value::MultiPoint(m) => {
+            write_tag(f, "MULTIPOINT", m.first().is_some_and(|p| p.len() > 2))?;
+            write_positions(f, m)
+        }
+        Value::LineString(l) => {
+            write_tag(f, "LINESTRING", l.first().is_some_and(|p| p.len() > 2))?;
+            write_positions(f, l)
+        }
+        Value::MultiLineString(m) => {
+            write_tag(f, "MULTILINESTRING", has_z(m))?;
+            write_rings(f, m)
+        }
+        Value::Polygon(p) => {
+            write_tag(f, "POLYGON", has_z(p))?;
+            write_rings(f, p)
+        }
+        Value::MultiPolygon(m) => {
+            write_tag(
+                f,
+                "MULTIPOLYGON",
+                m.first().is_some_and(|p| has_z(p.as_slice())),
+            )?;
+            f.write_char('(')?;
+            for (i, p) in m.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write_rings(f, p)?;
+            }
+            f.write_char(')')
+        }
+        Value::GeometryCollection(g) => {
+            f.write_str("GEOMETRYCOLLECTION(")?;
+            for (i, g) in g.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write_wkt(f, &g.value)?;
+            }
+            f.write_char(')')
+        }
+    }
+}
+
+fn has_z(rings: &[Vec<Vec<f64>>]) -> bool {
+    rings
+        .first()
+        .and_then(|r| r.first())
+        .is_some_and(|p| p.len() > 2)
+}
+
+fn write_tag(f: &mut Formatter<'_>, tag: &str, z: bool) -> fmt::Result {
+    f.write_str(tag)?;
+    if z {
+        f.write_str(" Z")?;
+    }
+    Ok(())
+}
+
+fn write_position(f: &mut Formatter<'_>, position: &[f64]) -> fmt::Result {
+    for (i, ordinate) in position.iter().enumerate() {
+        if i > 0 {
+            f.write_char(' ')?;
+        }
+        write!(f, "{ordinate}")?;
+    }
+    Ok(())
+}
+
+fn write_positions(f: &mut Formatter<'_>, positions: &[Vec<f64>]) -> fmt::Result {
+    f.write_char('(')?;
+    for (i, p) in positions.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write_position(f, p)?;
+    }
+    f.write_char(')')
+}
+
+fn write_rings(f: &mut Formatter<'_>, rings: &[Vec<Vec<f64>>]) -> fmt::Result {
+    f.write_char('(')?;
+    for (i, r) in rings.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write_positions(f, r)?;
+    }
+    f.write_char(')')
+}
+
+/// Parse a `CQL2-Text` expression
+pub fn parse_text(input: &str) -> Result<Expr, Error> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+
+    let expr = parser.expr()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(_) => Err(parser.error("Unexpected trailing input")),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Quoted(String),
+    String(String),
+    Number(serde_json::Number),
+    Op(&'static str),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, Error> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' | ')' | ',' | '=' => {
+                i += 1;
+                Token::Op(match c {
+                    '(' => "(",
+                    ')' => ")",
+                    ',' => ",",
+                    _ => "=",
+                })
+            }
+            '<' | '>' => {
+                i += 1;
+                let op = match (c, chars.get(i)) {
+                    ('<', Some('>')) => "<>",
+                    ('<', Some('=')) => "<=",
+                    ('>', Some('=')) => ">=",
+                    ('<', _) => "<",
+                    _ => ">",
+                };
+                i += op.len() - 1;
+                Token::Op(op)
+            }
+            '\'' | '"' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        Some(&q) if q == c && chars.get(i + 1) == Some(&c) => {
+                            value.push(c);
+                            i += 2;
+                        }
+                        Some(&q) if q == c => {
+                            i += 1;
+                            break;
+                        }
+                        Some(&other) => {
+                            value.push(other);
+                            i += 1;
+                        }
+                        None => return Err(Error::at("Unterminated quote", start)),
+                    }
+                }
+                if c == '\'' {
+                    Token::String(value)
+                } else {
+                    Token::Quoted(value)
+                }
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                i += 1;
+                while i < chars.len()
+                    && (chars[i].is_ascii_digit()
+                        || chars[i] == '.'
+                        || matches!(chars[i], 'e' | 'E')
+                        || (matches!(chars[i], '-' | '+') && matches!(chars[i - 1], 'e' | 'E')))
+                {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                Token::Number(
+                    parse_number(&text)
+                        .ok_or_else(|| Error::at(format!("Invalid number `{text}`"), start))?,
+                )
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '.' | ':'))
+                {
+                    i += 1;
+                }
+                Token::Ident(chars[start..i].iter().collect())
+            }
+            c => return Err(Error::at(format!("Unexpected character `{c}`"), start)),
+        };
+
+        tokens.push((token, start));
+    }
+
+    Ok(tokens)
+}
+
+fn parse_number(text: &str) -> Option<serde_json::Number> {
+    let text = text.strip_prefix('+').unwrap_or(text);
+    match text.parse::<i64>() {
+        Ok(i) => Some(i.into()),
+        Err(_) => text
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64),
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(t, _)| t.to_owned());
+        self.pos += 1;
+        token
+    }
+
+    fn error(&self, message: impl ToString) -> Error {
+        let position = self
+            .tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map(|(_, p)| *p)
+            .unwrap_or_default();
+        Error::at(message, position)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(i)) if i.eq_ignore_ascii_case(keyword))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if self.keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected `{keyword}`")))
+        }
+    }
+
+    fn is_op(&self, op: &str) -> bool {
+        matches!(self.peek(), Some(Token::Op(o)) if *o == op)
+    }
+
+    fn expect_op(&mut self, op: &str) -> Result<(), Error> {
+        if self.is_op(op) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected `{op}`")))
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.and()?;
+        while self.keyword("OR") {
+            expr = expr.or(self.and()?);
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.not()?;
+        while self.keyword("AND") {
+            expr = expr.and(self.not()?);
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, Error> {
+        if self.keyword("NOT") {
+            Ok(self.not()?.not())
+        } else {
+            self.predicate()
+        }
+    }
+
+    fn predicate(&mut self) -> Result<Expr, Error> {
+        if self.is_op("(") {
+            self.pos += 1;
+            let expr = self.expr()?;
+            self.expect_op(")")?;
+            return Ok(expr);
+        }
+
+        let lhs = self.scalar()?;
+
+        if let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if matches!(op, "=" | "<>" | "<" | "<=" | ">" | ">=") {
+                self.pos += 1;
+                let rhs = self.scalar()?;
+                return Ok(Expr::op(op, [lhs, rhs]));
+            }
+        }
+
+        let negated = self.keyword("NOT");
+
+        let expr = if self.keyword("LIKE") {
+            Expr::op("like", [lhs, self.scalar()?])
+        } else if self.keyword("BETWEEN") {
+            let lower = self.scalar()?;
+            self.expect_keyword("AND")?;
+            Expr::op("between", [lhs, lower, self.scalar()?])
+        } else if self.keyword("IN") {
+            Expr::op("in", [lhs, Expr::Array(self.list()?)])
+        } else if !negated && self.keyword("IS") {
+            let negated = self.keyword("NOT");
+            self.expect_keyword("NULL")?;
+            let expr = Expr::op("isNull", [lhs]);
+            return Ok(if negated { expr.not() } else { expr });
+        } else if negated {
+            return Err(self.error("Expected `LIKE`, `BETWEEN` or `IN` after `NOT`"));
+        } else {
+            return Ok(lhs);
+        };
+
+        Ok(if negated { expr.not() } else { expr })
+    }
+
+    /// Parenthesized, comma separated list of scalars
+    fn list(&mut self) -> Result<Vec<Expr>, Error> {
+        self.expect_op("(")?;
+        let mut items = Vec::new();
+        if !self.is_op(")") {
+            loop {
+                items.push(self.argument()?);
+                if !self.is_op(",") {
+                    break;
+                }
+                self.pos += 1;
+            }
+        }
+        self.expect_op(")")?;
+        Ok(items)
+    }
+
+    /// Function argument, which may also be an array or a nested predicate
+    fn argument(&mut self) -> Result<Expr, Error> {
+        if self.is_op("(") {
+            return Ok(Expr::Array(self.list()?));
+        }
+        self.expr()
+    }
+
+    fn scalar(&mut self) -> Result<Expr, Error> {
+        match self.next() {
+            Some(Token::String(s)) => Ok(Expr::String(s)),
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Quoted(q)) => Ok(Expr::Property { property: q }),
+            Some(Token::Ident(ident)) => self.identifier(ident),
+            Some(Token::Op("(")) => {
+                self.pos -= 1;
+                Ok(Expr::Array(self.list()?))
+            }
+            _ => {
+                self.pos -= 1;
+                Err(self.error("Expected a property, literal or function"))
+            }
+        }
+    }
+
+    fn identifier(&mut self, ident: String) -> Result<Expr, Error> {
+        let upper = ident.to_uppercase();
+
+        match upper.as_str() {
+            "TRUE" => return Ok(Expr::Bool(true)),
+            "FALSE" => return Ok(Expr::Bool(false)),
+            "NULL" => return Ok(Expr::Null),
+            _ => (),
+        }
+
+        if !self.is_op("(") && !is_geometry_tag(&upper) {
+            return Ok(Expr::Property { property: ident });
+        }
+
+        match upper.as_str() {
+            "TIMESTAMP" | "DATE" => {
+                self.expect_op("(")?;
+                let value = match self.next() {
+                    Some(Token::String(s)) => s,
+                    _ => return Err(self.error("Expected a quoted instant")),
+                };
+                self.expect_op(")")?;
+                Ok(if upper == "DATE" {
+                    Expr::Date { date: value }
+                } else {
+                    Expr::Timestamp { timestamp: value }
+                })
+            }
+            "INTERVAL" => Ok(Expr::Interval {
+                interval: self.list()?,
+            }),
+            "BBOX" => {
+                let bbox = self
+                    .list()?
+                    .into_iter()
+                    .map(|n| match n {
+                        Expr::Number(n) => n.as_f64().ok_or(()),
+                        _ => Err(()),
+                    })
+                    .collect::<Result<Vec<f64>, _>>()
+                    .map_err(|_| self.error("Expected numbers in `BBOX`"))?;
+                Ok(Expr::BBox { bbox })
+            }
+            tag if is_geometry_tag(tag) => {
+                self.pos -= 1;
+                Ok(Expr::Geometry(geojson::Geometry::new(self.geometry()?)))
+            }
+            _ => {
+                let op = if is_standard_function(&upper) {
+                    ident.to_lowercase()
+                } else {
+                    ident
+                };
+                Ok(Expr::Operation {
+                    op,
+                    args: self.list()?,
+                })
+            }
+        }
+    }
+
+    fn geometry(&mut self) -> Result<Value, Error> {
+        let tag = match self.next() {
+            Some(Token::Ident(tag)) => tag.to_uppercase(),
+            _ => return Err(self.error("Expected a geometry")),
+        };
+        // dimension is derived from the coordinates
+        self.keyword("Z");
+
+        let value = match tag.as_str() {
+            "POINT" => {
+                self.expect_op("(")?;
+                let position = self.position()?;
+                self.expect_op(")")?;
+                Value::Point(position)
+            }
+            "LINESTRING" => Value::LineString(self.positions()?),
+            "POLYGON" => Value::Polygon(self.rings()?),
+            "MULTIPOINT" => {
+                self.expect_op("(")?;
+                let mut points = Vec::new();
+                loop {
+                    // both `MULTIPOINT((1 2), (3 4))` and `MULTIPOINT(1 2, 3 4)` are valid
+                    if self.is_op("(") {
+                        self.pos += 1;
+                        points.push(self.position()?);
+                        self.expect_op(")")?;
+                    } else {
+                        points.push(self.position()?);
+                    }
+                    if !self.is_op(",") {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                self.expect_op(")")?;
+                Value::MultiPoint(points)
+            }
+            "MULTILINESTRING" => Value::MultiLineString(self.rings()?),
+            "MULTIPOLYGON" => {
+                self.expect_op("(")?;
+                let mut polygons = vec![self.rings()?];
+                while self.is_op(",") {
+                    self.pos += 1;
+                    polygons.push(self.rings()?);
+                }
+                self.expect_op(")")?;
+                Value::MultiPolygon(polygons)
+            }
+            "GEOMETRYCOLLECTION" => {
+                self.expect_op("(")?;
+                let mut geometries = vec![geojson::Geometry::new(self.geometry()?)];
+                while self.is_op(",") {
+                    self.pos += 1;
+                    geometries.push(geojson::Geometry::new(self.geometry()?));
+                }
+                self.expect_op(")")?;
+                Value::GeometryCollection(geometries)
+            }
+            tag => return Err(self.error(format!("Unsupported geometry type `{tag}`"))),
+        };
+
+        Ok(value)
+    }
+
+    fn position(&mut self) -> Result<Vec<f64>, Error> {
+        let mut position = Vec::new();
+        while let Some(Token::Number(n)) = self.peek() {
+            position.push(n.as_f64().unwrap_or_default());
+            self.pos += 1;
+        }
+        if position.len() < 2 {
+            return Err(self.error("Expected at least two coordinates"));
+        }
+        Ok(position)
+    }
+
+    fn positions(&mut self) -> Result<Vec<Vec<f64>>, Error> {
+        self.expect_op("(")?;
+        let mut positions = vec![self.position()?];
+        while self.is_op(",") {
+            self.pos += 1;
+            positions.push(self.position()?);
+        }
+        self.expect_op(")")?;
+        Ok(positions)
+    }
+
+    fn rings(&mut self) -> Result<Vec<Vec<Vec<f64>>>, Error> {
+        self.expect_op("(")?;
+        let mut rings = vec![self.positions()?];
+        while self.is_op(",") {
+            self.pos += 1;
+            rings.push(self.positions()?);
+        }
+        self.expect_op(")")?;
+        Ok(rings)
+    }
+}
+
+fn is_geometry_tag(tag: &str) -> bool {
+    matches!(
+        tag,
+        "POINT"
+            | "LINESTRING"
+            | "POLYGON"
+            | "MULTIPOINT"
+            | "MULTILINESTRING"
+            | "MULTIPOLYGON"
+            | "GEOMETRYCOLLECTION"
+    )
+}
+
+fn is_standard_function(name: &str) -> bool {
+    name.starts_with("S_")
+        || name.starts_with("T_")
+        || name.starts_with("A_")
+        || name == "CASEI"
+        || name == "ACCENTI"
+}
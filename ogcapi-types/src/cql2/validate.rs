@@ -0,0 +1,119 @@
+//! Validation hooks for `CQL2` expressions
+
+use serde_json::Value;
+
+use crate::features::Queryables;
+
+use super::{Error, Expr};
+
+/// Hook to check the properties and operations used in an expression,
+/// e.g. against the queryables of a collection.
+pub trait Validator {
+    /// Check that the property may be used in a filter
+    fn property(&self, name: &str) -> Result<(), String> {
+        let _ = name;
+        Ok(())
+    }
+
+    /// Check an operation or function call and its (already validated) arguments
+    fn operation(&self, op: &str, args: &[Expr]) -> Result<(), String> {
+        let _ = (op, args);
+        Ok(())
+    }
+}
+
+impl Expr {
+    /// Validate the expression, checking the arity of standard operations
+    /// and passing every property and operation to the validator.
+    pub fn validate(&self, validator: &impl Validator) -> Result<(), Error> {
+        match self {
+            Expr::Operation { op, args } => {
+                for arg in args {
+                    arg.validate(validator)?;
+                }
+
+                let arity = match op.as_str() {
+                    "=" | "<>" | "<" | "<=" | ">" | ">=" | "like" | "in" => Some(2),
+                    "between" => Some(3),
+                    "not" | "isNull" | "casei" | "accenti" => Some(1),
+                    op if op.starts_with("s_") || op.starts_with("t_") || op.starts_with("a_") => {
+                        Some(2)
+                    }
+                    _ => None,
+                };
+                if let Some(arity) = arity.filter(|arity| *arity != args.len()) {
+                    return Err(Error::new(format!(
+                        "Operation `{op}` expects {arity} arguments, found {}",
+                        args.len()
+                    )));
+                }
+
+                validator.operation(op, args).map_err(Error::new)
+            }
+            Expr::Property { property } => validator.property(property).map_err(Error::new),
+            Expr::Interval { interval: items } | Expr::Array(items) => {
+                for item in items {
+                    item.validate(validator)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Checks that properties are declared as queryable and that literals
+/// compared to them match their declared `type`.
+impl Validator for Queryables {
+    fn property(&self, name: &str) -> Result<(), String> {
+        if self.additional_properties || self.properties.contains_key(name) {
+            Ok(())
+        } else {
+            Err(format!("Property `{name}` is not queryable"))
+        }
+    }
+
+    fn operation(&self, op: &str, args: &[Expr]) -> Result<(), String> {
+        let Some(Expr::Property { property }) = args.first() else {
+            return Ok(());
+        };
+        let Some(schema) = self.properties.get(property) else {
+            return Ok(());
+        };
+
+        let spatial = op.starts_with("s_");
+        let geometry = schema
+            .get("format")
+            .and_then(Value::as_str)
+            .is_some_and(|f| f.starts_with("geometry"));
+        if spatial && !geometry {
+            return Err(format!("Property `{property}` is not a geometry"));
+        } else if !spatial && geometry && op != "isNull" {
+            return Err(format!("Geometry property `{property}` used with `{op}`"));
+        }
+
+        let Some(r#type) = schema.get("type").and_then(Value::as_str) else {
+            return Ok(());
+        };
+
+        let literals = args[1..].iter().flat_map(|arg| match arg {
+            Expr::Array(items) => items.iter().collect(),
+            arg => vec![arg],
+        });
+        for literal in literals {
+            let matches = match literal {
+                Expr::String(_) => r#type == "string",
+                Expr::Number(n) => r#type == "number" || (r#type == "integer" && !n.is_f64()),
+                Expr::Bool(_) => r#type == "boolean",
+                _ => true,
+            };
+            if !matches {
+                return Err(format!(
+                    "Property `{property}` of type `{type}` compared to `{literal}`"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ use super::QueryType;
 
 /// Detailed information relevant to individual query types
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DataQueries {
     pub position: Option<PositionLink>,
     // pub radius: Option<RadiusLink>,
@@ -18,6 +19,7 @@ pub struct DataQueries {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PositionLink {
     #[serde(flatten)]
     pub link: Link,
@@ -27,6 +29,7 @@ pub struct PositionLink {
 /// Property to contain any extra metadata information that is specific
 /// to an individual data queries
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PositionDataQuery {
     pub title: Option<String>,
     pub description: Option<String>,
@@ -37,6 +40,7 @@ pub struct PositionDataQuery {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CrsObject {
     /// name of the coordinate reference system, used as the value in the crs
     /// query parameter to define the required output CRS
@@ -13,6 +13,11 @@ pub use units::Units;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "EdrProvider")
+)]
 pub struct Provider {
     /// Name of organization providing the service
     name: Option<String>,
@@ -21,6 +26,7 @@ pub struct Provider {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Contact {
     /// Email address of service provider
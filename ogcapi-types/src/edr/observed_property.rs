@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 /// Description of the property
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 
 pub struct ObservedPropertyCollection {
@@ -16,6 +17,7 @@ pub struct ObservedPropertyCollection {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Category {
     /// URI linking to an external registry which contains the definitive
     /// definition of the observed property
@@ -25,6 +27,11 @@ pub struct Category {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "ObservedPropertyLabel")
+)]
 pub enum Label {
     String(String),
     Object {
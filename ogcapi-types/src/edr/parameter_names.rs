@@ -6,6 +6,7 @@ use crate::common::Extent;
 use super::{ObservedPropertyCollection, Units};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ParameterNames {
     #[serde(default)]
@@ -24,12 +25,22 @@ pub struct ParameterNames {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "ParameterType")
+)]
 pub enum Type {
     #[default]
     Parameter,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "ParameterDataType")
+)]
 #[serde(rename_all = "lowercase")]
 pub enum DataType {
     Integer,
@@ -38,6 +49,7 @@ pub enum DataType {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MeasurementType {
     /// Approach to calculating the data values
     pub method: String,
@@ -4,6 +4,7 @@ use serde_with::DisplayFromStr;
 use crate::common::{Crs, Datetime};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum QueryType {
     Position,
@@ -19,7 +20,12 @@ pub enum QueryType {
 
 #[serde_with::serde_as]
 #[derive(Serialize, Deserialize, Default, Debug)]
-#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "EdrQuery")
+)]
+#[serde(rename_all = "kebab-case")]
 pub struct Query {
     /// Well Known Text (WKT) of representation geometry. The representation
     /// type will depend on the [QueryType] of the API.
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 /// Definition of data units
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Units {
     pub id: Option<String>,
     pub label: Option<Label>,
@@ -12,6 +13,11 @@ pub struct Units {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "UnitLabel")
+)]
 #[serde(untagged)]
 pub enum Label {
     String(String),
@@ -20,6 +26,7 @@ pub enum Label {
 
 /// Describe unit symbol
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum Symbol {
     String(String),
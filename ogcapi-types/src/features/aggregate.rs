@@ -0,0 +1,203 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{formats::CommaSeparator, DisplayFromStr, StringWithSeparator};
+
+use crate::common::Bbox;
+
+/// Aggregation of the features of a collection into spatial groups, e.g.
+/// for dashboards over dense point data
+#[serde_with::serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct AggregateQuery {
+    /// Groups of the features, e.g. `h3:7`, `hexagon:1000` or
+    /// `collection:cantons`
+    #[serde_as(as = "DisplayFromStr")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub by: Grouping,
+    /// Comma separated aggregates per group, e.g. `count,avg(pm10)`,
+    /// defaults to `count`
+    #[serde(default)]
+    #[serde_as(as = "StringWithSeparator::<CommaSeparator, Aggregate>")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub agg: Vec<Aggregate>,
+    /// Features intersecting the bounding box in `CRS84`
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub bbox: Option<Bbox>,
+    /// Output format, `json` or `csv`
+    pub f: Option<String>,
+}
+
+impl AggregateQuery {
+    /// Requested aggregates, the count if none
+    pub fn aggregates(&self) -> Vec<Aggregate> {
+        if self.agg.is_empty() {
+            vec![Aggregate::Count]
+        } else {
+            self.agg.to_owned()
+        }
+    }
+}
+
+/// Spatial groups of features, by the position of their centroid
+#[derive(Debug, Clone, PartialEq)]
+pub enum Grouping {
+    /// Cells of the H3 index at a resolution from 0 to 15
+    H3(u8),
+    /// Hexagons of a size in meters of `WebMercator`
+    Hexagon(f64),
+    /// Squares of a size in meters of `WebMercator`
+    Square(f64),
+    /// Polygons of another collection, e.g. administrative units
+    Collection(String),
+}
+
+impl FromStr for Grouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Expected grouping as `kind:value`, got `{s}`"))?;
+
+        let size = || match value.parse::<f64>() {
+            Ok(size) if size > 0.0 => Ok(size),
+            _ => Err(format!("Invalid cell size `{value}`")),
+        };
+
+        match kind {
+            "h3" => match value.parse::<u8>() {
+                Ok(resolution) if resolution <= 15 => Ok(Grouping::H3(resolution)),
+                _ => Err(format!("Invalid H3 resolution `{value}`, expected 0 to 15")),
+            },
+            "hexagon" => size().map(Grouping::Hexagon),
+            "square" => size().map(Grouping::Square),
+            "collection" if !value.is_empty() => Ok(Grouping::Collection(value.to_owned())),
+            _ => Err(format!(
+                "Unknown grouping `{s}`, expected `h3`, `hexagon`, `square` or `collection`"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Grouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Grouping::H3(resolution) => write!(f, "h3:{resolution}"),
+            Grouping::Hexagon(size) => write!(f, "hexagon:{size}"),
+            Grouping::Square(size) => write!(f, "square:{size}"),
+            Grouping::Collection(id) => write!(f, "collection:{id}"),
+        }
+    }
+}
+
+/// Aggregate of the features of a group, of a numeric property except for
+/// the count
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Aggregate {
+    Count,
+    Avg(String),
+    Sum(String),
+    Min(String),
+    Max(String),
+}
+
+impl Aggregate {
+    /// Aggregated property, `None` for the count
+    pub fn property(&self) -> Option<&str> {
+        match self {
+            Aggregate::Count => None,
+            Aggregate::Avg(p) | Aggregate::Sum(p) | Aggregate::Min(p) | Aggregate::Max(p) => {
+                Some(p)
+            }
+        }
+    }
+
+    /// Name of the aggregate function
+    pub fn function(&self) -> &'static str {
+        match self {
+            Aggregate::Count => "count",
+            Aggregate::Avg(_) => "avg",
+            Aggregate::Sum(_) => "sum",
+            Aggregate::Min(_) => "min",
+            Aggregate::Max(_) => "max",
+        }
+    }
+}
+
+impl FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "count" {
+            return Ok(Aggregate::Count);
+        }
+
+        let (function, property) = s
+            .strip_suffix(')')
+            .and_then(|s| s.split_once('('))
+            .filter(|(_, property)| !property.is_empty())
+            .ok_or_else(|| format!("Invalid aggregate `{s}`, expected e.g. `avg(property)`"))?;
+        let property = property.to_owned();
+
+        match function {
+            "avg" => Ok(Aggregate::Avg(property)),
+            "sum" => Ok(Aggregate::Sum(property)),
+            "min" => Ok(Aggregate::Min(property)),
+            "max" => Ok(Aggregate::Max(property)),
+            _ => Err(format!(
+                "Unknown aggregate `{function}`, expected `count`, `avg`, `sum`, `min` or `max`"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.property() {
+            Some(property) => write!(f, "{}({property})", self.function()),
+            None => f.write_str(self.function()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn query() {
+        let query: AggregateQuery = serde_json::from_value(json!({
+            "by": "h3:7",
+            "agg": "count,avg(pm10),max(pm10)"
+        }))
+        .unwrap();
+        assert_eq!(query.by, Grouping::H3(7));
+        assert_eq!(
+            query.aggregates(),
+            [
+                Aggregate::Count,
+                Aggregate::Avg("pm10".to_string()),
+                Aggregate::Max("pm10".to_string())
+            ]
+        );
+        assert_eq!(query.agg[1].to_string(), "avg(pm10)");
+
+        let query: AggregateQuery =
+            serde_json::from_value(json!({ "by": "collection:cantons" })).unwrap();
+        assert_eq!(query.by, Grouping::Collection("cantons".to_string()));
+        assert_eq!(query.aggregates(), [Aggregate::Count]);
+
+        assert_eq!("square:500".parse(), Ok(Grouping::Square(500.0)));
+        assert!("h3:16".parse::<Grouping>().is_err());
+        assert!("hexagon:-1".parse::<Grouping>().is_err());
+        assert!("median(pm10)".parse::<Aggregate>().is_err());
+        assert!("avg()".parse::<Aggregate>().is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::Links;
+
+/// File attached to a feature, e.g. a photo or a document
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    #[serde(default)]
+    pub id: String,
+    /// File name of the attachment
+    pub title: Option<String>,
+    /// Media type of the content
+    pub r#type: String,
+    /// Length of the content in bytes
+    pub length: i64,
+    pub created: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub links: Links,
+}
+
+/// Attachments of a feature
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Attachments {
+    pub attachments: Vec<Attachment>,
+    #[serde(default)]
+    pub links: Links,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Attachment;
+
+    #[test]
+    fn attachment() {
+        let attachment: Attachment = serde_json::from_str(
+            r#"{"id": "1", "title": "front.jpg", "type": "image/jpeg", "length": 2048}"#,
+        )
+        .unwrap();
+
+        assert_eq!(attachment.title.as_deref(), Some("front.jpg"));
+        assert!(attachment.links.is_empty());
+
+        let value = serde_json::to_value(&attachment).unwrap();
+        assert!(value.get("created").is_none());
+        assert_eq!(value["type"], "image/jpeg");
+    }
+}
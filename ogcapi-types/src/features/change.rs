@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::Links;
+
+use super::Feature;
+
+/// Notification about a created, updated or deleted feature
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureChange {
+    pub kind: ChangeKind,
+    pub collection: String,
+    pub id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Create,
+    Update,
+    Delete,
+}
+
+/// Page of the ordered change log of a collection, for replication
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Changeset {
+    pub changes: Vec<LoggedChange>,
+    /// Position after the listed changes, to list the following ones `since`
+    pub token: String,
+    #[serde(default)]
+    pub links: Links,
+}
+
+/// Change of the log with the current state of the feature, missing once
+/// the feature is deleted
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LoggedChange {
+    #[serde(flatten)]
+    pub change: FeatureChange,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feature: Option<Feature>,
+}
+
+/// Logged change with the time it was made, for feeds of the latest changes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentChange {
+    #[serde(flatten)]
+    pub change: FeatureChange,
+    /// Start of the transaction of the change, shared by all its changes
+    pub changed: DateTime<Utc>,
+    /// Position `{transaction}-{sequence}` of the change in the log
+    pub position: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangeKind, Changeset, FeatureChange};
+
+    #[test]
+    fn from_notification() {
+        let change: FeatureChange =
+            serde_json::from_str(r#"{"kind":"delete","collection":"roads","id":"42"}"#).unwrap();
+
+        assert_eq!(
+            change,
+            FeatureChange {
+                kind: ChangeKind::Delete,
+                collection: "roads".to_string(),
+                id: "42".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn changeset() {
+        let changeset: Changeset = serde_json::from_str(
+            r#"{
+                "changes": [
+                    {"kind": "create", "collection": "roads", "id": "1", "feature": {
+                        "type": "Feature",
+                        "id": "1",
+                        "properties": {"name": "Main Street"},
+                        "geometry": {"type": "Point", "coordinates": [7.4, 46.9]}
+                    }},
+                    {"kind": "delete", "collection": "roads", "id": "2"}
+                ],
+                "token": "7402-12"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(changeset.token, "7402-12");
+        assert_eq!(changeset.changes[0].change.kind, ChangeKind::Create);
+        assert_eq!(
+            changeset.changes[0]
+                .feature
+                .as_ref()
+                .and_then(|f| f.id.as_deref()),
+            Some("1")
+        );
+        assert_eq!(changeset.changes[1].change.id, "2");
+        assert!(changeset.changes[1].feature.is_none());
+
+        let value = serde_json::to_value(&changeset.changes[1]).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"kind": "delete", "collection": "roads", "id": "2"})
+        );
+    }
+}
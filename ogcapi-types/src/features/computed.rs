@@ -0,0 +1,495 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Property computed from the stored ones when features are read, e.g.
+/// `area(geom) / 1e6` for the area in square kilometers
+///
+/// Expressions consist of numbers, `'text'`, properties by name (quoted
+/// with `"` if needed), the geometry `geom`, the operators `+`, `-`, `*`, `/`
+/// and the functions listed in [`Function`].
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ComputedProperty {
+    pub name: String,
+    pub expression: String,
+    pub description: Option<String>,
+}
+
+/// Parsed expression of a computed property
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expression {
+    Number(f64),
+    Text(String),
+    Property(String),
+    Geometry,
+    Negate(Box<Expression>),
+    Binary {
+        op: char,
+        lhs: Box<Expression>,
+        rhs: Box<Expression>,
+    },
+    Function {
+        function: Function,
+        args: Vec<Expression>,
+    },
+}
+
+/// Type of the value of an expression, properties may be of any type
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Kind {
+    Number,
+    Text,
+    Geometry,
+}
+
+/// Functions of computed properties, the names are case insensitive and the
+/// geometric ones may be prefixed with `ST_`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Function {
+    /// Area of the geometry in square meters
+    Area,
+    /// Length of linear geometries in meters
+    Length,
+    /// Perimeter of polygonal geometries in meters
+    Perimeter,
+    /// Number of vertices of the geometry
+    NumPoints,
+    /// Number rounded to the given number of decimal places, `0` by default
+    Round,
+    Abs,
+    Floor,
+    Ceil,
+    /// Square root, `null` for negative numbers
+    Sqrt,
+    Lower,
+    Upper,
+    /// First argument which isn't `null`
+    Coalesce,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        let function = match name.strip_prefix("st_").unwrap_or(&name) {
+            "area" => Function::Area,
+            "length" => Function::Length,
+            "perimeter" => Function::Perimeter,
+            "npoints" => Function::NumPoints,
+            _ => match name.as_str() {
+                "round" => Function::Round,
+                "abs" => Function::Abs,
+                "floor" => Function::Floor,
+                "ceil" => Function::Ceil,
+                "sqrt" => Function::Sqrt,
+                "lower" => Function::Lower,
+                "upper" => Function::Upper,
+                "coalesce" => Function::Coalesce,
+                _ => return None,
+            },
+        };
+        Some(function)
+    }
+
+    /// Check the number and kinds of the arguments
+    fn check(&self, args: &[Expression]) -> Result<(), String> {
+        let kinds: Vec<Option<Kind>> = args.iter().map(Expression::kind).collect();
+        let expected = match self {
+            Function::Area | Function::Length | Function::Perimeter | Function::NumPoints => (kinds
+                == [Some(Kind::Geometry)])
+            .then_some(())
+            .ok_or("the geometry"),
+            Function::Round => {
+                let places = match args.get(1) {
+                    None => true,
+                    Some(Expression::Number(places)) => places.fract() == 0.0,
+                    Some(_) => false,
+                };
+                (matches!(args.len(), 1 | 2) && is_number(kinds[0]) && places)
+                    .then_some(())
+                    .ok_or("a number and a whole number of decimal places")
+            }
+            Function::Abs | Function::Floor | Function::Ceil | Function::Sqrt => (args.len() == 1
+                && is_number(kinds[0]))
+            .then_some(())
+            .ok_or("a number"),
+            Function::Lower | Function::Upper => (args.len() == 1 && is_text(kinds[0]))
+                .then_some(())
+                .ok_or("a text"),
+            Function::Coalesce => {
+                let known: Vec<Kind> = kinds.iter().flatten().copied().collect();
+                (!args.is_empty()
+                    && !known.contains(&Kind::Geometry)
+                    && known.windows(2).all(|w| w[0] == w[1]))
+                .then_some(())
+                .ok_or("values of the same type")
+            }
+        };
+
+        expected.map_err(|expected| format!("Function `{self}` expects {expected}"))
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Function::Area => "area",
+            Function::Length => "length",
+            Function::Perimeter => "perimeter",
+            Function::NumPoints => "npoints",
+            Function::Round => "round",
+            Function::Abs => "abs",
+            Function::Floor => "floor",
+            Function::Ceil => "ceil",
+            Function::Sqrt => "sqrt",
+            Function::Lower => "lower",
+            Function::Upper => "upper",
+            Function::Coalesce => "coalesce",
+        };
+        f.write_str(name)
+    }
+}
+
+fn is_number(kind: Option<Kind>) -> bool {
+    kind.is_none() || kind == Some(Kind::Number)
+}
+
+fn is_text(kind: Option<Kind>) -> bool {
+    kind.is_none() || kind == Some(Kind::Text)
+}
+
+impl Expression {
+    /// Kind of the value, `None` if it depends on the properties
+    pub fn kind(&self) -> Option<Kind> {
+        match self {
+            Expression::Number(_) | Expression::Negate(_) | Expression::Binary { .. } => {
+                Some(Kind::Number)
+            }
+            Expression::Text(_) => Some(Kind::Text),
+            Expression::Property(_) => None,
+            Expression::Geometry => Some(Kind::Geometry),
+            Expression::Function { function, args } => match function {
+                Function::Lower | Function::Upper => Some(Kind::Text),
+                Function::Coalesce => args.iter().find_map(Expression::kind),
+                _ => Some(Kind::Number),
+            },
+        }
+    }
+
+    /// Names of the properties the value is computed from
+    pub fn properties(&self) -> Vec<&str> {
+        match self {
+            Expression::Property(name) => vec![name.as_str()],
+            Expression::Negate(operand) => operand.properties(),
+            Expression::Binary { lhs, rhs, .. } => {
+                let mut properties = lhs.properties();
+                properties.extend(rhs.properties());
+                properties
+            }
+            Expression::Function { args, .. } => {
+                args.iter().flat_map(Expression::properties).collect()
+            }
+            Expression::Number(_) | Expression::Text(_) | Expression::Geometry => Vec::new(),
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+        };
+        let expression = parser.sum()?;
+        if let Some(token) = parser.tokens.get(parser.position) {
+            return Err(format!("Unexpected `{token}`"));
+        }
+        if expression.kind() == Some(Kind::Geometry) {
+            return Err("The geometry can only be an argument of a function".to_string());
+        }
+        Ok(expression)
+    }
+}
+
+impl ComputedProperty {
+    /// Parse the expression
+    pub fn parse(&self) -> Result<Expression, String> {
+        self.expression
+            .parse()
+            .map_err(|e| format!("Computed property `{}`: {e}", self.name))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Identifier(String),
+    Quoted(String),
+    Op(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{n}"),
+            Token::Text(t) => write!(f, "'{t}'"),
+            Token::Identifier(i) => write!(f, "{i}"),
+            Token::Quoted(q) => write!(f, "\"{q}\""),
+            Token::Op(op) => write!(f, "{op}"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '+' | '-' | '*' | '/' | '(' | ')' | ',' => {
+                tokens.push(Token::Op(c));
+                i += 1;
+            }
+            '\'' | '"' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match (chars.get(i), chars.get(i + 1)) {
+                        (Some(&q), Some(&next)) if q == c && next == c => {
+                            text.push(c);
+                            i += 2;
+                        }
+                        (Some(&q), _) if q == c => break,
+                        (Some(&other), _) => {
+                            text.push(other);
+                            i += 1;
+                        }
+                        (None, _) => return Err(format!("Unterminated `{c}`")),
+                    }
+                }
+                i += 1;
+                tokens.push(if c == '\'' {
+                    Token::Text(text)
+                } else {
+                    Token::Quoted(text)
+                });
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_digit()
+                        || chars[i] == '.'
+                        || matches!(chars[i], 'e' | 'E')
+                        || (matches!(chars[i], '+' | '-') && matches!(chars[i - 1], 'e' | 'E')))
+                {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                match text.parse::<f64>() {
+                    Ok(n) if n.is_finite() => tokens.push(Token::Number(n)),
+                    _ => return Err(format!("Invalid number `{text}`")),
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Identifier(chars[start..i].iter().collect()));
+            }
+            c => return Err(format!("Unexpected `{c}`")),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn op(&mut self, ops: &[char]) -> Option<char> {
+        match self.tokens.get(self.position) {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                self.position += 1;
+                Some(*op)
+            }
+            _ => None,
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expression, String> {
+        let mut lhs = self.product()?;
+        while let Some(op) = self.op(&['+', '-']) {
+            lhs = binary(op, lhs, self.product()?)?;
+        }
+        Ok(lhs)
+    }
+
+    fn product(&mut self) -> Result<Expression, String> {
+        let mut lhs = self.factor()?;
+        while let Some(op) = self.op(&['*', '/']) {
+            lhs = binary(op, lhs, self.factor()?)?;
+        }
+        Ok(lhs)
+    }
+
+    fn factor(&mut self) -> Result<Expression, String> {
+        if self.op(&['-']).is_some() {
+            let operand = self.factor()?;
+            if !is_number(operand.kind()) {
+                return Err("Only numbers can be negated".to_string());
+            }
+            return Ok(Expression::Negate(Box::new(operand)));
+        }
+        if self.op(&['(']).is_some() {
+            let expression = self.sum()?;
+            return match self.op(&[')']) {
+                Some(_) => Ok(expression),
+                None => Err("Expected `)`".to_string()),
+            };
+        }
+
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expression::Number(n)),
+            Some(Token::Text(t)) => Ok(Expression::Text(t)),
+            Some(Token::Quoted(name)) => Ok(Expression::Property(name)),
+            Some(Token::Identifier(name)) if self.op(&['(']).is_some() => {
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| format!("Unknown function `{name}`"))?;
+                let mut args = Vec::new();
+                if self.op(&[')']).is_none() {
+                    loop {
+                        args.push(self.sum()?);
+                        match self.op(&[',', ')']) {
+                            Some(',') => continue,
+                            Some(_) => break,
+                            None => return Err("Expected `,` or `)`".to_string()),
+                        }
+                    }
+                }
+                function.check(&args)?;
+                Ok(Expression::Function { function, args })
+            }
+            Some(Token::Identifier(name)) if ["geom", "geometry"].contains(&name.as_str()) => {
+                Ok(Expression::Geometry)
+            }
+            Some(Token::Identifier(name)) => Ok(Expression::Property(name)),
+            Some(token) => Err(format!("Unexpected `{token}`")),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+fn binary(op: char, lhs: Expression, rhs: Expression) -> Result<Expression, String> {
+    if !is_number(lhs.kind()) || !is_number(rhs.kind()) {
+        return Err(format!("Operator `{op}` expects numbers"));
+    }
+    Ok(Expression::Binary {
+        op,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let expression: Expression = "ST_Area(geom) / 1e6".parse().unwrap();
+        assert_eq!(
+            expression,
+            Expression::Binary {
+                op: '/',
+                lhs: Box::new(Expression::Function {
+                    function: Function::Area,
+                    args: vec![Expression::Geometry]
+                }),
+                rhs: Box::new(Expression::Number(1e6)),
+            }
+        );
+
+        let expression: Expression = r#"round(-"floor height" * (floors + 1), 1)"#.parse().unwrap();
+        let Expression::Function { function, args } = expression else {
+            panic!("expected a function");
+        };
+        assert_eq!(function, Function::Round);
+        assert_eq!(
+            args[0],
+            Expression::Binary {
+                op: '*',
+                lhs: Box::new(Expression::Negate(Box::new(Expression::Property(
+                    "floor height".to_string()
+                )))),
+                rhs: Box::new(Expression::Binary {
+                    op: '+',
+                    lhs: Box::new(Expression::Property("floors".to_string())),
+                    rhs: Box::new(Expression::Number(1.0)),
+                }),
+            }
+        );
+
+        let expression: Expression = "upper(coalesce(name, 'unnamed'))".parse().unwrap();
+        assert_eq!(expression.kind(), Some(Kind::Text));
+        assert_eq!(
+            "owner".parse(),
+            Ok(Expression::Property("owner".to_string()))
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for (expression, error) in [
+            ("area(height)", "Function `area` expects the geometry"),
+            ("geom", "The geometry can only be an argument of a function"),
+            ("'a' + 1", "Operator `+` expects numbers"),
+            ("lower(length(geom))", "Function `lower` expects a text"),
+            (
+                "round(height, 0.5)",
+                "Function `round` expects a number and a whole number of decimal places",
+            ),
+            (
+                "coalesce('none', 0)",
+                "Function `coalesce` expects values of the same type",
+            ),
+            ("pg_sleep(10)", "Unknown function `pg_sleep`"),
+            ("height; DROP TABLE", "Unexpected `;`"),
+            ("(height", "Expected `)`"),
+            ("name = 'x", "Unexpected `=`"),
+        ] {
+            assert_eq!(
+                expression.parse::<Expression>(),
+                Err(error.to_string()),
+                "{expression}"
+            );
+        }
+
+        let property = ComputedProperty {
+            name: "area_km2".to_string(),
+            expression: "area(geom) /".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            property.parse(),
+            Err("Computed property `area_km2`: Unexpected end of expression".to_string())
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Changes applied by a harvest or import of a complete source
+///
+/// Features are compared by the checksums of their content with the previous
+/// harvest or import, only new and changed features are written, and
+/// previously harvested features missing from the source are deleted.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaSummary {
+    pub inserted: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub unchanged: usize,
+}
+
+impl DeltaSummary {
+    /// Whether the collection was changed
+    pub fn is_empty(&self) -> bool {
+        self.inserted + self.updated + self.deleted == 0
+    }
+}
@@ -9,7 +9,16 @@ use serde_json::{Map, Value};
 
 use crate::common::Links;
 
+/// Property with the version of the features of versioned collections, see
+/// [`crate::common::Collection::versioned`]
+pub const VERSION_PROPERTY: &str = "version";
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "FeatureType")
+)]
 pub enum Type {
     #[default]
     Feature,
@@ -18,6 +27,7 @@ pub enum Type {
 /// Abstraction of real world phenomena (ISO 19101-1:2014)
 #[serde_with::skip_serializing_none]
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Feature {
     pub id: Option<String>,
     pub collection: Option<String>,
@@ -25,6 +35,7 @@ pub struct Feature {
     pub r#type: Type,
     #[serialize_always]
     pub properties: Option<Map<String, Value>>,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::Geometry"))]
     pub geometry: Geometry,
     #[serde(default)]
     pub links: Links,
@@ -53,4 +64,170 @@ impl Feature {
             self.properties = Some(other);
         }
     }
+
+    /// Remove properties, e.g. the ones hidden from the requesting user
+    pub fn remove_properties(&mut self, names: &[String]) {
+        if let Some(properties) = self.properties.as_mut() {
+            for name in names {
+                properties.remove(name);
+            }
+        }
+    }
+
+    /// Version of the feature, `None` if it has none
+    pub fn version(&self) -> Option<u64> {
+        self.properties
+            .as_ref()
+            .and_then(|properties| properties.get(VERSION_PROPERTY))
+            .and_then(Value::as_u64)
+    }
+
+    /// Set the version of the feature, replacing the one it had
+    pub fn set_version(&mut self, version: u64) {
+        self.properties
+            .get_or_insert_with(Map::new)
+            .insert(VERSION_PROPERTY.to_string(), version.into());
+    }
+
+    /// Round the coordinates of the geometry to a number of decimals
+    pub fn round_coordinates(&mut self, decimals: u32) {
+        // beyond the precision of `f64`
+        if decimals > 15 {
+            return;
+        }
+
+        let factor = 10_f64.powi(decimals as i32);
+        round_value(&mut self.geometry.value, factor);
+
+        if let Some(bbox) = self.geometry.bbox.as_mut() {
+            bbox.iter_mut().for_each(|n| *n = round(*n, factor));
+        }
+    }
+
+    /// Swap the first two axes of the coordinates, between the `x`/`y` order
+    /// of `GeoJSON` and the axis order of crs with northing first, see
+    /// [`crate::common::Crs::is_northing_first`]
+    pub fn swap_axes(&mut self) {
+        for_each_position(&mut self.geometry.value, &mut |position| {
+            if position.len() >= 2 {
+                position.swap(0, 1);
+            }
+        });
+
+        if let Some(bbox) = self.geometry.bbox.as_mut() {
+            let dimensions = bbox.len() / 2;
+            if dimensions >= 2 {
+                bbox.swap(0, 1);
+                bbox.swap(dimensions, dimensions + 1);
+            }
+        }
+    }
+}
+
+fn round_value(value: &mut geojson::Value, factor: f64) {
+    for_each_position(value, &mut |position| {
+        position.iter_mut().for_each(|n| *n = round(*n, factor));
+    });
+}
+
+fn for_each_position(value: &mut geojson::Value, f: &mut impl FnMut(&mut Vec<f64>)) {
+    match value {
+        geojson::Value::Point(p) => f(p),
+        geojson::Value::MultiPoint(ps) | geojson::Value::LineString(ps) => {
+            ps.iter_mut().for_each(f)
+        }
+        geojson::Value::MultiLineString(ls) | geojson::Value::Polygon(ls) => {
+            ls.iter_mut().flatten().for_each(f)
+        }
+        geojson::Value::MultiPolygon(ps) => ps.iter_mut().flatten().flatten().for_each(f),
+        geojson::Value::GeometryCollection(gs) => gs
+            .iter_mut()
+            .for_each(|g| for_each_position(&mut g.value, f)),
+    }
+}
+
+fn round(n: f64, factor: f64) -> f64 {
+    (n * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_coordinates() {
+        let mut feature: Feature = serde_json::from_str(
+            r#"{
+                "type": "Feature",
+                "properties": null,
+                "geometry": {
+                    "type": "LineString",
+                    "coordinates": [[7.4474468, 46.9479739, 542.123], [7.4521351, 46.9465824]]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        feature.round_coordinates(3);
+
+        assert_eq!(
+            feature.geometry.value,
+            geojson::Value::LineString(vec![vec![7.447, 46.948, 542.123], vec![7.452, 46.947]])
+        );
+    }
+
+    #[test]
+    fn version() {
+        let mut feature: Feature = serde_json::from_str(
+            r#"{
+                "type": "Feature",
+                "properties": null,
+                "geometry": { "type": "Point", "coordinates": [7.4, 46.9] }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(feature.version(), None);
+
+        feature.set_version(1);
+        assert_eq!(feature.version(), Some(1));
+        feature.set_version(2);
+        assert_eq!(feature.properties.as_ref().unwrap()[VERSION_PROPERTY], 2);
+    }
+
+    #[test]
+    fn swap_axes() {
+        let mut feature: Feature = serde_json::from_str(
+            r#"{
+                "type": "Feature",
+                "properties": null,
+                "geometry": {
+                    "type": "GeometryCollection",
+                    "bbox": [7.4, 46.9, 542.0, 7.5, 47.0, 550.0],
+                    "geometries": [
+                        { "type": "Point", "coordinates": [7.4474468, 46.9479739, 542.123] },
+                        { "type": "LineString", "coordinates": [[7.4, 46.9], [7.5, 47.0]] }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        feature.swap_axes();
+
+        let geojson::Value::GeometryCollection(geometries) = &feature.geometry.value else {
+            panic!("geometry collection expected");
+        };
+        assert_eq!(
+            geometries[0].value,
+            geojson::Value::Point(vec![46.9479739, 7.4474468, 542.123])
+        );
+        assert_eq!(
+            geometries[1].value,
+            geojson::Value::LineString(vec![vec![46.9, 7.4], vec![47.0, 7.5]])
+        );
+        assert_eq!(
+            feature.geometry.bbox,
+            Some(vec![46.9, 7.4, 542.0, 47.0, 7.5, 550.0])
+        );
+    }
 }
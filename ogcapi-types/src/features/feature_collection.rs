@@ -6,6 +6,11 @@ use crate::common::Links;
 use super::Feature;
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "FeatureCollectionType")
+)]
 pub enum Type {
     #[default]
     FeatureCollection,
@@ -13,7 +18,8 @@ pub enum Type {
 
 /// A set of Features from a dataset
 #[serde_with::skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct FeatureCollection {
     #[serde(default)]
@@ -36,4 +42,25 @@ impl FeatureCollection {
             ..Default::default()
         }
     }
+
+    /// Round the coordinates of all features to a number of decimals
+    pub fn round_coordinates(&mut self, decimals: u32) {
+        for feature in self.features.iter_mut() {
+            feature.round_coordinates(decimals);
+        }
+    }
+
+    /// Swap the first two axes of the coordinates of all features
+    pub fn swap_axes(&mut self) {
+        for feature in self.features.iter_mut() {
+            feature.swap_axes();
+        }
+    }
+
+    /// Remove properties from all features
+    pub fn remove_properties(&mut self, names: &[String]) {
+        for feature in self.features.iter_mut() {
+            feature.remove_properties(names);
+        }
+    }
 }
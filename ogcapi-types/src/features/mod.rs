@@ -1,9 +1,33 @@
+mod aggregate;
+mod attachment;
+mod change;
+mod computed;
+mod delta;
 mod feature;
 mod feature_collection;
 mod query;
+mod queryables;
+mod relation;
+mod schema;
+mod stats;
+mod validation;
 
-pub use feature::Feature;
+pub use aggregate::{Aggregate, AggregateQuery, Grouping};
+pub use attachment::{Attachment, Attachments};
+pub use change::{ChangeKind, Changeset, FeatureChange, LoggedChange, RecentChange};
+pub use computed::{ComputedProperty, Expression, Function, Kind};
+pub use delta::DeltaSummary;
+pub use feature::{Feature, VERSION_PROPERTY};
 pub use feature_collection::FeatureCollection;
-pub use query::Query;
+pub use query::{LonLat, Query, ResultType};
+pub use queryables::Queryables;
+pub use relation::Relation;
+pub use schema::PropertiesSchema;
+pub(crate) use schema::{check_value, Subject};
+pub use stats::{
+    Bin, CollectionStats, Histogram, PropertyStats, PropertyValues, StatsQuery, ValueCount,
+    ValuesQuery,
+};
+pub use validation::{Rule, Severity, ValidationReport, ValidationRule, Violation};
 
 pub use geojson::Geometry;
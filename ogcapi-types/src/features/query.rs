@@ -1,12 +1,20 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, str::FromStr};
 
 use serde::{Deserialize, Serialize};
-use serde_with::DisplayFromStr;
+use serde_with::{formats::CommaSeparator, DisplayFromStr, StringWithSeparator};
 
-use crate::common::{Bbox, Crs, Datetime};
+use crate::{
+    common::{Bbox, Crs, Datetime},
+    cql2::Expr,
+};
 
 #[serde_with::serde_as]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "FeatureQuery")
+)]
 #[serde(rename_all = "kebab-case")]
 pub struct Query {
     pub limit: Option<usize>,
@@ -29,15 +37,117 @@ pub struct Query {
     #[serde(default)]
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub filter_crs: Option<Crs>,
+    /// Number of decimals of coordinates in the response
+    pub precision: Option<u32>,
+    /// Output format, e.g. `json` or `geojsonseq`
+    pub f: Option<String>,
+    /// Return only the number of matching features with `hits`, like
+    /// `limit=0`
+    pub resulttype: Option<ResultType>,
+    /// Return the features nearest to the position first
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+    pub nearest: Option<LonLat>,
+    /// Ids of the features to return, in this order
+    #[serde(default)]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
+    pub ids: Option<Vec<String>>,
     /// Parameters for filtering on feature properties
     #[serde(default, flatten)]
     pub additional_parameters: HashMap<String, String>,
+    /// Filter restricting the features accessible to the requesting user,
+    /// never part of the query string
+    #[serde(skip)]
+    pub access_filter: Option<Expr>,
+}
+
+/// Position as `lon,lat` in `CRS84`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LonLat {
+    pub lon: f64,
+    pub lat: f64,
+}
+
+impl FromStr for LonLat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let numbers = s
+            .split(',')
+            .map(|n| n.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid position `{s}`: {e}"))?;
+
+        match numbers[..] {
+            [lon, lat] if (-180.0..=180.0).contains(&lon) && (-90.0..=90.0).contains(&lat) => {
+                Ok(LonLat { lon, lat })
+            }
+            _ => Err(format!("Invalid position `{s}`, expected `lon,lat`")),
+        }
+    }
+}
+
+impl fmt::Display for LonLat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.lon, self.lat)
+    }
+}
+
+/// Content of the response to a query
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ResultType {
+    /// The matching features
+    #[default]
+    Results,
+    /// Only the number of matching features
+    Hits,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub enum FilterLang {
     #[default]
     CqlText,
     CqlJson,
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parameters() {
+        let query: Query = serde_json::from_value(json!({
+            "ids": "bern,basel",
+            "resulttype": "hits",
+            "name": "Bern"
+        }))
+        .unwrap();
+        assert_eq!(query.ids.unwrap(), ["bern", "basel"]);
+        assert_eq!(query.resulttype, Some(ResultType::Hits));
+        assert_eq!(query.additional_parameters["name"], "Bern");
+    }
+
+    #[test]
+    fn nearest() {
+        let query: Query = serde_json::from_value(json!({ "nearest": "7.44,46.95" })).unwrap();
+        assert_eq!(
+            query.nearest,
+            Some(LonLat {
+                lon: 7.44,
+                lat: 46.95
+            })
+        );
+        assert_eq!(query.nearest.unwrap().to_string(), "7.44,46.95");
+
+        assert!("7.44".parse::<LonLat>().is_err());
+        assert!("46.95,200".parse::<LonLat>().is_err());
+        assert!("a,b".parse::<LonLat>().is_err());
+    }
+}
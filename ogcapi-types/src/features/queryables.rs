@@ -1,16 +1,52 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Queryables {
-    queryables: Vec<Queryable>
+use super::PropertiesSchema;
+
+/// JSON Schema of the properties of a collection that can be used in filter
+/// expressions (OGC API - Features - Part 3)
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Queryables {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    #[serde(rename = "$id")]
+    pub id: Option<String>,
+    pub r#type: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Queryable properties as JSON Schema keyed by name
+    #[serde(default)]
+    pub properties: Map<String, Value>,
+    #[serde(default = "additional_properties")]
+    pub additional_properties: bool,
+}
+
+impl Default for Queryables {
+    fn default() -> Self {
+        Self {
+            schema: "https://json-schema.org/draft/2020-12/schema".to_string(),
+            id: None,
+            r#type: "object".to_string(),
+            title: None,
+            description: None,
+            properties: Map::new(),
+            additional_properties: true,
+        }
+    }
+}
+
+impl Queryables {
+    /// Declare the properties of a schema as queryables, replacing stored
+    /// ones of the same name
+    pub fn merge_schema(&mut self, schema: &PropertiesSchema) {
+        self.properties.extend(schema.properties());
+        self.additional_properties = schema.additional_properties();
+    }
+}
+
+fn additional_properties() -> bool {
+    true
 }
-#[derive(Serialize, Deserialize, Debug)]
-struct Queryable {
-    queryable: String,
-    title: Option<String>,
-    description: Option<String>,
-    language: Option<String>, // default en
-    r#type: String,
-    #[serde(rename = "type-ref")]
-    type_ref: String,
-}
\ No newline at end of file
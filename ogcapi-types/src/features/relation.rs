@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// Relation of the features of a collection to the features of another
+/// collection, by a property holding the ids of the related features like a
+/// foreign key
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Relation {
+    /// Id of the collection of the related features
+    pub collection: String,
+    /// Property holding the id, or a list of ids, of the related features
+    pub property: String,
+    /// Relation type of the links to the related features, `related` by
+    /// default
+    pub rel: Option<String>,
+    pub title: Option<String>,
+}
+
+impl Relation {
+    /// Related feature ids of a property value, ids or a list of ids
+    pub fn ids(value: &serde_json::Value) -> Vec<String> {
+        match value {
+            serde_json::Value::String(id) => vec![id.to_owned()],
+            serde_json::Value::Number(id) => vec![id.to_string()],
+            serde_json::Value::Array(values) => values.iter().flat_map(Relation::ids).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn ids() {
+        let relation: Relation = serde_json::from_value(json!({
+            "collection": "parcels",
+            "property": "parcel_id"
+        }))
+        .unwrap();
+        assert_eq!(relation.rel, None);
+
+        assert_eq!(Relation::ids(&json!("p1")), ["p1"]);
+        assert_eq!(Relation::ids(&json!([12, "p2", null])), ["12", "p2"]);
+        assert!(Relation::ids(&json!(null)).is_empty());
+    }
+}
@@ -0,0 +1,386 @@
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Types of JSON Schema
+const TYPES: [&str; 7] = [
+    "null", "boolean", "object", "array", "number", "integer", "string",
+];
+
+/// Names of the values checked against a schema, used in the violations
+#[derive(Clone, Copy)]
+pub(crate) struct Subject {
+    /// Name of the whole value, e.g. `Properties`
+    pub(crate) all: &'static str,
+    /// Name of a member, e.g. `property`
+    pub(crate) one: &'static str,
+}
+
+const PROPERTIES: Subject = Subject {
+    all: "Properties",
+    one: "property",
+};
+
+/// JSON Schema the properties of the features of a collection conform to
+///
+/// Properties are checked against the keywords `type`, `enum`, `const`,
+/// `required`, `properties`, `additionalProperties`, `items`, `minimum`,
+/// `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `minLength`,
+/// `maxLength`, `minItems`, `maxItems` and the formats `date` and
+/// `date-time`. Other keywords, e.g. `title` or `examples`, are only
+/// published.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct PropertiesSchema(pub Map<String, Value>);
+
+impl PropertiesSchema {
+    /// Schemas of the declared properties keyed by name
+    pub fn properties(&self) -> Map<String, Value> {
+        match self.0.get("properties") {
+            Some(Value::Object(properties)) => properties.to_owned(),
+            _ => Map::new(),
+        }
+    }
+
+    /// Whether properties which are not declared are allowed
+    pub fn additional_properties(&self) -> bool {
+        self.0.get("additionalProperties") != Some(&Value::Bool(false))
+    }
+
+    /// Check the properties of a feature, listing all violations
+    pub fn check(&self, properties: Option<&Map<String, Value>>) -> Vec<String> {
+        let properties = Value::Object(properties.cloned().unwrap_or_default());
+
+        let mut violations = Vec::new();
+        check_value(&self.0, &properties, "", PROPERTIES, &mut violations);
+        violations
+    }
+
+    /// Check the schema itself for keywords which can't be enforced
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self
+            .0
+            .get("type")
+            .is_some_and(|t| t != &Value::from("object"))
+        {
+            problems.push("Properties schema has to be of type `object`".to_string());
+        }
+        check_schema(&self.0, "", &mut problems);
+
+        problems
+    }
+}
+
+fn check_schema(schema: &Map<String, Value>, path: &str, problems: &mut Vec<String>) {
+    let at = |path: &str| {
+        if path.is_empty() {
+            "Properties schema".to_string()
+        } else {
+            format!("Schema of property `{path}`")
+        }
+    };
+
+    match schema.get("type") {
+        None => {}
+        Some(Value::String(t)) if TYPES.contains(&t.as_str()) => {}
+        Some(Value::Array(types))
+            if types
+                .iter()
+                .all(|t| t.as_str().is_some_and(|t| TYPES.contains(&t))) => {}
+        Some(t) => problems.push(format!("{} has an unknown type `{t}`", at(path))),
+    }
+
+    if let Some(required) = schema.get("required") {
+        if !required
+            .as_array()
+            .is_some_and(|required| required.iter().all(Value::is_string))
+        {
+            problems.push(format!("{} needs a list of `required` names", at(path)));
+        }
+    }
+
+    if let Some(Value::Object(properties)) = schema.get("properties") {
+        for (name, property) in properties {
+            if let Value::Object(property) = property {
+                check_schema(property, &nested(path, name), problems);
+            }
+        }
+    }
+    for keyword in ["items", "additionalProperties"] {
+        if let Some(Value::Object(subschema)) = schema.get(keyword) {
+            check_schema(subschema, &nested(path, "*"), problems);
+        }
+    }
+}
+
+/// Check a value against a schema, `path` is the slash separated location of
+/// the value within the checked subject
+pub(crate) fn check_value(
+    schema: &Map<String, Value>,
+    value: &Value,
+    path: &str,
+    subject: Subject,
+    violations: &mut Vec<String>,
+) {
+    let name = if path.is_empty() {
+        subject.all.to_string()
+    } else {
+        format!("{} `{path}`", capitalize(subject.one))
+    };
+
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|t| is_of_type(value, t)) {
+        violations.push(format!(
+            "{name} has to be of type `{}`",
+            types.join("` or `")
+        ));
+        return;
+    }
+
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        if !values.contains(value) {
+            let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+            violations.push(format!("{name} has to be one of `{}`", values.join("`, `")));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            violations.push(format!("{name} has to be `{constant}`"));
+        }
+    }
+
+    match value {
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or(f64::NAN);
+            let bound = |keyword| schema.get(keyword).and_then(Value::as_f64);
+            if let Some(minimum) = bound("minimum").filter(|m| number < *m) {
+                violations.push(format!("{name} has to be at least {minimum}"));
+            }
+            if let Some(maximum) = bound("maximum").filter(|m| number > *m) {
+                violations.push(format!("{name} has to be at most {maximum}"));
+            }
+            if let Some(minimum) = bound("exclusiveMinimum").filter(|m| number <= *m) {
+                violations.push(format!("{name} has to be greater than {minimum}"));
+            }
+            if let Some(maximum) = bound("exclusiveMaximum").filter(|m| number >= *m) {
+                violations.push(format!("{name} has to be less than {maximum}"));
+            }
+        }
+        Value::String(string) => {
+            let length = string.chars().count() as u64;
+            let limit = |keyword| schema.get(keyword).and_then(Value::as_u64);
+            if let Some(min) = limit("minLength").filter(|min| length < *min) {
+                violations.push(format!("{name} has to be at least {min} characters long"));
+            }
+            if let Some(max) = limit("maxLength").filter(|max| length > *max) {
+                violations.push(format!("{name} has to be at most {max} characters long"));
+            }
+            let format = schema.get("format").and_then(Value::as_str);
+            let valid = match format {
+                Some("date") => NaiveDate::parse_from_str(string, "%Y-%m-%d").is_ok(),
+                Some("date-time") => DateTime::parse_from_rfc3339(string).is_ok(),
+                _ => true,
+            };
+            if !valid {
+                violations.push(format!(
+                    "{name} has to be a `{}`",
+                    format.unwrap_or_default()
+                ));
+            }
+        }
+        Value::Array(items) => {
+            let length = items.len() as u64;
+            let limit = |keyword| schema.get(keyword).and_then(Value::as_u64);
+            if let Some(min) = limit("minItems").filter(|min| length < *min) {
+                violations.push(format!("{name} has to have at least {min} items"));
+            }
+            if let Some(max) = limit("maxItems").filter(|max| length > *max) {
+                violations.push(format!("{name} has to have at most {max} items"));
+            }
+            if let Some(items_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check_subschema(
+                        items_schema,
+                        item,
+                        &nested(path, &i.to_string()),
+                        subject,
+                        violations,
+                    );
+                }
+            }
+        }
+        Value::Object(object) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(key) {
+                        violations.push(format!("Missing {} `{}`", subject.one, nested(path, key)));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, value) in object {
+                let path = nested(path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(property) => check_subschema(property, value, &path, subject, violations),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            violations.push(format!("Unknown {} `{path}`", subject.one))
+                        }
+                        Some(additional) => {
+                            check_subschema(additional, value, &path, subject, violations)
+                        }
+                        None => {}
+                    },
+                }
+            }
+        }
+        Value::Null | Value::Bool(_) => {}
+    }
+}
+
+/// Check a value against a subschema, which is an object or a boolean
+fn check_subschema(
+    schema: &Value,
+    value: &Value,
+    path: &str,
+    subject: Subject,
+    violations: &mut Vec<String>,
+) {
+    match schema {
+        Value::Object(schema) => check_value(schema, value, path, subject, violations),
+        Value::Bool(false) => violations.push(format!(
+            "{} `{path}` is not allowed",
+            capitalize(subject.one)
+        )),
+        _ => {}
+    }
+}
+
+fn is_of_type(value: &Value, r#type: &str) -> bool {
+    match r#type {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "number" => value.is_number(),
+        "integer" => value
+            .as_f64()
+            .is_some_and(|n| value.is_i64() || value.is_u64() || n.fract() == 0.0),
+        "string" => value.is_string(),
+        _ => true,
+    }
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn nested(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{path}/{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema() -> PropertiesSchema {
+        serde_json::from_value(json!({
+            "type": "object",
+            "required": ["name", "height"],
+            "properties": {
+                "name": { "type": "string", "minLength": 1 },
+                "height": { "type": "number", "minimum": 0, "examples": [12.5] },
+                "floors": { "type": ["integer", "null"] },
+                "use": { "enum": ["residential", "commercial"] },
+                "built": { "type": "string", "format": "date" },
+                "tags": { "type": "array", "items": { "type": "string" }, "maxItems": 2 }
+            },
+            "additionalProperties": false
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn valid_properties() {
+        let schema = schema();
+        assert!(schema.problems().is_empty());
+
+        let properties = json!({
+            "name": "Town hall",
+            "height": 31,
+            "floors": null,
+            "use": "commercial",
+            "built": "1910-05-01",
+            "tags": ["historic"]
+        });
+        assert!(schema.check(properties.as_object()).is_empty());
+        assert!(!schema.additional_properties());
+        assert_eq!(
+            schema.properties().keys().collect::<Vec<_>>(),
+            ["built", "floors", "height", "name", "tags", "use"]
+        );
+    }
+
+    #[test]
+    fn violations() {
+        let properties = json!({
+            "name": "",
+            "floors": 2.5,
+            "use": "industrial",
+            "built": "yesterday",
+            "tags": ["historic", 1, "listed"],
+            "roof": "flat"
+        });
+
+        assert_eq!(
+            schema().check(properties.as_object()),
+            [
+                "Missing property `height`",
+                "Property `built` has to be a `date`",
+                "Property `floors` has to be of type `integer` or `null`",
+                "Property `name` has to be at least 1 characters long",
+                "Unknown property `roof`",
+                "Property `tags` has to have at most 2 items",
+                "Property `tags/1` has to be of type `string`",
+                "Property `use` has to be one of `\"residential\"`, `\"commercial\"`",
+            ]
+        );
+        assert_eq!(schema().check(None).len(), 2);
+    }
+
+    #[test]
+    fn problems() {
+        let schema: PropertiesSchema = serde_json::from_value(json!({
+            "type": "array",
+            "required": "name",
+            "properties": { "height": { "type": "float" } }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            schema.problems(),
+            [
+                "Properties schema has to be of type `object`",
+                "Properties schema needs a list of `required` names",
+                "Schema of property `height` has an unknown type `\"float\"`",
+            ]
+        );
+    }
+}
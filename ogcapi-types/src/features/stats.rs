@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::common::Bbox;
+
+/// Statistics of the features of a collection, e.g. for data catalogs
+///
+/// Statistics of large collections are computed from a random sample, the
+/// counts of geometry types and values then refer to the sample.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionStats {
+    /// Number of features, estimated if `exact` is false
+    pub number_matched: u64,
+    pub exact: bool,
+    /// Number of features the statistics were computed from
+    pub sample_size: u64,
+    /// Bounding box of the geometries in the storage crs
+    pub extent: Option<Bbox>,
+    /// Number of features per geometry type, e.g. `Point`
+    #[serde(default)]
+    pub geometry_types: BTreeMap<String, u64>,
+    /// Value distribution per feature property
+    #[serde(default)]
+    pub properties: BTreeMap<String, PropertyStats>,
+}
+
+/// Value distribution of a property
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyStats {
+    /// Number of features with a value other than `null`
+    pub count: u64,
+    /// Number of distinct values
+    pub distinct: Option<u64>,
+    pub histogram: Option<Histogram>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Histogram {
+    /// Equal width bins between the minimum and the maximum of numbers
+    Numeric { min: f64, max: f64, bins: Vec<Bin> },
+    /// Most frequent values of strings and booleans, in descending order
+    Categorical { values: Vec<ValueCount> },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Bin {
+    /// Lower bound, inclusive
+    pub lower: f64,
+    /// Upper bound, exclusive except for the last bin
+    pub upper: f64,
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ValueCount {
+    pub value: Value,
+    pub count: u64,
+}
+
+/// Distinct values of a property, most frequent first, e.g. for the options
+/// of filters in web clients
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PropertyValues {
+    pub property: String,
+    /// Number of distinct values, matching the prefix if any
+    pub distinct: u64,
+    pub values: Vec<ValueCount>,
+}
+
+/// Options of the distinct values of a property
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ValuesQuery {
+    /// Prefix of the values, case insensitive
+    pub prefix: Option<String>,
+    /// Maximum number of values
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_limit() -> usize {
+    100
+}
+
+impl Default for ValuesQuery {
+    fn default() -> Self {
+        ValuesQuery {
+            prefix: None,
+            limit: default_limit(),
+        }
+    }
+}
+
+/// Options of the statistics of a collection
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StatsQuery {
+    /// Number of bins of numeric histograms
+    #[serde(default = "default_bins")]
+    pub bins: usize,
+    /// Number of values of categorical histograms
+    #[serde(default = "default_values")]
+    pub values: usize,
+    /// Comma separated properties to describe, all if omitted
+    pub properties: Option<String>,
+}
+
+fn default_bins() -> usize {
+    10
+}
+
+fn default_values() -> usize {
+    10
+}
+
+impl Default for StatsQuery {
+    fn default() -> Self {
+        StatsQuery {
+            bins: default_bins(),
+            values: default_values(),
+            properties: None,
+        }
+    }
+}
+
+impl StatsQuery {
+    /// Requested properties, `None` for all
+    pub fn properties(&self) -> Option<Vec<String>> {
+        self.properties.as_ref().map(|properties| {
+            properties
+                .split(',')
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect()
+        })
+    }
+}
+
+impl Bin {
+    /// Equal width bins of a numeric range from counts by bin index
+    pub fn equal_width(min: f64, max: f64, counts: &[u64]) -> Vec<Bin> {
+        let width = (max - min) / counts.len().max(1) as f64;
+
+        counts
+            .iter()
+            .enumerate()
+            .map(|(i, count)| Bin {
+                lower: min + width * i as f64,
+                upper: if i + 1 == counts.len() {
+                    max
+                } else {
+                    min + width * (i + 1) as f64
+                },
+                count: *count,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_width() {
+        let bins = Bin::equal_width(0.0, 10.0, &[3, 0, 1, 1]);
+        assert_eq!(bins.len(), 4);
+        assert_eq!((bins[1].lower, bins[1].upper), (2.5, 5.0));
+        assert_eq!(bins[3].upper, 10.0);
+
+        let histogram = Histogram::Numeric {
+            min: 0.0,
+            max: 10.0,
+            bins,
+        };
+        assert_eq!(serde_json::to_value(&histogram).unwrap()["type"], "numeric");
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::Links;
+
+/// Rule the features of a collection are validated against when written
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationRule {
+    #[serde(flatten)]
+    pub rule: Rule,
+    /// Whether violations reject the write or are accepted with a warning
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "rule", rename_all = "camelCase")]
+pub enum Rule {
+    /// Geometries are valid, e.g. polygons without self-intersections
+    ValidGeometry,
+    /// Polygons don't overlap the other polygons of the collection
+    NoOverlap,
+    /// Properties are present and not `null`
+    #[serde(rename_all = "camelCase")]
+    RequiredProperties { properties: Vec<String> },
+}
+
+impl Rule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::ValidGeometry => "validGeometry",
+            Rule::NoOverlap => "noOverlap",
+            Rule::RequiredProperties { .. } => "requiredProperties",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+}
+
+/// Violation of a rule by a feature
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Violation {
+    /// Id of the feature, missing for new features without id
+    pub feature: Option<String>,
+    /// Position of the feature among the validated ones, missing for stored
+    /// features
+    pub index: Option<usize>,
+    /// Name of the rule
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.feature, self.index) {
+            (Some(id), _) => write!(f, "Feature `{id}`: {}", self.message),
+            (None, Some(index)) => write!(f, "Feature {index}: {}", self.message),
+            (None, None) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Violations of the rules of a collection by its stored features
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub collection: String,
+    /// Whether no feature violates a rule of severity `error`
+    pub valid: bool,
+    pub number_errors: usize,
+    pub number_warnings: usize,
+    pub violations: Vec<Violation>,
+    #[serde(default)]
+    pub links: Links,
+}
+
+impl ValidationReport {
+    pub fn new(collection: &str, violations: Vec<Violation>) -> Self {
+        let number_errors = violations
+            .iter()
+            .filter(|v| v.severity == Severity::Error)
+            .count();
+
+        ValidationReport {
+            collection: collection.to_owned(),
+            valid: number_errors == 0,
+            number_errors,
+            number_warnings: violations.len() - number_errors,
+            violations,
+            links: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules() {
+        let rules: Vec<ValidationRule> = serde_json::from_str(
+            r#"[
+                { "rule": "validGeometry" },
+                { "rule": "noOverlap", "severity": "warning" },
+                { "rule": "requiredProperties", "properties": ["owner"] }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(rules[0].rule, Rule::ValidGeometry);
+        assert_eq!(rules[0].severity, Severity::Error);
+        assert_eq!(rules[1].severity, Severity::Warning);
+        assert_eq!(
+            rules[2].rule,
+            Rule::RequiredProperties {
+                properties: vec!["owner".to_string()]
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&rules[1]).unwrap(),
+            serde_json::json!({ "rule": "noOverlap", "severity": "warning" })
+        );
+    }
+
+    #[test]
+    fn report() {
+        let violation = |severity| Violation {
+            feature: Some("p1".to_string()),
+            index: None,
+            rule: Rule::NoOverlap.name().to_string(),
+            severity,
+            message: "Overlaps `p2`".to_string(),
+        };
+        let report = ValidationReport::new("parcels", vec![violation(Severity::Warning); 2]);
+        assert!(report.valid);
+        assert_eq!(report.number_warnings, 2);
+
+        let report = ValidationReport::new("parcels", vec![violation(Severity::Error)]);
+        assert!(!report.valid);
+        assert_eq!(
+            report.violations[0].to_string(),
+            "Feature `p1`: Overlaps `p2`"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Atom feeds of the recently created, updated and deleted features of a
+//! collection, for feed readers and GIS clients following changes
+//!
+//! Entries are located with GeoRSS Simple, points as `georss:point` and other
+//! geometries by their bounding box as `georss:box`, both in `lat lon` order.
+
+use chrono::{SecondsFormat, Utc};
+use geojson::Value;
+
+use crate::{
+    common::{link_rel::SELF, media_type::GEO_JSON, Collection, Link},
+    features::{ChangeKind, Feature, RecentChange},
+    xml::escape,
+};
+
+/// Atom feed of the changes of a collection, newest first, with the current
+/// state of the features changed, missing once deleted
+///
+/// Features are linked below `items`, the items url of the collection.
+pub fn atom(
+    collection: &Collection,
+    links: &[Link],
+    items: &str,
+    changes: &[(RecentChange, Option<Feature>)],
+) -> String {
+    let id = links
+        .iter()
+        .find(|link| link.rel == SELF)
+        .map(|link| link.href.split('?').next().unwrap_or_default().to_owned())
+        .unwrap_or_else(|| format!("{items}#changes"));
+    let updated = changes
+        .iter()
+        .map(|(change, _)| change.changed)
+        .max()
+        .unwrap_or_else(Utc::now);
+
+    let mut feed = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:georss="http://www.georss.org/georss">"#
+            .to_string(),
+        format!("  <id>{}</id>", escape(&id)),
+        format!(
+            "  <title>Changes of {}</title>",
+            escape(collection.title.as_deref().unwrap_or(&collection.id))
+        ),
+        format!(
+            "  <updated>{}</updated>",
+            updated.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ),
+    ];
+    if let Some(description) = &collection.description {
+        feed.push(format!("  <subtitle>{}</subtitle>", escape(description)));
+    }
+    for link in links {
+        feed.push(format!("  {}", link_element(link)));
+    }
+
+    for (change, feature) in changes {
+        feed.extend(entry(&id, items, change, feature.as_ref()));
+    }
+
+    feed.push("</feed>".to_string());
+    feed.join("\n")
+}
+
+fn entry(feed: &str, items: &str, change: &RecentChange, feature: Option<&Feature>) -> Vec<String> {
+    let (verb, term) = match change.change.kind {
+        ChangeKind::Create => ("Created", "create"),
+        ChangeKind::Update => ("Updated", "update"),
+        ChangeKind::Delete => ("Deleted", "delete"),
+    };
+    let label = feature
+        .and_then(|feature| feature.properties.as_ref())
+        .and_then(|properties| {
+            ["title", "name"]
+                .iter()
+                .find_map(|key| properties.get(*key))
+        })
+        .and_then(|label| label.as_str())
+        .unwrap_or(&change.change.id);
+
+    let mut entry = vec![
+        "  <entry>".to_string(),
+        format!(
+            "    <id>{}</id>",
+            escape(&format!("{feed}#{}", change.position))
+        ),
+        format!("    <title>{verb} {}</title>", escape(label)),
+        format!(
+            "    <updated>{}</updated>",
+            change.changed.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ),
+        format!(r#"    <category term="{term}"/>"#),
+    ];
+
+    if let Some(feature) = feature {
+        let href = format!("{items}/{}", change.change.id);
+        let link = Link::new(href, "alternate").mediatype(GEO_JSON);
+        entry.push(format!("    {}", link_element(&link)));
+
+        if let Some(properties) = feature.properties.as_ref().filter(|p| !p.is_empty()) {
+            let summary: Vec<String> = properties
+                .iter()
+                .map(|(key, value)| match value.as_str() {
+                    Some(text) => format!("{key}: {text}"),
+                    None => format!("{key}: {value}"),
+                })
+                .collect();
+            entry.push(format!(
+                "    <summary>{}</summary>",
+                escape(&summary.join("\n"))
+            ));
+        }
+
+        if let Some(location) = georss(&feature.geometry.value) {
+            entry.push(format!("    {location}"));
+        }
+    }
+
+    entry.push("  </entry>".to_string());
+    entry
+}
+
+fn link_element(link: &Link) -> String {
+    let mut element = format!(
+        r#"<link rel="{}" href="{}""#,
+        escape(&link.rel),
+        escape(&link.href)
+    );
+    if let Some(media_type) = &link.r#type {
+        element.push_str(&format!(r#" type="{}""#, escape(media_type)));
+    }
+    if let Some(title) = &link.title {
+        element.push_str(&format!(r#" title="{}""#, escape(title)));
+    }
+    element.push_str("/>");
+    element
+}
+
+/// GeoRSS Simple location of a geometry in `CRS84`
+fn georss(geometry: &Value) -> Option<String> {
+    if let Value::Point(point) = geometry {
+        return Some(format!(
+            "<georss:point>{} {}</georss:point>",
+            point[1], point[0]
+        ));
+    }
+
+    let mut bbox = [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ];
+    extend(&mut bbox, geometry);
+    let [west, south, east, north] = bbox;
+
+    (west <= east && south <= north)
+        .then(|| format!("<georss:box>{south} {west} {north} {east}</georss:box>"))
+}
+
+/// Extend a bounding box `[west, south, east, north]` by a geometry
+fn extend(bbox: &mut [f64; 4], geometry: &Value) {
+    let mut add = |position: &Vec<f64>| {
+        bbox[0] = bbox[0].min(position[0]);
+        bbox[1] = bbox[1].min(position[1]);
+        bbox[2] = bbox[2].max(position[0]);
+        bbox[3] = bbox[3].max(position[1]);
+    };
+
+    match geometry {
+        Value::Point(p) => add(p),
+        Value::MultiPoint(ps) | Value::LineString(ps) => ps.iter().for_each(add),
+        Value::MultiLineString(ls) | Value::Polygon(ls) => ls.iter().flatten().for_each(add),
+        Value::MultiPolygon(ps) => ps.iter().flatten().flatten().for_each(add),
+        Value::GeometryCollection(geometries) => {
+            for geometry in geometries {
+                extend(bbox, &geometry.value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::features::FeatureChange;
+
+    use super::*;
+
+    fn change(kind: ChangeKind, id: &str, position: &str) -> RecentChange {
+        RecentChange {
+            change: FeatureChange {
+                kind,
+                collection: "places".to_string(),
+                id: id.to_string(),
+            },
+            changed: "2024-07-13T08:30:00Z".parse().unwrap(),
+            position: position.to_string(),
+        }
+    }
+
+    #[test]
+    fn atom_feed() {
+        let collection = Collection {
+            id: "places".to_string(),
+            title: Some("Places & sights".to_string()),
+            ..Default::default()
+        };
+        let links = [Link::new(
+            "https://example.com/collections/places/changes?f=atom",
+            SELF,
+        )];
+        let bern: Feature = serde_json::from_value(json!({
+            "type": "Feature",
+            "id": "bern",
+            "properties": { "name": "Bern", "population": 134794 },
+            "geometry": { "type": "Point", "coordinates": [7.4474, 46.948] }
+        }))
+        .unwrap();
+        let lake: Feature = serde_json::from_value(json!({
+            "type": "Feature",
+            "id": "lake",
+            "properties": {},
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[[7.5, 46.6], [7.9, 46.6], [7.9, 46.8], [7.5, 46.6]]]
+            }
+        }))
+        .unwrap();
+
+        let feed = atom(
+            &collection,
+            &links,
+            "https://example.com/collections/places/items",
+            &[
+                (change(ChangeKind::Update, "bern", "42-2"), Some(bern)),
+                (change(ChangeKind::Create, "lake", "42-1"), Some(lake)),
+                (change(ChangeKind::Delete, "thun", "41-7"), None),
+            ],
+        );
+
+        assert!(feed.contains("<title>Changes of Places &amp; sights</title>"));
+        assert!(feed.contains("<id>https://example.com/collections/places/changes</id>"));
+        assert!(feed.contains("<updated>2024-07-13T08:30:00Z</updated>"));
+        assert!(feed.contains("<title>Updated Bern</title>"));
+        assert!(feed.contains(r#"<category term="update"/>"#));
+        assert!(feed.contains("<georss:point>46.948 7.4474</georss:point>"));
+        assert!(feed.contains("population: 134794"));
+        assert!(feed.contains(
+            r#"<link rel="alternate" href="https://example.com/collections/places/items/bern" type="application/geo+json"/>"#
+        ));
+        assert!(feed.contains("<georss:box>46.6 7.5 46.8 7.9</georss:box>"));
+        assert!(feed.contains("<title>Deleted thun</title>"));
+        assert!(feed.contains("<id>https://example.com/collections/places/changes#41-7</id>"));
+        assert_eq!(feed.matches("<entry>").count(), 3);
+    }
+}
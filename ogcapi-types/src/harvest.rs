@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::common::Links;
+
+/// Scheduled harvest of the features of a remote source into a collection
+///
+/// Every run fetches all features of the source and applies the changes by id,
+/// it is listed in the jobs with the process id `harvest:{id}`.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Harvest {
+    #[serde(default)]
+    pub id: String,
+    /// Collection the features are upserted into
+    pub collection: String,
+    pub source: HarvestSource,
+    /// Cron expression of the runs in UTC, as `minute hour day month weekday`
+    /// or with seconds as first field
+    pub schedule: String,
+    /// Property holding the feature ids, the ids of the source features if
+    /// missing
+    pub id_property: Option<String>,
+    /// Whether the harvest is run on schedule, runs may still be started
+    /// manually
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    pub created: Option<DateTime<Utc>>,
+    /// Scheduled time of the latest run
+    pub last_run: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Links,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Remote source of a harvest
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum HarvestSource {
+    /// Items of a collection of an OGC API, e.g.
+    /// `https://example.com/collections/roads/items`, the pages are followed
+    /// with their `next` links
+    Features { url: String },
+    /// Feature type of a WFS 2.0 supporting GeoJSON output
+    #[serde(rename_all = "camelCase")]
+    Wfs { url: String, type_name: String },
+    /// Items of a STAC API search or items endpoint, or of a static catalog
+    /// walked along its `child` and `item` links
+    Stac { url: String },
+    /// GeoJSON file
+    File { url: String },
+}
+
+impl HarvestSource {
+    pub fn url(&self) -> &str {
+        match self {
+            HarvestSource::Features { url }
+            | HarvestSource::Wfs { url, .. }
+            | HarvestSource::Stac { url }
+            | HarvestSource::File { url } => url,
+        }
+    }
+}
+
+impl Harvest {
+    /// Process id of the jobs of the runs
+    pub fn process_id(&self) -> String {
+        format!("harvest:{}", self.id)
+    }
+
+    /// Check the harvest for problems, except for the schedule
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.collection.trim().is_empty() {
+            problems.push("Harvest needs a collection".to_string());
+        }
+
+        match Url::parse(self.source.url()) {
+            Ok(url) if ["http", "https"].contains(&url.scheme()) => {}
+            _ => problems.push(format!("Invalid source url `{}`", self.source.url())),
+        }
+        if let HarvestSource::Wfs { type_name, .. } = &self.source {
+            if type_name.trim().is_empty() {
+                problems.push("WFS source needs a type name".to_string());
+            }
+        }
+
+        if self.schedule.trim().is_empty() {
+            problems.push("Harvest needs a schedule".to_string());
+        }
+        if self.id_property.as_ref().is_some_and(|p| p.is_empty()) {
+            problems.push("Id property must not be empty".to_string());
+        }
+
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn harvest() {
+        let harvest: Harvest = serde_json::from_value(json!({
+            "collection": "stations",
+            "source": {
+                "type": "wfs",
+                "url": "https://example.com/wfs",
+                "typeName": "hydro:stations"
+            },
+            "schedule": "0 3 * * *",
+            "idProperty": "station_no"
+        }))
+        .unwrap();
+
+        assert!(harvest.enabled);
+        assert!(harvest.validate().is_empty());
+        assert_eq!(harvest.source.url(), "https://example.com/wfs");
+
+        let value = serde_json::to_value(&harvest).unwrap();
+        assert_eq!(value["source"]["typeName"], "hydro:stations");
+        assert!(value.get("lastRun").is_none());
+
+        let harvest = Harvest {
+            collection: " ".to_string(),
+            source: HarvestSource::Wfs {
+                url: "file:///etc/passwd".to_string(),
+                type_name: String::new(),
+            },
+            ..harvest
+        };
+        assert_eq!(
+            harvest.validate(),
+            [
+                "Harvest needs a collection",
+                "Invalid source url `file:///etc/passwd`",
+                "WFS source needs a type name",
+            ]
+        );
+    }
+}
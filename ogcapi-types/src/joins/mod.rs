@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::{is_valid_id, Links};
+
+/// Attribute file uploaded to be joined with a feature collection
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DataFile {
+    #[serde(default)]
+    pub id: String,
+    pub name: Option<String>,
+    /// Field names as found in the header of the uploaded file
+    #[serde(default)]
+    pub fields: Vec<String>,
+    pub number_of_rows: Option<u64>,
+    #[serde(default)]
+    pub links: Links,
+}
+
+/// List of uploaded attribute files
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DataFiles {
+    pub files: Vec<DataFile>,
+    #[serde(default)]
+    pub links: Links,
+}
+
+/// Definition of a join between a feature collection and an attribute file
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Join {
+    #[serde(default)]
+    pub id: String,
+    /// Identifier of the feature collection to join
+    pub collection_id: String,
+    /// Property of the features used as join key
+    pub collection_key: String,
+    /// Identifier of the attribute file to join
+    pub file_id: String,
+    /// Field of the attribute file used as join key
+    pub file_key: String,
+    /// Identifier of the resulting collection, defaults to the join id
+    pub target_collection_id: Option<String>,
+    /// Store the joined data in a new table instead of exposing a view
+    #[serde(default = "materialize")]
+    pub materialize: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Links,
+}
+
+impl Join {
+    /// Identifier of the collection holding the joined data
+    pub fn target(&self) -> &str {
+        self.target_collection_id.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Check the join for problems
+    ///
+    /// The target becomes the id of a collection, so it has to match the
+    /// pattern of collection ids. An empty id is assigned by the server.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let target = self.target();
+        if (self.target_collection_id.is_some() || !self.id.is_empty()) && !is_valid_id(target) {
+            problems.push(format!(
+                "Target collection id `{target}` has to start with a letter or digit, \
+                followed by up to 62 letters, digits, `_`, `-` or `.`"
+            ));
+        }
+
+        problems
+    }
+}
+
+fn materialize() -> bool {
+    true
+}
+
+/// List of joins
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Joins {
+    pub joins: Vec<Join>,
+    #[serde(default)]
+    pub links: Links,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let mut join: Join = serde_json::from_value(json!({
+            "collectionId": "municipalities",
+            "collectionKey": "code",
+            "fileId": "population",
+            "fileKey": "gm_code"
+        }))
+        .unwrap();
+
+        assert!(join.materialize);
+        assert!(join.validate().is_empty());
+
+        join.target_collection_id = Some("municipalities.population".to_string());
+        assert!(join.validate().is_empty());
+
+        join.target_collection_id = Some(r#"x" AS SELECT 1; --"#.to_string());
+        assert_eq!(join.validate().len(), 1);
+
+        join.target_collection_id = None;
+        join.id = "../joined".to_string();
+        assert_eq!(join.validate().len(), 1);
+    }
+}
@@ -0,0 +1,293 @@
+//! JSON-LD representations for linked data harvesters
+//!
+//! Features are described with the GeoSPARQL vocabulary, their geometry as
+//! `WKT` literal, and their properties in the vocabulary of the
+//! queryables of their collection. Collections are described as schema.org
+//! `Dataset`, as indexed by data portals and dataset search engines.
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    common::{
+        link_rel::{COLLECTION, DATA, ITEMS, SELF},
+        Collection, Crs, Links,
+    },
+    cql2::Expr,
+    features::{Feature, FeatureCollection},
+};
+
+const GEO: &str = "http://www.opengis.net/ont/geosparql#";
+const SCHEMA: &str = "https://schema.org/";
+const DCT: &str = "http://purl.org/dc/terms/";
+
+/// JSON-LD of a feature with its geometry in `crs`
+pub fn feature(feature: &Feature, crs: &Crs) -> Value {
+    let mut value = feature_node(feature, crs);
+    value.insert("@context".to_string(), feature_context(&feature.links));
+    Value::Object(value)
+}
+
+/// JSON-LD of a feature collection, with the features as graph
+pub fn feature_collection(fc: &FeatureCollection, crs: &Crs) -> Value {
+    let links = fc.features.first().map(|f| &f.links).unwrap_or(&fc.links);
+
+    let mut value = json!({
+        "@context": feature_context(links),
+        "@graph": fc
+            .features
+            .iter()
+            .map(|feature| feature_node(feature, crs))
+            .collect::<Vec<_>>(),
+    });
+    if let Some(href) = href(&fc.links, SELF) {
+        value["@id"] = href.into();
+    }
+    value
+}
+
+/// JSON-LD of a collection as schema.org `Dataset`
+pub fn collection(collection: &Collection) -> Value {
+    let mut value = Map::new();
+    value.insert("@context".to_string(), json!({ "@vocab": SCHEMA }));
+    value.insert("@type".to_string(), "Dataset".into());
+    if let Some(href) = href(&collection.links, SELF) {
+        value.insert("@id".to_string(), href.into());
+        value.insert("url".to_string(), href.into());
+    }
+    value.insert(
+        "name".to_string(),
+        collection
+            .title
+            .as_ref()
+            .unwrap_or(&collection.id)
+            .as_str()
+            .into(),
+    );
+    value.insert("identifier".to_string(), collection.id.as_str().into());
+    if let Some(doi) = &collection.doi {
+        value.insert(
+            "sameAs".to_string(),
+            format!("https://doi.org/{doi}").into(),
+        );
+    }
+    if let Some(description) = &collection.description {
+        value.insert("description".to_string(), description.as_str().into());
+    }
+    if !collection.keywords.is_empty() {
+        value.insert("keywords".to_string(), collection.keywords.clone().into());
+    }
+    #[cfg(not(feature = "stac"))]
+    let license = collection.license.as_deref();
+    #[cfg(feature = "stac")]
+    let license = Some(collection.license.as_str());
+    if let Some(license) = license {
+        value.insert("license".to_string(), license.into());
+    }
+    if let Some(attribution) = &collection.attribution {
+        value.insert("creditText".to_string(), attribution.as_str().into());
+    }
+    if !collection.providers.is_empty() {
+        let providers: Vec<Value> = collection
+            .providers
+            .iter()
+            .map(|provider| {
+                let mut organization = json!({
+                    "@type": "Organization",
+                    "name": provider.name,
+                });
+                if let Some(url) = &provider.url {
+                    organization["url"] = url.as_str().into();
+                }
+                organization
+            })
+            .collect();
+        value.insert("provider".to_string(), providers.into());
+    }
+
+    if let Some(extent) = &collection.extent {
+        // schema.org boxes are `south west north east`
+        if let Some(bbox) = extent.spatial.as_ref().and_then(|s| s.bbox.first()) {
+            let [minx, miny, maxx, maxy] = bbox.to_2d();
+            value.insert(
+                "spatialCoverage".to_string(),
+                json!({
+                    "@type": "Place",
+                    "geo": {
+                        "@type": "GeoShape",
+                        "box": format!("{miny} {minx} {maxy} {maxx}"),
+                    }
+                }),
+            );
+        }
+        if let Some(interval) = extent.temporal.as_ref().and_then(|t| t.interval.first()) {
+            let instant = |i: usize| {
+                interval
+                    .get(i)
+                    .and_then(|d| d.as_ref())
+                    .map_or("..".to_string(), |d| d.to_rfc3339())
+            };
+            value.insert(
+                "temporalCoverage".to_string(),
+                format!("{}/{}", instant(0), instant(1)).into(),
+            );
+        }
+    }
+
+    let distributions: Vec<Value> = collection
+        .links
+        .iter()
+        .filter(|link| link.rel == ITEMS || link.rel == DATA)
+        .map(|link| {
+            let mut distribution = json!({
+                "@type": "DataDownload",
+                "contentUrl": link.href,
+            });
+            if let Some(media_type) = &link.r#type {
+                distribution["encodingFormat"] = media_type.as_str().into();
+            }
+            distribution
+        })
+        .collect();
+    if !distributions.is_empty() {
+        value.insert("distribution".to_string(), distributions.into());
+    }
+
+    Value::Object(value)
+}
+
+/// Context of features, with the properties in the vocabulary of the
+/// queryables of the collection
+fn feature_context(links: &Links) -> Value {
+    let mut context = json!({
+        "geo": GEO,
+        "dct": DCT,
+        "schema": SCHEMA,
+    });
+    if let Some(collection) = href(links, COLLECTION) {
+        context["@vocab"] = format!("{}/queryables#", collection.trim_end_matches('/')).into();
+    }
+    context
+}
+
+fn feature_node(feature: &Feature, crs: &Crs) -> Map<String, Value> {
+    let mut node = Map::new();
+    if let Some(href) = href(&feature.links, SELF) {
+        node.insert("@id".to_string(), href.into());
+    }
+    node.insert("@type".to_string(), "geo:Feature".into());
+    if let Some(id) = &feature.id {
+        node.insert("dct:identifier".to_string(), id.as_str().into());
+    }
+    if let Some(collection) = href(&feature.links, COLLECTION) {
+        node.insert("schema:isPartOf".to_string(), json!({ "@id": collection }));
+    }
+    // literals in other crs than `CRS84` start with the crs
+    let mut wkt = Expr::Geometry(feature.geometry.clone()).to_string();
+    if crs != &Crs::default() {
+        wkt = format!("<{crs}> {wkt}");
+    }
+    node.insert(
+        "geo:hasGeometry".to_string(),
+        json!({
+            "@type": "geo:Geometry",
+            "geo:asWKT": { "@type": "geo:wktLiteral", "@value": wkt }
+        }),
+    );
+
+    for (name, value) in feature.properties.iter().flatten() {
+        // keywords and prefixed names would change the meaning
+        if !name.starts_with('@') && !name.contains(':') {
+            node.insert(name.to_owned(), value.to_owned());
+        }
+    }
+
+    node
+}
+
+/// Href of a link without query string, e.g. `f=jsonld`, to serve as id
+fn href<'a>(links: &'a Links, rel: &str) -> Option<&'a str> {
+    links
+        .iter()
+        .find(|link| link.rel == rel)
+        .and_then(|link| link.href.split('?').next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_as_json_ld() {
+        let feature: Feature = serde_json::from_value(json!({
+            "type": "Feature",
+            "id": "h1",
+            "properties": { "status": "ok", "@id": "x", "geo:x": 1 },
+            "geometry": { "type": "Point", "coordinates": [7.5, 47.0] },
+            "links": [
+                {
+                    "href": "https://api.example.com/collections/hydrants/items/h1?f=jsonld",
+                    "rel": "self"
+                },
+                { "href": "https://api.example.com/collections/hydrants", "rel": "collection" }
+            ]
+        }))
+        .unwrap();
+
+        let value = super::feature(&feature, &Crs::default());
+        assert_eq!(
+            value["@context"]["@vocab"],
+            "https://api.example.com/collections/hydrants/queryables#"
+        );
+        assert_eq!(
+            value["@id"],
+            "https://api.example.com/collections/hydrants/items/h1"
+        );
+        assert_eq!(value["@type"], "geo:Feature");
+        assert_eq!(
+            value["geo:hasGeometry"]["geo:asWKT"]["@value"],
+            "POINT(7.5 47)"
+        );
+        assert_eq!(value["status"], "ok");
+
+        let value = super::feature(&feature, &Crs::from_epsg(2056));
+        assert_eq!(
+            value["geo:hasGeometry"]["geo:asWKT"]["@value"],
+            "<http://www.opengis.net/def/crs/EPSG/0/2056> POINT(7.5 47)"
+        );
+        assert!(value.get("geo:x").is_none());
+    }
+
+    #[test]
+    fn collection_as_dataset() {
+        let collection: Collection = serde_json::from_value(json!({
+            "id": "hydrants",
+            "title": "Hydrants",
+            "license": "CC-BY-4.0",
+            "doi": "10.5281/zenodo.1234",
+            "extent": {
+                "spatial": { "bbox": [[7.0, 46.0, 8.0, 47.0]] },
+                "temporal": { "interval": [["2020-01-01T00:00:00Z", null]] }
+            },
+            "links": [
+                { "href": "https://api.example.com/collections/hydrants", "rel": "self" },
+                {
+                    "href": "https://api.example.com/collections/hydrants/items",
+                    "rel": "items",
+                    "type": "application/geo+json"
+                }
+            ]
+        }))
+        .unwrap();
+
+        let value = super::collection(&collection);
+        assert_eq!(value["@type"], "Dataset");
+        assert_eq!(value["name"], "Hydrants");
+        assert_eq!(value["sameAs"], "https://doi.org/10.5281/zenodo.1234");
+        assert_eq!(value["spatialCoverage"]["geo"]["box"], "46 7 47 8");
+        assert_eq!(value["temporalCoverage"], "2020-01-01T00:00:00+00:00/..");
+        assert_eq!(
+            value["distribution"][0]["encodingFormat"],
+            "application/geo+json"
+        );
+    }
+}
@@ -1,19 +1,44 @@
 #![doc = include_str!("../README.md")]
 
+/// Types for users and api keys, not part of any standard.
+pub mod auth;
 /// Types specified in the `OGC API - Common` standard.
 pub mod common;
+/// Types specified in the `Common Query Language (CQL2)` standard.
+pub mod cql2;
 /// Types specified in the `OGC API - Environmental Data Retrieval` standard.
 pub mod edr;
 /// Types specified in the `OGC API - Features` standard.
 pub mod features;
+/// Atom feeds of feature changes with GeoRSS, not part of any standard.
+pub mod feeds;
+/// Types for scheduled harvests, not part of any standard.
+pub mod harvest;
+/// Types specified in the `OGC API - Joins` draft standard.
+pub mod joins;
+/// JSON-LD representations, not part of any standard.
+pub mod jsonld;
+/// Metadata records in DCAT-AP and ISO 19139, not part of any standard.
+pub mod metadata;
 /// Types specified in the `OGC API - Processed` standard.
 pub mod processes;
+/// JSON Schemas of the types, requires the `schemars` feature.
+#[cfg(feature = "schemars")]
+pub mod schema;
 /// Types from the `SpatioTemporal Asset Catalog` specfication.
 #[cfg(feature = "stac")]
 pub mod stac;
 /// Types specified in the `OGC API - Styles` standard.
 pub mod styles;
+/// Types for the background tasks of the server, not part of any standard.
+pub mod tasks;
 /// Types specified in the `OGC API - Tiles` standard.
 pub mod tiles;
+/// Types for saved views of features, not part of any standard.
+pub mod views;
+/// Types for webhooks, not part of any standard.
+pub mod webhooks;
+/// Helpers for XML representations, not part of any standard.
+pub mod xml;
 
 mod coverage;
@@ -0,0 +1,462 @@
+//! Metadata records of collections for catalogs harvesting the API, e.g.
+//! GeoNetwork or data.europa.eu
+//!
+//! Records are described as `dcat:Dataset` according to DCAT-AP in Turtle,
+//! or as ISO 19115 metadata encoded as ISO 19139 XML. Links of the collection
+//! to its items and data become distributions.
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    common::{
+        link_rel::{DATA, ITEMS, SELF},
+        Collection, Link, Provider, ProviderRole,
+    },
+    xml::escape,
+};
+
+/// DCAT-AP record of a collection as Turtle
+pub fn dcat(collection: &Collection) -> String {
+    let id = self_href(collection);
+
+    let mut statements = vec![
+        "a dcat:Dataset".to_string(),
+        format!("dct:identifier {}", literal(&collection.id)),
+        format!("dct:title {}", literal(title(collection))),
+    ];
+    if let Some(description) = &collection.description {
+        statements.push(format!("dct:description {}", literal(description)));
+    }
+    if !collection.keywords.is_empty() {
+        let keywords: Vec<String> = collection.keywords.iter().map(|k| literal(k)).collect();
+        statements.push(format!("dcat:keyword {}", keywords.join(", ")));
+    }
+    if let Some(doi) = &collection.doi {
+        statements.push(format!(
+            "adms:identifier [ a adms:Identifier ; skos:notation {} ; adms:schemaAgency \"DOI\" ]",
+            literal(doi)
+        ));
+    }
+    if let Some(license) = license(collection) {
+        statements.push(format!("dct:license {}", iri(&license_url(license))));
+    }
+    if let Some(attribution) = &collection.attribution {
+        statements.push(format!(
+            "dct:rightsHolder [ a foaf:Agent ; foaf:name {} ]",
+            literal(attribution)
+        ));
+    }
+    for provider in &collection.providers {
+        let mut agent = format!("a foaf:Agent ; foaf:name {}", literal(&provider.name));
+        if let Some(url) = &provider.url {
+            agent.push_str(&format!(" ; foaf:homepage {}", iri(url)));
+        }
+        let predicate = if has_role(provider, ProviderRole::Producer) {
+            "dct:creator"
+        } else {
+            "dct:publisher"
+        };
+        statements.push(format!("{predicate} [ {agent} ]"));
+    }
+    if let Some([west, south, east, north]) = bbox(collection) {
+        statements.push(format!(
+            "dct:spatial [ a dct:Location ; dcat:bbox \"POLYGON(({west} {south}, {east} {south}, \
+            {east} {north}, {west} {north}, {west} {south}))\"^^gsp:wktLiteral ]"
+        ));
+    }
+    if let Some((start, end)) = interval(collection) {
+        let mut period = "a dct:PeriodOfTime".to_string();
+        if let Some(start) = start {
+            period.push_str(&format!(
+                " ; dcat:startDate \"{}\"^^xsd:dateTime",
+                start.to_rfc3339()
+            ));
+        }
+        if let Some(end) = end {
+            period.push_str(&format!(
+                " ; dcat:endDate \"{}\"^^xsd:dateTime",
+                end.to_rfc3339()
+            ));
+        }
+        statements.push(format!("dct:temporal [ {period} ]"));
+    }
+    if let Some(id) = &id {
+        statements.push(format!("dcat:landingPage {}", iri(id)));
+    }
+
+    let distributions = distributions(collection);
+    for link in &distributions {
+        statements.push(format!("dcat:distribution {}", iri(&link.href)));
+    }
+
+    let mut turtle = String::from(
+        "@prefix adms: <http://www.w3.org/ns/adms#> .\n\
+        @prefix dcat: <http://www.w3.org/ns/dcat#> .\n\
+        @prefix dct: <http://purl.org/dc/terms/> .\n\
+        @prefix foaf: <http://xmlns.com/foaf/0.1/> .\n\
+        @prefix gsp: <http://www.opengis.net/ont/geosparql#> .\n\
+        @prefix skos: <http://www.w3.org/2004/02/skos/core#> .\n\
+        @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n\n",
+    );
+
+    let subject = id.as_deref().map_or("[]".to_string(), iri);
+    turtle.push_str(&format!(
+        "{subject}\n    {} .\n",
+        statements.join(" ;\n    ")
+    ));
+
+    for link in distributions {
+        let mut statements = vec![
+            "a dcat:Distribution".to_string(),
+            format!("dcat:accessURL {}", iri(&link.href)),
+        ];
+        if let Some(title) = &link.title {
+            statements.push(format!("dct:title {}", literal(title)));
+        }
+        if let Some(media_type) = &link.r#type {
+            statements.push(format!(
+                "dcat:mediaType {}",
+                iri(&format!(
+                    "https://www.iana.org/assignments/media-types/{}",
+                    media_type.split(';').next().unwrap_or_default().trim()
+                ))
+            ));
+        }
+        turtle.push_str(&format!(
+            "\n{}\n    {} .\n",
+            iri(&link.href),
+            statements.join(" ;\n    ")
+        ));
+    }
+
+    turtle
+}
+
+/// ISO 19115 record of a collection as ISO 19139 XML, `date_stamp` being the
+/// date of the record
+pub fn iso19139(collection: &Collection, date_stamp: DateTime<Utc>) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<gmd:MD_Metadata xmlns:gmd="http://www.isotc211.org/2005/gmd" xmlns:gco="http://www.isotc211.org/2005/gco" xmlns:gml="http://www.opengis.net/gml/3.2" xmlns:xlink="http://www.w3.org/1999/xlink">
+"#,
+    );
+
+    xml.push_str(&format!(
+        "  <gmd:fileIdentifier>{}</gmd:fileIdentifier>\n",
+        character_string(&collection.id)
+    ));
+    xml.push_str(
+        "  <gmd:hierarchyLevel><gmd:MD_ScopeCode codeList=\"http://standards.iso.org/iso/19139/resources/gmxCodelists.xml#MD_ScopeCode\" codeListValue=\"dataset\">dataset</gmd:MD_ScopeCode></gmd:hierarchyLevel>\n",
+    );
+    if collection.providers.is_empty() {
+        xml.push_str("  <gmd:contact gco:nilReason=\"missing\"/>\n");
+    }
+    for provider in &collection.providers {
+        xml.push_str(&format!(
+            "  <gmd:contact>{}</gmd:contact>\n",
+            responsible_party(provider)
+        ));
+    }
+    xml.push_str(&format!(
+        "  <gmd:dateStamp><gco:DateTime>{}</gco:DateTime></gmd:dateStamp>\n",
+        date_stamp.format("%Y-%m-%dT%H:%M:%SZ")
+    ));
+
+    // identification
+    xml.push_str("  <gmd:identificationInfo>\n    <gmd:MD_DataIdentification>\n");
+    xml.push_str("      <gmd:citation><gmd:CI_Citation>");
+    xml.push_str(&format!(
+        "<gmd:title>{}</gmd:title>",
+        character_string(title(collection))
+    ));
+    xml.push_str(&format!(
+        "<gmd:date><gmd:CI_Date><gmd:date><gco:Date>{}</gco:Date></gmd:date><gmd:dateType><gmd:CI_DateTypeCode codeList=\"http://standards.iso.org/iso/19139/resources/gmxCodelists.xml#CI_DateTypeCode\" codeListValue=\"publication\">publication</gmd:CI_DateTypeCode></gmd:dateType></gmd:CI_Date></gmd:date>",
+        date_stamp.format("%Y-%m-%d")
+    ));
+    if let Some(doi) = &collection.doi {
+        xml.push_str(&format!(
+            "<gmd:identifier><gmd:MD_Identifier><gmd:code>{}</gmd:code></gmd:MD_Identifier></gmd:identifier>",
+            character_string(&format!("https://doi.org/{doi}"))
+        ));
+    }
+    xml.push_str("</gmd:CI_Citation></gmd:citation>\n");
+    xml.push_str(&format!(
+        "      <gmd:abstract>{}</gmd:abstract>\n",
+        character_string(
+            collection
+                .description
+                .as_deref()
+                .unwrap_or(title(collection))
+        )
+    ));
+    if !collection.keywords.is_empty() {
+        xml.push_str("      <gmd:descriptiveKeywords><gmd:MD_Keywords>");
+        for keyword in &collection.keywords {
+            xml.push_str(&format!(
+                "<gmd:keyword>{}</gmd:keyword>",
+                character_string(keyword)
+            ));
+        }
+        xml.push_str("</gmd:MD_Keywords></gmd:descriptiveKeywords>\n");
+    }
+    if let Some(license) = license(collection) {
+        xml.push_str(&format!(
+            "      <gmd:resourceConstraints><gmd:MD_LegalConstraints><gmd:otherConstraints>{}</gmd:otherConstraints></gmd:MD_LegalConstraints></gmd:resourceConstraints>\n",
+            character_string(license)
+        ));
+    }
+    xml.push_str("      <gmd:language><gmd:LanguageCode codeList=\"http://www.loc.gov/standards/iso639-2/\" codeListValue=\"und\"/></gmd:language>\n");
+
+    let bbox = bbox(collection);
+    let interval = interval(collection);
+    if bbox.is_some() || interval.is_some() {
+        xml.push_str("      <gmd:extent><gmd:EX_Extent>");
+        if let Some([west, south, east, north]) = bbox {
+            xml.push_str(&format!(
+                "<gmd:geographicElement><gmd:EX_GeographicBoundingBox>\
+                <gmd:westBoundLongitude><gco:Decimal>{west}</gco:Decimal></gmd:westBoundLongitude>\
+                <gmd:eastBoundLongitude><gco:Decimal>{east}</gco:Decimal></gmd:eastBoundLongitude>\
+                <gmd:southBoundLatitude><gco:Decimal>{south}</gco:Decimal></gmd:southBoundLatitude>\
+                <gmd:northBoundLatitude><gco:Decimal>{north}</gco:Decimal></gmd:northBoundLatitude>\
+                </gmd:EX_GeographicBoundingBox></gmd:geographicElement>"
+            ));
+        }
+        if let Some((start, end)) = interval {
+            let position = |instant: Option<DateTime<Utc>>| match instant {
+                Some(instant) => format!(">{}", instant.format("%Y-%m-%dT%H:%M:%SZ")),
+                None => " indeterminatePosition=\"unknown\">".to_string(),
+            };
+            xml.push_str(&format!(
+                "<gmd:temporalElement><gmd:EX_TemporalExtent><gmd:extent>\
+                <gml:TimePeriod gml:id=\"extent\">\
+                <gml:beginPosition{}</gml:beginPosition>\
+                <gml:endPosition{}</gml:endPosition>\
+                </gml:TimePeriod></gmd:extent></gmd:EX_TemporalExtent></gmd:temporalElement>",
+                position(start),
+                position(end)
+            ));
+        }
+        xml.push_str("</gmd:EX_Extent></gmd:extent>\n");
+    }
+    xml.push_str("    </gmd:MD_DataIdentification>\n  </gmd:identificationInfo>\n");
+
+    // distribution
+    let distributions = distributions(collection);
+    if !distributions.is_empty() {
+        xml.push_str("  <gmd:distributionInfo><gmd:MD_Distribution><gmd:transferOptions><gmd:MD_DigitalTransferOptions>\n");
+        for link in distributions {
+            xml.push_str(&format!(
+                "    <gmd:onLine><gmd:CI_OnlineResource><gmd:linkage><gmd:URL>{}</gmd:URL></gmd:linkage>",
+                escape(&link.href)
+            ));
+            if let Some(media_type) = &link.r#type {
+                xml.push_str(&format!(
+                    "<gmd:protocol>{}</gmd:protocol>",
+                    character_string(media_type)
+                ));
+            }
+            if let Some(title) = &link.title {
+                xml.push_str(&format!("<gmd:name>{}</gmd:name>", character_string(title)));
+            }
+            xml.push_str("</gmd:CI_OnlineResource></gmd:onLine>\n");
+        }
+        xml.push_str("  </gmd:MD_DigitalTransferOptions></gmd:transferOptions></gmd:MD_Distribution></gmd:distributionInfo>\n");
+    }
+
+    xml.push_str("</gmd:MD_Metadata>\n");
+    xml
+}
+
+fn responsible_party(provider: &Provider) -> String {
+    let role = match provider.roles.as_deref().and_then(|roles| roles.first()) {
+        Some(ProviderRole::Licensor) => "owner",
+        Some(ProviderRole::Producer) => "originator",
+        Some(ProviderRole::Processor) => "processor",
+        Some(ProviderRole::Host) => "distributor",
+        None => "pointOfContact",
+    };
+
+    let mut party = format!(
+        "<gmd:CI_ResponsibleParty><gmd:organisationName>{}</gmd:organisationName>",
+        character_string(&provider.name)
+    );
+    if let Some(url) = &provider.url {
+        party.push_str(&format!(
+            "<gmd:contactInfo><gmd:CI_Contact><gmd:onlineResource><gmd:CI_OnlineResource><gmd:linkage><gmd:URL>{}</gmd:URL></gmd:linkage></gmd:CI_OnlineResource></gmd:onlineResource></gmd:CI_Contact></gmd:contactInfo>",
+            escape(url)
+        ));
+    }
+    party.push_str(&format!(
+        "<gmd:role><gmd:CI_RoleCode codeList=\"http://standards.iso.org/iso/19139/resources/gmxCodelists.xml#CI_RoleCode\" codeListValue=\"{role}\">{role}</gmd:CI_RoleCode></gmd:role></gmd:CI_ResponsibleParty>"
+    ));
+    party
+}
+
+fn title(collection: &Collection) -> &str {
+    collection.title.as_deref().unwrap_or(&collection.id)
+}
+
+fn license(collection: &Collection) -> Option<&str> {
+    #[cfg(not(feature = "stac"))]
+    let license = collection.license.as_deref();
+    #[cfg(feature = "stac")]
+    let license = Some(collection.license.as_str());
+    license.filter(|l| !l.trim().is_empty())
+}
+
+/// License as url, SPDX identifiers refer to the SPDX license list
+fn license_url(license: &str) -> String {
+    if license.contains("://") {
+        license.to_owned()
+    } else {
+        format!("https://spdx.org/licenses/{license}")
+    }
+}
+
+fn has_role(provider: &Provider, role: ProviderRole) -> bool {
+    provider
+        .roles
+        .as_ref()
+        .is_some_and(|roles| roles.contains(&role))
+}
+
+fn self_href(collection: &Collection) -> Option<String> {
+    collection
+        .links
+        .iter()
+        .find(|link| link.rel == SELF)
+        .map(|link| link.href.split('?').next().unwrap_or_default().to_owned())
+}
+
+/// Bounding box in `CRS84` as west, south, east, north
+fn bbox(collection: &Collection) -> Option<[f64; 4]> {
+    let spatial = collection.extent.as_ref()?.spatial.as_ref()?;
+    spatial.bbox.first().map(|bbox| bbox.to_2d())
+}
+
+/// Start and end of the temporal extent, either may be open
+type Interval = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+fn interval(collection: &Collection) -> Option<Interval> {
+    let temporal = collection.extent.as_ref()?.temporal.as_ref()?;
+    let interval = temporal.interval.first()?;
+    let start = interval.first().copied().flatten();
+    let end = interval.get(1).copied().flatten();
+    (start.is_some() || end.is_some()).then_some((start, end))
+}
+
+fn distributions(collection: &Collection) -> Vec<&Link> {
+    collection
+        .links
+        .iter()
+        .filter(|link| link.rel == ITEMS || link.rel == DATA)
+        .collect()
+}
+
+/// Turtle string literal
+fn literal(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    )
+}
+
+/// Turtle IRI, characters not allowed in IRIs are percent encoded
+fn iri(value: &str) -> String {
+    let mut iri = String::new();
+    for c in value.chars() {
+        match c {
+            '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\' | ' ' => {
+                iri.push_str(&format!("%{:02X}", c as u32))
+            }
+            c if c.is_control() => iri.push_str(&format!("%{:02X}", c as u32)),
+            c => iri.push(c),
+        }
+    }
+    format!("<{iri}>")
+}
+
+fn character_string(value: &str) -> String {
+    format!(
+        "<gco:CharacterString>{}</gco:CharacterString>",
+        escape(value)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn collection() -> Collection {
+        serde_json::from_value(json!({
+            "id": "hydrants",
+            "title": "Hydrants \"Basel\"",
+            "description": "Fire hydrants & valves",
+            "keywords": ["water", "fire"],
+            "license": "CC-BY-4.0",
+            "doi": "10.5281/zenodo.1234",
+            "providers": [{ "name": "IWB", "roles": ["producer"], "url": "https://iwb.ch" }],
+            "extent": {
+                "spatial": { "bbox": [[7.5, 47.5, 7.7, 47.6]] },
+                "temporal": { "interval": [["2020-01-01T00:00:00Z", null]] }
+            },
+            "links": [
+                { "href": "https://api.example.com/collections/hydrants?f=json", "rel": "self" },
+                {
+                    "href": "https://api.example.com/collections/hydrants/items",
+                    "rel": "items",
+                    "type": "application/geo+json",
+                    "title": "Items"
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn dcat_record() {
+        let turtle = dcat(&collection());
+
+        assert!(turtle
+            .contains("<https://api.example.com/collections/hydrants>\n    a dcat:Dataset ;\n"));
+        assert!(turtle.contains(r#"dct:title "Hydrants \"Basel\"" ;"#));
+        assert!(turtle.contains(r#"dcat:keyword "water", "fire" ;"#));
+        assert!(turtle.contains("dct:license <https://spdx.org/licenses/CC-BY-4.0> ;"));
+        assert!(turtle.contains(
+            r#"dct:creator [ a foaf:Agent ; foaf:name "IWB" ; foaf:homepage <https://iwb.ch> ]"#
+        ));
+        assert!(turtle.contains(r#"dcat:startDate "2020-01-01T00:00:00+00:00"^^xsd:dateTime ]"#));
+        assert!(turtle.contains("POLYGON((7.5 47.5, 7.7 47.5, 7.7 47.6, 7.5 47.6, 7.5 47.5))"));
+        assert!(turtle.contains(
+            "dcat:mediaType <https://www.iana.org/assignments/media-types/application/geo+json>"
+        ));
+        assert!(turtle.ends_with(" .\n"));
+    }
+
+    #[test]
+    fn iso19139_record() {
+        let date = "2024-07-01T12:00:00Z".parse().unwrap();
+        let xml = iso19139(&collection(), date);
+
+        assert!(xml.contains(
+            "<gmd:title><gco:CharacterString>Hydrants &quot;Basel&quot;</gco:CharacterString></gmd:title>"
+        ));
+        assert!(xml.contains("Fire hydrants &amp; valves"));
+        assert!(xml.contains(r#"codeListValue="originator">originator"#));
+        assert!(xml.contains("<gmd:dateStamp><gco:DateTime>2024-07-01T12:00:00Z</gco:DateTime>"));
+        assert!(xml.contains("<gmd:westBoundLongitude><gco:Decimal>7.5</gco:Decimal>"));
+        assert!(
+            xml.contains(r#"<gml:endPosition indeterminatePosition="unknown"></gml:endPosition>"#)
+        );
+        assert!(
+            xml.contains("<gmd:URL>https://api.example.com/collections/hydrants/items</gmd:URL>")
+        );
+        assert!(xml.ends_with("</gmd:MD_Metadata>\n"));
+    }
+}
@@ -3,6 +3,7 @@ use serde_json::Value;
 
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DescriptionType {
     pub title: Option<String>,
     pub description: Option<String>,
@@ -17,12 +18,14 @@ pub struct DescriptionType {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AdditionalParameter {
     pub name: String,
     pub value: Vec<Value>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Metadata {
     pub title: Option<String>,
     pub role: Option<String>,
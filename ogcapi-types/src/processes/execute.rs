@@ -6,6 +6,7 @@ use serde_json::{Map, Value};
 use crate::common::{Bbox, Link};
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Execute {
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub inputs: HashMap<String, Input>,
@@ -17,6 +18,7 @@ pub struct Execute {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum Input {
     InlineOrRefData(InlineOrRefData),
@@ -24,6 +26,7 @@ pub enum Input {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum InlineOrRefData {
     InputValueNoObject(InputValueNoObject),
@@ -32,6 +35,7 @@ pub enum InlineOrRefData {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum InputValueNoObject {
     String(String),
@@ -45,12 +49,14 @@ pub enum InputValueNoObject {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BoundingBox {
     pub bbox: Bbox,
     pub crs: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct QualifiedInputValue {
     pub value: InputValue,
     #[serde(flatten)]
@@ -58,6 +64,7 @@ pub struct QualifiedInputValue {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum InputValue {
     InputValueNoObject(InputValueNoObject),
@@ -65,6 +72,7 @@ pub enum InputValue {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Output {
     pub format: Option<Format>,
@@ -73,6 +81,7 @@ pub struct Output {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Format {
     pub media_type: Option<String>,
@@ -81,6 +90,7 @@ pub struct Format {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum Schema {
     String(String),
@@ -88,6 +98,7 @@ pub enum Schema {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum TransmissionMode {
     #[default]
@@ -96,6 +107,7 @@ pub enum TransmissionMode {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum Response {
     #[default]
@@ -104,6 +116,7 @@ pub enum Response {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Subscriber {
     pub success_uri: String,
@@ -5,6 +5,7 @@ use super::DescriptionType;
 
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct InputDescription {
     #[serde(flatten)]
@@ -17,6 +18,7 @@ pub struct InputDescription {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum MaxOccurs {
     Integer(u64),
@@ -7,7 +7,8 @@ use crate::common::Links;
 
 use super::execute::InlineOrRefData;
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct StatusInfo {
     #[serde(rename = "processID", alias = "process_id")]
     pub process_id: Option<String>,
@@ -23,7 +24,8 @@ pub struct StatusInfo {
     pub links: Links,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum StatusCode {
     Accepted,
@@ -39,8 +41,17 @@ impl Default for StatusCode {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// List of jobs, latest first
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct JobList {
+    pub jobs: Vec<StatusInfo>,
+    pub links: Links,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Results {
     #[serde(flatten)]
-    results: HashMap<String, InlineOrRefData>,
+    pub results: HashMap<String, InlineOrRefData>,
 }
@@ -13,5 +13,5 @@ pub use input_description::{InputDescription, MaxOccurs};
 pub use job::*;
 pub use output_description::OutputDescription;
 pub use process::{Process, ProcessList};
-pub use process_summary::ProcessSummary;
-pub use query::ProcessQuery;
+pub use process_summary::{JobControlOptions, ProcessSummary};
+pub use query::{JobQuery, ProcessQuery};
@@ -1,17 +1,29 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::common::Links;
+use crate::{
+    common::Links,
+    features::{check_value, Subject},
+};
 
-use super::{DescriptionType, InputDescription, MaxOccurs, OutputDescription, ProcessSummary};
+use super::{
+    DescriptionType, Execute, InputDescription, MaxOccurs, OutputDescription, ProcessSummary,
+};
+
+const INPUTS: Subject = Subject {
+    all: "Inputs",
+    one: "input",
+};
 
 /// Information about the available processes
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ProcessList {
     pub processes: Vec<ProcessSummary>,
     pub links: Links,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Process {
     #[serde(flatten)]
     pub summary: ProcessSummary,
@@ -42,4 +54,119 @@ impl Process {
             },
         }
     }
+
+    /// Check the inputs of an execution against the schema of the inputs,
+    /// listing all violations
+    ///
+    /// Besides the keywords supported by [`crate::features::PropertiesSchema`],
+    /// the `contentMediaType` of an input restricts the media type of
+    /// qualified values and links.
+    pub fn check(&self, execute: &Execute) -> Vec<String> {
+        let Value::Object(schema) = &self.inputs.schema else {
+            return Vec::new();
+        };
+        let inputs = serde_json::to_value(&execute.inputs).unwrap_or_default();
+
+        let mut violations = Vec::new();
+        check_value(schema, &inputs, "", INPUTS, &mut violations);
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (id, input) in execute.inputs.iter() {
+            let media_type = properties
+                .and_then(|properties| properties.get(id))
+                .and_then(|property| property.get("contentMediaType"))
+                .and_then(Value::as_str);
+            let Some(media_type) = media_type else {
+                continue;
+            };
+
+            let input = serde_json::to_value(input).unwrap_or_default();
+            let values = match &input {
+                Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+            let mismatch = values.into_iter().any(|value| {
+                ["mediaType", "type"]
+                    .iter()
+                    .find_map(|key| value.get(key).and_then(Value::as_str))
+                    .is_some_and(|given| given != media_type)
+            });
+            if mismatch {
+                violations.push(format!(
+                    "Input `{id}` has to be of media type `{media_type}`"
+                ));
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn process() -> Process {
+        Process::new(
+            "import",
+            "0.1.0",
+            &json!({
+                "type": "object",
+                "required": ["collection", "data"],
+                "properties": {
+                    "collection": { "type": "string", "minLength": 1 },
+                    "layers": { "type": "array", "items": { "type": "string" }, "maxItems": 2 },
+                    "data": { "contentMediaType": "application/geopackage+sqlite3" }
+                }
+            }),
+            &json!({}),
+        )
+    }
+
+    #[test]
+    fn valid_inputs() {
+        let execute: Execute = serde_json::from_value(json!({
+            "inputs": {
+                "collection": "buildings",
+                "layers": ["buildings"],
+                "data": {
+                    "href": "https://example.com/buildings.gpkg",
+                    "rel": "data",
+                    "type": "application/geopackage+sqlite3"
+                }
+            }
+        }))
+        .unwrap();
+
+        assert!(process().check(&execute).is_empty());
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        let execute: Execute = serde_json::from_value(json!({
+            "inputs": {
+                "collection": 1,
+                "layers": ["a", "b", "c"],
+                "data": { "value": "UEsDBA==", "mediaType": "application/zip" }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            process().check(&execute),
+            [
+                "Input `collection` has to be of type `string`",
+                "Input `layers` has to have at most 2 items",
+                "Input `data` has to be of media type `application/geopackage+sqlite3`",
+            ]
+        );
+
+        let execute: Execute = serde_json::from_value(json!({ "inputs": {} })).unwrap();
+        assert_eq!(
+            process().check(&execute),
+            ["Missing input `collection`", "Missing input `data`"]
+        );
+    }
 }
@@ -5,6 +5,7 @@ use crate::common::Links;
 use super::DescriptionType;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ProcessSummary {
     pub id: String,
@@ -20,6 +21,7 @@ pub struct ProcessSummary {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub enum JobControlOptions {
     SyncExecute,
@@ -28,6 +30,11 @@ pub enum JobControlOptions {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "ProcessTransmissionMode")
+)]
 #[serde(rename_all = "lowercase")]
 pub enum TransmissionMode {
     Value,
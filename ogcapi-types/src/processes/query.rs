@@ -1,7 +1,20 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ProcessQuery {
     pub limit: Option<usize>,
     pub offset: Option<usize>,
 }
+
+/// Query parameters of the job list
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct JobQuery {
+    /// Only jobs of the process
+    #[serde(rename = "processID")]
+    pub process_id: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
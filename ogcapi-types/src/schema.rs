@@ -0,0 +1,149 @@
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::Schema,
+    JsonSchema, Map,
+};
+use serde_json::json;
+
+use crate::{auth, common, cql2, edr, features, joins, processes, styles, tasks, tiles, webhooks};
+
+/// JSON Schemas of the public types keyed by name, as used for the
+/// `components/schemas` of an OpenAPI document
+///
+/// Nested types are included and referenced as `#/components/schemas/{name}`.
+pub fn schemas() -> Map<String, Schema> {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+
+    add::<auth::User>(&mut gen);
+    add::<auth::ApiKey>(&mut gen);
+    add::<auth::UsageReport>(&mut gen);
+
+    add::<common::LandingPage>(&mut gen);
+    add::<common::Conformance>(&mut gen);
+    add::<common::Collection>(&mut gen);
+    add::<common::Collections>(&mut gen);
+    add::<common::Exception>(&mut gen);
+    add::<common::Query>(&mut gen);
+
+    add::<cql2::Expr>(&mut gen);
+
+    add::<edr::Query>(&mut gen);
+    add::<edr::DataQueries>(&mut gen);
+    add::<edr::ParameterNames>(&mut gen);
+
+    add::<features::Feature>(&mut gen);
+    add::<features::FeatureChange>(&mut gen);
+    add::<features::FeatureCollection>(&mut gen);
+    add::<features::Query>(&mut gen);
+    add::<features::Queryables>(&mut gen);
+
+    add::<joins::DataFiles>(&mut gen);
+    add::<joins::Joins>(&mut gen);
+
+    add::<processes::ProcessList>(&mut gen);
+    add::<processes::Process>(&mut gen);
+    add::<processes::Execute>(&mut gen);
+    add::<processes::StatusInfo>(&mut gen);
+    add::<processes::Results>(&mut gen);
+
+    add::<styles::Styles>(&mut gen);
+
+    add::<tasks::Tasks>(&mut gen);
+
+    add::<tiles::TileSets>(&mut gen);
+    add::<tiles::TileSet>(&mut gen);
+    add::<tiles::TileMatrixSets>(&mut gen);
+    add::<tiles::TileMatrixSet>(&mut gen);
+
+    add::<webhooks::Webhook>(&mut gen);
+    add::<webhooks::Event>(&mut gen);
+    add::<webhooks::Delivery>(&mut gen);
+
+    #[cfg(feature = "stac")]
+    {
+        add::<crate::stac::Catalog>(&mut gen);
+        add::<crate::stac::SearchBody>(&mut gen);
+    }
+
+    gen.take_definitions()
+}
+
+fn add<T: JsonSchema>(gen: &mut SchemaGenerator) {
+    // registers the type and its dependencies as definitions
+    gen.subschema_for::<T>();
+}
+
+/// Stand-in for `GeoJSON` geometries, which lack a `JsonSchema` implementation
+pub(crate) struct Geometry;
+
+impl JsonSchema for Geometry {
+    fn schema_name() -> String {
+        "Geometry".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        serde_json::from_value(json!({
+            "type": "object",
+            "required": ["type"],
+            "properties": {
+                "type": {
+                    "type": "string",
+                    "enum": [
+                        "Point",
+                        "MultiPoint",
+                        "LineString",
+                        "MultiLineString",
+                        "Polygon",
+                        "MultiPolygon",
+                        "GeometryCollection"
+                    ]
+                },
+                "coordinates": {
+                    "type": "array"
+                },
+                "geometries": {
+                    "type": "array",
+                    "items": {
+                        "type": "object"
+                    }
+                },
+                "bbox": {
+                    "type": "array",
+                    "items": {
+                        "type": "number"
+                    }
+                }
+            }
+        }))
+        .expect("valid geometry schema")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn schemas() {
+        let schemas = super::schemas();
+
+        for name in [
+            "Collection",
+            "Feature",
+            "FeatureQuery",
+            "EdrQuery",
+            "CollectionQuery",
+            "Styles",
+            "Process",
+            "Link",
+            "Bbox",
+            "Geometry",
+        ] {
+            assert!(schemas.contains_key(name), "missing `{name}`");
+        }
+
+        let feature = serde_json::to_value(&schemas["Feature"]).unwrap();
+        assert_eq!(
+            feature["properties"]["geometry"]["$ref"],
+            "#/components/schemas/Geometry"
+        );
+    }
+}
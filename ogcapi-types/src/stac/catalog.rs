@@ -7,6 +7,7 @@ use crate::common::Links;
 /// `Collection`, and `Item` objects.
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Catalog {
     /// Set to `Catalog` if this Catalog only implements the Catalog spec.
     #[serde(default = "crate::stac::catalog")]
@@ -7,6 +7,7 @@ use super::Catalog;
 
 /// Type of STAC entity.
 #[derive(Deserialize, Serialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum StacEntity {
     Catalog(Box<Catalog>),
@@ -1,17 +1,15 @@
 mod asset;
 mod catalog;
 mod entity;
-mod provider;
 mod search;
 
 pub use asset::Asset;
 pub use catalog::Catalog;
 pub use entity::StacEntity;
-pub use provider::{Provider, ProviderRole};
 pub use search::{SearchBody, SearchParams};
 
 #[doc(inline)]
-pub use crate::common::Collection;
+pub use crate::common::{Collection, Provider, ProviderRole};
 
 #[doc(inline)]
 pub use crate::features::Feature as Item;
@@ -7,6 +7,7 @@ use crate::common::{Bbox, Datetime};
 /// Search parameters for searching a SpatioTemporal Asset Catalog.
 #[serde_with::serde_as]
 #[derive(Serialize, Deserialize, Default, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SearchParams {
     pub limit: Option<u64>,
     pub offset: Option<u64>,
@@ -18,6 +19,10 @@ pub struct SearchParams {
     pub datetime: Option<Datetime>,
     #[serde(default)]
     #[serde_as(as = "Option<DisplayFromStr>")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "Option<crate::schema::Geometry>")
+    )]
     pub intersects: Option<Geometry>,
     #[serde(default)]
     #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
@@ -87,6 +92,7 @@ impl SearchParams {
 /// Search body for searching a SpatioTemporal Asset Catalog.
 #[serde_with::serde_as]
 #[derive(Serialize, Deserialize, Default, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SearchBody {
     pub limit: Option<u64>,
     pub offset: Option<u64>,
@@ -95,6 +101,10 @@ pub struct SearchBody {
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub datetime: Option<Datetime>,
     #[serde(default)]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "Option<crate::schema::Geometry>")
+    )]
     pub intersects: Option<Geometry>,
     #[serde(default)]
     pub ids: Option<Vec<String>>,
@@ -0,0 +1,492 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::raster::{attribute, color_expression, element, elements, parse_color, text, ColorMap};
+
+/// Legend of a style, with an entry per layer or rule and per class of their
+/// colors
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Legend {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub entries: Vec<LegendEntry>,
+}
+
+/// Entry of a legend, colors as `#rrggbb` or `#rrggbbaa`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct LegendEntry {
+    pub label: String,
+    pub symbol: LegendSymbol,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stroke: Option<String>,
+    /// Width of the stroke in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stroke_width: Option<f64>,
+}
+
+/// Kind of symbol of a legend entry
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum LegendSymbol {
+    Fill,
+    Line,
+    Point,
+    Raster,
+}
+
+impl Legend {
+    /// Legend of a Mapbox GL style, or of an SLD stored as string
+    ///
+    /// Mapbox GL layers with a constant color get a single entry, labeled by
+    /// the `title` of their `metadata` or else their id. Colors of `match`,
+    /// `step` and `interpolate` expressions get an entry per class.
+    pub fn from_stylesheet(stylesheet: &Value) -> Legend {
+        match stylesheet {
+            Value::String(sld) => Legend::from_sld(sld),
+            Value::Object(_) => Legend::from_mapbox(stylesheet),
+            _ => Legend::default(),
+        }
+    }
+
+    fn from_mapbox(style: &Value) -> Legend {
+        let mut entries = Vec::new();
+
+        let layers = style["layers"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for layer in layers {
+            if layer["layout"]["visibility"] == "none" {
+                continue;
+            }
+
+            let label = layer["metadata"]["title"]
+                .as_str()
+                .or(layer["id"].as_str())
+                .unwrap_or_default();
+            let paint = &layer["paint"];
+
+            let (symbol, color, opacity, stroke, stroke_width) = match layer["type"].as_str() {
+                Some("fill") => (
+                    LegendSymbol::Fill,
+                    "fill-color",
+                    "fill-opacity",
+                    Some("fill-outline-color"),
+                    None,
+                ),
+                Some("fill-extrusion") => (
+                    LegendSymbol::Fill,
+                    "fill-extrusion-color",
+                    "fill-extrusion-opacity",
+                    None,
+                    None,
+                ),
+                Some("line") => (
+                    LegendSymbol::Line,
+                    "line-color",
+                    "line-opacity",
+                    None,
+                    Some("line-width"),
+                ),
+                Some("circle") => (
+                    LegendSymbol::Point,
+                    "circle-color",
+                    "circle-opacity",
+                    Some("circle-stroke-color"),
+                    Some("circle-stroke-width"),
+                ),
+                Some("raster") => {
+                    let stops = match color_expression(&paint["raster-color"]) {
+                        Some(ColorMap::Ramp(stops) | ColorMap::Values(stops)) => stops
+                            .into_iter()
+                            .map(|(value, color)| (format!("{label}: {value}"), Some(color)))
+                            .collect(),
+                        Some(ColorMap::Step(stops)) => {
+                            let values: Vec<f64> = stops.iter().map(|(value, _)| *value).collect();
+                            intervals(label, &values)
+                                .into_iter()
+                                .zip(stops.into_iter().map(|(_, color)| Some(color)))
+                                .collect()
+                        }
+                        None => vec![(label.to_owned(), None)],
+                    };
+                    entries.extend(stops.into_iter().map(|(label, color)| LegendEntry {
+                        label,
+                        symbol: LegendSymbol::Raster,
+                        fill: color.map(hex),
+                        stroke: None,
+                        stroke_width: None,
+                    }));
+                    continue;
+                }
+                // backgrounds, labels, heatmaps and hillshades
+                _ => continue,
+            };
+
+            let opacity = paint[opacity].as_f64().unwrap_or(1.0);
+            let stroke = stroke
+                .and_then(|stroke| paint[stroke].as_str())
+                .and_then(parse_color)
+                .map(hex);
+            let stroke_width = stroke_width.map(|width| paint[width].as_f64().unwrap_or(1.0));
+
+            let colors = match &paint[color] {
+                Value::Null => vec![(label.to_owned(), Some([0, 0, 0, 255]))],
+                value => color_classes(label, value),
+            };
+            entries.extend(colors.into_iter().map(|(label, color)| {
+                let fill = color.map(|[r, g, b, a]| {
+                    hex([
+                        r,
+                        g,
+                        b,
+                        (f64::from(a) * opacity.clamp(0.0, 1.0)).round() as u8,
+                    ])
+                });
+                match symbol {
+                    // lines are drawn with their color
+                    LegendSymbol::Line => LegendEntry {
+                        label,
+                        symbol,
+                        fill: None,
+                        stroke: fill,
+                        stroke_width,
+                    },
+                    _ => LegendEntry {
+                        label,
+                        symbol,
+                        fill,
+                        stroke: stroke.to_owned(),
+                        stroke_width,
+                    },
+                }
+            }));
+        }
+
+        Legend {
+            title: style["name"].as_str().map(ToOwned::to_owned),
+            entries,
+        }
+    }
+
+    fn from_sld(sld: &str) -> Legend {
+        let mut entries = Vec::new();
+
+        for (_, rule) in elements(sld, "Rule") {
+            let label = element(rule, "Title")
+                .or_else(|| element(rule, "Name"))
+                .map(|label| text(label).trim().to_owned())
+                .unwrap_or_default();
+
+            if let Some(symbolizer) = element(rule, "RasterSymbolizer") {
+                let color_map = element(symbolizer, "ColorMap").unwrap_or_default();
+                for (tag, _) in elements(color_map, "ColorMapEntry") {
+                    let Some(mut color) = attribute(tag, "color").and_then(parse_color) else {
+                        continue;
+                    };
+                    if let Some(opacity) = attribute(tag, "opacity").and_then(|o| o.parse().ok()) {
+                        color[3] = (255.0 * f64::clamp(opacity, 0.0, 1.0)).round() as u8;
+                    }
+                    let value = attribute(tag, "label")
+                        .or_else(|| attribute(tag, "quantity"))
+                        .unwrap_or_default();
+                    entries.push(LegendEntry {
+                        label: match label.as_str() {
+                            "" => value.to_owned(),
+                            label => format!("{label}: {value}"),
+                        },
+                        symbol: LegendSymbol::Raster,
+                        fill: Some(hex(color)),
+                        stroke: None,
+                        stroke_width: None,
+                    });
+                }
+                continue;
+            }
+
+            let (symbol, symbolizer) = if let Some(s) = element(rule, "PolygonSymbolizer") {
+                (LegendSymbol::Fill, s)
+            } else if let Some(s) = element(rule, "LineSymbolizer") {
+                (LegendSymbol::Line, s)
+            } else if let Some(s) = element(rule, "PointSymbolizer") {
+                (LegendSymbol::Point, s)
+            } else {
+                continue;
+            };
+
+            let fill = element(symbolizer, "Fill").and_then(|fill| {
+                sld_color(
+                    parameter(fill, "fill").unwrap_or("#808080"),
+                    parameter(fill, "fill-opacity"),
+                )
+            });
+            let stroke = element(symbolizer, "Stroke");
+            let stroke_width = stroke
+                .map(|stroke| parameter(stroke, "stroke-width").and_then(|w| w.parse().ok()))
+                .map(|width| width.unwrap_or(1.0));
+            let stroke = stroke.and_then(|stroke| {
+                sld_color(
+                    parameter(stroke, "stroke").unwrap_or("#000000"),
+                    parameter(stroke, "stroke-opacity"),
+                )
+            });
+
+            entries.push(LegendEntry {
+                label,
+                symbol,
+                fill,
+                stroke,
+                stroke_width,
+            });
+        }
+
+        Legend {
+            title: element(sld, "NamedLayer")
+                .or_else(|| element(sld, "UserLayer"))
+                .and_then(|layer| element(layer, "Name"))
+                .map(|name| text(name).trim().to_owned()),
+            entries,
+        }
+    }
+}
+
+/// Classes of a color, one per value of a `match` and per stop of a `step` or
+/// `interpolate` expression
+fn color_classes(label: &str, color: &Value) -> Vec<(String, Option<[u8; 4]>)> {
+    let constant = |value: &Value| value.as_str().and_then(parse_color);
+
+    let Some(expression) = color.as_array() else {
+        return vec![(label.to_owned(), constant(color))];
+    };
+
+    match expression.first().and_then(Value::as_str) {
+        Some("match") if expression.len() >= 4 => {
+            let cases = &expression[2..expression.len() - 1];
+            let mut classes: Vec<_> = cases
+                .chunks_exact(2)
+                .map(|case| {
+                    let value = match &case[0] {
+                        Value::Array(values) => values
+                            .iter()
+                            .map(value_label)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        value => value_label(value),
+                    };
+                    (format!("{label}: {value}"), constant(&case[1]))
+                })
+                .collect();
+            classes.push((
+                format!("{label}: other"),
+                constant(&expression[expression.len() - 1]),
+            ));
+            classes
+        }
+        Some("step") if expression.len() >= 3 => {
+            let mut stops = vec![(f64::NEG_INFINITY, constant(&expression[2]))];
+            stops.extend(
+                expression[3..]
+                    .chunks_exact(2)
+                    .filter_map(|stop| Some((stop[0].as_f64()?, constant(&stop[1])))),
+            );
+            let values: Vec<f64> = stops.iter().map(|(value, _)| *value).collect();
+            intervals(label, &values)
+                .into_iter()
+                .zip(stops.into_iter().map(|(_, color)| color))
+                .collect()
+        }
+        Some("interpolate") if expression.len() >= 3 => expression[3..]
+            .chunks_exact(2)
+            .map(|stop| {
+                (
+                    format!("{label}: {}", value_label(&stop[0])),
+                    constant(&stop[1]),
+                )
+            })
+            .collect(),
+        _ => vec![(label.to_owned(), None)],
+    }
+}
+
+/// Labels of the intervals between the values of the stops of a step, the
+/// first from minus infinity
+fn intervals(label: &str, values: &[f64]) -> Vec<String> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| match (i, values.get(i + 1)) {
+            (0, Some(next)) => format!("{label}: < {next}"),
+            (_, Some(next)) => format!("{label}: {value} – {next}"),
+            (_, None) => format!("{label}: ≥ {value}"),
+        })
+        .collect()
+}
+
+fn value_label(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_owned(),
+        value => value.to_string(),
+    }
+}
+
+/// Value of an SLD `CssParameter` or SE `SvgParameter`
+fn parameter<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    elements(xml, "CssParameter")
+        .chain(elements(xml, "SvgParameter"))
+        .find(|(tag, _)| attribute(tag, "name") == Some(name))
+        .map(|(_, content)| text(content).trim())
+}
+
+fn sld_color(color: &str, opacity: Option<&str>) -> Option<String> {
+    let mut color = parse_color(color)?;
+    if let Some(opacity) = opacity.and_then(|o| o.parse::<f64>().ok()) {
+        color[3] = (255.0 * opacity.clamp(0.0, 1.0)).round() as u8;
+    }
+    Some(hex(color))
+}
+
+fn hex([r, g, b, a]: [u8; 4]) -> String {
+    match a {
+        255 => format!("#{r:02x}{g:02x}{b:02x}"),
+        a => format!("#{r:02x}{g:02x}{b:02x}{a:02x}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn mapbox() {
+        let style = json!({
+            "version": 8,
+            "name": "Land use",
+            "sources": {},
+            "layers": [
+                { "id": "background", "type": "background" },
+                {
+                    "id": "landuse",
+                    "type": "fill",
+                    "source": "landuse",
+                    "paint": {
+                        "fill-color": [
+                            "match", ["get", "class"],
+                            "forest", "#228b22",
+                            ["farmland", "meadow"], "#ffff00",
+                            "#cccccc"
+                        ],
+                        "fill-opacity": 0.5,
+                        "fill-outline-color": "#000"
+                    }
+                },
+                {
+                    "id": "roads",
+                    "type": "line",
+                    "source": "roads",
+                    "metadata": { "title": "Roads" },
+                    "paint": { "line-color": "#ff0000", "line-width": 2 }
+                },
+                {
+                    "id": "hidden",
+                    "type": "circle",
+                    "layout": { "visibility": "none" }
+                }
+            ]
+        });
+
+        let legend = Legend::from_stylesheet(&style);
+        assert_eq!(legend.title.as_deref(), Some("Land use"));
+        assert_eq!(legend.entries.len(), 4);
+        assert_eq!(legend.entries[0].label, "landuse: forest");
+        assert_eq!(legend.entries[0].fill.as_deref(), Some("#228b2280"));
+        assert_eq!(legend.entries[0].stroke.as_deref(), Some("#000000"));
+        assert_eq!(legend.entries[1].label, "landuse: farmland, meadow");
+        assert_eq!(legend.entries[2].label, "landuse: other");
+
+        assert_eq!(
+            serde_json::to_value(&legend.entries[3]).unwrap(),
+            json!({
+                "label": "Roads",
+                "symbol": "line",
+                "stroke": "#ff0000",
+                "strokeWidth": 2.0
+            })
+        );
+    }
+
+    #[test]
+    fn step() {
+        let classes = color_classes(
+            "density",
+            &json!(["step", ["get", "density"], "#fff", 10, "#888", 100, "#000"]),
+        );
+        let labels: Vec<_> = classes.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(
+            labels,
+            ["density: < 10", "density: 10 – 100", "density: ≥ 100"]
+        );
+        assert_eq!(classes[1].1, Some([136, 136, 136, 255]));
+    }
+
+    #[test]
+    fn sld() {
+        let sld = r##"<StyledLayerDescriptor version="1.0.0" xmlns="http://www.opengis.net/sld">
+              <NamedLayer>
+                <Name>parcels</Name>
+                <UserStyle>
+                  <FeatureTypeStyle>
+                    <Rule>
+                      <Title>Building zone</Title>
+                      <PolygonSymbolizer>
+                        <Fill>
+                          <CssParameter name="fill">#ff8800</CssParameter>
+                          <CssParameter name="fill-opacity">0.5</CssParameter>
+                        </Fill>
+                        <Stroke>
+                          <CssParameter name="stroke">#000000</CssParameter>
+                          <CssParameter name="stroke-width">0.5</CssParameter>
+                        </Stroke>
+                      </PolygonSymbolizer>
+                    </Rule>
+                    <Rule>
+                      <Name>border</Name>
+                      <LineSymbolizer>
+                        <Stroke><CssParameter name="stroke">#0000ff</CssParameter></Stroke>
+                      </LineSymbolizer>
+                    </Rule>
+                  </FeatureTypeStyle>
+                </UserStyle>
+              </NamedLayer>
+            </StyledLayerDescriptor>"##;
+
+        let legend = Legend::from_stylesheet(&Value::from(sld));
+        assert_eq!(legend.title.as_deref(), Some("parcels"));
+        assert_eq!(
+            legend.entries,
+            [
+                LegendEntry {
+                    label: "Building zone".to_owned(),
+                    symbol: LegendSymbol::Fill,
+                    fill: Some("#ff880080".to_owned()),
+                    stroke: Some("#000000".to_owned()),
+                    stroke_width: Some(0.5),
+                },
+                LegendEntry {
+                    label: "border".to_owned(),
+                    symbol: LegendSymbol::Line,
+                    fill: None,
+                    stroke: Some("#0000ff".to_owned()),
+                    stroke_width: Some(1.0),
+                },
+            ]
+        );
+    }
+}
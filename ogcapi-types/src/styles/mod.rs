@@ -1,5 +1,12 @@
+mod legend;
 mod mapbox;
+mod raster;
 mod symcore;
+mod validation;
+
+pub use legend::{Legend, LegendEntry, LegendSymbol};
+pub use raster::{parse_color, ColorMap, RasterStyle};
+pub use validation::{StyleReference, StyleValidation, StyleWarning};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -7,11 +14,13 @@ use serde_json::Value;
 use crate::common::Links;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Styles {
     pub styles: Vec<Style>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Style {
     pub id: String,
     pub title: Option<String>,
@@ -19,6 +28,7 @@ pub struct Style {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Stylesheet {
     pub id: String,
     pub value: Value,
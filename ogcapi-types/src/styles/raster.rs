@@ -0,0 +1,423 @@
+use serde_json::Value;
+
+/// Rendering of rasters on the server, from the `raster` layer of a Mapbox GL
+/// style or the `RasterSymbolizer` of an SLD stored as string
+#[derive(Debug, Clone, PartialEq)]
+pub struct RasterStyle {
+    /// Opacity from `0` to `1`
+    pub opacity: f64,
+    /// Minimum and maximum brightness from `0` to `1`
+    pub brightness: (f64, f64),
+    /// Contrast from `-1` to `1`
+    pub contrast: f64,
+    /// Saturation from `-1` to `1`
+    pub saturation: f64,
+    /// Colors of the values of the first band, which otherwise is rendered
+    /// as grayscale or with the next two as RGB
+    pub color_map: Option<ColorMap>,
+}
+
+impl Default for RasterStyle {
+    fn default() -> Self {
+        RasterStyle {
+            opacity: 1.0,
+            brightness: (0.0, 1.0),
+            contrast: 0.0,
+            saturation: 0.0,
+            color_map: None,
+        }
+    }
+}
+
+/// Colors of raster values, as RGBA
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorMap {
+    /// Colors interpolated between the values, clamped outside
+    Ramp(Vec<(f64, [u8; 4])>),
+    /// Color of the greatest value not greater than the raster value, none
+    /// below the first value
+    Step(Vec<(f64, [u8; 4])>),
+    /// Colors of exact values only
+    Values(Vec<(f64, [u8; 4])>),
+}
+
+impl ColorMap {
+    /// Color of a raster value, `None` if it has none
+    pub fn color(&self, value: f64) -> Option<[u8; 4]> {
+        match self {
+            ColorMap::Ramp(stops) => {
+                let (first, last) = (stops.first()?, stops.last()?);
+                if value <= first.0 {
+                    return Some(first.1);
+                }
+                if value >= last.0 {
+                    return Some(last.1);
+                }
+                let i = stops.iter().position(|(v, _)| *v > value)?;
+                let ((v0, c0), (v1, c1)) = (stops[i - 1], stops[i]);
+                let t = (value - v0) / (v1 - v0);
+                let mut color = [0; 4];
+                for (k, channel) in color.iter_mut().enumerate() {
+                    *channel = (f64::from(c0[k]) + t * (f64::from(c1[k]) - f64::from(c0[k])))
+                        .round() as u8;
+                }
+                Some(color)
+            }
+            ColorMap::Step(stops) => stops
+                .iter()
+                .take_while(|(v, _)| *v <= value)
+                .last()
+                .map(|(_, color)| *color),
+            ColorMap::Values(entries) => entries
+                .iter()
+                .find(|(v, _)| (v - value).abs() < 1e-9)
+                .map(|(_, color)| *color),
+        }
+    }
+}
+
+impl RasterStyle {
+    /// Raster style of a stylesheet for the collection with the id of
+    /// `source`, `None` if it has no raster layer or symbolizer
+    ///
+    /// Of Mapbox GL styles, the first visible `raster` layer of the source
+    /// is used, or else the first visible one. Its `raster-color` colors the
+    /// values of the first band with an `interpolate` or `step` expression of
+    /// `["raster-value"]`.
+    pub fn from_stylesheet(stylesheet: &Value, source: &str) -> Option<RasterStyle> {
+        match stylesheet {
+            Value::String(sld) => RasterStyle::from_sld(sld),
+            Value::Object(_) => RasterStyle::from_mapbox(stylesheet, source),
+            _ => None,
+        }
+    }
+
+    fn from_mapbox(style: &Value, source: &str) -> Option<RasterStyle> {
+        let layers: Vec<&Value> = style["layers"]
+            .as_array()?
+            .iter()
+            .filter(|layer| layer["type"] == "raster" && layer["layout"]["visibility"] != "none")
+            .collect();
+        let layer = layers
+            .iter()
+            .find(|layer| layer["source"] == source)
+            .or_else(|| layers.first())?;
+
+        let paint = &layer["paint"];
+        let number = |key: &str, default: f64| paint[key].as_f64().unwrap_or(default);
+
+        Some(RasterStyle {
+            opacity: number("raster-opacity", 1.0),
+            brightness: (
+                number("raster-brightness-min", 0.0),
+                number("raster-brightness-max", 1.0),
+            ),
+            contrast: number("raster-contrast", 0.0),
+            saturation: number("raster-saturation", 0.0),
+            color_map: color_expression(&paint["raster-color"]),
+        })
+    }
+
+    fn from_sld(sld: &str) -> Option<RasterStyle> {
+        let symbolizer = element(sld, "RasterSymbolizer")?;
+
+        let opacity = element(symbolizer, "Opacity")
+            .and_then(|opacity| text(opacity).trim().parse().ok())
+            .unwrap_or(1.0);
+
+        let color_map = elements(symbolizer, "ColorMap")
+            .next()
+            .and_then(|(tag, color_map)| {
+                let entries: Vec<(f64, [u8; 4])> = tags(color_map, "ColorMapEntry")
+                    .filter_map(|entry| {
+                        let quantity = attribute(entry, "quantity")?.parse().ok()?;
+                        let mut color = parse_color(attribute(entry, "color")?)?;
+                        if let Some(opacity) =
+                            attribute(entry, "opacity").and_then(|o| o.parse::<f64>().ok())
+                        {
+                            color[3] = (opacity * 255.0).round() as u8;
+                        }
+                        Some((quantity, color))
+                    })
+                    .collect();
+                if entries.is_empty() {
+                    return None;
+                }
+
+                match attribute(tag, "type") {
+                    // each color applies below its quantity
+                    Some("intervals") => {
+                        let mut stops = vec![(f64::NEG_INFINITY, entries[0].1)];
+                        for window in entries.windows(2) {
+                            stops.push((window[0].0, window[1].1));
+                        }
+                        stops.push((entries[entries.len() - 1].0, [0; 4]));
+                        Some(ColorMap::Step(stops))
+                    }
+                    Some("values") => Some(ColorMap::Values(entries)),
+                    _ => Some(ColorMap::Ramp(entries)),
+                }
+            });
+
+        Some(RasterStyle {
+            opacity,
+            color_map,
+            ..Default::default()
+        })
+    }
+
+    /// Adjust an RGBA pixel by the brightness, contrast, saturation and
+    /// opacity of the style
+    pub fn adjust(&self, [r, g, b, a]: [u8; 4]) -> [u8; 4] {
+        let mut rgb = [r, g, b].map(|c| f64::from(c) / 255.0);
+
+        if self.saturation != 0.0 {
+            let gray = (rgb[0] + rgb[1] + rgb[2]) / 3.0;
+            let factor = if self.saturation > 0.0 {
+                1.0 / (1.0 - self.saturation).max(f64::EPSILON)
+            } else {
+                1.0 + self.saturation
+            };
+            rgb = rgb.map(|c| gray + (c - gray) * factor);
+        }
+
+        if self.contrast != 0.0 {
+            let factor = if self.contrast > 0.0 {
+                1.0 / (1.0 - self.contrast).max(f64::EPSILON)
+            } else {
+                1.0 + self.contrast
+            };
+            rgb = rgb.map(|c| (c - 0.5) * factor + 0.5);
+        }
+
+        let (min, max) = self.brightness;
+        rgb = rgb.map(|c| min + c.clamp(0.0, 1.0) * (max - min));
+
+        let [r, g, b] = rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        let a = (f64::from(a) * self.opacity.clamp(0.0, 1.0)).round() as u8;
+
+        [r, g, b, a]
+    }
+}
+
+/// Color map of an `interpolate` or `step` expression of `["raster-value"]`
+pub(super) fn color_expression(expression: &Value) -> Option<ColorMap> {
+    let expression = expression.as_array()?;
+    match expression.first()?.as_str()? {
+        "interpolate" => {
+            let stops = expression.get(3..)?.chunks(2).map(|stop| match stop {
+                [value, color] => Some((value.as_f64()?, parse_color(color.as_str()?)?)),
+                _ => None,
+            });
+            Some(ColorMap::Ramp(stops.collect::<Option<_>>()?))
+        }
+        "step" => {
+            let mut stops = vec![(
+                f64::NEG_INFINITY,
+                parse_color(expression.get(2)?.as_str()?)?,
+            )];
+            for stop in expression.get(3..)?.chunks(2) {
+                let [value, color] = stop else {
+                    return None;
+                };
+                stops.push((value.as_f64()?, parse_color(color.as_str()?)?));
+            }
+            Some(ColorMap::Step(stops))
+        }
+        _ => None,
+    }
+}
+
+/// RGBA of a color as `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb(...)`, `rgba(...)`
+/// or `transparent`
+pub fn parse_color(color: &str) -> Option<[u8; 4]> {
+    let color = color.trim();
+
+    if color == "transparent" {
+        return Some([0; 4]);
+    }
+
+    if let Some(hex) = color.strip_prefix('#') {
+        let digits: Vec<u8> = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<_>>()?;
+        return match digits[..] {
+            [r, g, b] => Some([r * 17, g * 17, b * 17, 255]),
+            [r1, r2, g1, g2, b1, b2] => Some([r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, 255]),
+            [r1, r2, g1, g2, b1, b2, a1, a2] => {
+                Some([r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, a1 * 16 + a2])
+            }
+            _ => None,
+        };
+    }
+
+    let arguments = color
+        .strip_prefix("rgba(")
+        .or_else(|| color.strip_prefix("rgb("))?
+        .strip_suffix(')')?;
+    let numbers: Vec<f64> = arguments
+        .split(',')
+        .map(|n| n.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let channel = |n: f64| n.clamp(0.0, 255.0).round() as u8;
+    match numbers[..] {
+        [r, g, b] => Some([channel(r), channel(g), channel(b), 255]),
+        [r, g, b, a] => Some([channel(r), channel(g), channel(b), channel(a * 255.0)]),
+        _ => None,
+    }
+}
+
+/// Content of the first element with a local name, ignoring namespace
+/// prefixes
+pub(super) fn element<'a>(xml: &'a str, name: &'a str) -> Option<&'a str> {
+    elements(xml, name).next().map(|(_, content)| content)
+}
+
+/// Start tags and contents of the elements with a local name, nested elements
+/// of the same name are not supported
+pub(super) fn elements<'a>(
+    xml: &'a str,
+    name: &'a str,
+) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+    tags(xml, name).filter_map(move |tag| {
+        if tag.ends_with('/') {
+            return Some((tag, ""));
+        }
+
+        // after the closing angle bracket of the start tag
+        let start = tag.as_ptr() as usize - xml.as_ptr() as usize + tag.len() + 1;
+
+        let rest = &xml[start..];
+        let end = rest
+            .match_indices("</")
+            .find(|(i, _)| local_name(&rest[i + 2..]) == name)
+            .map(|(i, _)| i)?;
+
+        Some((tag, &rest[..end]))
+    })
+}
+
+/// Start tags with a local name, without the angle brackets
+pub(super) fn tags<'a>(xml: &'a str, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    xml.match_indices('<').filter_map(move |(i, _)| {
+        let rest = &xml[i + 1..];
+        if rest.starts_with('/') || local_name(rest) != name {
+            return None;
+        }
+        let end = rest.find('>')?;
+        Some(&rest[..end])
+    })
+}
+
+/// Local name of the tag at the start of the text
+fn local_name(tag: &str) -> &str {
+    let end = tag
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(tag.len());
+    let name = &tag[..end];
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+/// Value of an attribute of a start tag
+pub(super) fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    tag.match_indices(name).find_map(|(i, _)| {
+        if !tag[..i].ends_with(char::is_whitespace) {
+            return None;
+        }
+        let rest = tag[i + name.len()..].trim_start().strip_prefix('=')?;
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &rest[1..];
+        Some(&value[..value.find(quote)?])
+    })
+}
+
+/// Text of an element content, without nested tags
+pub(super) fn text(content: &str) -> &str {
+    content.split('<').next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn mapbox() {
+        let style = json!({
+            "version": 8,
+            "sources": {},
+            "layers": [
+                { "id": "hillshade", "type": "raster", "source": "hillshade" },
+                {
+                    "id": "dem",
+                    "type": "raster",
+                    "source": "dem",
+                    "paint": {
+                        "raster-opacity": 0.5,
+                        "raster-color": [
+                            "interpolate", ["linear"], ["raster-value"],
+                            0, "#0000ff",
+                            1000, "rgb(0, 255, 0)",
+                            4000, "#ffffff"
+                        ]
+                    }
+                }
+            ]
+        });
+
+        let raster = RasterStyle::from_stylesheet(&style, "dem").unwrap();
+        assert_eq!(raster.opacity, 0.5);
+        let color_map = raster.color_map.as_ref().unwrap();
+        assert_eq!(color_map.color(-10.0), Some([0, 0, 255, 255]));
+        assert_eq!(color_map.color(500.0), Some([0, 128, 128, 255]));
+        assert_eq!(color_map.color(9000.0), Some([255, 255, 255, 255]));
+        assert_eq!(raster.adjust([10, 20, 30, 255]), [10, 20, 30, 128]);
+
+        // the first raster layer for other collections
+        let raster = RasterStyle::from_stylesheet(&style, "landcover").unwrap();
+        assert_eq!(raster, RasterStyle::default());
+
+        let style = json!({ "version": 8, "layers": [{ "id": "roads", "type": "line" }] });
+        assert!(RasterStyle::from_stylesheet(&style, "dem").is_none());
+    }
+
+    #[test]
+    fn step() {
+        let color_map =
+            color_expression(&json!(["step", ["raster-value"], "#000", 10, "#fff"])).unwrap();
+        assert_eq!(color_map.color(5.0), Some([0, 0, 0, 255]));
+        assert_eq!(color_map.color(10.0), Some([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn sld() {
+        let sld = r##"<?xml version="1.0" encoding="UTF-8"?>
+            <sld:StyledLayerDescriptor xmlns:sld="http://www.opengis.net/sld" version="1.0.0">
+              <sld:NamedLayer>
+                <sld:UserStyle>
+                  <sld:FeatureTypeStyle>
+                    <sld:Rule>
+                      <sld:RasterSymbolizer>
+                        <sld:Opacity>0.8</sld:Opacity>
+                        <sld:ColorMap type="intervals">
+                          <sld:ColorMapEntry color="#00ff00" quantity="100" />
+                          <sld:ColorMapEntry color="#ff0000" quantity="200" opacity="0.5" />
+                        </sld:ColorMap>
+                      </sld:RasterSymbolizer>
+                    </sld:Rule>
+                  </sld:FeatureTypeStyle>
+                </sld:UserStyle>
+              </sld:NamedLayer>
+            </sld:StyledLayerDescriptor>"##;
+
+        let raster = RasterStyle::from_stylesheet(&Value::from(sld), "dem").unwrap();
+        assert_eq!(raster.opacity, 0.8);
+        let color_map = raster.color_map.unwrap();
+        assert_eq!(color_map.color(50.0), Some([0, 255, 0, 255]));
+        assert_eq!(color_map.color(150.0), Some([255, 0, 0, 128]));
+        assert_eq!(color_map.color(250.0), Some([0, 0, 0, 0]));
+    }
+}
@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::raster::{element, elements, text};
+
+/// Data referenced by a layer of a Mapbox GL style or a named layer of an SLD
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleReference {
+    /// Id of the style layer or name of the SLD layer
+    pub layer: String,
+    /// Tile layer the features are read from, for SLDs the layer name
+    pub source_layer: Option<String>,
+    /// Properties of the features used by filters and symbols
+    pub properties: Vec<String>,
+}
+
+/// Result of the validation of a style against the collections
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StyleValidation {
+    /// Whether all referenced layers and properties exist
+    pub valid: bool,
+    pub warnings: Vec<StyleWarning>,
+}
+
+/// Reference of a style layer to data that does not exist
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StyleWarning {
+    pub layer: String,
+    pub message: String,
+}
+
+impl StyleValidation {
+    pub fn new(warnings: Vec<StyleWarning>) -> Self {
+        StyleValidation {
+            valid: warnings.is_empty(),
+            warnings,
+        }
+    }
+}
+
+/// Legacy filter operators with a property name as first operand
+const LEGACY_FILTERS: [&str; 10] = ["==", "!=", "<", ">", "<=", ">=", "in", "!in", "has", "!has"];
+
+impl StyleReference {
+    /// References of a Mapbox GL style, or of an SLD stored as string
+    ///
+    /// Properties are those of `get` and `has` expressions, of legacy filters
+    /// and of `{token}`s in strings. Those starting with `$` refer to the
+    /// geometry type or id and are left out.
+    pub fn from_stylesheet(stylesheet: &Value) -> Vec<StyleReference> {
+        match stylesheet {
+            Value::String(sld) => StyleReference::from_sld(sld),
+            Value::Object(_) => StyleReference::from_mapbox(stylesheet),
+            _ => Vec::new(),
+        }
+    }
+
+    fn from_mapbox(style: &Value) -> Vec<StyleReference> {
+        let layers = style["layers"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        layers
+            .iter()
+            .filter(|layer| !matches!(layer["type"].as_str(), Some("background" | "raster")))
+            .map(|layer| {
+                let mut properties = Vec::new();
+                legacy_filter(&layer["filter"], &mut properties);
+                for value in [&layer["layout"], &layer["paint"]] {
+                    expression(value, &mut properties);
+                }
+                properties.retain(|property| !property.starts_with('$'));
+                properties.sort();
+                properties.dedup();
+
+                StyleReference {
+                    layer: layer["id"].as_str().unwrap_or_default().to_owned(),
+                    source_layer: layer["source-layer"].as_str().map(ToOwned::to_owned),
+                    properties,
+                }
+            })
+            .collect()
+    }
+
+    fn from_sld(sld: &str) -> Vec<StyleReference> {
+        elements(sld, "NamedLayer")
+            .map(|(_, layer)| {
+                let name = element(layer, "Name").map(|name| text(name).trim().to_owned());
+
+                let mut properties: Vec<String> = elements(layer, "PropertyName")
+                    .map(|(_, property)| text(property).trim().to_owned())
+                    .collect();
+                properties.sort();
+                properties.dedup();
+
+                StyleReference {
+                    layer: name.to_owned().unwrap_or_default(),
+                    source_layer: name,
+                    properties,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Properties of a filter in the legacy syntax, or else as expression
+fn legacy_filter(filter: &Value, properties: &mut Vec<String>) {
+    let Some([operator, operands @ ..]) = filter.as_array().map(Vec::as_slice) else {
+        return;
+    };
+
+    match (operator.as_str(), operands.first()) {
+        (Some("all" | "any" | "none"), _) => {
+            for filter in operands {
+                legacy_filter(filter, properties);
+            }
+        }
+        (Some(operator), Some(Value::String(property))) if LEGACY_FILTERS.contains(&operator) => {
+            properties.push(property.to_owned());
+        }
+        _ => expression(filter, properties),
+    }
+}
+
+/// Properties of the `get` and `has` expressions and the `{token}`s of strings
+/// in a value
+fn expression(value: &Value, properties: &mut Vec<String>) {
+    match value {
+        Value::Array(values) => match values.as_slice() {
+            [Value::String(operator), Value::String(property)]
+                if matches!(operator.as_str(), "get" | "has" | "!has") =>
+            {
+                properties.push(property.to_owned());
+            }
+            // a literal is data, not an expression
+            [Value::String(operator), ..] if operator == "literal" => {}
+            values => values
+                .iter()
+                .for_each(|value| expression(value, properties)),
+        },
+        Value::Object(map) => map.values().for_each(|value| expression(value, properties)),
+        Value::String(s) => {
+            let mut rest = s.as_str();
+            while let Some((_, after)) = rest.split_once('{') {
+                let Some((token, after)) = after.split_once('}') else {
+                    break;
+                };
+                if !token.is_empty() && !token.contains(char::is_whitespace) {
+                    properties.push(token.to_owned());
+                }
+                rest = after;
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn mapbox() {
+        let style = json!({
+            "version": 8,
+            "sources": {},
+            "layers": [
+                { "id": "background", "type": "background" },
+                {
+                    "id": "roads",
+                    "type": "line",
+                    "source": "tiles",
+                    "source-layer": "roads",
+                    "filter": ["all", ["==", "$type", "LineString"], ["in", "class", "major", "minor"]],
+                    "paint": {
+                        "line-width": ["interpolate", ["linear"], ["get", "lanes"], 1, 1, 4, 3],
+                        "line-color": ["match", ["get", "class"], "major", "#f00", "#000"]
+                    }
+                },
+                {
+                    "id": "labels",
+                    "type": "symbol",
+                    "source": "tiles",
+                    "source-layer": "places",
+                    "filter": ["has", ["literal", "ignored"]],
+                    "layout": { "text-field": "{name} ({population})" }
+                }
+            ]
+        });
+
+        assert_eq!(
+            StyleReference::from_stylesheet(&style),
+            [
+                StyleReference {
+                    layer: "roads".to_owned(),
+                    source_layer: Some("roads".to_owned()),
+                    properties: vec!["class".to_owned(), "lanes".to_owned()],
+                },
+                StyleReference {
+                    layer: "labels".to_owned(),
+                    source_layer: Some("places".to_owned()),
+                    properties: vec!["name".to_owned(), "population".to_owned()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sld() {
+        let sld = r#"<StyledLayerDescriptor version="1.0.0">
+              <NamedLayer>
+                <Name>parcels</Name>
+                <UserStyle>
+                  <FeatureTypeStyle>
+                    <Rule>
+                      <ogc:Filter>
+                        <ogc:PropertyIsEqualTo>
+                          <ogc:PropertyName>zone</ogc:PropertyName>
+                          <ogc:Literal>W2</ogc:Literal>
+                        </ogc:PropertyIsEqualTo>
+                      </ogc:Filter>
+                      <TextSymbolizer>
+                        <Label><ogc:PropertyName>number</ogc:PropertyName></Label>
+                      </TextSymbolizer>
+                    </Rule>
+                  </FeatureTypeStyle>
+                </UserStyle>
+              </NamedLayer>
+            </StyledLayerDescriptor>"#;
+
+        assert_eq!(
+            StyleReference::from_stylesheet(&Value::from(sld)),
+            [StyleReference {
+                layer: "parcels".to_owned(),
+                source_layer: Some("parcels".to_owned()),
+                properties: vec!["number".to_owned(), "zone".to_owned()],
+            }]
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::Links;
+
+/// Background task run periodically by the server, e.g. the refresh of the
+/// generalized tile geometries
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: String,
+    pub description: String,
+    /// Time between the runs in seconds
+    pub interval: u64,
+    /// Whether the runs on schedule are suspended, runs may still be started
+    /// manually
+    #[serde(default)]
+    pub paused: bool,
+    /// Whether the task is running right now
+    #[serde(default)]
+    pub running: bool,
+    /// Number of completed runs since the server started
+    #[serde(default)]
+    pub runs: u64,
+    /// Number of failed runs since the server started
+    #[serde(default)]
+    pub failures: u64,
+    pub last_run: Option<DateTime<Utc>>,
+    /// Duration of the latest run in milliseconds
+    pub last_duration: Option<u64>,
+    /// Error of the latest run, if it failed
+    pub last_error: Option<String>,
+    pub next_run: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Links,
+}
+
+/// Background tasks of the server
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Tasks {
+    pub tasks: Vec<Task>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Links,
+}
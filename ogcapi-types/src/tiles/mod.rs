@@ -20,6 +20,7 @@ pub type OrderedAxes = Vec<String>;
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TitleDescriptionKeywords {
     /// Title of this resource entity, normally used for display to a human
     pub title: Option<String>,
@@ -31,15 +32,99 @@ pub struct TitleDescriptionKeywords {
     pub keywords: Option<Vec<String>>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "TileQuery")
+)]
+#[serde(rename_all = "kebab-case")]
 pub struct Query {
     pub collections: Option<String>,
+    /// Add a layer `{collection}_labels` with label points of polygons and
+    /// lines
+    #[serde(default)]
+    pub labels: bool,
+    /// Aggregate point features into cells of a grid instead of rendering
+    /// them one by one
+    pub aggregate: Option<CellGrid>,
+    /// Numeric property averaged per cell of an aggregation
+    pub parameter: Option<String>,
+    /// Size of the cells of an aggregation in pixels of a 256 pixel tile
+    pub cell_size: Option<u32>,
+}
+
+impl Query {
+    /// Aggregation of the features, if requested
+    pub fn aggregation(&self) -> Option<Aggregation> {
+        self.aggregate.map(|grid| Aggregation {
+            grid,
+            parameter: self.parameter.to_owned(),
+            cell_size: self
+                .cell_size
+                .unwrap_or(Aggregation::CELL_SIZE)
+                .clamp(2, 256),
+        })
+    }
+}
+
+/// Aggregation of point features into the cells of a grid, e.g. for dense
+/// observations, cells keep their size on screen across zoom levels
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Aggregation {
+    pub grid: CellGrid,
+    /// Numeric property averaged per cell, besides the count of features
+    pub parameter: Option<String>,
+    /// Size of the cells in pixels of a 256 pixel tile
+    pub cell_size: u32,
+}
+
+impl Aggregation {
+    /// Default size of the cells in pixels
+    pub const CELL_SIZE: u32 = 16;
+}
+
+/// Shape of the cells of an aggregation
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum CellGrid {
+    #[serde(alias = "hexbin")]
+    Hexagon,
+    #[serde(alias = "grid")]
+    Square,
+}
+
+/// Tile requests of a collection at a tile matrix (zoom level)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TileUsage {
+    pub collection: String,
+    pub tile_matrix: String,
+    pub requests: u64,
+    /// Requests answered from cached data, without rendering the tile
+    pub hits: u64,
+}
+
+impl TileUsage {
+    /// Share of the requests answered from cached data
+    pub fn hit_ratio(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.requests as f64
+        }
+    }
 }
 
 /// Minimum bounding rectangle surrounding a 2D resource in the CRS indicated elsewere
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct BoundingBox2D {
     pub lower_left: Point2D,
@@ -49,3 +134,51 @@ pub struct BoundingBox2D {
     pub crs: Option<Crs>,
     pub ordered_axes: Option<OrderedAxes>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregation() {
+        let query: Query = serde_json::from_value(serde_json::json!({
+            "aggregate": "hexbin",
+            "parameter": "pm10",
+            "cell-size": 1000
+        }))
+        .unwrap();
+        assert_eq!(
+            query.aggregation(),
+            Some(Aggregation {
+                grid: CellGrid::Hexagon,
+                parameter: Some("pm10".to_string()),
+                cell_size: 256,
+            })
+        );
+
+        let query: Query =
+            serde_json::from_value(serde_json::json!({ "aggregate": "square" })).unwrap();
+        assert_eq!(
+            query.aggregation().unwrap().cell_size,
+            Aggregation::CELL_SIZE
+        );
+
+        let query: Query =
+            serde_json::from_value(serde_json::json!({ "collections": "stations" })).unwrap();
+        assert_eq!(query.aggregation(), None);
+    }
+
+    #[test]
+    fn hit_ratio() {
+        let mut usage = TileUsage {
+            collection: "roads".to_string(),
+            tile_matrix: "12".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(usage.hit_ratio(), 0.0);
+
+        usage.requests = 8;
+        usage.hits = 2;
+        assert_eq!(usage.hit_ratio(), 0.25);
+    }
+}
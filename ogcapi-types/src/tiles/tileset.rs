@@ -11,6 +11,7 @@ use super::{BoundingBox2D, Crs, Point2D, TitleDescriptionKeywords};
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TileSets {
     pub tilesets: Vec<TileSetItem>,
@@ -22,6 +23,7 @@ pub struct TileSets {
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TileSetItem {
     pub title: Option<String>,
@@ -38,6 +40,7 @@ pub struct TileSetItem {
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TileSet {
     #[serde(flatten)]
@@ -94,6 +97,7 @@ pub struct TileSet {
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct GeospatialData {
     #[serde(flatten)]
@@ -153,6 +157,7 @@ pub struct GeospatialData {
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TilePoint {
     pub coordinates: Option<Point2D>,
@@ -170,6 +175,11 @@ pub struct TilePoint {
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "TileSetStyle")
+)]
 #[serde(rename_all = "camelCase")]
 pub struct Style {
     #[serde(flatten)]
@@ -185,6 +195,7 @@ pub struct Style {
 /// A resource describing useful to create an array that describes the limits
 /// for a tile set [TileMatrixSet] based on the OGC TileSet Metadata Standard
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TileMatrixLimits {
     pub tile_matrix: String,
@@ -195,6 +206,11 @@ pub struct TileMatrixLimits {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "TileSetDataType")
+)]
 #[serde(rename_all = "lowercase")]
 #[non_exhaustive]
 pub enum DataType {
@@ -205,6 +221,7 @@ pub enum DataType {
 
 #[repr(u8)]
 #[derive(Serialize_repr, Deserialize_repr, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum GeometryDimension {
     Points = 0,
     Curves = 1,
@@ -213,6 +230,7 @@ pub enum GeometryDimension {
 }
 
 #[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum AccessConstraints {
     #[default]
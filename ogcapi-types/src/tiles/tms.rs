@@ -5,6 +5,7 @@ use serde_with::DisplayFromStr;
 use std::num::{NonZeroU16, NonZeroU64};
 
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TileMatrixSets {
     pub tile_matrix_sets: Vec<TileMatrixSetItem>,
@@ -15,6 +16,7 @@ pub struct TileMatrixSets {
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TileMatrixSetItem {
     /// Optional local tile matrix set identifier, e.g. for use as unspecified
@@ -38,6 +40,7 @@ pub struct TileMatrixSetItem {
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TileMatrixSet {
     #[serde(flatten)]
@@ -62,6 +65,7 @@ pub struct TileMatrixSet {
 #[serde_with::serde_as]
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TileMatrix {
     #[serde(flatten)]
@@ -96,6 +100,7 @@ pub struct TileMatrix {
 
 /// Variable Matrix Width data structure
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct VariableMatrixWidth {
     /// Number of tiles in width that coalesce in a single tile for these rows
@@ -107,6 +112,7 @@ pub struct VariableMatrixWidth {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum CornerOfOrigin {
     #[default]
@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::Links;
+
+/// Saved query of the features of a collection, to be opened again or shared
+///
+/// Views belong to the user of the api key they were saved with, views saved
+/// without api key to all requests without one.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct View {
+    #[serde(default)]
+    pub id: String,
+    pub title: Option<String>,
+    /// Collection of the features
+    pub collection: String,
+    /// `CQL2` filter, in the encoding of `filter_lang`
+    pub filter: Option<String>,
+    /// `cql2-text` (default) or `cql2-json`
+    pub filter_lang: Option<String>,
+    /// Bounding box in `CRS84`, with four or six numbers
+    pub bbox: Option<Vec<f64>>,
+    /// Instant or interval as in the `datetime` parameter
+    pub datetime: Option<String>,
+    /// Properties of the features to return, all if missing
+    pub properties: Option<Vec<String>>,
+    /// Sort order as in the `sortby` parameter
+    pub sortby: Option<String>,
+    /// Style to render the features with
+    pub style: Option<String>,
+    /// Whether users other than the owner may open the view
+    #[serde(default)]
+    pub shared: bool,
+    /// User of the api key the view was saved with, set by the server
+    pub owner: Option<String>,
+    pub created: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Links,
+}
+
+impl View {
+    /// Check the view for problems
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.collection.trim().is_empty() {
+            problems.push("View needs a collection".to_string());
+        }
+        if self
+            .bbox
+            .as_ref()
+            .is_some_and(|b| ![4, 6].contains(&b.len()))
+        {
+            problems.push("Bbox of a view needs four or six numbers".to_string());
+        }
+        if let Some(lang) = self
+            .filter_lang
+            .as_deref()
+            .filter(|lang| !["cql2-text", "cql2-json"].contains(lang))
+        {
+            problems.push(format!("Unsupported filter language `{lang}`"));
+        }
+        if self
+            .properties
+            .as_ref()
+            .is_some_and(|p| p.iter().any(|p| p.trim().is_empty() || p.contains(',')))
+        {
+            problems.push("Properties of a view need names without commas".to_string());
+        }
+
+        problems
+    }
+
+    /// Query parameters of the items request of the view, without the style
+    pub fn parameters(&self) -> Vec<(&'static str, String)> {
+        let mut parameters = Vec::new();
+
+        if let Some(filter) = &self.filter {
+            parameters.push(("filter", filter.to_owned()));
+        }
+        if let Some(lang) = &self.filter_lang {
+            parameters.push(("filter-lang", lang.to_owned()));
+        }
+        if let Some(bbox) = &self.bbox {
+            let bbox: Vec<String> = bbox.iter().map(ToString::to_string).collect();
+            parameters.push(("bbox", bbox.join(",")));
+        }
+        if let Some(datetime) = &self.datetime {
+            parameters.push(("datetime", datetime.to_owned()));
+        }
+        if let Some(properties) = &self.properties {
+            parameters.push(("properties", properties.join(",")));
+        }
+        if let Some(sortby) = &self.sortby {
+            parameters.push(("sortby", sortby.to_owned()));
+        }
+
+        parameters
+    }
+
+    /// Whether a user, or a request without api key for `None`, may open the
+    /// view
+    pub fn visible_to(&self, user: Option<&str>) -> bool {
+        self.shared || self.owner.as_deref() == user
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn view() {
+        let view: View = serde_json::from_value(json!({
+            "title": "Large parcels in the center",
+            "collection": "parcels",
+            "filter": "area > 1000",
+            "bbox": [7.4, 46.9, 7.5, 47.0],
+            "properties": ["number", "area"],
+            "style": "cadastre"
+        }))
+        .unwrap();
+
+        assert!(view.validate().is_empty());
+        assert!(!view.shared);
+        assert_eq!(
+            view.parameters(),
+            [
+                ("filter", "area > 1000".to_string()),
+                ("bbox", "7.4,46.9,7.5,47".to_string()),
+                ("properties", "number,area".to_string()),
+            ]
+        );
+
+        // private views of users
+        let view = View {
+            owner: Some("alice".to_string()),
+            ..view
+        };
+        assert!(view.visible_to(Some("alice")));
+        assert!(!view.visible_to(Some("bob")));
+        assert!(!view.visible_to(None));
+
+        let view = View {
+            collection: String::new(),
+            bbox: Some(vec![7.4, 46.9]),
+            filter_lang: Some("ecql".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(view.validate().len(), 3);
+    }
+}
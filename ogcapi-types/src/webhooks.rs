@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Registration of a webhook receiving event deliveries
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    #[serde(default)]
+    pub id: String,
+    /// Url the events are posted to
+    pub url: String,
+    /// Secret for signing the deliveries, never returned
+    #[serde(skip_serializing)]
+    pub secret: Option<String>,
+    /// Types of events to deliver, all if empty
+    #[serde(default)]
+    pub events: Vec<EventType>,
+    /// Collections to deliver feature events of, all if empty
+    #[serde(default)]
+    pub collections: Vec<String>,
+    /// User of the api key the webhook was registered with, set by the server
+    pub owner: Option<String>,
+    pub created: Option<DateTime<Utc>>,
+}
+
+impl Webhook {
+    /// Checks whether the event is to be delivered to the webhook
+    pub fn accepts(&self, event: &Event) -> bool {
+        if !self.events.is_empty() && !self.events.contains(&event.r#type) {
+            return false;
+        }
+
+        match &event.collection {
+            Some(collection) if !self.collections.is_empty() => {
+                self.collections.contains(collection)
+            }
+            _ => true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum EventType {
+    #[serde(rename = "feature.create")]
+    FeatureCreate,
+    #[serde(rename = "feature.update")]
+    FeatureUpdate,
+    #[serde(rename = "feature.delete")]
+    FeatureDelete,
+    #[serde(rename = "job.status")]
+    JobStatus,
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::FeatureCreate => "feature.create",
+            EventType::FeatureUpdate => "feature.update",
+            EventType::FeatureDelete => "feature.delete",
+            EventType::JobStatus => "job.status",
+        }
+    }
+}
+
+/// Event as delivered to webhooks
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub id: String,
+    pub r#type: EventType,
+    pub time: DateTime<Utc>,
+    /// Collection of feature events
+    pub collection: Option<String>,
+    /// The feature change or job status
+    pub data: Value,
+}
+
+/// Log entry of a delivery attempt
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Delivery {
+    pub webhook: String,
+    pub event: String,
+    pub r#type: EventType,
+    /// Attempt number, starting at `1`
+    pub attempt: u32,
+    /// Response status, missing if the request failed
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub time: DateTime<Utc>,
+}
+
+impl Delivery {
+    pub fn is_success(&self) -> bool {
+        self.status
+            .is_some_and(|status| (200..300).contains(&status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+
+    use super::{Event, EventType, Webhook};
+
+    #[test]
+    fn event_filter() {
+        let webhook: Webhook = serde_json::from_value(json!({
+            "url": "https://example.com/hook",
+            "secret": "s3cr3t",
+            "events": ["feature.create", "feature.delete"],
+            "collections": ["roads"]
+        }))
+        .unwrap();
+
+        assert_eq!(webhook.secret.as_deref(), Some("s3cr3t"));
+        assert!(serde_json::to_value(&webhook)
+            .unwrap()
+            .get("secret")
+            .is_none());
+
+        let mut event = Event {
+            id: "1".to_string(),
+            r#type: EventType::FeatureCreate,
+            time: Utc::now(),
+            collection: Some("roads".to_string()),
+            data: json!({}),
+        };
+        assert!(webhook.accepts(&event));
+
+        event.collection = Some("rivers".to_string());
+        assert!(!webhook.accepts(&event));
+
+        event.r#type = EventType::JobStatus;
+        event.collection = None;
+        assert!(!webhook.accepts(&event));
+    }
+}
@@ -0,0 +1,22 @@
+/// Escape XML text and attribute values
+pub fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_special_characters() {
+        assert_eq!(
+            escape(r#"<a href="x?b&c">"#),
+            "&lt;a href=&quot;x?b&amp;c&quot;&gt;"
+        );
+        assert_eq!(escape("plain"), "plain");
+    }
+}
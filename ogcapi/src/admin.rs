@@ -0,0 +1,470 @@
+use ogcapi_drivers::{
+    postgres::Db, AccessFilterTransactions, CollectionTransactions, QuotaTransactions,
+    TileTransactions, UserTransactions,
+};
+#[cfg(feature = "services")]
+use ogcapi_services::share::{Share, SHARE_PARAMETER};
+use ogcapi_types::{
+    auth::{AccessFilter, Quota},
+    common::{Collection, Crs},
+    cql2::{self, Expr},
+};
+
+#[derive(clap::Parser, Debug)]
+pub struct CollectionArgs {
+    #[clap(subcommand)]
+    pub command: CollectionCommand,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, value_parser)]
+    pub database_url: url::Url,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum CollectionCommand {
+    /// Create an empty feature collection
+    Create {
+        /// Collection id
+        id: String,
+        /// Human readable title of the collection
+        #[clap(long)]
+        title: Option<String>,
+        /// Detailed description of the collection
+        #[clap(long)]
+        description: Option<String>,
+        /// Storage srs of the collection, defaults to `4326`
+        #[clap(long)]
+        storage_srs: Option<i32>,
+    },
+    /// Delete a collection including its items
+    Delete {
+        /// Collection id
+        id: String,
+    },
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct UserArgs {
+    #[clap(subcommand)]
+    pub command: UserCommand,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, value_parser)]
+    pub database_url: url::Url,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum UserCommand {
+    /// Create a user
+    Create {
+        /// User id
+        id: String,
+    },
+    /// Delete a user and its api keys
+    Delete {
+        /// User id
+        id: String,
+    },
+    /// List all users
+    List,
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct KeyArgs {
+    #[clap(subcommand)]
+    pub command: KeyCommand,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, value_parser)]
+    pub database_url: url::Url,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum KeyCommand {
+    /// Create an api key, the key is only shown once
+    Create {
+        /// User the key belongs to
+        #[clap(long)]
+        user: String,
+    },
+    /// Revoke an api key
+    Delete {
+        /// Key id
+        id: String,
+    },
+    /// List api keys
+    List {
+        /// Only list keys of this user
+        #[clap(long)]
+        user: Option<String>,
+    },
+    /// List the metered usage of api keys per month, with `--metering`
+    Usage {
+        /// Only list the usage of keys of this user
+        #[clap(long)]
+        user: Option<String>,
+        /// Only list the usage of this month, as `YYYY-MM`
+        #[clap(long)]
+        month: Option<String>,
+    },
+    /// Set the monthly quota of an api key, removes it without limits
+    Quota {
+        /// Key id
+        id: String,
+        /// Maximum number of requests
+        #[clap(long)]
+        requests: Option<u64>,
+        /// Maximum number of features returned
+        #[clap(long)]
+        features: Option<u64>,
+        /// Maximum number of tiles rendered
+        #[clap(long)]
+        tiles: Option<u64>,
+        /// Maximum run time of jobs in seconds
+        #[clap(long)]
+        job_seconds: Option<f64>,
+    },
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct AccessArgs {
+    #[clap(subcommand)]
+    pub command: AccessCommand,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, value_parser)]
+    pub database_url: url::Url,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum AccessCommand {
+    /// Restrict the features of a collection accessible to a user
+    Set {
+        /// Collection id
+        #[clap(long)]
+        collection: String,
+        /// User the filter applies to, requests without api key if omitted
+        #[clap(long)]
+        user: Option<String>,
+        /// `CQL2-Text` or `CQL2-JSON` expression matching the accessible features
+        filter: String,
+    },
+    /// Remove the restriction of a user
+    Delete {
+        /// Collection id
+        #[clap(long)]
+        collection: String,
+        /// User of the filter, requests without api key if omitted
+        #[clap(long)]
+        user: Option<String>,
+    },
+    /// List access filters
+    List {
+        /// Only list filters of this collection
+        #[clap(long)]
+        collection: Option<String>,
+    },
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct TilesArgs {
+    #[clap(subcommand)]
+    pub command: TilesCommand,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, value_parser)]
+    pub database_url: url::Url,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum TilesCommand {
+    /// List the tile requests and cache hit ratios per collection and tile
+    /// matrix, as recorded by the services
+    Usage {
+        /// Only list the usage of this collection
+        #[clap(long)]
+        collection: Option<String>,
+    },
+}
+
+#[cfg(feature = "services")]
+#[derive(clap::Parser, Debug)]
+pub struct ShareArgs {
+    /// Collection id
+    #[clap(long)]
+    pub collection: String,
+    /// `CQL2-Text` or `CQL2-JSON` expression restricting the shared features
+    pub filter: Option<String>,
+    /// Hours until the link expires
+    #[clap(long, default_value = "72")]
+    pub expires_in: u64,
+    /// Public url of the service
+    #[clap(long, default_value = "http://localhost:8484")]
+    pub url: url::Url,
+    /// Secret signing share links, as configured for the service
+    #[clap(long, env, hide_env_values = true)]
+    pub share_secret: String,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, value_parser)]
+    pub database_url: url::Url,
+}
+
+/// Create the database if missing and run pending migrations
+pub async fn migrate(database_url: &url::Url) -> anyhow::Result<()> {
+    Db::setup(database_url).await?;
+
+    tracing::info!("Database is up to date");
+
+    Ok(())
+}
+
+pub async fn collection(args: CollectionArgs) -> anyhow::Result<()> {
+    let db = Db::setup(&args.database_url).await?;
+
+    match args.command {
+        CollectionCommand::Create {
+            id,
+            title,
+            description,
+            storage_srs,
+        } => {
+            let storage_crs = match storage_srs {
+                None | Some(4326) => Crs::default(),
+                Some(srid) => Crs::from_epsg(srid),
+            };
+
+            let mut crs = vec![Crs::default(), Crs::from_epsg(3857)];
+            if !crs.contains(&storage_crs) {
+                crs.push(storage_crs.clone());
+            }
+
+            let collection = Collection {
+                id,
+                title,
+                description,
+                item_type: Some("Feature".to_string()),
+                crs,
+                storage_crs: Some(storage_crs),
+                ..Default::default()
+            };
+
+            let id = db.create_collection(&collection).await?;
+            println!("Created collection `{id}`");
+        }
+        CollectionCommand::Delete { id } => {
+            if db.read_collection(&id).await?.is_none() {
+                anyhow::bail!("Unknown collection `{id}`");
+            }
+            db.delete_collection(&id).await?;
+            println!("Deleted collection `{id}`");
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn user(args: UserArgs) -> anyhow::Result<()> {
+    let db = Db::setup(&args.database_url).await?;
+
+    match args.command {
+        UserCommand::Create { id } => {
+            let id = db.create_user(&id).await?;
+            println!("Created user `{id}`");
+        }
+        UserCommand::Delete { id } => {
+            db.delete_user(&id).await?;
+            println!("Deleted user `{id}`");
+        }
+        UserCommand::List => {
+            for user in db.list_users().await? {
+                match user.created {
+                    Some(created) => println!("{}\t{}", user.id, created.to_rfc3339()),
+                    None => println!("{}", user.id),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn key(args: KeyArgs) -> anyhow::Result<()> {
+    let db = Db::setup(&args.database_url).await?;
+
+    match args.command {
+        KeyCommand::Create { user } => {
+            let key = db.create_key(&user).await?;
+            println!("Created api key `{}` for user `{}`", key.id, key.user);
+            println!("{}", key.key.unwrap_or_default());
+        }
+        KeyCommand::Delete { id } => {
+            db.delete_key(&id).await?;
+            println!("Revoked api key `{id}`");
+        }
+        KeyCommand::List { user } => {
+            for key in db.list_keys(user.as_deref()).await? {
+                let created = key.created.map(|c| c.to_rfc3339()).unwrap_or_default();
+                println!("{}\t{}\t{}", key.id, key.user, created);
+            }
+        }
+        KeyCommand::Usage { user, month } => {
+            for usage in db.key_usage(user.as_deref(), month.as_deref()).await? {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{:.1}",
+                    usage.key,
+                    usage.month,
+                    usage.requests,
+                    usage.features,
+                    usage.tiles,
+                    usage.job_seconds
+                );
+            }
+        }
+        KeyCommand::Quota {
+            id,
+            requests,
+            features,
+            tiles,
+            job_seconds,
+        } => {
+            if requests.is_none() && features.is_none() && tiles.is_none() && job_seconds.is_none()
+            {
+                db.delete_quota(&id).await?;
+                println!("Removed quota of api key `{id}`");
+            } else {
+                db.set_quota(&Quota {
+                    key: id.to_owned(),
+                    requests,
+                    features,
+                    tiles,
+                    job_seconds,
+                })
+                .await?;
+                println!("Set quota of api key `{id}`");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn access(args: AccessArgs) -> anyhow::Result<()> {
+    let db = Db::setup(&args.database_url).await?;
+
+    match args.command {
+        AccessCommand::Set {
+            collection,
+            user,
+            filter,
+        } => {
+            let filter = parse_filter(&filter)?;
+            check_filter(&db, &collection, &filter).await?;
+
+            db.set_access_filter(&AccessFilter {
+                collection: collection.to_owned(),
+                user: user.to_owned(),
+                filter,
+                created: None,
+            })
+            .await?;
+            match user {
+                Some(user) => println!("Restricted collection `{collection}` for user `{user}`"),
+                None => println!("Restricted collection `{collection}` without api key"),
+            }
+        }
+        AccessCommand::Delete { collection, user } => {
+            db.delete_access_filter(&collection, user.as_deref())
+                .await?;
+            println!("Removed access filter of collection `{collection}`");
+        }
+        AccessCommand::List { collection } => {
+            for filter in db.list_access_filters(collection.as_deref()).await? {
+                println!(
+                    "{}\t{}\t{}",
+                    filter.collection,
+                    filter.user.as_deref().unwrap_or("*"),
+                    filter.filter
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn tiles(args: TilesArgs) -> anyhow::Result<()> {
+    let db = Db::setup(&args.database_url).await?;
+
+    match args.command {
+        TilesCommand::Usage { collection } => {
+            for usage in db.tile_usage(collection.as_deref()).await? {
+                println!(
+                    "{}\t{}\t{}\t{}\t{:.3}",
+                    usage.collection,
+                    usage.tile_matrix,
+                    usage.requests,
+                    usage.hits,
+                    usage.hit_ratio()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a link granting temporary read access to the features of a collection
+#[cfg(feature = "services")]
+pub async fn share(args: ShareArgs) -> anyhow::Result<()> {
+    let db = Db::setup(&args.database_url).await?;
+
+    let filter = match &args.filter {
+        Some(filter) => {
+            let filter = parse_filter(filter)?;
+            check_filter(&db, &args.collection, &filter).await?;
+            Some(filter)
+        }
+        None => {
+            if db.read_collection(&args.collection).await?.is_none() {
+                anyhow::bail!("Unknown collection `{}`", args.collection);
+            }
+            None
+        }
+    };
+
+    let valid_for = std::time::Duration::from_secs(args.expires_in * 60 * 60);
+    let share = Share::new(&args.collection, filter, valid_for);
+
+    let mut url = args
+        .url
+        .join(&format!("collections/{}/items", args.collection))?;
+    url.query_pairs_mut()
+        .append_pair(SHARE_PARAMETER, &share.sign(&args.share_secret));
+
+    println!("{url}");
+
+    Ok(())
+}
+
+fn parse_filter(filter: &str) -> anyhow::Result<Expr> {
+    let filter = if filter.trim_start().starts_with('{') {
+        cql2::parse_json(filter)?
+    } else {
+        cql2::parse_text(filter)?
+    };
+
+    Ok(filter)
+}
+
+/// Checks the filter against the queryables of an existing collection
+async fn check_filter(db: &Db, collection: &str, filter: &Expr) -> anyhow::Result<()> {
+    if db.read_collection(collection).await?.is_none() {
+        anyhow::bail!("Unknown collection `{collection}`");
+    }
+    if let Some(queryables) = db.read_queryables(collection).await? {
+        filter.validate(&queryables)?;
+    }
+
+    Ok(())
+}
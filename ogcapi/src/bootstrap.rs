@@ -0,0 +1,135 @@
+use ogcapi_drivers::{postgres::Db, CollectionTransactions, FeatureTransactions};
+use ogcapi_types::{
+    common::{Bbox, Collection, Crs, Extent, SpatialExtent, TemporalExtent},
+    features::FeatureCollection,
+};
+
+#[derive(clap::Parser, Debug)]
+pub struct Args {
+    /// Replace existing collections with the ids of the example datasets
+    #[clap(long)]
+    pub replace: bool,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, value_parser)]
+    pub database_url: url::Url,
+}
+
+/// Small dataset embedded in the binary
+pub struct Dataset {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub bbox: [f64; 4],
+    /// Temporal extent as RFC 3339 `(start, end)`
+    pub interval: Option<(&'static str, &'static str)>,
+    geojson: &'static str,
+}
+
+/// Example datasets loaded by [bootstrap]
+pub const DATASETS: [Dataset; 3] = [
+    Dataset {
+        id: "countries",
+        title: "Countries",
+        description: "Country boundaries of Natural Earth at 1:110m (public domain)",
+        bbox: [-180.0, -90.0, 180.0, 83.6451],
+        interval: None,
+        geojson: include_str!("../assets/examples/countries.geojson"),
+    },
+    Dataset {
+        id: "places",
+        title: "Populated places",
+        description: "Populated places of Natural Earth at 1:110m (public domain)",
+        bbox: [-175.2206, -41.3, 179.2166, 64.15],
+        interval: None,
+        geojson: include_str!("../assets/examples/places.geojson"),
+    },
+    Dataset {
+        id: "weather-stations",
+        title: "Weather stations",
+        description: "Daily mean air temperatures of Swiss weather stations in the \
+            first week of 2024, sample values for demonstration",
+        bbox: [6.1279, 46.0042, 9.3435, 47.3779],
+        interval: Some(("2024-01-01T00:00:00Z", "2024-01-07T00:00:00Z")),
+        geojson: include_str!("../assets/examples/weather-stations.geojson"),
+    },
+];
+
+impl Dataset {
+    pub fn collection(&self) -> Collection {
+        let temporal = self.interval.map(|(start, end)| TemporalExtent {
+            interval: vec![vec![start.parse().ok(), end.parse().ok()]],
+            ..Default::default()
+        });
+
+        Collection {
+            id: self.id.to_string(),
+            title: Some(self.title.to_string()),
+            description: Some(self.description.to_string()),
+            item_type: Some("Feature".to_string()),
+            extent: Some(Extent {
+                spatial: Some(SpatialExtent {
+                    bbox: vec![Bbox::from(self.bbox)],
+                    crs: Crs::default(),
+                }),
+                temporal,
+            }),
+            crs: vec![Crs::default(), Crs::from_epsg(3857)],
+            storage_crs: Some(Crs::default()),
+            ..Default::default()
+        }
+    }
+
+    pub fn features(&self) -> anyhow::Result<FeatureCollection> {
+        Ok(serde_json::from_str(self.geojson)?)
+    }
+}
+
+/// Load the example datasets with any driver, returning the ids of the
+/// created collections
+///
+/// Existing collections are kept unless `replace` is set.
+pub async fn bootstrap<D>(driver: &D, replace: bool) -> anyhow::Result<Vec<String>>
+where
+    D: CollectionTransactions + FeatureTransactions,
+{
+    let mut created = Vec::new();
+
+    for dataset in DATASETS.iter() {
+        if driver.read_collection(dataset.id).await?.is_some() {
+            if !replace {
+                tracing::info!("Skipping existing collection `{}`", dataset.id);
+                continue;
+            }
+            driver.delete_collection(dataset.id).await?;
+        }
+
+        let collection = dataset.collection();
+        driver.create_collection(&collection).await?;
+
+        let fc = dataset.features()?;
+        driver
+            .create_features(&collection.id, &fc.features, &Crs::default())
+            .await?;
+
+        tracing::info!(
+            "Loaded {} features into `{}`",
+            fc.features.len(),
+            collection.id
+        );
+        created.push(collection.id);
+    }
+
+    Ok(created)
+}
+
+/// Load the example datasets into the database
+pub async fn load(args: Args) -> anyhow::Result<()> {
+    let db = Db::setup(&args.database_url).await?;
+
+    for id in bootstrap(&db, args.replace).await? {
+        println!("{id}");
+    }
+
+    Ok(())
+}
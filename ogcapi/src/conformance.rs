@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use ogcapi_client::FeaturesClient;
+
+#[derive(clap::Parser, Debug)]
+pub struct Args {
+    /// Endpoint of the deployed service
+    #[clap(long, default_value = "http://localhost:8484/")]
+    pub url: String,
+
+    /// Write the report as JSON, to be passed to `serve --conformance-report`
+    #[clap(long, value_parser)]
+    pub output: Option<PathBuf>,
+}
+
+/// Test the conformance classes declared by a service, failing if any of
+/// them does not pass
+pub async fn test(args: Args) -> anyhow::Result<()> {
+    let client = FeaturesClient::new(&args.url)?;
+    let report = client.test_conformance().await?;
+
+    for result in &report.results {
+        let outcome = if result.passed() { "passed" } else { "failed" };
+        println!(
+            "{outcome}\t{}\t({} assertions)",
+            result.class, result.assertions
+        );
+        for test in &result.tests {
+            let outcome = if test.passed { "passed" } else { "failed" };
+            println!("\t{outcome}\t{}", test.id);
+        }
+        for failure in &result.failures {
+            println!("\t{failure}");
+        }
+    }
+
+    if let Some(output) = &args.output {
+        tokio::fs::write(output, serde_json::to_vec_pretty(&report)?).await?;
+    }
+
+    let failed = report.results.iter().filter(|r| !r.passed()).count();
+    if failed > 0 {
+        anyhow::bail!("{failed} conformance classes failed");
+    }
+
+    Ok(())
+}
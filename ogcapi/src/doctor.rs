@@ -0,0 +1,29 @@
+use ogcapi_drivers::Severity;
+
+/// Check the environment of the service configuration, failing if any check
+/// failed
+pub async fn doctor(config: &ogcapi_services::Config) -> anyhow::Result<()> {
+    let diagnostics = ogcapi_services::doctor(config).await;
+
+    for d in &diagnostics {
+        let outcome = match d.severity {
+            Severity::Ok => "ok",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        println!("{outcome}\t{}\t{}", d.check, d.message);
+        if let Some(hint) = &d.hint {
+            println!("\t{hint}");
+        }
+    }
+
+    let failed = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{failed} checks failed");
+    }
+
+    Ok(())
+}
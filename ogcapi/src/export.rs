@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use futures::StreamExt;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+use ogcapi_drivers::{postgres::Db, CollectionTransactions, FeatureTransactions};
+use ogcapi_types::{
+    common::{Bbox, Crs},
+    features::Query,
+};
+
+/// Record separator of `GeoJSON` text sequences
+const RS: u8 = 0x1e;
+
+#[derive(clap::Parser, Debug)]
+pub struct Args {
+    /// Output file, the format is derived from the extension (`geojson`, `geojsons` or `gpkg`)
+    #[clap(long, value_parser)]
+    pub output: PathBuf,
+
+    /// Collection to export
+    #[clap(long)]
+    pub collection: String,
+
+    /// Only export items intersecting the bounding box `minx,miny,maxx,maxy`
+    #[clap(long)]
+    pub bbox: Option<Bbox>,
+
+    /// Only export items matching the `CQL2` text filter
+    #[clap(long)]
+    pub filter: Option<String>,
+
+    /// Target srs of the exported geometries, defaults to `CRS84`
+    #[clap(long)]
+    pub t_srs: Option<i32>,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, value_parser)]
+    pub database_url: url::Url,
+}
+
+/// Export the items of a collection to a file
+pub async fn export(args: Args) -> anyhow::Result<()> {
+    let db = Db::setup(&args.database_url).await?;
+
+    if db.read_collection(&args.collection).await?.is_none() {
+        anyhow::bail!("Unknown collection `{}`", args.collection);
+    }
+
+    let query = Query {
+        bbox: args.bbox.to_owned(),
+        filter: args.filter.to_owned(),
+        crs: args.t_srs.map(Crs::from_epsg).unwrap_or_default(),
+        ..Default::default()
+    };
+
+    let now = std::time::Instant::now();
+
+    let count = match args.output.extension().and_then(|e| e.to_str()) {
+        Some("geojson") | Some("json") => write_geojson(&db, &args, &query, false).await?,
+        Some("geojsons") | Some("geojsonl") => write_geojson(&db, &args, &query, true).await?,
+        #[cfg(feature = "services")]
+        Some("gpkg") => {
+            let state =
+                ogcapi_services::AppState::new_with(db, ogcapi_services::OpenAPI::default()).await;
+            ogcapi_services::export_geopackage(&state, &args.output, &args.collection, &query)
+                .await?;
+            None
+        }
+        _ => anyhow::bail!("Unsupported output format `{}`", args.output.display()),
+    };
+
+    let elapsed = now.elapsed().as_millis() as f64 / 1000.0;
+    match count {
+        Some(count) => tracing::info!("Exported {count} features in {elapsed} seconds"),
+        None => tracing::info!("Exported `{}` in {elapsed} seconds", args.collection),
+    }
+
+    Ok(())
+}
+
+/// Write the items as `GeoJSON` feature collection or text sequence
+async fn write_geojson(
+    db: &Db,
+    args: &Args,
+    query: &Query,
+    sequence: bool,
+) -> anyhow::Result<Option<usize>> {
+    let file = tokio::fs::File::create(&args.output).await?;
+    let mut writer = BufWriter::new(file);
+
+    if !sequence {
+        writer
+            .write_all(br#"{"type":"FeatureCollection","features":["#)
+            .await?;
+    }
+
+    let mut count = 0;
+    let mut features = db.stream_items(&args.collection, query);
+    while let Some(feature) = features.next().await {
+        let json = serde_json::to_vec(&feature?)?;
+
+        if sequence {
+            writer.write_u8(RS).await?;
+        } else if count > 0 {
+            writer.write_u8(b',').await?;
+        }
+        writer.write_all(&json).await?;
+        if sequence {
+            writer.write_u8(b'\n').await?;
+        }
+
+        count += 1;
+    }
+
+    if !sequence {
+        writer.write_all(b"]}").await?;
+    }
+    writer.flush().await?;
+
+    Ok(Some(count))
+}
@@ -20,6 +20,14 @@ pub struct Args {
     #[clap(long)]
     pub t_srs: Option<u32>,
 
+    /// Tag mapping (json) of osm objects to collections, defaults to all in `collection`
+    #[clap(long, value_parser)]
+    pub mapping: Option<std::path::PathBuf>,
+
+    /// Geometry column(s) of tabular input, a `wkt` column or `x,y` columns, detected if omitted
+    #[clap(long)]
+    pub geometry: Option<String>,
+
     /// Postgres database url
     #[clap(long, env, hide_env_values = true, value_parser)]
     pub database_url: url::Url,
@@ -37,6 +45,8 @@ impl Args {
             filter: None,
             s_srs: None,
             t_srs: None,
+            mapping: None,
+            geometry: None,
             database_url: database_url.to_owned(),
         }
     }
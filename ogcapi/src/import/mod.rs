@@ -3,6 +3,7 @@ mod boundaries;
 pub mod geojson;
 pub mod ogr;
 pub mod osm;
+pub mod tabular;
 
 pub use args::Args;
 
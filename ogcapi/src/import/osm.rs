@@ -1,7 +1,12 @@
-use std::{collections::BTreeMap, fs::File};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    path::Path,
+};
 
 use geo::{Coord, Geometry, GeometryCollection, LineString, MultiLineString, Point, Polygon};
 use osmpbfreader::{NodeId, OsmId, OsmObj, OsmPbfReader};
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
 use ogcapi_drivers::{postgres::Db, CollectionTransactions};
@@ -9,81 +14,228 @@ use ogcapi_types::common::{Collection, Crs};
 
 use super::Args;
 
+/// Mapping of osm objects to collections
+///
+/// ```json
+/// {
+///   "collections": [
+///     { "id": "buildings", "geometry": "polygon", "filter": { "building": [] } },
+///     {
+///       "id": "roads",
+///       "geometry": "line",
+///       "filter": { "highway": ["motorway", "primary", "secondary"] },
+///       "tags": ["highway", "name", "ref"]
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TagMapping {
+    pub collections: Vec<LayerMapping>,
+}
+
+/// Mapping of osm objects to a single collection
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LayerMapping {
+    /// Collection id
+    pub id: String,
+    /// Geometry type of the collection, any if omitted
+    pub geometry: Option<GeometryKind>,
+    /// Tags an object must have, an empty list of values matches any value
+    #[serde(default)]
+    pub filter: HashMap<String, Vec<String>>,
+    /// Tags to keep as properties, all if omitted
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GeometryKind {
+    Point,
+    Line,
+    Polygon,
+}
+
+impl TagMapping {
+    /// Read a mapping from a json file
+    pub fn from_path(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Ok(serde_json::from_reader(File::open(path)?)?)
+    }
+
+    /// Map all tagged objects into a single collection
+    pub fn all(collection: &str) -> Self {
+        TagMapping {
+            collections: vec![LayerMapping {
+                id: collection.to_owned(),
+                geometry: None,
+                filter: HashMap::new(),
+                tags: None,
+            }],
+        }
+    }
+
+    fn matches(&self, obj: &OsmObj) -> bool {
+        self.collections.iter().any(|l| l.matches(obj))
+    }
+}
+
+impl LayerMapping {
+    fn matches(&self, obj: &OsmObj) -> bool {
+        !obj.tags().is_empty()
+            && self.filter.iter().all(|(key, values)| {
+                obj.tags().get(key.as_str()).is_some_and(|v| {
+                    values.is_empty() || values.iter().any(|value| value == v.as_str())
+                })
+            })
+    }
+
+    fn properties(&self, obj: &OsmObj) -> Map<String, Value> {
+        obj.tags()
+            .iter()
+            .filter(|(k, _)| {
+                self.tags
+                    .as_ref()
+                    .map_or(true, |tags| tags.iter().any(|t| t == k.as_str()))
+            })
+            .map(|(k, v)| (k.to_string(), Value::from(v.as_str())))
+            .collect()
+    }
+
+    /// Cast the geometry to the kind of the collection if possible
+    fn geometry(&self, geometry: &Geometry<f64>) -> Option<Geometry<f64>> {
+        match (self.geometry, geometry) {
+            (None, g) => Some(g.to_owned()),
+            (Some(GeometryKind::Point), g @ (Geometry::Point(_) | Geometry::MultiPoint(_))) => {
+                Some(g.to_owned())
+            }
+            (
+                Some(GeometryKind::Line),
+                g @ (Geometry::LineString(_) | Geometry::MultiLineString(_)),
+            ) => Some(g.to_owned()),
+            // closed ways are built as polygons
+            (Some(GeometryKind::Line), Geometry::Polygon(p)) => {
+                Some(p.exterior().to_owned().into())
+            }
+            (
+                Some(GeometryKind::Polygon),
+                g @ (Geometry::Polygon(_) | Geometry::MultiPolygon(_)),
+            ) => Some(g.to_owned()),
+            _ => None,
+        }
+    }
+}
+
 /// Import osm data from pbf file
 pub async fn load(args: Args) -> Result<(), anyhow::Error> {
     // Setup a db connection pool
     let db = Db::setup(&args.database_url).await?;
 
-    // Create collection
-    let collection = Collection {
-        id: args.collection.to_owned(),
-        crs: vec![Crs::default()],
-        ..Default::default()
+    let mapping = match &args.mapping {
+        Some(path) => TagMapping::from_path(path)?,
+        None => TagMapping::all(&args.collection),
     };
-    // db.delete_collection(&collection.id).await?;
-    db.create_collection(&collection).await?;
+
+    import(&db, args.input.as_path(), &mapping).await?;
+
+    Ok(())
+}
+
+/// Import the objects of a pbf file into the collections of the mapping,
+/// returning the number of imported features per collection
+pub async fn import(
+    db: &Db,
+    path: &Path,
+    mapping: &TagMapping,
+) -> anyhow::Result<HashMap<String, usize>> {
+    // Create collections
+    for layer in &mapping.collections {
+        if db.read_collection(&layer.id).await?.is_none() {
+            let collection = Collection {
+                id: layer.id.to_owned(),
+                crs: vec![Crs::default()],
+                ..Default::default()
+            };
+            db.create_collection(&collection).await?;
+        }
+    }
 
     // Open file
-    let file = File::open(args.input.as_path())?;
+    let file = File::open(path)?;
     let mut pbf = OsmPbfReader::new(file);
 
     let blob_count = pbf.blobs().count();
     tracing::info!("Found {} blobs!", blob_count);
     pbf.rewind()?;
 
-    // Cache
-    let objs = pbf.get_objs_and_deps(|_| true)?;
+    // Cache matching objects and their dependencies
+    let objs = pbf.get_objs_and_deps(|obj| mapping.matches(obj))?;
     tracing::info!("Found {} obj and dependencies!", objs.len());
-    pbf.rewind()?;
 
     tracing::info!("Done caching!");
 
-    let mut tx = db.pool.begin().await?;
+    let mut items: HashMap<&str, Items> = HashMap::new();
 
-    for obj in pbf.par_iter() {
-        let obj = obj.unwrap();
+    for obj in objs.values() {
+        let layers: Vec<&LayerMapping> = mapping
+            .collections
+            .iter()
+            .filter(|l| l.matches(obj))
+            .collect();
 
-        // skip objects without tags
-        if obj.tags().is_empty() {
+        if layers.is_empty() {
             continue;
         }
 
-        // get id
-        let id = obj.id().inner_id();
-
-        // extract tags
-        let mut properties = Map::new();
-
-        let keys = obj.tags().keys();
-        let values = obj.tags().values();
+        // build geometry
+        let Some(geometry) = geometry_from_obj(obj, &objs) else {
+            continue;
+        };
 
-        for (k, v) in keys.zip(values) {
-            properties.insert(k.to_string(), Value::from(v.as_str()));
+        for layer in layers {
+            if let Some(wkb) = layer
+                .geometry(&geometry)
+                .and_then(|g| wkb::geom_to_wkb(&g).ok())
+            {
+                let items = items.entry(layer.id.as_str()).or_default();
+                items.ids.push(id(obj));
+                items
+                    .properties
+                    .push(Some(sqlx::types::Json(layer.properties(obj))));
+                items.geoms.push(wkb);
+            }
         }
+    }
 
-        // build geometry
-        if let Some(geometry) =
-            geometry_from_obj(&obj, &objs).and_then(|g| wkb::geom_to_wkb(&g).ok())
-        {
-            sqlx::query(&format!(
-                r#"INSERT INTO items.{} (
-                    id,
-                    properties,
-                    geom
-                ) VALUES ($1, $2, ST_GeomFromWKB($3, 4326))"#,
-                collection.id
-            ))
-            .bind(id.to_string())
-            .bind(Value::from(properties) as Value)
-            .bind(geometry as Vec<u8>)
-            .execute(&mut *tx)
-            .await?;
-        }
+    let mut counts = HashMap::new();
+    for (collection, items) in items {
+        super::bulk_load_items(
+            collection,
+            &items.ids,
+            &items.properties,
+            &items.geoms,
+            &db.pool,
+        )
+        .await?;
+        counts.insert(collection.to_owned(), items.ids.len());
     }
 
-    tx.commit().await?;
+    Ok(counts)
+}
 
-    Ok(())
+#[derive(Default)]
+struct Items {
+    ids: Vec<String>,
+    properties: Vec<Option<sqlx::types::Json<Map<String, Value>>>>,
+    geoms: Vec<Vec<u8>>,
+}
+
+/// Unique id across object types, e.g. `node/123`
+fn id(obj: &OsmObj) -> String {
+    match obj.id() {
+        OsmId::Node(id) => format!("node/{}", id.0),
+        OsmId::Way(id) => format!("way/{}", id.0),
+        OsmId::Relation(id) => format!("relation/{}", id.0),
+    }
 }
 
 fn geometry_from_obj(obj: &OsmObj, objs: &BTreeMap<OsmId, OsmObj>) -> Option<Geometry<f64>> {
@@ -197,3 +349,129 @@ impl<'a> Iterator for RefIter<'a> {
         None
     }
 }
+
+/// Asynchronous process importing a pbf file with a tag mapping
+///
+/// ```bash
+/// curl http://localhost:8484/processes/osm-import/execution \
+///         -H 'Content-Type: application/json' \
+///         -d "{\"inputs\": {\"pbf\": \"$(base64 -w0 extract.osm.pbf)\", \"mapping\": $(cat mapping.json)}}"
+/// ```
+#[cfg(feature = "services")]
+#[derive(Clone)]
+pub struct OsmImport;
+
+/// Inputs for the `osm-import` process
+#[cfg(feature = "services")]
+#[derive(Serialize, Deserialize)]
+struct OsmImportInputs {
+    pbf: String,
+    collection: Option<String>,
+    mapping: Option<TagMapping>,
+}
+
+#[cfg(feature = "services")]
+#[axum::async_trait]
+impl ogcapi_services::Processor for OsmImport {
+    fn id(&self) -> String {
+        "osm-import".to_string()
+    }
+
+    fn process(&self) -> ogcapi_types::processes::Process {
+        let mut process = ogcapi_types::processes::Process::new(
+            self.id(),
+            "0.1.0",
+            &serde_json::json!({
+                "type": "object",
+                "required": ["pbf"],
+                "properties": {
+                    "pbf": {
+                        "type": "string",
+                        "contentEncoding": "base64",
+                        "contentMediaType": "application/vnd.openstreetmap.data+pbf"
+                    },
+                    "collection": {
+                        "type": "string",
+                        "description": "Collection for all tagged objects if no mapping is given"
+                    },
+                    "mapping": {
+                        "type": "object",
+                        "description": "Mapping of osm objects to collections"
+                    }
+                }
+            }),
+            &serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "collections": { "type": "array" }
+                }
+            }),
+        );
+        process.summary.job_control_options =
+            vec![ogcapi_types::processes::JobControlOptions::AsyncExecute];
+        process
+    }
+
+    async fn execute(
+        &self,
+        execute: ogcapi_types::processes::Execute,
+        state: &ogcapi_services::AppState,
+        url: &url::Url,
+    ) -> ogcapi_services::Result<axum::response::Response> {
+        use axum::http::StatusCode;
+        use base64::Engine;
+        use ogcapi_services::Error;
+
+        let value = serde_json::to_value(execute.inputs).map_err(anyhow::Error::from)?;
+        let inputs: OsmImportInputs = serde_json::from_value(value)
+            .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+        if inputs.mapping.is_none() && inputs.collection.is_none() {
+            return Err(Error::Exception(
+                StatusCode::BAD_REQUEST,
+                "Either a `mapping` or a `collection` is required".to_string(),
+            ));
+        }
+
+        base64::engine::general_purpose::STANDARD
+            .decode(inputs.pbf.trim())
+            .map_err(|e| Error::Exception(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+        let inputs = serde_json::to_value(inputs).map_err(anyhow::Error::from)?;
+        ogcapi_services::queue_job(self, inputs, state, url).await
+    }
+
+    async fn run_job(
+        &self,
+        job_id: &str,
+        inputs: Value,
+        state: &ogcapi_services::AppState,
+    ) -> anyhow::Result<HashMap<String, ogcapi_types::processes::InlineOrRefData>> {
+        use base64::Engine;
+
+        let inputs: OsmImportInputs = serde_json::from_value(inputs)?;
+        let mapping = match (inputs.mapping, inputs.collection) {
+            (Some(mapping), _) => mapping,
+            (None, Some(collection)) => TagMapping::all(&collection),
+            (None, None) => anyhow::bail!("Either a `mapping` or a `collection` is required"),
+        };
+        let data = base64::engine::general_purpose::STANDARD.decode(inputs.pbf.trim())?;
+
+        let path = std::env::temp_dir().join(format!("{job_id}.osm.pbf"));
+        tokio::fs::write(&path, data).await?;
+
+        let counts = import(&state.db, &path, &mapping).await;
+
+        tokio::fs::remove_file(&path).await?;
+
+        let collections: Vec<Value> = counts?
+            .into_iter()
+            .map(|(id, count)| serde_json::json!({ "id": id, "numberImported": count }))
+            .collect();
+
+        Ok(HashMap::from([(
+            "collections".to_string(),
+            serde_json::from_value(Value::from(collections))?,
+        )]))
+    }
+}
@@ -0,0 +1,296 @@
+use serde_json::{json, Map, Value};
+
+use ogcapi_drivers::{postgres::Db, CollectionTransactions};
+use ogcapi_types::{
+    common::{Collection, Crs},
+    features::Queryables,
+};
+
+use super::Args;
+
+/// Number of rows sent to the database at once
+const CHUNK_SIZE: usize = 10000;
+
+/// Column names recognized as geometry columns
+const WKT_COLUMNS: [&str; 4] = ["wkt", "geometry", "geom", "the_geom"];
+const X_COLUMNS: [&str; 5] = ["x", "lon", "lng", "long", "longitude"];
+const Y_COLUMNS: [&str; 3] = ["y", "lat", "latitude"];
+
+/// Column(s) holding the geometry
+#[derive(Debug, PartialEq, Eq)]
+enum GeometryColumns {
+    Wkt(usize),
+    XY(usize, usize),
+}
+
+/// Type of a column, inferred from its values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldType {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+}
+
+impl FieldType {
+    fn of(value: &str) -> Self {
+        if value.is_empty() {
+            FieldType::Null
+        } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            FieldType::Boolean
+        } else if value.parse::<i64>().is_ok() {
+            FieldType::Integer
+        } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+            FieldType::Number
+        } else {
+            FieldType::String
+        }
+    }
+
+    /// Widen the type to also hold the value
+    fn merge(self, value: &str) -> Self {
+        match (self, FieldType::of(value)) {
+            (t, FieldType::Null) | (FieldType::Null, t) => t,
+            (a, b) if a == b => a,
+            (FieldType::Integer, FieldType::Number) | (FieldType::Number, FieldType::Integer) => {
+                FieldType::Number
+            }
+            _ => FieldType::String,
+        }
+    }
+
+    fn parse(self, value: &str) -> Value {
+        if value.is_empty() {
+            return Value::Null;
+        }
+        match self {
+            FieldType::Null => Value::Null,
+            FieldType::Boolean => Value::from(value.eq_ignore_ascii_case("true")),
+            FieldType::Integer => value.parse::<i64>().map(Value::from).unwrap_or_default(),
+            FieldType::Number => value.parse::<f64>().map(Value::from).unwrap_or_default(),
+            FieldType::String => Value::from(value),
+        }
+    }
+
+    fn schema(self) -> Value {
+        match self {
+            FieldType::Null | FieldType::String => json!({ "type": "string" }),
+            FieldType::Boolean => json!({ "type": "boolean" }),
+            FieldType::Integer => json!({ "type": "integer" }),
+            FieldType::Number => json!({ "type": "number" }),
+        }
+    }
+}
+
+/// Import tabular data (`CSV`) with `WKT` or `x`/`y` geometry columns
+pub async fn load(args: Args) -> anyhow::Result<()> {
+    // Setup driver
+    let db = Db::setup(&args.database_url).await?;
+
+    // Extract data
+    let delimiter = match args.input.extension().and_then(|e| e.to_str()) {
+        Some("tsv") => b'\t',
+        _ => b',',
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_path(&args.input)?;
+
+    let headers: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_owned())
+        .collect();
+
+    let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+
+    let geometry = geometry_columns(&headers, args.geometry.as_deref())?;
+    let id_column = headers.iter().position(|h| h.eq_ignore_ascii_case("id"));
+
+    let is_property = |i: usize| {
+        Some(i) != id_column
+            && match geometry {
+                GeometryColumns::Wkt(g) => i != g,
+                GeometryColumns::XY(x, y) => i != x && i != y,
+            }
+    };
+
+    // Infer property types
+    let mut types = vec![FieldType::Null; headers.len()];
+    for record in records.iter() {
+        for (t, value) in types.iter_mut().zip(record.iter()) {
+            *t = t.merge(value.trim());
+        }
+    }
+
+    // Create collection
+    let srid = args.s_srs.map(|srs| srs as i32).unwrap_or(4326);
+    let storage_srid = args.t_srs.map(|srs| srs as i32).unwrap_or(srid);
+    let storage_crs = match storage_srid {
+        4326 => Crs::default(),
+        srid => Crs::from_epsg(srid),
+    };
+
+    let mut crs = vec![Crs::default(), Crs::from_epsg(3857)];
+    if !crs.contains(&storage_crs) {
+        crs.push(storage_crs.clone());
+    }
+
+    let collection = Collection {
+        id: args.collection.to_owned(),
+        item_type: Some("Feature".to_string()),
+        crs,
+        storage_crs: Some(storage_crs),
+        ..Default::default()
+    };
+
+    db.delete_collection(&collection.id).await?;
+    db.create_collection(&collection).await?;
+
+    // Queryables
+    let mut properties: Map<String, Value> = headers
+        .iter()
+        .zip(types.iter())
+        .enumerate()
+        .filter(|(i, _)| is_property(*i))
+        .map(|(_, (name, t))| {
+            let mut schema = t.schema();
+            schema["title"] = Value::from(name.as_str());
+            (name.to_owned(), schema)
+        })
+        .collect();
+    properties.insert(
+        "geometry".to_string(),
+        match geometry {
+            GeometryColumns::Wkt(_) => json!({ "format": "geometry-any" }),
+            GeometryColumns::XY(_, _) => json!({ "format": "geometry-point" }),
+        },
+    );
+
+    let queryables = Queryables {
+        title: Some(collection.id.to_owned()),
+        properties,
+        ..Default::default()
+    };
+    db.update_queryables(&collection.id, &queryables).await?;
+
+    // Load features
+    let now = std::time::Instant::now();
+
+    let mut conn = db.pool.acquire().await?;
+
+    sqlx::query("CREATE TEMP TABLE import (id text, properties jsonb, geom geometry)")
+        .execute(&mut *conn)
+        .await?;
+
+    let mut copy = conn
+        .copy_in_raw("COPY import (id, properties, geom) FROM STDIN WITH (FORMAT csv)")
+        .await?;
+
+    let mut skipped = 0;
+    for (chunk_index, chunk) in records.chunks(CHUNK_SIZE).enumerate() {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        for (i, record) in chunk.iter().enumerate() {
+            let wkt = match geometry {
+                GeometryColumns::Wkt(g) => record.get(g).map(|wkt| wkt.trim().to_owned()),
+                GeometryColumns::XY(x, y) => {
+                    match (
+                        record.get(x).and_then(|x| x.trim().parse::<f64>().ok()),
+                        record.get(y).and_then(|y| y.trim().parse::<f64>().ok()),
+                    ) {
+                        (Some(x), Some(y)) => Some(format!("POINT({x} {y})")),
+                        _ => None,
+                    }
+                }
+            };
+
+            let Some(wkt) = wkt.filter(|wkt| !wkt.is_empty()) else {
+                skipped += 1;
+                continue;
+            };
+
+            let id = match id_column.and_then(|i| record.get(i)) {
+                Some(id) => id.trim().to_owned(),
+                None => (chunk_index * CHUNK_SIZE + i + 1).to_string(),
+            };
+
+            let properties: Map<String, Value> = record
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| is_property(*i))
+                .map(|(i, value)| (headers[i].to_owned(), types[i].parse(value.trim())))
+                .collect();
+
+            writer.write_record([id, Value::from(properties).to_string(), wkt])?;
+        }
+
+        copy.send(writer.into_inner()?).await?;
+    }
+
+    copy.finish().await?;
+
+    let count = sqlx::query(&format!(
+        r#"
+        INSERT INTO items."{}" (id, properties, geom)
+        SELECT id, properties, ST_Transform(ST_SetSRID(geom, $1), $2)
+        FROM import
+        "#,
+        collection.id
+    ))
+    .bind(srid)
+    .bind(storage_srid)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    sqlx::query("DROP TABLE import").execute(&mut *conn).await?;
+
+    if skipped > 0 {
+        tracing::warn!("Skipped {skipped} rows without geometry");
+    }
+
+    // stats
+    let elapsed = now.elapsed().as_millis() as f64 / 1000.0;
+    tracing::info!(
+        "Loaded {count} features in {elapsed} seconds ({:.2}/s)",
+        count as f64 / elapsed
+    );
+
+    Ok(())
+}
+
+/// Locate the geometry column(s), either given as `wkt` or `x,y` column names
+/// or detected by common names
+fn geometry_columns(headers: &[String], columns: Option<&str>) -> anyhow::Result<GeometryColumns> {
+    let position = |names: &[&str]| {
+        headers
+            .iter()
+            .position(|h| names.iter().any(|n| h.eq_ignore_ascii_case(n)))
+    };
+
+    let geometry = match columns.map(|c| c.split(',').map(str::trim).collect::<Vec<_>>()) {
+        Some(columns) => match columns.as_slice() {
+            [wkt] => position(&[wkt]).map(GeometryColumns::Wkt),
+            [x, y] => position(&[x])
+                .zip(position(&[y]))
+                .map(|(x, y)| GeometryColumns::XY(x, y)),
+            _ => None,
+        },
+        None => position(&WKT_COLUMNS)
+            .map(GeometryColumns::Wkt)
+            .or_else(|| {
+                position(&X_COLUMNS)
+                    .zip(position(&Y_COLUMNS))
+                    .map(|(x, y)| GeometryColumns::XY(x, y))
+            }),
+    };
+
+    geometry.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unable to find geometry column(s), use `--geometry` to specify a `wkt` column or `x,y` columns"
+        )
+    })
+}
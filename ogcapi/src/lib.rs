@@ -1,7 +1,19 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "drivers")]
+pub mod admin;
+#[cfg(feature = "drivers")]
+pub mod bootstrap;
+#[cfg(feature = "client")]
+pub mod conformance;
+#[cfg(all(feature = "drivers", feature = "services"))]
+pub mod doctor;
+#[cfg(feature = "drivers")]
+pub mod export;
 #[cfg(feature = "import")]
 pub mod import;
+#[cfg(all(feature = "client", feature = "drivers"))]
+pub mod sync;
 
 #[cfg(feature = "client")]
 pub mod client {
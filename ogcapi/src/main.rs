@@ -1,4 +1,5 @@
 use clap::Parser;
+#[cfg(not(feature = "services"))]
 use tracing_subscriber::prelude::*;
 
 #[derive(Parser, Debug)]
@@ -13,9 +14,50 @@ pub enum Command {
     /// Import geodata into the database
     #[cfg(feature = "import")]
     Import(ogcapi::import::Args),
+    /// Export a collection to a file
+    #[cfg(feature = "drivers")]
+    Export(ogcapi::export::Args),
     /// Start the ogcapi services
     #[cfg(feature = "services")]
-    Serve(ogcapi_services::Config),
+    Serve(Box<ogcapi_services::Config>),
+    /// Check the database, object store, directories and MQTT broker of the
+    /// service configuration
+    #[cfg(all(feature = "drivers", feature = "services"))]
+    Doctor(Box<ogcapi_services::Config>),
+    /// Create the database if missing and run pending migrations
+    #[cfg(feature = "drivers")]
+    Migrate {
+        /// Postgres database url
+        #[clap(long, env, hide_env_values = true, value_parser)]
+        database_url: url::Url,
+    },
+    /// Load small example datasets to try out the services
+    #[cfg(feature = "drivers")]
+    Bootstrap(ogcapi::bootstrap::Args),
+    /// Manage collections
+    #[cfg(feature = "drivers")]
+    Collection(ogcapi::admin::CollectionArgs),
+    /// Manage users
+    #[cfg(feature = "drivers")]
+    User(ogcapi::admin::UserArgs),
+    /// Manage api keys of users
+    #[cfg(feature = "drivers")]
+    Key(ogcapi::admin::KeyArgs),
+    /// Manage feature level access filters of collections
+    #[cfg(feature = "drivers")]
+    Access(ogcapi::admin::AccessArgs),
+    /// Inspect the usage of the vector tiles
+    #[cfg(feature = "drivers")]
+    Tiles(ogcapi::admin::TilesArgs),
+    /// Test a deployed service against the conformance classes it declares
+    #[cfg(feature = "client")]
+    Conformance(ogcapi::conformance::Args),
+    /// Create a link granting temporary read access to a collection
+    #[cfg(all(feature = "drivers", feature = "services"))]
+    Share(ogcapi::admin::ShareArgs),
+    /// Replicate collections from another instance
+    #[cfg(all(feature = "client", feature = "drivers"))]
+    Sync(ogcapi::sync::Args),
 }
 
 #[tokio::main]
@@ -23,7 +65,10 @@ async fn main() -> anyhow::Result<()> {
     // setup env
     dotenvy::dotenv().ok();
 
-    // setup tracing
+    // setup tracing, with the statement times of the request log
+    #[cfg(feature = "services")]
+    ogcapi_services::telemetry::init();
+    #[cfg(not(feature = "services"))]
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::from_default_env())
         .with(tracing_subscriber::fmt::layer().pretty())
@@ -43,16 +88,31 @@ async fn main() -> anyhow::Result<()> {
                         tracing::debug!("Using geojson loader ...");
                         ogcapi::import::geojson::load(args).await?
                     }
+                    Some("csv") | Some("tsv") => ogcapi::import::tabular::load(args).await?,
                     _ => ogcapi::import::ogr::load(args).await?,
                 }
             }
         }
+        #[cfg(feature = "drivers")]
+        Command::Export(args) => ogcapi::export::export(args).await?,
         #[cfg(feature = "services")]
         Command::Serve(config) => {
             // Application state
             let state = ogcapi_services::AppState::new_from(&config)
                 .await
-                .processors(vec![Box::new(ogcapi_services::Greeter)]);
+                .processors(vec![
+                    Box::new(ogcapi_services::Greeter),
+                    Box::new(ogcapi_services::GeoPackageImport),
+                    Box::new(ogcapi_services::GeoPackageExport),
+                    Box::new(ogcapi_services::OpenEo),
+                    Box::new(ogcapi_services::MapPrint),
+                    Box::new(ogcapi_services::CollectionSnapshot),
+                    Box::new(ogcapi_services::CollectionRestore),
+                    Box::new(ogcapi_services::CollectionReproject),
+                    Box::new(ogcapi_services::OfflineBundle),
+                    #[cfg(feature = "import")]
+                    Box::new(ogcapi::import::osm::OsmImport),
+                ]);
 
             // Build & run with hyper
             ogcapi_services::Service::new_with(&config, state)
@@ -60,6 +120,28 @@ async fn main() -> anyhow::Result<()> {
                 .serve()
                 .await;
         }
+        #[cfg(all(feature = "drivers", feature = "services"))]
+        Command::Doctor(config) => ogcapi::doctor::doctor(&config).await?,
+        #[cfg(feature = "drivers")]
+        Command::Migrate { database_url } => ogcapi::admin::migrate(&database_url).await?,
+        #[cfg(feature = "drivers")]
+        Command::Bootstrap(args) => ogcapi::bootstrap::load(args).await?,
+        #[cfg(feature = "drivers")]
+        Command::Collection(args) => ogcapi::admin::collection(args).await?,
+        #[cfg(feature = "drivers")]
+        Command::User(args) => ogcapi::admin::user(args).await?,
+        #[cfg(feature = "drivers")]
+        Command::Key(args) => ogcapi::admin::key(args).await?,
+        #[cfg(feature = "drivers")]
+        Command::Access(args) => ogcapi::admin::access(args).await?,
+        #[cfg(feature = "drivers")]
+        Command::Tiles(args) => ogcapi::admin::tiles(args).await?,
+        #[cfg(feature = "client")]
+        Command::Conformance(args) => ogcapi::conformance::test(args).await?,
+        #[cfg(all(feature = "drivers", feature = "services"))]
+        Command::Share(args) => ogcapi::admin::share(args).await?,
+        #[cfg(all(feature = "client", feature = "drivers"))]
+        Command::Sync(args) => ogcapi::sync::sync(args).await?,
     }
 
     Ok(())
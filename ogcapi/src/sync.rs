@@ -0,0 +1,194 @@
+//! Replication of collections from another instance
+//!
+//! The first run copies a collection with its items, later runs apply the
+//! changes logged by the other instance since the previous run. The position in
+//! the change log is kept in the database per instance and collection.
+
+use std::time::Duration;
+
+use futures::TryStreamExt;
+
+use ogcapi_client::FeaturesClient;
+use ogcapi_drivers::{postgres::Db, CollectionTransactions, FeatureChanges, FeatureTransactions};
+use ogcapi_types::{
+    common::Crs,
+    features::{Feature, Query},
+};
+
+/// Number of features and changes fetched at once
+const PAGE_SIZE: usize = 1000;
+
+#[derive(clap::Parser, Debug)]
+pub struct Args {
+    /// Endpoint of the instance to replicate from
+    #[clap(long)]
+    pub url: String,
+
+    /// Ids of the collections to replicate
+    #[clap(required = true)]
+    pub collections: Vec<String>,
+
+    /// Api key for the instance, required for collections restricted to
+    /// some of their features
+    #[clap(long, env = "OGCAPI_API_KEY", hide_env_values = true)]
+    pub api_key: Option<String>,
+
+    /// Keep replicating, polling for changes every given number of seconds
+    #[clap(long)]
+    pub interval: Option<u64>,
+
+    /// Postgres database url
+    #[clap(long, env, hide_env_values = true, value_parser)]
+    pub database_url: url::Url,
+}
+
+/// Replicate collections from another instance, once or continuously
+pub async fn sync(args: Args) -> anyhow::Result<()> {
+    let client = match &args.api_key {
+        Some(key) => FeaturesClient::with_api_key(&args.url, key)?,
+        None => FeaturesClient::new(&args.url)?,
+    };
+    let source = format!("{}/", args.url.trim_end_matches('/'));
+
+    let db = Db::setup(&args.database_url).await?;
+
+    loop {
+        for collection in &args.collections {
+            let result = sync_collection(&client, &db, &source, collection).await;
+
+            match (result, args.interval) {
+                (Ok(_), _) => {}
+                // retried with the next poll
+                (Err(e), Some(_)) => eprintln!("Failed to replicate `{collection}`: {e:#}"),
+                (Err(e), None) => return Err(e),
+            }
+        }
+
+        match args.interval {
+            Some(seconds) => tokio::time::sleep(Duration::from_secs(seconds)).await,
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Apply the changes of a collection since the last run, copying the
+/// collection on the first one
+async fn sync_collection(
+    client: &FeaturesClient,
+    db: &Db,
+    source: &str,
+    id: &str,
+) -> anyhow::Result<()> {
+    let mut token = match db.sync_token(source, id).await? {
+        Some(token) => token,
+        None => {
+            let token = copy_collection(client, db, source, id).await?;
+            db.set_sync_token(source, id, &token).await?;
+            token
+        }
+    };
+
+    let mut applied = 0;
+    loop {
+        let changeset = client.changes(id, Some(&token), Some(PAGE_SIZE)).await?;
+        if changeset.changes.is_empty() {
+            break;
+        }
+
+        // the current state of the feature is applied, whatever the change
+        for logged in changeset.changes {
+            let exists = db
+                .read_feature(id, &logged.change.id, &Crs::default())
+                .await?
+                .is_some();
+
+            match logged.feature {
+                Some(mut feature) => {
+                    prepare(&mut feature, id);
+                    if exists {
+                        db.update_feature(&feature).await?;
+                    } else {
+                        db.create_feature(&feature).await?;
+                    }
+                }
+                None if exists => db.delete_feature(id, &logged.change.id).await?,
+                None => {}
+            }
+            applied += 1;
+        }
+
+        token = changeset.token;
+        db.set_sync_token(source, id, &token).await?;
+    }
+
+    if applied > 0 {
+        println!("Applied {applied} changes to `{id}`");
+    }
+
+    Ok(())
+}
+
+/// Copy a collection with its items, returning the token to replicate the
+/// changes during and after the copy from
+async fn copy_collection(
+    client: &FeaturesClient,
+    db: &Db,
+    source: &str,
+    id: &str,
+) -> anyhow::Result<String> {
+    if db.read_collection(id).await?.is_some() {
+        anyhow::bail!("Collection `{id}` exists, delete it to replicate it from {source}");
+    }
+
+    // taken first, changes during the copy are applied again afterwards
+    let token = client.changes(id, None, None).await?.token;
+
+    let mut collection = client.collection(id).await?;
+    collection.links = Default::default();
+    db.create_collection(&collection).await?;
+
+    let query = Query {
+        limit: Some(PAGE_SIZE),
+        crs: Crs::default(),
+        ..Default::default()
+    };
+    let copy = async {
+        let mut items = client.items(id, &query)?;
+        let mut batch = Vec::with_capacity(PAGE_SIZE);
+        let mut copied = 0;
+        while let Some(mut feature) = items.try_next().await? {
+            prepare(&mut feature, id);
+            batch.push(feature);
+
+            if batch.len() >= PAGE_SIZE {
+                db.create_features(id, &batch, &Crs::default()).await?;
+                copied += batch.len();
+                batch.clear();
+            }
+        }
+        if !batch.is_empty() {
+            db.create_features(id, &batch, &Crs::default()).await?;
+            copied += batch.len();
+        }
+        anyhow::Ok(copied)
+    }
+    .await;
+
+    match copy {
+        Ok(copied) => {
+            println!("Copied `{id}` with {copied} features from {source}");
+            Ok(token)
+        }
+        Err(e) => {
+            // copied again on the next run
+            db.delete_collection(id).await?;
+            Err(e)
+        }
+    }
+}
+
+/// Feature of the other instance to be stored, without its links
+fn prepare(feature: &mut Feature, collection: &str) {
+    feature.collection = Some(collection.to_owned());
+    feature.links = Default::default();
+}